@@ -0,0 +1,50 @@
+use std::path::Path;
+
+fn main() {
+    emit_engine_versions();
+}
+
+/// Expose the locked sparrow and jagua-rs versions as `SPARROW_VERSION`
+/// and `JAGUA_RS_VERSION`, so every nesting output records which engine
+/// produced it
+fn emit_engine_versions() {
+    let lock_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock_path.display());
+
+    let lock = std::fs::read_to_string(&lock_path).unwrap_or_default();
+    for (package, var) in [
+        ("sparrow", "SPARROW_VERSION"),
+        ("jagua-rs", "JAGUA_RS_VERSION"),
+    ] {
+        let version = locked_version(&lock, package).unwrap_or_else(|| "unknown".to_string());
+        println!("cargo:rustc-env={}={}", var, version);
+    }
+}
+
+/// Version of a package in Cargo.lock, with the short commit for git sources
+/// (both crates are pinned to a git rev and rarely bump their version)
+fn locked_version(lock: &str, package: &str) -> Option<String> {
+    let name_line = format!("name = \"{}\"", package);
+    let lines = lock
+        .lines()
+        .skip_while(|line| line.trim() != name_line)
+        .skip(1);
+
+    let mut version = None;
+    let mut commit = None;
+    for line in lines.take_while(|line| !line.trim().is_empty()) {
+        if let Some(value) = line.strip_prefix("version = ") {
+            version = Some(value.trim_matches('"').to_string());
+        } else if let Some(value) = line.strip_prefix("source = ") {
+            commit = value
+                .trim_matches('"')
+                .rsplit_once('#')
+                .map(|(_, commit)| commit.chars().take(7).collect::<String>());
+        }
+    }
+
+    match (version, commit) {
+        (Some(version), Some(commit)) => Some(format!("{}+{}", version, commit)),
+        (version, _) => version,
+    }
+}
@@ -4,10 +4,13 @@ use clap::Parser;
 use jagua_rs::io::svg::s_layout_to_svg;
 use log::{info, warn, LevelFilter};
 use sparrow::consts::DRAW_OPTIONS;
-use sparrow::util::listener::DummySolListener;
+use sparroWASM::core::logging::{self, LogConfig};
 use sparroWASM::core::nesting::{run_nesting, NestingConfig};
+use sparroWASM::core::phases::PhaseTracker;
+use sparroWASM::core::resources::{self, PeakRssSampler, ResourceUsage};
 use sparroWASM::core::serializer::NestingOutput;
-use sparroWASM::native::logger;
+use sparroWASM::core::workers;
+use sparroWASM::core::{APP_VERSION, ENGINE_VERSION};
 use sparroWASM::native::terminator::NativeTerminator;
 use std::fs;
 use std::path::PathBuf;
@@ -47,6 +50,10 @@ struct Args {
     /// Enable early termination
     #[arg(short = 'e', long)]
     early_termination: bool,
+
+    /// Print memory and CPU usage of the run
+    #[arg(long)]
+    show_resources: bool,
 }
 
 fn main() -> Result<()> {
@@ -60,8 +67,11 @@ fn main() -> Result<()> {
         LevelFilter::Info
     };
 
-    logger::init_logger(log_level)
-        .map_err(|e| anyhow::anyhow!("Failed to initialize logger: {}", e))?;
+    logging::set_log_config(&LogConfig {
+        level: log_level,
+        ..Default::default()
+    })
+    .map_err(|e| anyhow::anyhow!("Failed to initialize logger: {}", e))?;
 
     println!("=== Sparrow Nesting CLI ===");
     println!("Reading input from: {}", args.input.display());
@@ -76,18 +86,25 @@ fn main() -> Result<()> {
     let _validation: serde_json::Value = serde_json::from_str(&input_content)
         .context("Input file is not valid JSON")?;
 
+    // Fall back to fewer workers where thread creation is restricted
+    let n_workers = workers::available_workers(args.workers, workers::spawn_rayon_pool);
+
     // Create nesting configuration
     let config = NestingConfig {
         time_limit: Some(args.timeout),
         seed: args.seed,
         use_early_termination: args.early_termination,
-        n_workers: args.workers,
+        n_workers,
     };
 
     // Display configuration
     println!("Configuration:");
     println!("  - Timeout: {}s", args.timeout);
-    println!("  - Workers: {}", args.workers);
+    if n_workers < args.workers {
+        println!("  - Workers: {} ({} requested)", n_workers, args.workers);
+    } else {
+        println!("  - Workers: {}", n_workers);
+    }
     println!("  - Early termination: {}", args.early_termination);
     if let Some(seed) = args.seed {
         println!("  - Seed: {}", seed);
@@ -98,24 +115,44 @@ fn main() -> Result<()> {
     println!("Starting nesting optimization...");
     info!("Phase: Exploration + Compression");
 
+    // The sampler thread stops when dropped, also if the run fails
+    let rss_before = resources::current_rss_bytes();
+    let rss_sampler = args.show_resources.then(PeakRssSampler::start);
+    let cpu_before = resources::process_cpu_secs();
+
     let mut terminator = NativeTerminator::new();
+    let mut phase_tracker = PhaseTracker::new();
     let result = run_nesting(
         &input_content,
         &config,
-        &mut DummySolListener,
+        &mut phase_tracker,
         &mut terminator,
     )?;
 
+    // Import is not timed separately here, CPU time covers the whole run
+    let resource_usage = rss_sampler.map(|sampler| ResourceUsage {
+        rss_before_import_bytes: rss_before,
+        rss_after_import_bytes: None,
+        optimize_cpu_secs: cpu_before
+            .zip(resources::process_cpu_secs())
+            .map(|(before, after)| after - before),
+        peak_rss_bytes: sampler.finish(),
+    });
+
     println!("Optimization completed!");
     println!();
 
     // Create output
-    let output = NestingOutput::from_solution(
+    let mut output = NestingOutput::from_solution(
         &result.solution,
         &result.instance,
         result.ext_instance.name.clone(),
         result.computation_time,
     );
+    output.engine_version = Some(ENGINE_VERSION.to_string());
+    output.app_version = Some(APP_VERSION.to_string());
+    output.algorithm_fingerprint = Some(result.algorithm_fingerprint.clone());
+    output.phase_stats = Some(phase_tracker.summarize(&result.solution));
 
     // Display summary
     println!("=== Results ===");
@@ -124,6 +161,15 @@ fn main() -> Result<()> {
     println!("Items placed: {} / {}", output.total_items_placed, output.items_requested.unwrap_or(0));
     println!("Utilization: {:.1}%", output.utilization * 100.0);
     println!("Computation time: {:.2}s", output.computation_time_secs);
+    println!("Engine: {} (algorithm {})", ENGINE_VERSION, result.algorithm_fingerprint);
+    if let Some(phases) = &output.phase_stats {
+        if let Some(phase) = phases.final_solution_phase {
+            println!("Final solution: {:?} phase", phase);
+        }
+        if let Some(gain) = phases.compression_gain_pct {
+            println!("Compression improved width by {:.1}%", gain);
+        }
+    }
 
     if let Some(status) = &output.status {
         println!("Status: {}", status);
@@ -137,6 +183,14 @@ fn main() -> Result<()> {
     }
     println!();
 
+    if let Some(resource_usage) = &resource_usage {
+        println!("=== Resources ===");
+        for line in resource_usage.to_lines() {
+            println!("{}", line);
+        }
+        println!();
+    }
+
     // Write JSON output
     println!("Writing output to: {}", args.output.display());
     let output_json = serde_json::to_string_pretty(&output)
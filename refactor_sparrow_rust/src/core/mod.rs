@@ -1,3 +1,18 @@
 // Core module - Platform-agnostic nesting logic
+pub mod logging;
 pub mod nesting;
+pub mod phases;
+pub mod resources;
 pub mod serializer;
+pub mod workers;
+
+/// Versions of the nesting crates, captured from Cargo.lock by build.rs
+pub const ENGINE_VERSION: &str = concat!(
+    "sparrow ",
+    env!("SPARROW_VERSION"),
+    ", jagua-rs ",
+    env!("JAGUA_RS_VERSION")
+);
+
+/// Version of this crate
+pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
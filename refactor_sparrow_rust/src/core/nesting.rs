@@ -3,7 +3,7 @@ use anyhow::{Context, Result};
 use jagua_rs::io::import::Importer;
 use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
 use jagua_rs::probs::spp::io::ext_repr::ExtSPInstance;
-use log::{info, warn};
+use log::{error, info, warn};
 use rand::SeedableRng;
 use rand_xoshiro::Xoshiro256PlusPlus;
 use sparrow::config::*;
@@ -14,6 +14,7 @@ use sparrow::consts::{
 use sparrow::optimizer::optimize;
 use sparrow::util::listener::SolutionListener;
 use sparrow::util::terminator::Terminator;
+use sha2::{Digest, Sha256};
 use std::time::Duration;
 
 /// Configuration for nesting optimization
@@ -41,6 +42,42 @@ pub struct NestingResult {
     pub instance: SPInstance,
     pub ext_instance: ExtSPInstance,
     pub computation_time: Duration,
+    pub algorithm_fingerprint: String,
+}
+
+/// Sparrow configuration used for a run
+pub fn resolve_sparrow_config(config: &NestingConfig) -> SparrowConfig {
+    let mut sparrow_config = DEFAULT_SPARROW_CONFIG;
+
+    let time_limit = Duration::from_secs(config.time_limit.unwrap_or(600));
+    sparrow_config.expl_cfg.time_limit = time_limit.mul_f32(DEFAULT_EXPLORE_TIME_RATIO);
+    sparrow_config.cmpr_cfg.time_limit = time_limit.mul_f32(DEFAULT_COMPRESS_TIME_RATIO);
+
+    sparrow_config.expl_cfg.separator_config.n_workers = config.n_workers;
+    sparrow_config.cmpr_cfg.separator_config.n_workers = config.n_workers;
+
+    if config.use_early_termination {
+        sparrow_config.expl_cfg.max_conseq_failed_attempts =
+            Some(DEFAULT_MAX_CONSEQ_FAILS_EXPL);
+        sparrow_config.cmpr_cfg.shrink_decay =
+            ShrinkDecayStrategy::FailureBased(DEFAULT_FAIL_DECAY_RATIO_CMPR);
+    }
+
+    sparrow_config
+}
+
+/// Short hash of the resolved sparrow config, without time budget and
+/// worker count; outputs with different fingerprints are not comparable
+pub fn algorithm_fingerprint(config: &NestingConfig) -> String {
+    let sparrow_config = resolve_sparrow_config(&NestingConfig {
+        time_limit: Some(0),
+        seed: None,
+        use_early_termination: config.use_early_termination,
+        n_workers: 0,
+    });
+
+    let digest = Sha256::digest(format!("{:?}", sparrow_config).as_bytes());
+    format!("{:x}", digest)[..12].to_string()
 }
 
 /// Core nesting function - platform-agnostic
@@ -57,42 +94,28 @@ pub fn run_nesting<L: SolutionListener, T: Terminator>(
 
     // Parse input JSON
     let ext_sp_instance: ExtSPInstance = serde_json::from_str(json_str)
+        .map_err(|e| {
+            error!(
+                "[MAIN] JSON parsing error: {} (first 500 bytes: {})",
+                e,
+                truncate_at_char_boundary(json_str, 500)
+            );
+            e
+        })
         .context("not a valid strip packing instance (ExtSPInstance)")?;
 
     // Configure optimization parameters
-    let mut sparrow_config = DEFAULT_SPARROW_CONFIG;
-
-    let (explore_dur, compress_dur) = match config.time_limit {
-        Some(time_limit) => (
-            Duration::from_secs(time_limit).mul_f32(DEFAULT_EXPLORE_TIME_RATIO),
-            Duration::from_secs(time_limit).mul_f32(DEFAULT_COMPRESS_TIME_RATIO),
-        ),
-        None => {
-            warn!("[MAIN] no time limit specified, using default 600s");
-            (
-                Duration::from_secs(600).mul_f32(DEFAULT_EXPLORE_TIME_RATIO),
-                Duration::from_secs(600).mul_f32(DEFAULT_COMPRESS_TIME_RATIO),
-            )
-        }
-    };
+    if config.time_limit.is_none() {
+        warn!("[MAIN] no time limit specified, using default 600s");
+    }
+    let sparrow_config = resolve_sparrow_config(config);
 
     info!(
         "[MAIN] Configured to explore for {}s and compress for {}s",
-        explore_dur.as_secs(),
-        compress_dur.as_secs()
+        sparrow_config.expl_cfg.time_limit.as_secs(),
+        sparrow_config.cmpr_cfg.time_limit.as_secs()
     );
-
-    sparrow_config.expl_cfg.time_limit = explore_dur;
-    sparrow_config.cmpr_cfg.time_limit = compress_dur;
-
-    sparrow_config.expl_cfg.separator_config.n_workers = config.n_workers;
-    sparrow_config.cmpr_cfg.separator_config.n_workers = config.n_workers;
-
     if config.use_early_termination {
-        sparrow_config.expl_cfg.max_conseq_failed_attempts =
-            Some(DEFAULT_MAX_CONSEQ_FAILS_EXPL);
-        sparrow_config.cmpr_cfg.shrink_decay =
-            ShrinkDecayStrategy::FailureBased(DEFAULT_FAIL_DECAY_RATIO_CMPR);
         warn!("[MAIN] early termination enabled!");
     }
 
@@ -147,5 +170,34 @@ pub fn run_nesting<L: SolutionListener, T: Terminator>(
         instance,
         ext_instance: ext_sp_instance,
         computation_time,
+        algorithm_fingerprint: algorithm_fingerprint(config),
     })
 }
+
+/// The longest prefix of `text` of at most `max_bytes` bytes that does
+/// not split a character (part names are often Vietnamese)
+fn truncate_at_char_boundary(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let end = (0..=max_bytes)
+        .rev()
+        .find(|&index| text.is_char_boundary(index))
+        .unwrap_or(0);
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_at_char_boundary() {
+        // "ấ" takes bytes 1..4 and "ệ" bytes 2..5
+        assert_eq!(truncate_at_char_boundary("Tấm", 2), "T");
+        assert_eq!(truncate_at_char_boundary("Tấm", 4), "Tấ");
+        assert_eq!(truncate_at_char_boundary("đệm", 4), "đ");
+        assert_eq!(truncate_at_char_boundary("đệm", 1), "");
+        assert_eq!(truncate_at_char_boundary("đệm", 6), "đệm");
+    }
+}
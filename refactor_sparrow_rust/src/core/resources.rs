@@ -0,0 +1,172 @@
+// Process resource usage (memory, CPU time) of a nesting run
+//
+// Native only: values come from `/proc` on Linux and the process APIs
+// on Windows; on other platforms (including WASM) they are `None`.
+
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Interval between RSS samples while the optimizer runs
+pub const SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Memory and CPU usage of a nesting run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// Resident memory before importing the instance (bytes)
+    pub rss_before_import_bytes: Option<u64>,
+    /// Resident memory after importing the instance (bytes)
+    pub rss_after_import_bytes: Option<u64>,
+    /// Highest resident memory sampled during optimization (bytes)
+    pub peak_rss_bytes: Option<u64>,
+    /// CPU time (user + system) of all threads during optimization (seconds)
+    pub optimize_cpu_secs: Option<f64>,
+}
+
+impl ResourceUsage {
+    /// Render as human readable lines
+    pub fn to_lines(&self) -> Vec<String> {
+        let mb = |bytes: Option<u64>| match bytes {
+            Some(bytes) => format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0)),
+            None => "n/a".to_string(),
+        };
+        vec![
+            format!("RSS before import: {}", mb(self.rss_before_import_bytes)),
+            format!("RSS after import:  {}", mb(self.rss_after_import_bytes)),
+            format!("Peak RSS:          {}", mb(self.peak_rss_bytes)),
+            match self.optimize_cpu_secs {
+                Some(secs) => format!("Optimize CPU time: {:.2}s", secs),
+                None => "Optimize CPU time: n/a".to_string(),
+            },
+        ]
+    }
+}
+
+/// Background thread sampling the peak RSS of the process
+///
+/// The thread stops when `finish` is called or the sampler is dropped,
+/// so an aborted run (error, cancellation, panic) never leaks it.
+pub struct PeakRssSampler {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<Option<u64>>>,
+}
+
+impl PeakRssSampler {
+    /// Start sampling every `SAMPLE_INTERVAL`
+    pub fn start() -> Self {
+        let (stop, stop_rx) = mpsc::channel::<()>();
+
+        let handle = std::thread::Builder::new()
+            .name("rss-sampler".to_string())
+            .spawn(move || {
+                let mut peak = current_rss_bytes();
+                // Runs until a stop is requested or the sampler is dropped
+                while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(SAMPLE_INTERVAL) {
+                    peak = peak.max(current_rss_bytes());
+                }
+                peak.max(current_rss_bytes())
+            })
+            .ok();
+
+        Self {
+            stop: Some(stop),
+            handle,
+        }
+    }
+
+    /// Stop the sampler and return the peak RSS seen (bytes)
+    pub fn finish(mut self) -> Option<u64> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> Option<u64> {
+        // Dropping the sender wakes the thread immediately
+        drop(self.stop.take());
+        self.handle.take()?.join().ok().flatten()
+    }
+}
+
+impl Drop for PeakRssSampler {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Current resident set size of this process (bytes)
+#[cfg(target_os = "linux")]
+pub fn current_rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Total CPU time (user + system) of all threads of this process (seconds)
+#[cfg(target_os = "linux")]
+pub fn process_cpu_secs() -> Option<f64> {
+    // /proc reports times in USER_HZ, which is 100 on all Linux ABIs
+    const USER_HZ: f64 = 100.0;
+
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // The command name may contain spaces, fields are counted after ')'
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some((utime + stime) as f64 / USER_HZ)
+}
+
+#[cfg(windows)]
+pub fn current_rss_bytes() -> Option<u64> {
+    use windows_sys::Win32::System::ProcessStatus::{
+        GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS,
+    };
+    use windows_sys::Win32::System::Threading::GetCurrentProcess;
+
+    let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+    // SAFETY: the pseudo handle of the current process is always valid and
+    // `counters` is a properly sized, writable struct
+    let ok = unsafe { GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) };
+    (ok != 0).then_some(counters.WorkingSetSize as u64)
+}
+
+#[cfg(windows)]
+pub fn process_cpu_secs() -> Option<f64> {
+    use windows_sys::Win32::Foundation::FILETIME;
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, GetProcessTimes};
+
+    let empty = || FILETIME {
+        dwLowDateTime: 0,
+        dwHighDateTime: 0,
+    };
+    let (mut creation, mut exit, mut kernel, mut user) = (empty(), empty(), empty(), empty());
+    // SAFETY: the pseudo handle of the current process is always valid and
+    // all out-parameters point to writable FILETIMEs
+    let ok = unsafe {
+        GetProcessTimes(
+            GetCurrentProcess(),
+            &mut creation,
+            &mut exit,
+            &mut kernel,
+            &mut user,
+        )
+    };
+    if ok == 0 {
+        return None;
+    }
+
+    // FILETIME counts 100 ns intervals
+    let ticks = |t: FILETIME| ((t.dwHighDateTime as u64) << 32) | t.dwLowDateTime as u64;
+    Some((ticks(kernel) + ticks(user)) as f64 / 1e7)
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn current_rss_bytes() -> Option<u64> {
+    None
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn process_cpu_secs() -> Option<f64> {
+    None
+}
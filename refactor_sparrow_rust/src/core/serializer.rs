@@ -1,4 +1,5 @@
 // JSON output serialization for CLI
+use super::phases::PhaseStats;
 use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
@@ -18,6 +19,14 @@ pub struct NestingOutput {
     pub items_requested: Option<usize>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub unplaced_item_ids: Vec<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub engine_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub app_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub algorithm_fingerprint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub phase_stats: Option<PhaseStats>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -122,6 +131,10 @@ impl NestingOutput {
             status,
             items_requested: Some(total_requested),
             unplaced_item_ids,
+            engine_version: None,
+            app_version: None,
+            algorithm_fingerprint: None,
+            phase_stats: None,
         }
     }
 }
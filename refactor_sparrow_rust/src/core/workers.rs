@@ -0,0 +1,50 @@
+// Worker count fallback for restricted machines
+//
+// sparrow sets up its rayon worker pool internally and panics when the
+// threads cannot be created. Native only: the WASM build gets its
+// threads from `init_thread_pool`.
+
+use log::warn;
+
+/// Largest worker count, at most `requested`, for which `spawn_pool`
+/// succeeds
+///
+/// Halves the count after every failure and ends at 1 worker, which
+/// is returned even if that check fails too (the optimizer then runs
+/// single threaded and reports its own error).
+pub fn available_workers<F>(requested: usize, spawn_pool: F) -> usize
+where
+    F: Fn(usize) -> Result<(), String>,
+{
+    let mut workers = requested.max(1);
+    loop {
+        match spawn_pool(workers) {
+            Ok(()) => return workers,
+            Err(e) if workers > 1 => {
+                let fewer = workers / 2;
+                warn!(
+                    "Could not start {} nesting workers ({}), retrying with {}",
+                    workers, e, fewer
+                );
+                println!(
+                    "⚠️ Could not start {} nesting workers ({}), retrying with {}",
+                    workers, e, fewer
+                );
+                workers = fewer;
+            }
+            Err(e) => {
+                warn!("Could not start a single nesting worker: {}", e);
+                return 1;
+            }
+        }
+    }
+}
+
+/// Start (and drop) a rayon pool with `workers` threads
+pub fn spawn_rayon_pool(workers: usize) -> Result<(), String> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(workers)
+        .build()
+        .map(drop)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,26 @@
+-- Migration: Add Nesting Snapshots
+-- Purpose: Store everything needed to regenerate a quote's layout exactly
+-- Created: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS nesting_snapshots (
+  quote_id TEXT PRIMARY KEY NOT NULL,
+
+  -- Replay inputs
+  seed TEXT NOT NULL, -- u64 stored as text (exceeds JS safe integer range)
+  config_json TEXT NOT NULL, -- Fully resolved NestingConfig
+  instance_json TEXT NOT NULL, -- Instance geometry as sent to the engine
+
+  -- Verification
+  instance_hash TEXT NOT NULL,
+  fingerprint_json TEXT NOT NULL, -- Per-item geometry hashes and quantities
+  engine_version TEXT NOT NULL,
+
+  -- Timestamps
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+  updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+  FOREIGN KEY (quote_id) REFERENCES quotes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_nesting_snapshots_instance_hash
+  ON nesting_snapshots(instance_hash);
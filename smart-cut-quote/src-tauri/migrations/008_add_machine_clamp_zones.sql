@@ -0,0 +1,25 @@
+-- Migration: Add Machine Clamp Zones
+-- Purpose: Clamp/fixture rectangles per machine, used as keep-out zones when nesting
+-- Created: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS machine_clamp_zones (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  machine_id TEXT NOT NULL,
+
+  -- Rectangle in sheet coordinates (mm)
+  x REAL NOT NULL,
+  y REAL NOT NULL,
+  width REAL NOT NULL CHECK (width > 0),
+  height REAL NOT NULL CHECK (height > 0),
+  label TEXT,
+
+  -- Order shown in settings
+  sort_order INTEGER NOT NULL DEFAULT 0,
+
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+  FOREIGN KEY (machine_id) REFERENCES machines(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_machine_clamp_zones_machine
+  ON machine_clamp_zones(machine_id);
@@ -0,0 +1,19 @@
+-- Migration: Add Material Densities
+-- Purpose: Density per material group for purchasing weight estimates
+-- Created: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS material_densities (
+  material_name TEXT PRIMARY KEY NOT NULL, -- Matches material_stock.name
+  density REAL NOT NULL, -- kg/m³
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+  updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+INSERT OR IGNORE INTO material_densities (material_name, density) VALUES
+  ('Stainless Steel', 8000),
+  ('Mild Steel', 7850),
+  ('Aluminum', 2700);
+
+-- Extra sheets ordered on top of the computed requirement (percent)
+INSERT OR IGNORE INTO settings (key, value) VALUES
+  ('material_scrap_allowance_percent', '5');
@@ -0,0 +1,6 @@
+-- Migration: Add Auto Re-nest Setting
+-- Purpose: Re-nest affected material groups when a watched source DXF changes
+-- Created: 2026-10-17
+
+INSERT OR IGNORE INTO settings (key, value) VALUES
+  ('auto_renest_on_source_change', 'false');
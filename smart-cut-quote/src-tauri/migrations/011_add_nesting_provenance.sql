@@ -0,0 +1,8 @@
+-- Migration: Add Nesting Provenance
+-- Purpose: Record app version and optimizer settings hash with nesting snapshots
+-- Created: 2026-10-17
+
+-- engine_version now holds the sparrow/jagua-rs versions; rows written
+-- before this migration contain the app version there instead
+ALTER TABLE nesting_snapshots ADD COLUMN app_version TEXT;
+ALTER TABLE nesting_snapshots ADD COLUMN algorithm_fingerprint TEXT;
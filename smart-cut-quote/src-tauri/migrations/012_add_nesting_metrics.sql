@@ -0,0 +1,37 @@
+-- Migration: Add Nesting Metrics
+-- Purpose: Append-only log of nesting runs for monthly analytics (no geometry)
+-- Created: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS nesting_metrics (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+  -- Input size
+  item_count INTEGER NOT NULL, -- Distinct parts
+  demand_total INTEGER NOT NULL, -- Sum of part quantities
+
+  -- Outcome
+  status TEXT NOT NULL CHECK (status IN ('complete', 'partial', 'failed')),
+  error_code TEXT, -- Only for failed runs
+  utilization REAL, -- 0.0 - 1.0, NULL for failed runs
+  sheets INTEGER NOT NULL, -- Strips produced (0 for failed runs)
+  duration_secs REAL NOT NULL,
+
+  -- Context
+  preset TEXT, -- Settings preset, NULL when none was named
+  engine_version TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_nesting_metrics_created_at
+  ON nesting_metrics(created_at);
+
+-- Rows are never changed once written
+CREATE TRIGGER IF NOT EXISTS nesting_metrics_append_only
+  BEFORE UPDATE ON nesting_metrics
+BEGIN
+  SELECT RAISE(ABORT, 'nesting_metrics is append-only');
+END;
+
+-- Opt-out switch for recording runs
+INSERT OR IGNORE INTO settings (key, value) VALUES
+  ('nesting_metrics_enabled', 'true');
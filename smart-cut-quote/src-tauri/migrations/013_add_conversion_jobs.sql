@@ -0,0 +1,34 @@
+-- Migration: Add Conversion Jobs
+-- Purpose: Resumable directory conversion jobs and a cache of converted files
+-- Created: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS conversion_jobs (
+  id TEXT PRIMARY KEY NOT NULL,
+  directory TEXT NOT NULL,
+  output_dir TEXT NOT NULL,
+  options TEXT NOT NULL, -- ConversionOptions JSON
+  max_files_per_minute INTEGER, -- NULL = unlimited
+  status TEXT NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'cancelled', 'completed')),
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+  updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- One row per DXF file of a job, the progress of the job
+CREATE TABLE IF NOT EXISTS conversion_job_files (
+  job_id TEXT NOT NULL,
+  path TEXT NOT NULL,
+  status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'done', 'failed')),
+  output_path TEXT, -- Converted JSON, set when done
+  error TEXT, -- Set when failed
+  updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+  PRIMARY KEY (job_id, path),
+  FOREIGN KEY (job_id) REFERENCES conversion_jobs(id) ON DELETE CASCADE
+);
+
+-- Converted output per file content and conversion options
+CREATE TABLE IF NOT EXISTS conversion_cache (
+  cache_key TEXT PRIMARY KEY NOT NULL, -- sha256 of file content + options JSON
+  source_path TEXT NOT NULL,
+  output_path TEXT NOT NULL,
+  created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,6 @@
+-- Migration: Add Material Min Web
+-- Purpose: Minimum web width between holes and outer contour per material
+-- Created: 2026-10-17
+
+-- NULL = no check; parts with narrower webs are flagged when quoting
+ALTER TABLE material_stock ADD COLUMN min_web REAL; -- mm
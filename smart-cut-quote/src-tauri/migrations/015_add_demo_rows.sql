@@ -0,0 +1,10 @@
+-- Migration: Add Demo Rows
+-- Purpose: Track rows inserted by load_demo_data so clear_demo_data removes exactly those
+-- Created: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS demo_rows (
+  table_name TEXT NOT NULL, -- clients, material_stock, quotes, nesting_snapshots
+  row_id TEXT NOT NULL, -- Primary key of the row (quote_id for nesting_snapshots)
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+  PRIMARY KEY (table_name, row_id)
+);
@@ -0,0 +1,14 @@
+-- Migration: Add Quote Drafts
+-- Purpose: Autosaved state of the quote being edited, recovered after a crash
+-- Created: 2026-10-17
+
+-- One row per app session; the draft is overwritten on every autosave
+CREATE TABLE IF NOT EXISTS quote_drafts (
+  id TEXT PRIMARY KEY NOT NULL, -- Session ID
+  draft_json TEXT NOT NULL, -- Quote draft JSON written by the frontend
+  size_bytes INTEGER NOT NULL,
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+  updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_quote_drafts_updated_at ON quote_drafts(updated_at);
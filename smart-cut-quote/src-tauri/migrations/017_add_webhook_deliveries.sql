@@ -0,0 +1,28 @@
+-- Migration: Add Webhook Deliveries
+-- Purpose: Audit log of nesting notifications POSTed to an external system (MES)
+-- Created: 2026-10-17
+
+-- One row per notification; attempts and the last response are updated while retrying
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  event TEXT NOT NULL, -- 'nesting.completed', 'nesting.failed' or 'webhook.test'
+  job_id TEXT, -- Nesting job, NULL for runs without one
+  quote_id TEXT,
+  link_token TEXT NOT NULL UNIQUE, -- Sent in the payload, identifies the delivery
+  url TEXT NOT NULL,
+  payload TEXT NOT NULL, -- JSON body as sent
+  status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'delivered', 'failed')),
+  attempts INTEGER NOT NULL DEFAULT 0,
+  http_status INTEGER, -- Of the last attempt, NULL when no response arrived
+  error TEXT, -- Of the last attempt
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+  updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_created_at
+  ON webhook_deliveries(created_at);
+
+-- Notifications are off while the URL is empty
+INSERT OR IGNORE INTO settings (key, value) VALUES
+  ('webhook_url', ''),
+  ('webhook_secret', '');
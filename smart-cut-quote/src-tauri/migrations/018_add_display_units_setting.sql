@@ -0,0 +1,6 @@
+-- Migration: Add Display Units Setting
+-- Purpose: Units the UI shows lengths and areas in ('mm' or 'in'); storage and nesting stay in mm
+-- Created: 2026-10-17
+
+INSERT OR IGNORE INTO settings (key, value) VALUES
+  ('display_units', 'mm');
@@ -0,0 +1,6 @@
+-- Migration: Add Material Price Review
+-- Purpose: Flag stock rows whose prices are placeholders (seeded standard materials)
+-- Created: 2026-10-17
+
+-- 1 = price_per_kg and cut_price_per_meter still need to be checked by the user
+ALTER TABLE material_stock ADD COLUMN needs_price_review INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,6 @@
+-- Migration: Add Max Strip Height Setting
+-- Purpose: Strip height (mm) above which a nesting run without stock data is flagged as a likely typo; raise it for coil stock
+-- Created: 2026-10-17
+
+INSERT OR IGNORE INTO settings (key, value) VALUES
+  ('max_strip_height', '4000');
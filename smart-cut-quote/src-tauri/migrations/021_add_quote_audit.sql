@@ -0,0 +1,16 @@
+-- Migration: Add Quote Audit
+-- Purpose: One row per quote save (save_quote), written in the same transaction as the quote
+-- Created: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS quote_audit (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  quote_id TEXT NOT NULL,
+  action TEXT NOT NULL, -- 'created', 'updated'
+  actor TEXT NOT NULL DEFAULT 'ADMIN',
+  summary TEXT, -- JSON: line count, total, nesting linked, task ID
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+  FOREIGN KEY (quote_id) REFERENCES quotes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_quote_audit_quote_id ON quote_audit(quote_id);
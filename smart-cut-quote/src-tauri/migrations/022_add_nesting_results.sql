@@ -0,0 +1,26 @@
+-- Migration: Add Nesting Results
+-- Purpose: Results imported from the legacy sparrow-cli output files (import_legacy_results)
+-- Created: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS nesting_results (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  quote_id TEXT, -- NULL: no quote matched the instance name
+  instance_name TEXT,
+
+  -- Summary of the solution (mm)
+  strip_width REAL NOT NULL,
+  strip_height REAL,
+  density REAL,
+  placed_items INTEGER NOT NULL DEFAULT 0,
+  run_time_sec REAL,
+
+  result_json TEXT NOT NULL, -- File content as read
+  svg_path TEXT, -- Relative to the app data dir
+  source TEXT NOT NULL DEFAULT 'legacy_import',
+  source_path TEXT UNIQUE, -- Original file, re-imports are skipped
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+  FOREIGN KEY (quote_id) REFERENCES quotes(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_nesting_results_quote_id ON nesting_results(quote_id);
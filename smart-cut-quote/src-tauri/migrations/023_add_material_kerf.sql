@@ -0,0 +1,6 @@
+-- Migration: Add Material Kerf
+-- Purpose: Kerf width per material, compared with the nesting separation
+-- Created: 2026-10-17
+
+-- NULL = unknown; runs then make no separation suggestion
+ALTER TABLE material_stock ADD COLUMN kerf REAL; -- mm
@@ -0,0 +1,21 @@
+-- Migration: Add Pipeline Undo Log
+-- Purpose: Undo actions of running quote pipelines (run_quote_pipeline), replayed on cancel, failure or next startup
+-- Created: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS pipeline_runs (
+  id TEXT PRIMARY KEY,
+  status TEXT NOT NULL DEFAULT 'running', -- 'running' | 'rolling_back'
+  started_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS pipeline_undo_log (
+  seq INTEGER PRIMARY KEY AUTOINCREMENT, -- Undone in descending order
+  run_id TEXT NOT NULL,
+  stage TEXT NOT NULL,
+  action TEXT NOT NULL, -- JSON UndoAction
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+  FOREIGN KEY (run_id) REFERENCES pipeline_runs(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_pipeline_undo_log_run_id ON pipeline_undo_log(run_id);
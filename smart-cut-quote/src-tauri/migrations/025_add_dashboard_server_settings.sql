@@ -0,0 +1,10 @@
+-- Migration: Add Dashboard Server Settings
+-- Purpose: Read-only HTTP endpoint for shop dashboards (jobs, nesting metrics, quote summaries); read at startup
+-- Created: 2026-10-17
+
+-- Off by default; it does not start without a token either
+INSERT OR IGNORE INTO settings (key, value) VALUES
+  ('dashboard_server_enabled', 'false'),
+  ('dashboard_server_port', '8787'),
+  ('dashboard_server_bind', 'localhost'), -- 'localhost' or 'lan'
+  ('dashboard_server_token', '');
@@ -0,0 +1,16 @@
+-- Migration: Add Instance Blobs
+-- Purpose: Instance JSON of nesting results stored once per content (see commands::instance_blobs)
+-- Created: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS instance_blobs (
+  hash TEXT PRIMARY KEY, -- Lowercase hex SHA-256 of the instance JSON
+  body BLOB NOT NULL, -- gzip-compressed instance JSON
+  size INTEGER NOT NULL, -- Uncompressed length in bytes
+  refcount INTEGER NOT NULL DEFAULT 0, -- nesting_results rows referencing the blob
+  created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- NULL: stored before this migration, or imported without an instance
+ALTER TABLE nesting_results ADD COLUMN instance_hash TEXT REFERENCES instance_blobs(hash);
+
+CREATE INDEX IF NOT EXISTS idx_nesting_results_instance_hash ON nesting_results(instance_hash);
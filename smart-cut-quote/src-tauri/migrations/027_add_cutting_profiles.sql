@@ -0,0 +1,32 @@
+-- Migration: Add Cutting Profiles
+-- Purpose: Cutting technology per machine, material and thickness range, read by both nesting and pricing
+-- Created: 2026-10-17
+
+CREATE TABLE IF NOT EXISTS cutting_profiles (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  -- NULL = any machine
+  machine_id TEXT,
+  material_id TEXT NOT NULL,
+
+  -- Thickness range in mm, lower bound inclusive, upper bound exclusive;
+  -- equal bounds match that exact thickness only
+  thickness_min REAL NOT NULL CHECK (thickness_min > 0),
+  thickness_max REAL NOT NULL CHECK (thickness_max >= thickness_min),
+
+  -- NULL = use the material's value
+  kerf REAL,              -- mm
+  min_separation REAL,    -- mm
+  feed_rate REAL,         -- mm/min
+  pierce_time REAL,       -- s
+  price_per_meter REAL,
+  price_per_pierce REAL,
+
+  created_at TEXT NOT NULL DEFAULT (datetime('now')),
+  updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+  FOREIGN KEY (machine_id) REFERENCES machines(id) ON DELETE CASCADE,
+  FOREIGN KEY (material_id) REFERENCES material_stock(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_cutting_profiles_material
+  ON cutting_profiles(material_id, machine_id);
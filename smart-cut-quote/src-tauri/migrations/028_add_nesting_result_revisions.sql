@@ -0,0 +1,8 @@
+-- Migration: Add Nesting Result Revisions
+-- Purpose: Results improved from a stored result link back to it (see commands::result_improvement)
+-- Created: 2026-10-17
+
+-- NULL: not derived from another result
+ALTER TABLE nesting_results ADD COLUMN parent_result_id INTEGER REFERENCES nesting_results(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_nesting_results_parent_result_id ON nesting_results(parent_result_id);
@@ -0,0 +1,7 @@
+-- Migration: Add Export Name Template
+-- Purpose: File names of exports, resolved by commands::export_naming
+-- Created: 2026-10-17
+
+-- Placeholders: {quote_no}, {customer}, {date}, {kind}, {n} (next free version, required)
+INSERT OR IGNORE INTO settings (key, value) VALUES
+  ('export_name_template', '{quote_no}_{customer}_{date}_{kind}_v{n}');
@@ -0,0 +1,6 @@
+-- Migration: Add Consolidation Results
+-- Purpose: Consolidated runs are stored as nesting results with their item sources (see commands::consolidation)
+-- Created: 2026-10-17
+
+-- NULL: not a consolidated run; else the quote of every merged item and the material cost
+ALTER TABLE nesting_results ADD COLUMN consolidation_json TEXT;
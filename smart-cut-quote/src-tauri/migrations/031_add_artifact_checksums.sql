@@ -0,0 +1,7 @@
+-- Migration: Add Artifact Checksums
+-- Purpose: SHA-256 of the files rows point at, checked when they are read (see commands::artifacts)
+-- Created: 2026-10-17
+
+-- Lowercase hex SHA-256 of the file as written; NULL: written before this migration
+ALTER TABLE nesting_results ADD COLUMN svg_sha256 TEXT;
+ALTER TABLE conversion_cache ADD COLUMN output_sha256 TEXT;
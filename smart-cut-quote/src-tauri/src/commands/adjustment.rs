@@ -0,0 +1,111 @@
+use crate::nesting_engine::{AdjustmentSession, NestingOutput, ProbeResult};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+/// Adjustment sessions kept at once; the oldest is dropped when a
+/// preview is closed without `end_adjustment`
+const MAX_ADJUSTMENT_SESSIONS: usize = 8;
+
+/// Cached layouts of the previews being adjusted by hand, oldest first
+#[derive(Default)]
+pub struct AdjustmentSessions {
+    sessions: Mutex<VecDeque<(String, Arc<AdjustmentSession>)>>,
+}
+
+impl AdjustmentSessions {
+    fn insert(&self, token: String, session: Arc<AdjustmentSession>) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.push_back((token, session));
+            while sessions.len() > MAX_ADJUSTMENT_SESSIONS {
+                sessions.pop_front();
+            }
+        }
+    }
+
+    fn get(&self, token: &str) -> Option<Arc<AdjustmentSession>> {
+        let sessions = self.sessions.lock().ok()?;
+        sessions
+            .iter()
+            .find(|(id, _)| id == token)
+            .map(|(_, session)| session.clone())
+    }
+
+    fn remove(&self, token: &str) -> bool {
+        self.sessions
+            .lock()
+            .map(|mut sessions| {
+                let before = sessions.len();
+                sessions.retain(|(id, _)| id != token);
+                sessions.len() != before
+            })
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AdjustmentStarted {
+    /// Passed to `probe_placement` and `end_adjustment`
+    pub placements_token: String,
+    pub instance_hash: Option<String>,
+    pub placements: usize,
+}
+
+/// Cache a layout for live collision feedback while dragging its parts
+///
+/// The output must carry outlines (`include_outlines`). Lengths are in
+/// the output's units.
+#[tauri::command]
+pub async fn begin_adjustment(
+    sessions: State<'_, AdjustmentSessions>,
+    output: NestingOutput,
+) -> Result<AdjustmentStarted, String> {
+    let session = tauri::async_runtime::spawn_blocking(move || AdjustmentSession::new(&output))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??;
+
+    let started = AdjustmentStarted {
+        placements_token: uuid::Uuid::new_v4().to_string(),
+        instance_hash: session.instance_hash().map(str::to_string),
+        placements: session.len(),
+    };
+    sessions.insert(started.placements_token.clone(), Arc::new(session));
+    Ok(started)
+}
+
+/// Check placement `placement_index` moved to `(x, y)` and `rotation`
+///
+/// Runs on the calling task, a probe takes well under a millisecond.
+/// `instance_hash` guards against probing a layout nested from another
+/// instance than the one being edited.
+#[tauri::command]
+pub async fn probe_placement(
+    sessions: State<'_, AdjustmentSessions>,
+    instance_hash: Option<String>,
+    placements_token: String,
+    placement_index: usize,
+    x: f64,
+    y: f64,
+    rotation: f64,
+) -> Result<ProbeResult, String> {
+    let session = sessions
+        .get(&placements_token)
+        .ok_or_else(|| format!("Adjustment '{}' is not available", placements_token))?;
+    if instance_hash.is_some() && instance_hash.as_deref() != session.instance_hash() {
+        return Err(format!(
+            "Adjustment '{}' belongs to another instance",
+            placements_token
+        ));
+    }
+    session.probe(placement_index, x, y, rotation)
+}
+
+/// Drop the cached layout of an adjustment
+#[tauri::command]
+pub async fn end_adjustment(
+    sessions: State<'_, AdjustmentSessions>,
+    placements_token: String,
+) -> Result<bool, String> {
+    Ok(sessions.remove(&placements_token))
+}
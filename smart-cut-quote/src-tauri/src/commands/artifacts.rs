@@ -0,0 +1,781 @@
+//! Read-only access to the stored files rows point at
+//!
+//! Nesting results reference their SVG (`nesting_results.svg_path`,
+//! relative to the app data dir) and their instance (`instance_blobs`),
+//! the conversion cache its converted JSON (`conversion_cache.output_path`).
+//! Files are written with the SHA-256 of their content (`svg_sha256`,
+//! `output_sha256`); instance blobs are keyed by theirs. A file that was
+//! moved, cleaned up or overwritten since is reported as missing or
+//! corrupt, with what it can be regenerated from, instead of showing up
+//! as a blank preview. Files written before the checksums were recorded
+//! are only checked for existence.
+//!
+//! `get_artifact` answers with small content inline and hands out a
+//! transfer token for larger content, read with `read_artifact_chunk`.
+//! `audit_artifacts` checks every stored reference at once.
+
+use crate::commands::instance_blobs::{decompress, instance_blob_body, instance_hash};
+use crate::commands::response::{respond, CommandError, CommandResponse, ErrorCode};
+use crate::db;
+use crate::workspace;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+/// Largest content `get_artifact` returns inline
+pub const INLINE_ARTIFACT_BYTES: usize = 1024 * 1024;
+
+/// Largest chunk of a transfer; chunks end on a character boundary
+pub const ARTIFACT_CHUNK_BYTES: usize = 1024 * 1024;
+
+/// Transfers kept at once; the oldest is dropped when one is abandoned
+const MAX_ARTIFACT_TRANSFERS: usize = 4;
+
+/// Lowercase hex SHA-256 of an artifact, as stored with its reference
+pub fn artifact_sha256(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    /// Layout SVG of a nesting result
+    Svg,
+    /// Instance JSON of a nesting result
+    Instance,
+    /// Converted part JSON of the conversion cache (audit only)
+    ConvertedPart,
+}
+
+impl ArtifactKind {
+    fn label(&self) -> &'static str {
+        match self {
+            ArtifactKind::Svg => "SVG",
+            ArtifactKind::Instance => "Instance",
+            ArtifactKind::ConvertedPart => "Converted part",
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactState {
+    Missing,
+    /// Content no longer matches its checksum, or cannot be read back
+    Corrupt,
+}
+
+/// A stored reference whose file is gone or changed
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ArtifactProblem {
+    pub kind: ArtifactKind,
+    pub state: ArtifactState,
+    pub result_id: Option<i64>,
+    /// Path as stored, or the instance hash
+    pub reference: String,
+    pub expected_sha256: Option<String>,
+    /// Checksum of the content found instead
+    pub actual_sha256: Option<String>,
+    /// Quote whose snapshot `renest_exact` replays to regenerate the layout
+    pub replay_quote_id: Option<String>,
+    /// DXF file a converted part is converted from again
+    pub source_path: Option<String>,
+}
+
+impl ArtifactProblem {
+    fn error(&self) -> CommandError {
+        let owner = self
+            .result_id
+            .map(|id| format!(" of nesting result {}", id))
+            .unwrap_or_default();
+        match self.state {
+            ArtifactState::Missing => CommandError::new(
+                ErrorCode::NotFound,
+                format!(
+                    "{}{} is missing ({})",
+                    self.kind.label(),
+                    owner,
+                    self.reference
+                ),
+            ),
+            ArtifactState::Corrupt => CommandError::new(
+                ErrorCode::Corrupt,
+                format!(
+                    "{}{} does not match its checksum ({})",
+                    self.kind.label(),
+                    owner,
+                    self.reference
+                ),
+            ),
+        }
+    }
+}
+
+/// Payload of `get_artifact`
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "delivery", rename_all = "snake_case")]
+pub enum ArtifactFetch {
+    /// Content up to `INLINE_ARTIFACT_BYTES`
+    Inline {
+        content: String,
+        size_bytes: u64,
+        sha256: String,
+    },
+    /// Larger content, read chunk by chunk with `read_artifact_chunk`
+    Chunked {
+        token: String,
+        size_bytes: u64,
+        chunk_count: usize,
+        sha256: String,
+    },
+    /// Missing or corrupt, the response fails with `not_found` or `corrupt`
+    Unavailable(ArtifactProblem),
+}
+
+/// One chunk of a transfer, in order
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ArtifactChunk {
+    pub index: usize,
+    pub content: String,
+    /// The transfer is dropped after its last chunk
+    pub last: bool,
+}
+
+/// Outcome of `audit_artifacts`
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct ArtifactAudit {
+    pub checked: usize,
+    /// Stored before checksums were recorded, only checked for existence
+    pub unverified: usize,
+    pub problems: Vec<ArtifactProblem>,
+}
+
+struct ArtifactTransfer {
+    content: String,
+    chunks: Vec<Range<usize>>,
+}
+
+/// Verified content of the large artifacts being read, oldest first
+#[derive(Default)]
+pub struct ArtifactTransfers {
+    transfers: Mutex<VecDeque<(String, Arc<ArtifactTransfer>)>>,
+}
+
+impl ArtifactTransfers {
+    fn insert(&self, token: String, transfer: Arc<ArtifactTransfer>) {
+        if let Ok(mut transfers) = self.transfers.lock() {
+            transfers.push_back((token, transfer));
+            while transfers.len() > MAX_ARTIFACT_TRANSFERS {
+                transfers.pop_front();
+            }
+        }
+    }
+
+    fn get(&self, token: &str) -> Option<Arc<ArtifactTransfer>> {
+        let transfers = self.transfers.lock().ok()?;
+        transfers
+            .iter()
+            .find(|(id, _)| id == token)
+            .map(|(_, transfer)| transfer.clone())
+    }
+
+    fn remove(&self, token: &str) {
+        if let Ok(mut transfers) = self.transfers.lock() {
+            transfers.retain(|(id, _)| id != token);
+        }
+    }
+}
+
+/// What reading a stored file found
+#[derive(Debug, PartialEq)]
+pub(crate) enum FileCheck {
+    Intact(Vec<u8>),
+    Missing,
+    Corrupt { actual_sha256: String },
+}
+
+/// Why an artifact is unavailable, with the checksum found instead
+type Defect = (ArtifactState, Option<String>);
+
+impl FileCheck {
+    fn into_content(self) -> Result<Vec<u8>, Defect> {
+        match self {
+            FileCheck::Intact(content) => Ok(content),
+            FileCheck::Missing => Err((ArtifactState::Missing, None)),
+            FileCheck::Corrupt { actual_sha256 } => {
+                Err((ArtifactState::Corrupt, Some(actual_sha256)))
+            }
+        }
+    }
+}
+
+/// Read a stored file and compare it with its recorded checksum
+///
+/// Without a checksum any content is intact.
+pub(crate) fn check_file(path: &Path, expected_sha256: Option<&str>) -> Result<FileCheck, String> {
+    let content = match std::fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(FileCheck::Missing),
+        Err(e) => return Err(format!("Failed to read '{}': {}", path.display(), e)),
+    };
+    match expected_sha256 {
+        Some(expected) => {
+            let actual_sha256 = artifact_sha256(&content);
+            if actual_sha256 == expected {
+                Ok(FileCheck::Intact(content))
+            } else {
+                Ok(FileCheck::Corrupt { actual_sha256 })
+            }
+        }
+        None => Ok(FileCheck::Intact(content)),
+    }
+}
+
+/// Load an instance blob and check it against its hash
+async fn check_instance(pool: &SqlitePool, hash: &str) -> Result<Result<String, Defect>, String> {
+    let Some(body) = instance_blob_body(pool, hash).await? else {
+        return Ok(Err((ArtifactState::Missing, None)));
+    };
+    let Ok(instance_json) = decompress(&body) else {
+        return Ok(Err((ArtifactState::Corrupt, None)));
+    };
+    let actual = instance_hash(&instance_json);
+    if actual == hash {
+        Ok(Ok(instance_json))
+    } else {
+        Ok(Err((ArtifactState::Corrupt, Some(actual))))
+    }
+}
+
+/// Byte ranges of at most `max_bytes` covering `content`, split on
+/// character boundaries
+fn chunk_bounds(content: &str, max_bytes: usize) -> Vec<Range<usize>> {
+    let mut bounds = Vec::new();
+    let mut start = 0;
+    while start < content.len() {
+        let mut end = (start + max_bytes).min(content.len());
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        bounds.push(start..end);
+        start = end;
+    }
+    bounds
+}
+
+type ResultRow = (
+    i64,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+const RESULT_ARTIFACTS_SQL: &str =
+    "SELECT id, quote_id, svg_path, svg_sha256, instance_hash FROM nesting_results";
+
+/// Artifact references of a nesting result
+struct ResultArtifacts {
+    id: i64,
+    quote_id: Option<String>,
+    svg_path: Option<String>,
+    svg_sha256: Option<String>,
+    instance_hash: Option<String>,
+}
+
+impl From<ResultRow> for ResultArtifacts {
+    fn from((id, quote_id, svg_path, svg_sha256, instance_hash): ResultRow) -> Self {
+        ResultArtifacts {
+            id,
+            quote_id,
+            svg_path,
+            svg_sha256,
+            instance_hash,
+        }
+    }
+}
+
+impl ResultArtifacts {
+    fn problem(
+        &self,
+        kind: ArtifactKind,
+        reference: &str,
+        expected_sha256: Option<&str>,
+        (state, actual_sha256): Defect,
+        replayable: &HashSet<String>,
+    ) -> ArtifactProblem {
+        ArtifactProblem {
+            kind,
+            state,
+            result_id: Some(self.id),
+            reference: reference.to_string(),
+            expected_sha256: expected_sha256.map(str::to_string),
+            actual_sha256,
+            replay_quote_id: self
+                .quote_id
+                .clone()
+                .filter(|quote_id| replayable.contains(quote_id)),
+            source_path: None,
+        }
+    }
+}
+
+/// Quotes with a nesting snapshot, which `renest_exact` can replay
+async fn replayable_quotes(pool: &SqlitePool) -> Result<HashSet<String>, String> {
+    let rows: Vec<(String,)> = sqlx::query_as("SELECT quote_id FROM nesting_snapshots")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to read nesting snapshots: {}", e))?;
+    Ok(rows.into_iter().map(|(quote_id,)| quote_id).collect())
+}
+
+/// Content and checksum of an artifact of a nesting result, or why it
+/// is unavailable
+pub async fn load_artifact(
+    pool: &SqlitePool,
+    data_dir: &Path,
+    result_id: i64,
+    kind: ArtifactKind,
+) -> Result<Result<(String, String), ArtifactProblem>, CommandError> {
+    let row: Option<ResultRow> = sqlx::query_as(&format!("{} WHERE id = ?", RESULT_ARTIFACTS_SQL))
+        .bind(result_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to load nesting result {}: {}", result_id, e))?;
+    let result = ResultArtifacts::from(row.ok_or_else(|| {
+        CommandError::new(
+            ErrorCode::NotFound,
+            format!("No nesting result with ID {}", result_id),
+        )
+    })?);
+    let no_artifact = || {
+        CommandError::new(
+            ErrorCode::NotFound,
+            format!(
+                "Nesting result {} has no stored {}",
+                result_id,
+                kind.label().to_lowercase()
+            ),
+        )
+    };
+
+    let (reference, expected_sha256, loaded) = match kind {
+        ArtifactKind::Svg => {
+            let path = result.svg_path.as_deref().ok_or_else(no_artifact)?;
+            let expected = result.svg_sha256.as_deref();
+            let loaded = check_file(&data_dir.join(path), expected)?
+                .into_content()
+                .map(|content| String::from_utf8_lossy(&content).into_owned());
+            (path, expected, loaded)
+        }
+        ArtifactKind::Instance => {
+            let hash = result.instance_hash.as_deref().ok_or_else(no_artifact)?;
+            (hash, Some(hash), check_instance(pool, hash).await?)
+        }
+        ArtifactKind::ConvertedPart => {
+            return Err(CommandError::new(
+                ErrorCode::InvalidInput,
+                "Converted parts belong to no nesting result",
+            ))
+        }
+    };
+    match loaded {
+        Ok(content) => {
+            let sha256 = artifact_sha256(content.as_bytes());
+            Ok(Ok((content, sha256)))
+        }
+        Err(defect) => {
+            let replayable = replayable_quotes(pool).await?;
+            Ok(Err(result.problem(
+                kind,
+                reference,
+                expected_sha256,
+                defect,
+                &replayable,
+            )))
+        }
+    }
+}
+
+/// Check every stored reference: result SVGs and instances, converted parts
+pub async fn audit_stored_artifacts(
+    pool: &SqlitePool,
+    data_dir: &Path,
+) -> Result<ArtifactAudit, String> {
+    let replayable = replayable_quotes(pool).await?;
+    let rows: Vec<ResultRow> = sqlx::query_as(&format!("{} ORDER BY id", RESULT_ARTIFACTS_SQL))
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to read nesting results: {}", e))?;
+
+    let mut audit = ArtifactAudit::default();
+    // Results of one quote often share their instance
+    let mut instances: HashMap<String, Option<Defect>> = HashMap::new();
+    for result in rows.into_iter().map(ResultArtifacts::from) {
+        if let Some(path) = &result.svg_path {
+            let expected = result.svg_sha256.as_deref();
+            audit.checked += 1;
+            if expected.is_none() {
+                audit.unverified += 1;
+            }
+            if let Err(defect) = check_file(&data_dir.join(path), expected)?.into_content() {
+                audit.problems.push(result.problem(
+                    ArtifactKind::Svg,
+                    path,
+                    expected,
+                    defect,
+                    &replayable,
+                ));
+            }
+        }
+        if let Some(hash) = &result.instance_hash {
+            let defect = match instances.get(hash) {
+                Some(defect) => defect.clone(),
+                None => {
+                    let defect = check_instance(pool, hash).await?.err();
+                    instances.insert(hash.clone(), defect.clone());
+                    defect
+                }
+            };
+            audit.checked += 1;
+            if let Some(defect) = defect {
+                audit.problems.push(result.problem(
+                    ArtifactKind::Instance,
+                    hash,
+                    Some(hash),
+                    defect,
+                    &replayable,
+                ));
+            }
+        }
+    }
+
+    let cached: Vec<(String, String, Option<String>)> = sqlx::query_as(
+        "SELECT source_path, output_path, output_sha256 FROM conversion_cache ORDER BY created_at",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to read conversion cache: {}", e))?;
+    for (source_path, output_path, expected) in cached {
+        audit.checked += 1;
+        if expected.is_none() {
+            audit.unverified += 1;
+        }
+        let check = check_file(Path::new(&output_path), expected.as_deref())?;
+        if let Err((state, actual_sha256)) = check.into_content() {
+            audit.problems.push(ArtifactProblem {
+                kind: ArtifactKind::ConvertedPart,
+                state,
+                result_id: None,
+                reference: output_path,
+                expected_sha256: expected,
+                actual_sha256,
+                replay_quote_id: None,
+                source_path: Some(source_path),
+            });
+        }
+    }
+    Ok(audit)
+}
+
+/// Stored SVG or instance of a nesting result, checked against its checksum
+///
+/// Content up to `INLINE_ARTIFACT_BYTES` comes inline, larger content as
+/// a token for `read_artifact_chunk`. A missing or changed file fails
+/// with `not_found` or `corrupt` and the `unavailable` payload naming
+/// the quote whose snapshot `renest_exact` can regenerate it from.
+#[tauri::command]
+pub async fn get_artifact(
+    app_handle: tauri::AppHandle,
+    transfers: State<'_, ArtifactTransfers>,
+    result_id: i64,
+    kind: ArtifactKind,
+) -> CommandResponse<ArtifactFetch> {
+    let loaded = async {
+        let pool = db::sqlite_pool(&app_handle).await?;
+        let data_dir = workspace::app_data_dir(&app_handle)?;
+        load_artifact(&pool, &data_dir, result_id, kind).await
+    }
+    .await;
+
+    match loaded {
+        Ok(Ok((content, sha256))) => {
+            let size_bytes = content.len() as u64;
+            if content.len() <= INLINE_ARTIFACT_BYTES {
+                return CommandResponse::ok(ArtifactFetch::Inline {
+                    content,
+                    size_bytes,
+                    sha256,
+                });
+            }
+            let chunks = chunk_bounds(&content, ARTIFACT_CHUNK_BYTES);
+            let token = uuid::Uuid::new_v4().to_string();
+            let chunk_count = chunks.len();
+            transfers.insert(
+                token.clone(),
+                Arc::new(ArtifactTransfer { content, chunks }),
+            );
+            CommandResponse::ok(ArtifactFetch::Chunked {
+                token,
+                size_bytes,
+                chunk_count,
+                sha256,
+            })
+        }
+        Ok(Err(problem)) => {
+            let error = problem.error();
+            println!("⚠️ {}", error.message);
+            CommandResponse::partial(ArtifactFetch::Unavailable(problem), error)
+        }
+        Err(error) => respond(Err(error)),
+    }
+}
+
+/// Chunk `index` of a transfer started by `get_artifact`
+#[tauri::command]
+pub async fn read_artifact_chunk(
+    transfers: State<'_, ArtifactTransfers>,
+    token: String,
+    index: usize,
+) -> Result<ArtifactChunk, String> {
+    let transfer = transfers
+        .get(&token)
+        .ok_or_else(|| format!("Artifact transfer '{}' is not available", token))?;
+    let range = transfer.chunks.get(index).cloned().ok_or_else(|| {
+        format!(
+            "Artifact transfer '{}' has {} chunks, no chunk {}",
+            token,
+            transfer.chunks.len(),
+            index
+        )
+    })?;
+    let last = index + 1 == transfer.chunks.len();
+    if last {
+        transfers.remove(&token);
+    }
+    Ok(ArtifactChunk {
+        index,
+        content: transfer.content[range].to_string(),
+        last,
+    })
+}
+
+/// Check every stored SVG, instance and converted part
+#[tauri::command]
+pub async fn audit_artifacts(app_handle: tauri::AppHandle) -> Result<ArtifactAudit, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let data_dir = workspace::app_data_dir(&app_handle)?;
+    let audit = audit_stored_artifacts(&pool, &data_dir).await?;
+    println!(
+        "🧾 Checked {} stored artifacts: {} missing or corrupt, {} without checksum",
+        audit.checked,
+        audit.problems.len(),
+        audit.unverified
+    );
+    Ok(audit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::instance_blobs::acquire_instance_blob;
+    use crate::db::test_support::{migrated_pool, test_dir};
+    use tauri::async_runtime::block_on;
+
+    /// Store a result row with an SVG written below `dir`, its checksum
+    /// recorded when `checksum`
+    async fn save_result(
+        pool: &SqlitePool,
+        dir: &Path,
+        quote_id: Option<&str>,
+        svg: &str,
+        checksum: bool,
+        instance_json: Option<&str>,
+    ) -> i64 {
+        let mut tx = pool.begin().await.unwrap();
+        let instance_hash = match instance_json {
+            Some(json) => Some(acquire_instance_blob(&mut tx, json).await.unwrap()),
+            None => None,
+        };
+        let svg_path = format!("svgs/pipeline/{}.svg", artifact_sha256(svg.as_bytes()));
+        std::fs::create_dir_all(dir.join("svgs/pipeline")).unwrap();
+        std::fs::write(dir.join(&svg_path), svg).unwrap();
+        let id = sqlx::query(
+            "INSERT INTO nesting_results
+                (quote_id, strip_width, result_json, svg_path, svg_sha256, instance_hash)
+             VALUES (?, 100.0, '{}', ?, ?, ?)",
+        )
+        .bind(quote_id)
+        .bind(&svg_path)
+        .bind(checksum.then(|| artifact_sha256(svg.as_bytes())))
+        .bind(&instance_hash)
+        .execute(&mut *tx)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        tx.commit().await.unwrap();
+        id
+    }
+
+    async fn svg_path(pool: &SqlitePool, id: i64) -> String {
+        let (path,): (String,) =
+            sqlx::query_as("SELECT svg_path FROM nesting_results WHERE id = ?")
+                .bind(id)
+                .fetch_one(pool)
+                .await
+                .unwrap();
+        path
+    }
+
+    const PARTS: &str = r#"{"name": "quote", "strip_height": 1500.0, "items": []}"#;
+    const OTHER_PARTS: &str = r#"{"name": "quote", "strip_height": 1250.0, "items": []}"#;
+
+    #[test]
+    fn test_check_file_and_chunk_bounds() {
+        let dir = test_dir("artifacts", "check");
+        let path = dir.join("layout.svg");
+        std::fs::write(&path, "<svg></svg>").unwrap();
+        let sha256 = artifact_sha256(b"<svg></svg>");
+
+        assert_eq!(
+            check_file(&path, Some(&sha256)).unwrap(),
+            FileCheck::Intact(b"<svg></svg>".to_vec())
+        );
+        assert_eq!(
+            check_file(&path, None).unwrap(),
+            FileCheck::Intact(b"<svg></svg>".to_vec())
+        );
+        std::fs::write(&path, "<svg/>").unwrap();
+        assert_eq!(
+            check_file(&path, Some(&sha256)).unwrap(),
+            FileCheck::Corrupt {
+                actual_sha256: artifact_sha256(b"<svg/>")
+            }
+        );
+        assert_eq!(
+            check_file(&dir.join("moved.svg"), Some(&sha256)).unwrap(),
+            FileCheck::Missing
+        );
+
+        // Part names are Vietnamese, chunks must not split a letter
+        let content = "Tấm đệm ".repeat(50);
+        let chunks = chunk_bounds(&content, 5);
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.len() <= 5 && !chunk.is_empty()));
+        let joined: String = chunks.into_iter().map(|chunk| &content[chunk]).collect();
+        assert_eq!(joined, content);
+        assert!(chunk_bounds("", 5).is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_audit_reports_missing_and_corrupt_artifacts() {
+        let dir = test_dir("artifacts", "audit");
+        block_on(async {
+            let pool = migrated_pool(&dir).await;
+            sqlx::raw_sql(
+                "INSERT INTO quotes (id, quote_number) VALUES ('q1', 'Q-1');
+                 INSERT INTO nesting_snapshots
+                    (quote_id, seed, config_json, instance_json, instance_hash,
+                     fingerprint_json, engine_version)
+                 VALUES ('q1', '7', '{}', '{}', 'h', '{}', '1');",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+            let intact =
+                save_result(&pool, &dir, Some("q1"), "<svg>1</svg>", true, Some(PARTS)).await;
+            let moved = save_result(&pool, &dir, Some("q1"), "<svg>2</svg>", true, None).await;
+            let changed =
+                save_result(&pool, &dir, None, "<svg>3</svg>", true, Some(OTHER_PARTS)).await;
+            let legacy = save_result(&pool, &dir, None, "<svg>4</svg>", false, None).await;
+
+            std::fs::remove_file(dir.join(svg_path(&pool, moved).await)).unwrap();
+            std::fs::write(dir.join(svg_path(&pool, changed).await), "<svg/>").unwrap();
+            std::fs::write(dir.join(svg_path(&pool, legacy).await), "<svg>4b</svg>").unwrap();
+            sqlx::query("UPDATE instance_blobs SET body = x'00' WHERE hash = ?")
+                .bind(instance_hash(OTHER_PARTS))
+                .execute(&pool)
+                .await
+                .unwrap();
+            sqlx::query(
+                "INSERT INTO conversion_cache (cache_key, source_path, output_path, output_sha256)
+                 VALUES ('k', 'C:/parts/flange.dxf', ?, 'abc')",
+            )
+            .bind(
+                dir.join("library/flange.json")
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+            let audit = audit_stored_artifacts(&pool, &dir).await.unwrap();
+            assert_eq!(audit.checked, 7);
+            assert_eq!(audit.unverified, 1);
+            let problems: Vec<_> = audit
+                .problems
+                .iter()
+                .map(|problem| (problem.kind, problem.state, problem.result_id))
+                .collect();
+            assert_eq!(
+                problems,
+                vec![
+                    (ArtifactKind::Svg, ArtifactState::Missing, Some(moved)),
+                    (ArtifactKind::Svg, ArtifactState::Corrupt, Some(changed)),
+                    (
+                        ArtifactKind::Instance,
+                        ArtifactState::Corrupt,
+                        Some(changed)
+                    ),
+                    (ArtifactKind::ConvertedPart, ArtifactState::Missing, None),
+                ]
+            );
+            // Only the quote with a snapshot can be replayed
+            assert_eq!(audit.problems[0].replay_quote_id.as_deref(), Some("q1"));
+            assert!(audit.problems[1].replay_quote_id.is_none());
+            assert_eq!(
+                audit.problems[1].actual_sha256.as_deref(),
+                Some(artifact_sha256(b"<svg/>").as_str())
+            );
+            assert_eq!(
+                audit.problems[3].source_path.as_deref(),
+                Some("C:/parts/flange.dxf")
+            );
+
+            let (content, sha256) = load_artifact(&pool, &dir, intact, ArtifactKind::Svg)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(content, "<svg>1</svg>");
+            assert_eq!(sha256, artifact_sha256(content.as_bytes()));
+            let (content, _) = load_artifact(&pool, &dir, intact, ArtifactKind::Instance)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(content, PARTS);
+
+            let problem = load_artifact(&pool, &dir, moved, ArtifactKind::Svg)
+                .await
+                .unwrap()
+                .unwrap_err();
+            assert_eq!(problem, audit.problems[0]);
+            assert_eq!(problem.error().code, ErrorCode::NotFound);
+            assert_eq!(audit.problems[1].error().code, ErrorCode::Corrupt);
+            let error = load_artifact(&pool, &dir, legacy, ArtifactKind::Instance)
+                .await
+                .unwrap_err();
+            assert_eq!(error.code, ErrorCode::NotFound);
+            pool.close().await;
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,19 @@
+use crate::nesting_engine::{self, BenchmarkReport};
+
+/// Benchmark the integrated engine on an embedded instance (hidden, for CI)
+///
+/// Only compiled into debug builds.
+#[tauri::command]
+pub async fn bench_nesting_engine(
+    iterations: usize,
+    instance_name: String,
+) -> Result<BenchmarkReport, String> {
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        nesting_engine::run_benchmark(iterations, &instance_name)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    println!("{}", report.to_table());
+    Ok(report)
+}
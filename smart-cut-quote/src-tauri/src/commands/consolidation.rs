@@ -0,0 +1,638 @@
+//! Consolidated production runs over several quotes
+//!
+//! Approved quotes on the same material are cut together: the parts
+//! stored with each quote's nesting snapshot are merged per material +
+//! thickness, nested as one run, and the placements and material cost
+//! are attributed back to the quotes (see `nesting_engine::consolidate`).
+//!
+//! Every run is stored as a nesting result with the quote of each merged
+//! item, so its sheet costs can be split again later with another policy
+//! (`allocate_sheet_costs`).
+
+use crate::commands::instance_blobs::acquire_instance_blob;
+use crate::commands::material_requirements::{group_key, nested_requirement, MaterialRequirement};
+use crate::commands::nesting_batch::load_app_overrides;
+use crate::commands::nesting_metrics::{record_nesting_run, NestingRunMetrics};
+use crate::commands::shutdown;
+use crate::db;
+use crate::nesting_engine::{
+    self, allocate_by_placed_area, merge_quote_instances, parse_instance, sheets_of_output,
+    ConfigOverrides, DisplayUnits, InstanceGeometry, ItemSource, NestingOutput, QuoteAllocation,
+    QuoteParts, SheetCostAllocation, SheetCostPolicy,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+
+/// Quote left out of the consolidation
+#[derive(Serialize, Debug, Clone)]
+pub struct SkippedQuote {
+    pub quote_id: String,
+    pub quote_number: Option<String>,
+    pub reason: String,
+}
+
+/// One material + thickness of a consolidated run
+#[derive(Serialize, Debug, Clone)]
+pub struct ConsolidatedGroup {
+    /// Batch key, e.g. "Mild Steel-2mm"
+    pub key: String,
+    pub material: String,
+    pub thickness: f64,
+    pub quote_ids: Vec<String>,
+    /// Quote and original item of every item ID in `output`
+    pub sources: Vec<ItemSource>,
+    pub output: Option<NestingOutput>,
+    /// Sheets, weight and cost of the run
+    pub requirement: Option<MaterialRequirement>,
+    /// Material cost split by placed part area
+    pub allocations: Vec<QuoteAllocation>,
+    /// Material cost split sheet by sheet
+    pub sheet_costs: Option<SheetCostAllocation>,
+    /// Stored nesting result of the run
+    pub result_id: Option<i64>,
+    pub error: Option<String>,
+}
+
+/// What a stored consolidated run needs to split its cost again
+/// (`nesting_results.consolidation_json`)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ConsolidationRecord {
+    key: String,
+    sources: Vec<ItemSource>,
+    material_cost: f64,
+    /// Stock sheets the strip is cut into
+    strip_sheets: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Consolidation {
+    /// Unit of every length in `groups`, areas are in its square
+    pub units: DisplayUnits,
+    pub groups: Vec<ConsolidatedGroup>,
+    pub skipped: Vec<SkippedQuote>,
+}
+
+// Subset of the quote JSON blob (see quoteService.ts)
+#[derive(Deserialize, Debug, Default)]
+struct QuoteFiles {
+    #[serde(default)]
+    files: Vec<QuoteFile>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct QuoteFile {
+    id: String,
+    material: Option<FileMaterial>,
+    material_group: Option<String>,
+    material_grade: Option<String>,
+    material_thickness: Option<f64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FileMaterial {
+    name: String,
+    grade: Option<String>,
+    thickness: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PartMaterial {
+    material: String,
+    grade: Option<String>,
+    thickness: f64,
+}
+
+impl PartMaterial {
+    fn of_file(file: &QuoteFile) -> Option<Self> {
+        let material = file
+            .material_group
+            .clone()
+            .or_else(|| file.material.as_ref().map(|m| m.name.clone()))?;
+        let thickness = file
+            .material_thickness
+            .or_else(|| file.material.as_ref().map(|m| m.thickness))?;
+        let grade = file
+            .material_grade
+            .clone()
+            .or_else(|| file.material.as_ref().and_then(|m| m.grade.clone()));
+        Some(PartMaterial {
+            material,
+            grade,
+            thickness,
+        })
+    }
+
+    fn key(&self) -> String {
+        group_key(&self.material, self.thickness)
+    }
+}
+
+/// Parts of all quotes on one material + thickness
+struct GroupParts {
+    material: PartMaterial,
+    quotes: Vec<QuoteParts>,
+}
+
+/// Nest the parts of several quotes together, one run per material
+///
+/// Parts come from each quote's stored nesting snapshot and are matched
+/// to their material through the `fileId` in their metadata (or the
+/// quote's only material). `material_filter` limits the run to some
+/// batch keys (`Material-Thicknessmm`). Quotes without stored geometry
+/// or with parts of unknown material are skipped and reported. The
+/// sheet costs are split following `cost_policy` (default: waste in
+/// proportion to the part area).
+/// Lengths are in mm unless other `units` are requested.
+#[tauri::command]
+pub async fn consolidate_quotes(
+    app_handle: tauri::AppHandle,
+    quote_ids: Vec<String>,
+    material_filter: Option<Vec<String>>,
+    units: Option<DisplayUnits>,
+    cost_policy: Option<SheetCostPolicy>,
+) -> Result<Consolidation, String> {
+    let units = units.unwrap_or_default();
+    let cost_policy = cost_policy.unwrap_or_default();
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let mut groups: BTreeMap<String, GroupParts> = BTreeMap::new();
+    let mut skipped = Vec::new();
+
+    for quote_id in quote_ids {
+        let row: Option<(String, Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT q.quote_number, q.data, s.instance_json
+             FROM quotes q LEFT JOIN nesting_snapshots s ON s.quote_id = q.id
+             WHERE q.id = ?",
+        )
+        .bind(&quote_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to load quote '{}': {}", quote_id, e))?;
+
+        let skip = |quote_number: Option<String>, reason: String| SkippedQuote {
+            quote_id: quote_id.clone(),
+            quote_number,
+            reason,
+        };
+        let Some((quote_number, data, instance_json)) = row else {
+            skipped.push(skip(None, "Quote not found".to_string()));
+            continue;
+        };
+        let Some(instance_json) = instance_json else {
+            skipped.push(skip(
+                Some(quote_number),
+                "No nesting geometry stored for this quote".to_string(),
+            ));
+            continue;
+        };
+
+        let parts = serde_json::from_str::<QuoteFiles>(data.as_deref().unwrap_or("{}"))
+            .map_err(|e| format!("Invalid quote data: {}", e))
+            .and_then(|files| {
+                let instance = parse_instance(&instance_json)?;
+                assign_materials(&instance, &files)
+            });
+        let parts = match parts {
+            Ok(parts) => parts,
+            Err(reason) => {
+                skipped.push(skip(Some(quote_number), reason));
+                continue;
+            }
+        };
+
+        for (material, item_ids) in parts {
+            let key = material.key();
+            if material_filter
+                .as_ref()
+                .is_some_and(|filter| !filter.contains(&key))
+            {
+                continue;
+            }
+            groups
+                .entry(key)
+                .or_insert_with(|| GroupParts {
+                    material,
+                    quotes: Vec::new(),
+                })
+                .quotes
+                .push(QuoteParts {
+                    quote_id: quote_id.clone(),
+                    quote_number: quote_number.clone(),
+                    instance_json: instance_json.clone(),
+                    item_ids,
+                });
+        }
+    }
+
+    for quote in &skipped {
+        println!("⚠️ Skipping quote {}: {}", quote.quote_id, quote.reason);
+    }
+
+    let app_settings = load_app_overrides(&app_handle).await;
+    let mut outputs = Vec::with_capacity(groups.len());
+    for (key, group) in groups {
+        let quote_ids: Vec<String> = group.quotes.iter().map(|q| q.quote_id.clone()).collect();
+        let mut consolidated = ConsolidatedGroup {
+            key: key.clone(),
+            material: group.material.material.clone(),
+            thickness: group.material.thickness,
+            quote_ids,
+            sources: Vec::new(),
+            output: None,
+            requirement: None,
+            allocations: Vec::new(),
+            sheet_costs: None,
+            result_id: None,
+            error: None,
+        };
+
+        let (json_input, sources) =
+            match merge_quote_instances(&format!("consolidated_{}", key), &group.quotes) {
+                Ok(merged) => merged,
+                Err(e) => {
+                    consolidated.error = Some(e);
+                    outputs.push(consolidated);
+                    continue;
+                }
+            };
+        consolidated.sources = sources;
+        println!(
+            "🏭 Consolidating {} parts of {} quotes on {}",
+            consolidated.sources.len(),
+            consolidated.quote_ids.len(),
+            key
+        );
+
+        let input = ConfigOverrides::resolve(
+            &ConfigOverrides::default(),
+            &ConfigOverrides::default(),
+            &app_settings,
+        )
+        .into_input(json_input.clone());
+        let started = std::time::Instant::now();
+        let metrics = NestingRunMetrics::for_input(&input);
+        let job_app_handle = app_handle.clone();
+        let result = tauri::async_runtime::spawn_blocking(move || {
+            shutdown::run_registered(&job_app_handle, None, |terminator| {
+                nesting_engine::run_nesting_engine_with_terminator(input, terminator)
+            })
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?;
+        record_nesting_run(&app_handle, metrics.finish(&result, started.elapsed()));
+
+        let output = match result {
+            Ok(output) => output,
+            Err(e) => {
+                consolidated.error = Some(e);
+                outputs.push(consolidated);
+                continue;
+            }
+        };
+
+        let placed_area = placed_area(&output, &consolidated.sources);
+        let requirement = nested_requirement(
+            &pool,
+            &group.material.material,
+            group.material.grade.clone(),
+            group.material.thickness,
+            placed_area,
+            output.strip_width,
+            output.strip_height,
+        )
+        .await?;
+        consolidated.allocations =
+            allocate_by_placed_area(&output.layouts, &consolidated.sources, requirement.cost);
+        let record = ConsolidationRecord {
+            key: key.clone(),
+            sources: consolidated.sources.clone(),
+            material_cost: requirement.cost,
+            strip_sheets: requirement.sheets_required as usize,
+        };
+        consolidated.sheet_costs = Some(split_sheet_costs(&output, &record, &cost_policy));
+        consolidated.result_id =
+            Some(store_consolidation(&pool, &json_input, &output, &record).await?);
+        consolidated.requirement = Some(requirement);
+        consolidated.output = Some(output);
+        outputs.push(consolidated);
+    }
+
+    for group in &mut outputs {
+        group.thickness = units.length(group.thickness);
+        group.output = group.output.take().map(|output| output.to_units(units));
+        group.requirement = group
+            .requirement
+            .take()
+            .map(|requirement| requirement.in_units(units));
+        for allocation in &mut group.allocations {
+            allocation.placed_area = units.area(allocation.placed_area);
+        }
+        if let Some(sheet_costs) = group.sheet_costs.as_mut() {
+            for line in sheet_costs
+                .sheets
+                .iter_mut()
+                .flat_map(|sheet| sheet.lines.iter_mut())
+                .chain(sheet_costs.quotes.iter_mut())
+            {
+                line.placed_area = units.area(line.placed_area);
+            }
+        }
+    }
+
+    Ok(Consolidation {
+        units,
+        groups: outputs,
+        skipped,
+    })
+}
+
+/// Split the material cost of a consolidated run over its sheets
+fn split_sheet_costs(
+    output: &NestingOutput,
+    record: &ConsolidationRecord,
+    policy: &SheetCostPolicy,
+) -> SheetCostAllocation {
+    nesting_engine::allocate_sheet_costs(
+        &sheets_of_output(output, record.strip_sheets),
+        &record.sources,
+        record.material_cost,
+        policy,
+    )
+}
+
+/// Store a consolidated run as a nesting result
+async fn store_consolidation(
+    pool: &SqlitePool,
+    instance_json: &str,
+    output: &NestingOutput,
+    record: &ConsolidationRecord,
+) -> Result<i64, String> {
+    let result_json = serde_json::to_string(output)
+        .map_err(|e| format!("Failed to serialize nesting result: {}", e))?;
+    let consolidation_json = serde_json::to_string(record)
+        .map_err(|e| format!("Failed to serialize consolidation: {}", e))?;
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let instance_hash = acquire_instance_blob(&mut tx, instance_json).await?;
+    let id = sqlx::query(
+        "INSERT INTO nesting_results
+            (instance_name, strip_width, strip_height, density, placed_items, run_time_sec,
+             result_json, source, instance_hash, consolidation_json)
+         VALUES (?, ?, ?, ?, ?, ?, ?, 'consolidation', ?, ?)",
+    )
+    .bind(&output.instance_name)
+    .bind(output.strip_width)
+    .bind(output.strip_height)
+    .bind(output.utilization)
+    .bind(output.total_items_placed as i64)
+    .bind(output.computation_time_secs)
+    .bind(&result_json)
+    .bind(&instance_hash)
+    .bind(&consolidation_json)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to store nesting result: {}", e))?
+    .last_insert_rowid();
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to store nesting result: {}", e))?;
+    Ok(id)
+}
+
+/// Split the sheet costs of a stored consolidated run again
+///
+/// Uses the material cost computed when the run was made; areas are in mm².
+#[tauri::command]
+pub async fn allocate_sheet_costs(
+    app_handle: tauri::AppHandle,
+    result_id: i64,
+    policy: Option<SheetCostPolicy>,
+) -> Result<SheetCostAllocation, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    load_sheet_costs(&pool, result_id, &policy.unwrap_or_default()).await
+}
+
+async fn load_sheet_costs(
+    pool: &SqlitePool,
+    result_id: i64,
+    policy: &SheetCostPolicy,
+) -> Result<SheetCostAllocation, String> {
+    let row: Option<(String, Option<String>)> =
+        sqlx::query_as("SELECT result_json, consolidation_json FROM nesting_results WHERE id = ?")
+            .bind(result_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Failed to load nesting result {}: {}", result_id, e))?;
+    let (result_json, consolidation_json) =
+        row.ok_or_else(|| format!("Nesting result {} not found", result_id))?;
+    let consolidation_json = consolidation_json
+        .ok_or_else(|| format!("Nesting result {} is not a consolidated run", result_id))?;
+
+    let output: NestingOutput = serde_json::from_str(&result_json)
+        .map_err(|e| format!("Invalid nesting result {}: {}", result_id, e))?;
+    let record: ConsolidationRecord = serde_json::from_str(&consolidation_json)
+        .map_err(|e| format!("Invalid consolidation of result {}: {}", result_id, e))?;
+    Ok(split_sheet_costs(
+        &output.to_units(DisplayUnits::Mm),
+        &record,
+        policy,
+    ))
+}
+
+/// Item IDs of a quote's instance per material
+///
+/// Items are matched to the quote file named by the `fileId` of their
+/// metadata; items without one belong to the quote's only material.
+fn assign_materials(
+    instance: &InstanceGeometry,
+    quote: &QuoteFiles,
+) -> Result<Vec<(PartMaterial, Vec<u64>)>, String> {
+    let mut file_materials: Vec<(&str, PartMaterial)> = Vec::new();
+    for file in &quote.files {
+        if let Some(material) = PartMaterial::of_file(file) {
+            file_materials.push((file.id.as_str(), material));
+        }
+    }
+    let only_material = match file_materials.first() {
+        Some((_, first)) if file_materials.iter().all(|(_, m)| m.key() == first.key()) => {
+            Some(first.clone())
+        }
+        _ => None,
+    };
+
+    let mut assigned: Vec<(PartMaterial, Vec<u64>)> = Vec::new();
+    let mut unknown = Vec::new();
+    for item in &instance.items {
+        let file_id = item
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get("fileId"))
+            .and_then(|id| id.as_str());
+        let material = file_id
+            .and_then(|id| file_materials.iter().find(|(file_id, _)| *file_id == id))
+            .map(|(_, material)| material.clone())
+            .or_else(|| only_material.clone());
+
+        match material {
+            Some(material) => match assigned.iter_mut().find(|(m, _)| m.key() == material.key()) {
+                Some((_, item_ids)) => item_ids.push(item.id),
+                None => assigned.push((material, vec![item.id])),
+            },
+            None => unknown.push(item.display_name()),
+        }
+    }
+
+    if !unknown.is_empty() {
+        return Err(format!("No material for parts: {}", unknown.join(", ")));
+    }
+    Ok(assigned)
+}
+
+/// Net area of all placed parts
+fn placed_area(output: &NestingOutput, sources: &[ItemSource]) -> f64 {
+    output
+        .layouts
+        .iter()
+        .filter_map(|placed| sources.get(placed.item_id))
+        .map(|source| source.net_area)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{migrated_pool, test_dir};
+    use tauri::async_runtime::block_on;
+
+    fn instance() -> InstanceGeometry {
+        parse_instance(
+            r#"{
+                "strip_height": 1500,
+                "items": [
+                    {"id": 0, "demand": 1, "metadata": {"fileId": "a"},
+                     "shape": {"type": "rectangle", "data": {"x_min": 0, "y_min": 0, "width": 10, "height": 10}}},
+                    {"id": 1, "demand": 1, "metadata": {"fileId": "b"},
+                     "shape": {"type": "rectangle", "data": {"x_min": 0, "y_min": 0, "width": 10, "height": 10}}},
+                    {"id": 2, "demand": 1, "label": "loose.dxf",
+                     "shape": {"type": "rectangle", "data": {"x_min": 0, "y_min": 0, "width": 10, "height": 10}}}
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_items_are_grouped_by_file_material() {
+        let files: QuoteFiles = serde_json::from_str(
+            r#"{"files": [
+                {"id": "a", "materialGroup": "Mild Steel", "materialThickness": 2},
+                {"id": "b", "material": {"name": "Aluminum", "grade": "5052", "thickness": 1}}
+            ]}"#,
+        )
+        .unwrap();
+
+        // Item 2 has no file and the quote has two materials
+        let error = assign_materials(&instance(), &files).unwrap_err();
+        assert!(error.contains("loose.dxf"), "{}", error);
+
+        let mut single = instance();
+        single.items.truncate(2);
+        let groups = assign_materials(&single, &files).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0.key(), "Mild Steel-2mm");
+        assert_eq!(groups[0].1, vec![0]);
+        assert_eq!(groups[1].0.grade.as_deref(), Some("5052"));
+        assert_eq!(groups[1].1, vec![1]);
+    }
+
+    #[test]
+    fn test_single_material_quote_takes_all_items() {
+        let files: QuoteFiles = serde_json::from_str(
+            r#"{"files": [{"id": "a", "materialGroup": "Mild Steel", "materialThickness": 2}]}"#,
+        )
+        .unwrap();
+
+        let groups = assign_materials(&instance(), &files).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].1, vec![0, 1, 2]);
+    }
+
+    fn source(item_id: usize, quote_id: &str, net_area: f64) -> ItemSource {
+        ItemSource {
+            item_id,
+            quote_id: quote_id.to_string(),
+            quote_number: quote_id.to_uppercase(),
+            source_item_id: 0,
+            label: format!("part {}", item_id),
+            demand: 1,
+            net_area,
+        }
+    }
+
+    #[test]
+    fn test_stored_run_is_split_again_with_another_policy() {
+        let dir = test_dir("consolidation", "split");
+        block_on(async {
+            let pool = migrated_pool(&dir).await;
+
+            // A 100 x 100 sheet holding 1000 mm² of quote a, 3000 mm² of b
+            let output: NestingOutput = serde_json::from_value(serde_json::json!({
+                "instance_name": "consolidated_Mild Steel-2mm",
+                "strip_width": 100.0,
+                "strip_height": 100.0,
+                "total_items_placed": 2,
+                "layouts": [
+                    {"item_id": 0, "rotation_degrees": 0.0, "position_x": 0.0, "position_y": 0.0},
+                    {"item_id": 1, "rotation_degrees": 0.0, "position_x": 40.0, "position_y": 0.0}
+                ],
+                "utilization": 0.4,
+                "computation_time_secs": 1.0,
+            }))
+            .unwrap();
+            let record = ConsolidationRecord {
+                key: "Mild Steel-2mm".to_string(),
+                sources: vec![source(0, "a", 1000.0), source(1, "b", 3000.0)],
+                material_cost: 50.0,
+                strip_sheets: 1,
+            };
+            let id = store_consolidation(&pool, r#"{"items": []}"#, &output, &record)
+                .await
+                .unwrap();
+
+            let proportional = load_sheet_costs(&pool, id, &SheetCostPolicy::Proportional)
+                .await
+                .unwrap();
+            let costs: Vec<f64> = proportional.quotes.iter().map(|line| line.cost).collect();
+            assert_eq!(costs, vec![12.5, 37.5]);
+
+            let overhead = SheetCostPolicy::Overhead {
+                bucket: "Shop".to_string(),
+            };
+            let split = load_sheet_costs(&pool, id, &overhead).await.unwrap();
+            let costs: Vec<f64> = split.quotes.iter().map(|line| line.cost).collect();
+            assert_eq!(costs, vec![5.0, 15.0, 30.0]);
+            assert_eq!(split.quotes[2].quote_id, None);
+
+            // Plain nesting results have no quotes to split between
+            let mut tx = pool.begin().await.unwrap();
+            let hash = acquire_instance_blob(&mut tx, "{}").await.unwrap();
+            let plain = sqlx::query(
+                "INSERT INTO nesting_results (strip_width, result_json, instance_hash)
+                 VALUES (100.0, '{}', ?)",
+            )
+            .bind(&hash)
+            .execute(&mut *tx)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+            tx.commit().await.unwrap();
+            let error = load_sheet_costs(&pool, plain, &overhead).await.unwrap_err();
+            assert!(error.contains("not a consolidated run"), "{}", error);
+            pool.close().await;
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,703 @@
+use crate::commands::artifacts::{artifact_sha256, check_file, FileCheck};
+use crate::commands::dxf_converter::{
+    converter_command, converter_exe_path, ConversionOptions, DxfFileInput, CONVERTER_TIME_LIMIT,
+};
+use crate::commands::event_bus::EventBus;
+use crate::commands::tool_process::{run_tool, Tool};
+use crate::db;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::async_runtime::block_on;
+use tauri::{AppHandle, Manager, State};
+
+/// Event emitted after every processed file and when a job stops
+///
+/// Coalesced per job by the event bus: a burst of files arrives as its
+/// last event, per-file errors stay available from `get_conversion_job`.
+pub const CONVERSION_PROGRESS_EVENT: &str = "conversion://progress";
+
+/// Cancel flags of the jobs with a running worker thread
+///
+/// Holds directory jobs and `convert_dxf_to_json` batches started with a
+/// job ID. A job marked `running` in the database without an entry here
+/// was interrupted (e.g. the app was closed) and can be resumed.
+#[derive(Default)]
+pub struct ConversionJobs {
+    running: Mutex<HashMap<String, RunningJob>>,
+}
+
+struct RunningJob {
+    cancel: Arc<AtomicBool>,
+    /// Directory job stored in the database (false for batches)
+    persisted: bool,
+}
+
+impl ConversionJobs {
+    /// Register a worker, fails if the job already has one
+    fn start(&self, job_id: &str) -> Result<Arc<AtomicBool>, String> {
+        self.register(job_id, true)
+    }
+
+    /// Register a `convert_dxf_to_json` batch under a caller chosen ID
+    pub(crate) fn start_batch(&self, job_id: &str) -> Result<Arc<AtomicBool>, String> {
+        self.register(job_id, false)
+    }
+
+    fn register(&self, job_id: &str, persisted: bool) -> Result<Arc<AtomicBool>, String> {
+        let mut running = self
+            .running
+            .lock()
+            .map_err(|_| "Conversion job registry is poisoned".to_string())?;
+        if running.contains_key(job_id) {
+            return Err(format!("Conversion job '{}' is already running", job_id));
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        running.insert(
+            job_id.to_string(),
+            RunningJob {
+                cancel: cancel.clone(),
+                persisted,
+            },
+        );
+        Ok(cancel)
+    }
+
+    pub(crate) fn finish(&self, job_id: &str) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(job_id);
+        }
+    }
+
+    /// Ask a running worker to stop
+    ///
+    /// Returns whether the job is persisted, None if it has no worker.
+    fn cancel(&self, job_id: &str) -> Option<bool> {
+        let running = self.running.lock().ok()?;
+        let job = running.get(job_id)?;
+        job.cancel.store(true, Ordering::SeqCst);
+        Some(job.persisted)
+    }
+
+    fn is_running(&self, job_id: &str) -> bool {
+        self.running
+            .lock()
+            .map(|running| running.contains_key(job_id))
+            .unwrap_or(false)
+    }
+
+    /// Stop all workers after their in-flight file (called when the app exits)
+    pub fn cancel_all(&self) {
+        if let Ok(running) = self.running.lock() {
+            for job in running.values() {
+                job.cancel.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+}
+
+/// Converted file, usable for instance building right away
+#[derive(Serialize, Debug, Clone)]
+pub struct ConvertedFile {
+    pub path: String,
+    pub output_path: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ConversionJobStatus {
+    pub job_id: String,
+    pub directory: String,
+    /// "running", "interrupted", "cancelled", "completed", or
+    /// "cancelling" for a `convert_dxf_to_json` batch
+    pub status: String,
+    pub files_done: usize,
+    pub files_failed: usize,
+    pub files_remaining: usize,
+    /// Outputs converted so far (partial results)
+    pub converted: Vec<ConvertedFile>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ConversionProgressEvent {
+    pub job_id: String,
+    pub status: String,
+    pub files_done: usize,
+    pub files_failed: usize,
+    pub files_remaining: usize,
+    /// Throughput of the current run (cache hits included)
+    pub files_per_minute: f64,
+    pub eta_secs: Option<f64>,
+    /// File processed last, None in the final event
+    pub file: Option<String>,
+    pub output_path: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Job row loaded for a worker
+struct JobConfig {
+    job_id: String,
+    output_dir: PathBuf,
+    options: ConversionOptions,
+    options_json: String,
+    max_files_per_minute: Option<u32>,
+}
+
+/// Convert all DXF files of a directory as a resumable background job
+///
+/// Files are converted one at a time with dxf-converter.exe, at most
+/// `max_files_per_minute` per minute when given. Progress is stored per
+/// file, so a job interrupted by closing the app continues with
+/// `resume_conversion_job`. Files whose content was already converted
+/// with the same options are taken from the conversion cache.
+#[tauri::command]
+pub async fn start_conversion_job(
+    app_handle: AppHandle,
+    jobs: State<'_, ConversionJobs>,
+    directory: String,
+    output_dir: String,
+    options: ConversionOptions,
+    max_files_per_minute: Option<u32>,
+) -> Result<ConversionJobStatus, String> {
+    let files = list_dxf_files(Path::new(&directory))?;
+    std::fs::create_dir_all(&output_dir)
+        .map_err(|e| format!("Failed to create '{}': {}", output_dir, e))?;
+    let options_json = serde_json::to_string(&options)
+        .map_err(|e| format!("Failed to serialize conversion options: {}", e))?;
+
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    sqlx::query(
+        "INSERT INTO conversion_jobs (id, directory, output_dir, options, max_files_per_minute)
+         VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(&job_id)
+    .bind(&directory)
+    .bind(&output_dir)
+    .bind(&options_json)
+    .bind(max_files_per_minute.map(i64::from))
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to create conversion job: {}", e))?;
+    for file in &files {
+        sqlx::query("INSERT INTO conversion_job_files (job_id, path) VALUES (?, ?)")
+            .bind(&job_id)
+            .bind(file.to_string_lossy().as_ref())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to add '{}' to the job: {}", file.display(), e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to create conversion job: {}", e))?;
+
+    println!(
+        "📂 Conversion job {} created for {} files in {}",
+        job_id,
+        files.len(),
+        directory
+    );
+    spawn_worker(&app_handle, &jobs, &pool, &job_id).await?;
+    job_status(&pool, &jobs, &job_id).await
+}
+
+/// Continue an interrupted or cancelled conversion job
+///
+/// Only files not yet converted are processed (failed files are
+/// retried); files converted before, in this or another job, are taken
+/// from the conversion cache.
+#[tauri::command]
+pub async fn resume_conversion_job(
+    app_handle: AppHandle,
+    jobs: State<'_, ConversionJobs>,
+    job_id: String,
+) -> Result<ConversionJobStatus, String> {
+    if jobs.is_running(&job_id) {
+        return Err(format!("Conversion job '{}' is already running", job_id));
+    }
+    let pool = db::sqlite_pool(&app_handle).await?;
+
+    // Failed files get another attempt
+    sqlx::query(
+        "UPDATE conversion_job_files SET status = 'pending', error = NULL
+         WHERE job_id = ? AND status = 'failed'",
+    )
+    .bind(&job_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to reset failed files: {}", e))?;
+
+    spawn_worker(&app_handle, &jobs, &pool, &job_id).await?;
+    println!("▶️ Resumed conversion job {}", job_id);
+    job_status(&pool, &jobs, &job_id).await
+}
+
+/// Stop a conversion job after the file currently being converted
+///
+/// Converted files stay available and the job can be resumed later.
+/// A `convert_dxf_to_json` batch stops right away (its converter
+/// process is killed) and returns the files it completed.
+#[tauri::command]
+pub async fn cancel_conversion_job(
+    app_handle: AppHandle,
+    jobs: State<'_, ConversionJobs>,
+    job_id: String,
+) -> Result<ConversionJobStatus, String> {
+    let persisted = jobs.cancel(&job_id);
+    if persisted == Some(false) {
+        println!("⏹️ Cancelling DXF conversion {}", job_id);
+        return Ok(ConversionJobStatus {
+            job_id,
+            directory: String::new(),
+            status: "cancelling".to_string(),
+            files_done: 0,
+            files_failed: 0,
+            files_remaining: 0,
+            converted: Vec::new(),
+        });
+    }
+
+    let pool = db::sqlite_pool(&app_handle).await?;
+    if persisted.is_some() {
+        println!("⏹️ Cancelling conversion job {}", job_id);
+    } else {
+        // No worker (interrupted job): mark it cancelled right away
+        sqlx::query(
+            "UPDATE conversion_jobs SET status = 'cancelled', updated_at = datetime('now')
+             WHERE id = ? AND status = 'running'",
+        )
+        .bind(&job_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to cancel conversion job '{}': {}", job_id, e))?;
+    }
+    job_status(&pool, &jobs, &job_id).await
+}
+
+/// Progress and partial results of a conversion job
+#[tauri::command]
+pub async fn get_conversion_job(
+    app_handle: AppHandle,
+    jobs: State<'_, ConversionJobs>,
+    job_id: String,
+) -> Result<ConversionJobStatus, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    job_status(&pool, &jobs, &job_id).await
+}
+
+/// DXF files directly in `directory`, sorted by path
+fn list_dxf_files(directory: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = std::fs::read_dir(directory)
+        .map_err(|e| format!("Failed to read '{}': {}", directory.display(), e))?;
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("dxf"))
+        })
+        .collect();
+    files.sort();
+
+    if files.is_empty() {
+        return Err(format!("No DXF files found in '{}'", directory.display()));
+    }
+    Ok(files)
+}
+
+async fn load_job(pool: &SqlitePool, job_id: &str) -> Result<JobConfig, String> {
+    let row: Option<(String, String, Option<i64>)> = sqlx::query_as(
+        "SELECT output_dir, options, max_files_per_minute FROM conversion_jobs WHERE id = ?",
+    )
+    .bind(job_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to load conversion job '{}': {}", job_id, e))?;
+    let (output_dir, options_json, max_files_per_minute) =
+        row.ok_or_else(|| format!("Conversion job '{}' not found", job_id))?;
+
+    let options = serde_json::from_str(&options_json)
+        .map_err(|e| format!("Conversion job '{}' has invalid options: {}", job_id, e))?;
+    Ok(JobConfig {
+        job_id: job_id.to_string(),
+        output_dir: PathBuf::from(output_dir),
+        options,
+        options_json,
+        max_files_per_minute: max_files_per_minute.and_then(|n| u32::try_from(n).ok()),
+    })
+}
+
+async fn spawn_worker(
+    app_handle: &AppHandle,
+    jobs: &ConversionJobs,
+    pool: &SqlitePool,
+    job_id: &str,
+) -> Result<(), String> {
+    let job = load_job(pool, job_id).await?;
+    let exe_path = converter_exe_path(app_handle)?;
+    if !exe_path.exists() {
+        return Err(format!(
+            "dxf-converter.exe not found at: {}",
+            exe_path.display()
+        ));
+    }
+
+    let cancel = jobs.start(job_id)?;
+    set_job_status(pool, job_id, "running").await?;
+
+    let thread_app = app_handle.clone();
+    let thread_pool = pool.clone();
+    std::thread::Builder::new()
+        .name(format!("conversion-{}", job_id))
+        .spawn(move || {
+            let job_id = job.job_id.clone();
+            let result = run_worker(&thread_app, &thread_pool, &job, &exe_path, &cancel);
+            if let Err(e) = result {
+                println!("❌ Conversion job {} stopped: {}", job_id, e);
+            }
+            thread_app.state::<ConversionJobs>().finish(&job_id);
+        })
+        .map_err(|e| {
+            jobs.finish(job_id);
+            format!("Failed to start conversion thread: {}", e)
+        })?;
+    Ok(())
+}
+
+/// Convert the pending files of a job one after another
+///
+/// Runs on its own thread; the cancel flag is checked between files so
+/// the file in flight is always finished and recorded.
+fn run_worker(
+    app_handle: &AppHandle,
+    pool: &SqlitePool,
+    job: &JobConfig,
+    exe_path: &Path,
+    cancel: &AtomicBool,
+) -> Result<(), String> {
+    let pending: Vec<(String,)> = block_on(
+        sqlx::query_as(
+            "SELECT path FROM conversion_job_files
+             WHERE job_id = ? AND status = 'pending' ORDER BY path",
+        )
+        .bind(&job.job_id)
+        .fetch_all(pool),
+    )
+    .map_err(|e| format!("Failed to load pending files: {}", e))?;
+
+    let (mut done, mut failed, _) = block_on(file_counts(pool, &job.job_id))?;
+    let min_interval = job
+        .max_files_per_minute
+        .filter(|&limit| limit > 0)
+        .map(|limit| Duration::from_secs_f64(60.0 / limit as f64));
+
+    let started = Instant::now();
+    let mut last_start: Option<Instant> = None;
+    let total_pending = pending.len();
+    let mut processed = 0;
+    let mut status = "completed";
+
+    for (path,) in pending {
+        if cancel.load(Ordering::SeqCst) {
+            status = "cancelled";
+            break;
+        }
+        if let (Some(interval), Some(last)) = (min_interval, last_start) {
+            std::thread::sleep(interval.saturating_sub(last.elapsed()));
+        }
+        last_start = Some(Instant::now());
+
+        let result = convert_file(
+            pool,
+            exe_path,
+            &job.output_dir,
+            &job.options,
+            &job.options_json,
+            &path,
+        );
+        let (output_path, error) = match &result {
+            Ok(output_path) => (Some(output_path.clone()), None),
+            Err(e) => (None, Some(e.clone())),
+        };
+        block_on(
+            sqlx::query(
+                "UPDATE conversion_job_files
+                 SET status = ?, output_path = ?, error = ?, updated_at = datetime('now')
+                 WHERE job_id = ? AND path = ?",
+            )
+            .bind(if result.is_ok() { "done" } else { "failed" })
+            .bind(&output_path)
+            .bind(&error)
+            .bind(&job.job_id)
+            .bind(&path)
+            .execute(pool),
+        )
+        .map_err(|e| format!("Failed to record progress: {}", e))?;
+
+        if result.is_ok() {
+            done += 1;
+        } else {
+            failed += 1;
+        }
+        processed += 1;
+
+        let remaining = total_pending - processed;
+        let (files_per_minute, eta_secs) = throughput(processed, started.elapsed(), remaining);
+        app_handle.state::<EventBus>().emit(
+            app_handle,
+            CONVERSION_PROGRESS_EVENT,
+            &job.job_id,
+            &ConversionProgressEvent {
+                job_id: job.job_id.clone(),
+                status: "running".to_string(),
+                files_done: done,
+                files_failed: failed,
+                files_remaining: remaining,
+                files_per_minute,
+                eta_secs,
+                file: Some(path),
+                output_path,
+                error,
+            },
+        );
+    }
+
+    block_on(set_job_status(pool, &job.job_id, status))?;
+    let (files_per_minute, _) = throughput(processed, started.elapsed(), 0);
+    app_handle.state::<EventBus>().emit(
+        app_handle,
+        CONVERSION_PROGRESS_EVENT,
+        &job.job_id,
+        &ConversionProgressEvent {
+            job_id: job.job_id.clone(),
+            status: status.to_string(),
+            files_done: done,
+            files_failed: failed,
+            files_remaining: total_pending - processed,
+            files_per_minute,
+            eta_secs: None,
+            file: None,
+            output_path: None,
+            error: None,
+        },
+    );
+    println!(
+        "📂 Conversion job {} {}: {} done, {} failed ({:.1} files/min)",
+        job.job_id, status, done, failed, files_per_minute
+    );
+    Ok(())
+}
+
+/// Convert one file, or take its output from the conversion cache
+///
+/// Blocking. `options_json` is `options` serialized, part of the cache key.
+pub(crate) fn convert_file(
+    pool: &SqlitePool,
+    exe_path: &Path,
+    output_dir: &Path,
+    options: &ConversionOptions,
+    options_json: &str,
+    path: &str,
+) -> Result<String, String> {
+    let content = std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let key = cache_key(&content, options_json);
+
+    let cached: Option<(String, Option<String>)> = block_on(
+        sqlx::query_as(
+            "SELECT output_path, output_sha256 FROM conversion_cache WHERE cache_key = ?",
+        )
+        .bind(&key)
+        .fetch_optional(pool),
+    )
+    .map_err(|e| format!("Failed to read conversion cache: {}", e))?;
+    // A removed or changed output is converted again
+    if let Some((output_path, output_sha256)) = cached {
+        let check = check_file(Path::new(&output_path), output_sha256.as_deref())?;
+        if matches!(check, FileCheck::Intact(_)) {
+            return Ok(output_path);
+        }
+    }
+
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "part".to_string());
+    let output_path = output_dir
+        .join(format!("{}-{}.json", stem, &key[..12]))
+        .to_string_lossy()
+        .into_owned();
+
+    let input = [DxfFileInput {
+        path: path.to_string(),
+        quantity: 1,
+    }];
+    let cmd = converter_command(exe_path, &input, &output_path, options);
+    run_tool(Tool::DxfConverter, cmd, CONVERTER_TIME_LIMIT, None)
+        .map_err(|error| error.message)?
+        .ok_or("Converter run was cancelled")?
+        .check(Tool::DxfConverter)
+        .map_err(|error| error.message)?;
+    let output = std::fs::read(&output_path)
+        .map_err(|e| format!("Failed to read converted '{}': {}", output_path, e))?;
+
+    block_on(
+        sqlx::query(
+            "INSERT OR REPLACE INTO conversion_cache
+                (cache_key, source_path, output_path, output_sha256)
+             VALUES (?, ?, ?, ?)",
+        )
+        .bind(&key)
+        .bind(path)
+        .bind(&output_path)
+        .bind(artifact_sha256(&output))
+        .execute(pool),
+    )
+    .map_err(|e| format!("Failed to update conversion cache: {}", e))?;
+    Ok(output_path)
+}
+
+/// Cache key of a file: its content and the options it is converted with
+fn cache_key(content: &[u8], options_json: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content);
+    hasher.update(options_json.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Files per minute so far and the estimated seconds left
+fn throughput(processed: usize, elapsed: Duration, remaining: usize) -> (f64, Option<f64>) {
+    let minutes = elapsed.as_secs_f64() / 60.0;
+    if processed == 0 || minutes <= 0.0 {
+        return (0.0, None);
+    }
+
+    let files_per_minute = processed as f64 / minutes;
+    (
+        files_per_minute,
+        Some(remaining as f64 / files_per_minute * 60.0),
+    )
+}
+
+async fn set_job_status(pool: &SqlitePool, job_id: &str, status: &str) -> Result<(), String> {
+    let result = sqlx::query(
+        "UPDATE conversion_jobs SET status = ?, updated_at = datetime('now') WHERE id = ?",
+    )
+    .bind(status)
+    .bind(job_id)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to update conversion job '{}': {}", job_id, e))?;
+
+    if result.rows_affected() == 0 {
+        return Err(format!("Conversion job '{}' not found", job_id));
+    }
+    Ok(())
+}
+
+/// Done, failed and pending file counts of a job
+async fn file_counts(pool: &SqlitePool, job_id: &str) -> Result<(usize, usize, usize), String> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT status, COUNT(*) FROM conversion_job_files WHERE job_id = ? GROUP BY status",
+    )
+    .bind(job_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to count job files: {}", e))?;
+
+    let count = |wanted: &str| {
+        rows.iter()
+            .find(|(status, _)| status == wanted)
+            .map_or(0, |(_, n)| *n as usize)
+    };
+    Ok((count("done"), count("failed"), count("pending")))
+}
+
+pub(crate) async fn job_status(
+    pool: &SqlitePool,
+    jobs: &ConversionJobs,
+    job_id: &str,
+) -> Result<ConversionJobStatus, String> {
+    let row: Option<(String, String)> =
+        sqlx::query_as("SELECT directory, status FROM conversion_jobs WHERE id = ?")
+            .bind(job_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Failed to load conversion job '{}': {}", job_id, e))?;
+    let (directory, mut status) =
+        row.ok_or_else(|| format!("Conversion job '{}' not found", job_id))?;
+    if status == "running" && !jobs.is_running(job_id) {
+        status = "interrupted".to_string();
+    }
+
+    let (files_done, files_failed, files_remaining) = file_counts(pool, job_id).await?;
+    let converted: Vec<(String, String)> = sqlx::query_as(
+        "SELECT path, output_path FROM conversion_job_files
+         WHERE job_id = ? AND status = 'done' ORDER BY path",
+    )
+    .bind(job_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load converted files: {}", e))?;
+
+    Ok(ConversionJobStatus {
+        job_id: job_id.to_string(),
+        directory,
+        status,
+        files_done,
+        files_failed,
+        files_remaining,
+        converted: converted
+            .into_iter()
+            .map(|(path, output_path)| ConvertedFile { path, output_path })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_dxf_files_ignores_other_files() {
+        let dir = std::env::temp_dir().join(format!("conversion_job_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["b.dxf", "a.DXF", "notes.txt"] {
+            std::fs::write(dir.join(name), "0\nEOF\n").unwrap();
+        }
+
+        let files = list_dxf_files(&dir).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        let names: Vec<_> = files
+            .iter()
+            .map(|file| file.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, vec!["a.DXF", "b.dxf"]);
+    }
+
+    #[test]
+    fn test_cache_key_depends_on_options() {
+        let a = cache_key(b"0\nEOF\n", r#"{"arcSegments":32}"#);
+        let b = cache_key(b"0\nEOF\n", r#"{"arcSegments":64}"#);
+        assert_ne!(a, b);
+        assert_eq!(a, cache_key(b"0\nEOF\n", r#"{"arcSegments":32}"#));
+    }
+
+    #[test]
+    fn test_throughput_and_eta() {
+        let (files_per_minute, eta) = throughput(10, Duration::from_secs(30), 40);
+        assert!((files_per_minute - 20.0).abs() < 1e-9);
+        assert!((eta.unwrap() - 120.0).abs() < 1e-9);
+
+        assert_eq!(throughput(0, Duration::from_secs(30), 40), (0.0, None));
+    }
+}
@@ -0,0 +1,669 @@
+//! Cutting technology per machine, material and thickness
+//!
+//! A cutting profile holds what a machine needs to cut one material at a
+//! range of thicknesses: kerf and minimum part separation for nesting,
+//! feed rate and pierce time for the cut time, and the prices per meter
+//! and per pierce for the quote. Nesting and pricing both look the row up
+//! here, so a changed kerf or price cannot leave the two out of step.
+//!
+//! Ranges include their lower bound and exclude the upper one; a profile
+//! whose bounds are equal matches that exact thickness only. Lookup
+//! precedence, first match wins:
+//! 1. profile of the machine, exact thickness
+//! 2. profile of the machine, thickness range
+//! 3. profile for any machine (no `machine_id`), exact thickness
+//! 4. profile for any machine, thickness range
+//!
+//! Ranges of the same machine and material may not overlap; an exact
+//! profile inside a range is allowed and takes precedence over it.
+//! Unset values fall back to the material's own columns.
+
+use crate::db;
+use crate::nesting_engine::{ConfigSource, NestingInput};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+/// Thicknesses closer than this (mm) are equal
+const THICKNESS_TOLERANCE: f64 = 1e-6;
+
+/// Editable columns of a cutting profile
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CuttingProfileFields {
+    /// None = any machine
+    pub machine_id: Option<String>,
+    pub material_id: String,
+    /// Lower bound of the thickness range in mm (inclusive)
+    pub thickness_min: f64,
+    /// Upper bound in mm (exclusive), equal to `thickness_min` for an exact thickness
+    pub thickness_max: f64,
+    /// Kerf width in mm
+    pub kerf: Option<f64>,
+    /// Separation between parts in mm when the nesting input sets none
+    pub min_separation: Option<f64>,
+    /// Cutting speed in mm/min
+    pub feed_rate: Option<f64>,
+    /// Seconds per pierce
+    pub pierce_time: Option<f64>,
+    pub price_per_meter: Option<f64>,
+    pub price_per_pierce: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CuttingProfile {
+    pub id: i64,
+    #[serde(flatten)]
+    pub fields: CuttingProfileFields,
+}
+
+/// How a profile matched the requested thickness
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileMatch {
+    Exact,
+    Range,
+}
+
+/// Profile used for a machine, material and thickness
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ResolvedCuttingProfile {
+    pub profile: CuttingProfile,
+    pub matched_by: ProfileMatch,
+    /// Thickness looked up in mm (the material's unless given)
+    pub thickness: f64,
+    /// The profile belongs to the requested machine, not to any machine
+    pub machine_specific: bool,
+}
+
+impl CuttingProfileFields {
+    fn is_exact(&self) -> bool {
+        self.thickness_max - self.thickness_min <= THICKNESS_TOLERANCE
+    }
+
+    /// How the profile matches `thickness`, if it does
+    fn matches(&self, thickness: f64) -> Option<ProfileMatch> {
+        if self.is_exact() {
+            ((thickness - self.thickness_min).abs() <= THICKNESS_TOLERANCE)
+                .then_some(ProfileMatch::Exact)
+        } else {
+            (thickness >= self.thickness_min - THICKNESS_TOLERANCE
+                && thickness < self.thickness_max - THICKNESS_TOLERANCE)
+                .then_some(ProfileMatch::Range)
+        }
+    }
+
+    /// True if both profiles would match the same thickness with the same precedence
+    fn overlaps(&self, other: &CuttingProfileFields) -> bool {
+        if self.machine_id != other.machine_id || self.material_id != other.material_id {
+            return false;
+        }
+        match (self.is_exact(), other.is_exact()) {
+            (true, true) => (self.thickness_min - other.thickness_min).abs() <= THICKNESS_TOLERANCE,
+            (false, false) => {
+                self.thickness_min < other.thickness_max - THICKNESS_TOLERANCE
+                    && other.thickness_min < self.thickness_max - THICKNESS_TOLERANCE
+            }
+            // An exact thickness inside a range refines it
+            _ => false,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.material_id.trim().is_empty() {
+            return Err("Cutting profile needs a material".to_string());
+        }
+        if self.thickness_min.is_nan()
+            || self.thickness_min <= 0.0
+            || !self.thickness_max.is_finite()
+        {
+            return Err(format!(
+                "Cutting profile thickness must be positive, got {}",
+                self.thickness_min
+            ));
+        }
+        if self.thickness_max < self.thickness_min {
+            return Err(format!(
+                "Cutting profile thickness range {}-{}mm is reversed",
+                self.thickness_min, self.thickness_max
+            ));
+        }
+        for (name, value) in [
+            ("kerf", self.kerf),
+            ("min_separation", self.min_separation),
+            ("pierce_time", self.pierce_time),
+            ("price_per_meter", self.price_per_meter),
+            ("price_per_pierce", self.price_per_pierce),
+        ] {
+            if let Some(value) = value.filter(|value| !value.is_finite() || *value < 0.0) {
+                return Err(format!(
+                    "Cutting profile {} must be >= 0, got {}",
+                    name, value
+                ));
+            }
+        }
+        if let Some(feed_rate) = self
+            .feed_rate
+            .filter(|rate| !rate.is_finite() || *rate <= 0.0)
+        {
+            return Err(format!(
+                "Cutting profile feed_rate must be positive, got {}",
+                feed_rate
+            ));
+        }
+        Ok(())
+    }
+
+    fn describe_range(&self) -> String {
+        if self.is_exact() {
+            format!("{}mm", self.thickness_min)
+        } else {
+            format!("{}-{}mm", self.thickness_min, self.thickness_max)
+        }
+    }
+}
+
+/// Fail if `fields` overlaps one of `existing` other than profile `id`
+fn check_overlaps(
+    fields: &CuttingProfileFields,
+    id: Option<i64>,
+    existing: &[CuttingProfile],
+) -> Result<(), String> {
+    match existing
+        .iter()
+        .filter(|profile| Some(profile.id) != id)
+        .find(|profile| profile.fields.overlaps(fields))
+    {
+        Some(profile) => Err(format!(
+            "Thickness {} overlaps cutting profile {} ({}) of the same machine and material",
+            fields.describe_range(),
+            profile.id,
+            profile.fields.describe_range()
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Best profile of `profiles` for `machine_id` and `thickness`
+///
+/// `profiles` are those of one material. Without a machine only the
+/// profiles for any machine are considered.
+pub fn match_cutting_profile(
+    profiles: &[CuttingProfile],
+    machine_id: Option<&str>,
+    thickness: f64,
+) -> Option<ResolvedCuttingProfile> {
+    profiles
+        .iter()
+        .filter_map(|profile| {
+            let machine_specific = match profile.fields.machine_id.as_deref() {
+                None => false,
+                Some(id) if Some(id) == machine_id => true,
+                Some(_) => return None,
+            };
+            let matched_by = profile.fields.matches(thickness)?;
+            Some((machine_specific, matched_by, profile))
+        })
+        .min_by_key(|(machine_specific, matched_by, profile)| {
+            (
+                !machine_specific,
+                *matched_by == ProfileMatch::Range,
+                profile.id,
+            )
+        })
+        .map(
+            |(machine_specific, matched_by, profile)| ResolvedCuttingProfile {
+                profile: profile.clone(),
+                matched_by,
+                thickness,
+                machine_specific,
+            },
+        )
+}
+
+type ProfileRow = (
+    i64,
+    Option<String>,
+    String,
+    f64,
+    f64,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+);
+
+const PROFILE_COLUMNS: &str = "id, machine_id, material_id, thickness_min, thickness_max, kerf,
+     min_separation, feed_rate, pierce_time, price_per_meter, price_per_pierce";
+
+fn profile_from_row(row: ProfileRow) -> CuttingProfile {
+    let (
+        id,
+        machine_id,
+        material_id,
+        thickness_min,
+        thickness_max,
+        kerf,
+        min_separation,
+        feed_rate,
+        pierce_time,
+        price_per_meter,
+        price_per_pierce,
+    ) = row;
+    CuttingProfile {
+        id,
+        fields: CuttingProfileFields {
+            machine_id,
+            material_id,
+            thickness_min,
+            thickness_max,
+            kerf,
+            min_separation,
+            feed_rate,
+            pierce_time,
+            price_per_meter,
+            price_per_pierce,
+        },
+    }
+}
+
+/// Profiles of a material (all materials when None), by material and thickness
+async fn load_profiles(
+    pool: &SqlitePool,
+    material_id: Option<&str>,
+) -> Result<Vec<CuttingProfile>, String> {
+    let rows: Vec<ProfileRow> = sqlx::query_as(&format!(
+        "SELECT {} FROM cutting_profiles
+         WHERE ?1 IS NULL OR material_id = ?1
+         ORDER BY material_id, machine_id, thickness_min, id",
+        PROFILE_COLUMNS
+    ))
+    .bind(material_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load cutting profiles: {}", e))?;
+    Ok(rows.into_iter().map(profile_from_row).collect())
+}
+
+async fn load_profile(pool: &SqlitePool, id: i64) -> Result<CuttingProfile, String> {
+    let row: Option<ProfileRow> = sqlx::query_as(&format!(
+        "SELECT {} FROM cutting_profiles WHERE id = ?",
+        PROFILE_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to load cutting profile {}: {}", id, e))?;
+    row.map(profile_from_row)
+        .ok_or_else(|| format!("Cutting profile {} not found", id))
+}
+
+/// Insert (`id` None) or update a profile after checking it against the others
+async fn save_profile(
+    pool: &SqlitePool,
+    id: Option<i64>,
+    fields: &CuttingProfileFields,
+) -> Result<CuttingProfile, String> {
+    fields.validate()?;
+    let db_err = |e: sqlx::Error| format!("Failed to save cutting profile: {}", e);
+
+    // Checked and written in one transaction so two saves cannot both pass
+    let mut tx = pool.begin().await.map_err(db_err)?;
+    let rows: Vec<ProfileRow> = sqlx::query_as(&format!(
+        "SELECT {} FROM cutting_profiles WHERE material_id = ?",
+        PROFILE_COLUMNS
+    ))
+    .bind(&fields.material_id)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_err)?;
+    let existing: Vec<CuttingProfile> = rows.into_iter().map(profile_from_row).collect();
+    check_overlaps(fields, id, &existing)?;
+
+    // The ID goes last, so both statements take the same binds
+    let query = match id {
+        Some(_) => {
+            "UPDATE cutting_profiles SET machine_id = ?1, material_id = ?2, thickness_min = ?3,
+             thickness_max = ?4, kerf = ?5, min_separation = ?6, feed_rate = ?7,
+             pierce_time = ?8, price_per_meter = ?9, price_per_pierce = ?10,
+             updated_at = datetime('now')
+             WHERE id = ?11"
+        }
+        None => {
+            "INSERT INTO cutting_profiles (id, machine_id, material_id, thickness_min,
+             thickness_max, kerf, min_separation, feed_rate, pierce_time, price_per_meter,
+             price_per_pierce)
+             VALUES (?11, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"
+        }
+    };
+    let result = sqlx::query(query)
+        .bind(&fields.machine_id)
+        .bind(&fields.material_id)
+        .bind(fields.thickness_min)
+        .bind(fields.thickness_max)
+        .bind(fields.kerf)
+        .bind(fields.min_separation)
+        .bind(fields.feed_rate)
+        .bind(fields.pierce_time)
+        .bind(fields.price_per_meter)
+        .bind(fields.price_per_pierce)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+    let id = match id {
+        Some(id) if result.rows_affected() == 0 => {
+            return Err(format!("Cutting profile {} not found", id))
+        }
+        Some(id) => id,
+        None => result.last_insert_rowid(),
+    };
+    tx.commit().await.map_err(db_err)?;
+
+    println!(
+        "💾 Saved cutting profile {} for material {} at {}",
+        id,
+        fields.material_id,
+        fields.describe_range()
+    );
+    Ok(CuttingProfile {
+        id,
+        fields: fields.clone(),
+    })
+}
+
+/// Profile for a machine, material and thickness
+///
+/// Without `thickness` the material's own thickness is used.
+pub async fn find_cutting_profile(
+    pool: &SqlitePool,
+    machine_id: Option<&str>,
+    material_id: &str,
+    thickness: Option<f64>,
+) -> Result<Option<ResolvedCuttingProfile>, String> {
+    let thickness = match thickness {
+        Some(thickness) => thickness,
+        None => {
+            let row: Option<(f64,)> =
+                sqlx::query_as("SELECT thickness FROM material_stock WHERE id = ?")
+                    .bind(material_id)
+                    .fetch_optional(pool)
+                    .await
+                    .map_err(|e| format!("Failed to load material '{}': {}", material_id, e))?;
+            row.ok_or_else(|| format!("Material '{}' not found", material_id))?
+                .0
+        }
+    };
+    let profiles = load_profiles(pool, Some(material_id)).await?;
+    Ok(match_cutting_profile(&profiles, machine_id, thickness))
+}
+
+/// List the cutting profiles of a material, or of all materials
+#[tauri::command]
+pub async fn list_cutting_profiles(
+    app_handle: tauri::AppHandle,
+    material_id: Option<String>,
+) -> Result<Vec<CuttingProfile>, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    load_profiles(&pool, material_id.as_deref()).await
+}
+
+/// Add a cutting profile; fails if its range overlaps another of the same machine and material
+#[tauri::command]
+pub async fn create_cutting_profile(
+    app_handle: tauri::AppHandle,
+    profile: CuttingProfileFields,
+) -> Result<CuttingProfile, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    save_profile(&pool, None, &profile).await
+}
+
+/// Replace the columns of a cutting profile
+///
+/// Stored nesting results keep the separation they were computed with,
+/// so editing a profile only affects future runs and prices.
+#[tauri::command]
+pub async fn update_cutting_profile(
+    app_handle: tauri::AppHandle,
+    id: i64,
+    profile: CuttingProfileFields,
+) -> Result<CuttingProfile, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    save_profile(&pool, Some(id), &profile).await
+}
+
+/// Delete a cutting profile, false if it did not exist
+#[tauri::command]
+pub async fn delete_cutting_profile(app_handle: tauri::AppHandle, id: i64) -> Result<bool, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let result = sqlx::query("DELETE FROM cutting_profiles WHERE id = ?")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to delete cutting profile {}: {}", id, e))?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Cutting profile nesting and pricing use for a machine, material and thickness
+///
+/// Returns None when no profile matches; the material's own values apply then.
+#[tauri::command]
+pub async fn resolve_cutting_profile(
+    app_handle: tauri::AppHandle,
+    machine_id: Option<String>,
+    material_id: String,
+    thickness: Option<f64>,
+) -> Result<Option<ResolvedCuttingProfile>, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    find_cutting_profile(&pool, machine_id.as_deref(), &material_id, thickness).await
+}
+
+/// Take the separation and kerf of a nesting input from its cutting profile
+///
+/// Values set in the input win; unset profile values leave the material's
+/// (`resolve_material_min_web`) and the engine defaults in charge.
+pub async fn resolve_profile_separation(
+    app_handle: &tauri::AppHandle,
+    machine_id: Option<&str>,
+    material_id: &str,
+    input: &mut NestingInput,
+) -> Result<(), String> {
+    let pool = db::sqlite_pool(app_handle).await?;
+    let Some(resolved) = find_cutting_profile(&pool, machine_id, material_id, None).await? else {
+        return Ok(());
+    };
+    println!(
+        "🔪 Cutting profile {} ({:?} match at {}mm) for material {}",
+        resolved.profile.id, resolved.matched_by, resolved.thickness, material_id
+    );
+
+    let profile = &resolved.profile.fields;
+    input.separation = input.config_sources.pick(
+        "separation",
+        &[
+            (ConfigSource::Input, input.separation),
+            (ConfigSource::CuttingProfile, profile.min_separation),
+        ],
+    );
+    input.kerf = input.config_sources.pick(
+        "kerf",
+        &[
+            (ConfigSource::Input, input.kerf),
+            (ConfigSource::CuttingProfile, profile.kerf),
+        ],
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{migrated_pool, test_dir};
+    use tauri::async_runtime::block_on;
+
+    fn fields(machine_id: Option<&str>, min: f64, max: f64, price: f64) -> CuttingProfileFields {
+        CuttingProfileFields {
+            machine_id: machine_id.map(str::to_string),
+            material_id: "steel-3".to_string(),
+            thickness_min: min,
+            thickness_max: max,
+            price_per_meter: Some(price),
+            ..Default::default()
+        }
+    }
+
+    fn profile(id: i64, fields: CuttingProfileFields) -> CuttingProfile {
+        CuttingProfile { id, fields }
+    }
+
+    fn matched(
+        profiles: &[CuttingProfile],
+        machine_id: Option<&str>,
+        thickness: f64,
+    ) -> Option<(i64, ProfileMatch, bool)> {
+        match_cutting_profile(profiles, machine_id, thickness).map(|resolved| {
+            (
+                resolved.profile.id,
+                resolved.matched_by,
+                resolved.machine_specific,
+            )
+        })
+    }
+
+    #[test]
+    fn test_lookup_precedence() {
+        let profiles = vec![
+            profile(1, fields(None, 1.0, 6.0, 1.0)),
+            profile(2, fields(None, 3.0, 3.0, 2.0)),
+            profile(3, fields(Some("laser"), 2.0, 4.0, 3.0)),
+            profile(4, fields(Some("laser"), 3.0, 3.0, 4.0)),
+            profile(5, fields(Some("plasma"), 1.0, 10.0, 5.0)),
+        ];
+
+        // Machine and exact thickness before machine and range
+        assert_eq!(
+            matched(&profiles, Some("laser"), 3.0),
+            Some((4, ProfileMatch::Exact, true))
+        );
+        assert_eq!(
+            matched(&profiles, Some("laser"), 2.5),
+            Some((3, ProfileMatch::Range, true))
+        );
+        // Any machine once the machine's ranges end, exact before range
+        assert_eq!(
+            matched(&profiles, Some("laser"), 5.0),
+            Some((1, ProfileMatch::Range, false))
+        );
+        assert_eq!(
+            matched(&profiles, None, 3.0),
+            Some((2, ProfileMatch::Exact, false))
+        );
+        // A machine profile's range beats an exact profile for any machine
+        assert_eq!(
+            matched(&profiles, Some("plasma"), 3.0),
+            Some((5, ProfileMatch::Range, true))
+        );
+        // Upper bounds are exclusive
+        assert_eq!(
+            matched(&profiles, Some("laser"), 4.0),
+            Some((1, ProfileMatch::Range, false))
+        );
+        assert_eq!(matched(&profiles, None, 6.0), None);
+        assert_eq!(matched(&profiles, Some("waterjet"), 0.5), None);
+    }
+
+    #[test]
+    fn test_overlapping_ranges_are_rejected() {
+        let existing = vec![
+            profile(1, fields(Some("laser"), 1.0, 4.0, 1.0)),
+            profile(2, fields(Some("laser"), 6.0, 6.0, 1.0)),
+        ];
+
+        let error =
+            check_overlaps(&fields(Some("laser"), 3.0, 5.0, 1.0), None, &existing).unwrap_err();
+        assert!(error.contains("overlaps cutting profile 1"), "{}", error);
+        assert!(check_overlaps(&fields(Some("laser"), 6.0, 6.0, 1.0), None, &existing).is_err());
+
+        // Adjacent ranges, exact thicknesses inside ranges and other machines are fine
+        assert!(check_overlaps(&fields(Some("laser"), 4.0, 8.0, 1.0), None, &existing).is_ok());
+        assert!(check_overlaps(&fields(Some("laser"), 2.0, 2.0, 1.0), None, &existing).is_ok());
+        assert!(check_overlaps(&fields(None, 1.0, 4.0, 1.0), None, &existing).is_ok());
+        // A profile does not overlap its own old range
+        assert!(check_overlaps(&fields(Some("laser"), 0.5, 3.0, 1.0), Some(1), &existing).is_ok());
+    }
+
+    #[test]
+    fn test_saved_profiles_resolve_by_material_thickness() {
+        let dir = test_dir("cutting-profiles", "resolve");
+        block_on(async {
+            let pool = migrated_pool(&dir).await;
+            sqlx::raw_sql(
+                "INSERT INTO machines (id, name, hourly_rate)
+                 VALUES ('laser', 'Laser', 75.0), ('plasma', 'Plasma', 60.0);
+                 INSERT INTO material_stock
+                   (id, name, grade, thickness, sheet_width, sheet_max_length, price_per_kg, density)
+                 VALUES ('steel-3', 'Steel', 'S235', 3.0, 1500, 3000, 2.5, 7850),
+                        ('steel-5', 'Steel', 'S235', 5.0, 1500, 3000, 2.5, 7850);",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            let range = save_profile(&pool, None, &fields(Some("laser"), 2.0, 4.0, 1.5))
+                .await
+                .unwrap();
+            let exact = save_profile(&pool, None, &fields(Some("laser"), 3.0, 3.0, 2.5))
+                .await
+                .unwrap();
+            assert!(
+                save_profile(&pool, None, &fields(Some("laser"), 3.5, 5.0, 1.0))
+                    .await
+                    .is_err()
+            );
+            // Widening one range into another fails, the stored row stays
+            assert!(
+                save_profile(&pool, Some(exact.id), &fields(Some("laser"), 1.0, 3.0, 1.0))
+                    .await
+                    .is_err()
+            );
+
+            // steel-3 is 3 mm thick
+            let resolved = find_cutting_profile(&pool, Some("laser"), "steel-3", None)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(resolved.profile, exact);
+            assert_eq!(resolved.matched_by, ProfileMatch::Exact);
+            assert_eq!(resolved.thickness, 3.0);
+
+            let resolved = find_cutting_profile(&pool, Some("laser"), "steel-3", Some(2.0))
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(resolved.profile, range);
+            assert_eq!(resolved.matched_by, ProfileMatch::Range);
+
+            assert_eq!(
+                find_cutting_profile(&pool, Some("plasma"), "steel-3", None)
+                    .await
+                    .unwrap(),
+                None
+            );
+            assert!(find_cutting_profile(&pool, None, "copper", None)
+                .await
+                .is_err());
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_invalid_profiles_are_rejected() {
+        assert!(fields(None, 0.0, 1.0, 1.0).validate().is_err());
+        assert!(fields(None, 3.0, 2.0, 1.0).validate().is_err());
+        let mut negative = fields(None, 1.0, 2.0, 1.0);
+        negative.kerf = Some(-0.2);
+        assert!(negative.validate().unwrap_err().contains("kerf"));
+        let mut stopped = fields(None, 1.0, 2.0, 1.0);
+        stopped.feed_rate = Some(0.0);
+        assert!(stopped.validate().is_err());
+    }
+}
@@ -0,0 +1,468 @@
+//! Read-only HTTP endpoint for shop dashboards
+//!
+//! Wall-mounted dashboards cannot use Tauri IPC, so with the
+//! `dashboard_server_enabled` setting the app serves a few JSON
+//! endpoints on `dashboard_server_port`:
+//!
+//! - `GET /jobs`: unified job statuses, as `list_jobs`
+//! - `GET /metrics?group_by=day&from=..&to=..`: nesting run aggregates,
+//!   as `get_nesting_metrics`
+//! - `GET /quote/{id}/summary`: totals and nesting state of a quote
+//!
+//! Every request needs the `dashboard_server_token` setting as a bearer
+//! token (`Authorization: Bearer <token>`); the server does not start
+//! without one. `dashboard_server_bind` = `lan` listens on all
+//! interfaces, anything else on localhost only. Settings are read at
+//! startup. A port that is taken is logged and the app starts without
+//! the server.
+
+use crate::commands::conversion_jobs::ConversionJobs;
+use crate::commands::job_status::{self, JobRegistry};
+use crate::commands::migrations;
+use crate::commands::nesting_metrics::{self, MetricsGroupBy, MetricsRange};
+use crate::db;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use tauri::{AppHandle, Manager};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const ENABLED_SETTING: &str = "dashboard_server_enabled";
+const PORT_SETTING: &str = "dashboard_server_port";
+const BIND_SETTING: &str = "dashboard_server_bind";
+const TOKEN_SETTING: &str = "dashboard_server_token";
+
+/// Port used when the setting is missing or not a number
+pub const DEFAULT_PORT: u16 = 8787;
+
+/// Server settings, read once at startup
+#[derive(Debug, Clone, PartialEq)]
+pub struct DashboardSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Listen on all interfaces instead of localhost only
+    pub lan: bool,
+    pub token: String,
+}
+
+impl DashboardSettings {
+    /// Settings from `(key, value)` rows of the settings table
+    pub fn from_rows(rows: &[(String, String)]) -> Self {
+        let value = |key: &str| {
+            rows.iter()
+                .find(|(row_key, _)| row_key == key)
+                .map(|(_, value)| value.trim())
+                .unwrap_or_default()
+        };
+        Self {
+            enabled: value(ENABLED_SETTING) == "true",
+            port: value(PORT_SETTING).parse().unwrap_or(DEFAULT_PORT),
+            lan: value(BIND_SETTING) == "lan",
+            token: value(TOKEN_SETTING).to_string(),
+        }
+    }
+
+    fn address(&self) -> String {
+        let host = if self.lan { "0.0.0.0" } else { "127.0.0.1" };
+        format!("{}:{}", host, self.port)
+    }
+}
+
+/// Endpoints of the server
+#[derive(Debug, PartialEq)]
+pub enum Route {
+    Jobs,
+    Metrics {
+        group_by: String,
+        range: (Option<String>, Option<String>),
+    },
+    QuoteSummary(String),
+}
+
+/// Route of a request URL, None for unknown paths
+pub fn route(url: &str) -> Option<Route> {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match segments.as_slice() {
+        ["jobs"] => Some(Route::Jobs),
+        ["metrics"] => {
+            let params = query_params(query);
+            let param = |key: &str| {
+                params
+                    .iter()
+                    .find(|(name, _)| name == key)
+                    .map(|(_, value)| value.clone())
+            };
+            Some(Route::Metrics {
+                group_by: param("group_by").unwrap_or_else(|| "day".to_string()),
+                range: (param("from"), param("to")),
+            })
+        }
+        ["quote", id, "summary"] => Some(Route::QuoteSummary(percent_decode(id))),
+        _ => None,
+    }
+}
+
+/// True if a header carries the token as `Authorization: Bearer <token>`
+pub fn is_authorized<'a>(
+    mut headers: impl Iterator<Item = (&'a str, &'a str)>,
+    token: &str,
+) -> bool {
+    !token.is_empty()
+        && headers.any(|(name, value)| {
+            name.eq_ignore_ascii_case("authorization")
+                && value
+                    .strip_prefix("Bearer ")
+                    .is_some_and(|sent| tokens_match(sent.trim(), token))
+        })
+}
+
+/// Compares two tokens in time that depends only on their lengths, so a
+/// client cannot find the token byte by byte from response timings
+fn tokens_match(sent: &str, token: &str) -> bool {
+    sent.len() == token.len()
+        && sent
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Totals and nesting state of a quote
+#[derive(Serialize, Debug)]
+pub struct QuoteSummary {
+    pub id: String,
+    pub quote_number: String,
+    pub status: Option<String>,
+    pub client: Option<String>,
+    pub subtotal: Option<f64>,
+    pub total: Option<f64>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    /// Stored nesting results of the quote
+    pub nesting_results: i64,
+    /// Utilization of the newest nesting result (0.0 - 1.0)
+    pub latest_utilization: Option<f64>,
+}
+
+/// Summary of a quote, None if it does not exist
+pub async fn quote_summary(pool: &SqlitePool, id: &str) -> Result<Option<QuoteSummary>, String> {
+    type Row = (
+        String,
+        String,
+        Option<String>,
+        Option<String>,
+        Option<f64>,
+        Option<f64>,
+        Option<String>,
+        Option<String>,
+        i64,
+        Option<f64>,
+    );
+    let row: Option<Row> = sqlx::query_as(
+        "SELECT q.id, q.quote_number, q.status, c.company_name, q.subtotal, q.total,
+                q.created_at, q.updated_at,
+                (SELECT COUNT(*) FROM nesting_results r WHERE r.quote_id = q.id),
+                (SELECT r.density FROM nesting_results r WHERE r.quote_id = q.id
+                 ORDER BY r.created_at DESC, r.id DESC LIMIT 1)
+         FROM quotes q LEFT JOIN clients c ON c.id = q.client_id
+         WHERE q.id = ?",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to load quote: {}", e))?;
+
+    Ok(row.map(
+        |(
+            id,
+            quote_number,
+            status,
+            client,
+            subtotal,
+            total,
+            created_at,
+            updated_at,
+            nesting_results,
+            latest_utilization,
+        )| QuoteSummary {
+            id,
+            quote_number,
+            status,
+            client,
+            subtotal,
+            total,
+            created_at,
+            updated_at,
+            nesting_results,
+            latest_utilization,
+        },
+    ))
+}
+
+/// The running server, stopped on app exit
+#[derive(Default)]
+pub struct DashboardServer {
+    running: Mutex<Option<(Arc<Server>, JoinHandle<()>)>>,
+}
+
+impl DashboardServer {
+    /// Stop accepting requests and wait for the one in progress
+    pub fn stop(&self) {
+        let running = self
+            .running
+            .lock()
+            .ok()
+            .and_then(|mut running| running.take());
+        if let Some((server, thread)) = running {
+            server.unblock();
+            let _ = thread.join();
+            println!("📊 Dashboard server stopped");
+        }
+    }
+}
+
+/// Start the server when enabled in the settings
+///
+/// Called from the app setup after the migrations ran. Never fails: a
+/// missing token or a taken port is logged and the app runs without it.
+pub fn start_dashboard_server(app_handle: &AppHandle) {
+    let settings = tauri::async_runtime::block_on(async {
+        let db_path = db::db_file_path(app_handle)?;
+        if !db_path.exists() {
+            return Ok(None);
+        }
+        let pool = migrations::open_pool(&db_path, false).await?;
+        let rows = sqlx::query_as("SELECT key, value FROM settings WHERE key IN (?, ?, ?, ?)")
+            .bind(ENABLED_SETTING)
+            .bind(PORT_SETTING)
+            .bind(BIND_SETTING)
+            .bind(TOKEN_SETTING)
+            .fetch_all(&pool)
+            .await
+            .map_err(|e| format!("Failed to load dashboard server settings: {}", e));
+        pool.close().await;
+        rows.map(|rows: Vec<(String, String)>| Some(DashboardSettings::from_rows(&rows)))
+    });
+    let settings = match settings {
+        Ok(Some(settings)) if settings.enabled => settings,
+        Ok(_) => return,
+        Err(e) => {
+            log::warn!("Dashboard server not started: {}", e);
+            return;
+        }
+    };
+    if settings.token.is_empty() {
+        log::warn!(
+            "Dashboard server not started: set {} to require a token",
+            TOKEN_SETTING
+        );
+        return;
+    }
+
+    let address = settings.address();
+    let server = match Server::http(&address) {
+        Ok(server) => Arc::new(server),
+        Err(e) => {
+            log::warn!(
+                "Dashboard server not started, {} unavailable: {}",
+                address,
+                e
+            );
+            return;
+        }
+    };
+    let thread_server = server.clone();
+    let thread_app_handle = app_handle.clone();
+    let token = settings.token;
+    let thread = std::thread::spawn(move || {
+        for request in thread_server.incoming_requests() {
+            handle_request(&thread_app_handle, &token, request);
+        }
+    });
+    if let Ok(mut running) = app_handle.state::<DashboardServer>().running.lock() {
+        *running = Some((server, thread));
+    }
+    println!("📊 Dashboard server listening on http://{}", address);
+}
+
+fn handle_request(app_handle: &AppHandle, token: &str, request: Request) {
+    let headers = request
+        .headers()
+        .iter()
+        .map(|header| (header.field.as_str().as_str(), header.value.as_str()));
+    let result = if *request.method() != Method::Get {
+        Err((405, "Only GET is supported".to_string()))
+    } else if !is_authorized(headers, token) {
+        Err((401, "Missing or wrong bearer token".to_string()))
+    } else {
+        match route(request.url()) {
+            Some(route) => tauri::async_runtime::block_on(respond(app_handle, route)),
+            None => Err((404, format!("No endpoint {}", request.url()))),
+        }
+    };
+
+    let (status, body) = match result {
+        Ok(body) => (200, body),
+        Err((status, error)) => (status, serde_json::json!({ "error": error })),
+    };
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("static header is valid");
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+    if let Err(e) = request.respond(response) {
+        log::warn!("Dashboard response not sent: {}", e);
+    }
+}
+
+async fn respond(app_handle: &AppHandle, route: Route) -> Result<serde_json::Value, (u16, String)> {
+    // The frontend loads the database; until then there is nothing to serve
+    let pool = db::sqlite_pool(app_handle).await.map_err(|e| (503, e))?;
+    let body = match route {
+        Route::Jobs => {
+            let registry = app_handle.state::<JobRegistry>();
+            let conversions = app_handle.state::<ConversionJobs>();
+            let jobs = job_status::collect_jobs(app_handle, &registry, &conversions, None)
+                .await
+                .map_err(|e| (500, e))?;
+            serde_json::to_value(jobs)
+        }
+        Route::Metrics { group_by, range } => {
+            let group_by: MetricsGroupBy = serde_json::from_value(serde_json::json!(group_by))
+                .map_err(|_| (400, format!("Unknown group_by '{}'", group_by)))?;
+            let range = MetricsRange {
+                from: range.0,
+                to: range.1,
+            };
+            let buckets = nesting_metrics::load_metrics(&pool, range, group_by)
+                .await
+                .map_err(|e| (500, e))?;
+            serde_json::to_value(buckets)
+        }
+        Route::QuoteSummary(id) => {
+            let summary = quote_summary(&pool, &id)
+                .await
+                .map_err(|e| (500, e))?
+                .ok_or_else(|| (404, format!("Quote '{}' not found", id)))?;
+            serde_json::to_value(summary)
+        }
+    };
+    body.map_err(|e| (500, format!("Failed to serialize response: {}", e)))
+}
+
+fn query_params(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes and `+` of a URL component
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes() {
+        assert_eq!(route("/jobs"), Some(Route::Jobs));
+        assert_eq!(
+            route("/metrics?group_by=preset&from=2026-10-01%2000:00:00"),
+            Some(Route::Metrics {
+                group_by: "preset".to_string(),
+                range: (Some("2026-10-01 00:00:00".to_string()), None),
+            })
+        );
+        assert_eq!(
+            route("/metrics"),
+            Some(Route::Metrics {
+                group_by: "day".to_string(),
+                range: (None, None),
+            })
+        );
+        assert_eq!(
+            route("/quote/q-42/summary"),
+            Some(Route::QuoteSummary("q-42".to_string()))
+        );
+        assert_eq!(route("/quote/q-42"), None);
+        assert_eq!(route("/settings"), None);
+    }
+
+    #[test]
+    fn test_bearer_token_required() {
+        let headers = [("Authorization", "Bearer s3cret")];
+        assert!(is_authorized(headers.into_iter(), "s3cret"));
+        assert!(!is_authorized(headers.into_iter(), "other"));
+        assert!(!is_authorized(
+            [("X-Token", "s3cret")].into_iter(),
+            "s3cret"
+        ));
+        // An empty token never authorizes
+        assert!(!is_authorized(
+            [("authorization", "Bearer ")].into_iter(),
+            ""
+        ));
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("s3cret", "s3cret"));
+        assert!(!tokens_match("s3creT", "s3cret"));
+        assert!(!tokens_match("s3cre", "s3cret"));
+        assert!(!tokens_match("s3crets", "s3cret"));
+        assert!(tokens_match("", ""));
+    }
+
+    #[test]
+    fn test_settings_default_to_localhost() {
+        let rows = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        };
+        let settings = DashboardSettings::from_rows(&rows(&[
+            (ENABLED_SETTING, "true"),
+            (PORT_SETTING, "not a port"),
+            (TOKEN_SETTING, " abc "),
+        ]));
+        assert!(settings.enabled);
+        assert_eq!(settings.address(), format!("127.0.0.1:{}", DEFAULT_PORT));
+        assert_eq!(settings.token, "abc");
+
+        let settings =
+            DashboardSettings::from_rows(&rows(&[(PORT_SETTING, "9000"), (BIND_SETTING, "lan")]));
+        assert!(!settings.enabled);
+        assert_eq!(settings.address(), "0.0.0.0:9000");
+    }
+}
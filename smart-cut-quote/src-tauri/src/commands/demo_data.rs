@@ -0,0 +1,339 @@
+use crate::commands::quote_nesting::store_nesting_snapshot;
+use crate::commands::shutdown;
+use crate::db;
+use crate::nesting_engine::{self, parse_instance, NestingInput, NestingOutput, DEMO_INSTANCE};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+const DEMO_CLIENT_ID: &str = "demo_client";
+const DEMO_QUOTE_ID: &str = "demo_quote";
+const DEMO_QUOTE_NUMBER: &str = "DEMO-0001";
+
+/// Nesting time of the demo quote (seconds)
+const DEMO_TIME_LIMIT: u64 = 5;
+const DEMO_SEED: u64 = 42;
+
+/// Sample stock row
+struct DemoMaterial {
+    id: &'static str,
+    name: &'static str,
+    grade: &'static str,
+    thickness: f64,
+    price_per_kg: f64,
+    density: f64,
+    cutting_speed: f64,
+    cut_price_per_meter: f64,
+}
+
+const DEMO_MATERIALS: &[DemoMaterial] = &[
+    DemoMaterial {
+        id: "demo_ms_3.0",
+        name: "Mild Steel",
+        grade: "A36",
+        thickness: 3.0,
+        price_per_kg: 2.5,
+        density: 7850.0,
+        cutting_speed: 2000.0,
+        cut_price_per_meter: 2.25,
+    },
+    DemoMaterial {
+        id: "demo_ss304_1.5",
+        name: "Stainless Steel",
+        grade: "304",
+        thickness: 1.5,
+        price_per_kg: 5.5,
+        density: 8000.0,
+        cutting_speed: 2000.0,
+        cut_price_per_meter: 2.25,
+    },
+    DemoMaterial {
+        id: "demo_al_2.0",
+        name: "Aluminum",
+        grade: "5052",
+        thickness: 2.0,
+        price_per_kg: 8.0,
+        density: 2700.0,
+        cutting_speed: 3000.0,
+        cut_price_per_meter: 1.5,
+    },
+];
+
+/// Tables demo rows are inserted into, dependents first, with their delete
+const DEMO_TABLES: &[(&str, &str)] = &[
+    (
+        "nesting_snapshots",
+        "DELETE FROM nesting_snapshots WHERE quote_id = ?",
+    ),
+    ("quotes", "DELETE FROM quotes WHERE id = ?"),
+    ("clients", "DELETE FROM clients WHERE id = ?"),
+    ("material_stock", "DELETE FROM material_stock WHERE id = ?"),
+];
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DemoDataReport {
+    pub client_id: String,
+    pub quote_id: String,
+    pub material_ids: Vec<String>,
+    /// Rows added by this call, 0 when the demo data was already loaded
+    pub rows_inserted: usize,
+    /// Utilization of the demo nesting, None when it was not run again
+    pub utilization: Option<f64>,
+}
+
+/// Insert a sample client, stock, and a nested quote
+///
+/// Safe to call twice: rows that already exist are kept and the nesting
+/// only runs when the demo quote is missing. Every inserted row is
+/// recorded in `demo_rows` for `clear_demo_data`.
+#[tauri::command]
+pub async fn load_demo_data(app_handle: tauri::AppHandle) -> Result<DemoDataReport, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let mut rows_inserted = 0;
+
+    let inserted = sqlx::query(
+        "INSERT OR IGNORE INTO clients (id, company_name, phone, email, quote_prefix)
+         VALUES (?, 'Demo Fabrication Pty Ltd', '0400 000 000', 'demo@example.com', 'DEMO')",
+    )
+    .bind(DEMO_CLIENT_ID)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to insert demo client: {}", e))?;
+    rows_inserted += register_if_inserted(&pool, "clients", DEMO_CLIENT_ID, inserted).await?;
+
+    for material in DEMO_MATERIALS {
+        let inserted = sqlx::query(
+            "INSERT OR IGNORE INTO material_stock
+                (id, name, grade, thickness, sheet_width, sheet_max_length, price_per_kg,
+                 density, cutting_speed, cut_price_per_meter, quantity_in_stock, min_quantity)
+             VALUES (?, ?, ?, ?, 1500, 3000, ?, ?, ?, ?, 20, 5)",
+        )
+        .bind(material.id)
+        .bind(material.name)
+        .bind(material.grade)
+        .bind(material.thickness)
+        .bind(material.price_per_kg)
+        .bind(material.density)
+        .bind(material.cutting_speed)
+        .bind(material.cut_price_per_meter)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to insert demo material '{}': {}", material.id, e))?;
+        rows_inserted +=
+            register_if_inserted(&pool, "material_stock", material.id, inserted).await?;
+    }
+
+    let quote_exists: Option<(String,)> = sqlx::query_as("SELECT id FROM quotes WHERE id = ?")
+        .bind(DEMO_QUOTE_ID)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to look up demo quote: {}", e))?;
+
+    let mut utilization = None;
+    if quote_exists.is_none() {
+        let output = run_demo_nesting(&app_handle).await?;
+        utilization = Some(output.utilization);
+
+        let inserted = sqlx::query(
+            "INSERT OR IGNORE INTO quotes
+                (id, quote_number, client_id, status, notes, data, created_by)
+             VALUES (?, ?, ?, 'draft', 'Sample quote created by demo mode', ?, 'DEMO')",
+        )
+        .bind(DEMO_QUOTE_ID)
+        .bind(DEMO_QUOTE_NUMBER)
+        .bind(DEMO_CLIENT_ID)
+        .bind(demo_quote_data(&output)?)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to insert demo quote: {}", e))?;
+        if inserted.rows_affected() == 0 {
+            return Err(format!(
+                "Quote number {} is already used, demo quote not created",
+                DEMO_QUOTE_NUMBER
+            ));
+        }
+        rows_inserted += register_if_inserted(&pool, "quotes", DEMO_QUOTE_ID, inserted).await?;
+
+        store_nesting_snapshot(&pool, DEMO_QUOTE_ID, DEMO_INSTANCE.to_string(), output).await?;
+        rows_inserted += register(&pool, "nesting_snapshots", DEMO_QUOTE_ID).await?;
+    }
+
+    println!(
+        "🎁 Demo data loaded: {} rows inserted (quote {})",
+        rows_inserted, DEMO_QUOTE_NUMBER
+    );
+    Ok(DemoDataReport {
+        client_id: DEMO_CLIENT_ID.to_string(),
+        quote_id: DEMO_QUOTE_ID.to_string(),
+        material_ids: DEMO_MATERIALS.iter().map(|m| m.id.to_string()).collect(),
+        rows_inserted,
+        utilization,
+    })
+}
+
+/// Remove the rows inserted by `load_demo_data`
+///
+/// Only rows recorded in `demo_rows` are deleted; returns their number.
+#[tauri::command]
+pub async fn clear_demo_data(app_handle: tauri::AppHandle) -> Result<usize, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT table_name, row_id FROM demo_rows")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to read demo rows: {}", e))?;
+
+    let mut removed = 0;
+    for &(table, delete) in DEMO_TABLES {
+        for (_, row_id) in rows.iter().filter(|(name, _)| name == table) {
+            let result = sqlx::query(delete)
+                .bind(row_id)
+                .execute(&pool)
+                .await
+                .map_err(|e| format!("Failed to delete demo row {}.{}: {}", table, row_id, e))?;
+            removed += result.rows_affected() as usize;
+            sqlx::query("DELETE FROM demo_rows WHERE table_name = ? AND row_id = ?")
+                .bind(table)
+                .bind(row_id)
+                .execute(&pool)
+                .await
+                .map_err(|e| format!("Failed to unregister demo row: {}", e))?;
+        }
+    }
+
+    println!("🧹 Demo data cleared: {} rows removed", removed);
+    Ok(removed)
+}
+
+/// Nest the demo parts; not recorded in the run metrics
+async fn run_demo_nesting(app_handle: &tauri::AppHandle) -> Result<NestingOutput, String> {
+    let input = NestingInput {
+        json_input: DEMO_INSTANCE.to_string(),
+        time_limit: Some(DEMO_TIME_LIMIT),
+        seed: Some(DEMO_SEED),
+        use_early_termination: Some(true),
+        ..Default::default()
+    };
+    let job_app_handle = app_handle.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shutdown::run_registered(&job_app_handle, None, |terminator| {
+            nesting_engine::run_nesting_engine_with_terminator(input, terminator)
+        })
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Quote data blob in the shape the frontend saves (`QuoteDataBlob`)
+fn demo_quote_data(output: &NestingOutput) -> Result<String, String> {
+    let instance = parse_instance(DEMO_INSTANCE)?;
+    let material = &DEMO_MATERIALS[0];
+    let files: Vec<serde_json::Value> = instance
+        .items
+        .iter()
+        .map(|item| {
+            let name = item
+                .label
+                .clone()
+                .unwrap_or_else(|| format!("part_{}.dxf", item.id));
+            serde_json::json!({
+                "id": format!("demo_part_{}", item.id),
+                "name": name,
+                "path": "",
+                "size": 0,
+                "quantity": item.demand,
+                "operations": [],
+                "status": "ok",
+                "materialGroup": material.name,
+                "materialGrade": material.grade,
+                "materialThickness": material.thickness,
+                "selected": true
+            })
+        })
+        .collect();
+
+    let placements: Vec<serde_json::Value> = output
+        .layouts
+        .iter()
+        .map(|placed| {
+            serde_json::json!({
+                "itemId": placed.item_id,
+                "x": placed.position_x,
+                "y": placed.position_y,
+                "rotation": placed.rotation_degrees
+            })
+        })
+        .collect();
+
+    let data = serde_json::json!({
+        "client": { "id": DEMO_CLIENT_ID, "name": "Demo Fabrication Pty Ltd" },
+        "files": files,
+        "nestingResult": {
+            "stripWidth": output.strip_width,
+            "stripHeight": output.strip_height,
+            "utilization": output.utilization,
+            "itemsPlaced": output.total_items_placed,
+            "placements": placements,
+            "svgPath": "",
+            "svgString": output.svg_string
+        }
+    });
+    serde_json::to_string(&data).map_err(|e| format!("Failed to serialize demo quote: {}", e))
+}
+
+/// Record a row in `demo_rows` when the insert added it
+async fn register_if_inserted(
+    pool: &SqlitePool,
+    table: &str,
+    row_id: &str,
+    inserted: sqlx::sqlite::SqliteQueryResult,
+) -> Result<usize, String> {
+    if inserted.rows_affected() == 0 {
+        return Ok(0);
+    }
+    register(pool, table, row_id).await
+}
+
+async fn register(pool: &SqlitePool, table: &str, row_id: &str) -> Result<usize, String> {
+    sqlx::query("INSERT OR IGNORE INTO demo_rows (table_name, row_id) VALUES (?, ?)")
+        .bind(table)
+        .bind(row_id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to record demo row {}.{}: {}", table, row_id, e))?;
+    Ok(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demo_quote_data_lists_parts_and_placements() {
+        let output: NestingOutput = serde_json::from_value(serde_json::json!({
+            "instance_name": "demo_parts",
+            "strip_width": 420.0,
+            "strip_height": 600.0,
+            "total_items_placed": 1,
+            "layouts": [{
+                "item_id": 2,
+                "rotation_degrees": 90.0,
+                "position_x": 10.0,
+                "position_y": 20.0
+            }],
+            "utilization": 0.4,
+            "computation_time_secs": 5.0
+        }))
+        .unwrap();
+
+        let data: serde_json::Value =
+            serde_json::from_str(&demo_quote_data(&output).unwrap()).unwrap();
+        let files = data["files"].as_array().unwrap();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0]["name"], "demo_bracket.dxf");
+        assert_eq!(files[0]["materialGroup"], "Mild Steel");
+        assert_eq!(data["client"]["id"], DEMO_CLIENT_ID);
+        assert_eq!(
+            data["nestingResult"]["placements"][0],
+            serde_json::json!({ "itemId": 2, "x": 10.0, "y": 20.0, "rotation": 90.0 })
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Autosaved quote drafts and crash recovery
+//!
+//! The frontend saves the quote being edited (debounced) with `save_draft`.
+//! Each app session owns one row of `quote_drafts`, keyed by a session ID,
+//! which every save overwrites. A session marker in the app data folder
+//! records when the app last exited cleanly; drafts written after that by
+//! another session were left behind by a crash and are offered at the next
+//! startup (`drafts://recoverable`, `list_recoverable_drafts`).
+
+use crate::commands::migrations;
+use crate::db;
+use crate::workspace;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Event carrying the recoverable `DraftSummary` list at startup
+pub const RECOVERABLE_DRAFTS_EVENT: &str = "drafts://recoverable";
+
+/// Largest draft accepted by `save_draft` (bytes of JSON)
+pub const MAX_DRAFT_BYTES: usize = 1024 * 1024;
+
+/// Drafts not updated for this many days are deleted at startup
+pub const DRAFT_RETENTION_DAYS: u32 = 14;
+
+/// Session marker file in the app data folder
+const SESSION_MARKER_FILE: &str = "session.json";
+
+/// Session marker written at startup and completed by a clean exit
+///
+/// Times are UTC `YYYY-MM-DD HH:MM:SS`, comparable with SQLite `datetime('now')`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct SessionMarker {
+    session_id: String,
+    started_at: String,
+    /// Set by the exit handler of this session
+    clean_shutdown_at: Option<String>,
+    /// Last clean exit before this session, kept across crashed sessions
+    previous_clean_shutdown_at: Option<String>,
+}
+
+/// Draft without its content
+#[derive(Serialize, Debug, Clone)]
+pub struct DraftSummary {
+    /// Session that wrote the draft
+    pub id: String,
+    pub size_bytes: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Draft left behind by a session that did not exit cleanly
+#[derive(Serialize, Debug, Clone)]
+pub struct RecoverableDraft {
+    #[serde(flatten)]
+    pub summary: DraftSummary,
+    /// Draft JSON as saved by the frontend
+    pub draft_json: String,
+}
+
+/// Draft session of this app run
+pub struct DraftSession {
+    session_id: String,
+    /// Last clean exit before this session (None: unknown, every draft counts)
+    last_clean_shutdown: Mutex<Option<String>>,
+}
+
+impl Default for DraftSession {
+    fn default() -> Self {
+        Self {
+            session_id: uuid::Uuid::new_v4().to_string(),
+            last_clean_shutdown: Mutex::new(None),
+        }
+    }
+}
+
+/// Start the session marker, prune old drafts and announce recoverable ones
+///
+/// Called from `setup` after the migrations. The frontend has not loaded
+/// the database yet, so the file is opened directly.
+pub fn start_draft_session(app_handle: &AppHandle) {
+    let session = app_handle.state::<DraftSession>();
+    let marker_path = match marker_path(app_handle) {
+        Ok(path) => path,
+        Err(e) => {
+            log::warn!("Draft recovery disabled: {}", e);
+            return;
+        }
+    };
+
+    let previous = read_marker(&marker_path);
+    let last_clean_shutdown = last_clean_shutdown(previous.as_ref());
+    let marker = SessionMarker {
+        session_id: session.session_id.clone(),
+        started_at: now(),
+        clean_shutdown_at: None,
+        previous_clean_shutdown_at: last_clean_shutdown.clone(),
+    };
+    if let Err(e) = write_marker(&marker_path, &marker) {
+        log::warn!("Failed to write session marker: {}", e);
+    }
+    *session.last_clean_shutdown.lock().unwrap() = last_clean_shutdown.clone();
+
+    let drafts = tauri::async_runtime::block_on(async {
+        let db_path = db::db_file_path(app_handle)?;
+        if !db_path.exists() {
+            return Ok(Vec::new());
+        }
+        let pool = migrations::open_pool(&db_path, false).await?;
+        let result = async {
+            prune_drafts(&pool).await?;
+            recoverable_drafts(&pool, &session.session_id, last_clean_shutdown.as_deref()).await
+        }
+        .await;
+        pool.close().await;
+        result
+    });
+
+    match drafts {
+        Ok(drafts) if !drafts.is_empty() => {
+            println!(
+                "📝 {} unsaved quote draft(s) can be recovered",
+                drafts.len()
+            );
+            let summaries: Vec<DraftSummary> =
+                drafts.into_iter().map(|draft| draft.summary).collect();
+            let _ = app_handle.emit(RECOVERABLE_DRAFTS_EVENT, summaries);
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("Failed to look up recoverable drafts: {}", e),
+    }
+}
+
+/// Record the clean exit of this session
+///
+/// Called on `RunEvent::Exit`; a crash leaves the marker open.
+pub fn mark_clean_shutdown(app_handle: &AppHandle) {
+    let session = app_handle.state::<DraftSession>();
+    let Ok(path) = marker_path(app_handle) else {
+        return;
+    };
+    let Some(mut marker) = read_marker(&path) else {
+        return;
+    };
+    // Another instance took over the marker
+    if marker.session_id != session.session_id {
+        return;
+    }
+    marker.clean_shutdown_at = Some(now());
+    if let Err(e) = write_marker(&path, &marker) {
+        log::warn!("Failed to record clean shutdown: {}", e);
+    }
+}
+
+/// Save the draft of this session, replacing the previous one
+#[tauri::command]
+pub async fn save_draft(
+    app_handle: AppHandle,
+    state: tauri::State<'_, DraftSession>,
+    quote_draft_json: String,
+) -> Result<DraftSummary, String> {
+    if quote_draft_json.len() > MAX_DRAFT_BYTES {
+        return Err(format!(
+            "Draft is too large ({} bytes, max {})",
+            quote_draft_json.len(),
+            MAX_DRAFT_BYTES
+        ));
+    }
+    serde_json::from_str::<serde_json::Value>(&quote_draft_json)
+        .map_err(|e| format!("Invalid draft JSON: {}", e))?;
+
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let (id, size_bytes, created_at, updated_at): (String, i64, String, String) = sqlx::query_as(
+        "INSERT INTO quote_drafts (id, draft_json, size_bytes) VALUES (?, ?, ?) \
+         ON CONFLICT(id) DO UPDATE SET draft_json = excluded.draft_json, \
+         size_bytes = excluded.size_bytes, updated_at = datetime('now') \
+         RETURNING id, size_bytes, created_at, updated_at",
+    )
+    .bind(&state.session_id)
+    .bind(&quote_draft_json)
+    .bind(quote_draft_json.len() as i64)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| format!("Failed to save draft: {}", e))?;
+
+    Ok(DraftSummary {
+        id,
+        size_bytes,
+        created_at,
+        updated_at,
+    })
+}
+
+/// Drafts of other sessions written after the last clean exit, newest first
+#[tauri::command]
+pub async fn list_recoverable_drafts(
+    app_handle: AppHandle,
+    state: tauri::State<'_, DraftSession>,
+) -> Result<Vec<RecoverableDraft>, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let last_clean_shutdown = state.last_clean_shutdown.lock().unwrap().clone();
+    recoverable_drafts(&pool, &state.session_id, last_clean_shutdown.as_deref()).await
+}
+
+/// Delete a draft (recovered, declined, or the quote was saved)
+///
+/// Returns false if there was no such draft.
+#[tauri::command]
+pub async fn discard_draft(app_handle: AppHandle, id: String) -> Result<bool, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let result = sqlx::query("DELETE FROM quote_drafts WHERE id = ?")
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to discard draft: {}", e))?;
+    Ok(result.rows_affected() > 0)
+}
+
+async fn recoverable_drafts(
+    pool: &SqlitePool,
+    session_id: &str,
+    last_clean_shutdown: Option<&str>,
+) -> Result<Vec<RecoverableDraft>, String> {
+    let rows: Vec<(String, i64, String, String, String)> = sqlx::query_as(
+        "SELECT id, size_bytes, created_at, updated_at, draft_json FROM quote_drafts \
+         WHERE id != ? AND (? IS NULL OR updated_at > ?) \
+         ORDER BY updated_at DESC",
+    )
+    .bind(session_id)
+    .bind(last_clean_shutdown)
+    .bind(last_clean_shutdown)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to list drafts: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(id, size_bytes, created_at, updated_at, draft_json)| RecoverableDraft {
+                summary: DraftSummary {
+                    id,
+                    size_bytes,
+                    created_at,
+                    updated_at,
+                },
+                draft_json,
+            },
+        )
+        .collect())
+}
+
+async fn prune_drafts(pool: &SqlitePool) -> Result<(), String> {
+    let result = sqlx::query("DELETE FROM quote_drafts WHERE updated_at < datetime('now', ?)")
+        .bind(format!("-{} days", DRAFT_RETENTION_DAYS))
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to prune drafts: {}", e))?;
+    if result.rows_affected() > 0 {
+        println!("🧹 Pruned {} old quote draft(s)", result.rows_affected());
+    }
+    Ok(())
+}
+
+/// Last clean exit known from the previous session's marker
+///
+/// A crashed session has no exit time of its own; the clean exit before
+/// it still counts, so drafts of several crashed sessions in a row are
+/// all offered.
+fn last_clean_shutdown(previous: Option<&SessionMarker>) -> Option<String> {
+    let previous = previous?;
+    previous
+        .clean_shutdown_at
+        .clone()
+        .or_else(|| previous.previous_clean_shutdown_at.clone())
+}
+
+fn marker_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(workspace::app_data_dir(app_handle)?.join(SESSION_MARKER_FILE))
+}
+
+fn read_marker(path: &Path) -> Option<SessionMarker> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_marker(path: &Path, marker: &SessionMarker) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(marker)
+        .map_err(|e| format!("Failed to serialize session marker: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn now() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(clean: Option<&str>, previous: Option<&str>) -> SessionMarker {
+        SessionMarker {
+            session_id: "s".to_string(),
+            started_at: "2026-10-01 08:00:00".to_string(),
+            clean_shutdown_at: clean.map(str::to_string),
+            previous_clean_shutdown_at: previous.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_last_clean_shutdown_survives_crashed_sessions() {
+        assert_eq!(last_clean_shutdown(None), None);
+
+        let clean = marker(Some("2026-10-01 17:00:00"), Some("2026-09-30 17:00:00"));
+        assert_eq!(
+            last_clean_shutdown(Some(&clean)).as_deref(),
+            Some("2026-10-01 17:00:00")
+        );
+
+        let crashed = marker(None, Some("2026-09-30 17:00:00"));
+        assert_eq!(
+            last_clean_shutdown(Some(&crashed)).as_deref(),
+            Some("2026-09-30 17:00:00")
+        );
+    }
+
+    #[test]
+    fn test_marker_round_trip() {
+        let dir = std::env::temp_dir().join(format!("drafts-marker-{}", std::process::id()));
+        let path = dir.join(SESSION_MARKER_FILE);
+        let written = marker(None, Some("2026-09-30 17:00:00"));
+
+        write_marker(&path, &written).unwrap();
+        assert_eq!(read_marker(&path), Some(written));
+
+        std::fs::write(&path, b"{ not json").unwrap();
+        assert_eq!(read_marker(&path), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,6 +1,17 @@
+use crate::commands::conversion_jobs::ConversionJobs;
+use crate::commands::job_status::{JobEntry, JobKind, JobRegistry, JobStatus, StepProgress};
+use crate::commands::response::{respond, CommandError, CommandResponse, ErrorCode};
+use crate::commands::tool_process::{run_tool, tool_path, Tool};
+use crate::nesting_engine::deserialize_dimension;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use tauri::Manager;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{Manager, State};
+
+/// Time a converter run may take before it is taken for hung and killed
+pub(crate) const CONVERTER_TIME_LIMIT: Duration = Duration::from_secs(10 * 60);
 
 /// Input file with path and quantity
 /// Frontend sends this struct instead of pre-formatted "PATH:QUANTITY" string
@@ -11,31 +22,77 @@ pub struct DxfFileInput {
     pub quantity: u32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ConversionOptions {
+    /// Accepts numbers or dimension strings as typed ("6 000", "5 mm")
+    #[serde(deserialize_with = "deserialize_dimension")]
     pub strip_height: f64,
+    #[serde(deserialize_with = "deserialize_dimension")]
     pub part_spacing: f64,
     pub arc_segments: u32,
 }
 
-#[derive(Serialize, Debug)]
-pub struct ConversionResult {
-    pub success: bool,
+/// Payload of `convert_dxf_to_json`
+#[derive(Serialize, Debug, Default)]
+pub struct ConversionOutput {
     pub output_path: Option<String>,
-    pub error: Option<String>,
+    /// Stopped by `cancel_conversion_job` before all files were converted
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+    /// Input files contained in `output_path` (only with a job ID)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub completed_files: Vec<String>,
 }
 
 /// Convert DXF files to JSON format for nesting
 /// Uses dxf-converter.exe bundled with the application
 /// Backend now handles path normalization and command building
+///
+/// With a `job_id` the files are converted one at a time and the batch
+/// can be stopped with `cancel_conversion_job`; the result then holds
+/// the files converted so far.
 #[tauri::command(rename_all = "camelCase")]
 pub async fn convert_dxf_to_json(
     app_handle: tauri::AppHandle,
+    jobs: State<'_, ConversionJobs>,
     input_files: Vec<DxfFileInput>,  // ✅ Changed: Now receives struct instead of pre-formatted strings
     output_path: String,
     options: ConversionOptions,
-) -> Result<ConversionResult, String> {
+    job_id: Option<String>,
+) -> Result<CommandResponse<ConversionOutput>, String> {
+    let result = convert_files(
+        &app_handle,
+        &jobs,
+        input_files,
+        output_path,
+        options,
+        job_id,
+    )
+    .await;
+    Ok(match result {
+        // The files converted before cancelling are still usable
+        Ok((output, warnings)) if output.cancelled => {
+            let message = format!(
+                "Conversion cancelled after {} files",
+                output.completed_files.len()
+            );
+            CommandResponse::partial(output, CommandError::new(ErrorCode::Cancelled, message))
+                .with_warnings(warnings)
+        }
+        Ok((output, warnings)) => CommandResponse::ok(output).with_warnings(warnings),
+        Err(error) => respond(Err(error)),
+    })
+}
+
+async fn convert_files(
+    app_handle: &tauri::AppHandle,
+    jobs: &ConversionJobs,
+    input_files: Vec<DxfFileInput>,
+    output_path: String,
+    options: ConversionOptions,
+    job_id: Option<String>,
+) -> Result<(ConversionOutput, Vec<String>), CommandError> {
     // Debug: Print received parameters
     println!("=== convert_dxf_to_json called (FIXED VERSION) ===");
     println!("Received {} files:", input_files.len());
@@ -45,36 +102,256 @@ pub async fn convert_dxf_to_json(
     println!("output_path: {}", output_path);
     println!("options: {:?}", options);
 
-    // Resolve the path to dxf-converter.exe
-    let resource_path = app_handle
-        .path()
-        .resolve("binaries/dxf-converter.exe", tauri::path::BaseDirectory::Resource)
-        .map_err(|e| format!("Failed to resolve dxf-converter.exe path: {}", e))?;
-
-    // Check if the executable exists (in dev mode, use relative path)
-    let exe_path = if resource_path.exists() {
-        resource_path
-    } else {
-        // Development mode: use relative path
-        std::env::current_dir()
-            .map_err(|e| format!("Failed to get current directory: {}", e))?
-            .join("../../binaries/dxf-converter.exe")
-    };
+    let exe_path = converter_exe_path(app_handle)?;
 
     if !exe_path.exists() {
         let error_msg = format!("dxf-converter.exe not found at: {}", exe_path.display());
         println!("❌ ERROR: {}", error_msg);
-        return Ok(ConversionResult {
-            success: false,
-            output_path: None,
-            error: Some(error_msg),
-        });
+        return Err(CommandError::new(ErrorCode::NotFound, error_msg));
     }
 
     println!("✓ Found dxf-converter.exe at: {}", exe_path.display());
 
+    if let Some(job_id) = job_id {
+        let cancel = jobs.start_batch(&job_id)?;
+        let total = input_files.len();
+        let report = |status: JobStatus, done: usize| {
+            app_handle.state::<JobRegistry>().report(JobEntry {
+                job_id: job_id.clone(),
+                kind: JobKind::Conversion,
+                status,
+                payload: StepProgress { done, total },
+            });
+        };
+        report(JobStatus::running("converting", Some(0.0), None), 0);
+
+        let result = tauri::async_runtime::spawn_blocking(move || {
+            convert_batch(&exe_path, &input_files, &output_path, &options, &cancel)
+        })
+        .await
+        .unwrap_or_else(|e| Err(format!("Conversion task failed: {}", e).into()));
+        jobs.finish(&job_id);
+
+        match &result {
+            Ok((output, _)) if output.cancelled => {
+                println!(
+                    "⏹️ Conversion {} cancelled after {} files",
+                    job_id,
+                    output.completed_files.len()
+                );
+                report(JobStatus::Cancelled, output.completed_files.len());
+            }
+            Ok((output, _)) => report(
+                JobStatus::Completed { summary_ref: None },
+                output.completed_files.len(),
+            ),
+            Err(error) => report(
+                JobStatus::Failed {
+                    error: error.message.clone(),
+                },
+                0,
+            ),
+        }
+        return result;
+    }
+
+    convert_once(
+        &exe_path,
+        &input_files,
+        output_path,
+        &options,
+        CONVERTER_TIME_LIMIT,
+    )
+}
+
+/// Convert all files in one converter run
+///
+/// Returns the output and the warnings the converter printed.
+fn convert_once(
+    exe_path: &Path,
+    input_files: &[DxfFileInput],
+    output_path: String,
+    options: &ConversionOptions,
+    time_limit: Duration,
+) -> Result<(ConversionOutput, Vec<String>), CommandError> {
+    let cmd = converter_command(exe_path, input_files, &output_path, options);
+
+    // Debug: Print the full command
+    println!("Executing command: {:?}", cmd);
+
+    let output = run_tool(Tool::DxfConverter, cmd, time_limit, None)?
+        .ok_or_else(|| "Converter run was cancelled".to_string())?;
+    if !output.stdout.is_empty() {
+        println!("dxf-converter stdout: {}", output.stdout);
+    }
+    if !output.stderr.is_empty() {
+        println!("dxf-converter stderr: {}", output.stderr);
+    }
+    let warnings = output.check(Tool::DxfConverter)?;
+
+    Ok((
+        ConversionOutput {
+            output_path: Some(output_path),
+            ..Default::default()
+        },
+        warnings,
+    ))
+}
+
+/// Files converted before a batch stopped
+struct BatchOutcome {
+    /// Input path and part output of every converted file, in input order
+    completed: Vec<(String, PathBuf)>,
+    cancelled: bool,
+}
+
+/// Convert the files one at a time and merge the outputs
+///
+/// A cancelled batch still writes the files converted so far to
+/// `output_path`.
+fn convert_batch(
+    exe_path: &Path,
+    input_files: &[DxfFileInput],
+    output_path: &str,
+    options: &ConversionOptions,
+    cancel: &AtomicBool,
+) -> Result<(ConversionOutput, Vec<String>), CommandError> {
+    let mut warnings = Vec::new();
+    let outcome = run_batch(input_files, cancel, |index, file| {
+        let part_path = format!("{}.part{}", output_path, index);
+        let cmd = converter_command(exe_path, std::slice::from_ref(file), &part_path, options);
+        let Some(output) = run_tool(Tool::DxfConverter, cmd, CONVERTER_TIME_LIMIT, Some(cancel))
+            .map_err(|error| error.message)?
+        else {
+            return Ok(None);
+        };
+        let file_warnings = output
+            .check(Tool::DxfConverter)
+            .map_err(|error| error.message)?;
+        warnings.extend(
+            file_warnings
+                .into_iter()
+                .map(|warning| format!("{}: {}", file.path, warning)),
+        );
+        Ok(Some(PathBuf::from(part_path)))
+    });
+    let outcome = outcome.map_err(|error| CommandError::new(ErrorCode::ToolFailed, error))?;
+
+    let parts: Vec<PathBuf> = outcome.completed.iter().map(|(_, part)| part.clone()).collect();
+    let merged = if parts.is_empty() {
+        Ok(false)
+    } else {
+        merge_outputs(&parts, output_path).map(|_| true)
+    };
+    remove_parts(&outcome.completed);
+
+    Ok((
+        ConversionOutput {
+            output_path: merged?.then(|| output_path.to_string()),
+            cancelled: outcome.cancelled,
+            completed_files: outcome.completed.into_iter().map(|(path, _)| path).collect(),
+        },
+        warnings,
+    ))
+}
+
+/// Convert files in order, checking `cancel` before every file
+///
+/// `convert` returns the output of one file, or None when it was
+/// cancelled mid-file. Outputs of a failed batch are removed.
+fn run_batch<F>(
+    input_files: &[DxfFileInput],
+    cancel: &AtomicBool,
+    mut convert: F,
+) -> Result<BatchOutcome, String>
+where
+    F: FnMut(usize, &DxfFileInput) -> Result<Option<PathBuf>, String>,
+{
+    let mut completed = Vec::new();
+    for (index, file) in input_files.iter().enumerate() {
+        if cancel.load(Ordering::SeqCst) {
+            return Ok(BatchOutcome {
+                completed,
+                cancelled: true,
+            });
+        }
+        match convert(index, file) {
+            Ok(Some(part)) => completed.push((file.path.clone(), part)),
+            Ok(None) => {
+                return Ok(BatchOutcome {
+                    completed,
+                    cancelled: true,
+                })
+            }
+            Err(e) => {
+                remove_parts(&completed);
+                return Err(format!("{}: {}", file.path, e));
+            }
+        }
+    }
+    Ok(BatchOutcome {
+        completed,
+        cancelled: false,
+    })
+}
+
+/// Combine the outputs of single-file conversions into one instance
+///
+/// Items are renumbered, jagua-rs requires consecutive IDs from 0.
+fn merge_outputs(parts: &[PathBuf], output_path: &str) -> Result<(), String> {
+    let mut merged: Option<serde_json::Value> = None;
+    let mut items = Vec::new();
+    for part in parts {
+        let content = std::fs::read_to_string(part)
+            .map_err(|e| format!("Failed to read '{}': {}", part.display(), e))?;
+        let mut value: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid converter output '{}': {}", part.display(), e))?;
+        if let Some(part_items) = value.get_mut("items").and_then(|i| i.as_array_mut()) {
+            items.append(part_items);
+        }
+        merged.get_or_insert(value);
+    }
+
+    let mut merged = merged.ok_or("No converter output to merge")?;
+    for (id, item) in items.iter_mut().enumerate() {
+        if let Some(item) = item.as_object_mut() {
+            item.insert("id".to_string(), id.into());
+        }
+    }
+    merged
+        .as_object_mut()
+        .ok_or("Converter output is not a JSON object")?
+        .insert("items".to_string(), serde_json::Value::Array(items));
+
+    let json = serde_json::to_string_pretty(&merged)
+        .map_err(|e| format!("Failed to serialize merged output: {}", e))?;
+    std::fs::write(output_path, json)
+        .map_err(|e| format!("Failed to write '{}': {}", output_path, e))
+}
+
+fn remove_parts(completed: &[(String, PathBuf)]) {
+    for (_, part) in completed {
+        let _ = std::fs::remove_file(part);
+    }
+}
+
+/// Resolve the path to dxf-converter.exe
+///
+/// Falls back to the repository copy in development mode; the returned
+/// path may not exist. `SMART_CUT_DXF_CONVERTER` overrides it.
+pub(crate) fn converter_exe_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    tool_path(app_handle, Tool::DxfConverter)
+}
+
+/// Build the dxf-converter.exe command for the given files
+pub(crate) fn converter_command(
+    exe_path: &Path,
+    input_files: &[DxfFileInput],
+    output_path: &str,
+    options: &ConversionOptions,
+) -> Command {
     // Build command
-    let mut cmd = Command::new(&exe_path);
+    let mut cmd = Command::new(exe_path);
 
     // ✅ WORKAROUND: Call -i multiple times instead of using :quantity syntax
     // REASON: dxf-converter.exe has a bug parsing Windows absolute paths with :quantity
@@ -88,7 +365,7 @@ pub async fn convert_dxf_to_json(
     //       Repo: https://github.com/truyentu/converters-mvp
 
     println!("Building command arguments (using duplicate -i workaround):");
-    for file_input in &input_files {
+    for file_input in input_files {
         // Step 1: Normalize path to Windows format (replace forward slashes with backslashes)
         // This ensures consistent Windows native paths
         let normalized_path = file_input.path.replace("/", "\\");
@@ -103,7 +380,7 @@ pub async fn convert_dxf_to_json(
     }
 
     // Add output
-    cmd.arg("--output").arg(&output_path);
+    cmd.arg("--output").arg(output_path);
 
     // Add options
     cmd.arg("--height")
@@ -113,40 +390,227 @@ pub async fn convert_dxf_to_json(
     cmd.arg("--arc-segments")
         .arg(options.arc_segments.to_string());
 
-    // Debug: Print the full command
-    println!("Executing command: {:?}", cmd);
+    cmd
+}
 
-    // Execute
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to execute dxf-converter: {}", e))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use crate::commands::tool_process::mock::{mock_tool, Scenario};
+    use std::sync::Arc;
+    use std::time::Instant;
 
-    // Debug: Print stdout and stderr
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    if !stdout.is_empty() {
-        println!("dxf-converter stdout: {}", stdout);
-    }
-    if !stderr.is_empty() {
-        println!("dxf-converter stderr: {}", stderr);
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
     }
 
-    if output.status.success() {
-        Ok(ConversionResult {
-            success: true,
-            output_path: Some(output_path),
-            error: None,
-        })
-    } else {
-        let error = String::from_utf8_lossy(&output.stderr).to_string();
-        Ok(ConversionResult {
-            success: false,
-            output_path: None,
-            error: Some(if error.is_empty() {
-                "Unknown error occurred during conversion".to_string()
-            } else {
-                error
-            }),
+    #[test]
+    fn test_batch_stops_between_files() {
+        let dir = temp_dir("dxf_batch_cancel");
+        let files: Vec<DxfFileInput> = (0..6)
+            .map(|i| {
+                let path = dir.join(format!("part{}.dxf", i));
+                std::fs::write(&path, "0\nEOF\n").unwrap();
+                DxfFileInput {
+                    path: path.to_string_lossy().into_owned(),
+                    quantity: 1,
+                }
+            })
+            .collect();
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let canceller = {
+            let cancel = cancel.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(120));
+                cancel.store(true, Ordering::SeqCst);
+            })
+        };
+
+        // Each "conversion" takes 50 ms
+        let outcome = run_batch(&files, &cancel, |index, _| {
+            std::thread::sleep(Duration::from_millis(50));
+            let part = dir.join(format!("out.part{}", index));
+            std::fs::write(&part, r#"{"items": []}"#).unwrap();
+            Ok(Some(part))
         })
+        .unwrap();
+        canceller.join().unwrap();
+
+        assert!(outcome.cancelled);
+        assert!(!outcome.completed.is_empty());
+        assert!(outcome.completed.len() < files.len());
+        for (i, (path, part)) in outcome.completed.iter().enumerate() {
+            assert_eq!(path, &files[i].path);
+            assert!(part.exists());
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_merge_outputs_renumbers_items() {
+        let dir = temp_dir("dxf_batch_merge");
+        let parts: Vec<PathBuf> = (0..2)
+            .map(|i| {
+                let part = dir.join(format!("out.part{}", i));
+                let json = serde_json::json!({
+                    "name": "nesting_job",
+                    "strip_height": 6000.0,
+                    "items": [{"id": 0, "demand": 1}, {"id": 1, "demand": 2 + i}]
+                });
+                std::fs::write(&part, json.to_string()).unwrap();
+                part
+            })
+            .collect();
+        let output_path = dir.join("out.json");
+
+        merge_outputs(&parts, output_path.to_str().unwrap()).unwrap();
+        let merged: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&output_path).unwrap()).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(merged["name"], "nesting_job");
+        let ids: Vec<u64> = merged["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["id"].as_u64().unwrap())
+            .collect();
+        assert_eq!(ids, vec![0, 1, 2, 3]);
+        assert_eq!(merged["items"][3]["demand"], 3);
+    }
+
+    #[cfg(unix)]
+    fn mock_conversion(
+        dir: &Path,
+        scenario: Scenario,
+        time_limit: Duration,
+    ) -> Result<(ConversionOutput, Vec<String>), CommandError> {
+        let input = DxfFileInput {
+            path: dir.join("bracket.dxf").to_string_lossy().into_owned(),
+            quantity: 2,
+        };
+        let options = ConversionOptions {
+            strip_height: 1000.0,
+            part_spacing: 5.0,
+            arc_segments: 16,
+        };
+        let output_path = dir.join("out.json").to_string_lossy().into_owned();
+        convert_once(
+            &mock_tool(dir, scenario),
+            &[input],
+            output_path,
+            &options,
+            time_limit,
+        )
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_converter_failures_are_classified() {
+        let dir = temp_dir("dxf_mock_failures");
+        let limit = Duration::from_secs(30);
+
+        let error = mock_conversion(&dir, Scenario::NonzeroExit, limit).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ToolFailed);
+        assert_eq!(
+            error.message,
+            "dxf-converter exited with code 3: Error: unsupported entity SPLINE"
+        );
+
+        let error = mock_conversion(&dir, Scenario::Crash, limit).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ToolFailed);
+        assert!(
+            error.message.starts_with("dxf-converter was killed by signal 11"),
+            "{}",
+            error.message
+        );
+
+        let error = mock_conversion(&dir, Scenario::HugeStderr, limit).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ToolFailed);
+        assert!(error.message.ends_with(" …"));
+        assert!(error.message.chars().count() < 2100);
+
+        let started = Instant::now();
+        let error =
+            mock_conversion(&dir, Scenario::SlowThenHang, Duration::from_millis(500)).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ToolFailed);
+        assert!(
+            error.message.starts_with("dxf-converter did not finish within 1s"),
+            "{}",
+            error.message
+        );
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_converter_warnings_are_reported() {
+        let dir = temp_dir("dxf_mock_warnings");
+        let (output, warnings) =
+            mock_conversion(&dir, Scenario::WarningsOnly, Duration::from_secs(30)).unwrap();
+        let written = std::fs::read_to_string(output.output_path.unwrap()).unwrap();
+        assert!(written.contains("\"items\""));
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(
+            warnings[0],
+            "dxf-converter: Warning: open contour closed on layer 0"
+        );
+
+        // A batch names the file each warning is about
+        let file = DxfFileInput {
+            path: dir.join("bracket.dxf").to_string_lossy().into_owned(),
+            quantity: 1,
+        };
+        let options = ConversionOptions {
+            strip_height: 1000.0,
+            part_spacing: 5.0,
+            arc_segments: 16,
+        };
+        let output_path = dir.join("batch.json").to_string_lossy().into_owned();
+        let (output, warnings) = convert_batch(
+            &mock_tool(&dir, Scenario::WarningsOnly),
+            &[file],
+            &output_path,
+            &options,
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+        assert_eq!(output.completed_files.len(), 1);
+        assert!(
+            warnings[1].ends_with("bracket.dxf: dxf-converter: Warning: 2 duplicate entities removed"),
+            "{}",
+            warnings[1]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cancel_kills_running_process() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let canceller = {
+            let cancel = cancel.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(100));
+                cancel.store(true, Ordering::SeqCst);
+            })
+        };
+
+        let started = Instant::now();
+        let mut cmd = Command::new("sleep");
+        cmd.arg("5");
+        let result =
+            run_tool(Tool::DxfConverter, cmd, Duration::from_secs(60), Some(&cancel)).unwrap();
+        canceller.join().unwrap();
+
+        assert!(result.is_none());
+        assert!(started.elapsed() < Duration::from_secs(2));
     }
 }
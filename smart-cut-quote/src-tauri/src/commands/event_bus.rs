@@ -0,0 +1,455 @@
+//! Rate limited event emission to the WebView
+//!
+//! Background threads (nesting jobs, conversion workers) emit through the
+//! managed `EventBus` instead of `AppHandle::emit`. Each topic has a
+//! policy:
+//!
+//! - `Delivery::Latest`: progress-type topics. Within `min_interval` only
+//!   the latest payload per key (job ID) is kept and sent when the interval
+//!   has passed, earlier ones are counted as coalesced.
+//! - `Delivery::Queued`: every event is delivered in order, at most one per
+//!   `min_interval` and key. Events beyond `QUEUE_CAPACITY` held events
+//!   are dropped and counted.
+//!
+//! Held events are sent by a flush thread started with the first emission.
+
+use crate::commands::conversion_jobs::CONVERSION_PROGRESS_EVENT;
+use crate::commands::legacy_import::LEGACY_IMPORT_PROGRESS_EVENT;
+use crate::commands::nesting_jobs::{
+    DERIVED_READY_EVENT, LAYOUT_SVG_EVENT, PREVIEW_EVENT, PROGRESS_EVENT, RESULT_READY_EVENT,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+
+/// Held events (coalesced and queued) above which queued events are dropped
+pub const QUEUE_CAPACITY: usize = 256;
+
+/// How often held events are checked by the flush thread
+const FLUSH_TICK: Duration = Duration::from_millis(20);
+
+/// Receiver of the events, the app handle outside of tests
+pub trait EventSink: Send + Sync {
+    fn send(&self, topic: &str, payload: &serde_json::Value) -> Result<(), String>;
+}
+
+impl EventSink for AppHandle {
+    fn send(&self, topic: &str, payload: &serde_json::Value) -> Result<(), String> {
+        self.emit(topic, payload).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// Only the latest payload per key is delivered
+    Latest,
+    /// All payloads are delivered in order, unless the queue is full
+    Queued,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopicPolicy {
+    pub delivery: Delivery,
+    /// Minimum time between two events of a topic and key
+    pub min_interval: Duration,
+}
+
+/// Topics without a policy are queued without rate limit
+const UNLIMITED: TopicPolicy = TopicPolicy {
+    delivery: Delivery::Queued,
+    min_interval: Duration::ZERO,
+};
+
+fn default_policies() -> HashMap<&'static str, TopicPolicy> {
+    HashMap::from([
+        (
+            CONVERSION_PROGRESS_EVENT,
+            TopicPolicy {
+                delivery: Delivery::Latest,
+                min_interval: Duration::from_millis(100),
+            },
+        ),
+        // The optimizer reports many solutions per second, a few are enough
+        (
+            PROGRESS_EVENT,
+            TopicPolicy {
+                delivery: Delivery::Latest,
+                min_interval: Duration::from_millis(250),
+            },
+        ),
+        // Patches apply to the SVG shown last, they cannot be coalesced
+        (
+            PREVIEW_EVENT,
+            TopicPolicy {
+                delivery: Delivery::Queued,
+                min_interval: Duration::from_millis(50),
+            },
+        ),
+        // Every layout SVG replaces the last one, the engine sends one per second
+        (
+            LAYOUT_SVG_EVENT,
+            TopicPolicy {
+                delivery: Delivery::Latest,
+                min_interval: Duration::from_secs(1),
+            },
+        ),
+        // Imports scan thousands of files, the latest one is enough
+        (
+            LEGACY_IMPORT_PROGRESS_EVENT,
+            TopicPolicy {
+                delivery: Delivery::Latest,
+                min_interval: Duration::from_millis(100),
+            },
+        ),
+        (RESULT_READY_EVENT, UNLIMITED),
+        (DERIVED_READY_EVENT, UNLIMITED),
+    ])
+}
+
+/// What happened to an emitted event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emission {
+    Sent,
+    /// Held until the rate limit allows it
+    Held,
+    /// Queue full or payload not serializable
+    Dropped,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct TopicStats {
+    pub emitted: u64,
+    /// Replaced by a later payload before being sent
+    pub coalesced: u64,
+    pub dropped: u64,
+    /// Rejected by the WebView
+    pub failed: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct EventBusStats {
+    pub topics: BTreeMap<String, TopicStats>,
+    /// Events currently held
+    pub held: usize,
+}
+
+struct HeldEvent {
+    topic: &'static str,
+    key: String,
+    payload: serde_json::Value,
+}
+
+/// Rate limiting state, independent of the app for tests
+struct BusCore {
+    policies: HashMap<&'static str, TopicPolicy>,
+    last_sent: HashMap<(&'static str, String), Instant>,
+    /// Latest payload per topic and key, in arrival order
+    latest: Vec<HeldEvent>,
+    queue: VecDeque<HeldEvent>,
+    stats: BTreeMap<&'static str, TopicStats>,
+}
+
+impl BusCore {
+    fn new(policies: HashMap<&'static str, TopicPolicy>) -> Self {
+        BusCore {
+            policies,
+            last_sent: HashMap::new(),
+            latest: Vec::new(),
+            queue: VecDeque::new(),
+            stats: BTreeMap::new(),
+        }
+    }
+
+    fn policy(&self, topic: &str) -> TopicPolicy {
+        self.policies.get(topic).copied().unwrap_or(UNLIMITED)
+    }
+
+    fn held(&self) -> usize {
+        self.latest.len() + self.queue.len()
+    }
+
+    fn due(&self, topic: &'static str, key: &str, now: Instant) -> bool {
+        let interval = self.policy(topic).min_interval;
+        self.last_sent
+            .get(&(topic, key.to_string()))
+            .is_none_or(|sent| now.duration_since(*sent) >= interval)
+    }
+
+    fn publish(&mut self, sink: &dyn EventSink, event: HeldEvent, now: Instant) -> Emission {
+        let same_key = |held: &HeldEvent| held.topic == event.topic && held.key == event.key;
+        match self.policy(event.topic).delivery {
+            Delivery::Latest => {
+                if let Some(held) = self.latest.iter_mut().find(|held| same_key(held)) {
+                    held.payload = event.payload;
+                    self.stats.entry(event.topic).or_default().coalesced += 1;
+                    return Emission::Held;
+                }
+                if self.due(event.topic, &event.key, now) {
+                    self.send(sink, event, now);
+                    return Emission::Sent;
+                }
+                self.latest.push(event);
+                Emission::Held
+            }
+            Delivery::Queued => {
+                if !self.queue.iter().any(same_key) && self.due(event.topic, &event.key, now) {
+                    self.send(sink, event, now);
+                    return Emission::Sent;
+                }
+                if self.held() >= QUEUE_CAPACITY {
+                    self.stats.entry(event.topic).or_default().dropped += 1;
+                    return Emission::Dropped;
+                }
+                self.queue.push_back(event);
+                Emission::Held
+            }
+        }
+    }
+
+    /// Send the held events whose rate limit has passed
+    fn flush(&mut self, sink: &dyn EventSink, now: Instant) {
+        for event in std::mem::take(&mut self.latest) {
+            if self.due(event.topic, &event.key, now) {
+                self.send(sink, event, now);
+            } else {
+                self.latest.push(event);
+            }
+        }
+
+        // An event waits while an earlier one of its key waits
+        let mut waiting: Vec<(&'static str, String)> = Vec::new();
+        for event in std::mem::take(&mut self.queue) {
+            let key = (event.topic, event.key.clone());
+            if !waiting.contains(&key) && self.due(event.topic, &event.key, now) {
+                self.send(sink, event, now);
+            } else {
+                waiting.push(key);
+                self.queue.push_back(event);
+            }
+        }
+    }
+
+    fn send(&mut self, sink: &dyn EventSink, event: HeldEvent, now: Instant) {
+        let stats = self.stats.entry(event.topic).or_default();
+        match sink.send(event.topic, &event.payload) {
+            Ok(()) => stats.emitted += 1,
+            Err(_) => stats.failed += 1,
+        }
+        self.last_sent.insert((event.topic, event.key), now);
+    }
+
+    fn stats(&self) -> EventBusStats {
+        EventBusStats {
+            topics: self
+                .stats
+                .iter()
+                .map(|(topic, stats)| (topic.to_string(), stats.clone()))
+                .collect(),
+            held: self.held(),
+        }
+    }
+}
+
+/// Managed event bus, see the module docs
+pub struct EventBus {
+    core: Arc<Mutex<BusCore>>,
+    flush_started: OnceLock<()>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus {
+            core: Arc::new(Mutex::new(BusCore::new(default_policies()))),
+            flush_started: OnceLock::new(),
+        }
+    }
+}
+
+impl EventBus {
+    /// Emit `payload` on `topic`; `key` separates the jobs of a topic
+    pub fn emit<P: Serialize>(
+        &self,
+        app_handle: &AppHandle,
+        topic: &'static str,
+        key: &str,
+        payload: &P,
+    ) -> Emission {
+        self.flush_started
+            .get_or_init(|| self.start_flush(app_handle.clone()));
+
+        let Ok(mut core) = self.core.lock() else {
+            return Emission::Dropped;
+        };
+        let payload = match serde_json::to_value(payload) {
+            Ok(payload) => payload,
+            Err(e) => {
+                println!("❌ Event {} not serializable: {}", topic, e);
+                core.stats.entry(topic).or_default().dropped += 1;
+                return Emission::Dropped;
+            }
+        };
+        let event = HeldEvent {
+            topic,
+            key: key.to_string(),
+            payload,
+        };
+        core.publish(app_handle, event, Instant::now())
+    }
+
+    fn start_flush(&self, app_handle: AppHandle) {
+        let core = self.core.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(FLUSH_TICK);
+            match core.lock() {
+                Ok(mut core) => core.flush(&app_handle, Instant::now()),
+                Err(_) => break,
+            }
+        });
+    }
+
+    pub fn stats(&self) -> EventBusStats {
+        self.core
+            .lock()
+            .map(|core| core.stats())
+            .unwrap_or_else(|_| EventBusStats {
+                topics: BTreeMap::new(),
+                held: 0,
+            })
+    }
+}
+
+/// Emitted, coalesced and dropped events per topic
+#[tauri::command]
+pub async fn get_event_bus_stats(bus: State<'_, EventBus>) -> Result<EventBusStats, String> {
+    Ok(bus.stats())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Default)]
+    struct RecordingSink {
+        sent: Mutex<Vec<(String, serde_json::Value)>>,
+    }
+
+    impl EventSink for RecordingSink {
+        fn send(&self, topic: &str, payload: &serde_json::Value) -> Result<(), String> {
+            self.sent
+                .lock()
+                .unwrap()
+                .push((topic.to_string(), payload.clone()));
+            Ok(())
+        }
+    }
+
+    impl RecordingSink {
+        fn payloads(&self) -> Vec<serde_json::Value> {
+            self.sent
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(_, payload)| payload.clone())
+                .collect()
+        }
+    }
+
+    fn event(topic: &'static str, key: &str, payload: serde_json::Value) -> HeldEvent {
+        HeldEvent {
+            topic,
+            key: key.to_string(),
+            payload,
+        }
+    }
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn test_progress_is_coalesced_latest_wins() {
+        let sink = RecordingSink::default();
+        let mut core = BusCore::new(default_policies());
+        let start = Instant::now();
+
+        for done in 0..5 {
+            let progress = event(CONVERSION_PROGRESS_EVENT, "job", json!({ "done": done }));
+            core.publish(&sink, progress, start + done * MS);
+        }
+        // First one immediately, the last one once 100 ms have passed
+        assert_eq!(sink.payloads(), vec![json!({ "done": 0 })]);
+        core.flush(&sink, start + 50 * MS);
+        assert_eq!(sink.payloads().len(), 1);
+        core.flush(&sink, start + 100 * MS);
+        assert_eq!(
+            sink.payloads(),
+            vec![json!({ "done": 0 }), json!({ "done": 4 })]
+        );
+
+        let stats = core.stats();
+        let progress = &stats.topics[CONVERSION_PROGRESS_EVENT];
+        assert_eq!((progress.emitted, progress.coalesced), (2, 3));
+        assert_eq!(stats.held, 0);
+    }
+
+    #[test]
+    fn test_keys_are_limited_separately() {
+        let sink = RecordingSink::default();
+        let mut core = BusCore::new(default_policies());
+        let now = Instant::now();
+
+        let a = core.publish(&sink, event(CONVERSION_PROGRESS_EVENT, "a", json!(1)), now);
+        let b = core.publish(&sink, event(CONVERSION_PROGRESS_EVENT, "b", json!(2)), now);
+        assert_eq!((a, b), (Emission::Sent, Emission::Sent));
+    }
+
+    #[test]
+    fn test_queued_topic_is_rate_limited_in_order() {
+        let sink = RecordingSink::default();
+        let mut core = BusCore::new(default_policies());
+        let start = Instant::now();
+
+        for patch in 0..3 {
+            core.publish(&sink, event(PREVIEW_EVENT, "job", json!(patch)), start);
+        }
+        assert_eq!(sink.payloads(), vec![json!(0)]);
+        // One per 50 ms, none skipped
+        core.flush(&sink, start + 50 * MS);
+        core.flush(&sink, start + 60 * MS);
+        assert_eq!(sink.payloads(), vec![json!(0), json!(1)]);
+        core.flush(&sink, start + 100 * MS);
+        assert_eq!(sink.payloads(), vec![json!(0), json!(1), json!(2)]);
+
+        // Unlimited topics are not held behind the preview
+        core.publish(
+            &sink,
+            event(PREVIEW_EVENT, "job", json!(3)),
+            start + 101 * MS,
+        );
+        let ready = core.publish(
+            &sink,
+            event(RESULT_READY_EVENT, "job", json!("ready")),
+            start + 101 * MS,
+        );
+        assert_eq!(ready, Emission::Sent);
+    }
+
+    #[test]
+    fn test_full_queue_drops_and_counts() {
+        let sink = RecordingSink::default();
+        let mut core = BusCore::new(default_policies());
+        let now = Instant::now();
+
+        let emissions: Vec<Emission> = (0..QUEUE_CAPACITY + 3)
+            .map(|i| core.publish(&sink, event(PREVIEW_EVENT, "job", json!(i)), now))
+            .collect();
+        assert_eq!(emissions[0], Emission::Sent);
+        assert_eq!(emissions[QUEUE_CAPACITY], Emission::Held);
+        assert_eq!(emissions[QUEUE_CAPACITY + 1], Emission::Dropped);
+
+        let stats = core.stats();
+        assert_eq!(stats.topics[PREVIEW_EVENT].dropped, 2);
+        assert_eq!(stats.held, QUEUE_CAPACITY);
+    }
+}
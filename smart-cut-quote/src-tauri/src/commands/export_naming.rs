@@ -0,0 +1,476 @@
+//! File names of exports
+//!
+//! Export commands used to write wherever the frontend pointed them, and
+//! estimators saving to a shared drive overwrote each other's files. The
+//! name of every export now comes from the `export_name_template`
+//! setting, e.g. `{quote_no}_{customer}_{date}_{kind}_v{n}`, resolved
+//! here: placeholder values are stripped of characters no filesystem
+//! accepts, and `{n}` is the first version whose file does not exist
+//! yet. The file is created right away with create-new semantics, so two
+//! exports racing for the same version never get the same name; the
+//! export then writes over the empty file it reserved.
+
+use crate::db;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// App setting holding the export name template
+const TEMPLATE_SETTING: &str = "export_name_template";
+pub const DEFAULT_EXPORT_NAME_TEMPLATE: &str = "{quote_no}_{customer}_{date}_{kind}_v{n}";
+
+/// Placeholders of a template, `{n}` is required
+const PLACEHOLDERS: &[&str] = &["quote_no", "customer", "date", "kind", "n"];
+/// Versions tried before giving up on a directory
+const MAX_VERSION: u32 = 9999;
+/// Placeholder values are cut to this length, so a long customer name
+/// cannot push the file name past filesystem limits or cut off `{n}`
+const MAX_VALUE_CHARS: usize = 60;
+
+/// Names Windows reserves for devices, with any extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// What is exported, named by `{kind}` and giving the extension
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportKind {
+    Dxf,
+    Html,
+    CutProgram,
+    Image,
+    Csv,
+    Pdf,
+    Archive,
+}
+
+impl ExportKind {
+    fn label(self) -> &'static str {
+        match self {
+            ExportKind::Dxf => "layout",
+            ExportKind::Html => "viewer",
+            ExportKind::CutProgram => "cut_program",
+            ExportKind::Image => "print",
+            ExportKind::Csv => "parts",
+            ExportKind::Pdf => "quote",
+            ExportKind::Archive => "workspace",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ExportKind::Dxf => "dxf",
+            ExportKind::Html => "html",
+            ExportKind::CutProgram => "json",
+            ExportKind::Image => "png",
+            ExportKind::Csv => "csv",
+            ExportKind::Pdf => "pdf",
+            ExportKind::Archive => "zip",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Placeholder(&'static str),
+}
+
+/// A parsed export name template
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameTemplate {
+    segments: Vec<Segment>,
+}
+
+impl NameTemplate {
+    /// Parse a template, naming the first problem found
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            if open > 0 {
+                segments.push(Segment::Text(rest[..open].to_string()));
+            }
+            let close = rest[open..].find('}').ok_or_else(|| {
+                format!("Export name template '{}' has an unclosed '{{'", template)
+            })?;
+            let name = &rest[open + 1..open + close];
+            let placeholder = PLACEHOLDERS
+                .iter()
+                .find(|known| **known == name)
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown placeholder {{{}}} in export name template, use {}",
+                        name,
+                        PLACEHOLDERS
+                            .iter()
+                            .map(|known| format!("{{{}}}", known))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })?;
+            segments.push(Segment::Placeholder(placeholder));
+            rest = &rest[open + close + 1..];
+        }
+        if rest.contains('}') {
+            return Err(format!(
+                "Export name template '{}' has a '}}' without '{{'",
+                template
+            ));
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+        if !segments.contains(&Segment::Placeholder("n")) {
+            return Err(
+                "Export name template needs {n}, so exports never overwrite each other".to_string(),
+            );
+        }
+        Ok(Self { segments })
+    }
+
+    /// File name stem of version `n`
+    ///
+    /// Empty placeholders drop out together with the separator after
+    /// them.
+    fn render(&self, fields: &NameFields, n: u32) -> String {
+        let mut name = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => name.push_str(text),
+                Segment::Placeholder(placeholder) => {
+                    let value = match *placeholder {
+                        "quote_no" => fields.quote_no.clone().unwrap_or_default(),
+                        "customer" => fields.customer.clone().unwrap_or_default(),
+                        "date" => fields.date.clone(),
+                        "kind" => fields.kind.label().to_string(),
+                        _ => n.to_string(),
+                    };
+                    name.extend(value.chars().take(MAX_VALUE_CHARS));
+                }
+            }
+        }
+        file_stem(&tidy(&sanitize(&name)))
+    }
+}
+
+/// Values of the placeholders other than `{n}`
+#[derive(Debug, Clone)]
+pub struct NameFields {
+    pub quote_no: Option<String>,
+    pub customer: Option<String>,
+    /// Export date as YYYY-MM-DD
+    pub date: String,
+    pub kind: ExportKind,
+}
+
+/// Replace characters Windows, macOS or Linux reject in file names
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Collapse runs of separators, e.g. left by empty placeholders
+fn tidy(name: &str) -> String {
+    let is_separator = |c: char| matches!(c, '_' | '-' | ' ');
+    let mut tidy = String::with_capacity(name.len());
+    for c in name.chars() {
+        if is_separator(c) && tidy.chars().last().is_none_or(is_separator) {
+            continue;
+        }
+        tidy.push(c);
+    }
+    tidy.trim_end_matches(['_', '-', ' ']).to_string()
+}
+
+/// Stem safe on every platform: not empty, not a device name, no
+/// trailing dot or space
+fn file_stem(name: &str) -> String {
+    let mut stem = name.trim_end_matches(['.', ' ']).to_string();
+    if stem.is_empty() {
+        return "export".to_string();
+    }
+    let device = stem.split('.').next().unwrap_or_default();
+    if RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(device))
+    {
+        stem.insert(0, '_');
+    }
+    stem
+}
+
+/// File names of an export of `pages` files with the stem `stem`
+///
+/// A single page is `stem.ext`; several pages are numbered `stem-1.ext`,
+/// `stem-2.ext`, ...
+fn page_names(stem: &str, extension: &str, pages: usize) -> Vec<String> {
+    if pages == 1 {
+        return vec![format!("{}.{}", stem, extension)];
+    }
+    (1..=pages)
+        .map(|page| format!("{}-{}.{}", stem, page, extension))
+        .collect()
+}
+
+/// Create the files of the first free version in `dir`
+///
+/// A version is taken when any of its files exists. Files created for a
+/// version that turns out to be taken are removed again.
+pub fn reserve_export_paths(
+    dir: &Path,
+    template: &NameTemplate,
+    fields: &NameFields,
+    pages: usize,
+) -> Result<Vec<PathBuf>, String> {
+    if pages == 0 {
+        return Err("Nothing to export".to_string());
+    }
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    'versions: for n in 1..=MAX_VERSION {
+        let stem = template.render(fields, n);
+        let mut created = Vec::new();
+        for name in page_names(&stem, fields.kind.extension(), pages) {
+            let path = dir.join(name);
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => created.push(path),
+                Err(e) => {
+                    for path in &created {
+                        let _ = std::fs::remove_file(path);
+                    }
+                    if e.kind() == ErrorKind::AlreadyExists {
+                        continue 'versions;
+                    }
+                    return Err(format!("Failed to create '{}': {}", path.display(), e));
+                }
+            }
+        }
+        return Ok(created);
+    }
+    Err(format!(
+        "No free export name left in '{}' (tried {} versions)",
+        dir.display(),
+        MAX_VERSION
+    ))
+}
+
+/// Remove files reserved for an export that failed
+pub fn release_export_paths(paths: &[PathBuf]) {
+    for path in paths {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Stored template, the default when unset
+async fn load_template(pool: &sqlx::SqlitePool) -> Result<NameTemplate, String> {
+    let value: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = ?")
+        .bind(TEMPLATE_SETTING)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to load export name template: {}", e))?;
+    match value {
+        Some((template,)) if !template.trim().is_empty() => NameTemplate::parse(template.trim()),
+        _ => NameTemplate::parse(DEFAULT_EXPORT_NAME_TEMPLATE),
+    }
+}
+
+/// Quote number and customer of a quote
+async fn quote_fields(
+    pool: &sqlx::SqlitePool,
+    quote_id: &str,
+) -> Result<(Option<String>, Option<String>), String> {
+    let row: Option<(String, Option<String>)> = sqlx::query_as(
+        "SELECT q.quote_number, c.company_name
+         FROM quotes q LEFT JOIN clients c ON c.id = q.client_id
+         WHERE q.id = ?",
+    )
+    .bind(quote_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to load quote '{}': {}", quote_id, e))?;
+    let (quote_no, customer) = row.ok_or_else(|| format!("Quote '{}' not found", quote_id))?;
+    Ok((Some(quote_no), customer))
+}
+
+/// Reserve the file(s) of an export in `dir`, named by the template
+///
+/// Used by every export command. Without a quote, `{quote_no}` and
+/// `{customer}` are empty.
+pub async fn resolve_export_paths(
+    app_handle: &tauri::AppHandle,
+    kind: ExportKind,
+    quote_id: Option<&str>,
+    dir: &Path,
+    pages: usize,
+) -> Result<Vec<PathBuf>, String> {
+    let pool = db::sqlite_pool(app_handle).await?;
+    let template = load_template(&pool).await?;
+    let (quote_no, customer) = match quote_id {
+        Some(quote_id) => quote_fields(&pool, quote_id).await?,
+        None => (None, None),
+    };
+    let fields = NameFields {
+        quote_no,
+        customer,
+        date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+        kind,
+    };
+    reserve_export_paths(dir, &template, &fields, pages)
+}
+
+/// Reserve the file of a single-file export, see `resolve_export_paths`
+pub async fn resolve_export_path(
+    app_handle: &tauri::AppHandle,
+    kind: ExportKind,
+    quote_id: Option<&str>,
+    dir: &Path,
+) -> Result<PathBuf, String> {
+    let mut paths = resolve_export_paths(app_handle, kind, quote_id, dir, 1).await?;
+    Ok(paths.remove(0))
+}
+
+/// Reserve the file of an export the frontend writes (CSV, PDF, ...)
+///
+/// The returned file exists and is empty.
+#[tauri::command]
+pub async fn reserve_export_path(
+    app_handle: tauri::AppHandle,
+    kind: ExportKind,
+    quote_id: Option<String>,
+    dir: String,
+) -> Result<String, String> {
+    let path = resolve_export_path(&app_handle, kind, quote_id.as_deref(), Path::new(&dir)).await?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Check an export name template before it is saved
+#[tauri::command]
+pub fn validate_export_name_template(template: String) -> Result<(), String> {
+    NameTemplate::parse(template.trim()).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_dir;
+    use std::sync::Arc;
+
+    fn fields(customer: Option<&str>) -> NameFields {
+        NameFields {
+            quote_no: Some("Q-1042".to_string()),
+            customer: customer.map(str::to_string),
+            date: "2026-10-17".to_string(),
+            kind: ExportKind::Dxf,
+        }
+    }
+
+    fn file_names(paths: &[PathBuf]) -> Vec<String> {
+        paths
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_template_validation() {
+        assert!(NameTemplate::parse(DEFAULT_EXPORT_NAME_TEMPLATE).is_ok());
+        let error = NameTemplate::parse("{quote_no}_{client}_v{n}").unwrap_err();
+        assert!(error.contains("Unknown placeholder {client}"), "{}", error);
+        assert!(error.contains("{customer}"), "{}", error);
+        assert!(NameTemplate::parse("{quote_no}_{date}")
+            .unwrap_err()
+            .contains("needs {n}"));
+        assert!(NameTemplate::parse("{quote_no_v{n}").is_err());
+        assert!(NameTemplate::parse("quote}_v{n}").is_err());
+    }
+
+    #[test]
+    fn test_render_sanitizes_values() {
+        let template = NameTemplate::parse(DEFAULT_EXPORT_NAME_TEMPLATE).unwrap();
+        assert_eq!(
+            template.render(&fields(Some("Smith/Jones: \"Steel\"")), 2),
+            "Q-1042_Smith_Jones_Steel_2026-10-17_layout_v2"
+        );
+        // No customer, no doubled separator
+        assert_eq!(
+            template.render(&fields(None), 1),
+            "Q-1042_2026-10-17_layout_v1"
+        );
+        let device = NameTemplate::parse("con.{n}").unwrap();
+        assert_eq!(device.render(&fields(None), 1), "_con.1");
+    }
+
+    #[test]
+    fn test_page_names() {
+        assert_eq!(page_names("job-17", "png", 1), vec!["job-17.png"]);
+        assert_eq!(
+            page_names("job-17", "png", 2),
+            vec!["job-17-1.png", "job-17-2.png"]
+        );
+    }
+
+    #[test]
+    fn test_versions_skip_existing_files() {
+        let dir = test_dir("export-naming", "versions");
+        let template = NameTemplate::parse("{quote_no}_v{n}").unwrap();
+        let first = reserve_export_paths(&dir, &template, &fields(None), 1).unwrap();
+        let second = reserve_export_paths(&dir, &template, &fields(None), 1).unwrap();
+        assert_eq!(file_names(&first), vec!["Q-1042_v1.dxf"]);
+        assert_eq!(file_names(&second), vec!["Q-1042_v2.dxf"]);
+
+        // v3 is taken as soon as one of its pages exists
+        std::fs::write(dir.join("Q-1042_v3-2.png"), "").unwrap();
+        let image = NameFields {
+            kind: ExportKind::Image,
+            ..fields(None)
+        };
+        let pages = reserve_export_paths(&dir, &template, &image, 2).unwrap();
+        assert_eq!(
+            file_names(&pages),
+            vec!["Q-1042_v4-1.png", "Q-1042_v4-2.png"]
+        );
+        assert!(!dir.join("Q-1042_v3-1.png").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_concurrent_exports_get_distinct_versions() {
+        let dir = Arc::new(test_dir("export-naming", "concurrent"));
+        let template = Arc::new(NameTemplate::parse("{quote_no}_{kind}_v{n}").unwrap());
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let dir = dir.clone();
+                let template = template.clone();
+                std::thread::spawn(move || {
+                    reserve_export_paths(&dir, &template, &fields(None), 1)
+                        .unwrap()
+                        .remove(0)
+                })
+            })
+            .collect();
+        let mut names = file_names(
+            &threads
+                .into_iter()
+                .map(|thread| thread.join().unwrap())
+                .collect::<Vec<_>>(),
+        );
+        names.sort();
+
+        let mut expected: Vec<String> = (1..=8)
+            .map(|n| format!("Q-1042_layout_v{}.dxf", n))
+            .collect();
+        expected.sort();
+        assert_eq!(names, expected);
+        let _ = std::fs::remove_dir_all(&*dir);
+    }
+}
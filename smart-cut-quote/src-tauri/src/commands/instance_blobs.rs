@@ -0,0 +1,366 @@
+//! Content-addressed storage of the instance JSON of nesting results
+//!
+//! The estimator nests the same parts many times with different
+//! settings, storing the instance with every `nesting_results` row would
+//! duplicate megabytes. Instances are stored once in `instance_blobs`,
+//! keyed by the SHA-256 of the JSON and gzip-compressed, with the number
+//! of result rows referencing them. Deleting a result releases its blob,
+//! which goes with its last reference; purges also recount the
+//! references, so rows deleted by hand do not leave blobs behind.
+
+use crate::db;
+use crate::workspace;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool};
+use std::io::{Read, Write};
+use std::path::Path;
+use tauri::AppHandle;
+
+/// Rows removed by `purge_nesting_results`
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct PurgeReport {
+    pub deleted_results: u64,
+    /// Blobs no result referenced any more
+    pub collected_blobs: u64,
+}
+
+/// Key of an instance: lowercase hex SHA-256 of its JSON as stored
+pub fn instance_hash(instance_json: &str) -> String {
+    format!("{:x}", Sha256::digest(instance_json.as_bytes()))
+}
+
+fn compress(text: &str) -> Result<Vec<u8>, String> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(text.as_bytes())
+        .map_err(|e| format!("Failed to compress instance: {}", e))?;
+    encoder
+        .finish()
+        .map_err(|e| format!("Failed to compress instance: {}", e))
+}
+
+pub(crate) fn decompress(body: &[u8]) -> Result<String, String> {
+    let mut text = String::new();
+    GzDecoder::new(body)
+        .read_to_string(&mut text)
+        .map_err(|e| format!("Failed to decompress instance: {}", e))?;
+    Ok(text)
+}
+
+/// Store an instance, or take one more reference to the stored copy
+///
+/// Call it in the transaction inserting the row that references the blob.
+///
+/// # Returns
+/// The hash to store in `nesting_results.instance_hash`
+pub async fn acquire_instance_blob(
+    conn: &mut SqliteConnection,
+    instance_json: &str,
+) -> Result<String, String> {
+    let hash = instance_hash(instance_json);
+    let updated = sqlx::query("UPDATE instance_blobs SET refcount = refcount + 1 WHERE hash = ?")
+        .bind(&hash)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to reference instance {}: {}", hash, e))?
+        .rows_affected();
+    if updated == 0 {
+        sqlx::query("INSERT INTO instance_blobs (hash, body, size, refcount) VALUES (?, ?, ?, 1)")
+            .bind(&hash)
+            .bind(compress(instance_json)?)
+            .bind(instance_json.len() as i64)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| format!("Failed to store instance {}: {}", hash, e))?;
+    }
+    Ok(hash)
+}
+
+/// Drop one reference to a blob, removing it with the last one
+pub async fn release_instance_blob(conn: &mut SqliteConnection, hash: &str) -> Result<(), String> {
+    sqlx::query("UPDATE instance_blobs SET refcount = refcount - 1 WHERE hash = ?")
+        .bind(hash)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to release instance {}: {}", hash, e))?;
+    sqlx::query("DELETE FROM instance_blobs WHERE hash = ? AND refcount <= 0")
+        .bind(hash)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to remove instance {}: {}", hash, e))?;
+    Ok(())
+}
+
+/// Decompressed instance JSON of a blob
+pub async fn load_instance_blob(
+    executor: impl SqliteExecutor<'_>,
+    hash: &str,
+) -> Result<String, String> {
+    let body = instance_blob_body(executor, hash)
+        .await?
+        .ok_or_else(|| format!("No instance stored with hash '{}'", hash))?;
+    decompress(&body)
+}
+
+/// Compressed body of a blob, None when no blob has this hash
+pub(crate) async fn instance_blob_body(
+    executor: impl SqliteExecutor<'_>,
+    hash: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    let row: Option<(Vec<u8>,)> = sqlx::query_as("SELECT body FROM instance_blobs WHERE hash = ?")
+        .bind(hash)
+        .fetch_optional(executor)
+        .await
+        .map_err(|e| format!("Failed to load instance {}: {}", hash, e))?;
+    Ok(row.map(|(body,)| body))
+}
+
+/// Delete a `nesting_results` row and release its instance
+///
+/// # Returns
+/// False when no row has this ID
+pub async fn delete_nesting_result(conn: &mut SqliteConnection, id: i64) -> Result<bool, String> {
+    let row: Option<(Option<String>,)> =
+        sqlx::query_as("SELECT instance_hash FROM nesting_results WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| format!("Failed to look up nesting result {}: {}", id, e))?;
+    let Some((hash,)) = row else {
+        return Ok(false);
+    };
+    sqlx::query("DELETE FROM nesting_results WHERE id = ?")
+        .bind(id)
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to delete nesting result {}: {}", id, e))?;
+    if let Some(hash) = hash {
+        release_instance_blob(conn, &hash).await?;
+    }
+    Ok(true)
+}
+
+/// Recount the references of every blob and remove unreferenced ones
+///
+/// # Returns
+/// The number of blobs removed
+pub async fn collect_garbage(conn: &mut SqliteConnection) -> Result<u64, String> {
+    sqlx::query(
+        "UPDATE instance_blobs SET refcount =
+            (SELECT COUNT(*) FROM nesting_results r WHERE r.instance_hash = instance_blobs.hash)",
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| format!("Failed to recount instance references: {}", e))?;
+    let collected = sqlx::query("DELETE FROM instance_blobs WHERE refcount <= 0")
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to remove unreferenced instances: {}", e))?
+        .rows_affected();
+    Ok(collected)
+}
+
+/// Delete the results of a quote and/or created before a date, with their SVGs
+///
+/// At least one filter is required. Rows and blobs go in one
+/// transaction; SVGs are removed after it committed, a missing SVG is
+/// not an error.
+pub async fn purge_results(
+    pool: &SqlitePool,
+    data_dir: &Path,
+    quote_id: Option<&str>,
+    before: Option<&str>,
+) -> Result<PurgeReport, String> {
+    if quote_id.is_none() && before.is_none() {
+        return Err("Pass a quote or a date to purge nesting results".to_string());
+    }
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let rows: Vec<(i64, Option<String>)> = sqlx::query_as(
+        "SELECT id, svg_path FROM nesting_results
+         WHERE (?1 IS NULL OR quote_id = ?1) AND (?2 IS NULL OR created_at < ?2)",
+    )
+    .bind(quote_id)
+    .bind(before)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to select nesting results: {}", e))?;
+
+    let mut report = PurgeReport::default();
+    for (id, _) in &rows {
+        if delete_nesting_result(&mut tx, *id).await? {
+            report.deleted_results += 1;
+        }
+    }
+    report.collected_blobs = collect_garbage(&mut tx).await?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to purge nesting results: {}", e))?;
+
+    for svg_path in rows.iter().filter_map(|(_, svg_path)| svg_path.as_deref()) {
+        match std::fs::remove_file(data_dir.join(svg_path)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => log::warn!("SVG '{}' of a purged result not removed: {}", svg_path, e),
+        }
+    }
+    Ok(report)
+}
+
+/// Instance JSON of a stored nesting result, for replay or renest
+#[tauri::command]
+pub async fn get_instance_blob(app_handle: AppHandle, hash: String) -> Result<String, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    load_instance_blob(&pool, &hash).await
+}
+
+/// Delete stored nesting results by quote and/or age (`before` as
+/// `YYYY-MM-DD HH:MM:SS`, compared with `created_at`)
+#[tauri::command]
+pub async fn purge_nesting_results(
+    app_handle: AppHandle,
+    quote_id: Option<String>,
+    before: Option<String>,
+) -> Result<PurgeReport, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let data_dir = workspace::app_data_dir(&app_handle)?;
+    let report = purge_results(&pool, &data_dir, quote_id.as_deref(), before.as_deref()).await?;
+    println!(
+        "🗑️ Purged {} nesting results, {} stored instances",
+        report.deleted_results, report.collected_blobs
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{migrated_pool, test_dir};
+    use tauri::async_runtime::block_on;
+
+    /// Store a result row of `quote_id` referencing `instance_json`
+    async fn save_result(pool: &SqlitePool, quote_id: Option<&str>, instance_json: &str) -> i64 {
+        let mut tx = pool.begin().await.unwrap();
+        let hash = acquire_instance_blob(&mut tx, instance_json).await.unwrap();
+        let id = sqlx::query(
+            "INSERT INTO nesting_results (quote_id, strip_width, result_json, instance_hash)
+             VALUES (?, 100.0, '{}', ?)",
+        )
+        .bind(quote_id)
+        .bind(&hash)
+        .execute(&mut *tx)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        tx.commit().await.unwrap();
+        id
+    }
+
+    async fn refcounts(pool: &SqlitePool) -> Vec<i64> {
+        sqlx::query_as::<_, (i64,)>("SELECT refcount FROM instance_blobs ORDER BY refcount")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(refcount,)| refcount)
+            .collect()
+    }
+
+    const PARTS: &str = r#"{"name": "quote", "strip_height": 1500.0, "items": []}"#;
+    const OTHER_PARTS: &str = r#"{"name": "quote", "strip_height": 1250.0, "items": []}"#;
+
+    #[test]
+    fn test_identical_instances_share_one_blob() {
+        let dir = test_dir("instance-blobs", "share");
+        block_on(async {
+            let pool = migrated_pool(&dir).await;
+            let first = save_result(&pool, None, PARTS).await;
+            save_result(&pool, None, PARTS).await;
+            save_result(&pool, None, OTHER_PARTS).await;
+            assert_eq!(refcounts(&pool).await, vec![1, 2]);
+
+            let hash = instance_hash(PARTS);
+            assert_eq!(load_instance_blob(&pool, &hash).await.unwrap(), PARTS);
+
+            let mut conn = pool.acquire().await.unwrap();
+            assert!(delete_nesting_result(&mut conn, first).await.unwrap());
+            assert!(!delete_nesting_result(&mut conn, first).await.unwrap());
+            drop(conn);
+            assert_eq!(refcounts(&pool).await, vec![1, 1]);
+            pool.close().await;
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_last_reference_removes_blob() {
+        let dir = test_dir("instance-blobs", "last");
+        block_on(async {
+            let pool = migrated_pool(&dir).await;
+            let id = save_result(&pool, None, PARTS).await;
+            let mut conn = pool.acquire().await.unwrap();
+            delete_nesting_result(&mut conn, id).await.unwrap();
+            drop(conn);
+
+            assert!(refcounts(&pool).await.is_empty());
+            assert!(load_instance_blob(&pool, &instance_hash(PARTS))
+                .await
+                .is_err());
+            pool.close().await;
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_purge_collects_unreferenced_blobs() {
+        let dir = test_dir("instance-blobs", "purge");
+        block_on(async {
+            let pool = migrated_pool(&dir).await;
+            sqlx::query(
+                "INSERT INTO quotes (id, quote_number) VALUES ('q1', 'Q-1'), ('q2', 'Q-2')",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            save_result(&pool, Some("q1"), PARTS).await;
+            save_result(&pool, Some("q1"), OTHER_PARTS).await;
+            save_result(&pool, Some("q2"), PARTS).await;
+            // Deleted by hand, its blob keeps a stale reference
+            let stray = save_result(&pool, Some("q2"), r#"{"items": []}"#).await;
+            sqlx::query("DELETE FROM nesting_results WHERE id = ?")
+                .bind(stray)
+                .execute(&pool)
+                .await
+                .unwrap();
+
+            assert!(purge_results(&pool, &dir, None, None).await.is_err());
+            let report = purge_results(&pool, &dir, Some("q1"), None).await.unwrap();
+            assert_eq!(
+                report,
+                PurgeReport {
+                    deleted_results: 2,
+                    collected_blobs: 1,
+                }
+            );
+            // The instance shared with q2 stays
+            assert_eq!(refcounts(&pool).await, vec![1]);
+            assert!(load_instance_blob(&pool, &instance_hash(PARTS))
+                .await
+                .is_ok());
+
+            let report = purge_results(&pool, &dir, None, Some("9999-12-31"))
+                .await
+                .unwrap();
+            assert_eq!(report.deleted_results, 1);
+            assert!(refcounts(&pool).await.is_empty());
+            pool.close().await;
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,29 @@
+use crate::nesting_engine::{self, InstanceDiff, DEFAULT_DIFF_TOLERANCE};
+
+/// Diff two instances (before/after a customer revision)
+///
+/// Items are matched by label / DXF file name. Geometry counts as
+/// unchanged when every vertex moved less than `tolerance` mm
+/// (default 0.01mm) after normalizing the part position.
+#[tauri::command]
+pub async fn diff_instances(
+    instance_a_json: String,
+    instance_b_json: String,
+    tolerance: Option<f64>,
+) -> Result<InstanceDiff, String> {
+    let diff = nesting_engine::diff_instances(
+        &instance_a_json,
+        &instance_b_json,
+        tolerance.unwrap_or(DEFAULT_DIFF_TOLERANCE),
+    )?;
+
+    println!(
+        "🔍 Instance diff: {} added, {} removed, {} quantity changed, {} geometry changed",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.quantity_changed.len(),
+        diff.geometry_changed.len()
+    );
+
+    Ok(diff)
+}
@@ -0,0 +1,376 @@
+//! One status shape for every long-running job
+//!
+//! Nesting runs, nesting batches and conversion batches report their
+//! state to the `JobRegistry` while they run; directory conversion jobs
+//! keep theirs in the database and are mapped to the same shape when
+//! asked for. The frontend polls all of them with `get_job_status` and
+//! `list_jobs` instead of one command per feature.
+
+use crate::commands::conversion_jobs::{self, ConversionJobStatus, ConversionJobs};
+use crate::db;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+/// Finished jobs kept for status queries (running jobs are always kept)
+const MAX_FINISHED_JOBS: usize = 32;
+/// Directory conversion jobs returned by `list_jobs`, newest first
+const MAX_LISTED_CONVERSION_JOBS: i64 = 20;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// `run_nesting_integrated` with a job ID
+    Nesting,
+    /// `run_nesting_batch` with a job ID
+    NestingBatch,
+    /// Directory conversion job or `convert_dxf_to_json` batch
+    Conversion,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running {
+        /// What the job is doing, e.g. "optimizing" or a batch group key
+        stage: String,
+        /// None when the job cannot tell how far it is
+        progress_pct: Option<f64>,
+        detail: Option<String>,
+    },
+    Completed {
+        /// ID to fetch the result with, if it is kept (e.g. for `get_derived_output`)
+        summary_ref: Option<String>,
+    },
+    Failed {
+        error: String,
+    },
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn running(stage: &str, progress_pct: Option<f64>, detail: Option<String>) -> Self {
+        JobStatus::Running {
+            stage: stage.to_string(),
+            progress_pct,
+            detail,
+        }
+    }
+
+    /// Done, successfully or not
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, JobStatus::Queued | JobStatus::Running { .. })
+    }
+}
+
+/// State of one job with its feature specific details
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JobEntry<P> {
+    pub job_id: String,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// E.g. the file counts of a conversion job
+    pub payload: P,
+}
+
+/// Job entry as stored and returned, with its payload as JSON
+pub type JobInfo = JobEntry<serde_json::Value>;
+
+/// Payload of jobs made of countable steps (batch groups, files)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl StepProgress {
+    pub fn pct(&self) -> Option<f64> {
+        (self.total > 0).then(|| self.done as f64 / self.total as f64 * 100.0)
+    }
+}
+
+impl<P: Serialize> JobEntry<P> {
+    pub fn into_info(self) -> JobInfo {
+        JobEntry {
+            job_id: self.job_id,
+            kind: self.kind,
+            status: self.status,
+            payload: serde_json::to_value(&self.payload).unwrap_or(serde_json::Value::Null),
+        }
+    }
+}
+
+/// Jobs reported by their runners, oldest first
+#[derive(Default)]
+pub struct JobRegistry {
+    jobs: Mutex<Vec<JobInfo>>,
+}
+
+impl JobRegistry {
+    /// Record the current state of a job, replacing the previous one
+    pub fn report<P: Serialize>(&self, entry: JobEntry<P>) {
+        let Ok(mut jobs) = self.jobs.lock() else {
+            return;
+        };
+        let entry = entry.into_info();
+        jobs.retain(|job| job.job_id != entry.job_id);
+        jobs.push(entry);
+
+        let finished = jobs.iter().filter(|job| job.status.is_finished()).count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+        jobs.retain(|job| {
+            if excess > 0 && job.status.is_finished() {
+                excess -= 1;
+                return false;
+            }
+            true
+        });
+    }
+
+    pub fn get(&self, job_id: &str) -> Option<JobInfo> {
+        let jobs = self.jobs.lock().ok()?;
+        jobs.iter().find(|job| job.job_id == job_id).cloned()
+    }
+
+    /// Newest first, optionally of one kind only
+    pub fn list(&self, kind: Option<JobKind>) -> Vec<JobInfo> {
+        let Ok(jobs) = self.jobs.lock() else {
+            return Vec::new();
+        };
+        jobs.iter()
+            .rev()
+            .filter(|job| kind.is_none_or(|kind| job.kind == kind))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Unified status of a directory conversion job
+pub(crate) fn conversion_job_info(status: ConversionJobStatus) -> JobInfo {
+    let files = StepProgress {
+        done: status.files_done + status.files_failed,
+        total: status.files_done + status.files_failed + status.files_remaining,
+    };
+    let progress_pct = files.pct();
+    let detail = Some(format!(
+        "{} of {} files ({} failed)",
+        files.done, files.total, status.files_failed
+    ));
+    let job_status = match status.status.as_str() {
+        "completed" => JobStatus::Completed {
+            summary_ref: Some(status.job_id.clone()),
+        },
+        "cancelled" => JobStatus::Cancelled,
+        "interrupted" => JobStatus::Failed {
+            error: "Interrupted, resume with resume_conversion_job".to_string(),
+        },
+        "cancelling" => JobStatus::running("cancelling", progress_pct, detail),
+        _ => JobStatus::running("converting", progress_pct, detail),
+    };
+    JobEntry {
+        job_id: status.job_id.clone(),
+        kind: JobKind::Conversion,
+        status: job_status,
+        payload: status,
+    }
+    .into_info()
+}
+
+/// Status of any job by ID
+///
+/// Jobs reported by their runner come first; other IDs are looked up as
+/// directory conversion jobs.
+#[tauri::command]
+pub async fn get_job_status(
+    app_handle: AppHandle,
+    registry: State<'_, JobRegistry>,
+    conversions: State<'_, ConversionJobs>,
+    job_id: String,
+) -> Result<JobInfo, String> {
+    if let Some(job) = registry.get(&job_id) {
+        return Ok(job);
+    }
+    let pool = db::sqlite_pool(&app_handle).await?;
+    conversion_jobs::job_status(&pool, &conversions, &job_id)
+        .await
+        .map(conversion_job_info)
+        .map_err(|_| format!("Job '{}' not found", job_id))
+}
+
+/// Known jobs, newest first, optionally of one kind only
+///
+/// Holds running and recently finished jobs, plus the latest directory
+/// conversion jobs from the database.
+#[tauri::command]
+pub async fn list_jobs(
+    app_handle: AppHandle,
+    registry: State<'_, JobRegistry>,
+    conversions: State<'_, ConversionJobs>,
+    kind_filter: Option<JobKind>,
+) -> Result<Vec<JobInfo>, String> {
+    collect_jobs(&app_handle, &registry, &conversions, kind_filter).await
+}
+
+/// `list_jobs`, shared with the dashboard server
+pub(crate) async fn collect_jobs(
+    app_handle: &AppHandle,
+    registry: &JobRegistry,
+    conversions: &ConversionJobs,
+    kind_filter: Option<JobKind>,
+) -> Result<Vec<JobInfo>, String> {
+    let mut jobs = registry.list(kind_filter);
+    if kind_filter.is_some_and(|kind| kind != JobKind::Conversion) {
+        return Ok(jobs);
+    }
+
+    let pool = db::sqlite_pool(app_handle).await?;
+    let ids: Vec<(String,)> =
+        sqlx::query_as("SELECT id FROM conversion_jobs ORDER BY created_at DESC LIMIT ?")
+            .bind(MAX_LISTED_CONVERSION_JOBS)
+            .fetch_all(&pool)
+            .await
+            .map_err(|e| format!("Failed to list conversion jobs: {}", e))?;
+    for (job_id,) in ids {
+        let status = conversion_jobs::job_status(&pool, conversions, &job_id).await?;
+        jobs.push(conversion_job_info(status));
+    }
+    Ok(jobs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(job_id: &str, kind: JobKind, status: JobStatus) -> JobEntry<()> {
+        JobEntry {
+            job_id: job_id.to_string(),
+            kind,
+            status,
+            payload: (),
+        }
+    }
+
+    #[test]
+    fn test_nesting_job_reports_through_registry() {
+        let registry = JobRegistry::default();
+        registry.report(entry(
+            "nest-1",
+            JobKind::Nesting,
+            JobStatus::running("optimizing", None, None),
+        ));
+        assert!(matches!(
+            registry.get("nest-1").unwrap().status,
+            JobStatus::Running { .. }
+        ));
+
+        registry.report(entry(
+            "nest-1",
+            JobKind::Nesting,
+            JobStatus::Completed {
+                summary_ref: Some("nest-1".to_string()),
+            },
+        ));
+        let jobs = registry.list(Some(JobKind::Nesting));
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(
+            serde_json::to_value(&jobs[0].status).unwrap(),
+            serde_json::json!({ "state": "completed", "summary_ref": "nest-1" })
+        );
+    }
+
+    #[test]
+    fn test_batch_job_progress_and_failure() {
+        let registry = JobRegistry::default();
+        let groups = StepProgress { done: 2, total: 4 };
+        registry.report(JobEntry {
+            job_id: "batch-1".to_string(),
+            kind: JobKind::NestingBatch,
+            status: JobStatus::running("steel-2mm", groups.pct(), Some("Group 3 of 4".into())),
+            payload: groups,
+        });
+        let job = registry.get("batch-1").unwrap();
+        assert_eq!(job.payload, serde_json::json!({ "done": 2, "total": 4 }));
+        assert_eq!(
+            serde_json::to_value(&job.status).unwrap(),
+            serde_json::json!({
+                "state": "running",
+                "stage": "steel-2mm",
+                "progress_pct": 50.0,
+                "detail": "Group 3 of 4"
+            })
+        );
+
+        registry.report(entry(
+            "batch-1",
+            JobKind::NestingBatch,
+            JobStatus::Failed {
+                error: "database locked".to_string(),
+            },
+        ));
+        assert!(registry.get("batch-1").unwrap().status.is_finished());
+        assert!(registry.list(Some(JobKind::Conversion)).is_empty());
+    }
+
+    #[test]
+    fn test_conversion_job_maps_to_unified_status() {
+        let status = ConversionJobStatus {
+            job_id: "conv-1".to_string(),
+            directory: "/parts".to_string(),
+            status: "running".to_string(),
+            files_done: 3,
+            files_failed: 1,
+            files_remaining: 4,
+            converted: Vec::new(),
+        };
+        let job = conversion_job_info(status.clone());
+        assert_eq!(job.kind, JobKind::Conversion);
+        assert_eq!(
+            job.status,
+            JobStatus::running(
+                "converting",
+                Some(50.0),
+                Some("4 of 8 files (1 failed)".into())
+            )
+        );
+        assert_eq!(job.payload["directory"], "/parts");
+
+        let interrupted = conversion_job_info(ConversionJobStatus {
+            status: "interrupted".to_string(),
+            ..status.clone()
+        });
+        assert!(matches!(interrupted.status, JobStatus::Failed { .. }));
+        let completed = conversion_job_info(ConversionJobStatus {
+            status: "completed".to_string(),
+            ..status
+        });
+        assert_eq!(
+            completed.status,
+            JobStatus::Completed {
+                summary_ref: Some("conv-1".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn test_registry_drops_oldest_finished_jobs() {
+        let registry = JobRegistry::default();
+        registry.report(entry(
+            "running",
+            JobKind::Nesting,
+            JobStatus::running("optimizing", None, None),
+        ));
+        for i in 0..MAX_FINISHED_JOBS + 3 {
+            registry.report(entry(
+                &format!("done-{}", i),
+                JobKind::Nesting,
+                JobStatus::Cancelled,
+            ));
+        }
+        let jobs = registry.list(None);
+        assert_eq!(jobs.len(), MAX_FINISHED_JOBS + 1);
+        assert!(registry.get("running").is_some());
+        assert!(registry.get("done-0").is_none());
+        assert_eq!(jobs[0].job_id, format!("done-{}", MAX_FINISHED_JOBS + 2));
+    }
+}
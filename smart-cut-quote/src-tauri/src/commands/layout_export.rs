@@ -0,0 +1,209 @@
+use crate::commands::export_naming::{
+    release_export_paths, resolve_export_path, resolve_export_paths, ExportKind,
+};
+use crate::nesting_engine::{
+    self, CutProgram, CutProgramOptions, DisplayUnits, InteractiveHtmlOptions, LayoutDxfOptions,
+    LayoutDxfReport, LayoutPrintOptions, NestingOutput, PrintPage,
+};
+use serde::Serialize;
+use std::path::Path;
+use tauri::AppHandle;
+
+/// Written DXF of a layout
+#[derive(Serialize, Debug, Clone)]
+pub struct LayoutDxfExport {
+    pub path: String,
+    #[serde(flatten)]
+    pub report: LayoutDxfReport,
+}
+
+/// Written HTML viewer of a layout
+#[derive(Serialize, Debug, Clone)]
+pub struct InteractiveHtmlExport {
+    pub path: String,
+    pub bytes: usize,
+    pub placements: usize,
+}
+
+/// Write an export to its reserved file, removing the file on failure
+fn write_reserved(path: &Path, content: impl AsRef<[u8]>) -> Result<(), String> {
+    std::fs::write(path, content).map_err(|e| {
+        release_export_paths(&[path.to_path_buf()]);
+        format!("Failed to write '{}': {}", path.display(), e)
+    })
+}
+
+/// Export a nested layout as DXF for the cutting machine
+///
+/// Every part is written with its input contours (outer and holes)
+/// at the nested position. Contours are oriented per
+/// `options.export_winding` (default: outer CCW, holes CW); the report
+/// lists per part whether any contour had to be reversed. The file is
+/// named by the export name template (see `export_naming`) in `dir`.
+#[tauri::command]
+pub async fn export_layout_dxf(
+    app_handle: AppHandle,
+    output: NestingOutput,
+    instance_json: String,
+    dir: String,
+    quote_id: Option<String>,
+    options: Option<LayoutDxfOptions>,
+) -> Result<LayoutDxfExport, String> {
+    let instance = nesting_engine::parse_instance(&instance_json)?;
+    // Parts are placed in mm, whatever units the frontend shows
+    let output = output.to_units(DisplayUnits::Mm);
+    let (dxf, report) =
+        nesting_engine::layout_dxf(&output, &instance, &options.unwrap_or_default())?;
+
+    let path = resolve_export_path(
+        &app_handle,
+        ExportKind::Dxf,
+        quote_id.as_deref(),
+        Path::new(&dir),
+    )
+    .await?;
+    write_reserved(&path, dxf)?;
+
+    println!(
+        "📐 Exported {} contours to {} ({} parts reversed to {:?})",
+        report.contour_count,
+        path.display(),
+        report.parts_reversed,
+        report.winding
+    );
+    Ok(LayoutDxfExport {
+        path: path.to_string_lossy().into_owned(),
+        report,
+    })
+}
+
+/// Export a layout as one self-contained HTML file for customers
+///
+/// The page shows the layout, the parts table and the utilization, with
+/// hover highlighting and zoom/pan; it needs no network access and no
+/// installation. Needs part outlines like the DXF export.
+#[tauri::command]
+pub async fn export_interactive_html(
+    app_handle: AppHandle,
+    output: NestingOutput,
+    instance_json: String,
+    dir: String,
+    quote_id: Option<String>,
+    options: Option<InteractiveHtmlOptions>,
+) -> Result<InteractiveHtmlExport, String> {
+    let instance = nesting_engine::parse_instance(&instance_json)?;
+    let output = output.to_units(DisplayUnits::Mm);
+    let html =
+        nesting_engine::layout_interactive_html(&output, &instance, &options.unwrap_or_default())?;
+
+    let path = resolve_export_path(
+        &app_handle,
+        ExportKind::Html,
+        quote_id.as_deref(),
+        Path::new(&dir),
+    )
+    .await?;
+    write_reserved(&path, &html)?;
+
+    println!(
+        "🌐 Exported HTML viewer with {} parts to {} ({} KB)",
+        output.layouts.len(),
+        path.display(),
+        html.len() / 1024
+    );
+    Ok(InteractiveHtmlExport {
+        path: path.to_string_lossy().into_owned(),
+        bytes: html.len(),
+        placements: output.layouts.len(),
+    })
+}
+
+/// Build the neutral JSON cut program of a nested layout
+///
+/// Contours come in cutting order with pierce points and lead-ins, in
+/// the coordinate frame of `options.frame` (see
+/// `schemas/cut-program-v1.schema.json`). Like the DXF export it needs
+/// part outlines.
+#[tauri::command]
+pub async fn export_cut_program(
+    output: NestingOutput,
+    instance_json: String,
+    options: Option<CutProgramOptions>,
+) -> Result<CutProgram, String> {
+    let instance = nesting_engine::parse_instance(&instance_json)?;
+    let output = output.to_units(DisplayUnits::Mm);
+    let program = nesting_engine::cut_program(&output, &instance, &options.unwrap_or_default())?;
+
+    for warning in &program.warnings {
+        println!("⚠️ Cut program: {}", warning);
+    }
+    println!(
+        "✂️ Cut program with {} contours (format v{})",
+        program.entries.len(),
+        program.version
+    );
+    Ok(program)
+}
+
+/// Draw a layout as print pages for work orders, one per sheet
+///
+/// Pages are SVG sized in pixels for `options.dpi`; the frontend
+/// rasterizes them and passes the PNGs to `export_layout_image`.
+#[tauri::command]
+pub async fn render_layout_print(
+    sheets: Vec<NestingOutput>,
+    instance_json: String,
+    options: LayoutPrintOptions,
+) -> Result<Vec<PrintPage>, String> {
+    let instance = nesting_engine::parse_instance(&instance_json)?;
+    let sheets: Vec<NestingOutput> = sheets
+        .into_iter()
+        .map(|sheet| sheet.to_units(DisplayUnits::Mm))
+        .collect();
+    let printed_on = chrono::Local::now().format("%Y-%m-%d").to_string();
+    nesting_engine::layout_print_pages(&sheets, &instance, &options, &printed_on)
+}
+
+/// Write rasterized print pages as PNG, recording the print DPI
+///
+/// Pages go to `dir`, named by the export name template; several pages
+/// are numbered `name-1.png`, `name-2.png`, ... Returns the written
+/// paths.
+#[tauri::command]
+pub async fn export_layout_image(
+    app_handle: AppHandle,
+    pages: Vec<Vec<u8>>,
+    dir: String,
+    quote_id: Option<String>,
+    dpi: f64,
+) -> Result<Vec<String>, String> {
+    let pngs = pages
+        .iter()
+        .map(|page| nesting_engine::png_with_dpi(page, dpi))
+        .collect::<Result<Vec<_>, _>>()?;
+    let paths = resolve_export_paths(
+        &app_handle,
+        ExportKind::Image,
+        quote_id.as_deref(),
+        Path::new(&dir),
+        pngs.len(),
+    )
+    .await?;
+    for (png, path) in pngs.iter().zip(&paths) {
+        if let Err(e) = std::fs::write(path, png) {
+            release_export_paths(&paths);
+            return Err(format!("Failed to write '{}': {}", path.display(), e));
+        }
+    }
+
+    println!(
+        "🖨️ Exported {} layout pages at {} DPI to {}",
+        paths.len(),
+        dpi,
+        dir
+    );
+    Ok(paths
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect())
+}
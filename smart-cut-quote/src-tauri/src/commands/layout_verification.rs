@@ -0,0 +1,61 @@
+use crate::nesting_engine::{self, DisplayUnits, NestingOutput, OverlapReport};
+use serde::Serialize;
+
+/// Outcome of `verify_nesting`
+#[derive(Serialize, Debug)]
+pub struct LayoutVerification {
+    pub placements_checked: usize,
+    /// The instance is not the one the layout was nested from (another
+    /// fingerprint), so the check may not match what was cut
+    pub instance_changed: bool,
+    /// In the units of the checked output
+    pub violations: Vec<OverlapReport>,
+}
+
+/// Check a saved layout for overlapping parts without nesting again
+///
+/// Places the parts of `json_input` where `output_json` has them and
+/// reports overlaps, parts closer than the separation of the layout's
+/// resolved config and parts off the sheet (see `nesting_engine::verify`).
+/// The output must carry its placement outlines (`include_outlines`).
+#[tauri::command]
+pub async fn verify_nesting(
+    json_input: String,
+    output_json: String,
+) -> Result<LayoutVerification, String> {
+    let output: NestingOutput = serde_json::from_str(&output_json)
+        .map_err(|e| format!("Invalid nesting output JSON: {}", e))?;
+    let units = output.units;
+
+    let verification = tauri::async_runtime::spawn_blocking(move || {
+        let instance = nesting_engine::parse_instance(&json_input)?;
+        let instance_hash = nesting_engine::fingerprint_instance(&json_input)?.instance_hash;
+        let output = output.to_units(DisplayUnits::Mm);
+        let violations = nesting_engine::verify_output(&output, &instance)?;
+        Ok::<_, String>(LayoutVerification {
+            placements_checked: output.layouts.len(),
+            instance_changed: output
+                .instance_hash
+                .as_ref()
+                .is_some_and(|hash| *hash != instance_hash),
+            violations: violations
+                .into_iter()
+                .map(|violation| violation.in_units(units, DisplayUnits::Mm))
+                .collect(),
+        })
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    println!(
+        "🔎 Layout check: {} violations in {} placements{}",
+        verification.violations.len(),
+        verification.placements_checked,
+        if verification.instance_changed {
+            " (instance changed since nesting)"
+        } else {
+            ""
+        }
+    );
+    Ok(verification)
+}
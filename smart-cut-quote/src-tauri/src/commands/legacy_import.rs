@@ -0,0 +1,480 @@
+//! Import of result files written by the old sparrow-cli integration
+//!
+//! Before the integrated engine, `run_nesting` left a result JSON and an
+//! SVG per run wherever the user pointed it. `import_legacy_results`
+//! scans such a folder, turns every file in the CLI output schema into a
+//! `nesting_results` row and copies its SVG to `svgs/legacy` in the app
+//! data dir. A result is linked to a quote when its instance name matches
+//! the quote reference or number; results without a unique match land in
+//! the unmatched bucket (`quote_id` NULL). Files already imported are
+//! recognised by their source path, so a folder can be imported again.
+
+use crate::commands::artifacts::artifact_sha256;
+use crate::commands::event_bus::EventBus;
+use crate::db;
+use crate::workspace;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Event emitted for every scanned file, coalesced by the event bus
+pub const LEGACY_IMPORT_PROGRESS_EVENT: &str = "legacy-import://progress";
+
+/// Workspace sub-directory receiving the copied SVGs
+const LEGACY_SVG_DIR: &str = "legacy";
+
+/// Shortest reference matched inside a longer instance name
+const MIN_CONTAINED_REFERENCE_LEN: usize = 4;
+
+// The CLI wrote jagua's output format. Early builds used camelCase and a
+// few other names, all accepted here.
+
+#[derive(Deserialize, Debug)]
+struct LegacyOutput {
+    #[serde(alias = "Instance")]
+    instance: Option<LegacyInstance>,
+    /// Pre-instance builds wrote the name at the top level
+    #[serde(alias = "instance_name", alias = "instanceName")]
+    name: Option<String>,
+    #[serde(alias = "Solution", alias = "result")]
+    solution: LegacySolution,
+}
+
+#[derive(Deserialize, Debug)]
+struct LegacyInstance {
+    name: Option<String>,
+    #[serde(alias = "stripHeight", alias = "height")]
+    strip_height: Option<f64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LegacySolution {
+    #[serde(alias = "stripWidth", alias = "width", alias = "length")]
+    strip_width: f64,
+    #[serde(alias = "utilization", alias = "usage")]
+    density: Option<f64>,
+    #[serde(alias = "runTimeSec", alias = "run_time", alias = "runtime")]
+    run_time_sec: Option<f64>,
+    layout: Option<LegacyLayout>,
+    /// Layout-less builds listed the placements on the solution
+    #[serde(default, alias = "placedItems", alias = "placements")]
+    placed_items: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LegacyLayout {
+    #[serde(default, alias = "placedItems", alias = "placements")]
+    placed_items: Vec<serde_json::Value>,
+    density: Option<f64>,
+}
+
+/// What a legacy result file contains, lengths in mm
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LegacyResult {
+    pub instance_name: Option<String>,
+    pub strip_width: f64,
+    pub strip_height: Option<f64>,
+    pub density: Option<f64>,
+    pub placed_items: usize,
+    pub run_time_sec: Option<f64>,
+}
+
+/// Read a result file, `Ok(None)` for JSON in another schema
+pub fn parse_legacy_output(text: &str) -> Result<Option<LegacyResult>, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let Ok(output) = serde_json::from_value::<LegacyOutput>(value) else {
+        return Ok(None);
+    };
+
+    let solution = output.solution;
+    let (placed_items, layout_density) = match solution.layout {
+        Some(layout) if !layout.placed_items.is_empty() => {
+            (layout.placed_items.len(), layout.density)
+        }
+        Some(layout) => (solution.placed_items.len(), layout.density),
+        None => (solution.placed_items.len(), None),
+    };
+    let (instance_name, strip_height) = match output.instance {
+        Some(instance) => (instance.name.or(output.name), instance.strip_height),
+        None => (output.name, None),
+    };
+
+    Ok(Some(LegacyResult {
+        instance_name: instance_name.filter(|name| !name.trim().is_empty()),
+        strip_width: solution.strip_width,
+        strip_height,
+        density: solution.density.or(layout_density),
+        placed_items,
+        run_time_sec: solution.run_time_sec,
+    }))
+}
+
+/// Quote a result can be linked to
+#[derive(Debug, Clone)]
+pub struct QuoteKey {
+    pub id: String,
+    pub quote_number: Option<String>,
+    pub reference: Option<String>,
+}
+
+/// Lowercase letters and digits only, "Q-2023 / 0042" -> "q20230042"
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Quote whose reference or number matches the instance name
+///
+/// An exact match (ignoring case and punctuation) wins. Otherwise a quote
+/// whose reference or number is contained in the name is used, as the
+/// CLI names often had a suffix ("ACME-42_run2"). Ambiguous names are
+/// not linked.
+pub fn match_quote<'a>(instance_name: &str, quotes: &'a [QuoteKey]) -> Option<&'a QuoteKey> {
+    let name = normalize(instance_name);
+    if name.is_empty() {
+        return None;
+    }
+    let keys = |quote: &'a QuoteKey| {
+        [quote.reference.as_deref(), quote.quote_number.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(normalize)
+            .filter(|key| !key.is_empty())
+    };
+    let unique = |found: Vec<&'a QuoteKey>| (found.len() == 1).then(|| found[0]);
+
+    let exact: Vec<_> = quotes
+        .iter()
+        .filter(|quote| keys(quote).any(|key| key == name))
+        .collect();
+    if !exact.is_empty() {
+        return unique(exact);
+    }
+    unique(
+        quotes
+            .iter()
+            .filter(|quote| {
+                keys(quote)
+                    .any(|key| key.len() >= MIN_CONTAINED_REFERENCE_LEN && name.contains(&key))
+            })
+            .collect(),
+    )
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportOutcome {
+    Imported,
+    /// Dry run: would be imported
+    WouldImport,
+    /// Source path already in nesting_results
+    AlreadyImported,
+    /// JSON in another schema
+    NotLegacy,
+    Failed,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ImportedFile {
+    /// Path relative to the scanned directory
+    pub file: String,
+    pub outcome: ImportOutcome,
+    pub instance_name: Option<String>,
+    /// None: unmatched bucket
+    pub quote_id: Option<String>,
+    /// Workspace-relative path of the copied SVG, the source SVG in a
+    /// dry run
+    pub svg_path: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct LegacyImportReport {
+    pub dry_run: bool,
+    pub scanned: usize,
+    pub imported: usize,
+    /// Imported (or importable) results not linked to a quote
+    pub unmatched: usize,
+    pub already_imported: usize,
+    pub failed: usize,
+    pub files: Vec<ImportedFile>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LegacyImportProgress {
+    pub current: usize,
+    pub total: usize,
+    pub file: String,
+}
+
+/// Import the legacy sparrow-cli results found below `dir`
+///
+/// With `dry_run` nothing is written and the report lists what would be
+/// imported.
+#[tauri::command]
+pub async fn import_legacy_results(
+    app_handle: AppHandle,
+    dir: String,
+    dry_run: Option<bool>,
+) -> Result<LegacyImportReport, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    let root = PathBuf::from(&dir);
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", dir));
+    }
+
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let quotes: Vec<QuoteKey> = sqlx::query_as::<_, (String, Option<String>, Option<String>)>(
+        "SELECT id, quote_number, reference FROM quotes",
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to load quotes: {}", e))?
+    .into_iter()
+    .map(|(id, quote_number, reference)| QuoteKey {
+        id,
+        quote_number,
+        reference,
+    })
+    .collect();
+    let svg_dir = workspace::workspace_dir(&app_handle, "svgs")?.join(LEGACY_SVG_DIR);
+
+    let candidates: Vec<PathBuf> = workspace::list_files(&root)?
+        .into_iter()
+        .filter(|path| has_extension(path, "json"))
+        .collect();
+    let mut report = LegacyImportReport {
+        dry_run,
+        scanned: candidates.len(),
+        ..Default::default()
+    };
+
+    let bus = app_handle.state::<EventBus>();
+    for (index, relative) in candidates.iter().enumerate() {
+        let file = relative.to_string_lossy().replace('\\', "/");
+        bus.emit(
+            &app_handle,
+            LEGACY_IMPORT_PROGRESS_EVENT,
+            &dir,
+            &LegacyImportProgress {
+                current: index,
+                total: candidates.len(),
+                file: file.clone(),
+            },
+        );
+
+        let entry = match import_file(&pool, &quotes, &root, relative, &svg_dir, dry_run).await {
+            Ok(entry) => entry,
+            Err(error) => ImportedFile {
+                file: String::new(),
+                outcome: ImportOutcome::Failed,
+                instance_name: None,
+                quote_id: None,
+                svg_path: None,
+                error: Some(error),
+            },
+        };
+        match entry.outcome {
+            ImportOutcome::Imported | ImportOutcome::WouldImport => {
+                report.imported += 1;
+                if entry.quote_id.is_none() {
+                    report.unmatched += 1;
+                }
+            }
+            ImportOutcome::AlreadyImported => report.already_imported += 1,
+            ImportOutcome::Failed => report.failed += 1,
+            ImportOutcome::NotLegacy => {}
+        }
+        report.files.push(ImportedFile { file, ..entry });
+    }
+
+    bus.emit(
+        &app_handle,
+        LEGACY_IMPORT_PROGRESS_EVENT,
+        &dir,
+        &LegacyImportProgress {
+            current: candidates.len(),
+            total: candidates.len(),
+            file: String::new(),
+        },
+    );
+    println!(
+        "📥 Legacy import{} of {}: {} results ({} unmatched), {} already imported, {} failed",
+        if dry_run { " (dry run)" } else { "" },
+        dir,
+        report.imported,
+        report.unmatched,
+        report.already_imported,
+        report.failed
+    );
+    Ok(report)
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
+
+/// SVG written next to a result, same file stem
+fn sibling_svg(path: &Path) -> Option<PathBuf> {
+    ["svg", "SVG"]
+        .into_iter()
+        .map(|extension| path.with_extension(extension))
+        .find(|svg| svg.is_file())
+}
+
+async fn import_file(
+    pool: &sqlx::SqlitePool,
+    quotes: &[QuoteKey],
+    root: &Path,
+    relative: &Path,
+    svg_dir: &Path,
+    dry_run: bool,
+) -> Result<ImportedFile, String> {
+    let path = root.join(relative);
+    let source_path = path.to_string_lossy().to_string();
+    let mut entry = ImportedFile {
+        file: String::new(),
+        outcome: ImportOutcome::NotLegacy,
+        instance_name: None,
+        quote_id: None,
+        svg_path: None,
+        error: None,
+    };
+
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let Some(result) = parse_legacy_output(&text)? else {
+        return Ok(entry);
+    };
+    entry.quote_id = result
+        .instance_name
+        .as_deref()
+        .and_then(|name| match_quote(name, quotes))
+        .map(|quote| quote.id.clone());
+    entry.instance_name = result.instance_name.clone();
+
+    let existing: Option<(i64,)> =
+        sqlx::query_as("SELECT id FROM nesting_results WHERE source_path = ?")
+            .bind(&source_path)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Failed to look up '{}': {}", source_path, e))?;
+    if existing.is_some() {
+        entry.outcome = ImportOutcome::AlreadyImported;
+        return Ok(entry);
+    }
+
+    let svg = sibling_svg(&path);
+    if dry_run {
+        entry.outcome = ImportOutcome::WouldImport;
+        entry.svg_path = svg.map(|svg| svg.to_string_lossy().to_string());
+        return Ok(entry);
+    }
+
+    // Flattened name, the source folders often reuse "result.svg"
+    let mut svg_sha256 = None;
+    if let Some(svg) = svg {
+        let name = relative
+            .with_extension("svg")
+            .to_string_lossy()
+            .replace(['/', '\\'], "__");
+        let content = std::fs::read(&svg)
+            .map_err(|e| format!("Failed to read '{}': {}", svg.display(), e))?;
+        std::fs::create_dir_all(svg_dir)
+            .map_err(|e| format!("Failed to create '{}': {}", svg_dir.display(), e))?;
+        std::fs::write(svg_dir.join(&name), &content)
+            .map_err(|e| format!("Failed to copy '{}': {}", svg.display(), e))?;
+        entry.svg_path = Some(format!("svgs/{}/{}", LEGACY_SVG_DIR, name));
+        svg_sha256 = Some(artifact_sha256(&content));
+    }
+
+    sqlx::query(
+        "INSERT INTO nesting_results
+            (quote_id, instance_name, strip_width, strip_height, density, placed_items,
+             run_time_sec, result_json, svg_path, svg_sha256, source, source_path)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'legacy_import', ?)",
+    )
+    .bind(&entry.quote_id)
+    .bind(&result.instance_name)
+    .bind(result.strip_width)
+    .bind(result.strip_height)
+    .bind(result.density)
+    .bind(result.placed_items as i64)
+    .bind(result.run_time_sec)
+    .bind(&text)
+    .bind(&entry.svg_path)
+    .bind(&svg_sha256)
+    .bind(&source_path)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to store '{}': {}", source_path, e))?;
+
+    entry.outcome = ImportOutcome::Imported;
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(id: &str, quote_number: &str, reference: Option<&str>) -> QuoteKey {
+        QuoteKey {
+            id: id.to_string(),
+            quote_number: Some(quote_number.to_string()),
+            reference: reference.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_parse_current_and_older_spellings() {
+        let current = r#"{
+            "instance": {"name": "ACME-42", "strip_height": 1500.0, "items": []},
+            "solution": {
+                "strip_width": 2310.5,
+                "layout": {"placed_items": [{"item_id": 0}, {"item_id": 1}], "density": 0.81},
+                "density": 0.8,
+                "run_time_sec": 60
+            }
+        }"#;
+        let parsed = parse_legacy_output(current).unwrap().unwrap();
+        assert_eq!(parsed.instance_name.as_deref(), Some("ACME-42"));
+        assert_eq!(parsed.strip_width, 2310.5);
+        assert_eq!(parsed.strip_height, Some(1500.0));
+        assert_eq!(parsed.density, Some(0.8));
+        assert_eq!(parsed.placed_items, 2);
+
+        let older = r#"{
+            "name": "ACME-42",
+            "solution": {"stripWidth": 1200, "placedItems": [{}], "utilization": 0.7, "runTimeSec": 5}
+        }"#;
+        let parsed = parse_legacy_output(older).unwrap().unwrap();
+        assert_eq!(parsed.instance_name.as_deref(), Some("ACME-42"));
+        assert_eq!(parsed.strip_width, 1200.0);
+        assert_eq!(parsed.density, Some(0.7));
+        assert_eq!(parsed.placed_items, 1);
+        assert_eq!(parsed.run_time_sec, Some(5.0));
+
+        assert_eq!(parse_legacy_output(r#"{"items": []}"#).unwrap(), None);
+        assert!(parse_legacy_output("{not json").is_err());
+    }
+
+    #[test]
+    fn test_match_quote_exact_then_contained_and_never_ambiguous() {
+        let quotes = [
+            quote("q1", "Q-2023-0001", Some("ACME 42")),
+            quote("q2", "Q-2023-0002", Some("Bracket")),
+            quote("q3", "Q-2023-0003", Some("Bracket")),
+        ];
+        assert_eq!(match_quote("acme_42", &quotes).unwrap().id, "q1");
+        assert_eq!(match_quote("q20230002", &quotes).unwrap().id, "q2");
+        assert_eq!(match_quote("ACME-42_run2", &quotes).unwrap().id, "q1");
+        // Two quotes share the reference
+        assert!(match_quote("bracket", &quotes).is_none());
+        assert!(match_quote("unknown job", &quotes).is_none());
+    }
+}
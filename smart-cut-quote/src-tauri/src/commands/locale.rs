@@ -0,0 +1,13 @@
+use crate::nesting_engine::{self, NumberLocale};
+
+/// Set the locale ambiguous dimension strings are read in
+///
+/// Takes the UI language tag ("vi-VN", "en-US"); "1,250" then means
+/// 1.25 in a decimal-comma locale and 1250 otherwise.
+#[tauri::command]
+pub fn set_number_locale(language_tag: String) -> Result<NumberLocale, String> {
+    let locale = NumberLocale::from_language_tag(&language_tag);
+    nesting_engine::set_number_locale(locale);
+    println!("🔢 Number locale set to {:?} ({})", locale, language_tag);
+    Ok(locale)
+}
@@ -0,0 +1,12 @@
+use crate::nesting_engine::logging::{self, LogConfig};
+
+/// Change log level and sinks at runtime
+///
+/// Applies to all records logged afterwards, including those of a
+/// nesting run that is already in progress.
+#[tauri::command]
+pub fn set_log_config(config: LogConfig) -> Result<(), String> {
+    logging::set_log_config(&config)?;
+    println!("📝 Log config changed: {:?}", config);
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use crate::db;
+use crate::nesting_engine::{
+    check_sheet_capacity, validate_keep_out_zones, ConfigSource, KeepOutZone, NestingInput,
+};
+use sqlx::SqlitePool;
+
+/// Get the clamp/fixture zones of a machine profile
+#[tauri::command]
+pub async fn get_machine_clamp_zones(
+    app_handle: tauri::AppHandle,
+    machine_id: String,
+) -> Result<Vec<KeepOutZone>, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    load_clamp_zones(&pool, &machine_id).await
+}
+
+/// Replace the clamp/fixture zones of a machine profile
+///
+/// Stored nesting results keep the zones they were computed with
+/// (inside their resolved config), so editing clamps here only
+/// affects future nesting runs.
+#[tauri::command]
+pub async fn set_machine_clamp_zones(
+    app_handle: tauri::AppHandle,
+    machine_id: String,
+    zones: Vec<KeepOutZone>,
+) -> Result<(), String> {
+    validate_keep_out_zones(&zones)?;
+
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let db_err = |e: sqlx::Error| {
+        format!(
+            "Failed to save clamp zones for machine '{}': {}",
+            machine_id, e
+        )
+    };
+
+    let mut tx = pool.begin().await.map_err(db_err)?;
+
+    sqlx::query("DELETE FROM machine_clamp_zones WHERE machine_id = ?")
+        .bind(&machine_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+
+    for (i, zone) in zones.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO machine_clamp_zones (machine_id, x, y, width, height, label, sort_order)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&machine_id)
+        .bind(zone.x)
+        .bind(zone.y)
+        .bind(zone.width)
+        .bind(zone.height)
+        .bind(&zone.label)
+        .bind(i as i64)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+    }
+
+    tx.commit().await.map_err(db_err)?;
+
+    println!(
+        "💾 Saved {} clamp zones for machine {}",
+        zones.len(),
+        machine_id
+    );
+    Ok(())
+}
+
+/// Add a machine profile's clamp zones to the keep-out zones of a nesting input
+///
+/// Also checks the parts against the machine's sheet size, if known,
+/// minus the area taken by clamps, and takes the sheet length for demand
+/// chunking from it when the input does not set one.
+pub async fn resolve_machine_keep_out(
+    app_handle: &tauri::AppHandle,
+    machine_id: &str,
+    input: &mut NestingInput,
+) -> Result<(), String> {
+    let pool = db::sqlite_pool(app_handle).await?;
+
+    let machine: Option<(Option<f64>, Option<f64>)> =
+        sqlx::query_as("SELECT max_sheet_length, max_sheet_width FROM machines WHERE id = ?")
+            .bind(machine_id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(|e| format!("Failed to load machine '{}': {}", machine_id, e))?;
+    let (sheet_length, sheet_width) =
+        machine.ok_or_else(|| format!("Machine profile '{}' not found", machine_id))?;
+
+    let clamp_zones = load_clamp_zones(&pool, machine_id).await?;
+    println!(
+        "🗜️ Machine {} adds {} clamp zones",
+        machine_id,
+        clamp_zones.len()
+    );
+
+    let input_zones = input.keep_out_zones.clone();
+    let zones = input.keep_out_zones.get_or_insert_with(Vec::new);
+    zones.extend(clamp_zones.iter().cloned());
+    input.config_sources.merge(
+        "keep_out_zones",
+        &[
+            (ConfigSource::Input, input_zones),
+            (ConfigSource::MachineProfile, Some(clamp_zones)),
+        ],
+        zones,
+    );
+
+    input.sheet_length = input.config_sources.pick(
+        "sheet_length",
+        &[
+            (ConfigSource::Input, input.sheet_length),
+            (ConfigSource::MachineProfile, sheet_length),
+        ],
+    );
+
+    // Sheet X runs along the machine length, Y along its width
+    if let (Some(length), Some(width)) = (sheet_length, sheet_width) {
+        check_sheet_capacity(&input.json_input, length, width, zones)?;
+    }
+
+    Ok(())
+}
+
+async fn load_clamp_zones(pool: &SqlitePool, machine_id: &str) -> Result<Vec<KeepOutZone>, String> {
+    let rows: Vec<(f64, f64, f64, f64, Option<String>)> = sqlx::query_as(
+        "SELECT x, y, width, height, label FROM machine_clamp_zones
+         WHERE machine_id = ? ORDER BY sort_order, id",
+    )
+    .bind(machine_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        format!(
+            "Failed to load clamp zones for machine '{}': {}",
+            machine_id, e
+        )
+    })?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(x, y, width, height, label)| KeepOutZone {
+            x,
+            y,
+            width,
+            height,
+            label,
+        })
+        .collect())
+}
@@ -0,0 +1,493 @@
+use crate::db;
+use crate::nesting_engine::{parse_number, DisplayUnits};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+
+/// Setting holding the extra sheets ordered on top of the requirement (percent)
+const SCRAP_ALLOWANCE_SETTING: &str = "material_scrap_allowance_percent";
+const DEFAULT_SCRAP_ALLOWANCE_PERCENT: f64 = 5.0;
+
+/// One line of the purchasing table (one material + thickness)
+#[derive(Serialize, Debug, Clone)]
+pub struct MaterialRequirement {
+    pub material: String,
+    pub grade: Option<String>,
+    pub thickness: f64,
+    /// Matching `material_stock` row, None if the material is not stocked
+    pub stock_id: Option<String>,
+    pub sheet_width: Option<f64>,
+    pub sheet_length: Option<f64>,
+    /// Sheets needed for the parts, before scrap allowance
+    pub sheets_required: u32,
+    /// Sheets to order, including scrap allowance
+    pub sheets: u32,
+    /// Total area of the parts in this group (mm²)
+    pub part_area: f64,
+    pub weight_kg: f64,
+    pub cost: f64,
+    /// Sheet count estimated from part area (group has no nesting result)
+    pub estimated: bool,
+}
+
+impl MaterialRequirement {
+    /// Same row with thickness, sheet size and area in `units` (computed in mm)
+    pub(crate) fn in_units(self, units: DisplayUnits) -> Self {
+        MaterialRequirement {
+            thickness: units.length(self.thickness),
+            sheet_width: self.sheet_width.map(|width| units.length(width)),
+            sheet_length: self.sheet_length.map(|length| units.length(length)),
+            part_area: units.area(self.part_area),
+            ..self
+        }
+    }
+}
+
+/// Purchasing table of a quote with totals
+#[derive(Serialize, Debug)]
+pub struct MaterialRequirements {
+    pub quote_id: String,
+    /// Unit of the lengths in `rows`, areas are in its square
+    pub units: DisplayUnits,
+    pub scrap_allowance_percent: f64,
+    pub rows: Vec<MaterialRequirement>,
+    pub total_sheets: u32,
+    pub total_weight_kg: f64,
+    pub total_cost: f64,
+    /// True if any row is an area-based estimate
+    pub has_estimates: bool,
+}
+
+// Subset of the quote JSON blob (see quoteService.ts)
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct QuoteData {
+    #[serde(default)]
+    files: Vec<QuoteFile>,
+    nesting_result: Option<NestedExtent>,
+    /// Nesting results of a batched run, one per material + thickness
+    #[serde(default)]
+    nesting_results_by_material: Vec<NestedExtent>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct QuoteFile {
+    #[serde(default = "default_quantity")]
+    quantity: u32,
+    selected: Option<bool>,
+    material: Option<FileMaterial>,
+    material_group: Option<String>,
+    material_grade: Option<String>,
+    material_thickness: Option<f64>,
+    metadata: Option<FileMetadata>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FileMaterial {
+    name: String,
+    grade: Option<String>,
+    thickness: f64,
+}
+
+#[derive(Deserialize, Debug)]
+struct FileMetadata {
+    area: f64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct NestedExtent {
+    material_group: Option<String>,
+    material_thickness: Option<f64>,
+    strip_width: f64,
+    strip_height: f64,
+}
+
+fn default_quantity() -> u32 {
+    1
+}
+
+/// Parts of one material + thickness (a nesting batch)
+#[derive(Debug)]
+struct MaterialGroup {
+    material: String,
+    grade: Option<String>,
+    thickness: f64,
+    part_area: f64,
+    nesting: Option<NestedExtent>,
+}
+
+/// Stock sheet used to buy a material group
+#[derive(Debug, Clone)]
+struct StockSheet {
+    id: String,
+    width: f64,
+    length: f64,
+    price_per_kg: f64,
+    density: f64,
+}
+
+/// Same key as the frontend batch key (`Material-Thicknessmm`)
+pub(crate) fn group_key(material: &str, thickness: f64) -> String {
+    format!("{}-{}mm", material, thickness)
+}
+
+/// Group the active files of a quote by material and thickness
+fn group_files(data: &QuoteData) -> Vec<MaterialGroup> {
+    let mut groups: BTreeMap<String, MaterialGroup> = BTreeMap::new();
+
+    for file in data.files.iter().filter(|f| f.selected != Some(false)) {
+        let material = file
+            .material_group
+            .clone()
+            .or_else(|| file.material.as_ref().map(|m| m.name.clone()))
+            .unwrap_or_else(|| "Unknown".to_string());
+        let thickness = file
+            .material_thickness
+            .or_else(|| file.material.as_ref().map(|m| m.thickness))
+            .unwrap_or(0.0);
+        let grade = file
+            .material_grade
+            .clone()
+            .or_else(|| file.material.as_ref().and_then(|m| m.grade.clone()));
+        let area = file.metadata.as_ref().map_or(0.0, |m| m.area) * file.quantity as f64;
+
+        let group = groups
+            .entry(group_key(&material, thickness))
+            .or_insert_with(|| MaterialGroup {
+                material,
+                grade,
+                thickness,
+                part_area: 0.0,
+                nesting: None,
+            });
+        group.part_area += area;
+    }
+
+    for nested in &data.nesting_results_by_material {
+        if let (Some(material), Some(thickness)) =
+            (&nested.material_group, nested.material_thickness)
+        {
+            if let Some(group) = groups.get_mut(&group_key(material, thickness)) {
+                group.nesting = Some(nested.clone());
+            }
+        }
+    }
+
+    // A single (unbatched) nesting result belongs to the only group
+    if groups.len() == 1 && data.nesting_results_by_material.is_empty() {
+        if let Some(group) = groups.values_mut().next() {
+            group.nesting = data.nesting_result.clone();
+        }
+    }
+
+    groups.into_values().collect()
+}
+
+/// Sheets covering a nested strip, in the better of both sheet orientations
+fn sheets_for_extent(strip_width: f64, strip_height: f64, sheet: &StockSheet) -> u32 {
+    let count = |along_x: f64, along_y: f64| {
+        (strip_width / along_x).ceil().max(1.0) * (strip_height / along_y).ceil().max(1.0)
+    };
+    count(sheet.length, sheet.width).min(count(sheet.width, sheet.length)) as u32
+}
+
+/// Weight of a plate (mm² area, mm thickness, kg/m³ density)
+fn weight_kg(area: f64, thickness: f64, density: f64) -> f64 {
+    area * thickness * 1e-9 * density
+}
+
+fn compute_requirement(
+    group: MaterialGroup,
+    stock: Option<StockSheet>,
+    density: Option<f64>,
+    scrap_allowance_percent: f64,
+) -> MaterialRequirement {
+    let estimated = group.nesting.is_none();
+
+    let Some(stock) = stock else {
+        // Not stocked: only the parts themselves can be weighed
+        let weight = density.map_or(0.0, |d| weight_kg(group.part_area, group.thickness, d));
+        return MaterialRequirement {
+            material: group.material,
+            grade: group.grade,
+            thickness: group.thickness,
+            stock_id: None,
+            sheet_width: None,
+            sheet_length: None,
+            sheets_required: 0,
+            sheets: 0,
+            part_area: group.part_area,
+            weight_kg: weight,
+            cost: 0.0,
+            estimated,
+        };
+    };
+
+    let sheet_area = stock.width * stock.length;
+    let sheets_required = match &group.nesting {
+        Some(nested) => sheets_for_extent(nested.strip_width, nested.strip_height, &stock),
+        None if sheet_area > 0.0 => (group.part_area / sheet_area).ceil() as u32,
+        None => 0,
+    };
+    // Tolerance keeps e.g. 10 sheets at +10% from rounding up to 12
+    let sheets =
+        (sheets_required as f64 * (1.0 + scrap_allowance_percent / 100.0) - 1e-9).ceil() as u32;
+
+    let density = density.unwrap_or(stock.density);
+    let weight = weight_kg(sheet_area * sheets as f64, group.thickness, density);
+
+    MaterialRequirement {
+        material: group.material,
+        grade: group.grade,
+        thickness: group.thickness,
+        stock_id: Some(stock.id),
+        sheet_width: Some(stock.width),
+        sheet_length: Some(stock.length),
+        sheets_required,
+        sheets,
+        part_area: group.part_area,
+        weight_kg: weight,
+        cost: weight * stock.price_per_kg,
+        estimated,
+    }
+}
+
+/// Compute the material purchasing table of a quote
+///
+/// Walks the quote's active parts per material + thickness. Groups with
+/// a nesting result are converted to whole stock sheets from
+/// `material_stock`; groups without one fall back to part area divided
+/// by sheet area and are flagged as `estimated`. The configured scrap
+/// allowance is added to every group before weight and cost.
+/// Lengths are in mm unless other `units` are requested.
+#[tauri::command]
+pub async fn compute_material_requirements(
+    app_handle: tauri::AppHandle,
+    quote_id: String,
+    units: Option<DisplayUnits>,
+) -> Result<MaterialRequirements, String> {
+    let units = units.unwrap_or_default();
+    let pool = db::sqlite_pool(&app_handle).await?;
+
+    let row: Option<(Option<String>,)> = sqlx::query_as("SELECT data FROM quotes WHERE id = ?")
+        .bind(&quote_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to load quote '{}': {}", quote_id, e))?;
+    let (data,) = row.ok_or_else(|| format!("Quote '{}' not found", quote_id))?;
+    let data: QuoteData = match data {
+        Some(data) => serde_json::from_str(&data)
+            .map_err(|e| format!("Quote '{}' has invalid data: {}", quote_id, e))?,
+        None => QuoteData::default(),
+    };
+
+    let scrap_allowance_percent = load_scrap_allowance(&pool).await?;
+
+    let mut rows = Vec::new();
+    for group in group_files(&data) {
+        let stock = load_stock_sheet(&pool, &group).await?;
+        let density = load_density(&pool, &group.material).await?;
+        rows.push(
+            compute_requirement(group, stock, density, scrap_allowance_percent).in_units(units),
+        );
+    }
+
+    let requirements = MaterialRequirements {
+        quote_id,
+        units,
+        scrap_allowance_percent,
+        total_sheets: rows.iter().map(|r| r.sheets).sum(),
+        total_weight_kg: rows.iter().map(|r| r.weight_kg).sum(),
+        total_cost: rows.iter().map(|r| r.cost).sum(),
+        has_estimates: rows.iter().any(|r| r.estimated),
+        rows,
+    };
+
+    println!(
+        "🧾 Material requirements for quote {}: {} groups, {} sheets, {:.1} kg",
+        requirements.quote_id,
+        requirements.rows.len(),
+        requirements.total_sheets,
+        requirements.total_weight_kg
+    );
+    Ok(requirements)
+}
+
+/// Purchasing row of a nested strip of one material + thickness
+///
+/// Used for runs not stored with a quote, e.g. consolidated production
+/// runs; the scrap allowance applies as for a quote.
+pub(crate) async fn nested_requirement(
+    pool: &SqlitePool,
+    material: &str,
+    grade: Option<String>,
+    thickness: f64,
+    part_area: f64,
+    strip_width: f64,
+    strip_height: f64,
+) -> Result<MaterialRequirement, String> {
+    let group = MaterialGroup {
+        material: material.to_string(),
+        grade,
+        thickness,
+        part_area,
+        nesting: Some(NestedExtent {
+            material_group: None,
+            material_thickness: None,
+            strip_width,
+            strip_height,
+        }),
+    };
+    let scrap_allowance_percent = load_scrap_allowance(pool).await?;
+    let stock = load_stock_sheet(pool, &group).await?;
+    let density = load_density(pool, material).await?;
+    Ok(compute_requirement(
+        group,
+        stock,
+        density,
+        scrap_allowance_percent,
+    ))
+}
+
+async fn load_scrap_allowance(pool: &SqlitePool) -> Result<f64, String> {
+    let value: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = ?")
+        .bind(SCRAP_ALLOWANCE_SETTING)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to load scrap allowance: {}", e))?;
+
+    Ok(value
+        .and_then(|(value,)| parse_number(&value).ok())
+        .unwrap_or(DEFAULT_SCRAP_ALLOWANCE_PERCENT))
+}
+
+async fn load_stock_sheet(
+    pool: &SqlitePool,
+    group: &MaterialGroup,
+) -> Result<Option<StockSheet>, String> {
+    // Prefer the part's grade, otherwise any active grade of the material
+    let row: Option<(String, f64, f64, f64, f64)> = sqlx::query_as(
+        "SELECT id, sheet_width, sheet_max_length, price_per_kg, density FROM material_stock
+         WHERE name = ? AND ABS(thickness - ?) < 0.001 AND is_active = 1
+         ORDER BY (grade = ?) DESC, id
+         LIMIT 1",
+    )
+    .bind(&group.material)
+    .bind(group.thickness)
+    .bind(&group.grade)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| {
+        format!(
+            "Failed to load stock for {} {}mm: {}",
+            group.material, group.thickness, e
+        )
+    })?;
+
+    Ok(
+        row.map(|(id, width, length, price_per_kg, density)| StockSheet {
+            id,
+            width,
+            length,
+            price_per_kg,
+            density,
+        }),
+    )
+}
+
+async fn load_density(pool: &SqlitePool, material: &str) -> Result<Option<f64>, String> {
+    let row: Option<(f64,)> =
+        sqlx::query_as("SELECT density FROM material_densities WHERE material_name = ?")
+            .bind(material)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Failed to load density of '{}': {}", material, e))?;
+
+    Ok(row.map(|(density,)| density))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stock() -> StockSheet {
+        StockSheet {
+            id: "ms_2.0".to_string(),
+            width: 1500.0,
+            length: 6000.0,
+            price_per_kg: 2.5,
+            density: 7850.0,
+        }
+    }
+
+    #[test]
+    fn test_group_files_uses_batch_results_and_flags_missing() {
+        let data: QuoteData = serde_json::from_str(
+            r#"{
+                "files": [
+                    {"quantity": 4, "materialGroup": "Mild Steel", "materialThickness": 2, "metadata": {"area": 100000}},
+                    {"quantity": 2, "materialGroup": "Mild Steel", "materialThickness": 2, "metadata": {"area": 50000}},
+                    {"quantity": 1, "materialGroup": "Aluminum", "materialThickness": 1, "metadata": {"area": 1000}},
+                    {"quantity": 9, "materialGroup": "Aluminum", "materialThickness": 3, "selected": false}
+                ],
+                "nestingResultsByMaterial": [
+                    {"materialGroup": "Mild Steel", "materialThickness": 2, "stripWidth": 2000, "stripHeight": 1500}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let groups = group_files(&data);
+        assert_eq!(groups.len(), 2);
+        let steel = groups.iter().find(|g| g.material == "Mild Steel").unwrap();
+        assert_eq!(steel.part_area, 500000.0);
+        assert!(steel.nesting.is_some());
+        let aluminum = groups.iter().find(|g| g.material == "Aluminum").unwrap();
+        assert!(aluminum.nesting.is_none());
+    }
+
+    #[test]
+    fn test_nested_group_orders_whole_sheets_with_allowance() {
+        let group = MaterialGroup {
+            material: "Mild Steel".to_string(),
+            grade: None,
+            thickness: 2.0,
+            part_area: 1.0,
+            nesting: Some(NestedExtent {
+                material_group: None,
+                material_thickness: None,
+                strip_width: 4000.0,
+                strip_height: 6000.0,
+            }),
+        };
+
+        // 4000 x 6000 strip = 3 sheets of 1500 x 6000, +5% rounds up to 4
+        let row = compute_requirement(group, Some(stock()), None, 5.0);
+        assert!(!row.estimated);
+        assert_eq!(row.sheets_required, 3);
+        assert_eq!(row.sheets, 4);
+        let expected_weight = 4.0 * 1500.0 * 6000.0 * 2.0 * 1e-9 * 7850.0;
+        assert!((row.weight_kg - expected_weight).abs() < 1e-9);
+        assert!((row.cost - expected_weight * 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_group_without_nesting_falls_back_to_area() {
+        let group = MaterialGroup {
+            material: "Mild Steel".to_string(),
+            grade: None,
+            thickness: 2.0,
+            part_area: 1.5 * 1500.0 * 6000.0,
+            nesting: None,
+        };
+
+        let row = compute_requirement(group, Some(stock()), Some(7800.0), 0.0);
+        assert!(row.estimated);
+        assert_eq!(row.sheets, 2);
+        // Density table takes precedence over the stock row
+        let expected_weight = 2.0 * 1500.0 * 6000.0 * 2.0 * 1e-9 * 7800.0;
+        assert!((row.weight_kg - expected_weight).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,506 @@
+//! Startup migration runner
+//!
+//! tauri-plugin-sql applies pending migrations when the frontend first
+//! loads the database, and a failing script leaves the migrations before
+//! it applied. This runner applies them at startup instead: it backs up
+//! the database file, runs every migration in its own transaction through
+//! the sqlx migrator the plugin uses (same `_sqlx_migrations` rows and
+//! checksums, so the plugin finds nothing left to do) and restores the
+//! backup when one of them fails.
+
+use crate::db;
+use serde::Serialize;
+use sqlx::error::BoxDynError;
+use sqlx::migrate::{Migration as SqlxMigration, MigrationSource, MigrationType, Migrator};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+use tauri_plugin_sql::{Migration, MigrationKind};
+
+/// Event emitted when the database could not be migrated at startup
+pub const STARTUP_ERROR_EVENT: &str = "startup-error";
+
+/// Folder next to the database holding pre-migration backups
+const BACKUP_DIR: &str = "backups";
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MigrationFailure {
+    /// First migration that did not apply, None if the runner failed before
+    pub version: Option<i64>,
+    pub message: String,
+    pub backup_path: Option<String>,
+    /// True when the database file is back in its pre-migration state
+    pub restored: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MigrationRun {
+    pub applied: Vec<i64>,
+    pub backup_path: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub description: String,
+    pub installed_on: String,
+    pub success: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PendingMigration {
+    pub version: i64,
+    pub description: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MigrationStatus {
+    pub latest_version: i64,
+    pub applied: Vec<AppliedMigration>,
+    pub pending: Vec<PendingMigration>,
+    /// Backup written before this session's migrations, if any ran
+    pub backup_path: Option<String>,
+    pub startup_error: Option<MigrationFailure>,
+}
+
+/// Outcome of the startup migration run
+#[derive(Default)]
+pub struct MigrationState {
+    backup_path: Mutex<Option<String>>,
+    startup_error: Mutex<Option<MigrationFailure>>,
+}
+
+impl MigrationState {
+    /// Failure of the startup migration run, if any
+    pub(crate) fn startup_error(&self) -> Option<MigrationFailure> {
+        self.startup_error.lock().unwrap().clone()
+    }
+}
+
+type ResolveFuture<'s> =
+    Pin<Box<dyn Future<Output = Result<Vec<SqlxMigration>, BoxDynError>> + Send + 's>>;
+
+/// Up migrations converted the way tauri-plugin-sql converts them
+#[derive(Debug)]
+struct UpMigrations(Vec<SqlxMigration>);
+
+impl UpMigrations {
+    fn new(migrations: Vec<Migration>) -> Self {
+        UpMigrations(
+            migrations
+                .into_iter()
+                .filter(|migration| matches!(migration.kind, MigrationKind::Up))
+                .map(|migration| {
+                    SqlxMigration::new(
+                        migration.version,
+                        migration.description.into(),
+                        MigrationType::ReversibleUp,
+                        migration.sql.into(),
+                        false,
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<'s> MigrationSource<'s> for UpMigrations {
+    fn resolve(self) -> ResolveFuture<'s> {
+        Box::pin(async move { Ok(self.0) })
+    }
+}
+
+/// Apply pending migrations before the frontend loads the database
+///
+/// Called from `setup`. A failure does not stop the app: it is kept for
+/// `get_migration_status`, emitted as `startup-error` and shown in an
+/// error dialog, and the database file is left as it was.
+pub fn run_startup_migrations(app_handle: &AppHandle) {
+    let outcome = db::db_file_path(app_handle)
+        .map_err(|message| MigrationFailure {
+            version: None,
+            message,
+            backup_path: None,
+            restored: false,
+        })
+        .and_then(|db_path| {
+            let backup_dir = db_path
+                .parent()
+                .map(|dir| dir.join(BACKUP_DIR))
+                .unwrap_or_else(|| PathBuf::from(BACKUP_DIR));
+            tauri::async_runtime::block_on(migrate_database(
+                &db_path,
+                &backup_dir,
+                crate::get_migrations(),
+            ))
+        });
+
+    let state = app_handle.state::<MigrationState>();
+    match outcome {
+        Ok(run) => {
+            if !run.applied.is_empty() {
+                println!(
+                    "🗄️ Applied migrations {:?} (backup: {})",
+                    run.applied,
+                    run.backup_path.as_deref().unwrap_or("new database")
+                );
+            }
+            *state.backup_path.lock().unwrap() = run.backup_path;
+        }
+        Err(failure) => {
+            eprintln!("❌ Database migration failed: {}", failure.message);
+            let _ = app_handle.emit(STARTUP_ERROR_EVENT, &failure);
+            app_handle
+                .dialog()
+                .message(failure_message(&failure))
+                .title("Database update failed")
+                .kind(MessageDialogKind::Error)
+                .show(|_| {});
+            *state.backup_path.lock().unwrap() = failure.backup_path.clone();
+            *state.startup_error.lock().unwrap() = Some(failure);
+        }
+    }
+}
+
+/// Applied and pending migrations of the database file
+#[tauri::command]
+pub async fn get_migration_status(
+    app_handle: AppHandle,
+    state: tauri::State<'_, MigrationState>,
+) -> Result<MigrationStatus, String> {
+    let applied = match db::sqlite_pool(&app_handle).await {
+        Ok(pool) => applied_migrations(&pool).await?,
+        Err(_) => {
+            let db_path = db::db_file_path(&app_handle)?;
+            if db_path.exists() {
+                let pool = open_pool(&db_path, false).await?;
+                let applied = applied_migrations(&pool).await;
+                pool.close().await;
+                applied?
+            } else {
+                Vec::new()
+            }
+        }
+    };
+
+    Ok(MigrationStatus {
+        latest_version: db::latest_schema_version(),
+        pending: pending_migrations(&crate::get_migrations(), &applied),
+        applied,
+        backup_path: state.backup_path.lock().unwrap().clone(),
+        startup_error: state.startup_error.lock().unwrap().clone(),
+    })
+}
+
+/// Back up `db_path`, apply pending migrations and restore on failure
+pub(crate) async fn migrate_database(
+    db_path: &Path,
+    backup_dir: &Path,
+    migrations: Vec<Migration>,
+) -> Result<MigrationRun, MigrationFailure> {
+    let failure = |message: String| MigrationFailure {
+        version: None,
+        message,
+        backup_path: None,
+        restored: false,
+    };
+
+    let existed = db_path.exists();
+    if let Some(dir) = db_path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| failure(format!("Failed to create database directory: {}", e)))?;
+    }
+    let pool = open_pool(db_path, true).await.map_err(failure)?;
+
+    let applied = match applied_migrations(&pool).await {
+        Ok(applied) => applied,
+        Err(message) => {
+            pool.close().await;
+            return Err(failure(message));
+        }
+    };
+    let pending = pending_migrations(&migrations, &applied);
+    if pending.is_empty() {
+        pool.close().await;
+        return Ok(MigrationRun::default());
+    }
+
+    let mut backup_path = None;
+    if existed {
+        let path = backup_dir.join(format!(
+            "smart_cut_quote-before-v{}-{}.db",
+            pending.last().map_or(0, |migration| migration.version),
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        let backup = match std::fs::create_dir_all(backup_dir) {
+            Ok(()) => db::backup_database(&pool, &path).await,
+            Err(e) => Err(format!("Failed to create backup directory: {}", e)),
+        };
+        if let Err(message) = backup {
+            pool.close().await;
+            return Err(failure(message));
+        }
+        backup_path = Some(path);
+    }
+    let backup_name = backup_path
+        .as_ref()
+        .map(|path| path.to_string_lossy().to_string());
+
+    let result = match Migrator::new(UpMigrations::new(migrations)).await {
+        Ok(migrator) => migrator.run(&pool).await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    let now_applied = applied_migrations(&pool).await.unwrap_or_default();
+    pool.close().await;
+
+    match result {
+        Ok(()) => Ok(MigrationRun {
+            applied: pending.iter().map(|migration| migration.version).collect(),
+            backup_path: backup_name,
+        }),
+        Err(message) => {
+            let version = pending_migrations_from(&pending, &now_applied)
+                .first()
+                .map(|migration| migration.version);
+            let restored = restore_database(db_path, backup_path.as_deref()).is_ok();
+            Err(MigrationFailure {
+                version,
+                message: format!(
+                    "Migration {} failed: {}",
+                    version.map_or("?".to_string(), |v| v.to_string()),
+                    message
+                ),
+                backup_path: backup_name,
+                restored,
+            })
+        }
+    }
+}
+
+/// Put the backup back in place, or remove a database created by the run
+fn restore_database(db_path: &Path, backup_path: Option<&Path>) -> Result<(), String> {
+    for suffix in ["-wal", "-shm"] {
+        let mut side_file = db_path.as_os_str().to_owned();
+        side_file.push(suffix);
+        let side_file = PathBuf::from(side_file);
+        if side_file.exists() {
+            std::fs::remove_file(&side_file)
+                .map_err(|e| format!("Failed to remove {}: {}", side_file.display(), e))?;
+        }
+    }
+
+    match backup_path {
+        Some(backup) => std::fs::copy(backup, db_path)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to restore database backup: {}", e)),
+        None => std::fs::remove_file(db_path)
+            .map_err(|e| format!("Failed to remove new database: {}", e)),
+    }
+}
+
+pub(crate) async fn open_pool(db_path: &Path, create: bool) -> Result<SqlitePool, String> {
+    let options = SqliteConnectOptions::new()
+        .filename(db_path)
+        .create_if_missing(create);
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .map_err(|e| format!("Failed to open database {}: {}", db_path.display(), e))
+}
+
+/// Rows of `_sqlx_migrations`, empty before the first migration
+async fn applied_migrations(pool: &SqlitePool) -> Result<Vec<AppliedMigration>, String> {
+    let table: Option<(String,)> = sqlx::query_as(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to read migration table: {}", e))?;
+    if table.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let rows: Vec<(i64, String, String, bool)> = sqlx::query_as(
+        "SELECT version, description, CAST(installed_on AS TEXT), success
+         FROM _sqlx_migrations ORDER BY version",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to read applied migrations: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(version, description, installed_on, success)| AppliedMigration {
+                version,
+                description,
+                installed_on,
+                success,
+            },
+        )
+        .collect())
+}
+
+/// Up migrations without a successful `_sqlx_migrations` row
+fn pending_migrations(
+    migrations: &[Migration],
+    applied: &[AppliedMigration],
+) -> Vec<PendingMigration> {
+    let up: Vec<PendingMigration> = migrations
+        .iter()
+        .filter(|migration| matches!(migration.kind, MigrationKind::Up))
+        .map(|migration| PendingMigration {
+            version: migration.version,
+            description: migration.description.to_string(),
+        })
+        .collect();
+    pending_migrations_from(&up, applied)
+}
+
+fn pending_migrations_from(
+    migrations: &[PendingMigration],
+    applied: &[AppliedMigration],
+) -> Vec<PendingMigration> {
+    let mut pending: Vec<PendingMigration> = migrations
+        .iter()
+        .filter(|migration| {
+            !applied
+                .iter()
+                .any(|row| row.version == migration.version && row.success)
+        })
+        .cloned()
+        .collect();
+    pending.sort_by_key(|migration| migration.version);
+    pending
+}
+
+fn failure_message(failure: &MigrationFailure) -> String {
+    let state = if failure.restored {
+        "The database was restored to its state before the update."
+    } else {
+        "The database could not be restored automatically."
+    };
+    match &failure.backup_path {
+        Some(backup) => format!("{}\n\n{}\nBackup: {}", failure.message, state, backup),
+        None => format!("{}\n\n{}", failure.message, state),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_dir;
+    use tauri::async_runtime::block_on;
+
+    fn migration(version: i64, sql: &'static str) -> Migration {
+        Migration {
+            version,
+            description: "test_migration",
+            sql,
+            kind: MigrationKind::Up,
+        }
+    }
+
+    fn base_migrations() -> Vec<Migration> {
+        vec![
+            migration(1, "CREATE TABLE parts (id TEXT PRIMARY KEY, name TEXT);"),
+            migration(2, "INSERT INTO parts (id, name) VALUES ('p1', 'bracket');"),
+        ]
+    }
+
+    async fn read_state(db_path: &Path) -> (Vec<i64>, Vec<String>, Vec<(String, String)>) {
+        let pool = open_pool(db_path, false).await.unwrap();
+        let versions = applied_migrations(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|row| row.version)
+            .collect();
+        let columns: Vec<(String,)> = sqlx::query_as("SELECT name FROM pragma_table_info('parts')")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT id, name FROM parts")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+        (
+            versions,
+            columns.into_iter().map(|(name,)| name).collect(),
+            rows,
+        )
+    }
+
+    #[test]
+    fn test_failing_migration_leaves_database_unchanged() {
+        let dir = test_dir("migrations", "failing");
+        let db_path = dir.join("app.db");
+        let backup_dir = dir.join(BACKUP_DIR);
+
+        block_on(async {
+            migrate_database(&db_path, &backup_dir, base_migrations())
+                .await
+                .unwrap();
+            let before = read_state(&db_path).await;
+
+            let mut migrations = base_migrations();
+            migrations.push(migration(3, "ALTER TABLE parts ADD COLUMN thickness REAL;"));
+            migrations.push(migration(
+                4,
+                "UPDATE parts SET name = 'x'; CREATE TABLE broken (",
+            ));
+            let failure = migrate_database(&db_path, &backup_dir, migrations)
+                .await
+                .unwrap_err();
+
+            assert_eq!(failure.version, Some(4));
+            assert!(failure.restored);
+            assert!(Path::new(failure.backup_path.as_deref().unwrap()).exists());
+            // Migration 3 succeeded on its own but is rolled back with the backup
+            assert_eq!(read_state(&db_path).await, before);
+            assert_eq!(before.0, vec![1, 2]);
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_pending_migrations_apply_once() {
+        let dir = test_dir("migrations", "apply");
+        let db_path = dir.join("app.db");
+        let backup_dir = dir.join(BACKUP_DIR);
+
+        block_on(async {
+            let first = migrate_database(&db_path, &backup_dir, base_migrations())
+                .await
+                .unwrap();
+            // Nothing to back up for a new database
+            assert_eq!(first.applied, vec![1, 2]);
+            assert!(first.backup_path.is_none());
+
+            let mut migrations = base_migrations();
+            migrations.push(migration(3, "ALTER TABLE parts ADD COLUMN thickness REAL;"));
+            let second = migrate_database(&db_path, &backup_dir, migrations)
+                .await
+                .unwrap();
+            assert_eq!(second.applied, vec![3]);
+            assert!(second.backup_path.is_some());
+
+            let (versions, columns, _) = read_state(&db_path).await;
+            assert_eq!(versions, vec![1, 2, 3]);
+            assert!(columns.contains(&"thickness".to_string()));
+
+            let again = migrate_database(&db_path, &backup_dir, base_migrations())
+                .await
+                .unwrap();
+            assert!(again.applied.is_empty());
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
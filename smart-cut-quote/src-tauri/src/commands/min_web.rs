@@ -0,0 +1,68 @@
+use crate::db;
+use crate::nesting_engine::{self, ConfigSource, MinWebReport, NestingInput};
+
+/// Check the parts of an instance for webs narrower than `min_web` (mm)
+///
+/// Measures the gap between every hole and the outer contour and
+/// between every pair of holes. Violations are returned as warnings;
+/// with `strict` they fail the command instead.
+#[tauri::command]
+pub async fn check_min_web(
+    instance_json: String,
+    min_web: f64,
+    strict: Option<bool>,
+) -> Result<MinWebReport, String> {
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        let instance = nesting_engine::parse_instance(&instance_json)?;
+        nesting_engine::check_min_web(&instance, min_web)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))??;
+
+    println!(
+        "📏 Min web {}mm: {} violations in {} items",
+        report.min_web,
+        report.violations.len(),
+        report.items_checked
+    );
+    if strict.unwrap_or(false) && !report.violations.is_empty() {
+        return Err(report.error_message());
+    }
+    Ok(report)
+}
+
+/// Use the material's minimum web and kerf for a nesting input without them
+///
+/// Materials without a minimum web leave the check disabled; the kerf
+/// only feeds the run suggestions.
+pub async fn resolve_material_min_web(
+    app_handle: &tauri::AppHandle,
+    material_id: &str,
+    input: &mut NestingInput,
+) -> Result<(), String> {
+    if input.min_web.is_some() && input.kerf.is_some() {
+        return Ok(());
+    }
+
+    let pool = db::sqlite_pool(app_handle).await?;
+    let row: Option<(Option<f64>, Option<f64>)> =
+        sqlx::query_as("SELECT min_web, kerf FROM material_stock WHERE id = ?")
+            .bind(material_id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(|e| format!("Failed to load material '{}': {}", material_id, e))?;
+    let (min_web, kerf) = row.ok_or_else(|| format!("Material '{}' not found", material_id))?;
+
+    input.min_web = input.config_sources.pick(
+        "min_web",
+        &[
+            (ConfigSource::Input, input.min_web),
+            (ConfigSource::Material, min_web),
+        ],
+    );
+    input.kerf = input.config_sources.pick(
+        "kerf",
+        &[(ConfigSource::Input, input.kerf), (ConfigSource::Material, kerf)],
+    );
+    Ok(())
+}
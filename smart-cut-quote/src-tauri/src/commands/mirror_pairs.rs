@@ -0,0 +1,25 @@
+use crate::nesting_engine::{self, MirrorPair, DEFAULT_MIRROR_TOLERANCE};
+
+/// Items of an instance that are the mirror image of another item
+///
+/// Lets the UI offer consolidation before the run: a confirmed run sets
+/// `allow_mirror` and `consolidate_mirror_pairs`. Vertices may differ by
+/// `tolerance` mm (default 0.05mm).
+#[tauri::command]
+pub async fn detect_mirror_pairs(
+    instance_json: String,
+    tolerance: Option<f64>,
+) -> Result<Vec<MirrorPair>, String> {
+    let instance = nesting_engine::parse_instance(&instance_json)?;
+    let pairs = nesting_engine::detect_mirror_pairs(
+        &instance,
+        tolerance.unwrap_or(DEFAULT_MIRROR_TOLERANCE),
+    );
+
+    println!(
+        "🪞 {} mirror pairs in {} items",
+        pairs.len(),
+        instance.items.len()
+    );
+    Ok(pairs)
+}
@@ -1,2 +1,49 @@
+pub mod adjustment;
+pub mod artifacts;
+// CI benchmark hook, left out of release builds
+#[cfg(debug_assertions)]
+pub mod bench;
+pub mod consolidation;
+pub mod conversion_jobs;
+pub mod cutting_profiles;
+pub mod dashboard_server;
+pub mod demo_data;
+pub mod drafts;
 pub mod dxf_converter;
+pub mod event_bus;
+pub mod export_naming;
+pub mod instance_blobs;
+pub mod instance_diff;
+pub mod job_status;
+pub mod layout_export;
+pub mod layout_verification;
+pub mod legacy_import;
+pub mod locale;
+pub mod logging;
+pub mod machine_profiles;
+pub mod material_requirements;
+pub mod migrations;
+pub mod min_web;
+pub mod mirror_pairs;
+pub mod nesting_batch;
+pub mod nesting_jobs;
+pub mod nesting_manager;
+pub mod nesting_metrics;
+pub mod pipeline;
+pub mod price_matrix;
+pub mod quote_nesting;
+pub mod quote_save;
+pub mod response;
+pub mod result_improvement;
+pub mod sheet_order;
+pub mod shutdown;
+pub mod source_watch;
 pub mod sparrow_cli;
+pub mod standard_materials;
+pub mod startup_check;
+pub mod strip_height;
+pub mod subset_renest;
+pub mod support_bundle;
+pub mod tool_process;
+pub mod webhook;
+pub mod workspace_archive;
@@ -0,0 +1,245 @@
+//! Batch nesting with per-group settings
+//!
+//! A batch holds one nesting group per material + thickness. Each group
+//! may override any optimizer setting; unset settings come from the batch
+//! default, then from the app settings (see `ConfigOverrides::resolve`).
+//!
+//! With `total_time_budget_secs` the groups share one time budget instead
+//! of each running for its own time limit (see `BatchTimeBudget`).
+
+use crate::commands::job_status::{JobEntry, JobKind, JobRegistry, JobStatus, StepProgress};
+use crate::commands::nesting_metrics::{record_nesting_run, NestingRunMetrics};
+use crate::commands::shutdown;
+use crate::db;
+use crate::nesting_engine::{
+    self, instance_difficulty, parse_number, BatchTimeBudget, ConfigOverrides, DisplayUnits,
+    GroupTimeBudget, NestingConfig, NestingOutput,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+/// App setting holding the default time limit in seconds
+const TIME_LIMIT_SETTING: &str = "nesting_time_limit";
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct NestingGroup {
+    /// Identifies the group in the output, e.g. "aluminum-3mm"
+    pub key: String,
+    pub json_input: String,
+    #[serde(default)]
+    pub config: ConfigOverrides,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct NestingBatch {
+    pub groups: Vec<NestingGroup>,
+    /// Settings for groups that do not set their own
+    #[serde(default)]
+    pub defaults: ConfigOverrides,
+    /// Return where every setting of a group came from (see `NestingInput::debug_trace`)
+    #[serde(default)]
+    pub debug_trace: Option<bool>,
+    /// Units of the group outputs (mm when unset)
+    #[serde(default)]
+    pub units: Option<DisplayUnits>,
+    /// Report progress under this ID for `get_job_status`
+    #[serde(default)]
+    pub job_id: Option<String>,
+    /// Seconds shared by all groups, in place of their own time limits
+    #[serde(default)]
+    pub total_time_budget_secs: Option<u64>,
+    /// Least seconds a group gets out of the total budget
+    #[serde(default)]
+    pub min_group_time_secs: Option<u64>,
+    /// Most seconds a group gets out of the total budget (the total when unset)
+    #[serde(default)]
+    pub max_group_time_secs: Option<u64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct NestingGroupOutput {
+    pub key: String,
+    /// Unit of the lengths in `resolved_config` and `output`
+    pub units: DisplayUnits,
+    /// Settings the group actually ran with
+    pub resolved_config: NestingConfig,
+    pub preset: Option<String>,
+    pub output: Option<NestingOutput>,
+    pub error: Option<String>,
+    /// Time allocated to the group out of the batch budget, and time it took
+    pub time_budget: Option<GroupTimeBudget>,
+}
+
+/// Run every group of a batch, one after the other
+///
+/// A failing group does not stop the batch; its error is reported in its
+/// output entry. With a total time budget, each group's time limit is its
+/// share of the budget the groups before it left.
+#[tauri::command]
+pub async fn run_nesting_batch(
+    app_handle: AppHandle,
+    batch: NestingBatch,
+) -> Result<Vec<NestingGroupOutput>, String> {
+    let app_settings = load_app_overrides(&app_handle).await;
+    let units = batch.units.unwrap_or_default();
+    let mut outputs = Vec::with_capacity(batch.groups.len());
+    let total = batch.groups.len();
+    let mut budget = batch.total_time_budget_secs.map(|total_secs| {
+        BatchTimeBudget::new(
+            total_secs,
+            batch.min_group_time_secs,
+            batch.max_group_time_secs,
+            batch
+                .groups
+                .iter()
+                .map(|group| (group.key.clone(), instance_difficulty(&group.json_input)))
+                .collect(),
+        )
+    });
+    let report = |status: JobStatus, groups: StepProgress| {
+        if let Some(job_id) = &batch.job_id {
+            app_handle.state::<JobRegistry>().report(JobEntry {
+                job_id: job_id.clone(),
+                kind: JobKind::NestingBatch,
+                status,
+                payload: groups,
+            });
+        }
+    };
+
+    for (done, group) in batch.groups.into_iter().enumerate() {
+        let groups = StepProgress { done, total };
+        report(
+            JobStatus::running(
+                &group.key,
+                groups.pct(),
+                Some(format!("Group {} of {}", done + 1, total)),
+            ),
+            groups,
+        );
+
+        let (resolved, config_sources) =
+            ConfigOverrides::resolve_traced(&group.config, &batch.defaults, &app_settings);
+        let preset = resolved.preset.clone();
+        let mut input = resolved.into_input(group.json_input);
+        input.config_sources = config_sources;
+        input.debug_trace = batch.debug_trace;
+        if let Some(budget) = budget.as_mut() {
+            let allocated = budget.allocate(done);
+            input.time_limit = Some(allocated);
+            input.config_sources.runtime("time_limit", &allocated);
+        }
+        let planned_config = NestingConfig::from(&input);
+        println!(
+            "📦 Nesting group '{}' (separation {} mm, preset {})",
+            group.key,
+            planned_config.separation,
+            preset.as_deref().unwrap_or("none")
+        );
+
+        let started = std::time::Instant::now();
+        let metrics = NestingRunMetrics::for_input(&input);
+        let job_app_handle = app_handle.clone();
+        let result = tauri::async_runtime::spawn_blocking(move || {
+            shutdown::run_registered(&job_app_handle, None, |terminator| {
+                nesting_engine::run_nesting_engine_with_terminator(input, terminator)
+            })
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))
+        .inspect_err(|e| report(JobStatus::Failed { error: e.clone() }, groups))?;
+        record_nesting_run(&app_handle, metrics.finish(&result, started.elapsed()));
+        let time_budget = budget.as_mut().map(|budget| {
+            budget.record(done, started.elapsed().as_secs_f64());
+            budget.groups[done].clone()
+        });
+
+        outputs.push(match result {
+            Ok(output) => NestingGroupOutput {
+                key: group.key,
+                units,
+                resolved_config: output
+                    .resolved_config
+                    .clone()
+                    .unwrap_or(planned_config)
+                    .in_units(units, DisplayUnits::Mm),
+                preset,
+                output: Some(output.to_units(units)),
+                error: None,
+                time_budget,
+            },
+            Err(e) => NestingGroupOutput {
+                key: group.key,
+                units,
+                resolved_config: planned_config.in_units(units, DisplayUnits::Mm),
+                preset,
+                output: None,
+                error: Some(e),
+                time_budget,
+            },
+        });
+    }
+
+    if let Some(budget) = &budget {
+        println!("⏱️ {}", budget.explain().trim_end());
+    }
+    report(
+        JobStatus::Completed { summary_ref: None },
+        StepProgress { done: total, total },
+    );
+    Ok(outputs)
+}
+
+/// Lowest settings layer, from the app settings table
+///
+/// Missing or unreadable settings leave the layer empty so the engine
+/// defaults apply.
+pub(crate) async fn load_app_overrides(app_handle: &AppHandle) -> ConfigOverrides {
+    let rows = match load_settings(app_handle).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            println!("⚠️ App nesting settings not loaded: {}", e);
+            return ConfigOverrides::default();
+        }
+    };
+    app_overrides_from_settings(&rows)
+}
+
+async fn load_settings(app_handle: &AppHandle) -> Result<Vec<(String, String)>, String> {
+    let pool = db::sqlite_pool(app_handle).await?;
+    sqlx::query_as("SELECT key, value FROM settings WHERE key = ?")
+        .bind(TIME_LIMIT_SETTING)
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| format!("Failed to load nesting settings: {}", e))
+}
+
+fn app_overrides_from_settings(rows: &[(String, String)]) -> ConfigOverrides {
+    let mut overrides = ConfigOverrides::default();
+    for (key, value) in rows {
+        if key == TIME_LIMIT_SETTING {
+            overrides.time_limit = parse_number(value)
+                .ok()
+                .filter(|secs| *secs > 0.0)
+                .map(|secs| secs.round() as u64);
+        }
+    }
+    overrides
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_settings_layer() {
+        let rows = vec![(TIME_LIMIT_SETTING.to_string(), "90".to_string())];
+        assert_eq!(app_overrides_from_settings(&rows).time_limit, Some(90));
+
+        let rows = vec![(TIME_LIMIT_SETTING.to_string(), "soon".to_string())];
+        assert_eq!(
+            app_overrides_from_settings(&rows),
+            ConfigOverrides::default()
+        );
+    }
+}
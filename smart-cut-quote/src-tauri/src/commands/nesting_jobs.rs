@@ -0,0 +1,242 @@
+use crate::commands::event_bus::{Emission, EventBus};
+use crate::commands::job_status::{JobEntry, JobKind, JobRegistry, JobStatus};
+use crate::commands::nesting_manager::NestingJobManager;
+use crate::nesting_engine::{
+    self, DerivedField, DerivedOutput, DisplayUnits, LayoutSvg, NativeTerminator, NestingInput,
+    NestingOutput, NestingRun, PreviewUpdate, ProgressReport,
+};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+
+/// Event emitted with placements and summary as soon as the optimizer finished
+pub const RESULT_READY_EVENT: &str = "nesting://result-ready";
+/// Event emitted once the heavy fields (outlines, heatmap, SVG) are derived
+pub const DERIVED_READY_EVENT: &str = "nesting://derived-ready";
+/// Event emitted for every improved solution while optimizing (`live_preview`)
+pub const PREVIEW_EVENT: &str = "nesting://preview";
+/// Event emitted with the stage, strip width and placed count while optimizing
+pub const PROGRESS_EVENT: &str = "nesting://progress";
+/// Event emitted with the full SVG of improved layouts (`live_layout_svg`)
+pub const LAYOUT_SVG_EVENT: &str = "nesting://layout-svg";
+
+/// Finished runs kept for `get_derived_output` (each holds a full solution)
+const MAX_CACHED_JOBS: usize = 4;
+
+/// Recently finished nesting runs, oldest first
+#[derive(Default)]
+pub struct NestingJobs {
+    runs: Mutex<VecDeque<(String, Arc<NestingRun>)>>,
+}
+
+impl NestingJobs {
+    fn insert(&self, job_id: String, run: Arc<NestingRun>) {
+        if let Ok(mut runs) = self.runs.lock() {
+            runs.retain(|(id, _)| *id != job_id);
+            runs.push_back((job_id, run));
+            while runs.len() > MAX_CACHED_JOBS {
+                runs.pop_front();
+            }
+        }
+    }
+
+    fn get(&self, job_id: &str) -> Option<Arc<NestingRun>> {
+        let runs = self.runs.lock().ok()?;
+        runs.iter()
+            .find(|(id, _)| id == job_id)
+            .map(|(_, run)| run.clone())
+    }
+
+    fn remove(&self, job_id: &str) -> bool {
+        self.runs
+            .lock()
+            .map(|mut runs| {
+                let before = runs.len();
+                runs.retain(|(id, _)| id != job_id);
+                runs.len() != before
+            })
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ResultReadyEvent<'a> {
+    pub job_id: &'a str,
+    /// Placements and summary without outlines, heatmap and SVG
+    pub output: &'a NestingOutput,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PreviewEvent<'a> {
+    pub job_id: &'a str,
+    /// Full SVG or a patch of the last one
+    pub update: &'a PreviewUpdate,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LayoutSvgEvent<'a> {
+    pub job_id: &'a str,
+    #[serde(flatten)]
+    pub layout: &'a LayoutSvg,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ProgressEvent<'a> {
+    pub job_id: &'a str,
+    #[serde(flatten)]
+    pub progress: &'a ProgressReport,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DerivedReadyEvent<'a> {
+    pub job_id: &'a str,
+    pub derived: &'a DerivedOutput,
+}
+
+/// Run a nesting job, reporting the slim result before deriving heavy fields
+///
+/// Blocking, call via `spawn_blocking`. Every reported solution is emitted
+/// as `nesting://progress` and, with `live_preview` set, improved
+/// solutions as `nesting://preview` while optimizing, rate limited by the
+/// `EventBus`. With `live_layout_svg` set the full SVG of improved
+/// layouts is emitted as `nesting://layout-svg`, at most once per second.
+/// The run is cached under `job_id` before `nesting://result-ready` is
+/// emitted, so the frontend can call `get_derived_output` as soon as it
+/// sees the event. The returned output is complete, as for
+/// `run_nesting_engine`.
+pub fn run_nesting_job(
+    app_handle: &AppHandle,
+    job_id: &str,
+    input: NestingInput,
+    terminator: NativeTerminator,
+) -> Result<NestingOutput, String> {
+    let bus = app_handle.state::<EventBus>();
+    // Patches after a dropped preview would apply to the wrong SVG, skip
+    // them until the next full update
+    let mut preview_lost = false;
+    let mut emit_preview = |update: PreviewUpdate| {
+        let full = matches!(update, PreviewUpdate::Full { .. });
+        if preview_lost && !full {
+            return;
+        }
+        let emission = bus.emit(
+            app_handle,
+            PREVIEW_EVENT,
+            job_id,
+            &PreviewEvent {
+                job_id,
+                update: &update,
+            },
+        );
+        preview_lost = emission == Emission::Dropped;
+    };
+    let on_preview: Option<&mut dyn FnMut(PreviewUpdate)> = match input.live_preview {
+        Some(true) => Some(&mut emit_preview),
+        _ => None,
+    };
+    // Kept for `get_nesting_status` of jobs from `start_nesting`
+    let manager = app_handle.state::<NestingJobManager>();
+    let mut emit_progress = |progress: ProgressReport| {
+        manager.record_progress(job_id, &progress);
+        bus.emit(
+            app_handle,
+            PROGRESS_EVENT,
+            job_id,
+            &ProgressEvent {
+                job_id,
+                progress: &progress,
+            },
+        );
+    };
+    let mut emit_layout_svg = |layout: LayoutSvg| {
+        bus.emit(
+            app_handle,
+            LAYOUT_SVG_EVENT,
+            job_id,
+            &LayoutSvgEvent {
+                job_id,
+                layout: &layout,
+            },
+        );
+    };
+    let on_layout_svg: Option<&mut dyn FnMut(LayoutSvg)> = match input.live_layout_svg {
+        Some(true) => Some(&mut emit_layout_svg),
+        _ => None,
+    };
+    let (mut output, run) = nesting_engine::run_nesting_core_with_preview(
+        input,
+        terminator,
+        on_preview,
+        Some(&mut emit_progress),
+        on_layout_svg,
+    )?;
+    let run = Arc::new(run);
+    app_handle
+        .state::<NestingJobs>()
+        .insert(job_id.to_string(), run.clone());
+
+    bus.emit(
+        app_handle,
+        RESULT_READY_EVENT,
+        job_id,
+        &ResultReadyEvent {
+            job_id,
+            output: &output,
+        },
+    );
+    println!("📨 Nesting job {} result ready, deriving output", job_id);
+    app_handle.state::<JobRegistry>().report(JobEntry {
+        job_id: job_id.to_string(),
+        kind: JobKind::Nesting,
+        status: JobStatus::running("deriving", None, Some("Placements ready".to_string())),
+        payload: (),
+    });
+
+    let derived = run.derive(&run.requested_fields());
+    bus.emit(
+        app_handle,
+        DERIVED_READY_EVENT,
+        job_id,
+        &DerivedReadyEvent {
+            job_id,
+            derived: &derived,
+        },
+    );
+
+    derived.apply_to(&mut output);
+    Ok(output)
+}
+
+/// Derive output fields of a finished nesting job
+///
+/// Fields already computed for the job are returned from cache; others
+/// (e.g. outlines that were not requested up front) are computed now.
+/// Fails once the job was released or evicted from the cache.
+/// Lengths are in mm unless other `units` are requested.
+#[tauri::command]
+pub async fn get_derived_output(
+    jobs: State<'_, NestingJobs>,
+    job_id: String,
+    fields: Vec<DerivedField>,
+    units: Option<DisplayUnits>,
+) -> Result<DerivedOutput, String> {
+    let run = jobs
+        .get(&job_id)
+        .ok_or_else(|| format!("Nesting job '{}' is not available", job_id))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        run.derive(&fields).to_units(units.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+}
+
+/// Drop the cached intermediate result of a nesting job
+#[tauri::command]
+pub async fn release_nesting_job(
+    jobs: State<'_, NestingJobs>,
+    job_id: String,
+) -> Result<bool, String> {
+    Ok(jobs.remove(&job_id))
+}
@@ -0,0 +1,512 @@
+//! Nesting jobs started in the background and polled by ID
+//!
+//! `run_nesting_integrated` answers when the run is done, so a window
+//! reloaded mid-run never sees the result. `start_nesting` returns a job
+//! ID at once instead; the run goes through `run_nesting_integrated` as
+//! a job (same events, metrics and `cancel_nesting`) and its response is
+//! kept here until `drop_nesting_result` or the app exits.
+//!
+//! At most `concurrency_limit` jobs run at a time, later ones wait in
+//! the order they were started.
+
+use crate::commands::job_status::{JobEntry, JobKind, JobRegistry, JobStatus};
+use crate::commands::response::{CommandError, CommandResponse, ErrorCode};
+use crate::nesting_engine::{DisplayUnits, NestingInput, NestingOutput, ProgressReport};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+/// Jobs running at the same time unless `set_nesting_concurrency` changed it
+pub const DEFAULT_CONCURRENCY_LIMIT: usize = 1;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ManagedState {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    /// Stopped with `cancel_nesting`; the result holds the best layout so
+    /// far, if the run got that far
+    Cancelled,
+}
+
+/// Answer of `get_nesting_status`
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct NestingJobStatus {
+    pub job_id: String,
+    pub state: ManagedState,
+    /// Last solution reported while optimizing
+    pub progress: Option<ProgressReport>,
+    /// Seconds the job has been running, or ran; 0 while queued
+    pub elapsed_secs: f64,
+    /// Jobs ahead of this one in the queue
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_position: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+struct ManagedJob {
+    /// Taken when the job starts
+    input: Option<NestingInput>,
+    quote_id: Option<String>,
+    state: ManagedState,
+    started: Option<Instant>,
+    elapsed: Option<Duration>,
+    progress: Option<ProgressReport>,
+    response: Option<CommandResponse<NestingOutput>>,
+    /// `cancel_nesting` came before the run registered its terminator
+    cancel_requested: bool,
+}
+
+#[derive(Default)]
+struct Jobs {
+    jobs: HashMap<String, ManagedJob>,
+    queue: VecDeque<String>,
+    running: usize,
+    limit: Option<usize>,
+}
+
+/// Jobs started with `start_nesting`, with their results
+#[derive(Default)]
+pub struct NestingJobManager {
+    jobs: Mutex<Jobs>,
+}
+
+/// Job taken off the queue, to be run now
+pub struct StartedJob {
+    pub job_id: String,
+    pub input: NestingInput,
+    pub quote_id: Option<String>,
+}
+
+impl NestingJobManager {
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Jobs>, String> {
+        self.jobs
+            .lock()
+            .map_err(|_| "Nesting job manager is unavailable".to_string())
+    }
+
+    /// Queue a job; it starts with the next `take_startable`
+    pub fn enqueue(
+        &self,
+        job_id: &str,
+        input: NestingInput,
+        quote_id: Option<String>,
+    ) -> Result<(), String> {
+        let mut jobs = self.lock()?;
+        if jobs.jobs.contains_key(job_id) {
+            return Err(format!("Nesting job '{}' already exists", job_id));
+        }
+        jobs.jobs.insert(
+            job_id.to_string(),
+            ManagedJob {
+                input: Some(input),
+                quote_id,
+                state: ManagedState::Queued,
+                started: None,
+                elapsed: None,
+                progress: None,
+                response: None,
+                cancel_requested: false,
+            },
+        );
+        jobs.queue.push_back(job_id.to_string());
+        Ok(())
+    }
+
+    /// Queued jobs that fit under the concurrency limit, marked running
+    pub fn take_startable(&self) -> Vec<StartedJob> {
+        let Ok(mut jobs) = self.jobs.lock() else {
+            return Vec::new();
+        };
+        let limit = jobs.limit.unwrap_or(DEFAULT_CONCURRENCY_LIMIT);
+        let mut started = Vec::new();
+        while jobs.running < limit {
+            let Some(job_id) = jobs.queue.pop_front() else {
+                break;
+            };
+            let Some(job) = jobs.jobs.get_mut(&job_id) else {
+                continue;
+            };
+            let Some(input) = job.input.take() else {
+                continue;
+            };
+            job.state = ManagedState::Running;
+            job.started = Some(Instant::now());
+            let quote_id = job.quote_id.clone();
+            jobs.running += 1;
+            started.push(StartedJob {
+                job_id,
+                input,
+                quote_id,
+            });
+        }
+        started
+    }
+
+    /// Keep the response of a finished job and free its slot
+    pub fn finish(&self, job_id: &str, response: CommandResponse<NestingOutput>) {
+        let Ok(mut jobs) = self.jobs.lock() else {
+            return;
+        };
+        let Some(job) = jobs.jobs.get_mut(job_id) else {
+            return;
+        };
+        if job.state != ManagedState::Running {
+            return;
+        }
+        job.state = finished_state(&response);
+        job.cancel_requested = false;
+        job.elapsed = job.started.map(|started| started.elapsed());
+        job.response = Some(response);
+        jobs.running = jobs.running.saturating_sub(1);
+    }
+
+    pub fn record_progress(&self, job_id: &str, progress: &ProgressReport) {
+        if let Ok(mut jobs) = self.jobs.lock() {
+            if let Some(job) = jobs.jobs.get_mut(job_id) {
+                job.progress = Some(progress.clone());
+            }
+        }
+    }
+
+    pub fn status(&self, job_id: &str) -> Option<NestingJobStatus> {
+        let jobs = self.jobs.lock().ok()?;
+        let job = jobs.jobs.get(job_id)?;
+        let elapsed = job
+            .elapsed
+            .or_else(|| job.started.map(|started| started.elapsed()))
+            .unwrap_or_default();
+        Some(NestingJobStatus {
+            job_id: job_id.to_string(),
+            state: job.state,
+            progress: job.progress.clone(),
+            elapsed_secs: elapsed.as_secs_f64(),
+            queue_position: jobs.queue.iter().position(|id| id == job_id),
+            error: job
+                .response
+                .as_ref()
+                .filter(|response| !response.ok)
+                .and_then(|response| response.message.clone()),
+        })
+    }
+
+    /// Response of a finished job
+    pub fn result(&self, job_id: &str) -> Result<CommandResponse<NestingOutput>, CommandError> {
+        let jobs = self.lock()?;
+        let job = jobs.jobs.get(job_id).ok_or_else(|| {
+            CommandError::new(ErrorCode::NotFound, format!("No nesting job '{}'", job_id))
+        })?;
+        job.response.clone().ok_or_else(|| {
+            CommandError::new(
+                ErrorCode::Conflict,
+                format!("Nesting job '{}' has not finished yet", job_id),
+            )
+        })
+    }
+
+    /// Take a queued job off the queue; false if it is not queued
+    pub fn cancel_queued(&self, job_id: &str) -> bool {
+        let Ok(mut jobs) = self.jobs.lock() else {
+            return false;
+        };
+        let before = jobs.queue.len();
+        jobs.queue.retain(|id| id != job_id);
+        if jobs.queue.len() == before {
+            return false;
+        }
+        if let Some(job) = jobs.jobs.get_mut(job_id) {
+            job.state = ManagedState::Cancelled;
+            job.input = None;
+            job.elapsed = Some(Duration::ZERO);
+        }
+        true
+    }
+
+    /// Keep a cancel for a job taken off the queue, for its run to pick
+    /// up when it starts; false if the job is not running
+    pub fn request_cancel(&self, job_id: &str) -> bool {
+        let Ok(mut jobs) = self.jobs.lock() else {
+            return false;
+        };
+        match jobs.jobs.get_mut(job_id) {
+            Some(job) if job.state == ManagedState::Running => {
+                job.cancel_requested = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the job was cancelled before its run started; forgets it
+    pub fn take_cancel_request(&self, job_id: &str) -> bool {
+        let Ok(mut jobs) = self.jobs.lock() else {
+            return false;
+        };
+        jobs.jobs
+            .get_mut(job_id)
+            .is_some_and(|job| std::mem::take(&mut job.cancel_requested))
+    }
+
+    /// Forget a finished job and its result; false if there is none
+    pub fn drop_result(&self, job_id: &str) -> Result<bool, String> {
+        let mut jobs = self.lock()?;
+        let finished = match jobs.jobs.get(job_id) {
+            None => return Ok(false),
+            Some(job) => job.response.is_some() || job.state == ManagedState::Cancelled,
+        };
+        if !finished {
+            return Err(format!(
+                "Nesting job '{}' has not finished, cancel it first",
+                job_id
+            ));
+        }
+        Ok(jobs.jobs.remove(job_id).is_some())
+    }
+
+    fn set_limit(&self, limit: usize) {
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.limit = Some(limit);
+        }
+    }
+}
+
+/// State of a job from the response of its run
+fn finished_state(response: &CommandResponse<NestingOutput>) -> ManagedState {
+    let cancelled_output = response
+        .data
+        .as_ref()
+        .is_some_and(|output| output.status.as_deref() == Some("cancelled"));
+    match response.code {
+        _ if cancelled_output => ManagedState::Cancelled,
+        Some(ErrorCode::CancelledBeforeSolution) => ManagedState::Cancelled,
+        _ if response.ok => ManagedState::Completed,
+        _ => ManagedState::Failed,
+    }
+}
+
+/// Start the queued jobs there is room for, each on its own task
+fn start_queued(app_handle: &AppHandle) {
+    let manager = app_handle.state::<NestingJobManager>();
+    for job in manager.take_startable() {
+        println!("▶️ Starting nesting job {}", job.job_id);
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            let response = crate::run_nesting_integrated(
+                app_handle.clone(),
+                job.input,
+                Some(job.job_id.clone()),
+                job.quote_id,
+                None,
+            )
+            .await;
+            app_handle
+                .state::<NestingJobManager>()
+                .finish(&job.job_id, response);
+            start_queued(&app_handle);
+        });
+    }
+}
+
+/// Start a nesting run in the background
+///
+/// Returns the job ID to poll with `get_nesting_status` and
+/// `get_nesting_result`. The job waits in a queue while the concurrency
+/// limit is reached. Progress, preview and result events are emitted as
+/// for `run_nesting_integrated` with a job ID.
+#[tauri::command]
+pub async fn start_nesting(
+    app_handle: AppHandle,
+    manager: State<'_, NestingJobManager>,
+    registry: State<'_, JobRegistry>,
+    input: NestingInput,
+    quote_id: Option<String>,
+) -> Result<String, String> {
+    let job_id = uuid::Uuid::new_v4().to_string();
+    manager.enqueue(&job_id, input, quote_id)?;
+    registry.report(JobEntry {
+        job_id: job_id.clone(),
+        kind: JobKind::Nesting,
+        status: JobStatus::Queued,
+        payload: (),
+    });
+    start_queued(&app_handle);
+    Ok(job_id)
+}
+
+/// State, last progress and running time of a job from `start_nesting`
+#[tauri::command]
+pub fn get_nesting_status(
+    manager: State<'_, NestingJobManager>,
+    job_id: String,
+) -> Result<NestingJobStatus, String> {
+    manager
+        .status(&job_id)
+        .ok_or_else(|| format!("No nesting job '{}'", job_id))
+}
+
+/// Response of a finished job from `start_nesting`
+///
+/// The same response `run_nesting_integrated` gives, in mm unless other
+/// `units` are requested. Fails with `conflict` while the job is queued
+/// or running; the result stays available until `drop_nesting_result`.
+#[tauri::command]
+pub fn get_nesting_result(
+    manager: State<'_, NestingJobManager>,
+    job_id: String,
+    units: Option<DisplayUnits>,
+) -> CommandResponse<NestingOutput> {
+    match manager.result(&job_id) {
+        Ok(mut response) => {
+            let units = units.unwrap_or_default();
+            response.data = response.data.map(|output| output.to_units(units));
+            response
+        }
+        Err(error) => CommandResponse::err(error),
+    }
+}
+
+/// Forget the result of a finished job
+#[tauri::command]
+pub fn drop_nesting_result(
+    manager: State<'_, NestingJobManager>,
+    job_id: String,
+) -> Result<bool, String> {
+    manager.drop_result(&job_id)
+}
+
+/// Change how many jobs from `start_nesting` run at the same time
+#[tauri::command]
+pub fn set_nesting_concurrency(
+    app_handle: AppHandle,
+    manager: State<'_, NestingJobManager>,
+    limit: usize,
+) -> Result<(), String> {
+    if limit == 0 {
+        return Err("Concurrency limit must be at least 1".to_string());
+    }
+    manager.set_limit(limit);
+    start_queued(&app_handle);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::ProgressStage;
+
+    fn output(status: &str) -> NestingOutput {
+        serde_json::from_value(serde_json::json!({
+            "instance_name": "managed",
+            "strip_width": 100.0,
+            "strip_height": 50.0,
+            "total_items_placed": 3,
+            "layouts": [],
+            "utilization": 0.6,
+            "computation_time_secs": 1.0,
+            "status": status,
+            "unplaced_item_ids": [],
+        }))
+        .unwrap()
+    }
+
+    fn started_ids(manager: &NestingJobManager) -> Vec<String> {
+        manager
+            .take_startable()
+            .into_iter()
+            .map(|job| job.job_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_jobs_wait_for_a_free_slot() {
+        let manager = NestingJobManager::default();
+        for job_id in ["a", "b", "c"] {
+            manager
+                .enqueue(job_id, NestingInput::default(), None)
+                .unwrap();
+        }
+        assert!(manager.enqueue("a", NestingInput::default(), None).is_err());
+
+        assert_eq!(started_ids(&manager), vec!["a"]);
+        assert!(started_ids(&manager).is_empty());
+        assert_eq!(manager.status("c").unwrap().queue_position, Some(1));
+        assert_eq!(manager.result("b").unwrap_err().code, ErrorCode::Conflict);
+
+        manager.finish("a", CommandResponse::ok(output("complete")));
+        assert_eq!(manager.status("a").unwrap().state, ManagedState::Completed);
+        assert_eq!(started_ids(&manager), vec!["b"]);
+
+        // A higher limit starts the rest right away
+        manager.set_limit(3);
+        assert_eq!(started_ids(&manager), vec!["c"]);
+        assert_eq!(manager.status("c").unwrap().state, ManagedState::Running);
+    }
+
+    #[test]
+    fn test_status_and_result_of_a_job() {
+        let manager = NestingJobManager::default();
+        manager
+            .enqueue("job", NestingInput::default(), None)
+            .unwrap();
+        let queued = manager.status("job").unwrap();
+        assert_eq!(queued.state, ManagedState::Queued);
+        assert_eq!(queued.elapsed_secs, 0.0);
+        manager.take_startable();
+
+        let progress = ProgressReport {
+            stage: ProgressStage::Exploration,
+            strip_width: 120.0,
+            placed_items: 3,
+            elapsed_secs: 0.5,
+        };
+        manager.record_progress("job", &progress);
+        assert_eq!(manager.status("job").unwrap().progress, Some(progress));
+
+        manager.finish("job", CommandResponse::ok(output("cancelled")));
+        let status = manager.status("job").unwrap();
+        assert_eq!(status.state, ManagedState::Cancelled);
+        let response = manager.result("job").unwrap();
+        assert!(response.ok);
+        assert_eq!(response.data.unwrap().total_items_placed, 3);
+
+        // Kept until dropped
+        assert!(manager.result("job").is_ok());
+        assert!(manager.drop_result("job").unwrap());
+        assert_eq!(manager.result("job").unwrap_err().code, ErrorCode::NotFound);
+        assert!(!manager.drop_result("job").unwrap());
+    }
+
+    #[test]
+    fn test_failed_and_cancelled_queued_jobs() {
+        let manager = NestingJobManager::default();
+        for job_id in ["running", "waiting"] {
+            manager
+                .enqueue(job_id, NestingInput::default(), None)
+                .unwrap();
+        }
+        manager.take_startable();
+        assert!(manager.drop_result("running").is_err());
+        assert!(!manager.cancel_queued("running"));
+
+        assert!(manager.cancel_queued("waiting"));
+        assert_eq!(
+            manager.status("waiting").unwrap().state,
+            ManagedState::Cancelled
+        );
+
+        manager.finish(
+            "running",
+            CommandResponse::err(CommandError::new(ErrorCode::InvalidInput, "bad instance")),
+        );
+        let status = manager.status("running").unwrap();
+        assert_eq!(status.state, ManagedState::Failed);
+        assert_eq!(status.error.as_deref(), Some("bad instance"));
+        // The cancelled job never takes a slot
+        assert!(manager.take_startable().is_empty());
+    }
+}
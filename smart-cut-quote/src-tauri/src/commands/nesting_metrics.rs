@@ -0,0 +1,306 @@
+use crate::db;
+use crate::nesting_engine::{self, NestingInput, NestingOutput, ENGINE_VERSION};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// Setting that turns metrics recording off when set to "false"
+const METRICS_ENABLED_SETTING: &str = "nesting_metrics_enabled";
+
+/// Row of `nesting_metrics`, summary only (no geometry)
+#[derive(Debug, Clone, PartialEq)]
+pub struct NestingRunMetrics {
+    pub item_count: i64,
+    pub demand_total: i64,
+    pub status: &'static str,
+    pub error_code: Option<&'static str>,
+    pub utilization: Option<f64>,
+    pub sheets: i64,
+    pub duration_secs: f64,
+    pub preset: Option<String>,
+}
+
+impl NestingRunMetrics {
+    /// Size a run from its input, before it starts
+    ///
+    /// An unparsable instance counts as zero items; the run still gets
+    /// a row once it fails.
+    pub fn for_input(input: &NestingInput) -> Self {
+        let (item_count, demand_total) = nesting_engine::parse_instance(&input.json_input)
+            .map(|instance| {
+                (
+                    instance.items.len() as i64,
+                    instance.items.iter().map(|item| item.demand as i64).sum(),
+                )
+            })
+            .unwrap_or((0, 0));
+
+        Self {
+            item_count,
+            demand_total,
+            status: "failed",
+            error_code: None,
+            utilization: None,
+            sheets: 0,
+            duration_secs: 0.0,
+            preset: input.preset.clone(),
+        }
+    }
+
+    /// Add the outcome of the run
+    pub fn finish(self, result: &Result<NestingOutput, String>, duration: Duration) -> Self {
+        let (status, error_code, utilization, sheets) = match result {
+            Ok(output) if output.status.as_deref() == Some("partial") => {
+                ("partial", None, Some(output.utilization), 1)
+            }
+            Ok(output) => ("complete", None, Some(output.utilization), 1),
+            Err(message) => ("failed", Some(error_code(message)), None, 0),
+        };
+
+        Self {
+            status,
+            error_code,
+            utilization,
+            sheets,
+            duration_secs: duration.as_secs_f64(),
+            ..self
+        }
+    }
+}
+
+/// Stable code for a nesting error message (messages change wording, codes don't)
+pub fn error_code(message: &str) -> &'static str {
+    if message.starts_with("Keep-out zone") {
+        "invalid_keep_out_zone"
+    } else if message.starts_with("Machine profile")
+        || message.starts_with("Failed to load machine")
+    {
+        "machine_profile"
+    } else if message.starts_with("Nesting failed") {
+        "engine_failed"
+    } else if message.starts_with("Task join error") {
+        "task_aborted"
+    } else {
+        "other"
+    }
+}
+
+/// Record a nesting run in the background
+///
+/// Never fails or delays the nesting itself: the insert runs on its own
+/// task and errors are only logged. Skipped when the opt-out setting
+/// `nesting_metrics_enabled` is "false".
+pub fn record_nesting_run(app_handle: &AppHandle, metrics: NestingRunMetrics) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = insert_metrics(&app_handle, &metrics).await {
+            println!("⚠️ Nesting metrics not recorded: {}", e);
+        }
+    });
+}
+
+async fn insert_metrics(app_handle: &AppHandle, metrics: &NestingRunMetrics) -> Result<(), String> {
+    let pool = db::sqlite_pool(app_handle).await?;
+
+    let enabled: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = ?")
+        .bind(METRICS_ENABLED_SETTING)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to load metrics setting: {}", e))?;
+    if enabled.is_some_and(|(value,)| value == "false" || value == "0") {
+        return Ok(());
+    }
+
+    sqlx::query(
+        "INSERT INTO nesting_metrics
+            (item_count, demand_total, status, error_code, utilization, sheets,
+             duration_secs, preset, engine_version)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(metrics.item_count)
+    .bind(metrics.demand_total)
+    .bind(metrics.status)
+    .bind(metrics.error_code)
+    .bind(metrics.utilization)
+    .bind(metrics.sheets)
+    .bind(metrics.duration_secs)
+    .bind(&metrics.preset)
+    .bind(ENGINE_VERSION)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to insert nesting metrics: {}", e))?;
+
+    Ok(())
+}
+
+/// Time range of `get_nesting_metrics` (UTC, `YYYY-MM-DD[ HH:MM:SS]`)
+#[derive(Deserialize, Debug, Default)]
+pub struct MetricsRange {
+    /// Inclusive start
+    pub from: Option<String>,
+    /// Exclusive end
+    pub to: Option<String>,
+}
+
+/// How runs are bucketed in `get_nesting_metrics`
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricsGroupBy {
+    Day,
+    Week,
+    Month,
+    Preset,
+    Status,
+    EngineVersion,
+}
+
+impl MetricsGroupBy {
+    /// SQL expression producing the bucket key
+    fn key_sql(self) -> &'static str {
+        match self {
+            Self::Day => "strftime('%Y-%m-%d', created_at)",
+            Self::Week => "strftime('%Y-W%W', created_at)",
+            Self::Month => "strftime('%Y-%m', created_at)",
+            Self::Preset => "COALESCE(preset, 'default')",
+            Self::Status => "status",
+            Self::EngineVersion => "engine_version",
+        }
+    }
+}
+
+/// Aggregated runs of one bucket
+#[derive(Serialize, Debug)]
+pub struct MetricsBucket {
+    pub key: String,
+    pub runs: i64,
+    /// Average over runs that produced a layout
+    pub avg_utilization: Option<f64>,
+    pub avg_duration_secs: Option<f64>,
+    pub partial_runs: i64,
+    pub failed_runs: i64,
+    pub total_sheets: i64,
+}
+
+/// Aggregated nesting run statistics for analytics charts
+///
+/// Buckets are sorted by key, so time series come out in order.
+#[tauri::command]
+pub async fn get_nesting_metrics(
+    app_handle: AppHandle,
+    range: Option<MetricsRange>,
+    group_by: MetricsGroupBy,
+) -> Result<Vec<MetricsBucket>, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    load_metrics(&pool, range.unwrap_or_default(), group_by).await
+}
+
+/// `get_nesting_metrics`, shared with the dashboard server
+pub(crate) async fn load_metrics(
+    pool: &SqlitePool,
+    range: MetricsRange,
+    group_by: MetricsGroupBy,
+) -> Result<Vec<MetricsBucket>, String> {
+    let sql = format!(
+        "SELECT {} AS bucket,
+                COUNT(*),
+                AVG(utilization),
+                AVG(duration_secs),
+                SUM(status = 'partial'),
+                SUM(status = 'failed'),
+                SUM(sheets)
+         FROM nesting_metrics
+         WHERE (?1 IS NULL OR created_at >= ?1)
+           AND (?2 IS NULL OR created_at < ?2)
+         GROUP BY bucket
+         ORDER BY bucket",
+        group_by.key_sql()
+    );
+    let rows: Vec<(String, i64, Option<f64>, Option<f64>, i64, i64, i64)> = sqlx::query_as(&sql)
+        .bind(range.from)
+        .bind(range.to)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load nesting metrics: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(
+                key,
+                runs,
+                avg_utilization,
+                avg_duration_secs,
+                partial_runs,
+                failed_runs,
+                total_sheets,
+            )| {
+                MetricsBucket {
+                    key,
+                    runs,
+                    avg_utilization,
+                    avg_duration_secs,
+                    partial_runs,
+                    failed_runs,
+                    total_sheets,
+                }
+            },
+        )
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTANCE: &str = r#"{
+        "name": "metrics",
+        "strip_height": 100.0,
+        "items": [
+            {"id": 0, "demand": 3, "shape": {"type": "rectangle", "data": {"x_min": 0.0, "y_min": 0.0, "width": 10.0, "height": 10.0}}},
+            {"id": 1, "demand": 2, "shape": {"type": "rectangle", "data": {"x_min": 0.0, "y_min": 0.0, "width": 20.0, "height": 5.0}}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_failed_run_is_sized_and_coded() {
+        let input = NestingInput {
+            json_input: INSTANCE.to_string(),
+            preset: Some("fast".to_string()),
+            ..Default::default()
+        };
+        let metrics = NestingRunMetrics::for_input(&input).finish(
+            &Err("Nesting failed: out of memory".to_string()),
+            Duration::from_millis(1500),
+        );
+
+        assert_eq!(metrics.item_count, 2);
+        assert_eq!(metrics.demand_total, 5);
+        assert_eq!(metrics.status, "failed");
+        assert_eq!(metrics.error_code, Some("engine_failed"));
+        assert_eq!(metrics.utilization, None);
+        assert_eq!(metrics.sheets, 0);
+        assert_eq!(metrics.duration_secs, 1.5);
+        assert_eq!(metrics.preset.as_deref(), Some("fast"));
+    }
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(
+            error_code("Keep-out zone 0 (clamp) has invalid dimensions: x=0"),
+            "invalid_keep_out_zone"
+        );
+        assert_eq!(
+            error_code("Machine profile 'm1' not found"),
+            "machine_profile"
+        );
+        assert_eq!(error_code("Task join error: panicked"), "task_aborted");
+        assert_eq!(error_code("something else"), "other");
+
+        // Unparsable input still produces a row
+        let input = NestingInput::default();
+        let metrics =
+            NestingRunMetrics::for_input(&input).finish(&Err(String::new()), Duration::ZERO);
+        assert_eq!((metrics.item_count, metrics.demand_total), (0, 0));
+    }
+}
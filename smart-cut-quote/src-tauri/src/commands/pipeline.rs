@@ -0,0 +1,872 @@
+//! Quote pipeline: conversion → validation → nesting → persistence
+//!
+//! `run_quote_pipeline` converts the DXF files of a quote, validates the
+//! nesting input, nests and stores the result with its SVG. Every stage
+//! that writes something registers how to undo it in `pipeline_undo_log`
+//! before (files) or together with (rows) the write. When the pipeline
+//! is cancelled or a stage fails, the log is replayed in reverse order,
+//! so no `nesting_results` row is left pointing at a missing SVG.
+//!
+//! The log lives in the database: a run interrupted by a crash, during
+//! the pipeline or during its rollback, still has its `pipeline_runs`
+//! row at the next startup and is rolled back then. Converted geometry
+//! in the conversion cache is kept on rollback, it is valid regardless.
+
+use crate::commands::artifacts::artifact_sha256;
+use crate::commands::conversion_jobs::convert_file;
+use crate::commands::dxf_converter::{converter_exe_path, ConversionOptions, DxfFileInput};
+use crate::commands::instance_blobs::{acquire_instance_blob, delete_nesting_result};
+use crate::commands::migrations;
+use crate::commands::shutdown::{run_registered, RunningNestings};
+use crate::db;
+use crate::nesting_engine::{self, validate_input, NestingInput, NestingOutput, ValidationIssue};
+use crate::workspace;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteExecutor, SqlitePool};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+
+/// Workspace directory of the per-run instance JSON (below `jobs`)
+const PIPELINE_JOB_DIR: &str = "pipeline";
+/// Workspace directory of the converted geometry cache (below `library`)
+const CONVERSION_CACHE_DIR: &str = "conversions";
+/// Workspace directory of the stored SVGs (below `svgs`)
+const PIPELINE_SVG_DIR: &str = "pipeline";
+
+/// Pipeline stages, in the order they run
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineStage {
+    Conversion,
+    Validation,
+    Nesting,
+    Persistence,
+}
+
+impl PipelineStage {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PipelineStage::Conversion => "conversion",
+            PipelineStage::Validation => "validation",
+            PipelineStage::Nesting => "nesting",
+            PipelineStage::Persistence => "persistence",
+        }
+    }
+
+    fn parse(stage: &str) -> Option<Self> {
+        [
+            PipelineStage::Conversion,
+            PipelineStage::Validation,
+            PipelineStage::Nesting,
+            PipelineStage::Persistence,
+        ]
+        .into_iter()
+        .find(|candidate| candidate.as_str() == stage)
+    }
+}
+
+/// How to undo one write of a stage
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UndoAction {
+    /// Remove a file, path relative to the app data dir
+    RemoveFile { path: String },
+    /// Delete a `nesting_results` row, releasing its instance blob
+    DeleteNestingResult { id: i64 },
+}
+
+/// Outcome of a pipeline run
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineStatus {
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// Payload of `run_quote_pipeline`
+#[derive(Serialize, Debug)]
+pub struct PipelineReport {
+    pub pipeline_id: String,
+    pub status: PipelineStatus,
+    /// Stages that finished, in order
+    pub completed_stages: Vec<PipelineStage>,
+    /// Stages whose writes were undone, in the order they were undone
+    pub rolled_back_stages: Vec<PipelineStage>,
+    /// Files kept on rollback on purpose (conversion cache entries)
+    pub retained_artifacts: Vec<String>,
+    /// `nesting_results` row of a completed run
+    pub result_id: Option<i64>,
+    pub output: Option<NestingOutput>,
+    /// Why the run failed
+    pub error: Option<String>,
+}
+
+/// Input of `run_quote_pipeline`
+#[derive(Deserialize, Debug)]
+pub struct PipelineRequest {
+    /// Chosen by the caller, identifies the run for `cancel_pipeline`
+    pub pipeline_id: String,
+    /// Quote the result is stored for
+    pub quote_id: Option<String>,
+    pub files: Vec<DxfFileInput>,
+    pub options: ConversionOptions,
+    /// Nesting settings, `json_input` is filled from the converted files
+    #[serde(default)]
+    pub nesting: NestingInput,
+}
+
+/// Cancel flags of the running pipelines
+#[derive(Default)]
+pub struct RunningPipelines {
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl RunningPipelines {
+    fn start(&self, pipeline_id: &str) -> Result<Arc<AtomicBool>, String> {
+        let mut running = self
+            .running
+            .lock()
+            .map_err(|_| "Pipeline registry is unavailable".to_string())?;
+        if running.contains_key(pipeline_id) {
+            return Err(format!("Pipeline '{}' is already running", pipeline_id));
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        running.insert(pipeline_id.to_string(), cancel.clone());
+        Ok(cancel)
+    }
+
+    fn finish(&self, pipeline_id: &str) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(pipeline_id);
+        }
+    }
+
+    fn cancel(&self, pipeline_id: &str) -> bool {
+        self.running
+            .lock()
+            .ok()
+            .and_then(|running| running.get(pipeline_id).cloned())
+            .map(|cancel| cancel.store(true, Ordering::SeqCst))
+            .is_some()
+    }
+}
+
+/// Persisted undo log of one pipeline run
+pub struct UndoLog<'a> {
+    pool: &'a SqlitePool,
+    run_id: String,
+}
+
+impl<'a> UndoLog<'a> {
+    /// Record a new run, which stays pending until `complete` or a rollback
+    pub async fn begin(pool: &'a SqlitePool, run_id: &str) -> Result<UndoLog<'a>, String> {
+        sqlx::query("INSERT INTO pipeline_runs (id) VALUES (?)")
+            .bind(run_id)
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to start pipeline '{}': {}", run_id, e))?;
+        Ok(Self {
+            pool,
+            run_id: run_id.to_string(),
+        })
+    }
+
+    /// Register an undo action
+    ///
+    /// Pass the transaction of a row insert as `executor`, so the row and
+    /// its undo action are written together.
+    pub async fn register(
+        &self,
+        executor: impl SqliteExecutor<'_>,
+        stage: PipelineStage,
+        action: &UndoAction,
+    ) -> Result<(), String> {
+        let action_json = serde_json::to_string(action)
+            .map_err(|e| format!("Failed to serialize undo action: {}", e))?;
+        sqlx::query("INSERT INTO pipeline_undo_log (run_id, stage, action) VALUES (?, ?, ?)")
+            .bind(&self.run_id)
+            .bind(stage.as_str())
+            .bind(&action_json)
+            .execute(executor)
+            .await
+            .map_err(|e| format!("Failed to register undo action: {}", e))?;
+        Ok(())
+    }
+
+    /// Keep everything the run wrote and forget its undo actions
+    pub async fn complete(self) -> Result<(), String> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        sqlx::query("DELETE FROM pipeline_undo_log WHERE run_id = ?")
+            .bind(&self.run_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to clear undo log: {}", e))?;
+        sqlx::query("DELETE FROM pipeline_runs WHERE id = ?")
+            .bind(&self.run_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to complete pipeline: {}", e))?;
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to complete pipeline: {}", e))
+    }
+}
+
+/// Undo everything a run registered, newest first
+///
+/// Each action is removed from the log as it is done, in the same
+/// transaction for rows, so a rollback interrupted by a crash continues
+/// where it stopped. Removing a missing file or row is not an error.
+///
+/// # Returns
+/// The stages that had something undone, in the order they were undone
+pub async fn rollback_run(
+    pool: &SqlitePool,
+    data_dir: &Path,
+    run_id: &str,
+) -> Result<Vec<PipelineStage>, String> {
+    sqlx::query("UPDATE pipeline_runs SET status = 'rolling_back' WHERE id = ?")
+        .bind(run_id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to mark pipeline '{}': {}", run_id, e))?;
+
+    let entries: Vec<(i64, String, String)> = sqlx::query_as(
+        "SELECT seq, stage, action FROM pipeline_undo_log WHERE run_id = ? ORDER BY seq DESC",
+    )
+    .bind(run_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to read undo log: {}", e))?;
+
+    let mut stages: Vec<PipelineStage> = Vec::new();
+    for (seq, stage, action_json) in entries {
+        let action: UndoAction = serde_json::from_str(&action_json)
+            .map_err(|e| format!("Invalid undo action {}: {}", seq, e))?;
+        let mut tx = pool
+            .begin()
+            .await
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        match &action {
+            UndoAction::RemoveFile { path } => match std::fs::remove_file(data_dir.join(path)) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("Failed to remove '{}': {}", path, e)),
+            },
+            UndoAction::DeleteNestingResult { id } => {
+                delete_nesting_result(&mut tx, *id).await?;
+            }
+        }
+        sqlx::query("DELETE FROM pipeline_undo_log WHERE seq = ?")
+            .bind(seq)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to update undo log: {}", e))?;
+        tx.commit()
+            .await
+            .map_err(|e| format!("Failed to undo {:?}: {}", action, e))?;
+
+        if let Some(stage) = PipelineStage::parse(&stage) {
+            if !stages.contains(&stage) {
+                stages.push(stage);
+            }
+        }
+    }
+
+    sqlx::query("DELETE FROM pipeline_runs WHERE id = ?")
+        .bind(run_id)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to finish rollback of '{}': {}", run_id, e))?;
+    Ok(stages)
+}
+
+/// Roll back every run left pending, returns their IDs
+pub async fn recover_pipelines(pool: &SqlitePool, data_dir: &Path) -> Result<Vec<String>, String> {
+    let runs: Vec<(String,)> = sqlx::query_as("SELECT id FROM pipeline_runs ORDER BY started_at")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to read pipeline runs: {}", e))?;
+    let mut recovered = Vec::new();
+    for (run_id,) in runs {
+        rollback_run(pool, data_dir, &run_id).await?;
+        recovered.push(run_id);
+    }
+    Ok(recovered)
+}
+
+/// Finish the rollback of pipelines interrupted by the previous session
+///
+/// Called from the app setup after the migrations ran.
+pub fn recover_interrupted_pipelines(app_handle: &AppHandle) {
+    let recovered = tauri::async_runtime::block_on(async {
+        let db_path = db::db_file_path(app_handle)?;
+        if !db_path.exists() {
+            return Ok(Vec::new());
+        }
+        let data_dir = workspace::app_data_dir(app_handle)?;
+        let pool = migrations::open_pool(&db_path, false).await?;
+        let result = recover_pipelines(&pool, &data_dir).await;
+        pool.close().await;
+        result
+    });
+    match recovered {
+        Ok(runs) if !runs.is_empty() => {
+            println!("↩️ Rolled back {} interrupted pipeline runs", runs.len());
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("Interrupted pipelines not rolled back: {}", e),
+    }
+}
+
+/// Combine converted part instances into one instance of the quote
+///
+/// Items get consecutive IDs and the quantity of their file as demand.
+pub fn merge_part_instances(
+    parts: &[(String, u32)],
+    options: &ConversionOptions,
+) -> Result<String, String> {
+    let mut items = Vec::new();
+    for (json, quantity) in parts {
+        let part: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| format!("Invalid converted part: {}", e))?;
+        let part_items = part
+            .get("items")
+            .and_then(|items| items.as_array())
+            .ok_or("Converted part has no items")?;
+        for item in part_items {
+            let mut item = item.clone();
+            item["id"] = serde_json::json!(items.len());
+            item["demand"] = serde_json::json!(quantity);
+            items.push(item);
+        }
+    }
+    serde_json::to_string(&serde_json::json!({
+        "name": "quote",
+        "strip_height": options.strip_height,
+        "items": items,
+    }))
+    .map_err(|e| format!("Failed to serialize instance: {}", e))
+}
+
+/// Write the quote instance of a run, registered for removal first
+pub async fn store_instance(
+    log: &UndoLog<'_>,
+    data_dir: &Path,
+    instance_json: &str,
+) -> Result<(), String> {
+    let path = format!("jobs/{}/{}/instance.json", PIPELINE_JOB_DIR, log.run_id);
+    log.register(
+        log.pool,
+        PipelineStage::Conversion,
+        &UndoAction::RemoveFile { path: path.clone() },
+    )
+    .await?;
+    write_file(&data_dir.join(&path), instance_json)
+}
+
+/// Store the SVG and `nesting_results` row of a run
+///
+/// The SVG is registered before it is written, the row together with
+/// its undo action, so neither outlives a rollback. The row records the
+/// SVG's checksum (see `artifacts`) and references the instance by
+/// hash, see `instance_blobs`.
+pub async fn store_result(
+    log: &UndoLog<'_>,
+    data_dir: &Path,
+    quote_id: Option<&str>,
+    instance_json: &str,
+    output: &NestingOutput,
+) -> Result<i64, String> {
+    let (svg_path, svg_sha256) = match &output.svg_string {
+        Some(svg) => {
+            let path = format!("svgs/{}/{}.svg", PIPELINE_SVG_DIR, log.run_id);
+            log.register(
+                log.pool,
+                PipelineStage::Persistence,
+                &UndoAction::RemoveFile { path: path.clone() },
+            )
+            .await?;
+            write_file(&data_dir.join(&path), svg)?;
+            (Some(path), Some(artifact_sha256(svg.as_bytes())))
+        }
+        None => (None, None),
+    };
+
+    let result_json = serde_json::to_string(output)
+        .map_err(|e| format!("Failed to serialize nesting result: {}", e))?;
+    let mut tx = log
+        .pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let instance_hash = acquire_instance_blob(&mut tx, instance_json).await?;
+    let id = sqlx::query(
+        "INSERT INTO nesting_results
+            (quote_id, instance_name, strip_width, strip_height, density, placed_items,
+             run_time_sec, result_json, svg_path, svg_sha256, source, instance_hash)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'pipeline', ?)",
+    )
+    .bind(quote_id)
+    .bind(&output.instance_name)
+    .bind(output.strip_width)
+    .bind(output.strip_height)
+    .bind(output.utilization)
+    .bind(output.total_items_placed as i64)
+    .bind(output.computation_time_secs)
+    .bind(&result_json)
+    .bind(&svg_path)
+    .bind(&svg_sha256)
+    .bind(&instance_hash)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to store nesting result: {}", e))?
+    .last_insert_rowid();
+    log.register(
+        &mut *tx,
+        PipelineStage::Persistence,
+        &UndoAction::DeleteNestingResult { id },
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to store nesting result: {}", e))?;
+    Ok(id)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    }
+    std::fs::write(path, content)
+        .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+/// Why a run stopped before completing
+enum Stop {
+    Cancelled,
+    Failed(String),
+}
+
+/// Convert, validate, nest and store the result of a quote
+///
+/// Stops at the next stage boundary after `cancel_pipeline`; a running
+/// conversion stops after the current file, a running nesting returns
+/// its best solution so far, which is then discarded. Cancelled and
+/// failed runs are rolled back before the report is returned.
+#[tauri::command]
+pub async fn run_quote_pipeline(
+    app_handle: AppHandle,
+    pipelines: State<'_, RunningPipelines>,
+    request: PipelineRequest,
+) -> Result<PipelineReport, String> {
+    let pipeline_id = request.pipeline_id.clone();
+    let cancel = pipelines.start(&pipeline_id)?;
+    let result = run_pipeline(&app_handle, request, cancel).await;
+    pipelines.finish(&pipeline_id);
+    result
+}
+
+async fn run_pipeline(
+    app_handle: &AppHandle,
+    request: PipelineRequest,
+    cancel: Arc<AtomicBool>,
+) -> Result<PipelineReport, String> {
+    let pool = db::sqlite_pool(app_handle).await?;
+    let data_dir = workspace::app_data_dir(app_handle)?;
+    let log = UndoLog::begin(&pool, &request.pipeline_id).await?;
+    let mut report = PipelineReport {
+        pipeline_id: request.pipeline_id.clone(),
+        status: PipelineStatus::Completed,
+        completed_stages: Vec::new(),
+        rolled_back_stages: Vec::new(),
+        retained_artifacts: Vec::new(),
+        result_id: None,
+        output: None,
+        error: None,
+    };
+
+    let stopped = run_stages(app_handle, &log, &data_dir, request, &cancel, &mut report).await;
+    match stopped {
+        None => {
+            log.complete().await?;
+            println!("✅ Pipeline {} completed", report.pipeline_id);
+        }
+        Some(stop) => {
+            report.status = match stop {
+                Stop::Cancelled => PipelineStatus::Cancelled,
+                Stop::Failed(error) => {
+                    report.error = Some(error);
+                    PipelineStatus::Failed
+                }
+            };
+            report.result_id = None;
+            report.output = None;
+            report.rolled_back_stages = rollback_run(&pool, &data_dir, &report.pipeline_id).await?;
+            println!(
+                "↩️ Pipeline {} {:?}, rolled back {:?}",
+                report.pipeline_id, report.status, report.rolled_back_stages
+            );
+        }
+    }
+    Ok(report)
+}
+
+/// Run the stages in order, checking for cancellation between them
+///
+/// Returns why the run stopped, None when every stage completed.
+async fn run_stages(
+    app_handle: &AppHandle,
+    log: &UndoLog<'_>,
+    data_dir: &Path,
+    request: PipelineRequest,
+    cancel: &AtomicBool,
+    report: &mut PipelineReport,
+) -> Option<Stop> {
+    let cancelled = || cancel.load(Ordering::SeqCst);
+    let failed = |error: String| Some(Stop::Failed(error));
+
+    // Conversion, through the conversion cache
+    if cancelled() {
+        return Some(Stop::Cancelled);
+    }
+    let converted = match convert_parts(app_handle, &request, cancel).await {
+        Ok(converted) => converted,
+        Err(error) => return failed(error),
+    };
+    report.retained_artifacts = converted.iter().map(|(path, _)| path.clone()).collect();
+    if cancelled() {
+        return Some(Stop::Cancelled);
+    }
+    let parts: Result<Vec<(String, u32)>, String> = converted
+        .iter()
+        .map(|(path, quantity)| {
+            std::fs::read_to_string(path)
+                .map(|json| (json, *quantity))
+                .map_err(|e| format!("Failed to read '{}': {}", path, e))
+        })
+        .collect();
+    let instance_json = match parts.and_then(|parts| merge_part_instances(&parts, &request.options))
+    {
+        Ok(json) => json,
+        Err(error) => return failed(error),
+    };
+    if let Err(error) = store_instance(log, data_dir, &instance_json).await {
+        return failed(error);
+    }
+    report.completed_stages.push(PipelineStage::Conversion);
+
+    // Validation
+    if cancelled() {
+        return Some(Stop::Cancelled);
+    }
+    let mut input = request.nesting;
+    input.json_input = instance_json.clone();
+    let errors: Vec<String> = validate_input(&input)
+        .into_iter()
+        .filter(ValidationIssue::is_error)
+        .map(|issue| issue.to_string())
+        .collect();
+    if !errors.is_empty() {
+        return failed(format!("Invalid nesting input: {}", errors.join("; ")));
+    }
+    report.completed_stages.push(PipelineStage::Validation);
+
+    // Nesting, cancellable through the nesting registry
+    if cancelled() {
+        return Some(Stop::Cancelled);
+    }
+    let job_app_handle = app_handle.clone();
+    let job_id = report.pipeline_id.clone();
+    let output = tauri::async_runtime::spawn_blocking(move || {
+        let output = run_registered(&job_app_handle, Some(&job_id), |terminator| {
+            nesting_engine::run_nesting_engine_with_terminator(input, terminator)
+        });
+        // Cancellation is tracked by the pipeline flag
+        job_app_handle
+            .state::<RunningNestings>()
+            .take_cancelled(&job_id);
+        output
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+    .and_then(|result| result);
+    let output = match output {
+        Ok(output) => output,
+        Err(error) => return failed(error),
+    };
+    report.completed_stages.push(PipelineStage::Nesting);
+
+    // Persistence
+    if cancelled() {
+        return Some(Stop::Cancelled);
+    }
+    let quote_id = request.quote_id.as_deref();
+    match store_result(log, data_dir, quote_id, &instance_json, &output).await {
+        Ok(id) => report.result_id = Some(id),
+        Err(error) => return failed(error),
+    }
+    report.completed_stages.push(PipelineStage::Persistence);
+    if cancelled() {
+        return Some(Stop::Cancelled);
+    }
+    report.output = Some(output);
+    None
+}
+
+/// Convert the files of a request one by one, returns (output path, quantity)
+async fn convert_parts(
+    app_handle: &AppHandle,
+    request: &PipelineRequest,
+    cancel: &AtomicBool,
+) -> Result<Vec<(String, u32)>, String> {
+    let pool = db::sqlite_pool(app_handle).await?;
+    let exe_path = converter_exe_path(app_handle)?;
+    let output_dir: PathBuf =
+        workspace::workspace_dir(app_handle, "library")?.join(CONVERSION_CACHE_DIR);
+    std::fs::create_dir_all(&output_dir)
+        .map_err(|e| format!("Failed to create '{}': {}", output_dir.display(), e))?;
+    let options_json = serde_json::to_string(&request.options)
+        .map_err(|e| format!("Failed to serialize conversion options: {}", e))?;
+
+    let mut converted = Vec::new();
+    for file in &request.files {
+        if cancel.load(Ordering::SeqCst) {
+            break;
+        }
+        let (pool, exe_path, output_dir, options, options_json, path) = (
+            pool.clone(),
+            exe_path.clone(),
+            output_dir.clone(),
+            request.options.clone(),
+            options_json.clone(),
+            file.path.clone(),
+        );
+        let output_path = tauri::async_runtime::spawn_blocking(move || {
+            convert_file(
+                &pool,
+                &exe_path,
+                &output_dir,
+                &options,
+                &options_json,
+                &path,
+            )
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|e| format!("Failed to convert '{}': {}", file.path, e))?;
+        converted.push((output_path, file.quantity));
+    }
+    Ok(converted)
+}
+
+/// Stop a running pipeline at the next stage boundary
+///
+/// A running nesting stops right away and its result is discarded.
+#[tauri::command]
+pub fn cancel_pipeline(
+    pipelines: State<'_, RunningPipelines>,
+    nestings: State<'_, RunningNestings>,
+    pipeline_id: String,
+) -> Result<(), String> {
+    if !pipelines.cancel(&pipeline_id) {
+        return Err(format!("No running pipeline '{}'", pipeline_id));
+    }
+    // Not nesting at the moment is fine, the flag stops the next stage
+    let _ = nestings.cancel(&pipeline_id);
+    println!("⏹️ Cancelling pipeline {}", pipeline_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{migrated_pool, test_dir};
+    use tauri::async_runtime::block_on;
+
+    fn output() -> NestingOutput {
+        serde_json::from_value(serde_json::json!({
+            "instance_name": "quote",
+            "strip_width": 800.0,
+            "strip_height": 1500.0,
+            "total_items_placed": 3,
+            "layouts": [],
+            "utilization": 0.6,
+            "computation_time_secs": 2.0,
+            "svg_string": "<svg></svg>",
+        }))
+        .unwrap()
+    }
+
+    async fn count(pool: &SqlitePool, table: &str) -> i64 {
+        let (count,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM {}", table))
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        count
+    }
+
+    const INSTANCE: &str = r#"{"items": []}"#;
+
+    /// Run the writing stages up to `boundary` (0 = before conversion)
+    async fn run_until(log: &UndoLog<'_>, dir: &Path, boundary: usize) {
+        if boundary >= 1 {
+            store_instance(log, dir, INSTANCE).await.unwrap();
+        }
+        // Validation and nesting write nothing
+        if boundary >= 4 {
+            store_result(log, dir, None, INSTANCE, &output())
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_cancel_at_each_stage_boundary_leaves_nothing_behind() {
+        for boundary in 0..=4 {
+            let dir = test_dir("pipeline", &format!("boundary-{}", boundary));
+            let cached = dir.join("library/conversions/part.json");
+            write_file(&cached, "{}").unwrap();
+
+            block_on(async {
+                let pool = migrated_pool(&dir).await;
+                let log = UndoLog::begin(&pool, "run-1").await.unwrap();
+                run_until(&log, &dir, boundary).await;
+
+                let stages = rollback_run(&pool, &dir, "run-1").await.unwrap();
+                let expected = match boundary {
+                    0 => vec![],
+                    1..=3 => vec![PipelineStage::Conversion],
+                    _ => vec![PipelineStage::Persistence, PipelineStage::Conversion],
+                };
+                assert_eq!(stages, expected, "boundary {}", boundary);
+
+                for table in [
+                    "nesting_results",
+                    "instance_blobs",
+                    "pipeline_undo_log",
+                    "pipeline_runs",
+                ] {
+                    assert_eq!(
+                        count(&pool, table).await,
+                        0,
+                        "{} at boundary {}",
+                        table,
+                        boundary
+                    );
+                }
+                pool.close().await;
+            });
+            assert!(workspace::list_files(&dir.join("jobs")).unwrap().is_empty());
+            assert!(workspace::list_files(&dir.join("svgs")).unwrap().is_empty());
+            // Converted geometry stays cached
+            assert!(cached.exists());
+            let _ = std::fs::remove_dir_all(&dir);
+        }
+    }
+
+    #[test]
+    fn test_completed_run_keeps_result_and_clears_log() {
+        let dir = test_dir("pipeline", "completed");
+        block_on(async {
+            let pool = migrated_pool(&dir).await;
+            let log = UndoLog::begin(&pool, "run-2").await.unwrap();
+            run_until(&log, &dir, 4).await;
+            log.complete().await.unwrap();
+
+            assert_eq!(count(&pool, "nesting_results").await, 1);
+            assert_eq!(count(&pool, "instance_blobs").await, 1);
+            assert_eq!(count(&pool, "pipeline_undo_log").await, 0);
+            let (svg_sha256,): (Option<String>,) =
+                sqlx::query_as("SELECT svg_sha256 FROM nesting_results")
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(svg_sha256, Some(artifact_sha256(b"<svg></svg>")));
+            assert!(recover_pipelines(&pool, &dir).await.unwrap().is_empty());
+            assert_eq!(count(&pool, "nesting_results").await, 1);
+            pool.close().await;
+        });
+        assert!(dir.join("svgs/pipeline/run-2.svg").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rollback_interrupted_by_crash_finishes_at_startup() {
+        let dir = test_dir("pipeline", "crash");
+        block_on(async {
+            let pool = migrated_pool(&dir).await;
+            let log = UndoLog::begin(&pool, "run-3").await.unwrap();
+            run_until(&log, &dir, 4).await;
+
+            // The crash hit after the row was deleted, before the SVG was
+            sqlx::query("UPDATE pipeline_runs SET status = 'rolling_back'")
+                .execute(&pool)
+                .await
+                .unwrap();
+            let (seq, id): (i64, i64) = sqlx::query_as(
+                "SELECT seq, json_extract(action, '$.id') FROM pipeline_undo_log
+                 WHERE action LIKE '%delete_nesting_result%'",
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            let mut conn = pool.acquire().await.unwrap();
+            assert!(delete_nesting_result(&mut conn, id).await.unwrap());
+            drop(conn);
+            sqlx::query("DELETE FROM pipeline_undo_log WHERE seq = ?")
+                .bind(seq)
+                .execute(&pool)
+                .await
+                .unwrap();
+
+            assert_eq!(
+                recover_pipelines(&pool, &dir).await.unwrap(),
+                vec!["run-3".to_string()]
+            );
+            for table in [
+                "nesting_results",
+                "instance_blobs",
+                "pipeline_undo_log",
+                "pipeline_runs",
+            ] {
+                assert_eq!(count(&pool, table).await, 0, "{}", table);
+            }
+            pool.close().await;
+        });
+        assert!(!dir.join("svgs/pipeline/run-3.svg").exists());
+        assert!(!dir.join("jobs/pipeline/run-3/instance.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_merge_part_instances() {
+        let options = ConversionOptions {
+            strip_height: 1500.0,
+            part_spacing: 5.0,
+            arc_segments: 32,
+        };
+        let part = |name: &str| {
+            serde_json::json!({
+                "name": name,
+                "items": [{ "id": 0, "demand": 1, "shape": { "type": "rectangle" } }],
+            })
+            .to_string()
+        };
+        let merged = merge_part_instances(&[(part("a"), 4), (part("b"), 2)], &options).unwrap();
+        let merged: serde_json::Value = serde_json::from_str(&merged).unwrap();
+        assert_eq!(merged["strip_height"], 1500.0);
+        assert_eq!(merged["items"][1]["id"], 1);
+        assert_eq!(merged["items"][0]["demand"], 4);
+        assert_eq!(merged["items"][1]["demand"], 2);
+    }
+}
@@ -0,0 +1,581 @@
+//! Price-break table of a single part
+//!
+//! Prices one part of a quote at several quantities on one material.
+//! The part's geometry comes from the quote's nesting snapshot and is
+//! parsed once for all rows. Each quantity is nested for real while the
+//! time budget allows a short run; later quantities are extrapolated from
+//! the largest nested run (or the area estimate when none was nested) and
+//! flagged as `estimated`, so the quote can disclose it.
+//!
+//! Material cost goes through the same sheet costing as the purchasing
+//! table (`material_requirements`); cutting and piercing are priced per
+//! meter and per pierce, and the cutting time comes from the feed rate and
+//! pierce time. These and the nesting separation are taken from the
+//! cutting profile of the machine and material when one matches, from the
+//! material otherwise. The default price markup is applied as in the
+//! frontend pricing. Operations are not included.
+
+use crate::commands::cutting_profiles::find_cutting_profile;
+use crate::commands::material_requirements::nested_requirement;
+use crate::commands::nesting_batch::load_app_overrides;
+use crate::commands::nesting_metrics::{record_nesting_run, NestingRunMetrics};
+use crate::commands::shutdown;
+use crate::db;
+use crate::nesting_engine::{
+    self, parse_instance, parse_number, quick_estimate_width, ConfigOverrides, DisplayUnits,
+    InstanceGeometry,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted before each row of a price matrix is computed
+pub const PRICE_MATRIX_PROGRESS_EVENT: &str = "quote://price-matrix-progress";
+
+/// Total time for the nesting runs of a matrix when the caller sets none
+const DEFAULT_TIME_BUDGET_SECS: u64 = 60;
+/// Time limit of each nesting run
+const NESTING_TIME_LIMIT_SECS: u64 = 10;
+const PRICE_MARKUP_SETTING: &str = "default_price_markup";
+
+/// Price of the part at one quantity
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PriceMatrixRow {
+    pub quantity: u32,
+    /// Stock sheets to order, including scrap allowance
+    pub sheets: u32,
+    /// Strip length the parts need (in `PriceMatrix::units`)
+    pub strip_width: f64,
+    pub utilization: f64,
+    pub material_cost: f64,
+    pub cutting_cost: f64,
+    pub piercing_cost: f64,
+    /// Cutting and piercing time of all pieces in seconds, None without a feed rate
+    pub cutting_time_secs: Option<f64>,
+    pub unit_cost: f64,
+    pub total: f64,
+    /// Extrapolated instead of nested
+    pub estimated: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PriceMatrix {
+    pub part_id: String,
+    pub quote_id: String,
+    pub material_id: String,
+    /// Cutting profile the prices and separation came from
+    pub cutting_profile_id: Option<i64>,
+    /// Unit of the strip widths
+    pub units: DisplayUnits,
+    /// Rows by ascending quantity
+    pub rows: Vec<PriceMatrixRow>,
+    /// True if any row was extrapolated
+    pub has_estimates: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PriceMatrixProgress {
+    pub part_id: String,
+    pub quantity: u32,
+    /// "nesting" or "estimate"
+    pub stage: String,
+    pub current: usize,
+    pub total: usize,
+}
+
+// Subset of the quote JSON blob (see quoteService.ts)
+#[derive(Deserialize, Debug, Default)]
+struct QuoteFiles {
+    #[serde(default)]
+    files: Vec<QuoteFile>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct QuoteFile {
+    id: String,
+    material: Option<FileMaterial>,
+    metadata: Option<FileMetadata>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FileMaterial {
+    id: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+struct FileMetadata {
+    #[serde(default)]
+    cut_length: f64,
+    #[serde(default)]
+    pierce_count: f64,
+}
+
+/// Stock row the part is priced on
+#[derive(Debug, Clone)]
+struct PricedMaterial {
+    id: String,
+    name: String,
+    grade: String,
+    thickness: f64,
+    sheet_width: f64,
+    cut_price_per_meter: f64,
+    pierce_cost: f64,
+    /// mm/min
+    feed_rate: Option<f64>,
+    /// Seconds per pierce
+    pierce_time: Option<f64>,
+    /// Separation of the nesting runs in mm, None for the app settings
+    separation: Option<f64>,
+    cutting_profile_id: Option<i64>,
+}
+
+/// Strip length and utilization of one quantity
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Extent {
+    strip_width: f64,
+    utilization: f64,
+    estimated: bool,
+}
+
+/// Instance with only the items of one quote file, on a strip of `strip_height`
+///
+/// Items are matched through the `fileId` of their metadata; demands are
+/// set per row by `with_demand`.
+fn part_instance(
+    instance_json: &str,
+    part_id: &str,
+    strip_height: f64,
+) -> Result<serde_json::Value, String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(instance_json).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+        .ok_or("Instance JSON has no items array")?;
+    items.retain(|item| {
+        item.pointer("/metadata/fileId").and_then(|id| id.as_str()) == Some(part_id)
+    });
+    if items.is_empty() {
+        return Err(format!("No nesting geometry stored for part '{}'", part_id));
+    }
+    value["strip_height"] = strip_height.into();
+    Ok(value)
+}
+
+/// Instance JSON for `quantity` pieces of every item
+fn with_demand(part: &serde_json::Value, quantity: u32) -> String {
+    let mut value = part.clone();
+    if let Some(items) = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+    {
+        for item in items {
+            item["demand"] = quantity.into();
+        }
+    }
+    value.to_string()
+}
+
+/// Net area of one piece of every item (mm²)
+fn piece_area(instance: &InstanceGeometry) -> f64 {
+    instance.items.iter().map(|item| item.net_area()).sum()
+}
+
+/// Extent of `quantity` pieces without nesting
+///
+/// Scales the largest nested run linearly by quantity; without one the
+/// area estimate of the instance is used.
+fn extrapolate(instance: &InstanceGeometry, quantity: u32, nested: Option<(u32, f64)>) -> Extent {
+    let strip_width = match nested {
+        Some((nested_quantity, nested_width)) if nested_quantity > 0 => {
+            nested_width * quantity as f64 / nested_quantity as f64
+        }
+        _ => {
+            let mut scaled = instance.clone();
+            for item in &mut scaled.items {
+                item.demand = quantity as u64;
+            }
+            quick_estimate_width(&scaled)
+        }
+    };
+    let strip_area = strip_width * instance.strip_height;
+    Extent {
+        strip_width,
+        utilization: if strip_area > 0.0 {
+            (piece_area(instance) * quantity as f64 / strip_area).min(1.0)
+        } else {
+            0.0
+        },
+        estimated: true,
+    }
+}
+
+fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// Price of one row: material + cutting + piercing, then the markup
+fn price_row(
+    quantity: u32,
+    extent: Extent,
+    sheets: u32,
+    material_cost: f64,
+    material: &PricedMaterial,
+    metadata: FileMetadata,
+    markup_percent: f64,
+) -> PriceMatrixRow {
+    let cutting_cost =
+        round_cents(metadata.cut_length / 1000.0 * material.cut_price_per_meter * quantity as f64);
+    let piercing_cost = round_cents(metadata.pierce_count * material.pierce_cost * quantity as f64);
+    let cutting_time_secs = material.feed_rate.map(|feed_rate| {
+        let per_piece = metadata.cut_length / feed_rate * 60.0
+            + metadata.pierce_count * material.pierce_time.unwrap_or(0.0);
+        per_piece * quantity as f64
+    });
+    let material_cost = round_cents(material_cost);
+    let total = (material_cost + cutting_cost + piercing_cost) * (1.0 + markup_percent / 100.0);
+
+    PriceMatrixRow {
+        quantity,
+        sheets,
+        strip_width: extent.strip_width,
+        utilization: extent.utilization,
+        material_cost,
+        cutting_cost,
+        piercing_cost,
+        cutting_time_secs,
+        unit_cost: round_cents(total / quantity.max(1) as f64),
+        total: round_cents(total),
+        estimated: extent.estimated,
+    }
+}
+
+/// Cost per piece of one part at several quantities
+///
+/// `part_id` is the ID of a quote file; its geometry comes from the
+/// quote's nesting snapshot. Without `material_id` the part's own
+/// material is used; `machine_id` selects the cutting profile, without
+/// it only profiles for any machine apply. Quantities are nested in ascending order while the
+/// runs fit into `time_budget_secs` (default 60 s); the rest are
+/// extrapolated. `quote://price-matrix-progress` is emitted before each row.
+/// Lengths are in mm unless other `units` are requested.
+#[tauri::command]
+pub async fn quantity_price_matrix(
+    app_handle: AppHandle,
+    part_id: String,
+    quantities: Vec<u32>,
+    material_id: Option<String>,
+    machine_id: Option<String>,
+    time_budget_secs: Option<u64>,
+    units: Option<DisplayUnits>,
+) -> Result<PriceMatrix, String> {
+    let mut quantities: Vec<u32> = quantities.into_iter().filter(|q| *q > 0).collect();
+    quantities.sort_unstable();
+    quantities.dedup();
+    if quantities.is_empty() {
+        return Err("No quantities to price".to_string());
+    }
+
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let row: Option<(String, Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT q.id, q.data, s.instance_json
+         FROM quotes q
+         JOIN json_each(q.data, '$.files') f
+         LEFT JOIN nesting_snapshots s ON s.quote_id = q.id
+         WHERE json_extract(f.value, '$.id') = ?
+         LIMIT 1",
+    )
+    .bind(&part_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| format!("Failed to load part '{}': {}", part_id, e))?;
+    let (quote_id, data, instance_json) =
+        row.ok_or_else(|| format!("Part '{}' not found", part_id))?;
+    let instance_json = instance_json
+        .ok_or_else(|| format!("No nesting geometry stored for quote '{}'", quote_id))?;
+
+    let files: QuoteFiles = serde_json::from_str(data.as_deref().unwrap_or("{}"))
+        .map_err(|e| format!("Quote '{}' has invalid data: {}", quote_id, e))?;
+    let file = files
+        .files
+        .into_iter()
+        .find(|file| file.id == part_id)
+        .ok_or_else(|| format!("Part '{}' not found", part_id))?;
+    let material_id = material_id
+        .or_else(|| file.material.map(|material| material.id))
+        .ok_or_else(|| format!("Part '{}' has no material", part_id))?;
+    let material = load_material(&pool, &material_id, machine_id.as_deref()).await?;
+    let metadata = file.metadata.unwrap_or_default();
+    let markup_percent = load_markup(&pool).await?;
+
+    // Geometry is parsed once and shared by all rows
+    let part = part_instance(&instance_json, &part_id, material.sheet_width)?;
+    let instance = parse_instance(&part.to_string())?;
+
+    let app_settings = load_app_overrides(&app_handle).await;
+    let run_settings = ConfigOverrides {
+        time_limit: Some(NESTING_TIME_LIMIT_SECS),
+        separation: material.separation,
+        ..Default::default()
+    };
+    let time_budget =
+        std::time::Duration::from_secs(time_budget_secs.unwrap_or(DEFAULT_TIME_BUDGET_SECS));
+    let started = std::time::Instant::now();
+    let mut largest_nested: Option<(u32, f64)> = None;
+    let mut rows = Vec::with_capacity(quantities.len());
+
+    for (index, &quantity) in quantities.iter().enumerate() {
+        let nest = started.elapsed() + std::time::Duration::from_secs(NESTING_TIME_LIMIT_SECS)
+            <= time_budget;
+        let _ = app_handle.emit(
+            PRICE_MATRIX_PROGRESS_EVENT,
+            PriceMatrixProgress {
+                part_id: part_id.clone(),
+                quantity,
+                stage: if nest { "nesting" } else { "estimate" }.to_string(),
+                current: index,
+                total: quantities.len(),
+            },
+        );
+
+        let nested = if nest {
+            let input =
+                ConfigOverrides::resolve(&run_settings, &ConfigOverrides::default(), &app_settings)
+                    .into_input(with_demand(&part, quantity));
+            let run_started = std::time::Instant::now();
+            let metrics = NestingRunMetrics::for_input(&input);
+            let job_app_handle = app_handle.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || {
+                shutdown::run_registered(&job_app_handle, None, |terminator| {
+                    nesting_engine::run_nesting_engine_with_terminator(input, terminator)
+                })
+            })
+            .await
+            .map_err(|e| format!("Task join error: {}", e))?;
+            record_nesting_run(&app_handle, metrics.finish(&result, run_started.elapsed()));
+
+            match result {
+                Ok(output) if output.status.as_deref() != Some("partial") => Some(Extent {
+                    strip_width: output.strip_width,
+                    utilization: output.utilization,
+                    estimated: false,
+                }),
+                Ok(_) => {
+                    println!("⚠️ {} pieces did not all fit, estimating instead", quantity);
+                    None
+                }
+                Err(e) => {
+                    println!(
+                        "⚠️ Nesting {} pieces failed, estimating instead: {}",
+                        quantity, e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let extent = match nested {
+            Some(extent) => {
+                largest_nested = Some((quantity, extent.strip_width));
+                extent
+            }
+            None => extrapolate(&instance, quantity, largest_nested),
+        };
+
+        let requirement = nested_requirement(
+            &pool,
+            &material.name,
+            Some(material.grade.clone()),
+            material.thickness,
+            piece_area(&instance) * quantity as f64,
+            extent.strip_width,
+            instance.strip_height,
+        )
+        .await?;
+        rows.push(price_row(
+            quantity,
+            extent,
+            requirement.sheets,
+            requirement.cost,
+            &material,
+            metadata,
+            markup_percent,
+        ));
+    }
+
+    let units = units.unwrap_or_default();
+    for row in &mut rows {
+        row.strip_width = units.length(row.strip_width);
+    }
+    let matrix = PriceMatrix {
+        part_id,
+        quote_id,
+        material_id: material.id,
+        cutting_profile_id: material.cutting_profile_id,
+        units,
+        has_estimates: rows.iter().any(|row| row.estimated),
+        rows,
+    };
+    println!(
+        "💲 Price matrix for part {}: {} quantities, {} estimated",
+        matrix.part_id,
+        matrix.rows.len(),
+        matrix.rows.iter().filter(|row| row.estimated).count()
+    );
+    Ok(matrix)
+}
+
+type MaterialRow = (
+    String,
+    String,
+    f64,
+    f64,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+);
+
+/// Stock row with the cutting profile of `machine_id` at its thickness
+async fn load_material(
+    pool: &SqlitePool,
+    material_id: &str,
+    machine_id: Option<&str>,
+) -> Result<PricedMaterial, String> {
+    let row: Option<MaterialRow> = sqlx::query_as(
+        "SELECT name, grade, thickness, sheet_width, cut_price_per_meter, pierce_cost,
+                cutting_speed, pierce_time
+         FROM material_stock WHERE id = ?",
+    )
+    .bind(material_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to load material '{}': {}", material_id, e))?;
+    let (name, grade, thickness, sheet_width, cut_price_per_meter, pierce_cost, speed, pierce) =
+        row.ok_or_else(|| format!("Material '{}' not found", material_id))?;
+    let profile = find_cutting_profile(pool, machine_id, material_id, Some(thickness))
+        .await?
+        .map(|resolved| resolved.profile);
+    let fields = profile.as_ref().map(|profile| &profile.fields);
+
+    Ok(PricedMaterial {
+        id: material_id.to_string(),
+        name,
+        grade,
+        thickness,
+        sheet_width,
+        cut_price_per_meter: fields
+            .and_then(|fields| fields.price_per_meter)
+            .or(cut_price_per_meter)
+            .unwrap_or(0.0),
+        pierce_cost: fields
+            .and_then(|fields| fields.price_per_pierce)
+            .or(pierce_cost)
+            .unwrap_or(0.0),
+        feed_rate: fields
+            .and_then(|fields| fields.feed_rate)
+            .or(speed)
+            .filter(|rate| *rate > 0.0),
+        pierce_time: fields.and_then(|fields| fields.pierce_time).or(pierce),
+        separation: fields.and_then(|fields| fields.min_separation),
+        cutting_profile_id: profile.as_ref().map(|profile| profile.id),
+    })
+}
+
+async fn load_markup(pool: &SqlitePool) -> Result<f64, String> {
+    let value: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = ?")
+        .bind(PRICE_MARKUP_SETTING)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to load price markup: {}", e))?;
+
+    Ok(value
+        .and_then(|(value,)| parse_number(&value).ok())
+        .unwrap_or(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> String {
+        let rectangle = |id: u64, file_id: &str, width: f64| {
+            serde_json::json!({
+                "id": id,
+                "demand": 3,
+                "metadata": { "fileId": file_id },
+                "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": width, "height": 50.0 } }
+            })
+        };
+        serde_json::json!({
+            "name": "quote",
+            "strip_height": 1250.0,
+            "items": [rectangle(0, "bracket", 100.0), rectangle(1, "plate", 400.0)]
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_part_instance_keeps_only_the_part() {
+        let part = part_instance(&snapshot(), "bracket", 1500.0).unwrap();
+        let instance = parse_instance(&with_demand(&part, 50)).unwrap();
+        assert_eq!(instance.strip_height, 1500.0);
+        assert_eq!(instance.items.len(), 1);
+        assert_eq!(instance.items[0].demand, 50);
+        assert!(part_instance(&snapshot(), "missing", 1500.0).is_err());
+    }
+
+    #[test]
+    fn test_extrapolation_scales_the_largest_nested_run() {
+        let part = part_instance(&snapshot(), "bracket", 1000.0).unwrap();
+        let instance = parse_instance(&part.to_string()).unwrap();
+
+        let extent = extrapolate(&instance, 500, Some((100, 600.0)));
+        assert!(extent.estimated);
+        assert_eq!(extent.strip_width, 3000.0);
+        // 500 pieces of 5000 mm² on 3000 x 1000 mm
+        assert!((extent.utilization - 2.5e6 / 3e6).abs() < 1e-9);
+
+        // Nothing nested: the area estimate
+        let estimate = extrapolate(&instance, 500, None);
+        assert!(estimate.strip_width >= 2500.0);
+    }
+
+    #[test]
+    fn test_unit_cost_includes_markup() {
+        let material = PricedMaterial {
+            id: "ms-2".to_string(),
+            name: "Mild Steel".to_string(),
+            grade: "S235".to_string(),
+            thickness: 2.0,
+            sheet_width: 1250.0,
+            cut_price_per_meter: 0.5,
+            pierce_cost: 0.1,
+            feed_rate: Some(3000.0),
+            pierce_time: Some(0.5),
+            separation: None,
+            cutting_profile_id: None,
+        };
+        let metadata = FileMetadata {
+            cut_length: 300.0,
+            pierce_count: 2.0,
+        };
+        let extent = Extent {
+            strip_width: 500.0,
+            utilization: 0.8,
+            estimated: false,
+        };
+
+        let row = price_row(10, extent, 1, 40.0, &material, metadata, 40.0);
+        assert_eq!(row.cutting_cost, 1.5);
+        assert_eq!(row.piercing_cost, 2.0);
+        assert_eq!(row.total, 60.9);
+        assert_eq!(row.unit_cost, 6.09);
+        // 10 x (300 mm at 3000 mm/min + 2 pierces of 0.5 s)
+        assert!((row.cutting_time_secs.unwrap() - 70.0).abs() < 1e-9);
+        assert!(!row.estimated);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::commands::nesting_metrics::{record_nesting_run, NestingRunMetrics};
+use crate::db;
+use crate::nesting_engine::{
+    self, compare_fingerprints, fingerprint_instance, provenance_warnings, DisplayUnits,
+    FingerprintChange, InstanceFingerprint, NestingConfig, NestingInput, NestingOutput,
+    OutputComparison, Provenance, APP_VERSION, ENGINE_VERSION,
+};
+use serde::Serialize;
+use sqlx::SqliteExecutor;
+
+/// Result of replaying a stored nesting for a quote
+#[derive(Serialize, Debug)]
+pub struct RenestReport {
+    pub quote_id: String,
+    /// True when the instance matched and the layout was regenerated
+    pub reproduced: bool,
+    /// What changed between the stored and the current instance
+    pub changes: Vec<FingerprintChange>,
+    pub stored_engine_version: String,
+    pub engine_version: String,
+    /// Results may legitimately differ when the engine version changed
+    pub engine_version_changed: bool,
+    pub stored_algorithm_fingerprint: Option<String>,
+    pub algorithm_fingerprint: String,
+    /// Engine or settings differences between the stored and the replayed run
+    pub warnings: Vec<String>,
+    pub output: Option<NestingOutput>,
+}
+
+/// Store seed, resolved config, instance hash and provenance for a quote
+///
+/// Called when a nesting result is saved so the layout can be
+/// regenerated later with `renest_exact`.
+#[tauri::command]
+pub async fn save_nesting_snapshot(
+    app_handle: tauri::AppHandle,
+    quote_id: String,
+    instance_json: String,
+    output: NestingOutput,
+) -> Result<(), String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let seed = store_nesting_snapshot(&pool, &quote_id, instance_json, output).await?;
+
+    println!("💾 Saved nesting snapshot for quote {} (seed={})", quote_id, seed);
+    Ok(())
+}
+
+/// Insert or replace the snapshot row of a quote, returns the stored seed
+pub(crate) async fn store_nesting_snapshot(
+    executor: impl SqliteExecutor<'_>,
+    quote_id: &str,
+    instance_json: String,
+    output: NestingOutput,
+) -> Result<u64, String> {
+    // Snapshots are stored in mm
+    let output = output.to_units(DisplayUnits::Mm);
+    let seed = output
+        .seed
+        .ok_or("Nesting output has no seed, cannot store snapshot")?;
+    let config = output
+        .resolved_config
+        .ok_or("Nesting output has no resolved config, cannot store snapshot")?;
+    let engine_version = output
+        .engine_version
+        .unwrap_or_else(|| ENGINE_VERSION.to_string());
+    let app_version = output
+        .app_version
+        .unwrap_or_else(|| APP_VERSION.to_string());
+    let algorithm_fingerprint = output
+        .algorithm_fingerprint
+        .unwrap_or_else(|| nesting_engine::algorithm_fingerprint(&config));
+
+    let fingerprint = fingerprint_instance(&instance_json)?;
+    let config_json = serde_json::to_string(&config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let fingerprint_json = serde_json::to_string(&fingerprint)
+        .map_err(|e| format!("Failed to serialize fingerprint: {}", e))?;
+
+    sqlx::query(
+        "INSERT INTO nesting_snapshots
+            (quote_id, seed, config_json, instance_json, instance_hash, fingerprint_json,
+             engine_version, app_version, algorithm_fingerprint)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(quote_id) DO UPDATE SET
+            seed = excluded.seed,
+            config_json = excluded.config_json,
+            instance_json = excluded.instance_json,
+            instance_hash = excluded.instance_hash,
+            fingerprint_json = excluded.fingerprint_json,
+            engine_version = excluded.engine_version,
+            app_version = excluded.app_version,
+            algorithm_fingerprint = excluded.algorithm_fingerprint,
+            updated_at = datetime('now')",
+    )
+    .bind(quote_id)
+    .bind(seed.to_string())
+    .bind(config_json)
+    .bind(instance_json)
+    .bind(&fingerprint.instance_hash)
+    .bind(fingerprint_json)
+    .bind(engine_version)
+    .bind(app_version)
+    .bind(algorithm_fingerprint)
+    .execute(executor)
+    .await
+    .map_err(|e| format!("Failed to save nesting snapshot for quote '{}': {}", quote_id, e))?;
+
+    Ok(seed)
+}
+
+/// Regenerate a quote's stored layout with the stored seed and config
+///
+/// The instance is re-built from the stored geometry (or taken from
+/// `current_instance_json` when the frontend passes the quote's current
+/// parts) and its fingerprint must match the stored one. On a mismatch
+/// the report lists what changed and no layout is produced.
+///
+/// Note: the optimizer is time-limited, so a machine under different
+/// load may still stop at a different point in the search.
+#[tauri::command]
+pub async fn renest_exact(
+    app_handle: tauri::AppHandle,
+    quote_id: String,
+    current_instance_json: Option<String>,
+) -> Result<RenestReport, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    #[allow(clippy::type_complexity)]
+    let row: Option<(String, String, String, String, String, Option<String>, Option<String>)> =
+        sqlx::query_as(
+            "SELECT seed, config_json, instance_json, fingerprint_json, engine_version,
+                    app_version, algorithm_fingerprint
+             FROM nesting_snapshots WHERE quote_id = ?",
+        )
+        .bind(&quote_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to load nesting snapshot for quote '{}': {}", quote_id, e))?;
+
+    let (
+        seed,
+        config_json,
+        stored_instance_json,
+        fingerprint_json,
+        stored_engine_version,
+        stored_app_version,
+        stored_algorithm_fingerprint,
+    ) = row.ok_or_else(|| format!("No nesting snapshot stored for quote '{}'", quote_id))?;
+
+    let seed: u64 = seed
+        .parse()
+        .map_err(|e| format!("Stored seed '{}' is invalid: {}", seed, e))?;
+    let config: NestingConfig = serde_json::from_str(&config_json)
+        .map_err(|e| format!("Stored config is invalid: {}", e))?;
+    let stored_fingerprint: InstanceFingerprint = serde_json::from_str(&fingerprint_json)
+        .map_err(|e| format!("Stored fingerprint is invalid: {}", e))?;
+
+    let instance_json = current_instance_json.unwrap_or(stored_instance_json);
+    let current_fingerprint = fingerprint_instance(&instance_json)?;
+    let changes = compare_fingerprints(&stored_fingerprint, &current_fingerprint);
+
+    let current = Provenance::current(&config);
+    let warnings = provenance_warnings(
+        &Provenance {
+            engine_version: Some(stored_engine_version.clone()),
+            app_version: stored_app_version,
+            algorithm_fingerprint: stored_algorithm_fingerprint.clone(),
+        },
+        &current,
+    );
+    for warning in &warnings {
+        println!("⚠️ Replaying quote {}: {}", quote_id, warning);
+    }
+    let engine_version_changed = stored_engine_version != ENGINE_VERSION;
+
+    let mut report = RenestReport {
+        quote_id,
+        reproduced: false,
+        changes,
+        stored_engine_version,
+        engine_version: ENGINE_VERSION.to_string(),
+        engine_version_changed,
+        stored_algorithm_fingerprint,
+        algorithm_fingerprint: current.algorithm_fingerprint.unwrap_or_default(),
+        warnings,
+        output: None,
+    };
+
+    if !report.changes.is_empty() {
+        println!(
+            "❌ Instance for quote {} changed since it was nested: {:?}",
+            report.quote_id, report.changes
+        );
+        return Ok(report);
+    }
+
+    // Zones as stored, so later clamp edits don't change the replay
+    let input = NestingInput::builder()
+        .json_input(instance_json)
+        .config(&config)
+        .seed(seed)
+        .build()
+        .map_err(|issues| {
+            let messages: Vec<String> = issues.iter().map(|issue| issue.to_string()).collect();
+            format!("Stored config is invalid: {}", messages.join("; "))
+        })?;
+
+    let started = std::time::Instant::now();
+    let metrics = NestingRunMetrics::for_input(&input);
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        nesting_engine::run_nesting_engine(input)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+    .and_then(|result| result);
+    record_nesting_run(&app_handle, metrics.finish(&result, started.elapsed()));
+    let output = result?;
+
+    report.reproduced = true;
+    report.output = Some(output);
+    Ok(report)
+}
+
+/// Compare two nesting outputs (e.g. before and after an engine upgrade)
+///
+/// The comparison carries warnings when the outputs were produced by
+/// different engine versions or optimizer settings.
+#[tauri::command]
+pub async fn compare_nesting_outputs(
+    output_a: NestingOutput,
+    output_b: NestingOutput,
+) -> Result<OutputComparison, String> {
+    let comparison = nesting_engine::compare_outputs(
+        &output_a.to_units(DisplayUnits::Mm),
+        &output_b.to_units(DisplayUnits::Mm),
+    );
+    for warning in &comparison.warnings {
+        println!("⚠️ Comparing nesting outputs: {}", warning);
+    }
+
+    println!(
+        "🔍 Nesting comparison: utilization {:.1}% vs {:.1}%, {} vs {} items placed",
+        comparison.utilization_a * 100.0,
+        comparison.utilization_b * 100.0,
+        comparison.items_placed_a,
+        comparison.items_placed_b
+    );
+    Ok(comparison)
+}
@@ -0,0 +1,545 @@
+//! Saving a quote in one transaction
+//!
+//! The frontend saved the quote row, the nesting snapshot and the
+//! follow-up task with separate calls, and a failure midway left orphan
+//! rows behind. `save_quote` checks the whole payload first, then writes
+//! the quote row with its data blob (part files and priced lines), the
+//! snapshot of the active nesting result, the follow-up task and an
+//! audit entry in one transaction. Saves of the same quote are
+//! serialized: a second save while one is running fails with a conflict
+//! instead of interleaving its writes.
+
+use crate::commands::quote_nesting::store_nesting_snapshot;
+use crate::commands::response::{respond, CommandError, CommandResponse, ErrorCode};
+use crate::db;
+use crate::nesting_engine::NestingOutput;
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tauri::State;
+
+/// Quote number prefix when the payload has no quote number
+const DEFAULT_QUOTE_PREFIX: &str = "Q";
+/// Values of the `quotes.status` check constraint
+const QUOTE_STATUSES: &[&str] = &["draft", "sent", "accepted", "rejected", "expired"];
+/// Tasks still to be done, updated instead of adding another one
+const OPEN_TASK_STATUSES: &str = "'pending', 'in_progress'";
+
+/// Priced line of a quote
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct QuoteLine {
+    /// `id` of the part file of the quote the line prices
+    pub part_id: String,
+    pub quantity: u32,
+    pub unit_price: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Active nesting result, stored as the quote's snapshot
+#[derive(Deserialize, Debug, Clone)]
+pub struct QuoteNesting {
+    pub instance_json: String,
+    pub output: NestingOutput,
+}
+
+/// Follow-up task of a quote
+#[derive(Deserialize, Debug, Clone)]
+pub struct QuoteTask {
+    pub title: String,
+    pub description: Option<String>,
+    /// Default: follow_up
+    pub category: Option<String>,
+    /// Default: normal
+    pub priority: Option<String>,
+    /// ISO datetime
+    pub due_date: Option<String>,
+}
+
+/// Everything saved with a quote
+///
+/// Unset header fields keep their stored value, or take the column
+/// default for a new quote.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct QuotePayload {
+    /// None for a new quote
+    pub id: Option<String>,
+    /// Generated from the quote counter for a new quote when unset
+    pub quote_number: Option<String>,
+    pub client_id: Option<String>,
+    pub status: Option<String>,
+    pub validity_days: Option<i64>,
+    pub price_markup: Option<f64>,
+    pub material_markup: Option<f64>,
+    pub tax_rate: Option<f64>,
+    pub discount: Option<f64>,
+    pub hidden_discount: Option<f64>,
+    #[serde(default)]
+    pub subtotal: f64,
+    #[serde(default)]
+    pub total: f64,
+    pub notes: Option<String>,
+    pub reference: Option<String>,
+    pub created_by: Option<String>,
+    /// Part files of the quote (`DxfFile` objects, each with an `id`)
+    #[serde(default)]
+    pub files: Vec<serde_json::Value>,
+    /// Replace the stored lines
+    #[serde(default)]
+    pub lines: Vec<QuoteLine>,
+    /// Other fields of the data blob (client, summary, nestingResult)
+    #[serde(default)]
+    pub data: serde_json::Map<String, serde_json::Value>,
+    /// Snapshot of the active nesting result, kept as stored when unset
+    pub nesting: Option<QuoteNesting>,
+    pub task: Option<QuoteTask>,
+}
+
+/// IDs written by `save_quote`
+#[derive(Serialize, Debug, Clone)]
+pub struct SavedQuoteIds {
+    pub quote_id: String,
+    pub quote_number: String,
+    /// False when an existing quote was updated
+    pub created: bool,
+    pub task_id: Option<String>,
+    pub audit_id: i64,
+}
+
+/// Quotes being saved, by ID
+#[derive(Default)]
+pub struct QuoteSaveLocks {
+    saving: Mutex<HashSet<String>>,
+}
+
+/// Save in progress, released when dropped
+pub struct QuoteSaveLock<'a> {
+    owner: &'a QuoteSaveLocks,
+    quote_id: String,
+}
+
+impl Drop for QuoteSaveLock<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut saving) = self.owner.saving.lock() {
+            saving.remove(&self.quote_id);
+        }
+    }
+}
+
+impl QuoteSaveLocks {
+    /// Claim a quote for saving; fails while another save holds it
+    pub fn lock(&self, quote_id: &str) -> Result<QuoteSaveLock<'_>, CommandError> {
+        let mut saving = self
+            .saving
+            .lock()
+            .map_err(|_| CommandError::new(ErrorCode::Internal, "Quote save locks unavailable"))?;
+        if !saving.insert(quote_id.to_string()) {
+            return Err(CommandError::new(
+                ErrorCode::Conflict,
+                format!(
+                    "Quote '{}' is being saved by another request, try again when it has finished",
+                    quote_id
+                ),
+            ));
+        }
+        Ok(QuoteSaveLock {
+            owner: self,
+            quote_id: quote_id.to_string(),
+        })
+    }
+}
+
+/// Problems that keep a payload from being saved, all at once
+pub fn validate_payload(payload: &QuotePayload) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if payload
+        .client_id
+        .as_deref()
+        .is_none_or(|id| id.trim().is_empty())
+    {
+        problems.push("Quote has no customer".to_string());
+    }
+    if let Some(status) = &payload.status {
+        if !QUOTE_STATUSES.contains(&status.as_str()) {
+            problems.push(format!("Unknown quote status '{}'", status));
+        }
+    }
+    let amounts = [
+        ("subtotal", Some(payload.subtotal)),
+        ("total", Some(payload.total)),
+        ("discount", payload.discount),
+        ("hidden_discount", payload.hidden_discount),
+    ];
+    for (name, amount) in amounts {
+        if let Some(amount) = amount.filter(|amount| !amount.is_finite() || *amount < 0.0) {
+            problems.push(format!("{} must not be negative, got {}", name, amount));
+        }
+    }
+
+    let part_ids: HashSet<&str> = payload
+        .files
+        .iter()
+        .filter_map(|file| file.get("id")?.as_str())
+        .collect();
+    for (i, line) in payload.lines.iter().enumerate() {
+        if !part_ids.contains(line.part_id.as_str()) {
+            problems.push(format!(
+                "Line {} refers to unknown part '{}'",
+                i + 1,
+                line.part_id
+            ));
+        }
+        if !line.unit_price.is_finite() || line.unit_price < 0.0 {
+            problems.push(format!(
+                "Line {} has a negative price {}",
+                i + 1,
+                line.unit_price
+            ));
+        }
+        if line.quantity == 0 {
+            problems.push(format!("Line {} has no quantity", i + 1));
+        }
+    }
+    if let Some(task) = &payload.task {
+        if task.title.trim().is_empty() {
+            problems.push("Task has no title".to_string());
+        }
+    }
+
+    problems
+}
+
+/// Data blob in the shape the frontend saves (`QuoteDataBlob`), with the lines
+fn quote_data(payload: &QuotePayload) -> Result<String, String> {
+    let mut data = payload.data.clone();
+    data.insert("files".to_string(), payload.files.clone().into());
+    data.insert(
+        "lines".to_string(),
+        serde_json::to_value(&payload.lines)
+            .map_err(|e| format!("Failed to serialize quote lines: {}", e))?,
+    );
+    serde_json::to_string(&data).map_err(|e| format!("Failed to serialize quote data: {}", e))
+}
+
+/// Save a quote with its lines, nesting snapshot, task and audit entry
+///
+/// Validation runs before any write; nothing is written unless all of it
+/// succeeds. Fails with `conflict` while another save of the same quote
+/// is running.
+#[tauri::command]
+pub async fn save_quote(
+    app_handle: tauri::AppHandle,
+    locks: State<'_, QuoteSaveLocks>,
+    quote_payload: QuotePayload,
+) -> CommandResponse<SavedQuoteIds> {
+    let result = async {
+        let problems = validate_payload(&quote_payload);
+        if !problems.is_empty() {
+            return Err(CommandError::new(
+                ErrorCode::InvalidInput,
+                problems.join("; "),
+            ));
+        }
+
+        let quote_id = quote_payload
+            .id
+            .clone()
+            .unwrap_or_else(|| format!("quote_{}", uuid::Uuid::new_v4()));
+        let _lock = locks.lock(&quote_id)?;
+
+        let pool = db::sqlite_pool(&app_handle).await?;
+        let db_err = |e: sqlx::Error| format!("Failed to save quote '{}': {}", quote_id, e);
+        // Dropped without commit on any error, which rolls everything back
+        let mut tx = pool.begin().await.map_err(db_err)?;
+        let saved = write_quote(&mut tx, &quote_id, quote_payload).await?;
+        tx.commit().await.map_err(db_err)?;
+        Ok(saved)
+    }
+    .await;
+
+    if let Ok(saved) = &result {
+        println!(
+            "💾 Saved quote {} ({}, audit #{})",
+            saved.quote_number,
+            if saved.created { "created" } else { "updated" },
+            saved.audit_id
+        );
+    }
+    respond(result)
+}
+
+async fn write_quote(
+    conn: &mut SqliteConnection,
+    quote_id: &str,
+    payload: QuotePayload,
+) -> Result<SavedQuoteIds, String> {
+    let db_err = |e: sqlx::Error| format!("Failed to save quote '{}': {}", quote_id, e);
+    let data = quote_data(&payload)?;
+
+    let existing: Option<(String,)> =
+        sqlx::query_as("SELECT quote_number FROM quotes WHERE id = ?")
+            .bind(quote_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(db_err)?;
+    let created = existing.is_none();
+
+    let quote_number = match existing {
+        Some((quote_number,)) => {
+            sqlx::query(
+                "UPDATE quotes SET
+                    client_id = ?, status = COALESCE(?, status),
+                    validity_days = COALESCE(?, validity_days),
+                    price_markup = COALESCE(?, price_markup),
+                    material_markup = COALESCE(?, material_markup),
+                    tax_rate = COALESCE(?, tax_rate), discount = COALESCE(?, discount),
+                    hidden_discount = COALESCE(?, hidden_discount),
+                    subtotal = ?, total = ?, notes = ?, reference = ?, data = ?,
+                    updated_at = datetime('now')
+                 WHERE id = ?",
+            )
+            .bind(&payload.client_id)
+            .bind(&payload.status)
+            .bind(payload.validity_days)
+            .bind(payload.price_markup)
+            .bind(payload.material_markup)
+            .bind(payload.tax_rate)
+            .bind(payload.discount)
+            .bind(payload.hidden_discount)
+            .bind(payload.subtotal)
+            .bind(payload.total)
+            .bind(&payload.notes)
+            .bind(&payload.reference)
+            .bind(&data)
+            .bind(quote_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_err)?;
+            quote_number
+        }
+        None => {
+            let quote_number = match &payload.quote_number {
+                Some(quote_number) => quote_number.clone(),
+                None => next_quote_number(&mut *conn, DEFAULT_QUOTE_PREFIX).await?,
+            };
+            sqlx::query(
+                "INSERT INTO quotes
+                    (id, quote_number, client_id, status, validity_days, price_markup,
+                     material_markup, tax_rate, discount, hidden_discount, subtotal, total,
+                     notes, reference, data, created_by)
+                 VALUES (?, ?, ?, COALESCE(?, 'draft'), COALESCE(?, 7), COALESCE(?, 0),
+                         COALESCE(?, 0), COALESCE(?, 0), COALESCE(?, 0), COALESCE(?, 0),
+                         ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(quote_id)
+            .bind(&quote_number)
+            .bind(&payload.client_id)
+            .bind(&payload.status)
+            .bind(payload.validity_days)
+            .bind(payload.price_markup)
+            .bind(payload.material_markup)
+            .bind(payload.tax_rate)
+            .bind(payload.discount)
+            .bind(payload.hidden_discount)
+            .bind(payload.subtotal)
+            .bind(payload.total)
+            .bind(&payload.notes)
+            .bind(&payload.reference)
+            .bind(&data)
+            .bind(&payload.created_by)
+            .execute(&mut *conn)
+            .await
+            .map_err(db_err)?;
+            quote_number
+        }
+    };
+
+    let nesting_linked = payload.nesting.is_some();
+    if let Some(nesting) = payload.nesting {
+        store_nesting_snapshot(&mut *conn, quote_id, nesting.instance_json, nesting.output).await?;
+    }
+
+    let task_id = match &payload.task {
+        Some(task) => Some(
+            upsert_task(
+                &mut *conn,
+                quote_id,
+                payload.client_id.as_deref(),
+                payload.created_by.as_deref(),
+                task,
+            )
+            .await?,
+        ),
+        None => None,
+    };
+
+    let summary = serde_json::json!({
+        "lines": payload.lines.len(),
+        "total": payload.total,
+        "nesting_linked": nesting_linked,
+        "task_id": task_id,
+    });
+    let audit: (i64,) = sqlx::query_as(
+        "INSERT INTO quote_audit (quote_id, action, actor, summary)
+         VALUES (?, ?, ?, ?) RETURNING id",
+    )
+    .bind(quote_id)
+    .bind(if created { "created" } else { "updated" })
+    .bind(payload.created_by.as_deref().unwrap_or("ADMIN"))
+    .bind(summary.to_string())
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(db_err)?;
+
+    Ok(SavedQuoteIds {
+        quote_id: quote_id.to_string(),
+        quote_number,
+        created,
+        task_id,
+        audit_id: audit.0,
+    })
+}
+
+/// Next number of a prefix's counter, e.g. Q00042
+async fn next_quote_number(conn: &mut SqliteConnection, prefix: &str) -> Result<String, String> {
+    let (number,): (i64,) = sqlx::query_as(
+        "INSERT INTO quote_counter (prefix, last_number) VALUES (?, 1)
+         ON CONFLICT(prefix) DO UPDATE SET last_number = last_number + 1
+         RETURNING last_number",
+    )
+    .bind(prefix)
+    .fetch_one(conn)
+    .await
+    .map_err(|e| format!("Failed to number quote with prefix '{}': {}", prefix, e))?;
+    Ok(format!("{}{:05}", prefix, number))
+}
+
+/// Update the quote's open task of the same category, or add one
+async fn upsert_task(
+    conn: &mut SqliteConnection,
+    quote_id: &str,
+    client_id: Option<&str>,
+    created_by: Option<&str>,
+    task: &QuoteTask,
+) -> Result<String, String> {
+    let db_err = |e: sqlx::Error| format!("Failed to save task of quote '{}': {}", quote_id, e);
+    let category = task.category.as_deref().unwrap_or("follow_up");
+    let priority = task.priority.as_deref().unwrap_or("normal");
+
+    let open: Option<(String,)> = sqlx::query_as(&format!(
+        "SELECT id FROM tasks
+         WHERE quote_id = ? AND category = ? AND status IN ({})
+         ORDER BY created_at LIMIT 1",
+        OPEN_TASK_STATUSES
+    ))
+    .bind(quote_id)
+    .bind(category)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(db_err)?;
+
+    if let Some((task_id,)) = open {
+        sqlx::query(
+            "UPDATE tasks SET title = ?, description = ?, priority = ?, due_date = ?
+             WHERE id = ?",
+        )
+        .bind(&task.title)
+        .bind(&task.description)
+        .bind(priority)
+        .bind(&task.due_date)
+        .bind(&task_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(db_err)?;
+        return Ok(task_id);
+    }
+
+    let task_id = format!("task_{}", uuid::Uuid::new_v4());
+    sqlx::query(
+        "INSERT INTO tasks
+            (id, title, description, category, priority, quote_id, client_id, due_date, created_by)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&task_id)
+    .bind(&task.title)
+    .bind(&task.description)
+    .bind(category)
+    .bind(priority)
+    .bind(quote_id)
+    .bind(client_id)
+    .bind(&task.due_date)
+    .bind(created_by.unwrap_or("ADMIN"))
+    .execute(&mut *conn)
+    .await
+    .map_err(db_err)?;
+    Ok(task_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload() -> QuotePayload {
+        QuotePayload {
+            client_id: Some("client_1".to_string()),
+            subtotal: 120.0,
+            total: 132.0,
+            files: vec![serde_json::json!({ "id": "part_a", "name": "bracket.dxf" })],
+            lines: vec![QuoteLine {
+                part_id: "part_a".to_string(),
+                quantity: 4,
+                unit_price: 30.0,
+                description: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_valid_payload_and_data_blob() {
+        let payload = payload();
+        assert!(validate_payload(&payload).is_empty());
+
+        let data: serde_json::Value = serde_json::from_str(&quote_data(&payload).unwrap()).unwrap();
+        assert_eq!(data["files"][0]["id"], "part_a");
+        assert_eq!(data["lines"][0]["quantity"], 4);
+    }
+
+    #[test]
+    fn test_invalid_payload_reports_every_problem() {
+        let mut payload = payload();
+        payload.client_id = None;
+        payload.total = -1.0;
+        payload.lines.push(QuoteLine {
+            part_id: "part_gone".to_string(),
+            quantity: 1,
+            unit_price: -5.0,
+            description: None,
+        });
+
+        let problems = validate_payload(&payload);
+        assert_eq!(
+            problems,
+            vec![
+                "Quote has no customer",
+                "total must not be negative, got -1",
+                "Line 2 refers to unknown part 'part_gone'",
+                "Line 2 has a negative price -5",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_concurrent_saves_of_one_quote_conflict() {
+        let locks = QuoteSaveLocks::default();
+        let first = locks.lock("quote_1").unwrap();
+        let error = locks.lock("quote_1").err().unwrap();
+        assert_eq!(error.code, ErrorCode::Conflict);
+        assert!(locks.lock("quote_2").is_ok());
+
+        drop(first);
+        assert!(locks.lock("quote_1").is_ok());
+    }
+}
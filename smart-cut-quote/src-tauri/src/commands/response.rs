@@ -0,0 +1,221 @@
+//! Result envelope shared by Tauri commands
+//!
+//! A command answers with a `CommandResponse<T>`; the payload fields are
+//! flattened into the envelope:
+//!
+//! ```json
+//! { "ok": true, "warnings": [], "output_path": "C:/jobs/parts.json" }
+//! { "ok": false, "code": "not_found", "message": "...", "warnings": [] }
+//! ```
+//!
+//! A failed response may still carry a payload, e.g. the files converted
+//! before a batch was cancelled. `success` and `error` repeat `ok` and
+//! `message` for callers of the older `{ success, error }` results; they
+//! will be removed in the next release.
+
+use crate::file_access::FileAccessError;
+use serde::Serialize;
+
+/// Kind of failure, for the frontend to react on without parsing messages
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidInput,
+    NotFound,
+    PermissionDenied,
+    /// An external executable (dxf-converter, sparrow-cli) failed
+    ToolFailed,
+    Cancelled,
+    /// A nesting run was cancelled before it found any layout to return
+    CancelledBeforeSolution,
+    /// Another request is changing the same record, retry later
+    Conflict,
+    /// A stored file no longer matches its recorded checksum
+    Corrupt,
+    Internal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl CommandError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        CommandError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Errors of the helpers returning `Result<_, String>` are internal
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        CommandError::new(ErrorCode::Internal, message)
+    }
+}
+
+impl From<FileAccessError> for CommandError {
+    fn from(error: FileAccessError) -> Self {
+        let code = match &error {
+            FileAccessError::PermissionDenied { .. } => ErrorCode::PermissionDenied,
+            FileAccessError::Io { kind, .. } if *kind == std::io::ErrorKind::NotFound => {
+                ErrorCode::NotFound
+            }
+            FileAccessError::Io { .. } => ErrorCode::Internal,
+        };
+        CommandError::new(code, error.to_string())
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CommandResponse<T> {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub warnings: Vec<String>,
+    #[serde(flatten)]
+    pub data: Option<T>,
+    /// Same as `ok` (deprecated)
+    success: bool,
+    /// Same as `message` (deprecated)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl<T> CommandResponse<T> {
+    pub fn ok(data: T) -> Self {
+        CommandResponse {
+            ok: true,
+            code: None,
+            message: None,
+            warnings: Vec::new(),
+            data: Some(data),
+            success: true,
+            error: None,
+        }
+    }
+
+    pub fn err(error: CommandError) -> Self {
+        CommandResponse {
+            ok: false,
+            code: Some(error.code),
+            message: Some(error.message.clone()),
+            warnings: Vec::new(),
+            data: None,
+            success: false,
+            error: Some(error.message),
+        }
+    }
+
+    /// Failed, with the part of the work that was done
+    pub fn partial(data: T, error: CommandError) -> Self {
+        CommandResponse {
+            data: Some(data),
+            ..Self::err(error)
+        }
+    }
+
+    pub fn with_warnings(mut self, warnings: Vec<String>) -> Self {
+        self.warnings = warnings;
+        self
+    }
+}
+
+impl<T> From<Result<T, CommandError>> for CommandResponse<T> {
+    fn from(result: Result<T, CommandError>) -> Self {
+        match result {
+            Ok(data) => CommandResponse::ok(data),
+            Err(error) => CommandResponse::err(error),
+        }
+    }
+}
+
+/// Wrap a command body: `respond(async { ... }.await)`
+///
+/// Errors are logged once here so command bodies can use `?` freely.
+pub fn respond<T>(result: Result<T, CommandError>) -> CommandResponse<T> {
+    if let Err(error) = &result {
+        println!("❌ Command failed ({:?}): {}", error.code, error.message);
+    }
+    result.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Serialize)]
+    struct Converted {
+        output_path: Option<String>,
+    }
+
+    #[test]
+    fn test_ok_keeps_legacy_success_shape() {
+        let response = CommandResponse::ok(Converted {
+            output_path: Some("parts.json".to_string()),
+        });
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            json!({
+                "ok": true,
+                "warnings": [],
+                "output_path": "parts.json",
+                "success": true
+            })
+        );
+    }
+
+    #[test]
+    fn test_err_keeps_legacy_error_field() {
+        let response: CommandResponse<Converted> = respond(Err(CommandError::new(
+            ErrorCode::NotFound,
+            "dxf-converter.exe not found",
+        )));
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            json!({
+                "ok": false,
+                "code": "not_found",
+                "message": "dxf-converter.exe not found",
+                "warnings": [],
+                "success": false,
+                "error": "dxf-converter.exe not found"
+            })
+        );
+    }
+
+    #[test]
+    fn test_partial_and_unit_payloads() {
+        let response = CommandResponse::partial(
+            Converted { output_path: None },
+            CommandError::new(ErrorCode::Cancelled, "cancelled"),
+        )
+        .with_warnings(vec!["2 files skipped".to_string()]);
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(value["code"], "cancelled");
+        assert_eq!(value["output_path"], serde_json::Value::Null);
+        assert_eq!(value["warnings"], json!(["2 files skipped"]));
+
+        let written = serde_json::to_value(CommandResponse::ok(())).unwrap();
+        assert_eq!(
+            written,
+            json!({"ok": true, "warnings": [], "success": true})
+        );
+    }
+
+    #[test]
+    fn test_file_access_errors_are_typed() {
+        let denied = CommandError::from(FileAccessError::PermissionDenied {
+            path: "C:/Windows/system32".into(),
+            reason: "outside the allowed folders".to_string(),
+        });
+        assert_eq!(denied.code, ErrorCode::PermissionDenied);
+        assert!(denied.message.contains("system32"));
+    }
+}
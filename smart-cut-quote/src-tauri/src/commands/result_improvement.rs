@@ -0,0 +1,299 @@
+//! Continuing the optimization of a stored nesting result
+//!
+//! `improve_nesting_result` gives a stored result a few more seconds: the
+//! optimizer is warm-started from its placements (see
+//! `nesting_engine::WarmStart`) with the stored config and a fresh seed.
+//! The new layout is stored as a revision of the result only when it is
+//! strictly better, so running the action repeatedly never clutters the
+//! results with equal or worse copies.
+
+use crate::commands::instance_blobs::{acquire_instance_blob, load_instance_blob};
+use crate::commands::nesting_metrics::{record_nesting_run, NestingRunMetrics};
+use crate::commands::shutdown;
+use crate::db;
+use crate::nesting_engine::{
+    self, compare_outputs, DisplayUnits, NestingInput, NestingOutput, OutputComparison, WarmStart,
+};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+/// Utilization differences below this are not an improvement
+const UTILIZATION_EPSILON: f64 = 1e-6;
+
+/// Outcome of `improve_nesting_result`
+#[derive(Serialize, Debug)]
+pub struct ImprovementReport {
+    pub result_id: i64,
+    /// Stored revision, None when the run found nothing better
+    pub new_result_id: Option<i64>,
+    pub improved: bool,
+    pub message: String,
+    /// False when the stored layout could not be continued and the run
+    /// started from scratch
+    pub warm_started: bool,
+    pub sheets_before: usize,
+    pub sheets_after: usize,
+    /// Stored result as `a`, the new run as `b`
+    pub comparison: OutputComparison,
+}
+
+/// Sheets used by an output, a strip counting as one
+fn sheet_count(output: &NestingOutput) -> usize {
+    output.sheets.len().max(1)
+}
+
+/// True if `after` uses fewer sheets or more of the same sheets
+///
+/// A run placing fewer parts is never better, whatever its utilization.
+pub fn is_improvement(before: &NestingOutput, after: &NestingOutput) -> bool {
+    if after.total_items_placed < before.total_items_placed {
+        return false;
+    }
+    match sheet_count(after).cmp(&sheet_count(before)) {
+        std::cmp::Ordering::Less => true,
+        std::cmp::Ordering::Equal => after.utilization > before.utilization + UTILIZATION_EPSILON,
+        std::cmp::Ordering::Greater => false,
+    }
+}
+
+/// Stored result row needed to continue it
+pub(crate) struct StoredResult {
+    pub quote_id: Option<String>,
+    pub instance_json: String,
+    pub output: NestingOutput,
+}
+
+pub(crate) async fn load_result(pool: &SqlitePool, result_id: i64) -> Result<StoredResult, String> {
+    let row: Option<(Option<String>, String, Option<String>)> = sqlx::query_as(
+        "SELECT quote_id, result_json, instance_hash FROM nesting_results WHERE id = ?",
+    )
+    .bind(result_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to load nesting result {}: {}", result_id, e))?;
+    let (quote_id, result_json, instance_hash) =
+        row.ok_or_else(|| format!("No nesting result with ID {}", result_id))?;
+    let instance_hash = instance_hash
+        .ok_or_else(|| format!("Nesting result {} has no stored instance", result_id))?;
+
+    let instance_json = load_instance_blob(pool, &instance_hash).await?;
+    let output: NestingOutput = serde_json::from_str(&result_json)
+        .map_err(|e| format!("Stored nesting result {} is invalid: {}", result_id, e))?;
+    Ok(StoredResult {
+        quote_id,
+        instance_json,
+        output: output.to_units(DisplayUnits::Mm),
+    })
+}
+
+/// Insert an output derived from `parent_id` as its revision
+///
+/// `source` tells how it was derived (`improve`, `renest`).
+pub(crate) async fn store_revision(
+    pool: &SqlitePool,
+    parent_id: i64,
+    quote_id: Option<&str>,
+    instance_json: &str,
+    output: &NestingOutput,
+    source: &str,
+) -> Result<i64, String> {
+    let result_json = serde_json::to_string(output)
+        .map_err(|e| format!("Failed to serialize nesting result: {}", e))?;
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let instance_hash = acquire_instance_blob(&mut tx, instance_json).await?;
+    let id = sqlx::query(
+        "INSERT INTO nesting_results
+            (quote_id, instance_name, strip_width, strip_height, density, placed_items,
+             run_time_sec, result_json, source, instance_hash, parent_result_id)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(quote_id)
+    .bind(&output.instance_name)
+    .bind(output.strip_width)
+    .bind(output.strip_height)
+    .bind(output.utilization)
+    .bind(output.total_items_placed as i64)
+    .bind(output.computation_time_secs)
+    .bind(&result_json)
+    .bind(source)
+    .bind(&instance_hash)
+    .bind(parent_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to store nesting result: {}", e))?
+    .last_insert_rowid();
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to store nesting result: {}", e))?;
+    Ok(id)
+}
+
+/// Spend `extra_secs` more on a stored nesting result
+///
+/// Runs with the stored config and a fresh seed, continuing from the
+/// stored placements where they can be restored. The new layout is
+/// stored, linked to the result, only if it uses fewer sheets or reaches
+/// a higher utilization; otherwise nothing is saved.
+#[tauri::command]
+pub async fn improve_nesting_result(
+    app_handle: tauri::AppHandle,
+    result_id: i64,
+    extra_secs: u64,
+) -> Result<ImprovementReport, String> {
+    if extra_secs == 0 {
+        return Err("extra_secs must be at least 1 second".to_string());
+    }
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let stored = load_result(&pool, result_id).await?;
+    let config = stored
+        .output
+        .resolved_config
+        .clone()
+        .ok_or_else(|| format!("Nesting result {} has no resolved config", result_id))?;
+
+    let mut builder = NestingInput::builder()
+        .json_input(stored.instance_json.clone())
+        .config(&config)
+        .seed(rand::random())
+        .time_limit_secs(extra_secs);
+    match WarmStart::from_output(&stored.output) {
+        Some(warm_start) => builder = builder.warm_start(warm_start),
+        None => println!(
+            "⚠️ Layout of nesting result {} cannot be continued, running from scratch",
+            result_id
+        ),
+    }
+    let input = builder.build().map_err(|issues| {
+        let messages: Vec<String> = issues.iter().map(|issue| issue.to_string()).collect();
+        format!("Stored config is invalid: {}", messages.join("; "))
+    })?;
+
+    let started = std::time::Instant::now();
+    let metrics = NestingRunMetrics::for_input(&input);
+    let job_app_handle = app_handle.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        shutdown::run_registered(&job_app_handle, None, |terminator| {
+            nesting_engine::run_nesting_engine_with_terminator(input, terminator)
+        })
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+    record_nesting_run(&app_handle, metrics.finish(&result, started.elapsed()));
+    let output = result?;
+
+    let improved = is_improvement(&stored.output, &output);
+    let new_result_id = if improved {
+        Some(
+            store_revision(
+                &pool,
+                result_id,
+                stored.quote_id.as_deref(),
+                &stored.instance_json,
+                &output,
+                "improve",
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+    let comparison = compare_outputs(&stored.output, &output);
+    let message = match new_result_id {
+        Some(id) => format!(
+            "Improved: utilization {:.1}% -> {:.1}%, saved as result {}",
+            comparison.utilization_a * 100.0,
+            comparison.utilization_b * 100.0,
+            id
+        ),
+        None => "No improvement".to_string(),
+    };
+    println!("✨ Nesting result {}: {}", result_id, message);
+
+    Ok(ImprovementReport {
+        result_id,
+        new_result_id,
+        improved,
+        message,
+        warm_started: output.warm_started,
+        sheets_before: sheet_count(&stored.output),
+        sheets_after: sheet_count(&output),
+        comparison,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{migrated_pool, test_dir};
+    use tauri::async_runtime::block_on;
+
+    fn output(utilization: f64, items_placed: usize) -> NestingOutput {
+        serde_json::from_value(serde_json::json!({
+            "instance_name": "quote",
+            "strip_width": 800.0,
+            "strip_height": 1500.0,
+            "total_items_placed": items_placed,
+            "layouts": [],
+            "utilization": utilization,
+            "computation_time_secs": 2.0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_only_strictly_better_results_are_improvements() {
+        let before = output(0.70, 9);
+        assert!(is_improvement(&before, &output(0.72, 9)));
+        assert!(!is_improvement(&before, &output(0.70, 9)));
+        assert!(!is_improvement(&before, &output(0.65, 9)));
+        // Denser only because parts were left out
+        assert!(!is_improvement(&before, &output(0.80, 8)));
+    }
+
+    const PARTS: &str = r#"{"name": "quote", "strip_height": 1500.0, "items": []}"#;
+
+    #[test]
+    fn test_revision_links_to_its_result() {
+        let dir = test_dir("result-improvement", "revision");
+        block_on(async {
+            let pool = migrated_pool(&dir).await;
+            let mut tx = pool.begin().await.unwrap();
+            let hash = acquire_instance_blob(&mut tx, PARTS).await.unwrap();
+            let parent = sqlx::query(
+                "INSERT INTO nesting_results (strip_width, result_json, instance_hash)
+                 VALUES (100.0, '{}', ?)",
+            )
+            .bind(&hash)
+            .execute(&mut *tx)
+            .await
+            .unwrap()
+            .last_insert_rowid();
+            tx.commit().await.unwrap();
+
+            let id = store_revision(&pool, parent, None, PARTS, &output(0.75, 9), "improve")
+                .await
+                .unwrap();
+            let (parent_id, source, refcount): (Option<i64>, String, i64) = sqlx::query_as(
+                "SELECT r.parent_result_id, r.source, b.refcount
+                 FROM nesting_results r JOIN instance_blobs b ON b.hash = r.instance_hash
+                 WHERE r.id = ?",
+            )
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            assert_eq!(parent_id, Some(parent));
+            assert_eq!(source, "improve");
+            assert_eq!(refcount, 2);
+
+            let stored = load_result(&pool, id).await.unwrap();
+            assert_eq!(stored.instance_json, PARTS);
+            assert_eq!(stored.output.total_items_placed, 9);
+            pool.close().await;
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,35 @@
+use crate::nesting_engine::{self, NestingOutput, SheetOrder};
+use serde::Serialize;
+
+/// Sheets of a job in cutting order, with the report of the reordering
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderedSheets {
+    pub sheets: Vec<NestingOutput>,
+    pub sheet_order: SheetOrder,
+}
+
+/// Order the sheets of a multi-sheet job for assembly
+///
+/// Sheets holding items with a low `due_rank` (metadata, 1 = needed
+/// first) move to the front. Layouts are returned unchanged, only their
+/// order differs; the index of a sheet is its position in `sheets`.
+/// Sheets in other units than the first one are converted to its units.
+#[tauri::command]
+pub async fn order_job_sheets(sheets: Vec<NestingOutput>) -> Result<OrderedSheets, String> {
+    if sheets.is_empty() {
+        return Err("No sheets to order".to_string());
+    }
+    let units = sheets[0].units;
+    let sheets = sheets
+        .into_iter()
+        .map(|sheet| sheet.to_units(units))
+        .collect();
+
+    let (sheets, sheet_order) = nesting_engine::order_sheets(sheets);
+    println!("🗂️ Sheet order: {}", sheet_order.sheet_order_rationale);
+
+    Ok(OrderedSheets {
+        sheets,
+        sheet_order,
+    })
+}
@@ -0,0 +1,547 @@
+//! Graceful shutdown of running nesting jobs
+//!
+//! Every nesting run registers its terminator here. When the app is asked
+//! to exit, all terminators are signalled; sparrow then returns its best
+//! solution so far, which the run writes as a checkpoint before it ends.
+//! Exit is held back until all runs finished or the grace period passed.
+//! `force_quit` skips the wait.
+//!
+//! Runs started with a job ID can also be stopped one by one with
+//! `cancel_nesting`; they return their best solution so far the same way.
+//! Jobs from `start_nesting` still waiting for a slot are simply dropped
+//! from the queue; one just leaving it is stopped as soon as its run
+//! registers.
+
+use crate::commands::job_status::{JobEntry, JobKind, JobRegistry, JobStatus};
+use crate::commands::nesting_manager::NestingJobManager;
+use crate::nesting_engine::{NativeTerminator, NestingOutput};
+use crate::workspace;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, ExitRequestApi, Manager, State};
+
+/// Event emitted while exit waits for running jobs
+pub const JOBS_FINISHING_EVENT: &str = "app://jobs-finishing";
+
+/// Time running jobs get to finish before the app exits anyway
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Workspace directory receiving outputs of runs stopped by shutdown
+const CHECKPOINT_DIR: &str = "jobs";
+
+/// Terminator of a run with the job ID it was started under
+struct RunningEntry {
+    job_id: Option<String>,
+    terminator: NativeTerminator,
+}
+
+#[derive(Default)]
+struct Registry {
+    next_id: u64,
+    running: HashMap<u64, RunningEntry>,
+    /// Job IDs stopped by `cancel_nesting`, until their caller asks
+    cancelled: HashSet<String>,
+    shutting_down: bool,
+    exit_allowed: bool,
+    grace_period: Option<Duration>,
+}
+
+/// Nesting runs in progress, with the shutdown state of the app
+#[derive(Default)]
+pub struct RunningNestings {
+    registry: Mutex<Registry>,
+    idle: Condvar,
+}
+
+/// Registration of a run, removed when dropped
+pub struct RunningNesting<'a> {
+    owner: &'a RunningNestings,
+    id: u64,
+    /// Terminator to hand to the engine
+    pub terminator: NativeTerminator,
+}
+
+impl Drop for RunningNesting<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut registry) = self.owner.registry.lock() {
+            registry.running.remove(&self.id);
+        }
+        self.owner.idle.notify_all();
+    }
+}
+
+impl RunningNestings {
+    /// Register a new run; refused once shutdown started
+    ///
+    /// A job ID makes the run cancellable with `cancel`; it must not be
+    /// in use by another running job.
+    pub fn register(&self, job_id: Option<&str>) -> Result<RunningNesting<'_>, String> {
+        let mut registry = self
+            .registry
+            .lock()
+            .map_err(|_| "Nesting registry is unavailable".to_string())?;
+        if registry.shutting_down {
+            return Err("The app is shutting down, nesting was not started".to_string());
+        }
+        if let Some(job_id) = job_id {
+            if registry
+                .running
+                .values()
+                .any(|entry| entry.job_id.as_deref() == Some(job_id))
+            {
+                return Err(format!("Nesting job '{}' is already running", job_id));
+            }
+            registry.cancelled.remove(job_id);
+        }
+
+        let id = registry.next_id;
+        registry.next_id += 1;
+        let terminator = NativeTerminator::new();
+        registry.running.insert(
+            id,
+            RunningEntry {
+                job_id: job_id.map(str::to_string),
+                terminator: terminator.get_handle(),
+            },
+        );
+
+        Ok(RunningNesting {
+            owner: self,
+            id,
+            terminator,
+        })
+    }
+
+    /// Stop the run started under `job_id`
+    pub fn cancel(&self, job_id: &str) -> Result<(), String> {
+        let mut registry = self
+            .registry
+            .lock()
+            .map_err(|_| "Nesting registry is unavailable".to_string())?;
+        let entry = registry
+            .running
+            .values()
+            .find(|entry| entry.job_id.as_deref() == Some(job_id))
+            .ok_or_else(|| format!("No running nesting job '{}'", job_id))?;
+        entry.terminator.terminate();
+        registry.cancelled.insert(job_id.to_string());
+        Ok(())
+    }
+
+    /// Whether the run of `job_id` was cancelled; forgets it afterwards
+    pub fn take_cancelled(&self, job_id: &str) -> bool {
+        self.registry
+            .lock()
+            .map(|mut registry| registry.cancelled.remove(job_id))
+            .unwrap_or(false)
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.registry
+            .lock()
+            .map(|registry| registry.shutting_down)
+            .unwrap_or(false)
+    }
+
+    /// Signal all running jobs to stop
+    ///
+    /// Returns the number of jobs still running, or None if shutdown
+    /// had already started.
+    pub fn begin_shutdown(&self) -> Option<usize> {
+        let mut registry = self.registry.lock().ok()?;
+        if registry.shutting_down {
+            return None;
+        }
+        registry.shutting_down = true;
+        for entry in registry.running.values() {
+            entry.terminator.terminate();
+        }
+        Some(registry.running.len())
+    }
+
+    /// Block until no job is running; false if `timeout` passed first
+    pub fn wait_idle(&self, timeout: Duration) -> bool {
+        let Ok(registry) = self.registry.lock() else {
+            return false;
+        };
+        self.idle
+            .wait_timeout_while(registry, timeout, |registry| !registry.running.is_empty())
+            .map(|(registry, _)| registry.running.is_empty())
+            .unwrap_or(false)
+    }
+
+    pub fn grace_period(&self) -> Duration {
+        self.registry
+            .lock()
+            .ok()
+            .and_then(|registry| registry.grace_period)
+            .unwrap_or(DEFAULT_GRACE_PERIOD)
+    }
+
+    fn set_grace_period(&self, grace_period: Duration) {
+        if let Ok(mut registry) = self.registry.lock() {
+            registry.grace_period = Some(grace_period);
+        }
+    }
+
+    fn exit_allowed(&self) -> bool {
+        self.registry
+            .lock()
+            .map(|registry| registry.exit_allowed)
+            .unwrap_or(true)
+    }
+
+    fn allow_exit(&self) {
+        if let Ok(mut registry) = self.registry.lock() {
+            registry.exit_allowed = true;
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct JobsFinishingEvent {
+    /// Jobs still running when exit was requested
+    pub running: usize,
+    pub grace_period_secs: f64,
+}
+
+/// Run a nesting job registered for graceful shutdown
+///
+/// Blocking, call via `spawn_blocking`. A run stopped by shutdown writes
+/// its best-so-far output as a checkpoint before it unregisters.
+pub fn run_registered(
+    app_handle: &AppHandle,
+    job_id: Option<&str>,
+    run: impl FnOnce(NativeTerminator) -> Result<NestingOutput, String>,
+) -> Result<NestingOutput, String> {
+    let nestings = app_handle.state::<RunningNestings>();
+    let manager = app_handle.state::<NestingJobManager>();
+    let registration = register_job(&nestings, &manager, job_id)?;
+
+    let result = run(registration.terminator.get_handle());
+    if nestings.is_shutting_down() {
+        if let Ok(output) = &result {
+            match write_checkpoint(app_handle, job_id, output) {
+                Ok(path) => println!("💾 Nesting checkpoint written to {}", path.display()),
+                Err(e) => eprintln!("⚠️ Nesting checkpoint not written: {}", e),
+            }
+        }
+    }
+
+    drop(registration);
+    result
+}
+
+/// Register a run, stopped at once when its job was cancelled between
+/// leaving the queue and starting
+fn register_job<'a>(
+    nestings: &'a RunningNestings,
+    manager: &NestingJobManager,
+    job_id: Option<&str>,
+) -> Result<RunningNesting<'a>, String> {
+    let registration = nestings.register(job_id)?;
+    if let Some(job_id) = job_id.filter(|job_id| manager.take_cancel_request(job_id)) {
+        nestings.cancel(job_id)?;
+    }
+    Ok(registration)
+}
+
+/// Stop a job, whether queued, starting or running; true if it was queued
+fn cancel_job(
+    nestings: &RunningNestings,
+    manager: &NestingJobManager,
+    job_id: &str,
+) -> Result<bool, String> {
+    if manager.cancel_queued(job_id) {
+        return Ok(true);
+    }
+    // A job just taken off the queue may not have registered its run yet;
+    // the request is kept first so its run sees it either way
+    let starting = manager.request_cancel(job_id);
+    match nestings.cancel(job_id) {
+        Err(_) if starting => Ok(false),
+        result => result.map(|()| false),
+    }
+}
+
+/// Hold back exit until running jobs finished or the grace period passed
+///
+/// Called for `RunEvent::ExitRequested`. Exit goes ahead immediately when
+/// nothing is running.
+pub fn on_exit_requested(app_handle: &AppHandle, code: Option<i32>, api: &ExitRequestApi) {
+    let nestings = app_handle.state::<RunningNestings>();
+    if nestings.exit_allowed() {
+        return;
+    }
+
+    let running = match nestings.begin_shutdown() {
+        // Already waiting for the jobs
+        None => {
+            api.prevent_exit();
+            return;
+        }
+        Some(0) => {
+            nestings.allow_exit();
+            return;
+        }
+        Some(running) => running,
+    };
+
+    api.prevent_exit();
+    let grace_period = nestings.grace_period();
+    println!(
+        "⏳ Waiting up to {:?} for {} nesting jobs to finish",
+        grace_period, running
+    );
+    let _ = app_handle.emit(
+        JOBS_FINISHING_EVENT,
+        JobsFinishingEvent {
+            running,
+            grace_period_secs: grace_period.as_secs_f64(),
+        },
+    );
+
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        let nestings = app_handle.state::<RunningNestings>();
+        if !nestings.wait_idle(grace_period) {
+            println!(
+                "⚠️ Nesting jobs still running after {:?}, exiting",
+                grace_period
+            );
+        }
+        nestings.allow_exit();
+        app_handle.exit(code.unwrap_or(0));
+    });
+}
+
+/// Stop a running nesting job started with this job ID
+///
+/// The run returns its best layout so far, usually within a second. A
+/// job from `start_nesting` that has not started yet never runs.
+#[tauri::command]
+pub fn cancel_nesting(
+    nestings: State<'_, RunningNestings>,
+    manager: State<'_, NestingJobManager>,
+    registry: State<'_, JobRegistry>,
+    job_id: String,
+) -> Result<(), String> {
+    if cancel_job(&nestings, &manager, &job_id)? {
+        registry.report(JobEntry {
+            job_id: job_id.clone(),
+            kind: JobKind::Nesting,
+            status: JobStatus::Cancelled,
+            payload: (),
+        });
+        println!("⏹️ Queued nesting job {} cancelled", job_id);
+    } else {
+        println!("⏹️ Nesting job {} cancelled", job_id);
+    }
+    Ok(())
+}
+
+/// Exit immediately, without waiting for running jobs
+#[tauri::command]
+pub fn force_quit(
+    app_handle: AppHandle,
+    nestings: State<'_, RunningNestings>,
+) -> Result<(), String> {
+    nestings.begin_shutdown();
+    nestings.allow_exit();
+    println!("🛑 Force quit requested");
+    app_handle.exit(0);
+    Ok(())
+}
+
+/// Change how long exit waits for running jobs
+#[tauri::command]
+pub fn set_shutdown_grace_period(
+    nestings: State<'_, RunningNestings>,
+    secs: f64,
+) -> Result<(), String> {
+    if !secs.is_finite() || secs < 0.0 {
+        return Err(format!(
+            "Grace period must be a non-negative number of seconds, got {}",
+            secs
+        ));
+    }
+    nestings.set_grace_period(Duration::from_secs_f64(secs));
+    Ok(())
+}
+
+/// Write the output of a run stopped by shutdown to the workspace
+fn write_checkpoint(
+    app_handle: &AppHandle,
+    job_id: Option<&str>,
+    output: &NestingOutput,
+) -> Result<PathBuf, String> {
+    let dir = workspace::workspace_dir(app_handle, CHECKPOINT_DIR)?.join("checkpoints");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+
+    let name = match job_id {
+        Some(job_id) => format!("nesting-{}.json", job_id),
+        None => format!(
+            "nesting-{}.json",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f")
+        ),
+    };
+    let json = serde_json::to_vec_pretty(output)
+        .map_err(|e| format!("Failed to serialize nesting output: {}", e))?;
+
+    let path = dir.join(name);
+    write_atomic(&path, &json)?;
+    Ok(path)
+}
+
+/// Write through a temporary file so an interrupted write leaves no partial file
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, contents)
+        .map_err(|e| format!("Failed to write '{}': {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, path)
+        .map_err(|e| format!("Failed to move '{}' into place: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::NestingInput;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+
+    /// Stand-in for an optimization: runs until terminated, then checkpoints
+    fn fake_long_job(nestings: &RunningNestings, checkpointed: &AtomicBool) {
+        let registration = nestings.register(None).unwrap();
+        while !registration.terminator.is_terminated() {
+            thread::sleep(Duration::from_millis(5));
+        }
+        thread::sleep(Duration::from_millis(50));
+        checkpointed.store(true, Ordering::SeqCst);
+    }
+
+    fn wait_until_running(nestings: &RunningNestings, count: usize) {
+        while nestings.registry.lock().unwrap().running.len() < count {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_shutdown_waits_for_running_jobs() {
+        let nestings = RunningNestings::default();
+        let checkpointed = [AtomicBool::new(false), AtomicBool::new(false)];
+
+        thread::scope(|scope| {
+            for flag in &checkpointed {
+                scope.spawn(|| fake_long_job(&nestings, flag));
+            }
+            wait_until_running(&nestings, 2);
+
+            assert_eq!(nestings.begin_shutdown(), Some(2));
+            assert!(nestings.wait_idle(DEFAULT_GRACE_PERIOD));
+        });
+
+        assert!(checkpointed.iter().all(|flag| flag.load(Ordering::SeqCst)));
+        // A second exit request finds shutdown in progress
+        assert_eq!(nestings.begin_shutdown(), None);
+        assert!(nestings.register(None).is_err());
+    }
+
+    #[test]
+    fn test_grace_period_expires() {
+        let nestings = RunningNestings::default();
+
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                // Ignores the terminator for longer than the grace period
+                let _registration = nestings.register(None).unwrap();
+                thread::sleep(Duration::from_millis(300));
+            });
+            wait_until_running(&nestings, 1);
+
+            assert_eq!(nestings.begin_shutdown(), Some(1));
+            assert!(!nestings.wait_idle(Duration::from_millis(20)));
+            assert!(nestings.wait_idle(Duration::from_secs(5)));
+        });
+    }
+
+    #[test]
+    fn test_cancel_stops_only_that_job() {
+        let nestings = RunningNestings::default();
+        let other = nestings.register(Some("other")).unwrap();
+
+        thread::scope(|scope| {
+            let job = scope.spawn(|| {
+                let registration = nestings.register(Some("job-1")).unwrap();
+                while !registration.terminator.is_terminated() {
+                    thread::sleep(Duration::from_millis(5));
+                }
+            });
+            wait_until_running(&nestings, 2);
+            assert!(nestings.register(Some("job-1")).is_err());
+
+            nestings.cancel("job-1").unwrap();
+            job.join().unwrap();
+        });
+
+        assert!(!other.terminator.is_terminated());
+        assert!(nestings.take_cancelled("job-1"));
+        assert!(!nestings.take_cancelled("job-1"));
+        assert!(!nestings.take_cancelled("other"));
+        // Finished and unknown jobs
+        assert!(nestings.cancel("job-1").is_err());
+        assert!(nestings.cancel("missing").is_err());
+    }
+
+    #[test]
+    fn test_cancel_while_a_job_is_starting() {
+        let nestings = RunningNestings::default();
+        let manager = NestingJobManager::default();
+        for job_id in ["job", "waiting"] {
+            manager
+                .enqueue(job_id, NestingInput::default(), None)
+                .unwrap();
+        }
+        assert_eq!(manager.take_startable().len(), 1);
+
+        // Taken off the queue, its run not registered yet
+        assert!(!cancel_job(&nestings, &manager, "job").unwrap());
+        let registration = register_job(&nestings, &manager, Some("job")).unwrap();
+        assert!(registration.terminator.is_terminated());
+        assert!(nestings.take_cancelled("job"));
+        drop(registration);
+
+        // A run registered without a request keeps going until cancelled
+        let registration = register_job(&nestings, &manager, Some("job")).unwrap();
+        assert!(!registration.terminator.is_terminated());
+        assert!(!cancel_job(&nestings, &manager, "job").unwrap());
+        assert!(registration.terminator.is_terminated());
+        drop(registration);
+
+        assert!(cancel_job(&nestings, &manager, "waiting").unwrap());
+        assert!(cancel_job(&nestings, &manager, "missing").is_err());
+    }
+
+    #[test]
+    fn test_idle_app_exits_immediately() {
+        let nestings = RunningNestings::default();
+        assert_eq!(nestings.begin_shutdown(), Some(0));
+        assert!(nestings.wait_idle(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_write_atomic_leaves_no_temporary_file() {
+        let dir = std::env::temp_dir().join(format!("shutdown-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("nesting-job.json");
+
+        write_atomic(&path, b"{}").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"{}");
+        assert!(!path.with_extension("tmp").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,366 @@
+use crate::commands::material_requirements::group_key;
+use crate::db;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+
+/// Event emitted when watched source files changed content
+pub const SOURCE_CHANGED_EVENT: &str = "quote://source-changed";
+/// Event emitted when watched source files were deleted or renamed away
+pub const SOURCE_MISSING_EVENT: &str = "quote://source-missing";
+
+/// Setting enabling a re-nest of the affected material groups on change
+const AUTO_RENEST_SETTING: &str = "auto_renest_on_source_change";
+
+/// Quiet period before a burst of file events is processed
+/// (CAD tools often write a file several times when saving)
+const DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Active source watchers, one per quote
+///
+/// Dropping a watcher stops it and ends its debounce thread, so
+/// removing the entry is all the cleanup needed.
+#[derive(Default)]
+pub struct SourceWatchers {
+    watchers: Mutex<HashMap<String, RecommendedWatcher>>,
+}
+
+impl SourceWatchers {
+    fn insert(&self, quote_id: String, watcher: RecommendedWatcher) {
+        if let Ok(mut watchers) = self.watchers.lock() {
+            watchers.insert(quote_id, watcher);
+        }
+    }
+
+    fn remove(&self, quote_id: &str) -> bool {
+        self.watchers
+            .lock()
+            .map(|mut watchers| watchers.remove(quote_id).is_some())
+            .unwrap_or(false)
+    }
+
+    /// Stop all watchers (called when the app exits)
+    pub fn stop_all(&self) {
+        if let Ok(mut watchers) = self.watchers.lock() {
+            if !watchers.is_empty() {
+                println!("👋 Stopping {} source watchers", watchers.len());
+            }
+            watchers.clear();
+        }
+    }
+}
+
+/// Quote part linked to a source DXF file
+#[derive(Serialize, Debug, Clone)]
+pub struct SourcePart {
+    pub file_id: String,
+    pub name: String,
+    pub path: String,
+    pub material_group: String,
+    pub material_thickness: f64,
+    /// Nesting batch of the part (`Material-Thicknessmm`)
+    pub batch_key: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SourceChangedEvent {
+    pub quote_id: String,
+    pub parts: Vec<SourcePart>,
+    /// Affected nesting batches should be re-nested in the background
+    pub auto_renest: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SourceMissingEvent {
+    pub quote_id: String,
+    pub parts: Vec<SourcePart>,
+}
+
+// Subset of the quote JSON blob (see quoteService.ts)
+#[derive(Deserialize, Debug, Default)]
+struct QuoteSources {
+    #[serde(default)]
+    files: Vec<SourceFile>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SourceFile {
+    id: String,
+    name: String,
+    path: String,
+    material: Option<SourceMaterial>,
+    material_group: Option<String>,
+    material_thickness: Option<f64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SourceMaterial {
+    name: String,
+    thickness: f64,
+}
+
+/// Watched file with the content hash seen last (None while missing)
+struct WatchedFile {
+    part: SourcePart,
+    dir: PathBuf,
+    file_name: OsString,
+    hash: Option<String>,
+}
+
+impl WatchedFile {
+    fn matches(&self, path: &Path) -> bool {
+        path.file_name() == Some(self.file_name.as_os_str())
+            && path.parent().is_some_and(|dir| dir == self.dir)
+    }
+}
+
+/// Start or stop watching the source DXF files of a quote
+///
+/// The parent directories are watched rather than the files themselves,
+/// so files replaced on save (write to temp + rename) or deleted and
+/// recreated keep being tracked. Changes are debounced and compared by
+/// content hash; the frontend reacts to `quote://source-changed` by
+/// re-extracting metadata, diffing the converted instance and, when
+/// `auto_renest` is set, queueing a re-nest of the affected batches.
+/// Files that disappear produce `quote://source-missing` instead.
+///
+/// Returns the number of watched files.
+#[tauri::command]
+pub async fn watch_quote_sources(
+    app_handle: AppHandle,
+    watchers: State<'_, SourceWatchers>,
+    quote_id: String,
+    enabled: bool,
+) -> Result<usize, String> {
+    // Re-enabling replaces the previous watcher (the file list may have changed)
+    if watchers.remove(&quote_id) {
+        println!("👁️ Stopped watching sources of quote {}", quote_id);
+    }
+    if !enabled {
+        return Ok(0);
+    }
+
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let row: Option<(Option<String>,)> = sqlx::query_as("SELECT data FROM quotes WHERE id = ?")
+        .bind(&quote_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to load quote '{}': {}", quote_id, e))?;
+    let (data,) = row.ok_or_else(|| format!("Quote '{}' not found", quote_id))?;
+    let sources: QuoteSources = match data {
+        Some(data) => serde_json::from_str(&data)
+            .map_err(|e| format!("Quote '{}' has invalid data: {}", quote_id, e))?,
+        None => QuoteSources::default(),
+    };
+
+    let mut files = Vec::new();
+    let mut missing = Vec::new();
+    let mut dirs = BTreeSet::new();
+    for file in sources.files {
+        let path = PathBuf::from(&file.path);
+        let (Some(parent), Some(file_name)) = (path.parent(), path.file_name()) else {
+            println!("⚠️ Ignoring source without file name: '{}'", file.path);
+            continue;
+        };
+        // Event paths are reported below the watched (canonical) directory
+        let Ok(dir) = parent.canonicalize() else {
+            missing.push(source_part(file));
+            continue;
+        };
+
+        let hash = hash_file(&path);
+        let part = source_part(file);
+        if hash.is_none() {
+            missing.push(part.clone());
+        }
+        dirs.insert(dir.clone());
+        files.push(WatchedFile {
+            part,
+            dir,
+            file_name: file_name.to_os_string(),
+            hash,
+        });
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let _ = tx.send(event);
+    })
+    .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+    for dir in &dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch '{}': {}", dir.display(), e))?;
+    }
+
+    if !missing.is_empty() {
+        emit_missing(&app_handle, &quote_id, missing);
+    }
+
+    let watched = files.len();
+    let thread_app = app_handle.clone();
+    let thread_quote_id = quote_id.clone();
+    std::thread::Builder::new()
+        .name(format!("source-watch-{}", quote_id))
+        .spawn(move || debounce_loop(thread_app, thread_quote_id, files, rx))
+        .map_err(|e| format!("Failed to start watcher thread: {}", e))?;
+
+    watchers.insert(quote_id.clone(), watcher);
+    println!(
+        "👁️ Watching {} source files of quote {} in {} directories",
+        watched,
+        quote_id,
+        dirs.len()
+    );
+    Ok(watched)
+}
+
+fn source_part(file: SourceFile) -> SourcePart {
+    let material_group = file
+        .material_group
+        .or_else(|| file.material.as_ref().map(|m| m.name.clone()))
+        .unwrap_or_else(|| "Unknown".to_string());
+    let material_thickness = file
+        .material_thickness
+        .or_else(|| file.material.as_ref().map(|m| m.thickness))
+        .unwrap_or(0.0);
+
+    SourcePart {
+        file_id: file.id,
+        name: file.name,
+        path: file.path,
+        batch_key: group_key(&material_group, material_thickness),
+        material_group,
+        material_thickness,
+    }
+}
+
+fn hash_file(path: &Path) -> Option<String> {
+    let content = std::fs::read(path).ok()?;
+    Some(format!("{:x}", Sha256::digest(&content)))
+}
+
+/// Collect file events until they go quiet, then process them
+///
+/// Ends when the watcher is dropped (the event channel disconnects).
+fn debounce_loop(
+    app_handle: AppHandle,
+    quote_id: String,
+    mut files: Vec<WatchedFile>,
+    events: Receiver<notify::Result<Event>>,
+) {
+    while let Ok(first) = events.recv() {
+        let mut touched = BTreeSet::new();
+        collect_paths(first, &mut touched);
+
+        loop {
+            match events.recv_timeout(DEBOUNCE) {
+                Ok(event) => collect_paths(event, &mut touched),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        process_changes(&app_handle, &quote_id, &mut files, &touched);
+    }
+}
+
+fn collect_paths(event: notify::Result<Event>, touched: &mut BTreeSet<PathBuf>) {
+    match event {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => touched.extend(event.paths),
+        Ok(_) => {}
+        // Watch errors (e.g. a transient permission problem) must not stop watching
+        Err(e) => println!("⚠️ Source watcher error: {}", e),
+    }
+}
+
+fn process_changes(
+    app_handle: &AppHandle,
+    quote_id: &str,
+    files: &mut [WatchedFile],
+    touched: &BTreeSet<PathBuf>,
+) {
+    let mut changed = Vec::new();
+    let mut missing = Vec::new();
+
+    for file in files
+        .iter_mut()
+        .filter(|file| touched.iter().any(|path| file.matches(path)))
+    {
+        let path = Path::new(&file.part.path);
+        match hash_file(path) {
+            // Saved with new content, or back after being missing
+            Some(hash) if file.hash.as_ref() != Some(&hash) => {
+                file.hash = Some(hash);
+                changed.push(file.part.clone());
+            }
+            Some(_) => {}
+            // Unreadable while the CAD tool still holds it, the next event retries
+            None if path.exists() => {}
+            // Report a disappearance once, not on every later event
+            None if file.hash.take().is_some() => missing.push(file.part.clone()),
+            None => {}
+        }
+    }
+
+    if !missing.is_empty() {
+        emit_missing(app_handle, quote_id, missing);
+    }
+    if changed.is_empty() {
+        return;
+    }
+
+    let auto_renest = tauri::async_runtime::block_on(auto_renest_enabled(app_handle))
+        .unwrap_or_else(|e| {
+            println!("⚠️ {}", e);
+            false
+        });
+    println!(
+        "📝 {} source files of quote {} changed (auto re-nest: {})",
+        changed.len(),
+        quote_id,
+        auto_renest
+    );
+    let _ = app_handle.emit(
+        SOURCE_CHANGED_EVENT,
+        SourceChangedEvent {
+            quote_id: quote_id.to_string(),
+            parts: changed,
+            auto_renest,
+        },
+    );
+}
+
+fn emit_missing(app_handle: &AppHandle, quote_id: &str, parts: Vec<SourcePart>) {
+    println!(
+        "❓ {} source files of quote {} are missing",
+        parts.len(),
+        quote_id
+    );
+    let _ = app_handle.emit(
+        SOURCE_MISSING_EVENT,
+        SourceMissingEvent {
+            quote_id: quote_id.to_string(),
+            parts,
+        },
+    );
+}
+
+async fn auto_renest_enabled(app_handle: &AppHandle) -> Result<bool, String> {
+    let pool = db::sqlite_pool(app_handle).await?;
+    let value: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = ?")
+        .bind(AUTO_RENEST_SETTING)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to load auto re-nest setting: {}", e))?;
+
+    Ok(value.is_some_and(|(value,)| value == "true" || value == "1"))
+}
@@ -1,6 +1,12 @@
+use crate::commands::response::{respond, CommandError, CommandResponse, ErrorCode};
+use crate::commands::tool_process::{run_tool, tool_path, Tool};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use std::process::Command;
-use tauri::Manager;
+use std::time::Duration;
+
+/// Time sparrow-cli gets past its own `--timeout` to write the results
+const TIMEOUT_GRACE: Duration = Duration::from_secs(60);
 
 #[derive(Deserialize, Debug)]
 pub struct NestingOptions {
@@ -8,12 +14,11 @@ pub struct NestingOptions {
     pub workers: u32,
 }
 
+/// Payload of `run_nesting`: where sparrow-cli wrote its results
 #[derive(Serialize, Debug)]
-pub struct NestingResult {
-    pub success: bool,
-    pub result_json: Option<String>,
-    pub result_svg: Option<String>,
-    pub error: Option<String>,
+pub struct SparrowCliOutput {
+    pub result_json: String,
+    pub result_svg: String,
 }
 
 /// Run nesting optimization using sparrow-cli.exe
@@ -24,37 +29,54 @@ pub async fn run_nesting(
     output_json: String,
     output_svg: String,
     options: NestingOptions,
-) -> Result<NestingResult, String> {
+) -> CommandResponse<SparrowCliOutput> {
+    match run_sparrow_cli(&app_handle, input_json, output_json, output_svg, options) {
+        Ok((output, warnings)) => CommandResponse::ok(output).with_warnings(warnings),
+        Err(error) => respond(Err(error)),
+    }
+}
+
+fn run_sparrow_cli(
+    app_handle: &tauri::AppHandle,
+    input_json: String,
+    output_json: String,
+    output_svg: String,
+    options: NestingOptions,
+) -> Result<(SparrowCliOutput, Vec<String>), CommandError> {
     // Resolve the path to sparrow-cli.exe
-    let resource_path = app_handle
-        .path()
-        .resolve("binaries/sparrow-cli.exe", tauri::path::BaseDirectory::Resource)
-        .map_err(|e| format!("Failed to resolve sparrow-cli.exe path: {}", e))?;
-
-    // Check if the executable exists (in dev mode, use relative path)
-    let exe_path = if resource_path.exists() {
-        resource_path
-    } else {
-        // Development mode: use relative path
-        std::env::current_dir()
-            .map_err(|e| format!("Failed to get current directory: {}", e))?
-            .join("../../binaries/sparrow-cli.exe")
-    };
+    let exe_path = tool_path(app_handle, Tool::SparrowCli)?;
 
     if !exe_path.exists() {
-        return Ok(NestingResult {
-            success: false,
-            result_json: None,
-            result_svg: None,
-            error: Some(format!(
-                "sparrow-cli.exe not found at: {}",
-                exe_path.display()
-            )),
-        });
+        return Err(CommandError::new(
+            ErrorCode::NotFound,
+            format!("sparrow-cli.exe not found at: {}", exe_path.display()),
+        ));
     }
 
+    let time_limit = Duration::from_secs(options.timeout.into()) + TIMEOUT_GRACE;
+    run_sparrow(
+        &exe_path,
+        input_json,
+        output_json,
+        output_svg,
+        &options,
+        time_limit,
+    )
+}
+
+/// Run sparrow-cli, killing it when it is still running at `time_limit`
+///
+/// Returns where the results were written and the warnings it printed.
+fn run_sparrow(
+    exe_path: &Path,
+    input_json: String,
+    output_json: String,
+    output_svg: String,
+    options: &NestingOptions,
+    time_limit: Duration,
+) -> Result<(SparrowCliOutput, Vec<String>), CommandError> {
     // Build command
-    let mut cmd = Command::new(&exe_path);
+    let mut cmd = Command::new(exe_path);
 
     cmd.arg("--input")
         .arg(&input_json)
@@ -68,28 +90,109 @@ pub async fn run_nesting(
         .arg(options.workers.to_string());
 
     // Execute
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to execute sparrow-cli: {}", e))?;
-
-    if output.status.success() {
-        Ok(NestingResult {
-            success: true,
-            result_json: Some(output_json),
-            result_svg: Some(output_svg),
-            error: None,
-        })
-    } else {
-        let error = String::from_utf8_lossy(&output.stderr).to_string();
-        Ok(NestingResult {
-            success: false,
-            result_json: None,
-            result_svg: None,
-            error: Some(if error.is_empty() {
-                "Unknown error occurred during nesting".to_string()
-            } else {
-                error
-            }),
-        })
+    let output = run_tool(Tool::SparrowCli, cmd, time_limit, None)?
+        .ok_or_else(|| "sparrow-cli run was cancelled".to_string())?;
+    let warnings = output.check(Tool::SparrowCli)?;
+
+    Ok((
+        SparrowCliOutput {
+            result_json: output_json,
+            result_svg: output_svg,
+        },
+        warnings,
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::commands::tool_process::mock::{mock_tool, Scenario};
+    use std::path::PathBuf;
+    use std::time::Instant;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn run_mock(
+        dir: &Path,
+        scenario: Scenario,
+        time_limit: Duration,
+    ) -> Result<(SparrowCliOutput, Vec<String>), CommandError> {
+        let output_json = dir.join("result.json").to_string_lossy().into_owned();
+        run_sparrow(
+            &mock_tool(dir, scenario),
+            dir.join("input.json").to_string_lossy().into_owned(),
+            output_json,
+            dir.join("result.svg").to_string_lossy().into_owned(),
+            &NestingOptions {
+                timeout: 1,
+                workers: 1,
+            },
+            time_limit,
+        )
+    }
+
+    #[test]
+    fn test_failures_are_classified() {
+        let dir = temp_dir("sparrow_cli_failures");
+        let limit = Duration::from_secs(30);
+
+        let error = run_mock(&dir, Scenario::NonzeroExit, limit).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ToolFailed);
+        assert_eq!(
+            error.message,
+            "sparrow-cli exited with code 3: Error: unsupported entity SPLINE"
+        );
+
+        let error = run_mock(&dir, Scenario::Crash, limit).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ToolFailed);
+        assert!(
+            error
+                .message
+                .starts_with("sparrow-cli was killed by signal 11: reading entities"),
+            "{}",
+            error.message
+        );
+
+        // Garbled and cut short, but the run still ends
+        let error = run_mock(&dir, Scenario::HugeStderr, limit).unwrap_err();
+        assert!(error
+            .message
+            .starts_with("sparrow-cli exited with code 1: \u{FFFD}"));
+        assert!(error.message.ends_with(" …"));
+        assert!(error.message.chars().count() < 2100);
+
+        let started = Instant::now();
+        let error = run_mock(&dir, Scenario::SlowThenHang, Duration::from_millis(500)).unwrap_err();
+        assert_eq!(error.code, ErrorCode::ToolFailed);
+        assert!(
+            error
+                .message
+                .starts_with("sparrow-cli did not finish within 1s"),
+            "{}",
+            error.message
+        );
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_warnings_of_a_successful_run() {
+        let dir = temp_dir("sparrow_cli_warnings");
+        let (output, warnings) =
+            run_mock(&dir, Scenario::WarningsOnly, Duration::from_secs(30)).unwrap();
+        assert!(Path::new(&output.result_json).exists());
+        assert_eq!(
+            warnings,
+            vec![
+                "sparrow-cli: Warning: open contour closed on layer 0",
+                "sparrow-cli: Warning: 2 duplicate entities removed",
+            ]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
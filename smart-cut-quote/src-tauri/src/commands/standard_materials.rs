@@ -0,0 +1,273 @@
+//! Standard stock for a first quote
+//!
+//! A new install has no usable stock, so the first quote starts with
+//! manual data entry. `seed_standard_materials` inserts mild steel,
+//! stainless and aluminum in common thicknesses and sheet sizes, metric
+//! or imperial. Prices are placeholders flagged `needs_price_review`.
+//! Rows the user already has (same material, thickness and sheet size,
+//! active or not) are skipped, so seeding can be repeated.
+
+use crate::db;
+use crate::nesting_engine::MM_PER_INCH;
+use serde::{Deserialize, Serialize};
+
+/// Thickness difference still treated as the same stock (mm)
+const THICKNESS_TOLERANCE: f64 = 0.01;
+/// Sheet size difference still treated as the same stock (mm)
+const SHEET_SIZE_TOLERANCE: f64 = 1.0;
+
+/// Cut price per meter times cutting speed of the sample stock, gives a
+/// placeholder cut price from the cutting speed
+const CUT_PRICE_SPEED_FACTOR: f64 = 4500.0;
+
+/// Sheet sizes and thicknesses to seed
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StockRegion {
+    /// mm thicknesses, 2500×1250, 3000×1500 and 6000×1500 sheets
+    #[default]
+    Metric,
+    /// Fractional inch thicknesses, 4×8, 5×10 and 5×12 ft sheets
+    Imperial,
+}
+
+struct StandardMaterial {
+    id_prefix: &'static str,
+    name: &'static str,
+    grade: &'static str,
+    /// kg/m³
+    density: f64,
+    /// Placeholder, flagged for review
+    price_per_kg: f64,
+    /// Cutting speed at 1 mm (mm/min), slower in proportion to thickness
+    speed_at_1mm: f64,
+    /// mm
+    metric_thicknesses: &'static [f64],
+    /// Inches
+    imperial_thicknesses: &'static [f64],
+}
+
+const STANDARD_MATERIALS: &[StandardMaterial] = &[
+    StandardMaterial {
+        id_prefix: "ms",
+        name: "Mild Steel",
+        grade: "A36",
+        density: 7850.0,
+        price_per_kg: 2.5,
+        speed_at_1mm: 6000.0,
+        metric_thicknesses: &[1.5, 2.0, 3.0, 4.0, 5.0, 6.0, 8.0, 10.0],
+        imperial_thicknesses: &[0.0625, 0.125, 0.1875, 0.25, 0.375, 0.5],
+    },
+    StandardMaterial {
+        id_prefix: "ss304",
+        name: "Stainless Steel",
+        grade: "304",
+        density: 8000.0,
+        price_per_kg: 5.5,
+        speed_at_1mm: 4000.0,
+        metric_thicknesses: &[1.0, 1.5, 2.0, 3.0, 4.0, 5.0, 6.0],
+        imperial_thicknesses: &[0.0625, 0.125, 0.1875, 0.25],
+    },
+    StandardMaterial {
+        id_prefix: "al",
+        name: "Aluminum",
+        grade: "5052",
+        density: 2700.0,
+        price_per_kg: 8.0,
+        speed_at_1mm: 5000.0,
+        metric_thicknesses: &[1.0, 1.5, 2.0, 3.0, 4.0, 5.0, 6.0],
+        imperial_thicknesses: &[0.0625, 0.125, 0.1875, 0.25],
+    },
+];
+
+/// (length, width) in mm
+const METRIC_SHEETS: &[(f64, f64)] = &[(2500.0, 1250.0), (3000.0, 1500.0), (6000.0, 1500.0)];
+/// (length, width) in inches
+const IMPERIAL_SHEETS: &[(f64, f64)] = &[(96.0, 48.0), (120.0, 60.0), (144.0, 60.0)];
+
+/// Stock row to seed, lengths in mm
+#[derive(Debug, Clone, PartialEq)]
+pub struct StandardStock {
+    pub id: String,
+    pub name: &'static str,
+    pub grade: &'static str,
+    pub thickness: f64,
+    pub sheet_width: f64,
+    pub sheet_max_length: f64,
+    pub price_per_kg: f64,
+    pub density: f64,
+    /// mm/min
+    pub cutting_speed: f64,
+    pub cut_price_per_meter: f64,
+}
+
+/// Every stock row of a region, material by material
+pub fn standard_stock(region: StockRegion) -> Vec<StandardStock> {
+    let (sheets, to_mm) = match region {
+        StockRegion::Metric => (METRIC_SHEETS, 1.0),
+        StockRegion::Imperial => (IMPERIAL_SHEETS, MM_PER_INCH),
+    };
+    let mut stock = Vec::new();
+    for material in STANDARD_MATERIALS {
+        let thicknesses = match region {
+            StockRegion::Metric => material.metric_thicknesses,
+            StockRegion::Imperial => material.imperial_thicknesses,
+        };
+        for &thickness in thicknesses {
+            let thickness = round_to(thickness * to_mm, 4);
+            let cutting_speed = (material.speed_at_1mm / thickness)
+                .round()
+                .clamp(300.0, 8000.0);
+            for &(length, width) in sheets {
+                let (length, width) = (round_to(length * to_mm, 1), round_to(width * to_mm, 1));
+                stock.push(StandardStock {
+                    id: format!(
+                        "std_{}_{}_{}x{}",
+                        material.id_prefix, thickness, length, width
+                    ),
+                    name: material.name,
+                    grade: material.grade,
+                    thickness,
+                    sheet_width: width,
+                    sheet_max_length: length,
+                    price_per_kg: material.price_per_kg,
+                    density: material.density,
+                    cutting_speed,
+                    cut_price_per_meter: round_to(CUT_PRICE_SPEED_FACTOR / cutting_speed, 2),
+                });
+            }
+        }
+    }
+    stock
+}
+
+/// Drops the float noise of inch conversions from ids and stored values
+fn round_to(value: f64, decimals: i32) -> f64 {
+    let scale = 10f64.powi(decimals);
+    (value * scale).round() / scale
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SeedReport {
+    pub region: StockRegion,
+    /// IDs of the rows added by this call
+    pub inserted: Vec<String>,
+    /// Rows the user already had (same material, thickness and size)
+    pub skipped: usize,
+}
+
+/// Insert the standard stock of a region (metric when unset)
+///
+/// Safe to call again: existing rows are never changed.
+#[tauri::command]
+pub async fn seed_standard_materials(
+    app_handle: tauri::AppHandle,
+    region: Option<StockRegion>,
+) -> Result<SeedReport, String> {
+    let region = region.unwrap_or_default();
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let mut report = SeedReport {
+        region,
+        inserted: Vec::new(),
+        skipped: 0,
+    };
+
+    for stock in standard_stock(region) {
+        // Sheets may be entered either way round
+        let existing: Option<(String,)> = sqlx::query_as(
+            "SELECT id FROM material_stock
+             WHERE name = ? COLLATE NOCASE AND abs(thickness - ?) < ?
+               AND ((abs(sheet_max_length - ?) < ? AND abs(sheet_width - ?) < ?)
+                 OR (abs(sheet_max_length - ?) < ? AND abs(sheet_width - ?) < ?))
+             LIMIT 1",
+        )
+        .bind(stock.name)
+        .bind(stock.thickness)
+        .bind(THICKNESS_TOLERANCE)
+        .bind(stock.sheet_max_length)
+        .bind(SHEET_SIZE_TOLERANCE)
+        .bind(stock.sheet_width)
+        .bind(SHEET_SIZE_TOLERANCE)
+        .bind(stock.sheet_width)
+        .bind(SHEET_SIZE_TOLERANCE)
+        .bind(stock.sheet_max_length)
+        .bind(SHEET_SIZE_TOLERANCE)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to look up stock '{}': {}", stock.id, e))?;
+        if existing.is_some() {
+            report.skipped += 1;
+            continue;
+        }
+
+        let inserted = sqlx::query(
+            "INSERT OR IGNORE INTO material_stock
+                (id, name, grade, thickness, sheet_width, sheet_max_length, price_per_kg,
+                 density, cutting_speed, cut_price_per_meter, quantity_in_stock, min_quantity,
+                 needs_price_review)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 0, 0, 1)",
+        )
+        .bind(&stock.id)
+        .bind(stock.name)
+        .bind(stock.grade)
+        .bind(stock.thickness)
+        .bind(stock.sheet_width)
+        .bind(stock.sheet_max_length)
+        .bind(stock.price_per_kg)
+        .bind(stock.density)
+        .bind(stock.cutting_speed)
+        .bind(stock.cut_price_per_meter)
+        .execute(&pool)
+        .await
+        .map_err(|e| format!("Failed to insert stock '{}': {}", stock.id, e))?;
+        if inserted.rows_affected() > 0 {
+            report.inserted.push(stock.id);
+        } else {
+            report.skipped += 1;
+        }
+    }
+
+    println!(
+        "🌱 Seeded {} standard stock rows ({:?}), {} already present",
+        report.inserted.len(),
+        region,
+        report.skipped
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_metric_stock_covers_every_material_thickness_and_size() {
+        let stock = standard_stock(StockRegion::Metric);
+        assert_eq!(stock.len(), (8 + 7 + 7) * 3);
+        let ids: HashSet<&str> = stock.iter().map(|row| row.id.as_str()).collect();
+        assert_eq!(ids.len(), stock.len());
+        assert!(ids.contains("std_ms_3_3000x1500"));
+        assert!(ids.contains("std_al_1.5_2500x1250"));
+        for row in &stock {
+            assert!(row.sheet_width <= row.sheet_max_length);
+            assert!(row.cutting_speed > 0.0 && row.cut_price_per_meter > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_imperial_stock_is_converted_to_mm() {
+        let stock = standard_stock(StockRegion::Imperial);
+        assert_eq!(stock.len(), (6 + 4 + 4) * 3);
+        let quarter_inch = stock
+            .iter()
+            .find(|row| row.name == "Stainless Steel" && row.thickness == 6.35)
+            .expect("1/4 in stainless");
+        assert_eq!(quarter_inch.sheet_max_length, 2438.4);
+        assert_eq!(quarter_inch.sheet_width, 1219.2);
+        assert_eq!(
+            serde_json::to_value(StockRegion::Imperial).unwrap(),
+            "imperial"
+        );
+    }
+}
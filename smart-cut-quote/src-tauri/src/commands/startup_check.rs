@@ -0,0 +1,349 @@
+//! Startup integrity check of the installation
+//!
+//! Fresh installs sometimes lose the bundled binaries (antivirus
+//! quarantine) or cannot write to the app folders, and the first
+//! conversion then fails without a useful message. The check runs once
+//! at startup, after the migrations, and reports what it finds as the
+//! `startup://issues` event and in the log. Findings never stop the app:
+//! the diagnostics screen shows them and offers `repair_installation`.
+
+use crate::commands::migrations::{self, MigrationState};
+use crate::db;
+use crate::workspace::{self, WORKSPACE_DIRS};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Event carrying the `StartupReport`
+pub const STARTUP_ISSUES_EVENT: &str = "startup://issues";
+
+/// File bundled with the installer (`bundle.resources`)
+struct ExpectedResource {
+    /// Path below the resource directory
+    relative: &'static str,
+    /// What stops working without it
+    needed_for: &'static str,
+    /// Copy compiled into the app, written back by `repair_installation`
+    /// (the converter binaries are too large to embed)
+    embedded: Option<&'static [u8]>,
+}
+
+const EXPECTED_RESOURCES: &[ExpectedResource] = &[
+    ExpectedResource {
+        relative: "binaries/dxf-converter.exe",
+        needed_for: "DXF conversion",
+        embedded: None,
+    },
+    ExpectedResource {
+        relative: "binaries/sparrow-cli.exe",
+        needed_for: "nesting with the external sparrow CLI",
+        embedded: None,
+    },
+];
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// A feature will not work
+    Warning,
+    /// The app cannot save or load data
+    Error,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    MissingResource,
+    DirectoryNotWritable,
+    DatabaseUnavailable,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub severity: Severity,
+    /// File or directory the finding is about
+    pub path: String,
+    pub message: String,
+    /// True when `repair_installation` can fix it
+    pub repairable: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct StartupReport {
+    pub checked_at: String,
+    pub findings: Vec<Finding>,
+    /// Repairs done by the last `repair_installation` call
+    pub repaired: Vec<String>,
+}
+
+/// Last startup or repair report
+#[derive(Default)]
+pub struct StartupCheck(Mutex<Option<StartupReport>>);
+
+impl StartupCheck {
+    pub fn latest(&self) -> Option<StartupReport> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Check the installation and publish the report
+///
+/// Called from `setup` after the migrations have run.
+pub fn run_startup_check(app_handle: &AppHandle) {
+    let report = tauri::async_runtime::block_on(check_installation(app_handle));
+    publish(app_handle, &report);
+}
+
+/// Report of the startup check (or of the last repair)
+#[tauri::command]
+pub fn get_startup_report(
+    state: tauri::State<'_, StartupCheck>,
+) -> Result<Option<StartupReport>, String> {
+    Ok(state.latest())
+}
+
+/// Recreate missing directories and embedded resources, then check again
+#[tauri::command]
+pub async fn repair_installation(app_handle: AppHandle) -> Result<StartupReport, String> {
+    let mut repaired = Vec::new();
+
+    for dir in app_directories(&app_handle) {
+        if !dir.is_dir() {
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            repaired.push(format!("Created {}", dir.display()));
+        }
+    }
+
+    for resource in EXPECTED_RESOURCES {
+        let Some(content) = resource.embedded else {
+            continue;
+        };
+        let path = installed_resource_path(&app_handle, resource.relative)?;
+        if !path.exists() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            std::fs::write(&path, content)
+                .map_err(|e| format!("Failed to restore {}: {}", path.display(), e))?;
+            repaired.push(format!("Restored {}", path.display()));
+        }
+    }
+
+    let mut report = check_installation(&app_handle).await;
+    report.repaired = repaired;
+    println!(
+        "🔧 Repair finished: {} fixes, {} findings left",
+        report.repaired.len(),
+        report.findings.len()
+    );
+    publish(&app_handle, &report);
+    Ok(report)
+}
+
+/// Resource bundled with the app, or its copy in the repository during development
+pub(crate) fn resource_path(app_handle: &AppHandle, relative: &str) -> Result<PathBuf, String> {
+    let resource_path = installed_resource_path(app_handle, relative)?;
+    if resource_path.exists() {
+        Ok(resource_path)
+    } else {
+        // Development mode: use relative path
+        Ok(std::env::current_dir()
+            .map_err(|e| format!("Failed to get current directory: {}", e))?
+            .join("../..")
+            .join(relative))
+    }
+}
+
+fn installed_resource_path(app_handle: &AppHandle, relative: &str) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .resolve(relative, tauri::path::BaseDirectory::Resource)
+        .map_err(|e| format!("Failed to resolve {} path: {}", relative, e))
+}
+
+/// Directories the app writes to: database folder and workspace folders
+fn app_directories(app_handle: &AppHandle) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(db_dir) = db::db_file_path(app_handle)
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+    {
+        dirs.push(db_dir);
+    }
+    dirs.extend(
+        WORKSPACE_DIRS
+            .iter()
+            .filter_map(|name| workspace::workspace_dir(app_handle, name).ok()),
+    );
+    dirs
+}
+
+async fn check_installation(app_handle: &AppHandle) -> StartupReport {
+    let mut findings = Vec::new();
+
+    for resource in EXPECTED_RESOURCES {
+        match resource_path(app_handle, resource.relative) {
+            Ok(path) if path.is_file() => {}
+            Ok(path) => findings.push(Finding {
+                kind: FindingKind::MissingResource,
+                severity: Severity::Warning,
+                path: path.display().to_string(),
+                message: format!(
+                    "{} is missing, {} will fail. Restore it from the antivirus \
+                     quarantine or reinstall the app.",
+                    resource.relative, resource.needed_for
+                ),
+                repairable: resource.embedded.is_some(),
+            }),
+            Err(message) => findings.push(Finding {
+                kind: FindingKind::MissingResource,
+                severity: Severity::Warning,
+                path: resource.relative.to_string(),
+                message,
+                repairable: false,
+            }),
+        }
+    }
+
+    findings.extend(
+        app_directories(app_handle)
+            .iter()
+            .filter_map(|dir| check_directory(dir)),
+    );
+
+    if let Some(finding) = check_database(app_handle).await {
+        findings.push(finding);
+    }
+
+    StartupReport {
+        checked_at: chrono::Local::now().to_rfc3339(),
+        findings,
+        repaired: Vec::new(),
+    }
+}
+
+/// Missing or read-only directory; a missing one is created on first use
+fn check_directory(dir: &Path) -> Option<Finding> {
+    let finding = |message: String| Finding {
+        kind: FindingKind::DirectoryNotWritable,
+        severity: Severity::Error,
+        path: dir.display().to_string(),
+        message,
+        repairable: false,
+    };
+
+    if !dir.exists() {
+        // Created on demand, unless the parent cannot be written either
+        let parent = dir.ancestors().skip(1).find(|ancestor| ancestor.exists())?;
+        return probe_write(parent)
+            .err()
+            .map(|e| finding(format!("{} cannot be created: {}", dir.display(), e)));
+    }
+    if !dir.is_dir() {
+        return Some(finding(format!(
+            "{} is a file, not a directory",
+            dir.display()
+        )));
+    }
+    probe_write(dir)
+        .err()
+        .map(|e| finding(format!("{} is not writable: {}", dir.display(), e)))
+}
+
+/// Create and remove a small file in `dir`
+fn probe_write(dir: &Path) -> std::io::Result<()> {
+    let probe = dir.join(format!(".write_check_{}", std::process::id()));
+    std::fs::write(&probe, b"ok")?;
+    std::fs::remove_file(&probe)
+}
+
+async fn check_database(app_handle: &AppHandle) -> Option<Finding> {
+    let finding = |path: String, message: String| Finding {
+        kind: FindingKind::DatabaseUnavailable,
+        severity: Severity::Error,
+        path,
+        message,
+        repairable: false,
+    };
+
+    if let Some(failure) = app_handle.state::<MigrationState>().startup_error() {
+        let path = failure.backup_path.clone().unwrap_or_default();
+        return Some(finding(path, failure.message));
+    }
+
+    let db_path = match db::db_file_path(app_handle) {
+        Ok(path) => path,
+        Err(message) => return Some(finding(String::new(), message)),
+    };
+    if !db_path.exists() {
+        return Some(finding(
+            db_path.display().to_string(),
+            "Database file was not created".to_string(),
+        ));
+    }
+    let result = match migrations::open_pool(&db_path, false).await {
+        Ok(pool) => {
+            let result = sqlx::query("SELECT COUNT(*) FROM sqlite_master")
+                .execute(&pool)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("Database cannot be read: {}", e));
+            pool.close().await;
+            result
+        }
+        Err(message) => Err(message),
+    };
+    result
+        .err()
+        .map(|message| finding(db_path.display().to_string(), message))
+}
+
+/// Log the findings, keep the report and emit it
+fn publish(app_handle: &AppHandle, report: &StartupReport) {
+    if report.findings.is_empty() {
+        log::info!("Startup check: installation OK");
+    }
+    for finding in &report.findings {
+        log::warn!(
+            "Startup check: {:?} {}: {}",
+            finding.kind,
+            finding.path,
+            finding.message
+        );
+    }
+    *app_handle.state::<StartupCheck>().0.lock().unwrap() = Some(report.clone());
+    let _ = app_handle.emit(STARTUP_ISSUES_EVENT, report);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::test_dir;
+
+    #[test]
+    fn test_writable_and_missing_directories_pass() {
+        let dir = test_dir("startup-check", "writable");
+
+        assert!(check_directory(&dir).is_none());
+        // Not created yet, but its parent is writable
+        assert!(check_directory(&dir.join("jobs")).is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_file_in_place_of_directory_is_reported() {
+        let dir = test_dir("startup-check", "file");
+        let blocked = dir.join("svgs");
+        std::fs::write(&blocked, b"not a directory").unwrap();
+
+        let finding = check_directory(&blocked).unwrap();
+        assert_eq!(finding.kind, FindingKind::DirectoryNotWritable);
+        assert_eq!(finding.severity, Severity::Error);
+        assert!(finding.message.contains("is a file"), "{}", finding.message);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,92 @@
+//! Context for the strip height check of `validate_input`
+//!
+//! A strip height with an extra zero (15000 instead of 1500) otherwise
+//! nests fine and quotes nonsense. The engine compares the height with
+//! the widest stock sheet of the run's material, or with the
+//! `max_strip_height` setting when the material is unknown; coil stock
+//! users raise the setting.
+
+use crate::db;
+use crate::nesting_engine::{parse_number, ConfigSource, NestingInput, StockSheet};
+
+/// App setting holding the sanity maximum of the strip height in mm
+const MAX_STRIP_HEIGHT_SETTING: &str = "max_strip_height";
+/// Thickness difference still treated as the same stock (mm)
+const THICKNESS_TOLERANCE: f64 = 0.01;
+
+/// Fill the stock sheet and sanity maximum the input does not set
+///
+/// The stock sheet is the widest active sheet of the same material and
+/// thickness as `material_id`.
+pub async fn resolve_strip_height_limits(
+    app_handle: &tauri::AppHandle,
+    material_id: Option<&str>,
+    input: &mut NestingInput,
+) -> Result<(), String> {
+    let pool = db::sqlite_pool(app_handle).await?;
+
+    let setting: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = ?")
+        .bind(MAX_STRIP_HEIGHT_SETTING)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| format!("Failed to load strip height setting: {}", e))?;
+    input.max_strip_height = input.config_sources.pick(
+        "max_strip_height",
+        &[
+            (ConfigSource::Input, input.max_strip_height),
+            (
+                ConfigSource::AppSettings,
+                setting.and_then(|(value,)| max_strip_height_from_setting(&value)),
+            ),
+        ],
+    );
+
+    let Some(material_id) = material_id else {
+        return Ok(());
+    };
+    let sheet: Option<(f64, f64)> = sqlx::query_as(
+        "SELECT s.sheet_max_length, s.sheet_width
+         FROM material_stock s JOIN material_stock m ON m.id = ?
+         WHERE s.name = m.name COLLATE NOCASE AND abs(s.thickness - m.thickness) < ?
+           AND (s.is_active = 1 OR s.id = m.id)
+         ORDER BY s.sheet_width DESC, s.sheet_max_length DESC
+         LIMIT 1",
+    )
+    .bind(material_id)
+    .bind(THICKNESS_TOLERANCE)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| format!("Failed to load stock of material '{}': {}", material_id, e))?;
+
+    input.stock_sheet = input.config_sources.pick(
+        "stock_sheet",
+        &[
+            (ConfigSource::Input, input.stock_sheet),
+            (
+                ConfigSource::Material,
+                sheet.map(|(length, width)| StockSheet { length, width }),
+            ),
+        ],
+    );
+    Ok(())
+}
+
+/// Sanity maximum of the setting, None when unset or not a positive length
+fn max_strip_height_from_setting(value: &str) -> Option<f64> {
+    parse_number(value)
+        .ok()
+        .filter(|height| height.is_finite() && *height > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_strip_height_setting() {
+        // Raised for coil stock
+        assert_eq!(max_strip_height_from_setting("12000"), Some(12000.0));
+        assert_eq!(max_strip_height_from_setting("0"), None);
+        assert_eq!(max_strip_height_from_setting("wide"), None);
+    }
+}
@@ -0,0 +1,166 @@
+//! Re-nesting only the revised items of a stored nesting result
+//!
+//! `renest_subset` keeps the placements of the unchanged items of a
+//! stored result and places only the revised geometry into the space
+//! they leave free (see `nesting_engine::renest_subset`). When the
+//! revised parts do not fit around the kept ones, or the stored layout
+//! cannot be kept, the whole job is nested again instead and the
+//! report says so. Either way the new layout is stored as a revision of
+//! the result.
+
+use crate::commands::nesting_metrics::{record_nesting_run, NestingRunMetrics};
+use crate::commands::result_improvement::{load_result, store_revision};
+use crate::commands::shutdown;
+use crate::db;
+use crate::nesting_engine::{self, parse_instance, NestingInput, NestingOutput, PlacementOrigins};
+use serde::Serialize;
+
+/// Outcome of `renest_subset`
+#[derive(Serialize, Debug)]
+pub struct SubsetRenestReport {
+    pub result_id: i64,
+    /// Stored revision holding the new layout
+    pub new_result_id: i64,
+    /// True when the whole job was nested again
+    pub full_renest: bool,
+    /// Why the revised items could not be placed around the kept ones
+    pub fallback_reason: Option<String>,
+    /// Items placed again: the requested ones and any other item whose
+    /// geometry changed, every item after a full re-nest
+    pub renested_item_ids: Vec<u64>,
+    /// Placements kept from the stored layout and placed anew
+    pub placements: PlacementOrigins,
+    pub output: NestingOutput,
+}
+
+/// Re-nest the changed items of a stored result, keeping all others
+///
+/// The revised geometry comes from `instance_json`, the quote's current
+/// parts, or else from the quote's saved nesting snapshot. Items keep
+/// their IDs across the revision. If the changed items do not fit the
+/// sheet around the kept placements, the whole updated instance is
+/// nested with the stored config for `extra_time_secs` and flagged
+/// `full_renest`. Lengths are in mm.
+#[tauri::command]
+pub async fn renest_subset(
+    app_handle: tauri::AppHandle,
+    result_id: i64,
+    changed_item_ids: Vec<u64>,
+    extra_time_secs: u64,
+    instance_json: Option<String>,
+) -> Result<SubsetRenestReport, String> {
+    if extra_time_secs == 0 {
+        return Err("extra_time_secs must be at least 1 second".to_string());
+    }
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let stored = load_result(&pool, result_id).await?;
+    let updated_json = match instance_json {
+        Some(json) => json,
+        None => {
+            let quote_id = stored.quote_id.as_deref().ok_or_else(|| {
+                format!(
+                    "Nesting result {} has no quote, pass the updated instance",
+                    result_id
+                )
+            })?;
+            let row: Option<(String,)> =
+                sqlx::query_as("SELECT instance_json FROM nesting_snapshots WHERE quote_id = ?")
+                    .bind(quote_id)
+                    .fetch_optional(&pool)
+                    .await
+                    .map_err(|e| {
+                        format!(
+                            "Failed to load nesting snapshot for quote '{}': {}",
+                            quote_id, e
+                        )
+                    })?;
+            row.map(|(json,)| json)
+                .ok_or_else(|| format!("No nesting snapshot stored for quote '{}'", quote_id))?
+        }
+    };
+
+    let started = std::time::Instant::now();
+    let subset = nesting_engine::renest_subset(
+        &stored.output,
+        &stored.instance_json,
+        &updated_json,
+        &changed_item_ids,
+    );
+    let (output, renested_item_ids, placements, fallback_reason) = match subset {
+        Ok(subset) => {
+            let mut output = subset.output;
+            output.computation_time_secs = started.elapsed().as_secs_f64();
+            println!(
+                "🧩 Nesting result {}: kept {} placements, placed {} anew",
+                result_id,
+                subset.origins.preserved.len(),
+                subset.origins.renested.len()
+            );
+            (output, subset.changed_item_ids, subset.origins, None)
+        }
+        Err(reason) => {
+            println!(
+                "⚠️ Nesting result {}: {}, nesting the whole job again",
+                result_id, reason
+            );
+            let config =
+                stored.output.resolved_config.clone().ok_or_else(|| {
+                    format!("Nesting result {} has no resolved config", result_id)
+                })?;
+            let input = NestingInput::builder()
+                .json_input(updated_json.clone())
+                .config(&config)
+                .seed(rand::random())
+                .time_limit_secs(extra_time_secs)
+                .build()
+                .map_err(|issues| {
+                    let messages: Vec<String> =
+                        issues.iter().map(|issue| issue.to_string()).collect();
+                    format!("Updated instance is invalid: {}", messages.join("; "))
+                })?;
+
+            let metrics = NestingRunMetrics::for_input(&input);
+            let job_app_handle = app_handle.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || {
+                shutdown::run_registered(&job_app_handle, None, |terminator| {
+                    nesting_engine::run_nesting_engine_with_terminator(input, terminator)
+                })
+            })
+            .await
+            .map_err(|e| format!("Task join error: {}", e))?;
+            record_nesting_run(&app_handle, metrics.finish(&result, started.elapsed()));
+            let output = result?;
+
+            let item_ids = parse_instance(&updated_json)?
+                .items
+                .iter()
+                .map(|item| item.id)
+                .collect();
+            let placements = PlacementOrigins {
+                preserved: Vec::new(),
+                renested: (0..output.layouts.len()).collect(),
+            };
+            (output, item_ids, placements, Some(reason))
+        }
+    };
+
+    let new_result_id = store_revision(
+        &pool,
+        result_id,
+        stored.quote_id.as_deref(),
+        &updated_json,
+        &output,
+        "renest",
+    )
+    .await?;
+
+    Ok(SubsetRenestReport {
+        result_id,
+        new_result_id,
+        full_renest: fallback_reason.is_some(),
+        fallback_reason,
+        renested_item_ids,
+        placements,
+        output,
+    })
+}
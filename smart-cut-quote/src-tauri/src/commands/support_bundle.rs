@@ -0,0 +1,1088 @@
+//! Support bundle for problems on site
+//!
+//! Instead of zipping folders by hand, users send the one archive made by
+//! `create_support_bundle`: recent log files, the startup check report,
+//! the last nesting metrics, the settings, the instance of the last
+//! failed nesting run, environment info and, if asked for, a database
+//! snapshot, all described by `manifest.json`.
+//!
+//! Secrets in the settings never leave the machine. Customer names and
+//! prices are removed unless the user explicitly includes them (see
+//! `BundlePrivacy`): client and contact names and emails are replaced in
+//! every text entry, price fields are redacted in the settings and item
+//! metadata, and the database snapshot is scrubbed the same way. The
+//! bundle is capped at `MAX_BUNDLE_BYTES`; entries that do not fit are
+//! left out and listed in the manifest.
+
+use crate::commands::migrations;
+use crate::commands::startup_check::StartupCheck;
+use crate::db;
+use crate::nesting_engine::{logging, ENGINE_VERSION};
+use crate::workspace;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DATABASE_ENTRY: &str = "database/smart_cut_quote.db";
+const LOGS_PREFIX: &str = "logs";
+
+/// Upper limit of the bundle, counted uncompressed
+pub const MAX_BUNDLE_BYTES: u64 = 50 * 1024 * 1024;
+/// Room kept for the manifest below the cap
+const MANIFEST_RESERVE_BYTES: u64 = 64 * 1024;
+/// Newest log files included
+const MAX_LOG_FILES: usize = 5;
+/// Tail of each log file included
+const MAX_LOG_BYTES: u64 = 2 * 1024 * 1024;
+/// Most recent `nesting_metrics` rows included
+const METRICS_ROWS: i64 = 200;
+/// Last failed nesting run, below the app data folder
+const FAILED_RUN_FILE: &str = "support/last_failed_run.json";
+
+const REDACTED: &str = "[redacted]";
+const CUSTOMER_PLACEHOLDER: &str = "[customer]";
+/// Names shorter than this would be replaced inside ordinary words
+const MIN_NAME_CHARS: usize = 3;
+/// Emails of customers not in the client list
+const EMAIL_PATTERN: &str = r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+";
+
+/// Setting and metadata keys whose values are never included
+const SECRET_KEY_PARTS: &[&str] = &["secret", "token", "password", "api_key"];
+/// Keys of customer data, included only with `include_customer_names`
+const NAME_KEY_PARTS: &[&str] = &[
+    "customer", "client", "company", "contact", "email", "phone", "address",
+];
+/// Keys of prices, included only with `include_prices`
+const PRICE_KEY_PARTS: &[&str] = &[
+    "price",
+    "cost",
+    "amount",
+    "total",
+    "markup",
+    "discount",
+    "tax",
+    "scrap_value",
+];
+
+/// Scrubs customer names from the database snapshot
+const NAME_SCRUB_SQL: &[&str] = &[
+    "UPDATE clients SET company_name = 'Customer ' || rowid, phone = NULL, email = NULL,
+        business_no = NULL, billing_address_line1 = NULL, billing_address_line2 = NULL,
+        billing_city = NULL, billing_state = NULL, billing_zip = NULL, billing_country = NULL,
+        shipping_address_line1 = NULL, shipping_address_line2 = NULL, shipping_city = NULL,
+        shipping_state = NULL, shipping_zip = NULL, shipping_country = NULL",
+    "UPDATE client_contacts SET name = 'Contact ' || rowid, phone = NULL, email = NULL",
+    "UPDATE quotes SET notes = NULL, reference = NULL, data = NULL",
+    "UPDATE tasks SET title = 'Task ' || rowid, description = NULL",
+];
+/// Scrubs prices from the database snapshot
+const PRICE_SCRUB_SQL: &[&str] = &[
+    "UPDATE quotes SET price_markup = 0, material_markup = 0, tax_rate = 0, discount = 0,
+        hidden_discount = 0, subtotal = 0, total = 0, data = NULL",
+    "UPDATE quote_audit SET summary = NULL",
+    "UPDATE clients SET additional_price_markup = 0, additional_material_markup = 0",
+    "UPDATE material_stock SET price_per_kg = 0, pierce_cost = NULL, cut_price_per_meter = 0",
+    "UPDATE machines SET hourly_rate = 0",
+    "UPDATE operations SET cost = 0",
+    "UPDATE cutting_profiles SET price_per_meter = NULL, price_per_pierce = NULL",
+];
+/// Tables of free-form JSON or file paths, emptied when names or prices
+/// are removed (in foreign key order)
+const SCRUBBED_TABLES: &[&str] = &[
+    "quote_drafts",
+    "nesting_snapshots",
+    "nesting_results",
+    "instance_blobs",
+    "webhook_deliveries",
+    "pipeline_undo_log",
+    "conversion_job_files",
+    "conversion_jobs",
+    "conversion_cache",
+];
+
+/// What the bundle may contain beyond technical data
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct BundlePrivacy {
+    /// Keep client and contact names, emails and addresses
+    pub include_customer_names: bool,
+    /// Keep prices, costs and markups
+    pub include_prices: bool,
+    /// Replace the part contours of the failed instance by their
+    /// bounding rectangles
+    pub exclude_geometry: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BundleEntry {
+    pub name: String,
+    pub size_bytes: u64,
+}
+
+/// Data left out of the bundle and why
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ExcludedEntry {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Describes the content of a support bundle
+#[derive(Serialize, Debug, Clone)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub engine_version: String,
+    pub created_at: String,
+    pub privacy: BundlePrivacy,
+    pub size_cap_bytes: u64,
+    pub entries: Vec<BundleEntry>,
+    pub excluded: Vec<ExcludedEntry>,
+}
+
+/// Written support bundle
+#[derive(Serialize, Debug, Clone)]
+pub struct SupportBundle {
+    pub path: String,
+    #[serde(flatten)]
+    pub manifest: BundleManifest,
+}
+
+/// Applies a `BundlePrivacy` to the collected data
+pub struct Redactor {
+    privacy: BundlePrivacy,
+    /// Any known customer name or an email, None when names are kept
+    names: Option<Regex>,
+}
+
+impl Redactor {
+    /// # Arguments
+    /// * `customer_names` - Client and contact names to replace in texts
+    pub fn new(privacy: BundlePrivacy, customer_names: &[String]) -> Result<Self, String> {
+        if privacy.include_customer_names {
+            return Ok(Self {
+                privacy,
+                names: None,
+            });
+        }
+
+        let mut names: Vec<&str> = customer_names
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| name.chars().count() >= MIN_NAME_CHARS)
+            .collect();
+        // Longest first, so "Acme Steel" is replaced before "Acme"
+        names.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+        names.dedup();
+        let mut alternatives: Vec<String> = names.into_iter().map(regex::escape).collect();
+        alternatives.push(EMAIL_PATTERN.to_string());
+        let names = Regex::new(&format!("(?i){}", alternatives.join("|")))
+            .map_err(|e| format!("Failed to build the customer name filter: {}", e))?;
+        Ok(Self {
+            privacy,
+            names: Some(names),
+        })
+    }
+
+    /// Text with every customer name and email replaced
+    pub fn text(&self, text: &str) -> String {
+        match &self.names {
+            Some(names) => names.replace_all(text, CUSTOMER_PLACEHOLDER).into_owned(),
+            None => text.to_string(),
+        }
+    }
+
+    fn hides_key(&self, key: &str) -> bool {
+        let key = key.to_lowercase();
+        let matches = |parts: &[&str]| parts.iter().any(|part| key.contains(part));
+        matches(SECRET_KEY_PARTS)
+            || (!self.privacy.include_customer_names && matches(NAME_KEY_PARTS))
+            || (!self.privacy.include_prices && matches(PRICE_KEY_PARTS))
+    }
+
+    /// Settings with secrets and, unless included, names and prices redacted
+    ///
+    /// Empty values stay empty, the bundle still shows what is unset.
+    pub fn settings(&self, settings: Vec<(String, String)>) -> BTreeMap<String, String> {
+        settings
+            .into_iter()
+            .map(|(key, value)| {
+                let value = if value.is_empty() {
+                    value
+                } else if self.hides_key(&key) {
+                    REDACTED.to_string()
+                } else {
+                    self.text(&value)
+                };
+                (key, value)
+            })
+            .collect()
+    }
+
+    /// Redact hidden keys and customer names at any depth
+    fn json(&self, value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                for (key, field) in fields.iter_mut() {
+                    if self.hides_key(key) && !field.is_null() {
+                        *field = Value::String(REDACTED.to_string());
+                    } else {
+                        self.json(field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.json(item)),
+            Value::String(text) => *text = self.text(text),
+            _ => {}
+        }
+    }
+
+    /// Instance JSON of a failed run with the privacy applied
+    ///
+    /// Without customer names the instance is renamed and the parts lose
+    /// their labels and file names, which often carry the customer.
+    pub fn instance(&self, instance: &mut Value) {
+        let hide_names = !self.privacy.include_customer_names;
+        if hide_names {
+            if let Some(name) = instance.get_mut("name") {
+                *name = Value::String("instance".to_string());
+            }
+        }
+        if let Some(items) = instance.get_mut("items").and_then(Value::as_array_mut) {
+            for item in items {
+                let Some(fields) = item.as_object_mut() else {
+                    continue;
+                };
+                if hide_names {
+                    fields.remove("label");
+                    if fields.contains_key("dxf") {
+                        let id = fields.get("id").cloned().unwrap_or(Value::Null);
+                        fields.insert("dxf".to_string(), format!("item_{}.dxf", id).into());
+                    }
+                }
+                if self.privacy.exclude_geometry {
+                    exclude_geometry(fields);
+                }
+            }
+        }
+        self.json(instance);
+    }
+}
+
+/// Replace an item's contours by their bounding rectangle
+fn exclude_geometry(item: &mut Map<String, Value>) {
+    item.remove("exact_geometry");
+    item.remove("bend_lines");
+    let Some(shape) = item.get("shape") else {
+        return;
+    };
+    if shape.get("type").and_then(Value::as_str) == Some("rectangle") {
+        return;
+    }
+
+    let mut points = Vec::new();
+    collect_points(shape, &mut points);
+    if points.is_empty() {
+        item.remove("shape");
+        return;
+    }
+    let (x_min, y_min, x_max, y_max) = points.iter().fold(
+        (
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ),
+        |(x_min, y_min, x_max, y_max), &(x, y)| {
+            (x_min.min(x), y_min.min(y), x_max.max(x), y_max.max(y))
+        },
+    );
+    item.insert(
+        "shape".to_string(),
+        serde_json::json!({
+            "type": "rectangle",
+            "data": { "x_min": x_min, "y_min": y_min, "width": x_max - x_min, "height": y_max - y_min }
+        }),
+    );
+}
+
+/// `[x, y]` pairs at any depth of a shape
+fn collect_points(value: &Value, points: &mut Vec<(f64, f64)>) {
+    if let Some(items) = value.as_array() {
+        if let [x, y] = items.as_slice() {
+            if let (Some(x), Some(y)) = (x.as_f64(), y.as_f64()) {
+                points.push((x, y));
+                return;
+            }
+        }
+        items.iter().for_each(|item| collect_points(item, points));
+    } else if let Some(fields) = value.as_object() {
+        fields
+            .values()
+            .for_each(|field| collect_points(field, points));
+    }
+}
+
+/// Entry collected for the bundle, before the size cap is applied
+enum PendingEntry {
+    Text { name: String, content: String },
+    File { name: String, path: PathBuf },
+}
+
+impl PendingEntry {
+    fn name(&self) -> &str {
+        match self {
+            PendingEntry::Text { name, .. } | PendingEntry::File { name, .. } => name,
+        }
+    }
+
+    fn size(&self) -> u64 {
+        match self {
+            PendingEntry::Text { content, .. } => content.len() as u64,
+            PendingEntry::File { path, .. } => std::fs::metadata(path).map_or(0, |m| m.len()),
+        }
+    }
+}
+
+/// Keep entries in order of priority while they fit under `cap`
+///
+/// A large entry that does not fit does not stop smaller ones after it.
+fn fit_to_cap(entries: Vec<PendingEntry>, cap: u64) -> (Vec<PendingEntry>, Vec<ExcludedEntry>) {
+    let mut total = 0;
+    let mut kept = Vec::new();
+    let mut excluded = Vec::new();
+    for entry in entries {
+        let size = entry.size();
+        if total + size <= cap {
+            total += size;
+            kept.push(entry);
+        } else {
+            excluded.push(ExcludedEntry {
+                name: entry.name().to_string(),
+                reason: format!(
+                    "{:.1} MB would exceed the {:.0} MB size cap",
+                    size as f64 / 1_048_576.0,
+                    cap as f64 / 1_048_576.0
+                ),
+            });
+        }
+    }
+    (kept, excluded)
+}
+
+/// Last failed nesting run, kept for the support bundle
+#[derive(Serialize, Deserialize, Debug)]
+struct FailedRun {
+    failed_at: String,
+    error: String,
+    /// Instance JSON, or the raw input when it was not valid JSON
+    instance: Value,
+}
+
+fn write_failed_run(path: &Path, instance_json: &str, error: &str) -> Result<(), String> {
+    let run = FailedRun {
+        failed_at: chrono::Utc::now().to_rfc3339(),
+        error: error.to_string(),
+        instance: serde_json::from_str(instance_json)
+            .unwrap_or_else(|_| Value::String(instance_json.to_string())),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string(&run)
+        .map_err(|e| format!("Failed to serialize failed run: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Keep the instance of a failed nesting run for the next support bundle
+///
+/// Replaces the previously kept run. Never fails the caller, errors are
+/// only logged.
+pub fn remember_failed_run(app_handle: &AppHandle, instance_json: &str, error: &str) {
+    let result = workspace::app_data_dir(app_handle)
+        .and_then(|dir| write_failed_run(&dir.join(FAILED_RUN_FILE), instance_json, error));
+    if let Err(e) = result {
+        println!("⚠️ Failed nesting run not kept for support: {}", e);
+    }
+}
+
+/// Kept failed run with the privacy applied, None if there is none
+fn failed_run_entry(path: &Path, redactor: &Redactor) -> Result<Option<String>, String> {
+    let json = match std::fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let mut run: FailedRun =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid failed run file: {}", e))?;
+    run.error = redactor.text(&run.error);
+    match &mut run.instance {
+        Value::String(raw) => *raw = redactor.text(raw),
+        instance => redactor.instance(instance),
+    }
+    serde_json::to_string_pretty(&run)
+        .map(Some)
+        .map_err(|e| format!("Failed to serialize failed run: {}", e))
+}
+
+/// Machine and build the bundle was made on
+#[derive(Serialize)]
+struct EnvironmentInfo {
+    app_version: String,
+    engine_version: &'static str,
+    /// None when the database could not be opened
+    schema_version: Option<i64>,
+    latest_schema_version: i64,
+    os: &'static str,
+    os_family: &'static str,
+    arch: &'static str,
+    cpu_count: usize,
+    app_data_dir: Option<String>,
+}
+
+#[derive(Serialize)]
+struct MetricsRow {
+    id: i64,
+    created_at: String,
+    item_count: i64,
+    demand_total: i64,
+    status: String,
+    error_code: Option<String>,
+    utilization: Option<f64>,
+    sheets: i64,
+    duration_secs: f64,
+    preset: Option<String>,
+    engine_version: String,
+}
+
+async fn load_customer_names(pool: &SqlitePool) -> Result<Vec<String>, String> {
+    let rows: Vec<(String,)> =
+        sqlx::query_as("SELECT company_name FROM clients UNION SELECT name FROM client_contacts")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to load customer names: {}", e))?;
+    Ok(rows.into_iter().map(|(name,)| name).collect())
+}
+
+async fn load_settings(pool: &SqlitePool) -> Result<Vec<(String, String)>, String> {
+    sqlx::query_as("SELECT key, value FROM settings ORDER BY key")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load settings: {}", e))
+}
+
+#[allow(clippy::type_complexity)]
+async fn load_metrics(pool: &SqlitePool) -> Result<Vec<MetricsRow>, String> {
+    let rows: Vec<(
+        i64,
+        String,
+        i64,
+        i64,
+        String,
+        Option<String>,
+        Option<f64>,
+        i64,
+        f64,
+        Option<String>,
+        String,
+    )> = sqlx::query_as(
+        "SELECT id, created_at, item_count, demand_total, status, error_code, utilization,
+                sheets, duration_secs, preset, engine_version
+         FROM nesting_metrics ORDER BY id DESC LIMIT ?",
+    )
+    .bind(METRICS_ROWS)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load nesting metrics: {}", e))?;
+    Ok(rows
+        .into_iter()
+        .map(|row| MetricsRow {
+            id: row.0,
+            created_at: row.1,
+            item_count: row.2,
+            demand_total: row.3,
+            status: row.4,
+            error_code: row.5,
+            utilization: row.6,
+            sheets: row.7,
+            duration_secs: row.8,
+            preset: row.9,
+            engine_version: row.10,
+        })
+        .collect())
+}
+
+/// Remove what the privacy excludes from a database snapshot
+///
+/// Secrets in the settings are always redacted. The snapshot is vacuumed
+/// afterwards so no removed value survives in free pages.
+///
+/// # Returns
+/// Tables emptied because their rows cannot be filtered field by field
+async fn scrub_database(snapshot: &Path, redactor: &Redactor) -> Result<Vec<String>, String> {
+    let pool = migrations::open_pool(snapshot, false).await?;
+    let result = scrub_pool(&pool, redactor).await;
+    pool.close().await;
+    result
+}
+
+async fn scrub_pool(pool: &SqlitePool, redactor: &Redactor) -> Result<Vec<String>, String> {
+    let scrub_err = |e: sqlx::Error| format!("Failed to scrub database snapshot: {}", e);
+    let privacy = redactor.privacy;
+
+    let mut statements = Vec::new();
+    if !privacy.include_customer_names {
+        statements.extend_from_slice(NAME_SCRUB_SQL);
+    }
+    if !privacy.include_prices {
+        statements.extend_from_slice(PRICE_SCRUB_SQL);
+    }
+    for sql in statements {
+        sqlx::query(sql).execute(pool).await.map_err(scrub_err)?;
+    }
+
+    let mut emptied = Vec::new();
+    if !privacy.include_customer_names || !privacy.include_prices {
+        for table in SCRUBBED_TABLES {
+            sqlx::query(&format!("DELETE FROM \"{}\"", table))
+                .execute(pool)
+                .await
+                .map_err(scrub_err)?;
+            emptied.push(table.to_string());
+        }
+    }
+
+    for (key, value) in redactor.settings(load_settings(pool).await?) {
+        sqlx::query("UPDATE settings SET value = ? WHERE key = ?")
+            .bind(value)
+            .bind(key)
+            .execute(pool)
+            .await
+            .map_err(scrub_err)?;
+    }
+    sqlx::query("VACUUM")
+        .execute(pool)
+        .await
+        .map_err(scrub_err)?;
+    Ok(emptied)
+}
+
+/// Newest log files: the active log file and those in the app log folder
+fn recent_log_files(app_handle: &AppHandle) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = logging::log_file().into_iter().collect();
+    if let Ok(dir) = app_handle.path().app_log_dir() {
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            files.extend(
+                entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "log")),
+            );
+        }
+    }
+    files.sort();
+    files.dedup();
+
+    let modified = |path: &PathBuf| {
+        std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .unwrap_or(std::time::UNIX_EPOCH)
+    };
+    files.sort_by_key(|path| std::cmp::Reverse(modified(path)));
+    files.truncate(MAX_LOG_FILES);
+    files
+}
+
+/// Last `max_bytes` of a log file
+fn read_log_tail(path: &Path, max_bytes: u64) -> Result<String, String> {
+    let read_err = |e: std::io::Error| format!("Failed to read {}: {}", path.display(), e);
+    let mut file = File::open(path).map_err(read_err)?;
+    let len = file.metadata().map_err(read_err)?.len();
+    let mut tail = Vec::new();
+    if len > max_bytes {
+        file.seek(SeekFrom::Start(len - max_bytes))
+            .map_err(read_err)?;
+    }
+    file.read_to_end(&mut tail).map_err(read_err)?;
+    let text = String::from_utf8_lossy(&tail);
+    // Drop the cut-off first line
+    Ok(match text.find('\n') {
+        Some(newline) if len > max_bytes => text[newline + 1..].to_string(),
+        _ => text.into_owned(),
+    })
+}
+
+fn json_entry(
+    name: &str,
+    value: &impl Serialize,
+    redactor: &Redactor,
+) -> Result<PendingEntry, String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", name, e))?;
+    Ok(PendingEntry::Text {
+        name: name.to_string(),
+        content: redactor.text(&json),
+    })
+}
+
+/// Collect logs, diagnostics, metrics, settings and the last failed run
+/// into a zip file with a manifest
+///
+/// Secrets are never included, customer names and prices only when
+/// `privacy` says so. Data that cannot be collected (e.g. with a broken
+/// database) is listed as excluded instead of failing the bundle, unless
+/// it is needed to filter customer names.
+#[tauri::command]
+pub async fn create_support_bundle(
+    app_handle: AppHandle,
+    dest_zip: String,
+    include_db: bool,
+    privacy: Option<BundlePrivacy>,
+) -> Result<SupportBundle, String> {
+    let privacy = privacy.unwrap_or_default();
+    let pool = db::sqlite_pool(&app_handle).await;
+    let customer_names = match &pool {
+        _ if privacy.include_customer_names => Vec::new(),
+        Ok(pool) => load_customer_names(pool).await?,
+        Err(e) => {
+            return Err(format!(
+                "Customer names are needed to filter the bundle but the database is unavailable ({}), include customer names or repair the database first",
+                e
+            ))
+        }
+    };
+    let redactor = Redactor::new(privacy, &customer_names)?;
+
+    let mut entries = Vec::new();
+    let mut excluded = Vec::new();
+    let mut exclude = |name: &str, reason: String| {
+        excluded.push(ExcludedEntry {
+            name: name.to_string(),
+            reason,
+        })
+    };
+
+    let schema_version = match &pool {
+        Ok(pool) => db::schema_version(pool).await.ok(),
+        Err(_) => None,
+    };
+    let environment = EnvironmentInfo {
+        app_version: app_handle.package_info().version.to_string(),
+        engine_version: ENGINE_VERSION,
+        schema_version,
+        latest_schema_version: db::latest_schema_version(),
+        os: std::env::consts::OS,
+        os_family: std::env::consts::FAMILY,
+        arch: std::env::consts::ARCH,
+        cpu_count: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        app_data_dir: workspace::app_data_dir(&app_handle)
+            .ok()
+            .map(|dir| dir.to_string_lossy().into_owned()),
+    };
+    entries.push(json_entry("environment.json", &environment, &redactor)?);
+
+    match app_handle.state::<StartupCheck>().latest() {
+        Some(report) => entries.push(json_entry("startup_report.json", &report, &redactor)?),
+        None => exclude(
+            "startup_report.json",
+            "the startup check has not run".to_string(),
+        ),
+    }
+
+    match &pool {
+        Ok(pool) => {
+            match load_settings(pool).await {
+                Ok(settings) => entries.push(json_entry(
+                    "settings.json",
+                    &redactor.settings(settings),
+                    &redactor,
+                )?),
+                Err(e) => exclude("settings.json", e),
+            }
+            match load_metrics(pool).await {
+                Ok(rows) => entries.push(json_entry("nesting_metrics.json", &rows, &redactor)?),
+                Err(e) => exclude("nesting_metrics.json", e),
+            }
+        }
+        Err(e) => {
+            exclude("settings.json", e.clone());
+            exclude("nesting_metrics.json", e.clone());
+        }
+    }
+
+    let failed_run = workspace::app_data_dir(&app_handle)
+        .and_then(|dir| failed_run_entry(&dir.join(FAILED_RUN_FILE), &redactor));
+    match failed_run {
+        Ok(Some(content)) => entries.push(PendingEntry::Text {
+            name: "last_failed_run.json".to_string(),
+            content,
+        }),
+        Ok(None) => exclude(
+            "last_failed_run.json",
+            "no failed nesting run recorded".to_string(),
+        ),
+        Err(e) => exclude("last_failed_run.json", e),
+    }
+
+    for path in recent_log_files(&app_handle) {
+        let file_name = path
+            .file_name()
+            .map_or_else(|| "app.log".into(), |name| name.to_string_lossy());
+        let name = format!("{}/{}", LOGS_PREFIX, file_name);
+        match read_log_tail(&path, MAX_LOG_BYTES) {
+            Ok(text) => entries.push(PendingEntry::Text {
+                name,
+                content: redactor.text(&text),
+            }),
+            Err(e) => exclude(&name, e),
+        }
+    }
+
+    let mut snapshot_path = None;
+    if include_db {
+        match &pool {
+            Ok(pool) => {
+                let path = std::env::temp_dir().join(format!(
+                    "smart_cut_quote_support_{}.db",
+                    uuid::Uuid::new_v4()
+                ));
+                let scrubbed = match db::backup_database(pool, &path).await {
+                    Ok(()) => scrub_database(&path, &redactor).await,
+                    Err(e) => Err(e),
+                };
+                match scrubbed {
+                    Ok(emptied) => {
+                        for table in emptied {
+                            exclude(
+                                &format!("{} ({})", DATABASE_ENTRY, table),
+                                "table emptied, its rows hold free-form JSON or file paths"
+                                    .to_string(),
+                            );
+                        }
+                        entries.push(PendingEntry::File {
+                            name: DATABASE_ENTRY.to_string(),
+                            path: path.clone(),
+                        });
+                    }
+                    // Never ship a snapshot that could not be scrubbed
+                    Err(e) => exclude(DATABASE_ENTRY, e),
+                }
+                snapshot_path = Some(path);
+            }
+            Err(e) => exclude(DATABASE_ENTRY, e.clone()),
+        }
+    }
+
+    let (entries, over_cap) = fit_to_cap(entries, MAX_BUNDLE_BYTES - MANIFEST_RESERVE_BYTES);
+    excluded.extend(over_cap);
+    let manifest = BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        app_version: environment.app_version.clone(),
+        engine_version: ENGINE_VERSION.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        privacy,
+        size_cap_bytes: MAX_BUNDLE_BYTES,
+        entries: entries
+            .iter()
+            .map(|entry| BundleEntry {
+                name: entry.name().to_string(),
+                size_bytes: entry.size(),
+            })
+            .collect(),
+        excluded,
+    };
+
+    let zip_path = PathBuf::from(&dest_zip);
+    let bundle_manifest = manifest.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        write_bundle(&zip_path, &entries, &bundle_manifest)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+    .and_then(|result| result);
+    if let Some(path) = snapshot_path {
+        let _ = std::fs::remove_file(path);
+    }
+    if result.is_err() {
+        let _ = std::fs::remove_file(&dest_zip);
+    }
+    result?;
+
+    println!(
+        "🩺 Support bundle written to {} ({} entries, {} excluded)",
+        dest_zip,
+        manifest.entries.len(),
+        manifest.excluded.len()
+    );
+    Ok(SupportBundle {
+        path: dest_zip,
+        manifest,
+    })
+}
+
+fn write_bundle(
+    dest_zip: &Path,
+    entries: &[PendingEntry],
+    manifest: &BundleManifest,
+) -> Result<(), String> {
+    let file = File::create(dest_zip)
+        .map_err(|e| format!("Failed to create bundle '{}': {}", dest_zip.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let zip_err = |e: zip::result::ZipError| format!("Failed to write bundle: {}", e);
+    let io_err = |e: std::io::Error| format!("Failed to write bundle: {}", e);
+
+    let manifest_json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    zip.start_file(MANIFEST_ENTRY, options).map_err(zip_err)?;
+    zip.write_all(manifest_json.as_bytes()).map_err(io_err)?;
+
+    for entry in entries {
+        zip.start_file(entry.name(), options).map_err(zip_err)?;
+        match entry {
+            PendingEntry::Text { content, .. } => {
+                zip.write_all(content.as_bytes()).map_err(io_err)?
+            }
+            PendingEntry::File { path, .. } => {
+                let mut source = File::open(path)
+                    .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+                std::io::copy(&mut source, &mut zip).map_err(io_err)?;
+            }
+        }
+    }
+
+    zip.finish().map_err(zip_err)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_support::{migrated_pool, test_dir};
+    use tauri::async_runtime::block_on;
+
+    fn redactor(privacy: BundlePrivacy) -> Redactor {
+        let names = ["Acme", "Acme Steel", "Nguyễn Văn An", "Co"].map(String::from);
+        Redactor::new(privacy, &names).unwrap()
+    }
+
+    #[test]
+    fn test_customer_names_and_emails_are_replaced() {
+        let text = redactor(BundlePrivacy::default())
+            .text("Quote for ACME STEEL (acme) sent to an@example.com.vn by nguyễn văn an, Co-op");
+        assert_eq!(
+            text,
+            "Quote for [customer] ([customer]) sent to [customer] by [customer], Co-op"
+        );
+
+        let kept = redactor(BundlePrivacy {
+            include_customer_names: true,
+            ..Default::default()
+        });
+        assert_eq!(kept.text("Acme Steel"), "Acme Steel");
+    }
+
+    #[test]
+    fn test_settings_lose_secrets_always_and_prices_unless_included() {
+        let settings = || {
+            vec![
+                ("webhook_secret".to_string(), "s3cr3t".to_string()),
+                ("dashboard_server_token".to_string(), String::new()),
+                ("default_price_markup".to_string(), "15".to_string()),
+                ("minimum_order_amount".to_string(), "50".to_string()),
+                ("display_units".to_string(), "mm".to_string()),
+            ]
+        };
+
+        let filtered = redactor(BundlePrivacy::default()).settings(settings());
+        assert_eq!(filtered["webhook_secret"], REDACTED);
+        assert_eq!(filtered["dashboard_server_token"], "");
+        assert_eq!(filtered["default_price_markup"], REDACTED);
+        assert_eq!(filtered["minimum_order_amount"], REDACTED);
+        assert_eq!(filtered["display_units"], "mm");
+
+        let with_prices = redactor(BundlePrivacy {
+            include_prices: true,
+            include_customer_names: true,
+            ..Default::default()
+        })
+        .settings(settings());
+        assert_eq!(with_prices["webhook_secret"], REDACTED);
+        assert_eq!(with_prices["default_price_markup"], "15");
+    }
+
+    fn failed_instance() -> Value {
+        serde_json::json!({
+            "name": "Q-0042 Acme Steel",
+            "strip_height": 1500.0,
+            "items": [{
+                "id": 3,
+                "demand": 2,
+                "label": "Bracket for Acme",
+                "dxf": "C:\\Customers\\Acme\\bracket.dxf",
+                "metadata": {
+                    "part_number": "BR-1",
+                    "customer_name": "Someone",
+                    "unit_price": 12.5,
+                    "extra_separation": 2.0,
+                    "notes": "call acme before cutting"
+                },
+                "shape": { "type": "simple_polygon", "data": [[10.0, 5.0], [40.0, 5.0], [25.0, 30.0]] },
+                "bend_lines": [[[10.0, 5.0], [40.0, 5.0]]]
+            }]
+        })
+    }
+
+    #[test]
+    fn test_instance_loses_names_and_prices() {
+        let mut instance = failed_instance();
+        redactor(BundlePrivacy::default()).instance(&mut instance);
+        let item = &instance["items"][0];
+        assert_eq!(instance["name"], "instance");
+        assert!(item.get("label").is_none());
+        assert_eq!(item["dxf"], "item_3.dxf");
+        assert_eq!(item["metadata"]["customer_name"], REDACTED);
+        assert_eq!(item["metadata"]["unit_price"], REDACTED);
+        assert_eq!(item["metadata"]["part_number"], "BR-1");
+        assert_eq!(item["metadata"]["extra_separation"], 2.0);
+        assert_eq!(item["metadata"]["notes"], "call [customer] before cutting");
+        // Geometry is kept unless excluded
+        assert_eq!(item["shape"], failed_instance()["items"][0]["shape"]);
+
+        let mut everything = failed_instance();
+        redactor(BundlePrivacy {
+            include_customer_names: true,
+            include_prices: true,
+            exclude_geometry: false,
+        })
+        .instance(&mut everything);
+        assert_eq!(everything, failed_instance());
+    }
+
+    #[test]
+    fn test_excluded_geometry_becomes_bounding_rectangles() {
+        let mut instance = failed_instance();
+        redactor(BundlePrivacy {
+            exclude_geometry: true,
+            ..Default::default()
+        })
+        .instance(&mut instance);
+        let item = &instance["items"][0];
+        assert_eq!(
+            item["shape"],
+            serde_json::json!({
+                "type": "rectangle",
+                "data": { "x_min": 10.0, "y_min": 5.0, "width": 30.0, "height": 25.0 }
+            })
+        );
+        assert!(item.get("bend_lines").is_none());
+    }
+
+    #[test]
+    fn test_entries_over_the_cap_are_excluded() {
+        let text = |name: &str, size: usize| PendingEntry::Text {
+            name: name.to_string(),
+            content: "x".repeat(size),
+        };
+        let (kept, excluded) = fit_to_cap(
+            vec![
+                text("environment.json", 40),
+                text("logs/app.log", 100),
+                text("settings.json", 50),
+            ],
+            100,
+        );
+        let names: Vec<&str> = kept.iter().map(PendingEntry::name).collect();
+        assert_eq!(names, vec!["environment.json", "settings.json"]);
+        assert_eq!(excluded.len(), 1);
+        assert_eq!(excluded[0].name, "logs/app.log");
+    }
+
+    #[test]
+    fn test_failed_run_is_kept_and_filtered() {
+        let dir = test_dir("support-bundle", "failed-run");
+        let path = dir.join(FAILED_RUN_FILE);
+        let redactor = redactor(BundlePrivacy::default());
+        assert_eq!(failed_run_entry(&path, &redactor).unwrap(), None);
+
+        write_failed_run(&path, &failed_instance().to_string(), "Acme part too large").unwrap();
+        let entry = failed_run_entry(&path, &redactor).unwrap().unwrap();
+        let run: Value = serde_json::from_str(&entry).unwrap();
+        assert_eq!(run["error"], "[customer] part too large");
+        assert_eq!(run["instance"]["name"], "instance");
+        assert!(!entry.to_lowercase().contains("acme"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_log_tail_starts_at_a_full_line() {
+        let dir = test_dir("support-bundle", "log-tail");
+        let path = dir.join("app.log");
+        std::fs::write(&path, "first line\nsecond line\nthird\n").unwrap();
+        assert_eq!(read_log_tail(&path, 14).unwrap(), "third\n");
+        assert_eq!(
+            read_log_tail(&path, 1024).unwrap(),
+            "first line\nsecond line\nthird\n"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_database_snapshot_is_scrubbed() {
+        let dir = test_dir("support-bundle", "scrub");
+        block_on(async {
+            let pool = migrated_pool(&dir).await;
+            sqlx::raw_sql(
+                "INSERT INTO clients (id, company_name, email) VALUES ('c1', 'Acme Steel', 'buy@acme.vn');
+                 INSERT INTO quotes (id, quote_number, client_id, total, notes, data)
+                 VALUES ('q1', 'Q-1', 'c1', 1234.5, 'Acme rush order', '{}');
+                 INSERT INTO quote_drafts (id, draft_json, size_bytes) VALUES ('s1', '{}', 2);
+                 UPDATE settings SET value = 's3cr3t' WHERE key = 'webhook_secret';",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+            let emptied = scrub_pool(&pool, &redactor(BundlePrivacy::default()))
+                .await
+                .unwrap();
+            assert!(emptied.contains(&"quote_drafts".to_string()));
+
+            let (company, email): (String, Option<String>) =
+                sqlx::query_as("SELECT company_name, email FROM clients")
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            assert!(!company.contains("Acme"));
+            assert_eq!(email, None);
+            let (total, notes): (f64, Option<String>) =
+                sqlx::query_as("SELECT total, notes FROM quotes")
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(total, 0.0);
+            assert_eq!(notes, None);
+            let (drafts,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM quote_drafts")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(drafts, 0);
+            let (secret,): (String,) =
+                sqlx::query_as("SELECT value FROM settings WHERE key = 'webhook_secret'")
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(secret, REDACTED);
+            pool.close().await;
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,339 @@
+//! Running the bundled command line tools
+//!
+//! dxf-converter.exe and sparrow-cli.exe run as separate processes. This
+//! module finds them, runs them with a time limit and turns a failed run
+//! into a `ToolFailed` error saying what happened: the exit code, the
+//! Windows crash code or Unix signal, and the start of stderr.
+//!
+//! The environment variables `SMART_CUT_DXF_CONVERTER` and
+//! `SMART_CUT_SPARROW_CLI` point the commands at another binary, e.g. a
+//! debug build of the converter at a customer site, or the mock tool of
+//! the tests.
+
+use crate::commands::response::{CommandError, ErrorCode};
+use crate::commands::startup_check::resource_path;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How often a running tool is checked for exit, cancellation and time limit
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Bytes of stdout and stderr kept, the rest is read and dropped
+const MAX_CAPTURED_BYTES: usize = 64 * 1024;
+
+/// Characters of stderr quoted in an error message
+const MAX_ERROR_DETAIL_CHARS: usize = 2000;
+
+/// Warnings taken from the stderr of a successful run
+const MAX_WARNINGS: usize = 20;
+
+/// Windows reports crashes as NTSTATUS error codes (0xC0000000 and up)
+const NTSTATUS_ERROR: u32 = 0xC000_0000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    DxfConverter,
+    SparrowCli,
+}
+
+impl Tool {
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::DxfConverter => "dxf-converter",
+            Tool::SparrowCli => "sparrow-cli",
+        }
+    }
+
+    /// Path below the resource directory
+    fn resource(self) -> &'static str {
+        match self {
+            Tool::DxfConverter => "binaries/dxf-converter.exe",
+            Tool::SparrowCli => "binaries/sparrow-cli.exe",
+        }
+    }
+
+    /// Environment variable overriding the bundled binary
+    pub fn override_var(self) -> &'static str {
+        match self {
+            Tool::DxfConverter => "SMART_CUT_DXF_CONVERTER",
+            Tool::SparrowCli => "SMART_CUT_SPARROW_CLI",
+        }
+    }
+}
+
+/// Path of a tool: the override if set, else the bundled binary
+///
+/// The returned path may not exist.
+pub(crate) fn tool_path(app_handle: &tauri::AppHandle, tool: Tool) -> Result<PathBuf, String> {
+    match std::env::var_os(tool.override_var()).filter(|path| !path.is_empty()) {
+        Some(path) => {
+            println!("🔧 {} overridden by {}", tool.name(), tool.override_var());
+            Ok(PathBuf::from(path))
+        }
+        None => resource_path(app_handle, tool.resource()),
+    }
+}
+
+/// Why a tool run failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolFailure {
+    /// Ended with a nonzero exit code of its own
+    ExitCode(i32),
+    /// Crashed on Windows, with the NTSTATUS code as exit code
+    /// (e.g. 0xC0000005 for an access violation)
+    Crashed(u32),
+    /// Killed by a signal on Unix
+    Signal(i32),
+    /// Still running at the time limit, and killed
+    TimedOut(Duration),
+}
+
+impl ToolFailure {
+    /// Classify an unsuccessful exit status
+    pub fn of(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+        #[cfg(not(unix))]
+        let signal = None;
+        Self::from_parts(status.code(), signal)
+    }
+
+    fn from_parts(code: Option<i32>, signal: Option<i32>) -> Self {
+        match (code, signal) {
+            (Some(code), _) if code as u32 >= NTSTATUS_ERROR => ToolFailure::Crashed(code as u32),
+            (Some(code), _) => ToolFailure::ExitCode(code),
+            (None, Some(signal)) => ToolFailure::Signal(signal),
+            (None, None) => ToolFailure::ExitCode(-1),
+        }
+    }
+
+    fn describe(self) -> String {
+        match self {
+            ToolFailure::ExitCode(code) => format!("exited with code {}", code),
+            ToolFailure::Crashed(code) => match crash_name(code) {
+                Some(name) => format!("crashed with {:#010X} ({})", code, name),
+                None => format!("crashed with {:#010X}", code),
+            },
+            ToolFailure::Signal(signal) => format!("was killed by signal {}", signal),
+            ToolFailure::TimedOut(limit) => format!(
+                "did not finish within {}s and was stopped",
+                limit.as_secs_f64().ceil()
+            ),
+        }
+    }
+}
+
+/// Names of the NTSTATUS codes seen in customer reports
+fn crash_name(code: u32) -> Option<&'static str> {
+    match code {
+        0xC000_0005 => Some("access violation"),
+        0xC000_00FD => Some("stack overflow"),
+        0xC000_0017 | 0xC000_009A => Some("out of memory"),
+        0xC000_0409 => Some("stack buffer overrun"),
+        0xC000_0135 => Some("missing DLL"),
+        _ => None,
+    }
+}
+
+/// Output of a finished run, stdout and stderr cut at `MAX_CAPTURED_BYTES`
+#[derive(Debug)]
+pub(crate) struct ToolOutput {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ToolOutput {
+    /// Warnings of a successful run, the error of a failed one
+    pub fn check(self, tool: Tool) -> Result<Vec<String>, CommandError> {
+        if self.status.success() {
+            Ok(self
+                .stderr
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .take(MAX_WARNINGS)
+                .map(|line| format!("{}: {}", tool.name(), line))
+                .collect())
+        } else {
+            Err(tool_error(tool, ToolFailure::of(self.status), &self.stderr))
+        }
+    }
+}
+
+/// `ToolFailed` error for a failed run, quoting the start of stderr
+pub(crate) fn tool_error(tool: Tool, failure: ToolFailure, stderr: &str) -> CommandError {
+    let mut message = format!("{} {}", tool.name(), failure.describe());
+    let detail = stderr.trim();
+    if !detail.is_empty() {
+        message.push_str(": ");
+        message.extend(detail.chars().take(MAX_ERROR_DETAIL_CHARS));
+        if detail.chars().nth(MAX_ERROR_DETAIL_CHARS).is_some() {
+            message.push_str(" …");
+        }
+    }
+    CommandError::new(ErrorCode::ToolFailed, message)
+}
+
+/// Run a tool, killing it at `time_limit` or as soon as `cancel` is set
+///
+/// Returns None when it was cancelled. A run over the time limit is a
+/// `TimedOut` error; a finished run is returned whatever its exit
+/// status, see `ToolOutput::check`.
+pub(crate) fn run_tool(
+    tool: Tool,
+    mut cmd: Command,
+    time_limit: Duration,
+    cancel: Option<&AtomicBool>,
+) -> Result<Option<ToolOutput>, CommandError> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to execute {}: {}", tool.name(), e))?;
+
+    // Drained on their own threads so a chatty process never blocks on a full pipe
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let started = Instant::now();
+    loop {
+        let finished = child
+            .try_wait()
+            .map_err(|e| format!("Failed to wait for {}: {}", tool.name(), e))?;
+        if let Some(status) = finished {
+            return Ok(Some(ToolOutput {
+                status,
+                stdout: stdout.join().unwrap_or_default(),
+                stderr: stderr.join().unwrap_or_default(),
+            }));
+        }
+        if cancel.is_some_and(|cancel| cancel.load(Ordering::SeqCst)) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        if started.elapsed() > time_limit {
+            let _ = child.kill();
+            let _ = child.wait();
+            // A grandchild holding the pipes open must not block the error
+            let drained = Instant::now();
+            while !stderr.is_finished() && drained.elapsed() < POLL_INTERVAL * 4 {
+                std::thread::sleep(POLL_INTERVAL / 5);
+            }
+            let stderr = if stderr.is_finished() {
+                stderr.join().unwrap_or_default()
+            } else {
+                String::new()
+            };
+            return Err(tool_error(tool, ToolFailure::TimedOut(time_limit), &stderr));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Read a pipe to the end, keeping the first `MAX_CAPTURED_BYTES`
+fn drain(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut kept = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let mut buffer = [0u8; 8192];
+            while let Ok(read) = pipe.read(&mut buffer) {
+                if read == 0 {
+                    break;
+                }
+                let room = MAX_CAPTURED_BYTES.saturating_sub(kept.len());
+                kept.extend_from_slice(&buffer[..read.min(room)]);
+            }
+        }
+        // Garbled output (other code pages, binary) is kept readable
+        String::from_utf8_lossy(&kept).into_owned()
+    })
+}
+
+/// Mock of the external tools for the failure tests
+///
+/// Writes a shell script acting out one scenario; the commands are
+/// pointed at it instead of the real binary.
+#[cfg(all(test, unix))]
+pub(crate) mod mock {
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::{Path, PathBuf};
+
+    #[derive(Debug, Clone, Copy)]
+    pub enum Scenario {
+        /// Error message and exit code 3
+        NonzeroExit,
+        /// Killed by SIGSEGV
+        Crash,
+        /// 5 MB of invalid UTF-8 on stderr, then exit code 1
+        HugeStderr,
+        /// Some output, then no progress for a minute
+        SlowThenHang,
+        /// Writes the `--output` file and warns on stderr
+        WarningsOnly,
+    }
+
+    const OUTPUT: &str = r#"{"name":"mock","strip_height":1000,"items":[]}"#;
+
+    /// Write the mock tool for `scenario` into `dir`
+    pub fn mock_tool(dir: &Path, scenario: Scenario) -> PathBuf {
+        let body = match scenario {
+            Scenario::NonzeroExit => {
+                "echo 'Error: unsupported entity SPLINE' >&2\nexit 3".to_string()
+            }
+            Scenario::Crash => "echo 'reading entities' >&2\nkill -SEGV $$".to_string(),
+            Scenario::HugeStderr => {
+                "head -c 5000000 /dev/zero | tr '\\000' '\\377' >&2\nexit 1".to_string()
+            }
+            Scenario::SlowThenHang => {
+                "echo 'loading'\nsleep 0.2\necho 'nesting'\nexec sleep 60".to_string()
+            }
+            Scenario::WarningsOnly => format!(
+                "while [ $# -gt 0 ]; do\n  \
+                   if [ \"$1\" = --output ]; then printf '%s' '{}' > \"$2\"; fi\n  \
+                   shift\n\
+                 done\n\
+                 echo 'Warning: open contour closed on layer 0' >&2\n\
+                 echo 'Warning: 2 duplicate entities removed' >&2",
+                OUTPUT
+            ),
+        };
+        let path = dir.join(format!("mock-{:?}.sh", scenario).to_lowercase());
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_classification() {
+        assert_eq!(
+            ToolFailure::from_parts(Some(3), None),
+            ToolFailure::ExitCode(3)
+        );
+        // "converter exited with code -1073741819"
+        let crash = ToolFailure::from_parts(Some(-1073741819), None);
+        assert_eq!(crash, ToolFailure::Crashed(0xC000_0005));
+        assert_eq!(
+            crash.describe(),
+            "crashed with 0xC0000005 (access violation)"
+        );
+        assert_eq!(
+            ToolFailure::from_parts(None, Some(9)),
+            ToolFailure::Signal(9)
+        );
+
+        let error = tool_error(Tool::DxfConverter, ToolFailure::ExitCode(2), "  bad file\n");
+        assert_eq!(error.code, ErrorCode::ToolFailed);
+        assert_eq!(error.message, "dxf-converter exited with code 2: bad file");
+    }
+}
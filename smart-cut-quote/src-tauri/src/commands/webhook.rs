@@ -0,0 +1,427 @@
+//! Outbound nesting notifications (webhook)
+//!
+//! When the `webhook_url` setting is filled in, the outcome of every
+//! integrated nesting run is POSTed to it as a compact JSON payload, with
+//! the `webhook_secret` setting in the `X-Webhook-Secret` header. Delivery
+//! runs on its own task after the result went back to the UI: network
+//! errors are retried with exponential backoff and only ever end up in the
+//! `webhook_deliveries` log. An empty URL (the default) turns it all off.
+
+use crate::commands::nesting_metrics::NestingRunMetrics;
+use crate::db;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+/// Setting holding the endpoint; notifications are off while it is empty
+const WEBHOOK_URL_SETTING: &str = "webhook_url";
+
+/// Setting holding the shared secret sent with every request
+const WEBHOOK_SECRET_SETTING: &str = "webhook_secret";
+
+/// Header carrying the shared secret
+pub const SECRET_HEADER: &str = "X-Webhook-Secret";
+
+/// Attempts per notification, the first one included
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// Wait before the first retry, doubled for every further one
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+
+/// Longest wait between two attempts
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Time limit of a single request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body POSTed to the endpoint
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct WebhookPayload {
+    /// `nesting.completed`, `nesting.failed` or `webhook.test`
+    pub event: &'static str,
+    pub quote_id: Option<String>,
+    pub job_id: Option<String>,
+    /// `complete`, `partial` or `failed`
+    pub status: &'static str,
+    /// 0.0 - 1.0, None for failed runs
+    pub utilization: Option<f64>,
+    /// Strips produced (0 for failed runs)
+    pub sheets: i64,
+    /// Stable error code of a failed run
+    pub error_code: Option<&'static str>,
+    /// Identifies this notification in the app's delivery log
+    pub link_token: String,
+    /// UTC `YYYY-MM-DD HH:MM:SS`
+    pub sent_at: String,
+}
+
+impl WebhookPayload {
+    /// Notification for a finished nesting run
+    pub fn for_run(
+        metrics: &NestingRunMetrics,
+        job_id: Option<String>,
+        quote_id: Option<String>,
+    ) -> Self {
+        Self {
+            event: if metrics.status == "failed" {
+                "nesting.failed"
+            } else {
+                "nesting.completed"
+            },
+            quote_id,
+            job_id,
+            status: metrics.status,
+            utilization: metrics.utilization,
+            sheets: metrics.sheets,
+            error_code: metrics.error_code,
+            link_token: uuid::Uuid::new_v4().to_string(),
+            sent_at: now(),
+        }
+    }
+
+    /// Sample notification sent by `test_webhook`
+    fn test() -> Self {
+        Self {
+            event: "webhook.test",
+            quote_id: None,
+            job_id: None,
+            status: "complete",
+            utilization: None,
+            sheets: 0,
+            error_code: None,
+            link_token: uuid::Uuid::new_v4().to_string(),
+            sent_at: now(),
+        }
+    }
+}
+
+/// Endpoint from the settings
+#[derive(Debug, Clone)]
+struct WebhookTarget {
+    url: String,
+    secret: String,
+}
+
+/// Outcome of one request
+#[derive(Debug, Clone, Default)]
+struct Attempt {
+    http_status: Option<u16>,
+    error: Option<String>,
+}
+
+impl Attempt {
+    fn delivered(&self) -> bool {
+        self.http_status
+            .is_some_and(|status| (200..300).contains(&status))
+    }
+}
+
+/// Result of `test_webhook`
+#[derive(Serialize, Debug)]
+pub struct WebhookTestResult {
+    pub ok: bool,
+    /// None when no response arrived
+    pub http_status: Option<u16>,
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// Wait before retrying after the given (1-based) failed attempt
+pub fn retry_delay(attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    RETRY_BASE_DELAY.saturating_mul(factor).min(RETRY_MAX_DELAY)
+}
+
+/// Whether a failed attempt is worth repeating
+///
+/// Network errors (no status), timeouts, rate limits and server errors
+/// are; other client errors mean the request itself is rejected.
+pub fn is_retryable(http_status: Option<u16>) -> bool {
+    match http_status {
+        None => true,
+        Some(status) => status == 408 || status == 429 || status >= 500,
+    }
+}
+
+/// Endpoint URLs must be plain http(s)
+pub fn validate_url(url: &str) -> Result<(), String> {
+    let url = url.trim();
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(format!(
+            "Webhook URL must start with http:// or https://, got '{}'",
+            url
+        ));
+    }
+    Ok(())
+}
+
+/// Notify the configured endpoint about a finished nesting run
+///
+/// Returns immediately; loading the settings, the requests and the log
+/// run on their own task, and every error is only logged, so the run's
+/// result is never affected.
+pub fn notify_nesting_finished(
+    app_handle: &AppHandle,
+    metrics: &NestingRunMetrics,
+    job_id: Option<String>,
+    quote_id: Option<String>,
+) {
+    let app_handle = app_handle.clone();
+    let payload = WebhookPayload::for_run(metrics, job_id, quote_id);
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = deliver(&app_handle, payload).await {
+            println!("⚠️ Nesting webhook not delivered: {}", e);
+        }
+    });
+}
+
+async fn deliver(app_handle: &AppHandle, payload: WebhookPayload) -> Result<(), String> {
+    let pool = db::sqlite_pool(app_handle).await?;
+    let Some(target) = load_target(&pool).await? else {
+        return Ok(());
+    };
+    validate_url(&target.url)?;
+
+    let client = http_client()?;
+    let delivery_id = insert_delivery(&pool, &target, &payload).await?;
+    for attempt_number in 1..=MAX_ATTEMPTS {
+        let attempt = post(&client, &target, &payload).await;
+        let delivered = attempt.delivered();
+        let finished =
+            delivered || attempt_number == MAX_ATTEMPTS || !is_retryable(attempt.http_status);
+        let status = match (delivered, finished) {
+            (true, _) => "delivered",
+            (false, true) => "failed",
+            (false, false) => "pending",
+        };
+        update_delivery(&pool, delivery_id, status, attempt_number, &attempt).await?;
+
+        if delivered {
+            println!(
+                "🔔 Webhook {} delivered ({})",
+                payload.event, payload.link_token
+            );
+            return Ok(());
+        }
+        if finished {
+            return Err(format!(
+                "{} after {} attempts",
+                attempt.error.unwrap_or_else(|| "failed".to_string()),
+                attempt_number
+            ));
+        }
+        tokio::time::sleep(retry_delay(attempt_number)).await;
+    }
+    Ok(())
+}
+
+/// Send a test notification to check connectivity
+///
+/// Uses `url`/`secret` when given (to check values before saving them),
+/// the settings otherwise. A single attempt, logged like any delivery.
+#[tauri::command]
+pub async fn test_webhook(
+    app_handle: AppHandle,
+    url: Option<String>,
+    secret: Option<String>,
+) -> Result<WebhookTestResult, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let saved = load_target(&pool).await?;
+    let target = WebhookTarget {
+        url: match url {
+            Some(url) => url.trim().to_string(),
+            None => saved
+                .as_ref()
+                .map(|target| target.url.clone())
+                .ok_or("No webhook URL configured")?,
+        },
+        secret: secret
+            .or_else(|| saved.map(|target| target.secret))
+            .unwrap_or_default(),
+    };
+    validate_url(&target.url)?;
+
+    let payload = WebhookPayload::test();
+    let delivery_id = insert_delivery(&pool, &target, &payload).await?;
+    let started = Instant::now();
+    let attempt = post(&http_client()?, &target, &payload).await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    let status = if attempt.delivered() {
+        "delivered"
+    } else {
+        "failed"
+    };
+    update_delivery(&pool, delivery_id, status, 1, &attempt).await?;
+
+    println!(
+        "🔔 Webhook test: {} in {} ms",
+        attempt
+            .http_status
+            .map(|status| format!("HTTP {}", status))
+            .or_else(|| attempt.error.clone())
+            .unwrap_or_default(),
+        elapsed_ms
+    );
+    Ok(WebhookTestResult {
+        ok: attempt.delivered(),
+        http_status: attempt.http_status,
+        error: attempt.error,
+        elapsed_ms,
+    })
+}
+
+fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("smart-cut-quote/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+async fn post(
+    client: &reqwest::Client,
+    target: &WebhookTarget,
+    payload: &WebhookPayload,
+) -> Attempt {
+    let mut request = client.post(&target.url).json(payload);
+    if !target.secret.is_empty() {
+        request = request.header(SECRET_HEADER, &target.secret);
+    }
+    match request.send().await {
+        Ok(response) => {
+            let status = response.status();
+            Attempt {
+                http_status: Some(status.as_u16()),
+                error: (!status.is_success()).then(|| format!("HTTP {}", status)),
+            }
+        }
+        Err(e) => Attempt {
+            http_status: None,
+            error: Some(format!("Request failed: {}", e)),
+        },
+    }
+}
+
+/// Endpoint and secret, None while no URL is set
+async fn load_target(pool: &SqlitePool) -> Result<Option<WebhookTarget>, String> {
+    let rows: Vec<(String, String)> =
+        sqlx::query_as("SELECT key, value FROM settings WHERE key IN (?, ?)")
+            .bind(WEBHOOK_URL_SETTING)
+            .bind(WEBHOOK_SECRET_SETTING)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to load webhook settings: {}", e))?;
+
+    let value = |key: &str| {
+        rows.iter()
+            .find(|(row_key, _)| row_key == key)
+            .map(|(_, value)| value.trim().to_string())
+            .unwrap_or_default()
+    };
+    let url = value(WEBHOOK_URL_SETTING);
+    if url.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(WebhookTarget {
+        url,
+        secret: value(WEBHOOK_SECRET_SETTING),
+    }))
+}
+
+async fn insert_delivery(
+    pool: &SqlitePool,
+    target: &WebhookTarget,
+    payload: &WebhookPayload,
+) -> Result<i64, String> {
+    let body = serde_json::to_string(payload)
+        .map_err(|e| format!("Failed to serialize webhook payload: {}", e))?;
+    let (id,): (i64,) = sqlx::query_as(
+        "INSERT INTO webhook_deliveries (event, job_id, quote_id, link_token, url, payload)
+         VALUES (?, ?, ?, ?, ?, ?)
+         RETURNING id",
+    )
+    .bind(payload.event)
+    .bind(&payload.job_id)
+    .bind(&payload.quote_id)
+    .bind(&payload.link_token)
+    .bind(&target.url)
+    .bind(body)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to log webhook delivery: {}", e))?;
+    Ok(id)
+}
+
+async fn update_delivery(
+    pool: &SqlitePool,
+    id: i64,
+    status: &str,
+    attempts: u32,
+    attempt: &Attempt,
+) -> Result<(), String> {
+    sqlx::query(
+        "UPDATE webhook_deliveries
+         SET status = ?, attempts = ?, http_status = ?, error = ?, updated_at = datetime('now')
+         WHERE id = ?",
+    )
+    .bind(status)
+    .bind(attempts)
+    .bind(attempt.http_status)
+    .bind(&attempt.error)
+    .bind(id)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to log webhook delivery: {}", e))?;
+    Ok(())
+}
+
+fn now() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_the_cap() {
+        assert_eq!(retry_delay(1), Duration::from_secs(2));
+        assert_eq!(retry_delay(2), Duration::from_secs(4));
+        assert_eq!(retry_delay(4), Duration::from_secs(16));
+        assert_eq!(retry_delay(10), RETRY_MAX_DELAY);
+        assert_eq!(retry_delay(u32::MAX), RETRY_MAX_DELAY);
+    }
+
+    #[test]
+    fn test_only_transient_failures_are_retried() {
+        assert!(is_retryable(None));
+        assert!(is_retryable(Some(503)));
+        assert!(is_retryable(Some(429)));
+        assert!(!is_retryable(Some(401)));
+        assert!(!is_retryable(Some(404)));
+    }
+
+    #[test]
+    fn test_payload_of_failed_run() {
+        let metrics = NestingRunMetrics {
+            item_count: 3,
+            demand_total: 10,
+            status: "failed",
+            error_code: Some("engine_failed"),
+            utilization: None,
+            sheets: 0,
+            duration_secs: 1.5,
+            preset: None,
+        };
+        let payload =
+            WebhookPayload::for_run(&metrics, Some("job-1".to_string()), Some("q-7".to_string()));
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["event"], "nesting.failed");
+        assert_eq!(json["status"], "failed");
+        assert_eq!(json["quote_id"], "q-7");
+        assert_eq!(json["sheets"], 0);
+        assert!(json["utilization"].is_null());
+        assert_eq!(json["link_token"].as_str().unwrap().len(), 36);
+    }
+}
@@ -0,0 +1,551 @@
+use crate::commands::export_naming::{release_export_paths, resolve_export_path, ExportKind};
+use crate::db;
+use crate::nesting_engine::ENGINE_VERSION;
+use crate::workspace::{self, WORKSPACE_DIRS};
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Event emitted while an archive is exported or imported
+pub const ARCHIVE_PROGRESS_EVENT: &str = "workspace-archive-progress";
+
+const ARCHIVE_FORMAT_VERSION: u32 = 1;
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DATABASE_ENTRY: &str = "database/smart_cut_quote.db";
+const FILES_PREFIX: &str = "files";
+
+/// Tables that can be merged into an existing workspace (additive data only)
+const MERGEABLE_TABLES: &[&str] = &["clients", "client_contacts"];
+
+/// Columns ignored when deciding whether a merged row conflicts
+const MERGE_IGNORED_COLUMNS: &[&str] = &["created_at", "updated_at"];
+
+/// Describes the content of a workspace archive
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub engine_version: String,
+    /// Highest migration applied to the archived database
+    pub schema_version: i64,
+    pub created_at: String,
+    /// Workspace files as archive entry names
+    pub files: Vec<String>,
+}
+
+/// Written workspace archive
+#[derive(Serialize, Debug, Clone)]
+pub struct ArchiveExport {
+    pub path: String,
+    #[serde(flatten)]
+    pub manifest: ArchiveManifest,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Replace all data and files with the archive content
+    Replace,
+    /// Only add customers and missing files, report conflicts
+    Merge,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ArchiveProgress {
+    /// "database", "extract", "files" or "done"
+    pub stage: String,
+    pub current: usize,
+    pub total: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImportConflict {
+    /// Table name or workspace file path
+    pub location: String,
+    /// Row ID or file name
+    pub key: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImportReport {
+    pub mode: ImportMode,
+    pub schema_version: i64,
+    /// Rows inserted per table
+    pub rows_imported: BTreeMap<String, u64>,
+    pub files_imported: usize,
+    pub conflicts: Vec<ImportConflict>,
+}
+
+fn emit_progress(app_handle: &AppHandle, stage: &str, current: usize, total: usize) {
+    let _ = app_handle.emit(
+        ARCHIVE_PROGRESS_EVENT,
+        ArchiveProgress {
+            stage: stage.to_string(),
+            current,
+            total,
+        },
+    );
+}
+
+/// Package the database, workspace files and a manifest into a zip archive
+///
+/// The database is copied with `VACUUM INTO`, which produces a consistent
+/// snapshot even while the frontend keeps using it. The archive is named
+/// by the export name template in `dest_dir`.
+#[tauri::command]
+pub async fn export_workspace_archive(
+    app_handle: AppHandle,
+    dest_dir: String,
+) -> Result<ArchiveExport, String> {
+    let pool = db::sqlite_pool(&app_handle).await?;
+    let schema_version = db::schema_version(&pool).await?;
+
+    emit_progress(&app_handle, "database", 0, 1);
+    let snapshot_path = std::env::temp_dir().join(format!(
+        "smart_cut_quote_export_{}.db",
+        uuid::Uuid::new_v4()
+    ));
+    db::backup_database(&pool, &snapshot_path).await?;
+    emit_progress(&app_handle, "database", 1, 1);
+
+    let mut files = Vec::new();
+    for dir_name in WORKSPACE_DIRS {
+        let dir = workspace::workspace_dir(&app_handle, dir_name)?;
+        for relative in workspace::list_files(&dir)? {
+            let entry = format!(
+                "{}/{}/{}",
+                FILES_PREFIX,
+                dir_name,
+                relative.to_string_lossy().replace('\\', "/")
+            );
+            files.push((entry, dir.join(relative)));
+        }
+    }
+
+    let manifest = ArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        app_version: app_handle.package_info().version.to_string(),
+        engine_version: ENGINE_VERSION.to_string(),
+        schema_version,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        files: files.iter().map(|(entry, _)| entry.clone()).collect(),
+    };
+
+    let dest_zip =
+        match resolve_export_path(&app_handle, ExportKind::Archive, None, Path::new(&dest_dir))
+            .await
+        {
+            Ok(path) => path,
+            Err(e) => {
+                let _ = std::fs::remove_file(&snapshot_path);
+                return Err(e);
+            }
+        };
+    let app = app_handle.clone();
+    let archive_manifest = manifest.clone();
+    let snapshot = snapshot_path.clone();
+    let zip_path = dest_zip.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        write_archive(&app, &zip_path, &snapshot, &files, &archive_manifest)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))
+    .and_then(|result| result);
+
+    let _ = std::fs::remove_file(&snapshot_path);
+    if result.is_err() {
+        release_export_paths(std::slice::from_ref(&dest_zip));
+    }
+    result?;
+
+    emit_progress(&app_handle, "done", 1, 1);
+    println!(
+        "📦 Exported workspace archive to {} ({} files, schema v{})",
+        dest_zip.display(),
+        manifest.files.len(),
+        manifest.schema_version
+    );
+    Ok(ArchiveExport {
+        path: dest_zip.to_string_lossy().into_owned(),
+        manifest,
+    })
+}
+
+fn write_archive(
+    app_handle: &AppHandle,
+    dest_zip: &Path,
+    database_path: &Path,
+    files: &[(String, PathBuf)],
+    manifest: &ArchiveManifest,
+) -> Result<(), String> {
+    let file = File::create(dest_zip)
+        .map_err(|e| format!("Failed to create archive '{}': {}", dest_zip.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let zip_err = |e: zip::result::ZipError| format!("Failed to write archive: {}", e);
+    let io_err = |e: std::io::Error| format!("Failed to write archive: {}", e);
+
+    let manifest_json = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    zip.start_file(MANIFEST_ENTRY, options).map_err(zip_err)?;
+    zip.write_all(manifest_json.as_bytes()).map_err(io_err)?;
+
+    zip.start_file(DATABASE_ENTRY, options).map_err(zip_err)?;
+    let mut database = File::open(database_path)
+        .map_err(|e| format!("Failed to open database snapshot: {}", e))?;
+    std::io::copy(&mut database, &mut zip).map_err(io_err)?;
+
+    for (index, (entry, path)) in files.iter().enumerate() {
+        let mut source = File::open(path)
+            .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+        zip.start_file(entry.as_str(), options).map_err(zip_err)?;
+        std::io::copy(&mut source, &mut zip).map_err(io_err)?;
+        emit_progress(app_handle, "files", index + 1, files.len());
+    }
+
+    zip.finish().map_err(zip_err)?;
+    Ok(())
+}
+
+/// Restore a workspace archive created by `export_workspace_archive`
+///
+/// `Replace` overwrites every table and workspace file. `Merge` only adds
+/// customers and missing files; rows or files that exist locally with
+/// different content are kept and listed as conflicts.
+#[tauri::command]
+pub async fn import_workspace_archive(
+    app_handle: AppHandle,
+    src_zip: String,
+    mode: ImportMode,
+) -> Result<ImportReport, String> {
+    let extract_dir = std::env::temp_dir().join(format!(
+        "smart_cut_quote_import_{}",
+        uuid::Uuid::new_v4()
+    ));
+
+    let app = app_handle.clone();
+    let target = extract_dir.clone();
+    let extracted = tauri::async_runtime::spawn_blocking(move || {
+        extract_archive(&app, Path::new(&src_zip), &target)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?;
+
+    let result = match extracted {
+        Ok(manifest) => import_extracted(&app_handle, &extract_dir, &manifest, mode).await,
+        Err(e) => Err(e),
+    };
+
+    let _ = std::fs::remove_dir_all(&extract_dir);
+    let report = result?;
+
+    emit_progress(&app_handle, "done", 1, 1);
+    println!(
+        "📦 Imported workspace archive ({:?}): {} files, {} conflicts",
+        report.mode,
+        report.files_imported,
+        report.conflicts.len()
+    );
+    Ok(report)
+}
+
+/// Validate the manifest and unpack the archive into `target`
+fn extract_archive(
+    app_handle: &AppHandle,
+    src_zip: &Path,
+    target: &Path,
+) -> Result<ArchiveManifest, String> {
+    let file = File::open(src_zip)
+        .map_err(|e| format!("Failed to open archive '{}': {}", src_zip.display(), e))?;
+    let mut zip =
+        zip::ZipArchive::new(file).map_err(|e| format!("Not a valid workspace archive: {}", e))?;
+
+    let mut manifest_json = String::new();
+    zip.by_name(MANIFEST_ENTRY)
+        .map_err(|_| "Not a workspace archive: manifest.json is missing".to_string())?
+        .read_to_string(&mut manifest_json)
+        .map_err(|e| format!("Failed to read manifest: {}", e))?;
+    let manifest: ArchiveManifest = serde_json::from_str(&manifest_json)
+        .map_err(|e| format!("Invalid archive manifest: {}", e))?;
+
+    validate_manifest(&manifest, db::latest_schema_version())?;
+
+    let total = zip.len();
+    for index in 0..total {
+        let mut entry = zip
+            .by_index(index)
+            .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        // enclosed_name() rejects absolute paths and ".." components
+        let Some(relative) = entry.enclosed_name() else {
+            return Err(format!("Archive entry has an unsafe path: {}", entry.name()));
+        };
+
+        let out_path = target.join(relative);
+        if let Some(parent) = out_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+        }
+        let mut out = File::create(&out_path)
+            .map_err(|e| format!("Failed to extract '{}': {}", out_path.display(), e))?;
+        std::io::copy(&mut entry, &mut out)
+            .map_err(|e| format!("Failed to extract '{}': {}", out_path.display(), e))?;
+
+        emit_progress(app_handle, "extract", index + 1, total);
+    }
+
+    Ok(manifest)
+}
+
+/// Reject archives this build cannot read
+fn validate_manifest(manifest: &ArchiveManifest, latest_schema_version: i64) -> Result<(), String> {
+    if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+        return Err(format!(
+            "Archive format v{} is newer than supported v{}. Please update the app first.",
+            manifest.format_version, ARCHIVE_FORMAT_VERSION
+        ));
+    }
+
+    if manifest.schema_version > latest_schema_version {
+        return Err(format!(
+            "Archive was created with database schema v{} (app {}), but this app only supports up to v{}. Please update the app before importing.",
+            manifest.schema_version, manifest.app_version, latest_schema_version
+        ));
+    }
+
+    Ok(())
+}
+
+async fn import_extracted(
+    app_handle: &AppHandle,
+    extract_dir: &Path,
+    manifest: &ArchiveManifest,
+    mode: ImportMode,
+) -> Result<ImportReport, String> {
+    let mut report = ImportReport {
+        mode,
+        schema_version: manifest.schema_version,
+        rows_imported: BTreeMap::new(),
+        files_imported: 0,
+        conflicts: Vec::new(),
+    };
+
+    emit_progress(app_handle, "database", 0, 1);
+    let pool = db::sqlite_pool(app_handle).await?;
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|e| format!("Failed to acquire database connection: {}", e))?;
+
+    // ATTACH is per connection, so all statements run on this one
+    let database_path = extract_dir.join(DATABASE_ENTRY);
+    sqlx::query("ATTACH DATABASE ? AS archive")
+        .bind(database_path.to_string_lossy().to_string())
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to open archived database: {}", e))?;
+
+    let imported = import_tables(&mut conn, mode, &mut report).await;
+    let _ = sqlx::query("DETACH DATABASE archive")
+        .execute(&mut *conn)
+        .await;
+    imported?;
+    emit_progress(app_handle, "database", 1, 1);
+
+    import_files(app_handle, &extract_dir.join(FILES_PREFIX), mode, &mut report)?;
+    Ok(report)
+}
+
+async fn import_tables(
+    conn: &mut SqliteConnection,
+    mode: ImportMode,
+    report: &mut ImportReport,
+) -> Result<(), String> {
+    let db_err = |e: sqlx::Error| format!("Failed to import database: {}", e);
+
+    let tables: Vec<(String,)> = sqlx::query_as(
+        "SELECT name FROM main.sqlite_master
+         WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != '_sqlx_migrations'",
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(db_err)?;
+
+    sqlx::query("BEGIN").execute(&mut *conn).await.map_err(db_err)?;
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *conn)
+        .await
+        .map_err(db_err)?;
+
+    let mut result = Ok(());
+    for (table,) in tables {
+        let merge_only = mode == ImportMode::Merge;
+        if merge_only && !MERGEABLE_TABLES.contains(&table.as_str()) {
+            continue;
+        }
+
+        result = import_table(conn, &table, mode, report).await;
+        if result.is_err() {
+            break;
+        }
+    }
+
+    match result {
+        Ok(()) => {
+            sqlx::query("COMMIT").execute(&mut *conn).await.map_err(db_err)?;
+            Ok(())
+        }
+        Err(e) => {
+            let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+            Err(e)
+        }
+    }
+}
+
+async fn table_columns(
+    conn: &mut SqliteConnection,
+    schema: &str,
+    table: &str,
+) -> Result<Vec<String>, String> {
+    let columns: Vec<(String,)> = sqlx::query_as("SELECT name FROM pragma_table_info(?, ?)")
+        .bind(table)
+        .bind(schema)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| format!("Failed to read columns of '{}': {}", table, e))?;
+
+    Ok(columns.into_iter().map(|(name,)| name).collect())
+}
+
+async fn import_table(
+    conn: &mut SqliteConnection,
+    table: &str,
+    mode: ImportMode,
+    report: &mut ImportReport,
+) -> Result<(), String> {
+    let db_err = |e: sqlx::Error| format!("Failed to import table '{}': {}", table, e);
+
+    // Older archives may lack newer columns: copy only shared columns
+    let archive_columns = table_columns(conn, "archive", table).await?;
+    if archive_columns.is_empty() {
+        return Ok(());
+    }
+    let columns: Vec<String> = table_columns(conn, "main", table)
+        .await?
+        .into_iter()
+        .filter(|column| archive_columns.contains(column))
+        .collect();
+    let column_list = columns
+        .iter()
+        .map(|column| format!("\"{}\"", column))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let inserted = match mode {
+        ImportMode::Replace => {
+            sqlx::query(&format!("DELETE FROM main.\"{}\"", table))
+                .execute(&mut *conn)
+                .await
+                .map_err(db_err)?;
+            sqlx::query(&format!(
+                "INSERT INTO main.\"{table}\" ({column_list}) SELECT {column_list} FROM archive.\"{table}\""
+            ))
+            .execute(&mut *conn)
+            .await
+            .map_err(db_err)?
+            .rows_affected()
+        }
+        ImportMode::Merge => {
+            let compared: Vec<String> = columns
+                .iter()
+                .filter(|column| !MERGE_IGNORED_COLUMNS.contains(&column.as_str()))
+                .map(|column| format!("a.\"{column}\" IS m.\"{column}\""))
+                .collect();
+            let conflicts: Vec<(String,)> = sqlx::query_as(&format!(
+                "SELECT a.id FROM archive.\"{table}\" a JOIN main.\"{table}\" m ON a.id = m.id
+                 WHERE NOT ({})",
+                compared.join(" AND ")
+            ))
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(db_err)?;
+
+            for (id,) in conflicts {
+                report.conflicts.push(ImportConflict {
+                    location: table.to_string(),
+                    key: id,
+                    reason: "Row exists locally with different content, kept local version"
+                        .to_string(),
+                });
+            }
+
+            sqlx::query(&format!(
+                "INSERT INTO main.\"{table}\" ({column_list})
+                 SELECT {column_list} FROM archive.\"{table}\"
+                 WHERE id NOT IN (SELECT id FROM main.\"{table}\")"
+            ))
+            .execute(&mut *conn)
+            .await
+            .map_err(db_err)?
+            .rows_affected()
+        }
+    };
+
+    report.rows_imported.insert(table.to_string(), inserted);
+    Ok(())
+}
+
+fn import_files(
+    app_handle: &AppHandle,
+    files_root: &Path,
+    mode: ImportMode,
+    report: &mut ImportReport,
+) -> Result<(), String> {
+    for dir_name in WORKSPACE_DIRS {
+        let source_dir = files_root.join(dir_name);
+        let target_dir = workspace::workspace_dir(app_handle, dir_name)?;
+
+        if mode == ImportMode::Replace && target_dir.exists() {
+            std::fs::remove_dir_all(&target_dir)
+                .map_err(|e| format!("Failed to clear '{}': {}", target_dir.display(), e))?;
+        }
+
+        let files = workspace::list_files(&source_dir)?;
+        for (index, relative) in files.iter().enumerate() {
+            let source = source_dir.join(relative);
+            let target = target_dir.join(relative);
+
+            if target.exists() {
+                let same = std::fs::read(&source).ok() == std::fs::read(&target).ok();
+                if !same {
+                    report.conflicts.push(ImportConflict {
+                        location: dir_name.to_string(),
+                        key: relative.to_string_lossy().to_string(),
+                        reason: "File exists locally with different content, kept local version"
+                            .to_string(),
+                    });
+                }
+                continue;
+            }
+
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+            }
+            std::fs::copy(&source, &target)
+                .map_err(|e| format!("Failed to restore '{}': {}", target.display(), e))?;
+            report.files_imported += 1;
+            emit_progress(app_handle, "files", index + 1, files.len());
+        }
+    }
+
+    Ok(())
+}
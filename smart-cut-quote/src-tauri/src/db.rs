@@ -0,0 +1,96 @@
+//! Backend access to the application database
+//!
+//! The SQLite database is opened by the frontend through tauri-plugin-sql.
+//! Backend commands reuse the same connection pool instead of opening
+//! a second connection to the file.
+
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_sql::{DbInstances, DbPool};
+
+/// Connection string shared with the frontend (`Database.load`)
+pub const DB_URL: &str = "sqlite:smart_cut_quote.db";
+
+/// Get the SQLite pool loaded by tauri-plugin-sql
+///
+/// Fails if the frontend has not loaded the database yet.
+pub async fn sqlite_pool(app_handle: &AppHandle) -> Result<SqlitePool, String> {
+    let instances = app_handle.state::<DbInstances>();
+    let instances = instances.0.read().await;
+
+    match instances.get(DB_URL) {
+        Some(DbPool::Sqlite(pool)) => Ok(pool.clone()),
+        None => Err(format!("Database '{}' is not loaded", DB_URL)),
+    }
+}
+
+/// Location of the database file on disk
+///
+/// tauri-plugin-sql resolves relative SQLite paths against the app config dir.
+pub fn db_file_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let file_name = DB_URL.trim_start_matches("sqlite:");
+    app_handle
+        .path()
+        .app_config_dir()
+        .map(|dir| dir.join(file_name))
+        .map_err(|e| format!("Failed to resolve app config directory: {}", e))
+}
+
+/// Write a consistent copy of the database to `dest` (`VACUUM INTO`)
+///
+/// Safe while the database is in use; `dest` must not exist yet.
+pub async fn backup_database(pool: &SqlitePool, dest: &Path) -> Result<(), String> {
+    sqlx::query("VACUUM INTO ?")
+        .bind(dest.to_string_lossy().to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to back up database to {}: {}", dest.display(), e))?;
+    Ok(())
+}
+
+/// Latest schema version known to this build (highest migration version)
+pub fn latest_schema_version() -> i64 {
+    crate::get_migrations()
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Schema version applied to the loaded database
+pub async fn schema_version(pool: &SqlitePool) -> Result<i64, String> {
+    let (version,): (Option<i64>,) = sqlx::query_as("SELECT MAX(version) FROM _sqlx_migrations")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to read schema version: {}", e))?;
+
+    Ok(version.unwrap_or(0))
+}
+
+/// Fixtures for tests that need a scratch directory or the app database
+#[cfg(test)]
+pub(crate) mod test_support {
+    use crate::commands::migrations;
+    use sqlx::SqlitePool;
+    use std::path::{Path, PathBuf};
+
+    /// Empty directory for one test, left over runs are cleared first
+    pub(crate) fn test_dir(module: &str, name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("{}-test-{}-{}", module, name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Pool on a new database in `dir`, migrated by the startup runner
+    /// with every migration the app ships
+    pub(crate) async fn migrated_pool(dir: &Path) -> SqlitePool {
+        let db_path = dir.join("app.db");
+        migrations::migrate_database(&db_path, &dir.join("backups"), crate::get_migrations())
+            .await
+            .unwrap();
+        migrations::open_pool(&db_path, false).await.unwrap()
+    }
+}
@@ -0,0 +1,342 @@
+//! Checks for file paths handed over IPC
+//!
+//! Commands that read or write a path given by the frontend only touch
+//! files below the allowed roots (the app data dir and the user's home).
+//! Paths are canonicalized before the check, so `..` segments and
+//! symlinks cannot lead outside; each kind of file also has its own
+//! extensions and size cap.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Files a command may access, by extension and size
+#[derive(Debug, Clone, Copy)]
+pub struct FileRule {
+    /// Accepted extensions, lower case without the dot
+    pub extensions: &'static [&'static str],
+    pub max_bytes: u64,
+}
+
+/// DXF drawings read or written by the DXF commands
+pub const DXF_FILES: FileRule = FileRule {
+    extensions: &["dxf"],
+    max_bytes: 100 * 1024 * 1024,
+};
+
+/// Instance JSON read by the nesting engine in `json_path` mode
+pub const INSTANCE_JSON_FILES: FileRule = FileRule {
+    extensions: &["json"],
+    max_bytes: 64 * 1024 * 1024,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileAccessError {
+    /// The path is outside the allowed roots or breaks the file rule
+    PermissionDenied { path: PathBuf, reason: String },
+    Io {
+        path: PathBuf,
+        kind: std::io::ErrorKind,
+        message: String,
+    },
+}
+
+impl fmt::Display for FileAccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileAccessError::PermissionDenied { path, reason } => {
+                write!(f, "Permission denied for '{}': {}", path.display(), reason)
+            }
+            FileAccessError::Io { path, message, .. } => {
+                write!(f, "Failed to access '{}': {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl From<FileAccessError> for String {
+    fn from(error: FileAccessError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Canonical directories below which commands may access files
+#[derive(Debug, Clone)]
+pub struct AllowedRoots {
+    roots: Vec<PathBuf>,
+}
+
+impl AllowedRoots {
+    /// Roots that do not exist are left out
+    pub fn new(roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        AllowedRoots {
+            roots: roots
+                .into_iter()
+                .filter_map(|root| root.canonicalize().ok())
+                .collect(),
+        }
+    }
+
+    /// App data dir and home dir
+    pub fn for_app(app_handle: &AppHandle) -> Self {
+        let resolver = app_handle.path();
+        Self::new(
+            resolver
+                .app_data_dir()
+                .into_iter()
+                .chain(resolver.home_dir()),
+        )
+    }
+
+    /// Canonical path of an existing file the rule allows
+    pub fn check_existing(&self, path: &Path, rule: FileRule) -> Result<PathBuf, FileAccessError> {
+        reject_parent_segments(path)?;
+        let canonical = path.canonicalize().map_err(|e| io_error(path, &e))?;
+        self.check_canonical(path, &canonical, rule)?;
+
+        let size = std::fs::metadata(&canonical)
+            .map_err(|e| io_error(path, &e))?
+            .len();
+        if size > rule.max_bytes {
+            return Err(denied(
+                path,
+                format!("file is {} bytes, the limit is {}", size, rule.max_bytes),
+            ));
+        }
+        Ok(canonical)
+    }
+
+    /// Canonical path to write; the file itself may not exist yet
+    pub fn check_target(&self, path: &Path, rule: FileRule) -> Result<PathBuf, FileAccessError> {
+        reject_parent_segments(path)?;
+        if path.exists() {
+            let canonical = path.canonicalize().map_err(|e| io_error(path, &e))?;
+            self.check_canonical(path, &canonical, rule)?;
+            return Ok(canonical);
+        }
+
+        let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+            return Err(denied(path, "not a file path".to_string()));
+        };
+        let canonical = parent
+            .canonicalize()
+            .map_err(|e| io_error(path, &e))?
+            .join(name);
+        self.check_canonical(path, &canonical, rule)?;
+        Ok(canonical)
+    }
+
+    fn check_canonical(
+        &self,
+        path: &Path,
+        canonical: &Path,
+        rule: FileRule,
+    ) -> Result<(), FileAccessError> {
+        if !self.roots.iter().any(|root| canonical.starts_with(root)) {
+            return Err(denied(path, "outside the allowed folders".to_string()));
+        }
+
+        let extension = canonical
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        if !extension.is_some_and(|extension| rule.extensions.contains(&extension.as_str())) {
+            return Err(denied(
+                path,
+                format!("only .{} files are allowed", rule.extensions.join(", .")),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Read a text file after checking it against the roots and the rule
+pub fn read_checked(
+    roots: &AllowedRoots,
+    path: &str,
+    rule: FileRule,
+) -> Result<String, FileAccessError> {
+    let canonical = roots.check_existing(Path::new(path), rule)?;
+    std::fs::read_to_string(&canonical).map_err(|e| io_error(Path::new(path), &e))
+}
+
+/// Write a text file after checking the target against the roots and the rule
+pub fn write_checked(
+    roots: &AllowedRoots,
+    path: &str,
+    rule: FileRule,
+    contents: &str,
+) -> Result<(), FileAccessError> {
+    let canonical = roots.check_target(Path::new(path), rule)?;
+    std::fs::write(&canonical, contents).map_err(|e| io_error(Path::new(path), &e))
+}
+
+fn io_error(path: &Path, error: &std::io::Error) -> FileAccessError {
+    FileAccessError::Io {
+        path: path.to_path_buf(),
+        kind: error.kind(),
+        message: error.to_string(),
+    }
+}
+
+fn denied(path: &Path, reason: String) -> FileAccessError {
+    FileAccessError::PermissionDenied {
+        path: path.to_path_buf(),
+        reason,
+    }
+}
+
+/// `..` segments are refused outright, with either separator
+///
+/// Canonicalizing would resolve them, but a traversal attempt is never
+/// a legitimate path from the UI.
+fn reject_parent_segments(path: &Path) -> Result<(), FileAccessError> {
+    let raw = path.to_string_lossy();
+    if raw.split(['/', '\\']).any(|segment| segment == "..") {
+        return Err(denied(
+            path,
+            "parent directory segments are not allowed".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `root/` is allowed, `outside/` is not
+    struct Sandbox {
+        dir: PathBuf,
+        roots: AllowedRoots,
+    }
+
+    impl Sandbox {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("file-access-{}-{}", name, std::process::id()));
+            std::fs::create_dir_all(dir.join("root")).unwrap();
+            std::fs::create_dir_all(dir.join("outside")).unwrap();
+            let roots = AllowedRoots::new([dir.join("root")]);
+            Sandbox { dir, roots }
+        }
+
+        fn file(&self, relative: &str, contents: &str) -> PathBuf {
+            let path = self.dir.join(relative);
+            std::fs::write(&path, contents).unwrap();
+            path
+        }
+    }
+
+    impl Drop for Sandbox {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn is_denied(result: Result<String, FileAccessError>) -> bool {
+        matches!(result, Err(FileAccessError::PermissionDenied { .. }))
+    }
+
+    #[test]
+    fn test_reads_instance_json_inside_roots() {
+        let sandbox = Sandbox::new("inside");
+        let path = sandbox.file("root/job.json", "{}");
+        let json = read_checked(&sandbox.roots, path.to_str().unwrap(), INSTANCE_JSON_FILES);
+        assert_eq!(json.unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_traversal_is_denied() {
+        let sandbox = Sandbox::new("traversal");
+        sandbox.file("outside/secret.json", "{}");
+        let root = sandbox.dir.join("root");
+
+        for attempt in [
+            format!("{}/../outside/secret.json", root.display()),
+            format!("{}\\..\\..\\windows\\system32", root.display()),
+            "..\\..\\windows\\system32".to_string(),
+        ] {
+            let result = read_checked(&sandbox.roots, &attempt, INSTANCE_JSON_FILES);
+            match result {
+                Err(FileAccessError::PermissionDenied { path, .. }) => {
+                    assert_eq!(path, PathBuf::from(&attempt))
+                }
+                other => panic!("{} was not denied: {:?}", attempt, other),
+            }
+        }
+
+        let outside = sandbox.dir.join("outside/secret.json");
+        assert!(is_denied(read_checked(
+            &sandbox.roots,
+            outside.to_str().unwrap(),
+            INSTANCE_JSON_FILES
+        )));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_are_resolved_before_the_check() {
+        let sandbox = Sandbox::new("symlink");
+        let secret = sandbox.file("outside/secret.json", "{}");
+        let link = sandbox.dir.join("root/link.json");
+        std::os::unix::fs::symlink(&secret, &link).unwrap();
+        assert!(is_denied(read_checked(
+            &sandbox.roots,
+            link.to_str().unwrap(),
+            INSTANCE_JSON_FILES
+        )));
+
+        // A link to a non-JSON file inside the roots is refused by its target
+        let notes = sandbox.file("root/notes.txt", "{}");
+        let disguised = sandbox.dir.join("root/disguised.json");
+        std::os::unix::fs::symlink(&notes, &disguised).unwrap();
+        assert!(is_denied(read_checked(
+            &sandbox.roots,
+            disguised.to_str().unwrap(),
+            INSTANCE_JSON_FILES
+        )));
+    }
+
+    #[test]
+    fn test_extension_and_size_cap() {
+        let sandbox = Sandbox::new("rules");
+        let drawing = sandbox.file("root/part.dxf", "0\nEOF\n");
+        assert!(is_denied(read_checked(
+            &sandbox.roots,
+            drawing.to_str().unwrap(),
+            INSTANCE_JSON_FILES
+        )));
+
+        let big = sandbox.file("root/big.json", "[0000000000]");
+        let tiny = FileRule {
+            extensions: &["json"],
+            max_bytes: 4,
+        };
+        assert!(is_denied(read_checked(
+            &sandbox.roots,
+            big.to_str().unwrap(),
+            tiny
+        )));
+    }
+
+    #[test]
+    fn test_write_target_may_not_exist_yet() {
+        let sandbox = Sandbox::new("write");
+        let target = sandbox.dir.join("root/healed.dxf");
+        write_checked(
+            &sandbox.roots,
+            target.to_str().unwrap(),
+            DXF_FILES,
+            "0\nEOF\n",
+        )
+        .unwrap();
+        assert!(target.exists());
+
+        let outside = sandbox.dir.join("outside/healed.dxf");
+        let result = write_checked(&sandbox.roots, outside.to_str().unwrap(), DXF_FILES, "");
+        assert!(matches!(
+            result,
+            Err(FileAccessError::PermissionDenied { .. })
+        ));
+    }
+}
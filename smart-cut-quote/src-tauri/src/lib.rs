@@ -1,11 +1,80 @@
 // Commands module for external executables
 mod commands;
 
+// Backend access to the database opened by tauri-plugin-sql
+mod db;
+
+// Allowed-roots checks for file paths received over IPC
+mod file_access;
+
 // Integrated nesting engine (replaces sparrow-cli.exe)
 pub mod nesting_engine;
 
+// App data directories (jobs, library, SVGs, thumbnails)
+mod workspace;
+
+use commands::adjustment::{begin_adjustment, end_adjustment, probe_placement, AdjustmentSessions};
+use commands::artifacts::{audit_artifacts, get_artifact, read_artifact_chunk, ArtifactTransfers};
+#[cfg(debug_assertions)]
+use commands::bench::bench_nesting_engine;
+use commands::consolidation::{allocate_sheet_costs, consolidate_quotes};
+use commands::conversion_jobs::{
+    cancel_conversion_job, get_conversion_job, resume_conversion_job, start_conversion_job,
+    ConversionJobs,
+};
+use commands::cutting_profiles::{
+    create_cutting_profile, delete_cutting_profile, list_cutting_profiles, resolve_cutting_profile,
+    resolve_profile_separation, update_cutting_profile,
+};
+use commands::dashboard_server::DashboardServer;
+use commands::demo_data::{clear_demo_data, load_demo_data};
+use commands::drafts::{discard_draft, list_recoverable_drafts, save_draft, DraftSession};
 use commands::dxf_converter::convert_dxf_to_json;
+use commands::event_bus::{get_event_bus_stats, EventBus};
+use commands::export_naming::{reserve_export_path, validate_export_name_template};
+use commands::instance_blobs::{get_instance_blob, purge_nesting_results};
+use commands::instance_diff::diff_instances;
+use commands::job_status::{get_job_status, list_jobs, JobEntry, JobKind, JobRegistry, JobStatus};
+use commands::layout_export::{
+    export_cut_program, export_interactive_html, export_layout_dxf, export_layout_image,
+    render_layout_print,
+};
+use commands::layout_verification::verify_nesting;
+use commands::legacy_import::import_legacy_results;
+use commands::locale::set_number_locale;
+use commands::logging::set_log_config;
+use commands::machine_profiles::{
+    get_machine_clamp_zones, resolve_machine_keep_out, set_machine_clamp_zones,
+};
+use commands::material_requirements::compute_material_requirements;
+use commands::migrations::{get_migration_status, MigrationState};
+use commands::min_web::{check_min_web, resolve_material_min_web};
+use commands::mirror_pairs::detect_mirror_pairs;
+use commands::nesting_batch::run_nesting_batch;
+use commands::nesting_jobs::{get_derived_output, release_nesting_job, NestingJobs};
+use commands::nesting_manager::{
+    drop_nesting_result, get_nesting_result, get_nesting_status, set_nesting_concurrency,
+    start_nesting, NestingJobManager,
+};
+use commands::nesting_metrics::{get_nesting_metrics, record_nesting_run, NestingRunMetrics};
+use commands::pipeline::{cancel_pipeline, run_quote_pipeline, RunningPipelines};
+use commands::price_matrix::quantity_price_matrix;
+use commands::quote_nesting::{compare_nesting_outputs, renest_exact, save_nesting_snapshot};
+use commands::quote_save::{save_quote, QuoteSaveLocks};
+use commands::result_improvement::improve_nesting_result;
+use commands::response::{respond, CommandError, CommandResponse, ErrorCode};
+use commands::sheet_order::order_job_sheets;
+use commands::shutdown::{cancel_nesting, force_quit, set_shutdown_grace_period, RunningNestings};
+use commands::source_watch::{watch_quote_sources, SourceWatchers};
 use commands::sparrow_cli::run_nesting;
+use commands::standard_materials::seed_standard_materials;
+use commands::startup_check::{get_startup_report, repair_installation, StartupCheck};
+use commands::strip_height::resolve_strip_height_limits;
+use commands::subset_renest::renest_subset;
+use commands::support_bundle::{create_support_bundle, remember_failed_run};
+use commands::webhook::{notify_nesting_finished, test_webhook};
+use commands::workspace_archive::{export_workspace_archive, import_workspace_archive};
+use tauri::Manager;
 use tauri_plugin_sql::{Migration, MigrationKind};
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
@@ -53,6 +122,156 @@ fn get_migrations() -> Vec<Migration> {
             sql: include_str!("../migrations/006_add_production_tracking.sql"),
             kind: MigrationKind::Up,
         },
+        Migration {
+            version: 7,
+            description: "Add nesting snapshots for exact re-quotes",
+            sql: include_str!("../migrations/007_add_nesting_snapshots.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 8,
+            description: "Add clamp zones per machine profile",
+            sql: include_str!("../migrations/008_add_machine_clamp_zones.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 9,
+            description: "Add material densities and scrap allowance",
+            sql: include_str!("../migrations/009_add_material_densities.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 10,
+            description: "Add auto re-nest on source change setting",
+            sql: include_str!("../migrations/010_add_auto_renest_setting.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 11,
+            description: "Add nesting snapshot provenance",
+            sql: include_str!("../migrations/011_add_nesting_provenance.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 12,
+            description: "Add nesting run metrics",
+            sql: include_str!("../migrations/012_add_nesting_metrics.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 13,
+            description: "Add conversion jobs",
+            sql: include_str!("../migrations/013_add_conversion_jobs.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 14,
+            description: "Add material min web",
+            sql: include_str!("../migrations/014_add_material_min_web.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 15,
+            description: "Add demo rows",
+            sql: include_str!("../migrations/015_add_demo_rows.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 16,
+            description: "Add quote drafts",
+            sql: include_str!("../migrations/016_add_quote_drafts.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 17,
+            description: "Add webhook deliveries",
+            sql: include_str!("../migrations/017_add_webhook_deliveries.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 18,
+            description: "Add display units setting",
+            sql: include_str!("../migrations/018_add_display_units_setting.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 19,
+            description: "Add material price review",
+            sql: include_str!("../migrations/019_add_material_price_review.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 20,
+            description: "Add max strip height setting",
+            sql: include_str!("../migrations/020_add_max_strip_height_setting.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 21,
+            description: "Add quote audit",
+            sql: include_str!("../migrations/021_add_quote_audit.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 22,
+            description: "Add nesting results",
+            sql: include_str!("../migrations/022_add_nesting_results.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 23,
+            description: "Add material kerf",
+            sql: include_str!("../migrations/023_add_material_kerf.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 24,
+            description: "Add pipeline undo log",
+            sql: include_str!("../migrations/024_add_pipeline_undo_log.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 25,
+            description: "Add dashboard server settings",
+            sql: include_str!("../migrations/025_add_dashboard_server_settings.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 26,
+            description: "Add instance blobs",
+            sql: include_str!("../migrations/026_add_instance_blobs.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 27,
+            description: "Add cutting profiles",
+            sql: include_str!("../migrations/027_add_cutting_profiles.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 28,
+            description: "Add nesting result revisions",
+            sql: include_str!("../migrations/028_add_nesting_result_revisions.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 29,
+            description: "Add export name template",
+            sql: include_str!("../migrations/029_add_export_name_template.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 30,
+            description: "Add consolidation results",
+            sql: include_str!("../migrations/030_add_consolidation_results.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 31,
+            description: "Add artifact checksums",
+            sql: include_str!("../migrations/031_add_artifact_checksums.sql"),
+            kind: MigrationKind::Up,
+        },
     ]
 }
 
@@ -60,55 +279,334 @@ fn get_migrations() -> Vec<Migration> {
 ///
 /// This replaces the old CLI-based approach with direct function call.
 /// Should be called via spawn_blocking for long-running operations.
+///
+/// With a `job_id`, placements and summary are emitted as soon as the
+/// optimizer finished and the heavy fields follow in a second event
+/// (see `commands::nesting_jobs`). The return value is always complete.
+/// It is in mm unless other `units` are requested; the job events are
+/// always in mm. A job stopped with `cancel_nesting` returns its best
+/// layout so far with a warning.
 #[tauri::command]
 async fn run_nesting_integrated(
-    input: nesting_engine::NestingInput,
-) -> Result<nesting_engine::NestingOutput, String> {
-    // Run in blocking thread to avoid freezing UI
-    tauri::async_runtime::spawn_blocking(move || {
-        nesting_engine::run_nesting_engine(input)
-    })
-    .await
-    .map_err(|e| format!("Task join error: {}", e))?
+    app_handle: tauri::AppHandle,
+    mut input: nesting_engine::NestingInput,
+    job_id: Option<String>,
+    quote_id: Option<String>,
+    units: Option<nesting_engine::DisplayUnits>,
+) -> CommandResponse<nesting_engine::NestingOutput> {
+    let started = std::time::Instant::now();
+    let metrics = NestingRunMetrics::for_input(&input);
+    let notified_job_id = job_id.clone();
+    if let Some(job_id) = &job_id {
+        report_nesting_job(
+            &app_handle,
+            job_id,
+            JobStatus::running("optimizing", None, None),
+        );
+    }
+
+    let result = async {
+        // Instance JSON on disk, only from the allowed folders
+        if let Some(json_path) = input.json_path.take() {
+            if !input.json_input.trim().is_empty() {
+                return Err(CommandError::new(
+                    ErrorCode::InvalidInput,
+                    "Pass either json_input or json_path, not both",
+                ));
+            }
+            let roots = file_access::AllowedRoots::for_app(&app_handle);
+            input.json_input =
+                file_access::read_checked(&roots, &json_path, file_access::INSTANCE_JSON_FILES)?;
+        }
+        // Machine clamps become keep-out zones; the engine only sees the resolved list
+        let machine_id = input.machine_profile_id.take();
+        if let Some(machine_id) = &machine_id {
+            resolve_machine_keep_out(&app_handle, machine_id, &mut input).await?;
+        }
+        // Stock and settings the strip height is checked against
+        let material_id = input.material_id.take();
+        resolve_strip_height_limits(&app_handle, material_id.as_deref(), &mut input).await?;
+        if let Some(material_id) = material_id {
+            // Cutting profile first, the material fills in what it leaves unset
+            resolve_profile_separation(
+                &app_handle,
+                machine_id.as_deref(),
+                &material_id,
+                &mut input,
+            )
+            .await?;
+            resolve_material_min_web(&app_handle, &material_id, &mut input).await?;
+        }
+
+        // Kept for the support bundle if the run fails
+        let instance_json = input.json_input.clone();
+
+        // Run in blocking thread to avoid freezing UI
+        let job_app_handle = app_handle.clone();
+        // Registered so closing the app stops the run instead of orphaning it
+        tauri::async_runtime::spawn_blocking(move || {
+            commands::shutdown::run_registered(
+                &job_app_handle,
+                job_id.as_deref(),
+                |terminator| match &job_id {
+                    Some(job_id) => commands::nesting_jobs::run_nesting_job(
+                        &job_app_handle,
+                        job_id,
+                        input,
+                        terminator,
+                    ),
+                    None => nesting_engine::run_nesting_engine_with_terminator(input, terminator),
+                },
+            )
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+        .map_err(|message| {
+            remember_failed_run(&app_handle, &instance_json, &message);
+            if message.starts_with(nesting_engine::CANCELLED_BEFORE_SOLUTION) {
+                CommandError::new(ErrorCode::CancelledBeforeSolution, message)
+            } else {
+                CommandError::from(message)
+            }
+        })
+    }
+    .await;
+
+    // Every run is recorded, failures included
+    let (result, error_code) = match result {
+        Ok(output) => (Ok(output), ErrorCode::Internal),
+        Err(error) => (Err(error.message), error.code),
+    };
+    let metrics = metrics.finish(&result, started.elapsed());
+    let cancelled = notified_job_id
+        .as_deref()
+        .is_some_and(|job_id| app_handle.state::<RunningNestings>().take_cancelled(job_id));
+    if let Some(job_id) = &notified_job_id {
+        let status = match &result {
+            _ if cancelled => JobStatus::Cancelled,
+            Ok(_) => JobStatus::Completed {
+                summary_ref: Some(job_id.clone()),
+            },
+            Err(message) => JobStatus::Failed {
+                error: message.clone(),
+            },
+        };
+        report_nesting_job(&app_handle, job_id, status);
+    }
+    // Off unless a webhook URL is configured; delivery never touches the result
+    notify_nesting_finished(&app_handle, &metrics, notified_job_id, quote_id);
+    record_nesting_run(&app_handle, metrics);
+
+    let warnings = result
+        .as_ref()
+        .map(|output| {
+            cancelled
+                .then(|| "Nesting was cancelled, showing the best layout so far".to_string())
+                .into_iter()
+                .chain(output.input_warnings.iter().cloned())
+                .chain(output.sanity_warning.iter().map(|warning| warning.message.clone()))
+                .chain(output.svg_warnings.iter().map(|warning| warning.message.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+    respond(
+        result
+            .map(|output| output.to_units(units.unwrap_or_default()))
+            .map_err(|message| CommandError::new(error_code, message)),
+    )
+    .with_warnings(warnings)
+}
+
+/// State of a `run_nesting_integrated` job for `get_job_status`
+fn report_nesting_job(app_handle: &tauri::AppHandle, job_id: &str, status: JobStatus) {
+    app_handle.state::<JobRegistry>().report(JobEntry {
+        job_id: job_id.to_string(),
+        kind: JobKind::Nesting,
+        status,
+        payload: (),
+    });
+}
+
+/// Payload of `read_dxf_file`
+#[derive(serde::Serialize, Debug)]
+struct DxfFileContent {
+    content: String,
 }
 
 /// Read DXF file content from disk
 ///
 /// Used by DXF healing editor to load file for editing
 #[tauri::command]
-async fn read_dxf_file(path: String) -> Result<String, String> {
-    std::fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read DXF file '{}': {}", path, e))
+async fn read_dxf_file(
+    app_handle: tauri::AppHandle,
+    path: String,
+) -> CommandResponse<DxfFileContent> {
+    let roots = file_access::AllowedRoots::for_app(&app_handle);
+    respond(
+        file_access::read_checked(&roots, &path, file_access::DXF_FILES)
+            .map(|content| DxfFileContent { content })
+            .map_err(CommandError::from),
+    )
 }
 
 /// Write DXF file content to disk
 ///
 /// Used by DXF healing editor to save modified file
 #[tauri::command]
-async fn write_dxf_file(path: String, content: String) -> Result<(), String> {
-    std::fs::write(&path, content)
-        .map_err(|e| format!("Failed to write DXF file '{}': {}", path, e))
+async fn write_dxf_file(
+    app_handle: tauri::AppHandle,
+    path: String,
+    content: String,
+) -> CommandResponse<()> {
+    let roots = file_access::AllowedRoots::for_app(&app_handle);
+    respond(
+        file_access::write_checked(&roots, &path, file_access::DXF_FILES, &content)
+            .map_err(CommandError::from),
+    )
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    if let Err(e) = nesting_engine::logging::init_logging() {
+        eprintln!("⚠️ Logging not initialized: {}", e);
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(
             tauri_plugin_sql::Builder::default()
-                .add_migrations("sqlite:smart_cut_quote.db", get_migrations())
+                .add_migrations(db::DB_URL, get_migrations())
                 .build(),
         )
+        .manage(SourceWatchers::default())
+        .manage(NestingJobs::default())
+        .manage(NestingJobManager::default())
+        .manage(ConversionJobs::default())
+        .manage(JobRegistry::default())
+        .manage(RunningNestings::default())
+        .manage(EventBus::default())
+        .manage(MigrationState::default())
+        .manage(StartupCheck::default())
+        .manage(DraftSession::default())
+        .manage(QuoteSaveLocks::default())
+        .manage(RunningPipelines::default())
+        .manage(DashboardServer::default())
+        .manage(AdjustmentSessions::default())
+        .manage(ArtifactTransfers::default())
+        .setup(|app| {
+            // Migrate before the frontend loads the database
+            commands::migrations::run_startup_migrations(app.handle());
+            // Report missing binaries and unwritable folders, never fatal
+            commands::startup_check::run_startup_check(app.handle());
+            // Offer drafts left behind by a session that did not exit cleanly
+            commands::drafts::start_draft_session(app.handle());
+            // Finish rolling back pipelines a crash interrupted
+            commands::pipeline::recover_interrupted_pipelines(app.handle());
+            // Read-only endpoint for shop dashboards, when enabled
+            commands::dashboard_server::start_dashboard_server(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             convert_dxf_to_json,
+            start_conversion_job,
+            resume_conversion_job,
+            cancel_conversion_job,
+            get_conversion_job,
+            get_job_status,
+            list_jobs,
             run_nesting,
             run_nesting_integrated,
+            run_nesting_batch,
+            consolidate_quotes,
+            allocate_sheet_costs,
+            order_job_sheets,
+            get_derived_output,
+            release_nesting_job,
+            start_nesting,
+            get_nesting_status,
+            get_nesting_result,
+            drop_nesting_result,
+            set_nesting_concurrency,
+            begin_adjustment,
+            probe_placement,
+            end_adjustment,
             read_dxf_file,
-            write_dxf_file
+            write_dxf_file,
+            save_nesting_snapshot,
+            save_quote,
+            renest_exact,
+            compare_nesting_outputs,
+            improve_nesting_result,
+            renest_subset,
+            diff_instances,
+            detect_mirror_pairs,
+            export_layout_dxf,
+            export_cut_program,
+            render_layout_print,
+            export_layout_image,
+            export_interactive_html,
+            verify_nesting,
+            reserve_export_path,
+            validate_export_name_template,
+            get_machine_clamp_zones,
+            set_machine_clamp_zones,
+            list_cutting_profiles,
+            create_cutting_profile,
+            update_cutting_profile,
+            delete_cutting_profile,
+            resolve_cutting_profile,
+            compute_material_requirements,
+            quantity_price_matrix,
+            check_min_web,
+            watch_quote_sources,
+            #[cfg(debug_assertions)]
+            bench_nesting_engine,
+            export_workspace_archive,
+            import_workspace_archive,
+            import_legacy_results,
+            load_demo_data,
+            clear_demo_data,
+            seed_standard_materials,
+            set_log_config,
+            set_number_locale,
+            get_nesting_metrics,
+            get_event_bus_stats,
+            get_migration_status,
+            get_startup_report,
+            repair_installation,
+            create_support_bundle,
+            save_draft,
+            list_recoverable_drafts,
+            discard_draft,
+            test_webhook,
+            force_quit,
+            cancel_nesting,
+            run_quote_pipeline,
+            cancel_pipeline,
+            get_instance_blob,
+            purge_nesting_results,
+            get_artifact,
+            read_artifact_chunk,
+            audit_artifacts,
+            set_shutdown_grace_period
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| match event {
+            // Give running nesting jobs a chance to stop and checkpoint
+            tauri::RunEvent::ExitRequested { code, api, .. } => {
+                commands::shutdown::on_exit_requested(app_handle, code, &api);
+            }
+            // File watchers hold OS handles, release them before exiting
+            tauri::RunEvent::Exit => {
+                app_handle.state::<SourceWatchers>().stop_all();
+                app_handle.state::<ConversionJobs>().cancel_all();
+                app_handle.state::<DashboardServer>().stop();
+                commands::drafts::mark_clean_shutdown(app_handle);
+            }
+            _ => {}
+        });
 }
@@ -0,0 +1,486 @@
+//! Collision checks while a placement is dragged in the preview
+//!
+//! An `AdjustmentSession` is built once from a nesting output with
+//! outlines: every placement's outline is taken back to its own item
+//! frame, and the placed outlines go into a uniform grid. Probing a
+//! moved placement then only looks at the grid cells around it (broad
+//! phase, bounding boxes) and measures the exact boundary distance to
+//! the few placements found there (narrow phase), which keeps a probe
+//! well below a frame even on layouts with hundreds of parts.
+//!
+//! Outlines are outer contours only. A part lying completely inside
+//! another one counts as nested in a hole when hole nesting or the small
+//! part pass put it there, anywhere else it collides.
+
+use super::geometry::{
+    boundary_distance, bounding_box, open_ring, point_in_polygon, rotate_polygon,
+    translate_polygon, DisplayUnits, Polygon,
+};
+use super::nesting::DEFAULT_ITEM_SEPARATION;
+use super::serializer::NestingOutput;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Distance up to which neighbours are measured for `min_clearance` (mm)
+pub const CLEARANCE_RADIUS: f64 = 20.0;
+
+/// Clearance shortfall still accepted, absorbing the f32 rounding of
+/// optimizer placements (mm)
+const CLEARANCE_TOLERANCE: f64 = 0.01;
+
+/// Outcome of probing one placement at a new position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbeResult {
+    /// Indices into `layouts` of the placements closer than the separation
+    pub colliding: Vec<usize>,
+    /// Smallest distance to another placement, None when none is within
+    /// the clearance radius
+    pub min_clearance: Option<f64>,
+    /// The placement reaches past the strip (or sheet) edge
+    pub outside_strip: bool,
+}
+
+/// Placement as cached for probing
+#[derive(Debug, Clone)]
+struct CachedPlacement {
+    /// Outer contour in the placement's own frame (unrotated, at the origin)
+    local: Polygon,
+    /// Outer contour where the placement currently lies
+    outline: Polygon,
+    bbox: (f64, f64, f64, f64),
+    /// Put into a hole of another part by hole nesting or the small part pass
+    nested: bool,
+}
+
+/// Placement outlines of one layout, cached for fast collision probes
+#[derive(Debug, Clone)]
+pub struct AdjustmentSession {
+    instance_hash: Option<String>,
+    separation: f64,
+    radius: f64,
+    strip_width: f64,
+    strip_height: f64,
+    placements: Vec<CachedPlacement>,
+    grid: SpatialGrid,
+}
+
+impl AdjustmentSession {
+    /// Cache the placements of an output nested with `include_outlines`
+    pub fn new(output: &NestingOutput) -> Result<Self, String> {
+        let placements = output
+            .layouts
+            .iter()
+            .enumerate()
+            .map(|(i, placed)| {
+                let outline = placed
+                    .outline
+                    .as_deref()
+                    .map(|outline| open_ring(outline).to_vec())
+                    .filter(|outline| outline.len() >= 3)
+                    .ok_or_else(|| {
+                        format!(
+                            "Placement {} has no outline; nest with include_outlines to adjust it",
+                            i
+                        )
+                    })?;
+                let local = rotate_polygon(
+                    &translate_polygon(&outline, -placed.position_x, -placed.position_y),
+                    -placed.rotation_degrees,
+                );
+                let bbox = bounding_box(&outline).unwrap_or_default();
+                Ok(CachedPlacement {
+                    local,
+                    outline,
+                    bbox,
+                    nested: placed.host_item_id.is_some() || placed.post_placed,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let separation = output
+            .resolved_config
+            .as_ref()
+            .map(|config| config.separation)
+            .unwrap_or_else(|| {
+                output
+                    .units
+                    .length_from(DEFAULT_ITEM_SEPARATION, DisplayUnits::Mm)
+            });
+        let radius = separation.max(output.units.length_from(CLEARANCE_RADIUS, DisplayUnits::Mm));
+        let bboxes: Vec<_> = placements.iter().map(|placement| placement.bbox).collect();
+
+        Ok(Self {
+            instance_hash: output.instance_hash.clone(),
+            separation,
+            radius,
+            strip_width: output.strip_width,
+            strip_height: output.strip_height,
+            grid: SpatialGrid::new(&bboxes),
+            placements,
+        })
+    }
+
+    /// Fingerprint of the instance the layout was nested from
+    pub fn instance_hash(&self) -> Option<&str> {
+        self.instance_hash.as_deref()
+    }
+
+    /// Number of cached placements
+    pub fn len(&self) -> usize {
+        self.placements.len()
+    }
+
+    /// True for a layout without placements
+    pub fn is_empty(&self) -> bool {
+        self.placements.is_empty()
+    }
+
+    /// Check placement `index` moved to `(x, y)` with `rotation_degrees`
+    ///
+    /// Position and rotation have the meaning of `PlacedItem`; the other
+    /// placements stay where they were when the session began.
+    pub fn probe(
+        &self,
+        index: usize,
+        x: f64,
+        y: f64,
+        rotation_degrees: f64,
+    ) -> Result<ProbeResult, String> {
+        let moved = self.placements.get(index).ok_or_else(|| {
+            format!(
+                "Placement {} does not exist, the layout has {}",
+                index,
+                self.placements.len()
+            )
+        })?;
+        if !x.is_finite() || !y.is_finite() || !rotation_degrees.is_finite() {
+            return Err("Probe position must be finite".to_string());
+        }
+
+        let outline = translate_polygon(&rotate_polygon(&moved.local, rotation_degrees), x, y);
+        let bbox = bounding_box(&outline).unwrap_or_default();
+        let search = (
+            bbox.0 - self.radius,
+            bbox.1 - self.radius,
+            bbox.2 + self.radius,
+            bbox.3 + self.radius,
+        );
+
+        let mut colliding = Vec::new();
+        let mut min_clearance: Option<f64> = None;
+        for other_index in self.grid.query(search) {
+            if other_index == index {
+                continue;
+            }
+            let other = &self.placements[other_index];
+            if !boxes_overlap(search, other.bbox) {
+                continue;
+            }
+            let clearance = clearance(&outline, moved.nested, &other.outline, other.nested);
+            if clearance < self.separation - CLEARANCE_TOLERANCE {
+                colliding.push(other_index);
+            }
+            if clearance <= self.radius {
+                min_clearance = Some(min_clearance.map_or(clearance, |min| min.min(clearance)));
+            }
+        }
+
+        let outside_strip = bbox.0 < -CLEARANCE_TOLERANCE
+            || bbox.1 < -CLEARANCE_TOLERANCE
+            || bbox.2 > self.strip_width + CLEARANCE_TOLERANCE
+            || bbox.3 > self.strip_height + CLEARANCE_TOLERANCE;
+
+        Ok(ProbeResult {
+            colliding,
+            min_clearance,
+            outside_strip,
+        })
+    }
+}
+
+/// Distance between two outlines, 0 when they overlap
+///
+/// A part completely inside the other is only apart (by the boundary
+/// distance) when it is `nested` in a hole.
+fn clearance(a: &[(f64, f64)], a_nested: bool, b: &[(f64, f64)], b_nested: bool) -> f64 {
+    let distance = boundary_distance(a, b);
+    if distance == 0.0 {
+        return 0.0;
+    }
+    let a_inside = point_in_polygon(a[0], b);
+    let b_inside = point_in_polygon(b[0], a);
+    if (a_inside && !a_nested) || (b_inside && !b_nested) {
+        return 0.0;
+    }
+    distance
+}
+
+fn boxes_overlap(a: (f64, f64, f64, f64), b: (f64, f64, f64, f64)) -> bool {
+    a.0 <= b.2 && b.0 <= a.2 && a.1 <= b.3 && b.1 <= a.3
+}
+
+/// Uniform grid of bounding boxes, cells about as large as a typical part
+#[derive(Debug, Clone)]
+pub(super) struct SpatialGrid {
+    cell_size: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub(super) fn new(bboxes: &[(f64, f64, f64, f64)]) -> Self {
+        let mean_size = bboxes
+            .iter()
+            .map(|bbox| (bbox.2 - bbox.0).max(bbox.3 - bbox.1))
+            .sum::<f64>()
+            / bboxes.len().max(1) as f64;
+        let mut grid = Self {
+            cell_size: mean_size.max(1.0),
+            cells: HashMap::new(),
+        };
+        for (index, &bbox) in bboxes.iter().enumerate() {
+            for cell in grid.cells_of(bbox) {
+                grid.cells.entry(cell).or_default().push(index);
+            }
+        }
+        grid
+    }
+
+    fn cells_of(&self, bbox: (f64, f64, f64, f64)) -> impl Iterator<Item = (i64, i64)> {
+        let cell = |value: f64| (value / self.cell_size).floor() as i64;
+        let (min_x, min_y, max_x, max_y) = (cell(bbox.0), cell(bbox.1), cell(bbox.2), cell(bbox.3));
+        (min_x..=max_x).flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+    }
+
+    /// Indices of the boxes sharing a cell with `bbox`, each once
+    pub(super) fn query(&self, bbox: (f64, f64, f64, f64)) -> Vec<usize> {
+        let mut found: Vec<usize> = self
+            .cells_of(bbox)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn rectangle(x: f64, y: f64, width: f64, height: f64) -> Polygon {
+        vec![
+            (x, y),
+            (x + width, y),
+            (x + width, y + height),
+            (x, y + height),
+        ]
+    }
+
+    /// Output with the given outlines placed at their lower left corner
+    fn output(outlines: &[Polygon], strip_width: f64, strip_height: f64) -> NestingOutput {
+        let layouts: Vec<_> = outlines
+            .iter()
+            .enumerate()
+            .map(|(i, outline)| {
+                let (x, y, _, _) = bounding_box(outline).unwrap();
+                serde_json::json!({
+                    "item_id": i,
+                    "rotation_degrees": 0.0,
+                    "position_x": x,
+                    "position_y": y,
+                    "outline": outline,
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "instance_name": "adjust",
+            "strip_width": strip_width,
+            "strip_height": strip_height,
+            "total_items_placed": layouts.len(),
+            "layouts": layouts,
+            "utilization": 0.5,
+            "computation_time_secs": 1.0,
+            "unplaced_item_ids": [],
+            "instance_hash": "abc",
+        }))
+        .unwrap()
+    }
+
+    /// 25 x 20 parts of 40 x 30 mm, 2 mm apart; every fifth an L shape
+    fn large_layout() -> NestingOutput {
+        let mut outlines = Vec::new();
+        for row in 0..20 {
+            for column in 0..25 {
+                let (x, y) = (column as f64 * 42.0, row as f64 * 32.0);
+                if (row * 25 + column) % 5 == 0 {
+                    outlines.push(vec![
+                        (x, y),
+                        (x + 40.0, y),
+                        (x + 40.0, y + 10.0),
+                        (x + 10.0, y + 10.0),
+                        (x + 10.0, y + 30.0),
+                        (x, y + 30.0),
+                    ]);
+                } else {
+                    outlines.push(rectangle(x, y, 40.0, 30.0));
+                }
+            }
+        }
+        output(&outlines, 25.0 * 42.0, 20.0 * 32.0)
+    }
+
+    /// Probe against every placement, without the grid
+    fn brute_force(
+        output: &NestingOutput,
+        index: usize,
+        x: f64,
+        y: f64,
+        rotation: f64,
+    ) -> Vec<usize> {
+        let session = AdjustmentSession::new(output).unwrap();
+        let moved = &session.placements[index];
+        let outline = translate_polygon(&rotate_polygon(&moved.local, rotation), x, y);
+        (0..session.len())
+            .filter(|&other| other != index)
+            .filter(|&other| {
+                clearance(&outline, false, &session.placements[other].outline, false)
+                    < session.separation - CLEARANCE_TOLERANCE
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_probe_reports_collisions_and_clearance() {
+        let output = output(
+            &[
+                rectangle(0.0, 0.0, 100.0, 50.0),
+                rectangle(110.0, 0.0, 100.0, 50.0),
+            ],
+            300.0,
+            100.0,
+        );
+        let session = AdjustmentSession::new(&output).unwrap();
+
+        let at_rest = session.probe(0, 0.0, 0.0, 0.0).unwrap();
+        assert!(at_rest.colliding.is_empty());
+        assert_eq!(at_rest.min_clearance, Some(10.0));
+        assert!(!at_rest.outside_strip);
+
+        // 0.5 mm apart with the default 1 mm separation
+        let close = session.probe(0, 9.5, 0.0, 0.0).unwrap();
+        assert_eq!(close.colliding, vec![1]);
+        assert!((close.min_clearance.unwrap() - 0.5).abs() < 1e-9);
+
+        let overlapping = session.probe(0, 60.0, 20.0, 0.0).unwrap();
+        assert_eq!(overlapping.colliding, vec![1]);
+        assert_eq!(overlapping.min_clearance, Some(0.0));
+
+        // Turned upright at the far end: out of the strip, nobody near
+        let turned = session.probe(0, 300.0, 0.0, 90.0).unwrap();
+        assert!(turned.colliding.is_empty());
+        assert_eq!(turned.min_clearance, None);
+        assert!(!turned.outside_strip);
+        assert!(session.probe(0, 290.0, 0.0, 0.0).unwrap().outside_strip);
+
+        assert!(session.probe(2, 0.0, 0.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_parts_inside_other_parts_collide_unless_nested() {
+        let mut output = output(
+            &[
+                rectangle(0.0, 0.0, 200.0, 200.0),
+                rectangle(10.0, 10.0, 180.0, 180.0),
+            ],
+            300.0,
+            300.0,
+        );
+        let session = AdjustmentSession::new(&output).unwrap();
+        assert_eq!(
+            session.probe(1, 10.0, 10.0, 0.0).unwrap().colliding,
+            vec![0]
+        );
+
+        output.layouts[1].host_item_id = Some(0);
+        let session = AdjustmentSession::new(&output).unwrap();
+        let probe = session.probe(1, 10.0, 10.0, 0.0).unwrap();
+        assert!(probe.colliding.is_empty());
+        assert_eq!(probe.min_clearance, Some(10.0));
+    }
+
+    #[test]
+    fn test_output_without_outlines_is_rejected() {
+        let mut output = output(&[rectangle(0.0, 0.0, 10.0, 10.0)], 100.0, 100.0);
+        output.layouts[0].outline = None;
+        let message = AdjustmentSession::new(&output).unwrap_err();
+        assert!(message.contains("include_outlines"), "{}", message);
+    }
+
+    #[test]
+    fn test_large_layout_probes_match_brute_force() {
+        let output = large_layout();
+        let session = AdjustmentSession::new(&output).unwrap();
+        assert_eq!(session.len(), 500);
+
+        for (index, dx, dy, rotation) in [
+            (0, 0.0, 0.0, 0.0),
+            (137, 1.5, 0.0, 0.0),
+            (250, -20.0, 15.0, 0.0),
+            (260, 5.0, 5.0, 90.0),
+            (499, 3.0, 1.5, 180.0),
+            (312, 21.0, 16.0, 45.0),
+        ] {
+            let placed = &output.layouts[index];
+            let (x, y) = (placed.position_x + dx, placed.position_y + dy);
+            let probe = session.probe(index, x, y, rotation).unwrap();
+            assert_eq!(
+                probe.colliding,
+                brute_force(&output, index, x, y, rotation),
+                "placement {} moved by ({}, {}) at {}°",
+                index,
+                dx,
+                dy,
+                rotation
+            );
+        }
+
+        let untouched = session.probe(137, 42.0 * 12.0, 32.0 * 5.0, 0.0).unwrap();
+        assert!(untouched.colliding.is_empty());
+        assert_eq!(untouched.min_clearance, Some(2.0));
+        let nudged = session
+            .probe(137, 42.0 * 12.0 + 1.5, 32.0 * 5.0, 0.0)
+            .unwrap();
+        assert_eq!(nudged.colliding, vec![138]);
+    }
+
+    #[test]
+    fn test_large_layout_probe_latency() {
+        let output = large_layout();
+        let session = AdjustmentSession::new(&output).unwrap();
+
+        let mut slowest = Duration::ZERO;
+        for step in 0..1000 {
+            let index = (step * 7) % session.len();
+            let placed = &output.layouts[index];
+            let offset = (step % 40) as f64 - 20.0;
+            let started = Instant::now();
+            session
+                .probe(
+                    index,
+                    placed.position_x + offset,
+                    placed.position_y - offset / 2.0,
+                    (step % 4) as f64 * 90.0,
+                )
+                .unwrap();
+            slowest = slowest.max(started.elapsed());
+        }
+        assert!(
+            slowest < Duration::from_millis(10),
+            "slowest probe took {:?}",
+            slowest
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Headless benchmark of the integrated engine
+//!
+//! Runs embedded instances through `run_nesting_engine` with fixed
+//! seeds and short time budgets and reports per-stage timing
+//! percentiles, so CI can catch performance regressions.
+
+use super::{run_nesting_engine, NestingInput, StageTimings};
+use serde::Serialize;
+
+/// Parts of the demo quote (`load_demo_data`), also benchmarked
+pub const DEMO_INSTANCE: &str = include_str!("../../demo/parts.json");
+
+/// Embedded benchmark instances as `(name, instance JSON)`
+pub const BENCHMARK_INSTANCES: &[(&str, &str)] = &[
+    (
+        "rectangles",
+        include_str!("../../bench_instances/rectangles.json"),
+    ),
+    ("mixed", include_str!("../../bench_instances/mixed.json")),
+    ("demo", DEMO_INSTANCE),
+];
+
+/// Time budget per benchmark run (seconds)
+const BENCHMARK_TIME_LIMIT: u64 = 2;
+
+/// Base seed, iteration `i` uses `BENCHMARK_SEED + i`
+const BENCHMARK_SEED: u64 = 42;
+
+/// Timing distribution of one stage (seconds)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StagePercentiles {
+    pub min: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub max: f64,
+}
+
+impl StagePercentiles {
+    fn from_samples(mut samples: Vec<f64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_by(|a, b| a.total_cmp(b));
+
+        // Nearest-rank percentile
+        let rank = |p: f64| {
+            samples[((p * samples.len() as f64).ceil() as usize).clamp(1, samples.len()) - 1]
+        };
+
+        Self {
+            min: samples[0],
+            p50: rank(0.5),
+            p90: rank(0.9),
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Per-stage timing percentiles over all iterations
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    pub instance_name: String,
+    pub iterations: usize,
+    pub parse: StagePercentiles,
+    pub import: StagePercentiles,
+    pub optimize: StagePercentiles,
+    pub serialize: StagePercentiles,
+}
+
+impl BenchmarkReport {
+    /// Render the report as a plain text table
+    pub fn to_table(&self) -> String {
+        let mut table = format!(
+            "Benchmark '{}' ({} iterations)\n{:<10} {:>9} {:>9} {:>9} {:>9}\n",
+            self.instance_name, self.iterations, "stage", "min", "p50", "p90", "max"
+        );
+        for (name, stage) in [
+            ("parse", &self.parse),
+            ("import", &self.import),
+            ("optimize", &self.optimize),
+            ("serialize", &self.serialize),
+        ] {
+            table.push_str(&format!(
+                "{:<10} {:>8.3}s {:>8.3}s {:>8.3}s {:>8.3}s\n",
+                name, stage.min, stage.p50, stage.p90, stage.max
+            ));
+        }
+        table
+    }
+}
+
+/// Run an embedded instance `iterations` times and collect stage timings
+///
+/// # Arguments
+/// * `iterations` - Number of runs (at least 1)
+/// * `instance_name` - Name from `BENCHMARK_INSTANCES`
+pub fn run_benchmark(iterations: usize, instance_name: &str) -> Result<BenchmarkReport, String> {
+    let (_, instance_json) = BENCHMARK_INSTANCES
+        .iter()
+        .find(|(name, _)| *name == instance_name)
+        .ok_or_else(|| {
+            let names: Vec<&str> = BENCHMARK_INSTANCES.iter().map(|(name, _)| *name).collect();
+            format!(
+                "Unknown benchmark instance '{}', expected one of: {}",
+                instance_name,
+                names.join(", ")
+            )
+        })?;
+
+    let iterations = iterations.max(1);
+    let mut timings: Vec<StageTimings> = Vec::with_capacity(iterations);
+
+    for i in 0..iterations {
+        let input = NestingInput {
+            json_input: instance_json.to_string(),
+            time_limit: Some(BENCHMARK_TIME_LIMIT),
+            seed: Some(BENCHMARK_SEED + i as u64),
+            use_early_termination: Some(true),
+            n_workers: Some(1),
+            ..Default::default()
+        };
+
+        let output = run_nesting_engine(input)?;
+        timings.push(output.stage_timings.unwrap_or_default());
+    }
+
+    let collect = |stage: fn(&StageTimings) -> f64| {
+        StagePercentiles::from_samples(timings.iter().map(stage).collect())
+    };
+
+    Ok(BenchmarkReport {
+        instance_name: instance_name.to_string(),
+        iterations,
+        parse: collect(|t| t.parse_secs),
+        import: collect(|t| t.import_secs),
+        optimize: collect(|t| t.optimize_secs),
+        serialize: collect(|t| t.serialize_secs),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_nearest_rank() {
+        let p = StagePercentiles::from_samples((1..=10).map(|v| v as f64).collect());
+        assert_eq!(p.min, 1.0);
+        assert_eq!(p.p50, 5.0);
+        assert_eq!(p.p90, 9.0);
+        assert_eq!(p.max, 10.0);
+    }
+
+    /// Run explicitly in CI: `cargo test -- --ignored bench_`
+    #[test]
+    #[ignore]
+    fn bench_stage_timings_within_thresholds() {
+        for (name, _) in BENCHMARK_INSTANCES {
+            let report = run_benchmark(3, name).unwrap();
+            println!("{}", report.to_table());
+
+            // Generous thresholds, only meant to catch gross regressions
+            assert!(report.parse.p90 < 1.0, "parse too slow: {:?}", report.parse);
+            assert!(
+                report.import.p90 < 5.0,
+                "import too slow: {:?}",
+                report.import
+            );
+            assert!(
+                report.optimize.p90 < BENCHMARK_TIME_LIMIT as f64 + 10.0,
+                "optimize too slow: {:?}",
+                report.optimize
+            );
+            assert!(
+                report.serialize.p90 < 5.0,
+                "serialize too slow: {:?}",
+                report.serialize
+            );
+        }
+    }
+}
@@ -0,0 +1,895 @@
+//! Builders for `NestingInput` and `NestingConfig`
+//!
+//! Entry point for code embedding the engine. The raw structs use
+//! `Option` fields with engine-side defaults, which makes values like
+//! `time_limit: Some(0)` easy to write and hard to spot. The builders
+//! take plain values and `build()` runs the same validation as the
+//! Tauri command path, reporting every problem at once.
+//!
+//! A quick quote: short time budget, stop as soon as the layout settles.
+//!
+//! ```
+//! use smart_cut_quote_lib::nesting_engine::NestingInput;
+//!
+//! let instance_json = r#"{"name": "quote", "strip_height": 1500, "items": []}"#;
+//! let input = NestingInput::builder()
+//!     .json_input(instance_json)
+//!     .time_limit_secs(30)
+//!     .early_termination(true)
+//!     .preset("quick")
+//!     .build()
+//!     .expect("valid quick quote input");
+//!
+//! assert_eq!(input.time_limit, Some(30));
+//! assert_eq!(input.preset.as_deref(), Some("quick"));
+//! ```
+//!
+//! A production nest: longer run on all cores, reproducible seed, a wider
+//! gap for a thick plate and parts kept along the grain direction.
+//!
+//! ```
+//! use smart_cut_quote_lib::nesting_engine::{NestingConfig, NestingInput};
+//!
+//! # let instance_json = r#"{"name": "order", "strip_height": 1500, "items": []}"#;
+//! let input = NestingInput::builder()
+//!     .json_input(instance_json)
+//!     .time_limit_secs(300)
+//!     .workers(4)
+//!     .seed(42)
+//!     .separation_mm(3.0)
+//!     .rotations(vec![0.0, 180.0])
+//!     .preset("production")
+//!     .build()
+//!     .expect("valid production input");
+//!
+//! let config = NestingConfig::from(&input);
+//! assert_eq!(config.n_workers, 4);
+//! assert_eq!(config.separation, 3.0);
+//! assert_eq!(config.rotations, Some(vec![0.0, 180.0]));
+//! ```
+//!
+//! Invalid values are collected instead of failing on the first one.
+//!
+//! ```
+//! use smart_cut_quote_lib::nesting_engine::NestingConfig;
+//!
+//! let issues = NestingConfig::builder()
+//!     .time_limit_secs(0)
+//!     .separation_mm(-1.0)
+//!     .build()
+//!     .unwrap_err();
+//!
+//! let fields: Vec<_> = issues.iter().map(|issue| issue.field).collect();
+//! assert_eq!(fields, vec!["time_limit", "separation"]);
+//! ```
+
+use super::defects::{validate_defect_zones, zones_off_strip};
+use super::filler::{validate_filler_items, FillerItem};
+use super::geometry::Polygon;
+use super::instance::parse_instance;
+use super::keep_out::{validate_keep_out_zones, KeepOutZone};
+use super::locked::check_fixed_positions;
+use super::nesting::NestingConfig;
+use super::quality::Quality;
+use super::remnant::{validate_container, ContainerPolygon};
+use super::sheet_mode::SheetMode;
+use super::warm_start::WarmStart;
+use super::NestingInput;
+use serde::Serialize;
+use std::fmt;
+
+/// Strip height above which a run is most likely a typo, when the
+/// material's stock is unknown (mm)
+pub const DEFAULT_MAX_STRIP_HEIGHT: f64 = 4000.0;
+
+/// Whether an issue stops the run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueSeverity {
+    Error,
+    /// Reported with the result, the run goes ahead
+    Warning,
+}
+
+/// Invalid value in a nesting input or configuration
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    /// Name of the `NestingInput` field
+    pub field: &'static str,
+    pub message: String,
+    pub severity: IssueSeverity,
+}
+
+impl ValidationIssue {
+    fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field,
+            message: message.into(),
+            severity: IssueSeverity::Error,
+        }
+    }
+
+    fn warning(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity: IssueSeverity::Warning,
+            ..Self::new(field, message)
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == IssueSeverity::Error
+    }
+}
+
+/// Largest sheet of a material's stock in mm; sheets run along the
+/// strip, so the strip height must fit the sheet width
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, serde::Deserialize)]
+pub struct StockSheet {
+    pub length: f64,
+    pub width: f64,
+}
+
+impl fmt::Display for StockSheet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} × {} mm", self.length, self.width)
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Check the configuration values of an input
+///
+/// The instance JSON itself is checked when it is parsed, apart from a
+/// strip height no sheet can hold. Warnings do not stop a run; callers
+/// fail on the issues that `is_error()`.
+pub fn validate_input(input: &NestingInput) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    if input.time_limit == Some(0) {
+        issues.push(ValidationIssue::new(
+            "time_limit",
+            "must be at least 1 second (leave it unset for the default)",
+        ));
+    }
+    if input.n_workers == Some(0) {
+        issues.push(ValidationIssue::new("n_workers", "must be at least 1"));
+    }
+    if input.n_restarts == Some(0) {
+        issues.push(ValidationIssue::new("n_restarts", "must be at least 1"));
+    }
+    if let Some(target) = input.target_utilization {
+        if target.is_nan() || target <= 0.0 || target > 1.0 {
+            issues.push(ValidationIssue::new(
+                "target_utilization",
+                format!("must be above 0 and at most 1, got {}", target),
+            ));
+        }
+    }
+    if let Some(separation) = input.separation {
+        if !separation.is_finite() || separation < 0.0 {
+            issues.push(ValidationIssue::new(
+                "separation",
+                format!("must be a non-negative distance in mm, got {}", separation),
+            ));
+        }
+    }
+    if let Some(kerf_width) = input.kerf_width {
+        if !kerf_width.is_finite() || kerf_width < 0.0 {
+            issues.push(ValidationIssue::new(
+                "kerf_width",
+                format!("must be a non-negative distance in mm, got {}", kerf_width),
+            ));
+        }
+    }
+    if input.compress_only == Some(true) {
+        if input.warm_start.is_none() {
+            issues.push(ValidationIssue::new(
+                "compress_only",
+                "needs a warm_start layout to compress",
+            ));
+        }
+        if input.skip_compression == Some(true) {
+            issues.push(ValidationIssue::new(
+                "compress_only",
+                "cannot be combined with skip_compression",
+            ));
+        }
+    }
+    if let Some(rotations) = &input.rotations {
+        if rotations.iter().any(|angle| !angle.is_finite()) {
+            issues.push(ValidationIssue::new(
+                "rotations",
+                format!("must be finite angles in degrees, got {:?}", rotations),
+            ));
+        }
+    }
+    if let Some(tolerance) = input.bend_angle_tolerance {
+        if !(0.0..=45.0).contains(&tolerance) {
+            issues.push(ValidationIssue::new(
+                "bend_angle_tolerance",
+                format!("must be between 0 and 45 degrees, got {}", tolerance),
+            ));
+        }
+    }
+    if let Some(area) = input.small_item_area {
+        if !area.is_finite() || area < 0.0 {
+            issues.push(ValidationIssue::new(
+                "small_item_area",
+                format!("must be a non-negative area in mm², got {}", area),
+            ));
+        }
+    }
+    if let Some(fillers) = &input.filler_items {
+        let item_ids: Vec<u64> = parse_instance(&input.json_input)
+            .map(|instance| instance.items.iter().map(|item| item.id).collect())
+            .unwrap_or_default();
+        if let Err(message) = validate_filler_items(fillers, &item_ids) {
+            issues.push(ValidationIssue::new("filler_items", message));
+        }
+    }
+    if let Some(area) = input.max_filler_area {
+        if !area.is_finite() || area < 0.0 {
+            issues.push(ValidationIssue::new(
+                "max_filler_area",
+                format!("must be a non-negative area in mm², got {}", area),
+            ));
+        }
+    }
+    if let Some(zones) = &input.keep_out_zones {
+        if let Err(message) = validate_keep_out_zones(zones) {
+            issues.push(ValidationIssue::new("keep_out_zones", message));
+        }
+    }
+    if let Some(holes) = &input.holes {
+        match validate_defect_zones(holes) {
+            Ok(()) => issues.extend(check_defect_zones(input, holes)),
+            Err(message) => issues.push(ValidationIssue::new("holes", message)),
+        }
+    }
+    if let Some(min_web) = input.min_web {
+        if !min_web.is_finite() || min_web < 0.0 {
+            issues.push(ValidationIssue::new(
+                "min_web",
+                format!("must be a non-negative width in mm, got {}", min_web),
+            ));
+        }
+    }
+    if let Some(length) = input.sheet_length {
+        if !length.is_finite() || length <= 0.0 {
+            issues.push(ValidationIssue::new(
+                "sheet_length",
+                format!("must be a positive length in mm, got {}", length),
+            ));
+        }
+    }
+    if let Some(factor) = input.sanity_factor {
+        if !factor.is_finite() || factor < 1.0 {
+            issues.push(ValidationIssue::new(
+                "sanity_factor",
+                format!("must be at least 1.0, got {}", factor),
+            ));
+        }
+    }
+    if let Some(max) = input.max_strip_height {
+        if !max.is_finite() || max <= 0.0 {
+            issues.push(ValidationIssue::new(
+                "max_strip_height",
+                format!("must be a positive length in mm, got {}", max),
+            ));
+        }
+    }
+    if let Some(SheetMode::FixedSheet { fixed_width }) = input.sheet_mode {
+        if !fixed_width.is_finite() || fixed_width <= 0.0 {
+            issues.push(ValidationIssue::new(
+                "sheet_mode",
+                format!(
+                    "fixed_width must be a positive length in mm, got {}",
+                    fixed_width
+                ),
+            ));
+        }
+    }
+    if let Some(container) = &input.container_polygon {
+        if let Err(message) = validate_container(container) {
+            issues.push(ValidationIssue::new("container_polygon", message));
+        }
+        if matches!(input.sheet_mode, Some(SheetMode::FixedSheet { .. })) {
+            issues.push(ValidationIssue::new(
+                "container_polygon",
+                "replaces the fixed sheet, set either one",
+            ));
+        }
+    }
+    if input.max_sheets == Some(0) {
+        issues.push(ValidationIssue::new("max_sheets", "must be at least 1"));
+    }
+    issues.extend(check_strip_height(input));
+    issues.extend(check_locked_items(input));
+
+    issues
+}
+
+/// Strip height beyond the material's widest sheet or, without stock
+/// data, beyond the sanity maximum (usually an extra zero)
+fn check_strip_height(input: &NestingInput) -> Option<ValidationIssue> {
+    let instance: serde_json::Value = serde_json::from_str(&input.json_input).ok()?;
+    let strip_height = instance.get("strip_height")?.as_f64()?;
+
+    let message = match input.stock_sheet {
+        Some(sheet) if strip_height > sheet.width => format!(
+            "strip height {} mm is more than the largest stock sheet of the material ({})",
+            strip_height, sheet
+        ),
+        Some(_) => return None,
+        None => {
+            let max = input.max_strip_height.unwrap_or(DEFAULT_MAX_STRIP_HEIGHT);
+            if strip_height <= max {
+                return None;
+            }
+            format!(
+                "strip height {} mm is more than {} mm, check for a typo \
+                 (raise the max_strip_height setting for coil stock)",
+                strip_height, max
+            )
+        }
+    };
+    Some(if input.strict_strip_height.unwrap_or(false) {
+        ValidationIssue::new("strip_height", message)
+    } else {
+        ValidationIssue::warning("strip_height", message)
+    })
+}
+
+/// Length (None for a growing strip) and height of the area parts are
+/// nested into, None if the instance has no strip height
+fn strip_extent(input: &NestingInput) -> Option<(Option<f64>, f64)> {
+    let strip_height = serde_json::from_str::<serde_json::Value>(&input.json_input)
+        .ok()
+        .and_then(|instance| instance.get("strip_height")?.as_f64())?;
+    Some(match (&input.container_polygon, input.sheet_mode) {
+        (Some(container), _) => {
+            let (width, height) = container.clone().normalized().size();
+            (Some(width), height)
+        }
+        (None, Some(SheetMode::FixedSheet { fixed_width })) => (Some(fixed_width), strip_height),
+        (None, _) => (None, strip_height),
+    })
+}
+
+/// Defect zones off the strip, they cannot conflict with any part
+fn check_defect_zones(input: &NestingInput, holes: &[Polygon]) -> Vec<ValidationIssue> {
+    let Some((strip_width, strip_height)) = strip_extent(input) else {
+        return Vec::new();
+    };
+    zones_off_strip(holes, strip_width, strip_height)
+        .into_iter()
+        .map(|message| ValidationIssue::warning("holes", message))
+        .collect()
+}
+
+/// Fixed positions off the strip or overlapping each other
+fn check_locked_items(input: &NestingInput) -> Vec<ValidationIssue> {
+    let (Some((strip_width, strip_height)), Ok(instance)) =
+        (strip_extent(input), parse_instance(&input.json_input))
+    else {
+        return Vec::new();
+    };
+    check_fixed_positions(&instance, strip_width, strip_height)
+        .into_iter()
+        .map(|message| ValidationIssue::new("fixed_position", message))
+        .collect()
+}
+
+/// Builder for `NestingInput`, see the module docs for examples
+#[derive(Debug, Clone, Default)]
+pub struct NestingInputBuilder {
+    input: NestingInput,
+}
+
+impl NestingInput {
+    /// Start building an input with all engine defaults
+    pub fn builder() -> NestingInputBuilder {
+        NestingInputBuilder::default()
+    }
+}
+
+impl NestingInputBuilder {
+    /// Instance JSON (sparroWASM format)
+    pub fn json_input(mut self, json_input: impl Into<String>) -> Self {
+        self.input.json_input = json_input.into();
+        self
+    }
+
+    /// Time budget of the optimizer
+    pub fn time_limit_secs(mut self, secs: u64) -> Self {
+        self.input.time_limit = Some(secs);
+        self
+    }
+
+    /// Seed for a reproducible run
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.input.seed = Some(seed);
+        self
+    }
+
+    /// Number of worker threads
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.input.n_workers = Some(workers);
+        self
+    }
+
+    /// Minimum distance between parts and to the strip edge
+    pub fn separation_mm(mut self, separation: f64) -> Self {
+        self.input.separation = Some(separation);
+        self
+    }
+
+    /// Width of the cut, part outlines are grown by half of it
+    pub fn kerf_width_mm(mut self, kerf_width: f64) -> Self {
+        self.input.kerf_width = Some(kerf_width);
+        self
+    }
+
+    /// Orientations allowed for every part, replacing the instance's
+    pub fn rotations(mut self, degrees: Vec<f64>) -> Self {
+        self.input.rotations = Some(degrees);
+        self
+    }
+
+    /// Keep the bend lines of bent parts along the rolling direction
+    pub fn respect_grain(mut self, enabled: bool) -> Self {
+        self.input.respect_grain = Some(enabled);
+        self
+    }
+
+    /// Max angle between bend lines and the rolling direction in degrees
+    pub fn bend_angle_tolerance(mut self, degrees: f64) -> Self {
+        self.input.bend_angle_tolerance = Some(degrees);
+        self
+    }
+
+    /// Turn parts drawn at an angle to their smallest bounding box first
+    pub fn pre_orient_items(mut self, enabled: bool) -> Self {
+        self.input.pre_orient_items = Some(enabled);
+        self
+    }
+
+    /// Net area in mm² below which parts are placed after packing (0 = never)
+    pub fn small_item_area(mut self, area: f64) -> Self {
+        self.input.small_item_area = Some(area);
+        self
+    }
+
+    /// Stock parts dropped into the gaps after nesting
+    pub fn filler_items(mut self, fillers: Vec<FillerItem>) -> Self {
+        self.input.filler_items = Some(fillers);
+        self
+    }
+
+    /// Most fillers placed, and most net area in mm² they may cover
+    pub fn filler_caps(mut self, max_count: usize, max_area: f64) -> Self {
+        self.input.max_filler_count = Some(max_count);
+        self.input.max_filler_area = Some(max_area);
+        self
+    }
+
+    /// Nest into one sheet of `width` mm along the strip instead of a
+    /// growing strip
+    pub fn fixed_sheet_mm(mut self, width: f64) -> Self {
+        self.input.sheet_mode = Some(SheetMode::FixedSheet { fixed_width: width });
+        self
+    }
+
+    /// Nest parts left off the fixed sheet onto up to `count` sheets in all
+    pub fn max_sheets(mut self, count: usize) -> Self {
+        self.input.max_sheets = Some(count);
+        self
+    }
+
+    /// Spend the whole time limit exploring, without compression
+    pub fn skip_compression(mut self, skip: bool) -> Self {
+        self.input.skip_compression = Some(skip);
+        self
+    }
+
+    /// Only compress the warm start layout, without exploring first
+    pub fn compress_only(mut self, enabled: bool) -> Self {
+        self.input.compress_only = Some(enabled);
+        self
+    }
+
+    /// Nest into a remnant of this outline instead of the strip
+    pub fn container_polygon(mut self, container: ContainerPolygon) -> Self {
+        self.input.container_polygon = Some(container);
+        self
+    }
+
+    /// Stop when the layout stops improving
+    pub fn early_termination(mut self, enabled: bool) -> Self {
+        self.input.use_early_termination = Some(enabled);
+        self
+    }
+
+    /// Areas of the sheet where no part may be placed
+    pub fn keep_out_zones(mut self, zones: Vec<KeepOutZone>) -> Self {
+        self.input.keep_out_zones = Some(zones);
+        self
+    }
+
+    /// Defect zones of the sheet no part may overlap
+    pub fn holes(mut self, holes: Vec<Polygon>) -> Self {
+        self.input.holes = Some(holes);
+        self
+    }
+
+    /// Place small parts inside the holes of larger parts
+    pub fn hole_nesting(mut self, enabled: bool) -> Self {
+        self.input.hole_nesting = Some(enabled);
+        self
+    }
+
+    /// Name of the settings preset, recorded in the run metrics
+    pub fn preset(mut self, name: impl Into<String>) -> Self {
+        self.input.preset = Some(name.into());
+        self
+    }
+
+    /// Optimizer runs with different seeds sharing the time limit
+    pub fn restarts(mut self, restarts: usize) -> Self {
+        self.input.n_restarts = Some(restarts);
+        self
+    }
+
+    /// Stop at the first layout reaching this utilization
+    pub fn target_utilization(mut self, target: f64) -> Self {
+        self.input.target_utilization = Some(target);
+        self
+    }
+
+    /// Separator preset trading response time for strip length
+    pub fn quality(mut self, quality: Quality) -> Self {
+        self.input.quality = Some(quality);
+        self
+    }
+
+    /// Length of one stock sheet, enables demand chunking
+    pub fn sheet_length_mm(mut self, length: f64) -> Self {
+        self.input.sheet_length = Some(length);
+        self
+    }
+
+    /// Demand of a single item above which it is cut on repeated sheets
+    pub fn chunk_demand_threshold(mut self, demand: usize) -> Self {
+        self.input.chunk_demand_threshold = Some(demand);
+        self
+    }
+
+    /// Largest stock sheet of the material, the strip height must fit it
+    pub fn stock_sheet(mut self, sheet: StockSheet) -> Self {
+        self.input.stock_sheet = Some(sheet);
+        self
+    }
+
+    /// Strip height accepted without stock data, e.g. for coil stock
+    pub fn max_strip_height_mm(mut self, height: f64) -> Self {
+        self.input.max_strip_height = Some(height);
+        self
+    }
+
+    /// Fail instead of warning on a strip height the stock cannot hold
+    pub fn strict_strip_height(mut self, enabled: bool) -> Self {
+        self.input.strict_strip_height = Some(enabled);
+        self
+    }
+
+    /// Nest the items that import when some do not, instead of failing
+    pub fn skip_bad_items(mut self, enabled: bool) -> Self {
+        self.input.skip_bad_items = Some(enabled);
+        self
+    }
+
+    /// Continue from the placements of a stored output of the instance
+    pub fn warm_start(mut self, warm_start: WarmStart) -> Self {
+        self.input.warm_start = Some(warm_start);
+        self
+    }
+
+    /// The machine may cut parts mirrored; the optimizer may flip them
+    pub fn allow_mirror(mut self, enabled: bool) -> Self {
+        self.input.allow_mirror = Some(enabled);
+        self
+    }
+
+    /// Allow or forbid mirroring one item, whatever `allow_mirror` says
+    pub fn item_allow_mirror(mut self, item_id: u64, enabled: bool) -> Self {
+        self.input
+            .item_allow_mirror
+            .get_or_insert_with(Default::default)
+            .insert(item_id, enabled);
+        self
+    }
+
+    /// Nest mirror image pairs as one item (needs `allow_mirror`)
+    pub fn consolidate_mirror_pairs(mut self, enabled: bool) -> Self {
+        self.input.consolidate_mirror_pairs = Some(enabled);
+        self
+    }
+
+    /// Print where every setting came from and return it with the output
+    pub fn debug_trace(mut self, enabled: bool) -> Self {
+        self.input.debug_trace = Some(enabled);
+        self
+    }
+
+    /// Take all optimizer settings from a configuration
+    ///
+    /// Used to replay a run from its `resolved_config`.
+    pub fn config(mut self, config: &NestingConfig) -> Self {
+        self.input.time_limit = config.time_limit;
+        self.input.seed = config.seed;
+        self.input.use_early_termination = Some(config.use_early_termination);
+        self.input.n_workers = Some(config.n_workers);
+        self.input.keep_out_zones = Some(config.keep_out_zones.clone());
+        self.input.holes = Some(config.holes.clone());
+        self.input.hole_nesting = Some(config.hole_nesting);
+        self.input.separation = Some(config.separation);
+        self.input.kerf_width = Some(config.kerf_width);
+        self.input.rotations = config.rotations.clone();
+        self.input.respect_grain = Some(config.respect_grain);
+        self.input.bend_angle_tolerance = Some(config.bend_angle_tolerance);
+        self.input.pre_orient_items = Some(config.pre_orient_items);
+        self.input.small_item_area = Some(config.small_item_area);
+        self.input.filler_items = Some(config.filler_items.clone());
+        self.input.max_filler_count = Some(config.max_filler_count);
+        self.input.max_filler_area = config.max_filler_area;
+        self.input.sheet_mode = Some(config.sheet_mode);
+        self.input.skip_compression = Some(config.skip_compression);
+        self.input.compress_only = Some(config.compress_only);
+        self.input.container_polygon = config.container_polygon.clone();
+        self.input.quality = Some(config.quality);
+        self
+    }
+
+    /// Validate and return the input
+    pub fn build(self) -> Result<NestingInput, Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        if self.input.json_input.trim().is_empty() {
+            issues.push(ValidationIssue::new("json_input", "instance JSON is empty"));
+        }
+        issues.extend(validate_input(&self.input));
+        issues.retain(ValidationIssue::is_error);
+
+        if issues.is_empty() {
+            Ok(self.input)
+        } else {
+            Err(issues)
+        }
+    }
+}
+
+/// Builder for `NestingConfig`, see the module docs for examples
+#[derive(Debug, Clone, Default)]
+pub struct NestingConfigBuilder {
+    input: NestingInputBuilder,
+}
+
+impl NestingConfig {
+    /// Start building a configuration with all engine defaults
+    pub fn builder() -> NestingConfigBuilder {
+        NestingConfigBuilder::default()
+    }
+}
+
+impl NestingConfigBuilder {
+    /// Time budget of the optimizer
+    pub fn time_limit_secs(self, secs: u64) -> Self {
+        self.map(|input| input.time_limit_secs(secs))
+    }
+
+    /// Seed for a reproducible run
+    pub fn seed(self, seed: u64) -> Self {
+        self.map(|input| input.seed(seed))
+    }
+
+    /// Number of worker threads
+    pub fn workers(self, workers: usize) -> Self {
+        self.map(|input| input.workers(workers))
+    }
+
+    /// Minimum distance between parts and to the strip edge
+    pub fn separation_mm(self, separation: f64) -> Self {
+        self.map(|input| input.separation_mm(separation))
+    }
+
+    /// Width of the cut, part outlines are grown by half of it
+    pub fn kerf_width_mm(self, kerf_width: f64) -> Self {
+        self.map(|input| input.kerf_width_mm(kerf_width))
+    }
+
+    /// Orientations allowed for every part, replacing the instance's
+    pub fn rotations(self, degrees: Vec<f64>) -> Self {
+        self.map(|input| input.rotations(degrees))
+    }
+
+    /// Keep the bend lines of bent parts along the rolling direction
+    pub fn respect_grain(self, enabled: bool) -> Self {
+        self.map(|input| input.respect_grain(enabled))
+    }
+
+    /// Max angle between bend lines and the rolling direction in degrees
+    pub fn bend_angle_tolerance(self, degrees: f64) -> Self {
+        self.map(|input| input.bend_angle_tolerance(degrees))
+    }
+
+    /// Turn parts drawn at an angle to their smallest bounding box first
+    pub fn pre_orient_items(self, enabled: bool) -> Self {
+        self.map(|input| input.pre_orient_items(enabled))
+    }
+
+    /// Net area in mm² below which parts are placed after packing (0 = never)
+    pub fn small_item_area(self, area: f64) -> Self {
+        self.map(|input| input.small_item_area(area))
+    }
+
+    /// Stock parts dropped into the gaps after nesting
+    pub fn filler_items(self, fillers: Vec<FillerItem>) -> Self {
+        self.map(|input| input.filler_items(fillers))
+    }
+
+    /// Nest into one sheet of `width` mm along the strip
+    pub fn fixed_sheet_mm(self, width: f64) -> Self {
+        self.map(|input| input.fixed_sheet_mm(width))
+    }
+
+    /// Spend the whole time limit exploring, without compression
+    pub fn skip_compression(self, skip: bool) -> Self {
+        self.map(|input| input.skip_compression(skip))
+    }
+
+    /// Separator preset trading response time for strip length
+    pub fn quality(self, quality: Quality) -> Self {
+        self.map(|input| input.quality(quality))
+    }
+
+    /// Stop when the layout stops improving
+    pub fn early_termination(self, enabled: bool) -> Self {
+        self.map(|input| input.early_termination(enabled))
+    }
+
+    /// Areas of the sheet where no part may be placed
+    pub fn keep_out_zones(self, zones: Vec<KeepOutZone>) -> Self {
+        self.map(|input| input.keep_out_zones(zones))
+    }
+
+    /// Defect zones of the sheet no part may overlap
+    pub fn holes(self, holes: Vec<Polygon>) -> Self {
+        self.map(|input| input.holes(holes))
+    }
+
+    /// Place small parts inside the holes of larger parts
+    pub fn hole_nesting(self, enabled: bool) -> Self {
+        self.map(|input| input.hole_nesting(enabled))
+    }
+
+    /// Validate and return the configuration
+    pub fn build(self) -> Result<NestingConfig, Vec<ValidationIssue>> {
+        let mut issues = validate_input(&self.input.input);
+        issues.retain(ValidationIssue::is_error);
+        if issues.is_empty() {
+            Ok(NestingConfig::from(&self.input.input))
+        } else {
+            Err(issues)
+        }
+    }
+
+    fn map(self, f: impl FnOnce(NestingInputBuilder) -> NestingInputBuilder) -> Self {
+        Self {
+            input: f(self.input),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input_with_strip_height(strip_height: f64) -> NestingInputBuilder {
+        NestingInput::builder().json_input(
+            serde_json::json!({ "name": "typo", "strip_height": strip_height, "items": [] })
+                .to_string(),
+        )
+    }
+
+    #[test]
+    fn test_strip_height_typo_against_stock() {
+        let sheet = StockSheet {
+            length: 3000.0,
+            width: 1500.0,
+        };
+        let input = input_with_strip_height(15000.0)
+            .stock_sheet(sheet)
+            .build()
+            .expect("a warning does not stop the run");
+        let issues = validate_input(&input);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "strip_height");
+        assert_eq!(issues[0].severity, IssueSeverity::Warning);
+        assert!(
+            issues[0].message.contains("3000 × 1500 mm"),
+            "{}",
+            issues[0]
+        );
+
+        let strict = input_with_strip_height(15000.0)
+            .stock_sheet(sheet)
+            .strict_strip_height(true)
+            .build()
+            .unwrap_err();
+        assert_eq!(strict.len(), 1);
+        assert!(strict[0].is_error());
+
+        let fits = input_with_strip_height(1500.0)
+            .stock_sheet(sheet)
+            .build()
+            .unwrap();
+        assert!(validate_input(&fits).is_empty());
+    }
+
+    #[test]
+    fn test_strip_height_sanity_max_without_stock() {
+        let typo = input_with_strip_height(15000.0).build().unwrap();
+        let issues = validate_input(&typo);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("4000 mm"), "{}", issues[0]);
+
+        // Coil stock, the setting raises the limit
+        let coil = input_with_strip_height(5000.0)
+            .max_strip_height_mm(6000.0)
+            .build()
+            .unwrap();
+        assert!(validate_input(&coil).is_empty());
+
+        let zero = input_with_strip_height(1500.0)
+            .max_strip_height_mm(0.0)
+            .build();
+        assert_eq!(zero.unwrap_err()[0].field, "max_strip_height");
+    }
+
+    #[test]
+    fn test_target_utilization_range() {
+        for target in [0.0, -0.5, 1.2, f64::NAN] {
+            let issues = input_with_strip_height(1500.0)
+                .target_utilization(target)
+                .build()
+                .unwrap_err();
+            assert_eq!(issues[0].field, "target_utilization");
+        }
+        let full = input_with_strip_height(1500.0)
+            .target_utilization(1.0)
+            .build()
+            .unwrap();
+        assert!(validate_input(&full).is_empty());
+    }
+
+    #[test]
+    fn test_defect_zone_off_strip_is_a_warning() {
+        let scratch = vec![(100.0, 1400.0), (200.0, 1400.0), (200.0, 1600.0)];
+        let input = input_with_strip_height(1500.0)
+            .holes(vec![scratch])
+            .build()
+            .expect("a zone off the strip does not stop the run");
+        let issues = validate_input(&input);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "holes");
+        assert_eq!(issues[0].severity, IssueSeverity::Warning);
+
+        let degenerate = input_with_strip_height(1500.0)
+            .holes(vec![vec![(0.0, 0.0), (10.0, 0.0)]])
+            .build();
+        assert_eq!(degenerate.unwrap_err()[0].field, "holes");
+    }
+}
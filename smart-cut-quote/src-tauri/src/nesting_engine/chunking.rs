@@ -0,0 +1,372 @@
+//! Demand chunking for items ordered by the thousand
+//!
+//! 10,000 copies of a small clip overwhelm the optimizer although the
+//! answer is "N per sheet × K sheets". When one item's demand is above
+//! the threshold and the item makes up most of the demanded area, one
+//! sheet of it is grid-filled (no optimizer), the number of identical
+//! sheets is computed from that, and only the remainder (the copies left
+//! over plus every other item) is nested on the strip.
+//!
+//! The output keeps describing the nested remainder; the identical
+//! sheets are reported in `NestingOutput::repeated_sheet`.
+
+use super::geometry::{bounding_box, open_ring, rotate_polygon, translate_polygon, DisplayUnits};
+use super::grain::grain_orientations;
+use super::instance::{parse_instance, InstanceItem};
+use super::keep_out::KeepOutZone;
+use super::nesting::NestingConfig;
+use super::serializer::PlacedItem;
+use serde::{Deserialize, Serialize};
+
+/// Demand of a single item above which it is chunked, when the input
+/// does not set one
+pub const DEFAULT_CHUNK_DEMAND_THRESHOLD: usize = 1000;
+
+/// Share of the demanded part area the item must make up to be chunked
+pub const CHUNK_MIN_AREA_SHARE: f64 = 0.8;
+
+/// Rounding slack when fitting grid cells on the sheet (mm)
+const FIT_EPSILON: f64 = 1e-9;
+
+/// Identical full sheets of one item, cut `multiplier` times
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepeatedSheet {
+    pub item_id: usize,
+    /// Number of identical sheets
+    pub multiplier: usize,
+    pub copies_per_sheet: usize,
+    /// Sheet size along the strip
+    pub sheet_length: f64,
+    /// Strip height of the instance
+    pub sheet_height: f64,
+    /// Placements of one sheet, in sheet coordinates
+    pub layouts: Vec<PlacedItem>,
+    /// Net part area over the area of one sheet (0.0 - 1.0)
+    pub utilization: f64,
+}
+
+impl RepeatedSheet {
+    /// Copies cut on all repeated sheets together
+    pub fn total_copies(&self) -> usize {
+        self.multiplier * self.copies_per_sheet
+    }
+
+    /// Sheet given in `from` units, in `units`
+    pub fn in_units(mut self, units: DisplayUnits, from: DisplayUnits) -> Self {
+        self.sheet_length = units.length_from(self.sheet_length, from);
+        self.sheet_height = units.length_from(self.sheet_height, from);
+        for placed in &mut self.layouts {
+            (placed.position_x, placed.position_y) =
+                units.point_from((placed.position_x, placed.position_y), from);
+        }
+        self
+    }
+}
+
+/// Outcome of chunking: the repeated sheets and the instance left to nest
+#[derive(Debug, Clone)]
+pub struct ChunkPlan {
+    pub sheet: RepeatedSheet,
+    /// Instance JSON with the demand of the chunked item reduced to the
+    /// copies the repeated sheets do not cover
+    pub remainder_json: String,
+}
+
+/// Plan the repeated sheets of the dominating item, if chunking applies
+///
+/// Returns None when no item is above `threshold`, the item does not
+/// make up `CHUNK_MIN_AREA_SHARE` of the demanded area, or one sheet does
+/// not hold enough copies to leave a repeated sheet. At least one copy is
+/// always left for the remainder, so the strip has something to nest.
+pub fn plan_demand_chunking(
+    json_str: &str,
+    config: &NestingConfig,
+    sheet_length: f64,
+    threshold: usize,
+) -> Result<Option<ChunkPlan>, String> {
+    let instance = parse_instance(json_str)?;
+    let Some(item) = instance.items.iter().max_by_key(|item| item.demand) else {
+        return Ok(None);
+    };
+    let demand = item.demand as usize;
+    if demand <= threshold {
+        return Ok(None);
+    }
+
+    let total_area: f64 = instance
+        .items
+        .iter()
+        .map(|item| item.net_area() * item.demand as f64)
+        .sum();
+    let share = if total_area > 0.0 {
+        item.net_area() * demand as f64 / total_area
+    } else {
+        0.0
+    };
+    if share <= CHUNK_MIN_AREA_SHARE {
+        println!(
+            "🧩 Item {} ({} copies) is only {:.0}% of the part area, not chunked",
+            item.id,
+            demand,
+            share * 100.0
+        );
+        return Ok(None);
+    }
+
+    let layouts = fill_sheet(
+        item,
+        &orientations(item, config),
+        (sheet_length, instance.strip_height),
+        config.cut_separation() + item.extra_separation,
+        &config.keep_out_zones,
+    );
+    let copies_per_sheet = layouts.len();
+    if copies_per_sheet == 0 {
+        return Ok(None);
+    }
+    let multiplier = (demand - 1) / copies_per_sheet;
+    if multiplier == 0 {
+        return Ok(None);
+    }
+    let remainder = demand - multiplier * copies_per_sheet;
+    println!(
+        "🧩 Item {}: {} per sheet × {} sheets, {} copies left to nest",
+        item.id, copies_per_sheet, multiplier, remainder
+    );
+
+    let sheet_area = sheet_length * instance.strip_height;
+    let sheet = RepeatedSheet {
+        item_id: item.id as usize,
+        multiplier,
+        copies_per_sheet,
+        sheet_length,
+        sheet_height: instance.strip_height,
+        layouts,
+        utilization: item.net_area() * copies_per_sheet as f64 / sheet_area,
+    };
+    Ok(Some(ChunkPlan {
+        sheet,
+        remainder_json: with_demand(json_str, item.id, remainder)?,
+    }))
+}
+
+/// Orientations the run allows for the item (both axes when unrestricted)
+pub(super) fn orientations(item: &InstanceItem, config: &NestingConfig) -> Vec<f64> {
+    let allowed = config
+        .rotations
+        .as_deref()
+        .or(item.allowed_orientations.as_deref());
+    match item.bend_direction_deg {
+        Some(bend) if config.respect_grain => {
+            grain_orientations(bend, config.bend_angle_tolerance, allowed)
+        }
+        _ => allowed.map_or_else(|| vec![0.0, 90.0], <[f64]>::to_vec),
+    }
+}
+
+/// Grid-fill one sheet with copies of the item
+///
+/// Bounding boxes are laid out in rows and columns, `clearance` apart and
+/// from the sheet edges; cells overlapping a keep-out zone stay empty.
+/// The orientation giving the most copies wins.
+fn fill_sheet(
+    item: &InstanceItem,
+    orientations: &[f64],
+    (length, height): (f64, f64),
+    clearance: f64,
+    keep_out_zones: &[KeepOutZone],
+) -> Vec<PlacedItem> {
+    let mut best: Vec<PlacedItem> = Vec::new();
+    for &rotation_degrees in orientations {
+        let rotated = rotate_polygon(open_ring(&item.outer), rotation_degrees);
+        let Some((min_x, min_y, max_x, max_y)) = bounding_box(&rotated) else {
+            continue;
+        };
+        let (width, depth) = (max_x - min_x, max_y - min_y);
+        if width + clearance <= 0.0 || depth + clearance <= 0.0 {
+            continue;
+        }
+        let mut placements = Vec::new();
+        let mut x = clearance;
+        while x + width + clearance <= length + FIT_EPSILON {
+            let mut y = clearance;
+            while y + depth + clearance <= height + FIT_EPSILON {
+                let (dx, dy) = (x - min_x, y - min_y);
+                let outline = translate_polygon(&rotated, dx, dy);
+                if !keep_out_zones.iter().any(|zone| zone.overlaps(&outline)) {
+                    placements.push(PlacedItem {
+                        item_id: item.id as usize,
+                        rotation_degrees,
+                        position_x: dx,
+                        position_y: dy,
+                        host_item_id: None,
+                        outline: None,
+                        display_outline: None,
+                        metadata: item.metadata.clone(),
+                        bend_line_angle_deg: None,
+                        post_placed: false,
+                        mirrored: false,
+                        locked: false,
+                        rotation_radians: None,
+                        filler: false,
+                    });
+                }
+                y += depth + clearance;
+            }
+            x += width + clearance;
+        }
+        if placements.len() > best.len() {
+            best = placements;
+        }
+    }
+    best
+}
+
+/// Instance JSON with the demand of one item replaced
+fn with_demand(json_str: &str, item_id: u64, demand: usize) -> Result<String, String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+        .ok_or("Instance JSON has no items array")?;
+    for item in items.iter_mut().filter_map(|item| item.as_object_mut()) {
+        if item.get("id").and_then(|id| id.as_u64()) == Some(item_id) {
+            item.insert("demand".to_string(), demand.into());
+        }
+    }
+    serde_json::to_string(&value).map_err(|e| format!("Failed to serialize instance: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 20 x 10 clip with `clips` copies and a 100 x 100 plate with `plates`
+    fn instance(clips: u64, plates: u64) -> String {
+        serde_json::json!({
+            "name": "clips",
+            "strip_height": 100.0,
+            "items": [
+                {
+                    "id": 0,
+                    "demand": clips,
+                    "allowed_orientations": [0.0],
+                    "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 20.0, "height": 10.0 } }
+                },
+                {
+                    "id": 1,
+                    "demand": plates,
+                    "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 100.0, "height": 100.0 } }
+                }
+            ]
+        })
+        .to_string()
+    }
+
+    fn config(separation: f64) -> NestingConfig {
+        NestingConfig {
+            separation,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_dominating_item_is_split_into_repeated_sheets_and_remainder() {
+        // 1000 mm sheet, 5 mm apart: 39 columns x 6 rows of 20 x 10 clips
+        let json = instance(10_000, 1);
+        let plan = plan_demand_chunking(&json, &config(5.0), 1000.0, 1000)
+            .unwrap()
+            .expect("chunked");
+        assert_eq!(plan.sheet.copies_per_sheet, 39 * 6);
+        assert_eq!(plan.sheet.multiplier, 42);
+
+        let remainder = parse_instance(&plan.remainder_json).unwrap();
+        assert_eq!(remainder.items[0].demand as usize, 10_000 - 42 * 234);
+        assert_eq!(remainder.items[1].demand, 1);
+        assert_eq!(
+            plan.sheet.total_copies() + remainder.items[0].demand as usize,
+            10_000
+        );
+    }
+
+    #[test]
+    fn test_repeated_sheet_respects_separation_and_keep_out() {
+        let zone = KeepOutZone {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+            label: None,
+        };
+        let config = NestingConfig {
+            separation: 5.0,
+            keep_out_zones: vec![zone.clone()],
+            ..Default::default()
+        };
+        let plan = plan_demand_chunking(&instance(5_000, 0), &config, 500.0, 1000)
+            .unwrap()
+            .expect("chunked");
+        let sheet = &plan.sheet;
+        assert!(sheet.layouts.iter().all(|placed| {
+            placed.position_x >= 5.0
+                && placed.position_y >= 5.0
+                && placed.position_x + 20.0 <= sheet.sheet_length - 5.0 + 1e-9
+                && placed.position_y + 10.0 <= sheet.sheet_height - 5.0 + 1e-9
+        }));
+        assert!(sheet.layouts.iter().all(|placed| {
+            let outline = translate_polygon(
+                &[(0.0, 0.0), (20.0, 0.0), (20.0, 10.0), (0.0, 10.0)],
+                placed.position_x,
+                placed.position_y,
+            );
+            !zone.overlaps(&outline)
+        }));
+        // Cells 25 mm wide and 15 mm deep never overlap each other
+        for (i, a) in sheet.layouts.iter().enumerate() {
+            for b in &sheet.layouts[i + 1..] {
+                assert!(
+                    (a.position_x - b.position_x).abs() >= 25.0 - 1e-9
+                        || (a.position_y - b.position_y).abs() >= 15.0 - 1e-9
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_chunking_guard_rails() {
+        // Below the threshold
+        assert!(
+            plan_demand_chunking(&instance(900, 0), &config(5.0), 1000.0, 1000)
+                .unwrap()
+                .is_none()
+        );
+        // 2000 clips are 400,000 mm², the 50 plates 500,000 mm²
+        assert!(
+            plan_demand_chunking(&instance(2_000, 50), &config(5.0), 1000.0, 1000)
+                .unwrap()
+                .is_none()
+        );
+        // Fewer copies than one sheet holds: nothing to repeat
+        assert!(
+            plan_demand_chunking(&instance(200, 0), &config(5.0), 1000.0, 100)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_best_orientation_fills_the_sheet() {
+        let json = instance(10_000, 0).replace("[0.0]", "[0.0, 90.0]");
+        // 95 x 100 mm sheet without separation: 4 x 10 lying, 9 x 5 upright
+        let plan = plan_demand_chunking(&json, &config(0.0), 95.0, 1000)
+            .unwrap()
+            .expect("chunked");
+        assert_eq!(plan.sheet.copies_per_sheet, 45);
+        assert!(plan
+            .sheet
+            .layouts
+            .iter()
+            .all(|placed| placed.rotation_degrees == 90.0));
+    }
+}
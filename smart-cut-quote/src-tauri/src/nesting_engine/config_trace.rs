@@ -0,0 +1,342 @@
+//! Where each setting of a run came from
+//!
+//! A setting can come from a batch group, the batch default, the app
+//! settings (`ConfigOverrides::resolve`), the material, machine or
+//! cutting profile (filled in by the Tauri commands), the explicit input field or the
+//! engine default. The helpers applying these precedence rules record
+//! every candidate they looked at while resolving, so a run can answer
+//! "why 4 mm separation". With `debug_trace` the trace is printed and
+//! returned in `NestingOutput::config_trace`.
+
+use super::geometry::DisplayUnits;
+use super::keep_out::KeepOutZone;
+use serde::{Deserialize, Serialize};
+
+/// Layer a candidate value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    /// Settings of a batch group
+    Group,
+    /// Settings shared by the groups of a batch
+    BatchDefault,
+    /// App settings table
+    AppSettings,
+    /// Material of the run (e.g. its minimum web)
+    Material,
+    /// Machine profile of the run (clamp zones)
+    MachineProfile,
+    /// Cutting profile of the run's machine, material and thickness
+    CuttingProfile,
+    /// Field of the nesting input
+    Input,
+    /// Input item (e.g. `extra_separation` in its metadata)
+    Item,
+    /// Engine default
+    Default,
+    /// Decided while running (worker fallback, generated seed)
+    Runtime,
+}
+
+/// One value considered for a setting
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceCandidate {
+    pub source: ConfigSource,
+    /// None when the layer does not set it
+    pub value: Option<serde_json::Value>,
+    /// Whether this value made it into the resolved setting
+    pub used: bool,
+}
+
+/// Resolution of one setting
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterTrace {
+    pub parameter: String,
+    /// Value the run used (null when unset)
+    pub value: serde_json::Value,
+    /// Candidates in precedence order
+    pub candidates: Vec<TraceCandidate>,
+}
+
+/// Resolution of every traced setting, in resolution order
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigTrace {
+    pub parameters: Vec<ParameterTrace>,
+}
+
+fn json<T: Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or(serde_json::Value::Null)
+}
+
+/// Value given in `from` units of a traced setting, in `units`
+fn value_in_units(
+    parameter: &str,
+    value: &serde_json::Value,
+    units: DisplayUnits,
+    from: DisplayUnits,
+) -> serde_json::Value {
+    match parameter {
+        "separation" | "min_web" | "sheet_length" | "kerf" => value
+            .as_f64()
+            .map_or(value.clone(), |mm| json(&units.length_from(mm, from))),
+        // Per-item separations: "separation[item 3]"
+        name if name.starts_with("separation[") => value
+            .as_f64()
+            .map_or(value.clone(), |mm| json(&units.length_from(mm, from))),
+        "small_item_area" => value
+            .as_f64()
+            .map_or(value.clone(), |mm2| json(&units.area_from(mm2, from))),
+        "keep_out_zones" => match serde_json::from_value::<Vec<KeepOutZone>>(value.clone()) {
+            Ok(zones) => json(
+                &zones
+                    .into_iter()
+                    .map(|zone| zone.in_units(units, from))
+                    .collect::<Vec<_>>(),
+            ),
+            Err(_) => value.clone(),
+        },
+        _ => value.clone(),
+    }
+}
+
+impl ConfigTrace {
+    /// Resolution of a setting, if it was traced
+    pub fn get(&self, parameter: &str) -> Option<&ParameterTrace> {
+        self.parameters
+            .iter()
+            .find(|trace| trace.parameter == parameter)
+    }
+
+    /// Source of the value a setting resolved to
+    pub fn source(&self, parameter: &str) -> Option<ConfigSource> {
+        self.get(parameter)?
+            .candidates
+            .iter()
+            .find(|candidate| candidate.used)
+            .map(|candidate| candidate.source)
+    }
+
+    /// First set candidate wins; every candidate is recorded
+    ///
+    /// A setting resolved earlier (e.g. by the batch layers) keeps its
+    /// chain in place of the `Input` candidate it was passed on as, unless
+    /// the input field was changed since.
+    pub fn pick<T: Clone + Serialize>(
+        &mut self,
+        parameter: &str,
+        candidates: &[(ConfigSource, Option<T>)],
+    ) -> Option<T> {
+        let winner = candidates.iter().position(|(_, value)| value.is_some());
+        let value = winner.and_then(|index| candidates[index].1.clone());
+        self.record(
+            parameter,
+            json(&value),
+            candidates
+                .iter()
+                .enumerate()
+                .map(|(index, (source, candidate))| (*source, candidate, Some(index) == winner)),
+        );
+        value
+    }
+
+    /// Record a setting combined from several candidates (all set ones are used)
+    pub fn merge<T: Serialize, V: Serialize>(
+        &mut self,
+        parameter: &str,
+        candidates: &[(ConfigSource, Option<T>)],
+        value: &V,
+    ) {
+        self.record(
+            parameter,
+            json(value),
+            candidates
+                .iter()
+                .map(|(source, candidate)| (*source, candidate, candidate.is_some())),
+        );
+    }
+
+    fn record<'a, T: Serialize + 'a>(
+        &mut self,
+        parameter: &str,
+        value: serde_json::Value,
+        candidates: impl Iterator<Item = (ConfigSource, &'a Option<T>, bool)>,
+    ) {
+        let earlier = self.take(parameter);
+        let mut recorded = Vec::new();
+        for (source, candidate, used) in candidates {
+            let candidate = candidate.as_ref().map(json);
+            match (&earlier, source) {
+                (Some(earlier), ConfigSource::Input)
+                    if candidate.as_ref().unwrap_or(&serde_json::Value::Null) == &earlier.value =>
+                {
+                    recorded.extend(earlier.candidates.iter().map(|earlier| TraceCandidate {
+                        used: used && earlier.used,
+                        ..earlier.clone()
+                    }));
+                }
+                _ => recorded.push(TraceCandidate {
+                    source,
+                    value: candidate,
+                    used,
+                }),
+            }
+        }
+        self.parameters.push(ParameterTrace {
+            parameter: parameter.to_string(),
+            value,
+            candidates: recorded,
+        });
+    }
+
+    /// Record a value decided while running, replacing the resolved one
+    pub fn runtime<T: Serialize>(&mut self, parameter: &str, value: &T) {
+        let mut trace = self.take(parameter).unwrap_or_else(|| ParameterTrace {
+            parameter: parameter.to_string(),
+            value: serde_json::Value::Null,
+            candidates: Vec::new(),
+        });
+        for candidate in &mut trace.candidates {
+            candidate.used = false;
+        }
+        trace.value = json(value);
+        trace.candidates.push(TraceCandidate {
+            source: ConfigSource::Runtime,
+            value: Some(trace.value.clone()),
+            used: true,
+        });
+        self.parameters.push(trace);
+    }
+
+    fn take(&mut self, parameter: &str) -> Option<ParameterTrace> {
+        let index = self
+            .parameters
+            .iter()
+            .position(|trace| trace.parameter == parameter)?;
+        Some(self.parameters.remove(index))
+    }
+
+    /// Trace given in `from` units, in `units`
+    pub fn in_units(mut self, units: DisplayUnits, from: DisplayUnits) -> Self {
+        for trace in &mut self.parameters {
+            trace.value = value_in_units(&trace.parameter, &trace.value, units, from);
+            for candidate in &mut trace.candidates {
+                if let Some(value) = candidate.value.as_mut() {
+                    *value = value_in_units(&trace.parameter, value, units, from);
+                }
+            }
+        }
+        self
+    }
+
+    /// One line per setting: value, then the candidates (`*` = used)
+    pub fn explain(&self) -> String {
+        let mut text = String::new();
+        for trace in &self.parameters {
+            let candidates: Vec<String> = trace
+                .candidates
+                .iter()
+                .map(|candidate| {
+                    format!(
+                        "{}{}={}",
+                        if candidate.used { "*" } else { "" },
+                        json(&candidate.source).as_str().unwrap_or_default(),
+                        candidate
+                            .value
+                            .as_ref()
+                            .map(|value| value.to_string())
+                            .unwrap_or_else(|| "unset".to_string())
+                    )
+                })
+                .collect();
+            text.push_str(&format!(
+                "{} = {}  <- {}\n",
+                trace.parameter,
+                trace.value,
+                candidates.join(", ")
+            ));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::{ConfigOverrides, NestingConfig};
+
+    #[test]
+    fn test_earlier_chain_replaces_input_candidate() {
+        let mut trace = ConfigTrace::default();
+        let separation = trace.pick(
+            "separation",
+            &[
+                (ConfigSource::Group, None),
+                (ConfigSource::BatchDefault, Some(4.0)),
+                (ConfigSource::AppSettings, Some(5.0)),
+            ],
+        );
+        assert_eq!(separation, Some(4.0));
+
+        let separation = trace.pick(
+            "separation",
+            &[
+                (ConfigSource::Input, separation),
+                (ConfigSource::Default, Some(1.0)),
+            ],
+        );
+        assert_eq!(separation, Some(4.0));
+
+        let resolved = trace.get("separation").unwrap();
+        assert_eq!(resolved.value, serde_json::json!(4.0));
+        let sources: Vec<(ConfigSource, bool)> = resolved
+            .candidates
+            .iter()
+            .map(|candidate| (candidate.source, candidate.used))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                (ConfigSource::Group, false),
+                (ConfigSource::BatchDefault, true),
+                (ConfigSource::AppSettings, false),
+                (ConfigSource::Default, false),
+            ]
+        );
+        assert!(trace.explain().contains("*batch_default=4.0"));
+    }
+
+    #[test]
+    fn test_trace_matches_resolved_config() {
+        let group = ConfigOverrides {
+            hole_nesting: Some(true),
+            ..Default::default()
+        };
+        let batch_default = ConfigOverrides {
+            separation: Some(4.0),
+            ..Default::default()
+        };
+        let app_settings = ConfigOverrides {
+            separation: Some(2.0),
+            time_limit: Some(60),
+            ..Default::default()
+        };
+        let (resolved, config_sources) =
+            ConfigOverrides::resolve_traced(&group, &batch_default, &app_settings);
+        let mut input = resolved.into_input("{}".to_string());
+        input.config_sources = config_sources;
+        input.n_workers = Some(2);
+
+        let (config, trace) = NestingConfig::resolve(&input);
+        let values = serde_json::to_value(&config).unwrap();
+        for (parameter, value) in values.as_object().unwrap() {
+            assert_eq!(&trace.get(parameter).unwrap().value, value, "{}", parameter);
+        }
+
+        assert_eq!(config.separation, 4.0);
+        assert_eq!(trace.source("separation"), Some(ConfigSource::BatchDefault));
+        assert_eq!(trace.source("time_limit"), Some(ConfigSource::AppSettings));
+        assert_eq!(trace.source("hole_nesting"), Some(ConfigSource::Group));
+        assert_eq!(trace.source("n_workers"), Some(ConfigSource::Input));
+        assert_eq!(trace.source("small_item_area"), Some(ConfigSource::Default));
+    }
+}
@@ -0,0 +1,653 @@
+//! Consolidated nesting of parts from several quotes
+//!
+//! Production batches the approved quotes of one material into a single
+//! cutting run. The parts of each quote are merged into one instance:
+//! item IDs are renumbered, quantities kept and labels prefixed with the
+//! quote number (`Q-0012/bracket.dxf`). The returned item sources map
+//! every merged item back to its quote, so placements and material cost
+//! can be attributed per quote.
+//!
+//! For accounting the cost is also split sheet by sheet (see
+//! `allocate_sheet_costs`): each physical sheet's cost goes to the quotes
+//! with parts on it, and the waste either follows the part area or goes
+//! to an overhead bucket. Amounts are rounded to cents by the largest
+//! remainder, so the lines of a sheet add up to its cost exactly.
+
+use super::instance::parse_instance;
+use super::serializer::{NestingOutput, PlacedItem};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Parts of one quote to merge
+#[derive(Debug, Clone)]
+pub struct QuoteParts {
+    pub quote_id: String,
+    pub quote_number: String,
+    /// Instance JSON stored for the quote
+    pub instance_json: String,
+    /// Items of the instance to take (the quote's parts of one material)
+    pub item_ids: Vec<u64>,
+}
+
+/// Origin of an item of a merged instance
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ItemSource {
+    /// Item ID in the merged instance
+    pub item_id: usize,
+    pub quote_id: String,
+    pub quote_number: String,
+    /// Item ID in the quote's own instance
+    pub source_item_id: u64,
+    pub label: String,
+    pub demand: u64,
+    /// Net area of one copy (mm²)
+    pub net_area: f64,
+}
+
+/// Placed parts and material cost attributed to one quote
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct QuoteAllocation {
+    pub quote_id: String,
+    pub quote_number: String,
+    pub items_placed: usize,
+    /// Net area of the quote's placed parts (mm²)
+    pub placed_area: f64,
+    /// Fraction of the placed area of the run (0.0 - 1.0)
+    pub share: f64,
+    pub material_cost: f64,
+}
+
+/// Merge the parts of several quotes into one instance
+///
+/// The merged strip height is the smallest of the source instances, so
+/// every part still fits the sheet. Returns the instance JSON and the
+/// source of every merged item.
+pub fn merge_quote_instances(
+    name: &str,
+    quotes: &[QuoteParts],
+) -> Result<(String, Vec<ItemSource>), String> {
+    let mut strip_height = f64::INFINITY;
+    let mut items = Vec::new();
+    let mut sources = Vec::new();
+
+    for quote in quotes {
+        let parsed = parse_instance(&quote.instance_json)
+            .map_err(|e| format!("Quote {}: {}", quote.quote_number, e))?;
+        let raw: serde_json::Value = serde_json::from_str(&quote.instance_json)
+            .map_err(|e| format!("Quote {}: invalid instance JSON: {}", quote.quote_number, e))?;
+        let raw_items = raw
+            .get("items")
+            .and_then(|items| items.as_array())
+            .ok_or_else(|| format!("Quote {}: instance has no items", quote.quote_number))?;
+        strip_height = strip_height.min(parsed.strip_height);
+
+        // Items are parsed in input order, so both lists line up
+        for (item, raw_item) in parsed.items.iter().zip(raw_items) {
+            if !quote.item_ids.contains(&item.id) {
+                continue;
+            }
+            let item_id = sources.len();
+            let label = format!("{}/{}", quote.quote_number, item.display_name());
+
+            let mut merged = raw_item.clone();
+            merged["id"] = serde_json::json!(item_id);
+            merged["label"] = serde_json::json!(label);
+            items.push(merged);
+
+            sources.push(ItemSource {
+                item_id,
+                quote_id: quote.quote_id.clone(),
+                quote_number: quote.quote_number.clone(),
+                source_item_id: item.id,
+                label,
+                demand: item.demand,
+                net_area: item.net_area(),
+            });
+        }
+    }
+
+    if sources.is_empty() {
+        return Err(format!("No parts to nest for '{}'", name));
+    }
+
+    let instance = serde_json::json!({
+        "name": name,
+        "strip_height": strip_height,
+        "items": items,
+    });
+    let json = serde_json::to_string(&instance)
+        .map_err(|e| format!("Failed to serialize merged instance: {}", e))?;
+    Ok((json, sources))
+}
+
+/// Split a run's material cost between the quotes by placed net area
+///
+/// Every quote with items in `sources` gets an entry, in order of first
+/// appearance; quotes with nothing placed get a zero share.
+pub fn allocate_by_placed_area(
+    layouts: &[PlacedItem],
+    sources: &[ItemSource],
+    material_cost: f64,
+) -> Vec<QuoteAllocation> {
+    let by_item: HashMap<usize, &ItemSource> = sources
+        .iter()
+        .map(|source| (source.item_id, source))
+        .collect();
+
+    let mut allocations: Vec<QuoteAllocation> = Vec::new();
+    for source in sources {
+        if !allocations
+            .iter()
+            .any(|allocation| allocation.quote_id == source.quote_id)
+        {
+            allocations.push(QuoteAllocation {
+                quote_id: source.quote_id.clone(),
+                quote_number: source.quote_number.clone(),
+                items_placed: 0,
+                placed_area: 0.0,
+                share: 0.0,
+                material_cost: 0.0,
+            });
+        }
+    }
+
+    for placed in layouts {
+        let Some(source) = by_item.get(&placed.item_id) else {
+            continue;
+        };
+        if let Some(allocation) = allocations
+            .iter_mut()
+            .find(|allocation| allocation.quote_id == source.quote_id)
+        {
+            allocation.items_placed += 1;
+            allocation.placed_area += source.net_area;
+        }
+    }
+
+    let total_area: f64 = allocations.iter().map(|a| a.placed_area).sum();
+    if total_area > 0.0 {
+        for allocation in &mut allocations {
+            allocation.share = allocation.placed_area / total_area;
+            allocation.material_cost = material_cost * allocation.share;
+        }
+    }
+    allocations
+}
+
+/// Where the waste of a sheet is charged
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(tag = "waste", rename_all = "snake_case")]
+pub enum SheetCostPolicy {
+    /// Quotes pay the whole sheet in proportion to their part area
+    #[default]
+    Proportional,
+    /// Quotes pay their part area at the sheet's price per mm², the
+    /// rest of the sheet goes to the named bucket
+    Overhead { bucket: String },
+}
+
+/// Placed parts of one physical sheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetParts {
+    /// Item IDs of the merged instance, one per placed copy
+    pub item_ids: Vec<usize>,
+    /// Area of the sheet (mm²)
+    pub area: f64,
+}
+
+/// Cost charged to one quote, or to the overhead bucket
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CostLine {
+    /// None for the overhead bucket
+    pub quote_id: Option<String>,
+    /// Quote number, or the bucket name
+    pub label: String,
+    /// Net area of the quote's parts (mm², 0 for the bucket)
+    pub placed_area: f64,
+    pub cost: f64,
+}
+
+/// Cost of one sheet and how it is split
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SheetCostSplit {
+    pub sheet_index: usize,
+    pub cost: f64,
+    /// Quotes with parts on the sheet, then the bucket if charged
+    pub lines: Vec<CostLine>,
+}
+
+/// Material cost of a consolidated run split sheet by sheet
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SheetCostAllocation {
+    pub policy: SheetCostPolicy,
+    pub total_cost: f64,
+    pub sheets: Vec<SheetCostSplit>,
+    /// Every quote over all sheets, in order of first appearance in the
+    /// sources, then the bucket if charged; adds up to `total_cost`
+    pub quotes: Vec<CostLine>,
+}
+
+/// Physical sheets of a run and the parts on them
+///
+/// Sheets of a fixed-sheet run are taken as they are. A strip is cut
+/// into `strip_sheets` equal lengths along X, and every part counts on
+/// the length its placement position falls into. Each repeated full
+/// sheet is one more sheet.
+pub fn sheets_of_output(output: &NestingOutput, strip_sheets: usize) -> Vec<SheetParts> {
+    let item_ids = |layouts: &[PlacedItem]| layouts.iter().map(|placed| placed.item_id).collect();
+    let mut sheets: Vec<SheetParts> = if !output.sheets.is_empty() {
+        output
+            .sheets
+            .iter()
+            .map(|sheet| SheetParts {
+                item_ids: item_ids(&sheet.layouts),
+                area: output.strip_width * output.strip_height,
+            })
+            .collect()
+    } else {
+        let count = strip_sheets.max(1);
+        let length = output.strip_width / count as f64;
+        let mut sheets = vec![
+            SheetParts {
+                item_ids: Vec::new(),
+                area: length * output.strip_height,
+            };
+            count
+        ];
+        for placed in &output.layouts {
+            let index = if length > 0.0 {
+                ((placed.position_x / length).floor().max(0.0) as usize).min(count - 1)
+            } else {
+                0
+            };
+            sheets[index].item_ids.push(placed.item_id);
+        }
+        sheets
+    };
+    if let Some(repeated) = &output.repeated_sheet {
+        let sheet = SheetParts {
+            item_ids: item_ids(&repeated.layouts),
+            area: repeated.sheet_length * repeated.sheet_height,
+        };
+        sheets.extend(std::iter::repeat_n(sheet, repeated.multiplier));
+    }
+    sheets
+}
+
+/// Split `total` cents by `weights`, rounding by the largest remainder
+///
+/// The parts add up to `total` exactly; ties go to the earlier weight.
+/// All zero weights split evenly.
+fn split_cents(total: i64, weights: &[f64]) -> Vec<i64> {
+    if weights.is_empty() {
+        return Vec::new();
+    }
+    let sum: f64 = weights.iter().sum();
+    let weights: Vec<f64> = if sum > 0.0 {
+        weights.to_vec()
+    } else {
+        vec![1.0; weights.len()]
+    };
+    let sum: f64 = weights.iter().sum();
+
+    let exact: Vec<f64> = weights
+        .iter()
+        .map(|weight| total as f64 * weight / sum)
+        .collect();
+    let mut parts: Vec<i64> = exact.iter().map(|amount| amount.floor() as i64).collect();
+    let mut order: Vec<usize> = (0..parts.len()).collect();
+    order.sort_by(|&a, &b| {
+        let remainder = |i: usize| exact[i] - parts[i] as f64;
+        remainder(b).total_cmp(&remainder(a)).then(a.cmp(&b))
+    });
+    let left = total - parts.iter().sum::<i64>();
+    for &i in order.iter().cycle().take(left.max(0) as usize) {
+        parts[i] += 1;
+    }
+    parts
+}
+
+fn cents(amount: f64) -> i64 {
+    (amount * 100.0).round() as i64
+}
+
+/// Split a run's material cost sheet by sheet between the quotes
+///
+/// The total is first split between the sheets by area, then every
+/// sheet between the quotes with parts on it following `policy`. A sheet
+/// without parts is split like the whole run, or charged to the bucket.
+pub fn allocate_sheet_costs(
+    sheets: &[SheetParts],
+    sources: &[ItemSource],
+    total_cost: f64,
+    policy: &SheetCostPolicy,
+) -> SheetCostAllocation {
+    let by_item: HashMap<usize, &ItemSource> = sources
+        .iter()
+        .map(|source| (source.item_id, source))
+        .collect();
+    let mut quotes: Vec<(&str, &str)> = Vec::new();
+    for source in sources {
+        if !quotes.iter().any(|(id, _)| *id == source.quote_id) {
+            quotes.push((&source.quote_id, &source.quote_number));
+        }
+    }
+    let quote_index = |quote_id: &str| quotes.iter().position(|(id, _)| *id == quote_id);
+
+    // Placed area of every quote on every sheet
+    let areas: Vec<Vec<f64>> = sheets
+        .iter()
+        .map(|sheet| {
+            let mut areas = vec![0.0; quotes.len()];
+            for source in sheet.item_ids.iter().filter_map(|id| by_item.get(id)) {
+                if let Some(index) = quote_index(&source.quote_id) {
+                    areas[index] += source.net_area;
+                }
+            }
+            areas
+        })
+        .collect();
+    let run_areas: Vec<f64> = (0..quotes.len())
+        .map(|index| areas.iter().map(|sheet| sheet[index]).sum())
+        .collect();
+
+    let sheet_areas: Vec<f64> = sheets.iter().map(|sheet| sheet.area).collect();
+    let sheet_cents = split_cents(cents(total_cost), &sheet_areas);
+    let bucket = match policy {
+        SheetCostPolicy::Proportional => None,
+        SheetCostPolicy::Overhead { bucket } => Some(bucket.as_str()),
+    };
+
+    let mut quote_cents = vec![0i64; quotes.len()];
+    let mut bucket_cents = 0i64;
+    let mut splits = Vec::with_capacity(sheets.len());
+    for (sheet_index, (sheet, &sheet_total)) in sheets.iter().zip(&sheet_cents).enumerate() {
+        let placed: f64 = areas[sheet_index].iter().sum();
+        // Weights of the quotes, then of the bucket
+        let mut weights = match bucket {
+            None if placed > 0.0 => areas[sheet_index].clone(),
+            None => run_areas.clone(),
+            Some(_) => areas[sheet_index].clone(),
+        };
+        if bucket.is_some() {
+            weights.push((sheet.area - placed).max(0.0));
+        }
+        let parts = split_cents(sheet_total, &weights);
+
+        let mut lines = Vec::new();
+        for (index, (quote_id, quote_number)) in quotes.iter().enumerate() {
+            if areas[sheet_index][index] <= 0.0 && parts[index] == 0 {
+                continue;
+            }
+            quote_cents[index] += parts[index];
+            lines.push(CostLine {
+                quote_id: Some(quote_id.to_string()),
+                label: quote_number.to_string(),
+                placed_area: areas[sheet_index][index],
+                cost: parts[index] as f64 / 100.0,
+            });
+        }
+        if let Some(bucket) = bucket {
+            let part = parts[quotes.len()];
+            bucket_cents += part;
+            if part != 0 {
+                lines.push(CostLine {
+                    quote_id: None,
+                    label: bucket.to_string(),
+                    placed_area: 0.0,
+                    cost: part as f64 / 100.0,
+                });
+            }
+        }
+        splits.push(SheetCostSplit {
+            sheet_index,
+            cost: sheet_total as f64 / 100.0,
+            lines,
+        });
+    }
+
+    let mut totals: Vec<CostLine> = quotes
+        .iter()
+        .enumerate()
+        .map(|(index, (quote_id, quote_number))| CostLine {
+            quote_id: Some(quote_id.to_string()),
+            label: quote_number.to_string(),
+            placed_area: run_areas[index],
+            cost: quote_cents[index] as f64 / 100.0,
+        })
+        .collect();
+    if let Some(bucket) = bucket.filter(|_| bucket_cents != 0) {
+        totals.push(CostLine {
+            quote_id: None,
+            label: bucket.to_string(),
+            placed_area: 0.0,
+            cost: bucket_cents as f64 / 100.0,
+        });
+    }
+
+    SheetCostAllocation {
+        policy: policy.clone(),
+        total_cost: sheet_cents.iter().sum::<i64>() as f64 / 100.0,
+        sheets: splits,
+        quotes: totals,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(
+        quote_id: &str,
+        quote_number: &str,
+        strip_height: f64,
+        item_ids: &[u64],
+    ) -> QuoteParts {
+        let instance = serde_json::json!({
+            "name": quote_id,
+            "strip_height": strip_height,
+            "items": [
+                {
+                    "id": 0,
+                    "demand": 3,
+                    "dxf": "C:\\parts\\bracket.dxf",
+                    "metadata": { "fileId": "f1" },
+                    "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 10.0, "height": 10.0 } }
+                },
+                {
+                    "id": 1,
+                    "demand": 1,
+                    "label": "plate",
+                    "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 20.0, "height": 10.0 } }
+                }
+            ]
+        });
+        QuoteParts {
+            quote_id: quote_id.to_string(),
+            quote_number: quote_number.to_string(),
+            instance_json: instance.to_string(),
+            item_ids: item_ids.to_vec(),
+        }
+    }
+
+    fn placed(item_id: usize) -> PlacedItem {
+        PlacedItem {
+            item_id,
+            rotation_degrees: 0.0,
+            position_x: 0.0,
+            position_y: 0.0,
+            host_item_id: None,
+            outline: None,
+            display_outline: None,
+            metadata: None,
+            bend_line_angle_deg: None,
+            post_placed: false,
+            mirrored: false,
+            locked: false,
+            rotation_radians: None,
+            filler: false,
+        }
+    }
+
+    #[test]
+    fn test_merge_renumbers_and_prefixes_labels() {
+        let quotes = vec![
+            quote("a", "Q-0001", 1500.0, &[0, 1]),
+            quote("b", "Q-0002", 1250.0, &[1]),
+        ];
+        let (json, sources) = merge_quote_instances("friday", &quotes).unwrap();
+
+        let merged = parse_instance(&json).unwrap();
+        assert_eq!(merged.strip_height, 1250.0);
+        assert_eq!(
+            merged.items.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        assert_eq!(merged.items[0].label.as_deref(), Some("Q-0001/bracket.dxf"));
+        assert_eq!(merged.items[0].demand, 3);
+        assert_eq!(
+            merged.items[0].metadata,
+            Some(serde_json::json!({ "fileId": "f1" }))
+        );
+        assert_eq!(merged.items[2].label.as_deref(), Some("Q-0002/plate"));
+
+        assert_eq!(sources[2].quote_id, "b");
+        assert_eq!(sources[2].source_item_id, 1);
+        assert_eq!(sources[2].net_area, 200.0);
+    }
+
+    #[test]
+    fn test_cost_is_allocated_by_placed_area() {
+        let quotes = vec![
+            quote("a", "Q-0001", 1500.0, &[0]),
+            quote("b", "Q-0002", 1500.0, &[1]),
+            quote("c", "Q-0003", 1500.0, &[0]),
+        ];
+        let (_, sources) = merge_quote_instances("friday", &quotes).unwrap();
+
+        // Two 100 mm² parts of quote a, one 200 mm² part of quote b, none of c
+        let layouts = vec![placed(0), placed(0), placed(1)];
+        let allocations = allocate_by_placed_area(&layouts, &sources, 120.0);
+
+        assert_eq!(allocations.len(), 3);
+        assert_eq!(allocations[0].items_placed, 2);
+        assert_eq!(allocations[0].placed_area, 200.0);
+        assert!((allocations[0].share - 0.5).abs() < 1e-12);
+        assert!((allocations[1].material_cost - 60.0).abs() < 1e-12);
+        assert_eq!(allocations[2].items_placed, 0);
+        assert_eq!(allocations[2].material_cost, 0.0);
+    }
+
+    fn three_quotes() -> Vec<ItemSource> {
+        let quotes = vec![
+            quote("a", "Q-0001", 1500.0, &[0]),
+            quote("b", "Q-0002", 1500.0, &[1]),
+            quote("c", "Q-0003", 1500.0, &[0]),
+        ];
+        merge_quote_instances("friday", &quotes).unwrap().1
+    }
+
+    #[test]
+    fn test_split_cents_adds_up_exactly() {
+        assert_eq!(split_cents(10000, &[1.0, 1.0, 1.0]), vec![3334, 3333, 3333]);
+        assert_eq!(split_cents(101, &[0.0, 0.0]), vec![51, 50]);
+        // The largest remainder wins the last cent, not the first weight
+        assert_eq!(split_cents(100, &[1.0, 2.0]), vec![33, 67]);
+        for total in [0, 1, 99, 12345, 1_000_001] {
+            let parts = split_cents(total, &[0.3, 1.7, 2.9, 0.0, 5.1]);
+            assert_eq!(parts.iter().sum::<i64>(), total);
+        }
+    }
+
+    #[test]
+    fn test_proportional_policy_splits_every_sheet_by_part_area() {
+        let sources = three_quotes();
+        // Sheet 0 carries a, b and c (100, 200, 100 mm²), sheet 1 only b
+        let sheets = vec![
+            SheetParts {
+                item_ids: vec![0, 1, 2],
+                area: 1000.0,
+            },
+            SheetParts {
+                item_ids: vec![1],
+                area: 1000.0,
+            },
+        ];
+        let allocation =
+            allocate_sheet_costs(&sheets, &sources, 100.01, &SheetCostPolicy::Proportional);
+
+        assert_eq!(allocation.total_cost, 100.01);
+        assert_eq!(allocation.sheets[0].cost, 50.01);
+        assert_eq!(allocation.sheets[1].cost, 50.0);
+        let costs: Vec<f64> = allocation.sheets[0]
+            .lines
+            .iter()
+            .map(|line| line.cost)
+            .collect();
+        assert_eq!(costs, vec![12.5, 25.01, 12.5]);
+        assert_eq!(allocation.sheets[1].lines.len(), 1);
+        assert_eq!(allocation.sheets[1].lines[0].label, "Q-0002");
+
+        for sheet in &allocation.sheets {
+            let split: i64 = sheet.lines.iter().map(|line| cents(line.cost)).sum();
+            assert_eq!(split, cents(sheet.cost));
+        }
+        let totals: Vec<f64> = allocation.quotes.iter().map(|line| line.cost).collect();
+        assert_eq!(totals, vec![12.5, 75.01, 12.5]);
+        assert!(allocation.quotes.iter().all(|line| line.quote_id.is_some()));
+    }
+
+    #[test]
+    fn test_overhead_policy_charges_the_waste_to_the_bucket() {
+        let sources = three_quotes();
+        let sheets = vec![SheetParts {
+            item_ids: vec![0, 1],
+            area: 1000.0,
+        }];
+        let policy = SheetCostPolicy::Overhead {
+            bucket: "Workshop overhead".to_string(),
+        };
+        let allocation = allocate_sheet_costs(&sheets, &sources, 90.0, &policy);
+
+        // 100 and 200 of 1000 mm² at 0.09 per mm², 700 mm² of waste
+        let lines = &allocation.sheets[0].lines;
+        assert_eq!(lines.len(), 3);
+        assert_eq!((lines[0].label.as_str(), lines[0].cost), ("Q-0001", 9.0));
+        assert_eq!((lines[1].label.as_str(), lines[1].cost), ("Q-0002", 18.0));
+        assert_eq!(lines[2].quote_id, None);
+        assert_eq!(
+            (lines[2].label.as_str(), lines[2].cost),
+            ("Workshop overhead", 63.0)
+        );
+        let total: i64 = allocation.quotes.iter().map(|line| cents(line.cost)).sum();
+        assert_eq!(total, 9000);
+        assert_eq!(allocation.quotes[2].cost, 0.0);
+    }
+
+    #[test]
+    fn test_strip_is_cut_into_equal_sheets_by_position() {
+        let mut output: NestingOutput = serde_json::from_value(serde_json::json!({
+            "instance_name": "friday",
+            "strip_width": 3000.0,
+            "strip_height": 1000.0,
+            "total_items_placed": 3,
+            "layouts": [],
+            "utilization": 0.5,
+            "computation_time_secs": 1.0,
+        }))
+        .unwrap();
+        output.layouts = [(0, 10.0), (1, 1500.0), (2, 2999.0)]
+            .into_iter()
+            .map(|(item_id, x)| PlacedItem {
+                position_x: x,
+                ..placed(item_id)
+            })
+            .collect();
+
+        let sheets = sheets_of_output(&output, 2);
+        assert_eq!(sheets.len(), 2);
+        assert_eq!(sheets[0].item_ids, vec![0]);
+        assert_eq!(sheets[1].item_ids, vec![1, 2]);
+        assert_eq!(sheets[0].area, 1500.0 * 1000.0);
+    }
+}
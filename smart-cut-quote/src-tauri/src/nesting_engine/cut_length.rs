@@ -0,0 +1,146 @@
+//! Cut length and pierce count of a layout
+//!
+//! Cutting is priced per meter of cut plus per pierce. Every placed copy
+//! is cut along its outer contour and each of its holes, and every closed
+//! contour needs one pierce. Lengths are measured on the polygons the
+//! engine nests with, so arcs count with their tessellated length, the
+//! same contours the net areas and the SVG come from.
+//!
+//! The totals cover the whole job: every sheet of a fixed-sheet run and
+//! the copies on repeated full sheets, not only the flat layout.
+
+use super::geometry::PartGeometry;
+use super::instance::InstanceItem;
+use super::serializer::{NestingOutput, PlacedItem};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Cutting of one item, a quote line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemCut {
+    pub item_id: usize,
+    /// Cut length of one copy (mm)
+    pub cut_length: f64,
+    /// Pierces of one copy, one per closed contour
+    pub pierces: usize,
+    /// Copies placed in the job
+    pub count: usize,
+}
+
+/// Placed copies of the job by item ID
+fn placed_copies(output: &NestingOutput) -> BTreeMap<usize, usize> {
+    let mut counts = BTreeMap::new();
+    let mut count = |layouts: &[PlacedItem], copies: usize| {
+        for placed in layouts {
+            *counts.entry(placed.item_id).or_insert(0) += copies;
+        }
+    };
+    // Sheet 0 of a fixed-sheet run is the flat layout
+    if output.sheets.is_empty() {
+        count(&output.layouts, 1);
+    }
+    for sheet in &output.sheets {
+        count(&sheet.layouts, 1);
+    }
+    if let Some(sheet) = &output.repeated_sheet {
+        count(&sheet.layouts, sheet.multiplier);
+    }
+    counts
+}
+
+/// Fill in the cut length and pierce totals of an output
+///
+/// Items missing from `items` are left out of the totals.
+pub fn attach_cut_totals(output: &mut NestingOutput, items: &[InstanceItem]) {
+    let cuts: Vec<ItemCut> = placed_copies(output)
+        .into_iter()
+        .filter_map(|(item_id, count)| {
+            let item = items.iter().find(|item| item.id == item_id as u64)?;
+            Some(ItemCut {
+                item_id,
+                cut_length: PartGeometry::from_polygons(&item.outer, &item.holes).perimeter(),
+                pierces: 1 + item.holes.len(),
+                count,
+            })
+        })
+        .collect();
+
+    output.total_cut_length_mm = cuts
+        .iter()
+        .map(|cut| cut.cut_length * cut.count as f64)
+        .sum();
+    output.total_pierces = cuts.iter().map(|cut| cut.pierces * cut.count).sum();
+    output.item_cuts = cuts;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::instance::parse_instance;
+    use super::super::{run_nesting_engine, NestingInput};
+    use super::*;
+
+    /// A 100 x 50 plate with two 10 x 10 holes and a circle of radius 10
+    fn instance() -> String {
+        let circle: Vec<[f64; 2]> = (0..64)
+            .map(|i| {
+                let angle = i as f64 / 64.0 * std::f64::consts::TAU;
+                [10.0 * angle.cos(), 10.0 * angle.sin()]
+            })
+            .collect();
+        serde_json::json!({
+            "name": "cut_length",
+            "strip_height": 200.0,
+            "items": [
+                {
+                    "id": 0,
+                    "demand": 2,
+                    "shape": {
+                        "type": "polygon",
+                        "data": {
+                            "outer": [[0.0, 0.0], [100.0, 0.0], [100.0, 50.0], [0.0, 50.0]],
+                            "inner": [
+                                [[10.0, 10.0], [20.0, 10.0], [20.0, 20.0], [10.0, 20.0]],
+                                [[60.0, 10.0], [70.0, 10.0], [70.0, 20.0], [60.0, 20.0]]
+                            ]
+                        }
+                    }
+                },
+                {
+                    "id": 1,
+                    "demand": 3,
+                    "shape": { "type": "simple_polygon", "data": circle }
+                }
+            ]
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_every_contour_is_cut_and_pierced() {
+        let json = instance();
+        let output = run_nesting_engine(NestingInput {
+            json_input: json.clone(),
+            time_limit: Some(1),
+            seed: Some(7),
+            use_early_termination: Some(true),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let plate = &output.item_cuts[0];
+        assert_eq!((plate.item_id, plate.pierces, plate.count), (0, 3, 2));
+        assert!((plate.cut_length - (300.0 + 2.0 * 40.0)).abs() < 1e-9);
+
+        // The tessellated circle, slightly shorter than the true one
+        let circle = &output.item_cuts[1];
+        let polygon = &parse_instance(&json).unwrap().items[1].outer;
+        let tessellated = PartGeometry::from_polygons(polygon, &[]).perimeter();
+        assert_eq!((circle.pierces, circle.count), (1, 3));
+        assert!((circle.cut_length - tessellated).abs() < 1e-9);
+        assert!(circle.cut_length < 20.0 * std::f64::consts::PI);
+
+        assert_eq!(output.total_pierces, 2 * 3 + 3);
+        let expected = 2.0 * plate.cut_length + 3.0 * circle.cut_length;
+        assert!((output.total_cut_length_mm - expected).abs() < 1e-9);
+    }
+}
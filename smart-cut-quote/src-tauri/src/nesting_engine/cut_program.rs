@@ -0,0 +1,507 @@
+//! Neutral JSON cut program for post-processors
+//!
+//! Lists every contour of a nested layout in cutting order, in machine
+//! coordinates, with a pierce point and a lead-in. The format is
+//! versioned (`CUT_PROGRAM_VERSION`) and described by
+//! `schemas/cut-program-v1.schema.json`.
+//!
+//! Sequence: parts nested into holes come first (their host's hole
+//! would otherwise drop out with them), then the remaining parts from
+//! left to right. Within a part the holes are cut before the outer
+//! contour, so the part stays held by the sheet until its last cut.
+//!
+//! The lead-in starts at the middle of a contour edge, longest edges
+//! first. Outer contours are pierced in the scrap outside the part,
+//! holes inside the hole. A pierce point must not lie in any part, and
+//! the lead-in must keep half the part separation from every other part
+//! and from the sheet edge, shortened to half its length if needed.
+//! When no edge allows that, the longest edge is used and the entry is
+//! flagged (`lead_in_clear: false`).
+
+use super::geometry::{boundary_distance, bounding_box, point_in_polygon, signed_area, Polygon};
+use super::instance::InstanceGeometry;
+use super::layout_dxf::{placed_contours, PlacedContours};
+use super::nesting::DEFAULT_ITEM_SEPARATION;
+use super::serializer::NestingOutput;
+use super::winding::{orient_contours, Winding};
+use serde::{Deserialize, Serialize};
+
+/// Identifies the format in `CutProgram::format`
+pub const CUT_PROGRAM_FORMAT: &str = "smart-cut-quote/cut-program";
+/// Bumped on every incompatible change of the format
+pub const CUT_PROGRAM_VERSION: u32 = 1;
+/// Lead-in length when the options do not set one (mm)
+pub const DEFAULT_LEAD_IN_LENGTH: f64 = 2.0;
+
+/// Where the program's origin lies and which way y points
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordinateFrame {
+    /// Nesting coordinates: origin at the bottom-left sheet corner, y up
+    #[default]
+    BottomLeft,
+    /// Origin at the top-left sheet corner, y down
+    TopLeft,
+}
+
+/// Technology of a contour
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Technology {
+    Outer,
+    Hole,
+    /// Marking only, not cut through (no contours are engraved yet)
+    Engrave,
+}
+
+/// Options for `export_cut_program`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CutProgramOptions {
+    #[serde(default)]
+    pub frame: CoordinateFrame,
+    /// Direction of the contours in the program's frame (default: CCW outer)
+    #[serde(default)]
+    pub export_winding: Winding,
+    /// Distance from pierce point to contour in mm (default: DEFAULT_LEAD_IN_LENGTH)
+    pub lead_in_length: Option<f64>,
+    /// Part separation in mm (default: the separation the layout was nested with)
+    pub separation: Option<f64>,
+}
+
+/// One contour to cut
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CutEntry {
+    /// Position in the cutting order, from 0
+    pub sequence: usize,
+    /// Index into `NestingOutput::layouts`
+    pub placement_index: usize,
+    pub item_id: usize,
+    pub technology: Technology,
+    /// Closed polyline (last point = first point) starting at the lead-in end
+    pub contour: Polygon,
+    pub pierce: (f64, f64),
+    /// Vector from the pierce point to the contour start
+    pub lead_in: (f64, f64),
+    /// False when the lead-in could not keep clear of other parts
+    pub lead_in_clear: bool,
+}
+
+/// Cut program of one sheet
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CutProgram {
+    pub format: String,
+    pub version: u32,
+    pub units: String,
+    pub frame: CoordinateFrame,
+    pub winding: Winding,
+    pub sheet_width: f64,
+    pub sheet_height: f64,
+    pub separation: f64,
+    pub lead_in_length: f64,
+    pub entries: Vec<CutEntry>,
+    /// Entries whose lead-in is not clear, as messages
+    pub warnings: Vec<String>,
+}
+
+/// Placed part with its contours and material test
+struct Part {
+    placement_index: usize,
+    is_nested: bool,
+    contours: PlacedContours,
+    bbox: (f64, f64, f64, f64),
+}
+
+impl Part {
+    /// True when the point lies in the part's material (not in a hole)
+    fn covers(&self, point: (f64, f64)) -> bool {
+        point_in_polygon(point, &self.contours.outer)
+            && !self
+                .contours
+                .holes
+                .iter()
+                .any(|hole| point_in_polygon(point, hole))
+    }
+
+    fn distance_to(&self, segment: &[(f64, f64)]) -> f64 {
+        std::iter::once(&self.contours.outer)
+            .chain(&self.contours.holes)
+            .map(|ring| boundary_distance(segment, ring))
+            .fold(f64::INFINITY, f64::min)
+    }
+}
+
+/// Build the cut program of a nested layout
+///
+/// Needs placement outlines, like the DXF export.
+pub fn cut_program(
+    output: &NestingOutput,
+    instance: &InstanceGeometry,
+    options: &CutProgramOptions,
+) -> Result<CutProgram, String> {
+    let separation = options
+        .separation
+        .or_else(|| output.resolved_config.as_ref().map(|c| c.separation))
+        .unwrap_or(DEFAULT_ITEM_SEPARATION);
+    let lead_in_length = options.lead_in_length.unwrap_or(DEFAULT_LEAD_IN_LENGTH);
+    if !(lead_in_length > 0.0 && lead_in_length.is_finite()) {
+        return Err(format!(
+            "Lead-in length must be positive, got {}",
+            lead_in_length
+        ));
+    }
+
+    // Flipping y reverses every contour, so orient for the flipped result
+    let winding = match (options.frame, options.export_winding) {
+        (CoordinateFrame::BottomLeft, winding) => winding,
+        (CoordinateFrame::TopLeft, Winding::CcwOuter) => Winding::CwOuter,
+        (CoordinateFrame::TopLeft, Winding::CwOuter) => Winding::CcwOuter,
+    };
+
+    let mut parts: Vec<Part> = placed_contours(output, instance)?
+        .into_iter()
+        .enumerate()
+        // Locked parts are on the sheet already, cut in an earlier program
+        .filter(|(placement_index, _)| !output.layouts[*placement_index].locked)
+        .map(|(placement_index, mut contours)| {
+            orient_contours(&mut contours.outer, &mut contours.holes, winding);
+            Part {
+                placement_index,
+                is_nested: output.layouts[placement_index].host_item_id.is_some(),
+                bbox: bounding_box(&contours.outer).unwrap_or_default(),
+                contours,
+            }
+        })
+        .collect();
+    parts.sort_by(|a, b| {
+        b.is_nested
+            .cmp(&a.is_nested)
+            .then(a.bbox.0.total_cmp(&b.bbox.0))
+            .then(a.bbox.1.total_cmp(&b.bbox.1))
+    });
+
+    let sheet = (output.strip_width, output.strip_height);
+    let to_frame = |(x, y): (f64, f64)| match options.frame {
+        CoordinateFrame::BottomLeft => (x, y),
+        CoordinateFrame::TopLeft => (x, output.strip_height - y),
+    };
+
+    let mut entries = Vec::new();
+    let mut warnings = Vec::new();
+    for (index, part) in parts.iter().enumerate() {
+        let contours = part
+            .contours
+            .holes
+            .iter()
+            .map(|hole| (Technology::Hole, hole))
+            .chain(std::iter::once((Technology::Outer, &part.contours.outer)));
+
+        let neighbours: Vec<&Part> = parts
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != index)
+            .map(|(_, other)| other)
+            .collect();
+
+        for (technology, ring) in contours {
+            let lead_in = plan_lead_in(
+                ring,
+                technology,
+                &neighbours,
+                sheet,
+                lead_in_length,
+                separation / 2.0,
+            );
+            let Some(lead_in) = lead_in else {
+                continue;
+            };
+
+            let sequence = entries.len();
+            if !lead_in.clear {
+                warnings.push(format!(
+                    "Entry {} (item {}, {:?}): no lead-in keeps {} mm from other parts",
+                    sequence,
+                    part.contours.item_id,
+                    technology,
+                    separation / 2.0
+                ));
+            }
+            let pierce = to_frame(lead_in.pierce);
+            let start = to_frame(lead_in.start);
+            entries.push(CutEntry {
+                sequence,
+                placement_index: part.placement_index,
+                item_id: part.contours.item_id,
+                technology,
+                contour: lead_in.contour.into_iter().map(to_frame).collect(),
+                pierce,
+                lead_in: (start.0 - pierce.0, start.1 - pierce.1),
+                lead_in_clear: lead_in.clear,
+            });
+        }
+    }
+
+    Ok(CutProgram {
+        format: CUT_PROGRAM_FORMAT.to_string(),
+        version: CUT_PROGRAM_VERSION,
+        units: "mm".to_string(),
+        frame: options.frame,
+        winding: options.export_winding,
+        sheet_width: output.strip_width,
+        sheet_height: output.strip_height,
+        separation,
+        lead_in_length,
+        entries,
+        warnings,
+    })
+}
+
+/// Chosen lead-in of a contour, in nesting coordinates
+struct LeadIn {
+    /// Closed ring starting and ending at `start`
+    contour: Polygon,
+    start: (f64, f64),
+    pierce: (f64, f64),
+    clear: bool,
+}
+
+/// Pick the edge and length of a contour's lead-in, see the module docs
+///
+/// None for degenerate contours (fewer than 3 points or no area).
+fn plan_lead_in(
+    ring: &Polygon,
+    technology: Technology,
+    neighbours: &[&Part],
+    sheet: (f64, f64),
+    length: f64,
+    clearance: f64,
+) -> Option<LeadIn> {
+    let n = ring.len();
+    let area = signed_area(ring);
+    if n < 3 || area == 0.0 {
+        return None;
+    }
+
+    let mut edges: Vec<usize> = (0..n).collect();
+    let edge_length = |i: usize| {
+        let (a, b) = (ring[i], ring[(i + 1) % n]);
+        (b.0 - a.0).hypot(b.1 - a.1)
+    };
+    edges.sort_by(|&a, &b| edge_length(b).total_cmp(&edge_length(a)));
+
+    // Unit normal pointing to the side of the pierce point
+    let pierce_at = |edge: usize, distance: f64| {
+        let (a, b) = (ring[edge], ring[(edge + 1) % n]);
+        let len = edge_length(edge);
+        let start = ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+        // Left of the edge is inside a CCW ring
+        let (mut nx, mut ny) = (-(b.1 - a.1) / len, (b.0 - a.0) / len);
+        let into_ring = area > 0.0;
+        let outward = technology == Technology::Outer;
+        if into_ring == outward {
+            nx = -nx;
+            ny = -ny;
+        }
+        (start, (start.0 + nx * distance, start.1 + ny * distance))
+    };
+
+    let is_clear = |start: (f64, f64), pierce: (f64, f64)| {
+        let inside_ring = point_in_polygon(pierce, ring);
+        let on_side = match technology {
+            Technology::Outer => !inside_ring,
+            _ => inside_ring,
+        };
+        let on_sheet = (clearance..=sheet.0 - clearance).contains(&pierce.0)
+            && (clearance..=sheet.1 - clearance).contains(&pierce.1);
+        on_side
+            && on_sheet
+            && neighbours.iter().all(|part| {
+                let (x0, y0, x1, y1) = part.bbox;
+                let far = pierce.0 < x0 - clearance - length
+                    || pierce.0 > x1 + clearance + length
+                    || pierce.1 < y0 - clearance - length
+                    || pierce.1 > y1 + clearance + length;
+                far || (!part.covers(pierce) && part.distance_to(&[pierce, start]) >= clearance)
+            })
+    };
+
+    let chosen = edges
+        .iter()
+        .filter(|&&edge| edge_length(edge) > 0.0)
+        .flat_map(|&edge| [(edge, length), (edge, length / 2.0)])
+        .find(|&(edge, distance)| {
+            let (start, pierce) = pierce_at(edge, distance);
+            is_clear(start, pierce)
+        });
+    let clear = chosen.is_some();
+    let (edge, distance) = chosen.unwrap_or((edges[0], length));
+    let (start, pierce) = pierce_at(edge, distance);
+
+    let mut contour = Vec::with_capacity(n + 2);
+    contour.push(start);
+    contour.extend((1..=n).map(|k| ring[(edge + k) % n]));
+    contour.push(start);
+
+    Some(LeadIn {
+        contour,
+        start,
+        pierce,
+        clear,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::parse_instance;
+
+    /// 100 x 50 plate with a 20 x 20 hole
+    const PLATE: &str = r#"{
+        "name": "plate",
+        "strip_height": 200,
+        "items": [{
+            "id": 0,
+            "demand": 2,
+            "shape": {"type": "polygon", "data": {
+                "outer": [[0, 0], [100, 0], [100, 50], [0, 50]],
+                "inner": [[[40, 15], [60, 15], [60, 35], [40, 35]]]
+            }}
+        }]
+    }"#;
+
+    /// Two plates side by side, 5 mm apart, along the bottom edge
+    fn output() -> NestingOutput {
+        let placed = |x: f64| {
+            serde_json::json!({
+                "item_id": 0,
+                "rotation_degrees": 0.0,
+                "position_x": 0.0,
+                "position_y": 0.0,
+                "outline": [[x, 5.0], [x + 100.0, 5.0], [x + 100.0, 55.0], [x, 55.0]]
+            })
+        };
+        serde_json::from_value(serde_json::json!({
+            "instance_name": "plate",
+            "strip_width": 215.0,
+            "strip_height": 200.0,
+            "total_items_placed": 2,
+            "layouts": [placed(110.0), placed(5.0)],
+            "utilization": 0.2,
+            "computation_time_secs": 1.0
+        }))
+        .unwrap()
+    }
+
+    fn options() -> CutProgramOptions {
+        CutProgramOptions {
+            separation: Some(5.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_holes_are_cut_before_outer_from_left_to_right() {
+        let instance = parse_instance(PLATE).unwrap();
+        let program = cut_program(&output(), &instance, &options()).unwrap();
+
+        let order: Vec<(usize, Technology)> = program
+            .entries
+            .iter()
+            .map(|entry| (entry.placement_index, entry.technology))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (1, Technology::Hole),
+                (1, Technology::Outer),
+                (0, Technology::Hole),
+                (0, Technology::Outer),
+            ]
+        );
+        assert_eq!(program.version, CUT_PROGRAM_VERSION);
+        assert!(program.warnings.is_empty(), "{:?}", program.warnings);
+
+        for entry in &program.entries {
+            assert_eq!(entry.contour.first(), entry.contour.last());
+            let start = entry.contour[0];
+            let end = (
+                entry.pierce.0 + entry.lead_in.0,
+                entry.pierce.1 + entry.lead_in.1,
+            );
+            assert!((start.0 - end.0).abs() < 1e-9 && (start.1 - end.1).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_pierce_points_lie_in_scrap() {
+        let instance = parse_instance(PLATE).unwrap();
+        let program = cut_program(&output(), &instance, &options()).unwrap();
+
+        // Left plate spans x 5..105, y 5..55 with its hole at x 45..65, y 20..40
+        let hole = &program.entries[0];
+        assert!((45.0..=65.0).contains(&hole.pierce.0) && (20.0..=40.0).contains(&hole.pierce.1));
+        assert!(hole.lead_in_clear);
+
+        // Bottom edge first: 2 mm into the 5 mm gap to the sheet edge
+        let outer = &program.entries[1];
+        assert_eq!(outer.pierce, (55.0, 3.0));
+        assert_eq!(outer.lead_in, (0.0, 2.0));
+        assert!(outer.lead_in_clear);
+    }
+
+    #[test]
+    fn test_lead_in_without_clearance_is_flagged() {
+        let instance = parse_instance(PLATE).unwrap();
+        let mut output = output();
+        // Sheet edges 5 mm from the plates on all sides, and they are 5 mm apart
+        output.strip_height = 60.0;
+        let options = CutProgramOptions {
+            separation: Some(12.0),
+            ..Default::default()
+        };
+        let program = cut_program(&output, &instance, &options).unwrap();
+
+        let flagged: Vec<Technology> = program
+            .entries
+            .iter()
+            .filter(|entry| !entry.lead_in_clear)
+            .map(|entry| entry.technology)
+            .collect();
+        assert_eq!(flagged, vec![Technology::Outer, Technology::Outer]);
+        assert_eq!(program.warnings.len(), 2);
+    }
+
+    #[test]
+    fn test_top_left_frame_flips_y_and_keeps_winding() {
+        let instance = parse_instance(PLATE).unwrap();
+        let options = CutProgramOptions {
+            frame: CoordinateFrame::TopLeft,
+            ..options()
+        };
+        let program = cut_program(&output(), &instance, &options).unwrap();
+
+        // Plate spans y 145..195 from the top; pierced 2 mm below or above it
+        let outer = &program.entries[1];
+        assert!(
+            outer.pierce == (55.0, 197.0) || outer.pierce == (55.0, 143.0),
+            "{:?}",
+            outer.pierce
+        );
+        assert_eq!(outer.lead_in.1.abs(), 2.0);
+        assert!(signed_area(&outer.contour) > 0.0);
+        assert!(signed_area(&program.entries[0].contour) < 0.0);
+    }
+
+    #[test]
+    fn test_schema_matches_format_version() {
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("../../schemas/cut-program-v1.schema.json")).unwrap();
+        assert_eq!(
+            schema["properties"]["version"]["const"],
+            serde_json::json!(CUT_PROGRAM_VERSION)
+        );
+        assert_eq!(
+            schema["properties"]["format"]["const"],
+            serde_json::json!(CUT_PROGRAM_FORMAT)
+        );
+    }
+}
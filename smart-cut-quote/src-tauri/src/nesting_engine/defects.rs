@@ -0,0 +1,197 @@
+//! Defect zones on the sheet (scratches, pre-drilled regions)
+//!
+//! Zones are arbitrary polygons in sheet coordinates, taken as holes of
+//! the container: like keep-out zones they are enforced after
+//! optimization, placements reaching into one are removed from the
+//! layout. A zone off the strip cannot conflict with anything and is
+//! only reported as a warning, it usually means a coordinate typo.
+
+use super::geometry::{bounding_box, clip_polygon_to_rect, open_ring, polygon_area, Polygon};
+use super::keep_out::escape_xml;
+use super::remnant::reaches_into;
+use jagua_rs::probs::spp::entities::SPSolution;
+
+/// Reject zones with non-finite coordinates or without an area
+pub fn validate_defect_zones(holes: &[Polygon]) -> Result<(), String> {
+    for (i, hole) in holes.iter().enumerate() {
+        let ring = open_ring(hole);
+        if ring.iter().any(|&(x, y)| !x.is_finite() || !y.is_finite()) {
+            return Err(format!("Defect zone {} has non-finite coordinates", i));
+        }
+        if ring.len() < 3 || polygon_area(ring) <= 0.0 {
+            return Err(format!(
+                "Defect zone {} needs at least 3 points enclosing an area",
+                i
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Zones not lying completely on the strip, as warning messages
+///
+/// # Arguments
+/// * `holes` - Defect zones in sheet coordinates
+/// * `strip_width` - Sheet length along X, None for a growing strip
+/// * `strip_height` - Strip height
+pub fn zones_off_strip(
+    holes: &[Polygon],
+    strip_width: Option<f64>,
+    strip_height: f64,
+) -> Vec<String> {
+    let max_x = strip_width.unwrap_or(f64::INFINITY);
+    holes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, hole)| {
+            let (min_x, min_y, hole_max_x, hole_max_y) = bounding_box(open_ring(hole))?;
+            let inside =
+                min_x >= 0.0 && min_y >= 0.0 && hole_max_x <= max_x && hole_max_y <= strip_height;
+            if inside {
+                return None;
+            }
+            let off =
+                min_x >= max_x || hole_max_x <= 0.0 || min_y >= strip_height || hole_max_y <= 0.0;
+            Some(format!(
+                "defect zone {} ({:.1}, {:.1})-({:.1}, {:.1}) lies {} the strip",
+                i,
+                min_x,
+                min_y,
+                hole_max_x,
+                hole_max_y,
+                if off { "outside" } else { "partly outside" }
+            ))
+        })
+        .collect()
+}
+
+/// True if the polygon covers part of any zone, touching does not count
+pub fn overlaps_any(holes: &[Polygon], polygon: &[(f64, f64)]) -> bool {
+    holes
+        .iter()
+        .any(|hole| reaches_into(polygon, open_ring(hole)))
+}
+
+/// Total zone area inside a `width` x `height` sheet
+///
+/// Overlapping zones are counted twice, like keep-out zones.
+pub fn blocked_area(holes: &[Polygon], width: f64, height: f64) -> f64 {
+    holes
+        .iter()
+        .map(|hole| {
+            polygon_area(&clip_polygon_to_rect(
+                open_ring(hole),
+                0.0,
+                0.0,
+                width,
+                height,
+            ))
+        })
+        .sum()
+}
+
+/// Remove placements reaching into any zone from the solution
+///
+/// # Returns
+/// Item IDs of the removed placements (one entry per removed copy)
+pub fn evict_defect_overlaps(solution: &mut SPSolution, holes: &[Polygon]) -> Vec<usize> {
+    if holes.is_empty() {
+        return Vec::new();
+    }
+
+    let conflicting: Vec<_> = solution
+        .layout_snapshot
+        .placed_items
+        .iter()
+        .filter(|(_, placed_item)| {
+            let polygon: Polygon = placed_item
+                .shape
+                .vertices
+                .iter()
+                .map(|p| (p.0 as f64, p.1 as f64))
+                .collect();
+            overlaps_any(holes, &polygon)
+        })
+        .map(|(key, placed_item)| (key, placed_item.item_id))
+        .collect();
+
+    conflicting
+        .into_iter()
+        .map(|(key, item_id)| {
+            solution.layout_snapshot.placed_items.remove(key);
+            item_id
+        })
+        .collect()
+}
+
+/// Render zones as cross-hatched SVG paths, distinct from keep-out zones
+pub fn render_defects_overlay(holes: &[Polygon]) -> String {
+    let mut overlay = String::from(concat!(
+        r#"<g id="defect_zones" pointer-events="none">"#,
+        r#"<defs><pattern id="defect_hatch" width="8" height="8" patternUnits="userSpaceOnUse">"#,
+        r#"<path d="M0 0 L8 8 M8 0 L0 8" stroke="darkred" stroke-width="1.5"/></pattern></defs>"#
+    ));
+
+    for (i, hole) in holes.iter().enumerate() {
+        let mut path = String::new();
+        for (j, (x, y)) in open_ring(hole).iter().enumerate() {
+            path.push_str(&format!("{}{} {} ", if j == 0 { "M" } else { "L" }, x, y));
+        }
+        path.push('Z');
+        overlay.push_str(&format!(
+            r#"<path d="{}" fill="url(#defect_hatch)" fill-opacity="0.7" stroke="darkred" stroke-width="2"><title>{}</title></path>"#,
+            path,
+            escape_xml(&format!("Defect zone {}", i))
+        ));
+    }
+
+    overlay.push_str("</g>");
+    overlay
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangle(x: f64, y: f64) -> Polygon {
+        vec![(x, y), (x + 100.0, y), (x, y + 100.0)]
+    }
+
+    #[test]
+    fn test_overlap_ignores_touching() {
+        let scratch = vec![triangle(100.0, 100.0)];
+        let touching = vec![(100.0, 0.0), (200.0, 0.0), (200.0, 100.0), (100.0, 100.0)];
+        let covering = vec![(90.0, 90.0), (150.0, 90.0), (150.0, 150.0), (90.0, 150.0)];
+
+        assert!(!overlaps_any(&scratch, &touching));
+        assert!(overlaps_any(&scratch, &covering));
+    }
+
+    #[test]
+    fn test_zones_off_strip_are_reported() {
+        let holes = vec![
+            triangle(10.0, 10.0),
+            triangle(10.0, 950.0),
+            triangle(10.0, 2000.0),
+        ];
+        let warnings = zones_off_strip(&holes, None, 1000.0);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("partly outside"), "{}", warnings[0]);
+        assert!(warnings[1].contains("zone 2"), "{}", warnings[1]);
+
+        assert_eq!(zones_off_strip(&holes[..1], Some(50.0), 1000.0).len(), 1);
+    }
+
+    #[test]
+    fn test_blocked_area_clipped_to_sheet() {
+        let holes = vec![triangle(0.0, 0.0), triangle(-50.0, 0.0)];
+        let expected = 5000.0 + 1250.0;
+        assert!((blocked_area(&holes, 1000.0, 1000.0) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_validation_rejects_degenerate_zone() {
+        assert!(validate_defect_zones(&[triangle(0.0, 0.0)]).is_ok());
+        assert!(validate_defect_zones(&[vec![(0.0, 0.0), (10.0, 0.0), (20.0, 0.0)]]).is_err());
+    }
+}
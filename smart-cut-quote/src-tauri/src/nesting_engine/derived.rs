@@ -0,0 +1,389 @@
+//! Heavy output fields derived after the optimizer finished
+//!
+//! Exact and display outlines, the utilization heatmap and the SVG
+//! preview take seconds for layouts with thousands of placements.
+//! `run_nesting_core` returns placements and summary right away and
+//! keeps a `NestingRun` from which these fields are derived afterwards,
+//! each computed at most once per run.
+
+use super::defects;
+use super::filler::{self, FillerLayout};
+use super::geometry::{self, DisplayUnits, Polygon};
+use super::grain;
+use super::heatmap::{self, UtilizationGrid, UtilizationGridOptions};
+use super::holes::{self, HoleLayout};
+use super::keep_out::{self, KeepOutZone};
+use super::locked::{self, LockedPart};
+use super::nesting::NestingResult;
+use super::remnant::{self, ContainerPolygon};
+use super::serializer::{self, NestingOutput};
+use super::small_parts::{self, SmallPartLayout};
+use super::spacing;
+use super::viewbox::SvgWarning;
+use super::{append_svg_overlay, generate_svg};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Output fields computed after the placements are known
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DerivedField {
+    /// Exact and display outline of every placement
+    Outlines,
+    /// Covered fraction per grid cell (needs `utilization_grid` options)
+    UtilizationGrid,
+    /// SVG rendering of the layout with the requested overlays
+    Svg,
+}
+
+/// Exact and decimated outline of one placement in strip coordinates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemOutlines {
+    pub outline: Polygon,
+    pub display_outline: Polygon,
+}
+
+/// Derived fields of a run, only the requested ones are set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DerivedOutput {
+    /// Unit of the outlines and grid cells (derived in mm)
+    #[serde(default)]
+    pub units: DisplayUnits,
+    /// Same order as `NestingOutput::layouts`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub outlines: Option<Vec<ItemOutlines>>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub utilization_grid: Option<UtilizationGrid>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub svg_string: Option<String>,
+    /// Problems while post-processing `svg_string`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub svg_warnings: Vec<SvgWarning>,
+}
+
+impl DerivedOutput {
+    /// Same fields with every length in `units`
+    pub fn to_units(mut self, units: DisplayUnits) -> Self {
+        let from = self.units;
+        if units == from {
+            return self;
+        }
+        if let Some(outlines) = self.outlines.as_mut() {
+            for outlines in outlines {
+                outlines.outline = units.polygon_from(&outlines.outline, from);
+                outlines.display_outline = units.polygon_from(&outlines.display_outline, from);
+            }
+        }
+        self.utilization_grid = self.utilization_grid.map(|grid| grid.in_units(units, from));
+        self.units = units;
+        self
+    }
+
+    /// Merge into the slim output returned by `run_nesting_core`
+    ///
+    /// Converted to the units of the output first, so it never mixes units.
+    pub fn apply_to(self, output: &mut NestingOutput) {
+        let derived = self.to_units(output.units);
+        if let Some(outlines) = derived.outlines {
+            for (placed, outlines) in output.layouts.iter_mut().zip(outlines) {
+                placed.outline = Some(outlines.outline);
+                placed.display_outline = Some(outlines.display_outline);
+            }
+        }
+        if derived.utilization_grid.is_some() {
+            output.utilization_grid = derived.utilization_grid;
+        }
+        if derived.svg_string.is_some() {
+            output.svg_string = derived.svg_string;
+            output.svg_warnings = derived.svg_warnings;
+        }
+    }
+}
+
+/// Intermediate state of a finished run, kept to derive heavy fields
+pub struct NestingRun {
+    result: NestingResult,
+    hole_layout: HoleLayout,
+    small_layout: SmallPartLayout,
+    filler_layout: FillerLayout,
+    keep_out_zones: Vec<KeepOutZone>,
+    holes: Vec<Polygon>,
+    container_polygon: Option<ContainerPolygon>,
+    locked_parts: Vec<LockedPart>,
+    strip_width: f64,
+    strip_height: f64,
+    include_outlines: bool,
+    display_tolerance: f64,
+    grid_options: Option<UtilizationGridOptions>,
+    heatmap_overlay: bool,
+    clearance_overlay: bool,
+    kerf_overlay: bool,
+    fit_svg_viewbox: bool,
+    outlines: OnceLock<Vec<ItemOutlines>>,
+    utilization_grid: OnceLock<Option<UtilizationGrid>>,
+    svg: OnceLock<(String, Option<SvgWarning>)>,
+}
+
+impl NestingRun {
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn new(
+        result: NestingResult,
+        hole_layout: HoleLayout,
+        small_layout: SmallPartLayout,
+        filler_layout: FillerLayout,
+        keep_out_zones: Vec<KeepOutZone>,
+        holes: Vec<Polygon>,
+        container_polygon: Option<ContainerPolygon>,
+        locked_parts: Vec<LockedPart>,
+        output: &NestingOutput,
+        include_outlines: bool,
+        display_tolerance: f64,
+        grid_options: Option<UtilizationGridOptions>,
+        heatmap_overlay: bool,
+        clearance_overlay: bool,
+        kerf_overlay: bool,
+        fit_svg_viewbox: bool,
+    ) -> Self {
+        Self {
+            result,
+            hole_layout,
+            small_layout,
+            filler_layout,
+            keep_out_zones,
+            holes,
+            container_polygon,
+            locked_parts,
+            strip_width: output.strip_width,
+            strip_height: output.strip_height,
+            include_outlines,
+            display_tolerance,
+            grid_options,
+            heatmap_overlay,
+            clearance_overlay,
+            kerf_overlay,
+            fit_svg_viewbox,
+            outlines: OnceLock::new(),
+            utilization_grid: OnceLock::new(),
+            svg: OnceLock::new(),
+        }
+    }
+
+    /// Fields the nesting input asked for
+    pub fn requested_fields(&self) -> Vec<DerivedField> {
+        let mut fields = Vec::new();
+        if self.include_outlines {
+            fields.push(DerivedField::Outlines);
+        }
+        if self.grid_options.is_some() {
+            fields.push(DerivedField::UtilizationGrid);
+        }
+        fields.push(DerivedField::Svg);
+        fields
+    }
+
+    /// Compute (or take from cache) the given fields
+    ///
+    /// Safe to call from several threads: a field requested while
+    /// another caller computes it waits for that result.
+    pub fn derive(&self, fields: &[DerivedField]) -> DerivedOutput {
+        let mut derived = DerivedOutput::default();
+        for field in fields {
+            match field {
+                DerivedField::Outlines => derived.outlines = Some(self.outlines().clone()),
+                DerivedField::UtilizationGrid => {
+                    derived.utilization_grid = self.utilization_grid().clone()
+                }
+                DerivedField::Svg => {
+                    let (svg, warning) = self.svg();
+                    derived.svg_string = Some(svg.clone());
+                    derived.svg_warnings = warning.iter().cloned().collect();
+                }
+            }
+        }
+        derived
+    }
+
+    /// Outlines in layout order: jagua-rs placements first, then the
+    /// parts nested into holes (see `NestingOutput::from_solution`), the
+    /// post-placed small parts, the fillers and the locked parts. The
+    /// display outline never deviates more than `display_tolerance` (mm)
+    /// from the exact one.
+    fn outlines(&self) -> &Vec<ItemOutlines> {
+        self.outlines.get_or_init(|| {
+            serializer::placed_polygons(&self.result.solution)
+                .into_iter()
+                .chain(
+                    self.hole_layout
+                        .placements
+                        .iter()
+                        .map(|p| p.outline.clone()),
+                )
+                .chain(
+                    self.small_layout
+                        .placements
+                        .iter()
+                        .map(|p| p.outline.clone()),
+                )
+                .chain(
+                    self.filler_layout
+                        .placements
+                        .iter()
+                        .map(|p| p.outline.clone()),
+                )
+                .chain(self.locked_parts.iter().map(|p| p.outline.clone()))
+                .map(|outline| ItemOutlines {
+                    display_outline: geometry::simplify_ring(&outline, self.display_tolerance),
+                    outline,
+                })
+                .collect()
+        })
+    }
+
+    fn utilization_grid(&self) -> &Option<UtilizationGrid> {
+        self.utilization_grid.get_or_init(|| {
+            let grid_options = self.grid_options.as_ref()?;
+            let mut polygons = serializer::placed_polygons(&self.result.solution);
+            polygons.extend(
+                self.hole_layout
+                    .placements
+                    .iter()
+                    .map(|p| p.outline.clone()),
+            );
+            polygons.extend(
+                self.small_layout
+                    .placements
+                    .iter()
+                    .map(|p| p.outline.clone()),
+            );
+            polygons.extend(self.locked_parts.iter().map(|p| p.outline.clone()));
+            heatmap::compute_utilization_grid(
+                &polygons,
+                &self.hole_layout.hole_outlines,
+                self.strip_width,
+                self.strip_height,
+                grid_options,
+            )
+        })
+    }
+
+    fn svg(&self) -> &(String, Option<SvgWarning>) {
+        self.svg.get_or_init(|| {
+            let (mut svg_string, warning) =
+                generate_svg(&self.result, self.fit_svg_viewbox, || {
+                    self.outlines()
+                        .iter()
+                        .map(|outlines| outlines.outline.clone())
+                        .collect()
+                });
+            if self.heatmap_overlay {
+                if let Some(grid) = self.utilization_grid() {
+                    svg_string =
+                        append_svg_overlay(&svg_string, &heatmap::render_heatmap_overlay(grid));
+                }
+            }
+            if !self.hole_layout.hole_outlines.is_empty() {
+                svg_string =
+                    append_svg_overlay(&svg_string, &holes::render_hole_overlay(&self.hole_layout));
+            }
+            if !self.small_layout.placements.is_empty() {
+                svg_string = append_svg_overlay(
+                    &svg_string,
+                    &small_parts::render_small_part_overlay(&self.small_layout),
+                );
+            }
+            if !self.filler_layout.placements.is_empty() {
+                svg_string = append_svg_overlay(
+                    &svg_string,
+                    &filler::render_filler_overlay(&self.filler_layout),
+                );
+            }
+            if !self.result.bend_plan.items.is_empty() {
+                svg_string = append_svg_overlay(
+                    &svg_string,
+                    &grain::render_bend_overlay(
+                        &self.result.bend_plan,
+                        &self.result.solution,
+                        &self.hole_layout,
+                    ),
+                );
+            }
+            if self.clearance_overlay && !self.result.spacing_plan.items.is_empty() {
+                svg_string = append_svg_overlay(
+                    &svg_string,
+                    &spacing::render_clearance_overlay(
+                        &self.result.spacing_plan,
+                        &self.result.solution,
+                    ),
+                );
+            }
+            if self.kerf_overlay && self.result.spacing_plan.kerf_offset > 0.0 {
+                svg_string = append_svg_overlay(
+                    &svg_string,
+                    &spacing::render_kerf_overlay(&self.result.spacing_plan, &self.result.solution),
+                );
+            }
+            if !self.keep_out_zones.is_empty() {
+                svg_string = append_svg_overlay(
+                    &svg_string,
+                    &keep_out::render_zones_overlay(&self.keep_out_zones),
+                );
+            }
+            if !self.holes.is_empty() {
+                svg_string =
+                    append_svg_overlay(&svg_string, &defects::render_defects_overlay(&self.holes));
+            }
+            if let Some(container) = &self.container_polygon {
+                svg_string =
+                    append_svg_overlay(&svg_string, &remnant::render_container_overlay(container));
+            }
+            if !self.locked_parts.is_empty() {
+                svg_string = append_svg_overlay(
+                    &svg_string,
+                    &locked::render_locked_overlay(&self.locked_parts),
+                );
+            }
+            (svg_string, warning)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::{run_nesting_core, NestingInput};
+
+    const THIN_FRAME: &str = include_str!("../../test_instances/thin_frame.json");
+
+    #[test]
+    fn test_core_output_is_slim_until_derived() {
+        let (mut output, run) = run_nesting_core(NestingInput {
+            json_input: THIN_FRAME.to_string(),
+            time_limit: Some(1),
+            seed: Some(3),
+            use_early_termination: Some(true),
+            hole_nesting: Some(true),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(output.svg_string.is_none());
+        assert!(output.layouts.iter().all(|placed| placed.outline.is_none()));
+        assert_eq!(run.requested_fields(), vec![DerivedField::Svg]);
+
+        // Outlines were not requested up front but can still be derived
+        let derived = run.derive(&[DerivedField::Outlines, DerivedField::UtilizationGrid]);
+        assert_eq!(
+            derived.outlines.as_ref().unwrap().len(),
+            output.layouts.len()
+        );
+        assert!(derived.utilization_grid.is_none());
+        assert!(derived.svg_string.is_none());
+
+        let svg = run.derive(&[DerivedField::Svg]).svg_string.unwrap();
+        assert_eq!(run.derive(&[DerivedField::Svg]).svg_string.unwrap(), svg);
+
+        derived.apply_to(&mut output);
+        assert!(output.layouts.iter().all(|placed| placed.outline.is_some()));
+        assert!(output.svg_string.is_none());
+    }
+}
@@ -0,0 +1,316 @@
+//! Instance diff for customer revisions
+//!
+//! Compares two instances item by item (matched by label / DXF file
+//! name) to tell which parts really changed geometry and which only
+//! changed quantity, so the frontend can decide if a re-nest is needed.
+
+use super::fingerprint::sha256_hex;
+use super::geometry::{bounding_box, open_ring};
+use super::instance::{parse_instance, InstanceItem};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Default vertex tolerance for "unchanged" geometry (mm)
+pub const DEFAULT_DIFF_TOLERANCE: f64 = 0.01;
+
+/// Geometric metrics of a single item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemMetrics {
+    /// Net area (outer minus holes)
+    pub area: f64,
+    /// Total contour length (outer plus holes)
+    pub perimeter: f64,
+    /// Number of vertices over all contours
+    pub vertex_count: usize,
+    /// Bounding box width
+    pub width: f64,
+    /// Bounding box height
+    pub height: f64,
+    /// Hash of the normalized, tolerance-quantized vertices
+    pub geometry_hash: String,
+}
+
+/// Item present in only one of the instances
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffItem {
+    pub label: String,
+    pub item_id: u64,
+    pub demand: u64,
+    pub metrics: ItemMetrics,
+}
+
+/// Item whose demand changed but whose geometry is unchanged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantityChange {
+    pub label: String,
+    pub demand_a: u64,
+    pub demand_b: u64,
+}
+
+/// Item whose geometry changed beyond the tolerance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeometryChange {
+    pub label: String,
+    pub item_id_a: u64,
+    pub item_id_b: u64,
+    pub demand_a: u64,
+    pub demand_b: u64,
+    pub metrics_a: ItemMetrics,
+    pub metrics_b: ItemMetrics,
+    /// Area of B minus area of A
+    pub area_delta: f64,
+    /// Perimeter of B minus perimeter of A
+    pub perimeter_delta: f64,
+    /// Largest vertex displacement (None when the vertex counts differ)
+    pub max_vertex_deviation: Option<f64>,
+}
+
+/// Differences between instance A (before) and instance B (after)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstanceDiff {
+    pub added: Vec<DiffItem>,
+    pub removed: Vec<DiffItem>,
+    pub quantity_changed: Vec<QuantityChange>,
+    pub geometry_changed: Vec<GeometryChange>,
+    /// Labels of items identical in geometry and quantity
+    pub unchanged: Vec<String>,
+    /// Vertex tolerance that was applied
+    pub tolerance: f64,
+}
+
+impl InstanceDiff {
+    /// True when a re-nest (and re-quote) is needed
+    pub fn requires_renest(&self) -> bool {
+        !self.added.is_empty()
+            || !self.removed.is_empty()
+            || !self.quantity_changed.is_empty()
+            || !self.geometry_changed.is_empty()
+    }
+}
+
+/// Diff two instance JSON strings
+///
+/// # Arguments
+/// * `instance_a_json` - Instance before the revision
+/// * `instance_b_json` - Instance after the revision
+/// * `tolerance` - Max vertex displacement still considered unchanged (mm)
+pub fn diff_instances(
+    instance_a_json: &str,
+    instance_b_json: &str,
+    tolerance: f64,
+) -> Result<InstanceDiff, String> {
+    let instance_a = parse_instance(instance_a_json).map_err(|e| format!("Instance A: {}", e))?;
+    let instance_b = parse_instance(instance_b_json).map_err(|e| format!("Instance B: {}", e))?;
+    let tolerance = if tolerance > 0.0 {
+        tolerance
+    } else {
+        DEFAULT_DIFF_TOLERANCE
+    };
+
+    let items_a = index_by_label(&instance_a.items);
+    let mut items_b = index_by_label(&instance_b.items);
+
+    let mut diff = InstanceDiff {
+        tolerance,
+        ..Default::default()
+    };
+
+    for (key, item_a) in items_a {
+        let Some(item_b) = items_b.remove(&key) else {
+            diff.removed.push(diff_item(item_a, tolerance));
+            continue;
+        };
+
+        let metrics_a = item_metrics(item_a, tolerance);
+        let metrics_b = item_metrics(item_b, tolerance);
+        let deviation = max_vertex_deviation(item_a, item_b);
+        let same_geometry = metrics_a.geometry_hash == metrics_b.geometry_hash
+            || deviation.is_some_and(|d| d <= tolerance);
+
+        if !same_geometry {
+            diff.geometry_changed.push(GeometryChange {
+                label: item_b.display_name(),
+                item_id_a: item_a.id,
+                item_id_b: item_b.id,
+                demand_a: item_a.demand,
+                demand_b: item_b.demand,
+                area_delta: metrics_b.area - metrics_a.area,
+                perimeter_delta: metrics_b.perimeter - metrics_a.perimeter,
+                metrics_a,
+                metrics_b,
+                max_vertex_deviation: deviation,
+            });
+        } else if item_a.demand != item_b.demand {
+            diff.quantity_changed.push(QuantityChange {
+                label: item_b.display_name(),
+                demand_a: item_a.demand,
+                demand_b: item_b.demand,
+            });
+        } else {
+            diff.unchanged.push(item_b.display_name());
+        }
+    }
+
+    diff.added = items_b
+        .into_values()
+        .map(|item| diff_item(item, tolerance))
+        .collect();
+
+    Ok(diff)
+}
+
+/// Index items by lowercase display name, disambiguating duplicates
+fn index_by_label(items: &[InstanceItem]) -> BTreeMap<String, &InstanceItem> {
+    let mut index = BTreeMap::new();
+
+    for item in items {
+        let base = item.display_name().to_lowercase();
+        let mut key = base.clone();
+        let mut n = 2;
+        while index.contains_key(&key) {
+            key = format!("{}#{}", base, n);
+            n += 1;
+        }
+        index.insert(key, item);
+    }
+
+    index
+}
+
+fn diff_item(item: &InstanceItem, tolerance: f64) -> DiffItem {
+    DiffItem {
+        label: item.display_name(),
+        item_id: item.id,
+        demand: item.demand,
+        metrics: item_metrics(item, tolerance),
+    }
+}
+
+/// All contours of an item, translated so the outer bounding box starts at (0, 0)
+fn normalized_rings(item: &InstanceItem) -> Vec<Vec<(f64, f64)>> {
+    let (min_x, min_y) = bounding_box(&item.outer)
+        .map(|(min_x, min_y, _, _)| (min_x, min_y))
+        .unwrap_or((0.0, 0.0));
+
+    std::iter::once(&item.outer)
+        .chain(item.holes.iter())
+        .map(|ring| {
+            open_ring(ring)
+                .iter()
+                .map(|&(x, y)| (x - min_x, y - min_y))
+                .collect()
+        })
+        .collect()
+}
+
+fn item_metrics(item: &InstanceItem, tolerance: f64) -> ItemMetrics {
+    let geometry = item.geometry();
+    let (min_x, min_y, max_x, max_y) = geometry.bounding_box().unwrap_or((0.0, 0.0, 0.0, 0.0));
+
+    let rings = normalized_rings(item);
+    let quantized: Vec<Vec<(i64, i64)>> = rings
+        .iter()
+        .map(|ring| {
+            ring.iter()
+                .map(|&(x, y)| {
+                    (
+                        (x / tolerance).round() as i64,
+                        (y / tolerance).round() as i64,
+                    )
+                })
+                .collect()
+        })
+        .collect();
+
+    ItemMetrics {
+        area: geometry.area(),
+        perimeter: geometry.perimeter(),
+        vertex_count: rings.iter().map(Vec::len).sum(),
+        width: max_x - min_x,
+        height: max_y - min_y,
+        geometry_hash: sha256_hex(&format!("{:?}", quantized)),
+    }
+}
+
+/// Largest displacement between corresponding normalized vertices
+///
+/// Returns `None` when the contour or vertex counts differ.
+fn max_vertex_deviation(a: &InstanceItem, b: &InstanceItem) -> Option<f64> {
+    let rings_a = normalized_rings(a);
+    let rings_b = normalized_rings(b);
+
+    if rings_a.len() != rings_b.len() {
+        return None;
+    }
+
+    let mut max_deviation: f64 = 0.0;
+    for (ring_a, ring_b) in rings_a.iter().zip(rings_b.iter()) {
+        if ring_a.len() != ring_b.len() {
+            return None;
+        }
+        for (&(xa, ya), &(xb, yb)) in ring_a.iter().zip(ring_b.iter()) {
+            max_deviation = max_deviation.max((xb - xa).abs().max((yb - ya).abs()));
+        }
+    }
+
+    Some(max_deviation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(items: &str) -> String {
+        format!(r#"{{"name": "rev", "strip_height": 1000.0, "items": [{}]}}"#, items)
+    }
+
+    fn square_item(id: u64, dxf: &str, demand: u64, size: f64, offset: f64) -> String {
+        format!(
+            r#"{{"id": {}, "demand": {}, "dxf": "{}", "shape": {{"type": "simple_polygon",
+                "data": [[{o}, {o}], [{s}, {o}], [{s}, {s}], [{o}, {s}]]}}}}"#,
+            id,
+            demand,
+            dxf,
+            o = offset,
+            s = offset + size
+        )
+    }
+
+    #[test]
+    fn test_diff_classifies_changes() {
+        let a = instance(&[
+            square_item(0, "C:\\\\parts\\\\bracket.dxf", 2, 10.0, 0.0),
+            square_item(1, "plate.dxf", 1, 20.0, 0.0),
+            square_item(2, "old.dxf", 1, 5.0, 0.0),
+        ]
+        .join(","));
+        let b = instance(&[
+            // Moved in the DXF and re-exported with rounding noise: unchanged
+            square_item(0, "bracket.dxf", 2, 10.004, 100.0),
+            square_item(1, "plate.dxf", 4, 20.0, 0.0),
+            square_item(2, "new.dxf", 1, 5.0, 0.0),
+        ]
+        .join(","));
+
+        let diff = diff_instances(&a, &b, DEFAULT_DIFF_TOLERANCE).unwrap();
+        assert_eq!(diff.unchanged, vec!["bracket.dxf".to_string()]);
+        assert_eq!(diff.quantity_changed.len(), 1);
+        assert_eq!(diff.quantity_changed[0].demand_b, 4);
+        assert_eq!(diff.removed[0].label, "old.dxf");
+        assert_eq!(diff.added[0].label, "new.dxf");
+        assert!(diff.requires_renest());
+    }
+
+    #[test]
+    fn test_diff_detects_geometry_change() {
+        let a = instance(&square_item(0, "bracket.dxf", 2, 10.0, 0.0));
+        let b = instance(&square_item(0, "bracket.dxf", 2, 12.0, 0.0));
+
+        let diff = diff_instances(&a, &b, DEFAULT_DIFF_TOLERANCE).unwrap();
+        assert_eq!(diff.geometry_changed.len(), 1);
+        let change = &diff.geometry_changed[0];
+        assert!((change.area_delta - 44.0).abs() < 1e-9);
+        assert!((change.perimeter_delta - 8.0).abs() < 1e-9);
+        assert!((change.max_vertex_deviation.unwrap() - 2.0).abs() < 1e-9);
+    }
+}
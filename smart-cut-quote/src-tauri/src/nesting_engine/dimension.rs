@@ -0,0 +1,368 @@
+//! Locale-safe parsing of dimension strings
+//!
+//! UI inputs arrive as the user typed them: "1.250,5" from a German or
+//! Vietnamese locale, "1,250.5 mm" from an English one, "2 in" from a
+//! drawing in inches. Values accepted as strings go through
+//! `parse_dimension`, which handles both decimal separators, thousands
+//! separators, whitespace and unit suffixes and returns millimetres.
+//!
+//! Only "1,250" and "1.250" are truly ambiguous (1250 or 1.25); they are
+//! read per the configured locale and a warning is logged.
+
+use log::warn;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Decimal separator convention of the user's locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberLocale {
+    /// "1,250.5" (English, Chinese, Japanese, ...)
+    #[default]
+    DecimalPoint,
+    /// "1.250,5" (German, Vietnamese, French, ...)
+    DecimalComma,
+}
+
+/// Languages writing decimals with a comma
+const DECIMAL_COMMA_LANGUAGES: &[&str] = &[
+    "vi", "de", "fr", "es", "it", "pt", "nl", "ru", "pl", "cs", "sk", "sv", "da", "nb", "fi", "tr",
+    "id", "uk", "hu", "ro",
+];
+
+impl NumberLocale {
+    /// Convention of a BCP 47 language tag ("vi-VN", "de", "en-US")
+    pub fn from_language_tag(tag: &str) -> Self {
+        let language = tag.split(['-', '_']).next().unwrap_or("").to_lowercase();
+        if DECIMAL_COMMA_LANGUAGES.contains(&language.as_str()) {
+            NumberLocale::DecimalComma
+        } else {
+            NumberLocale::DecimalPoint
+        }
+    }
+
+    fn decimal_separator(self) -> char {
+        match self {
+            NumberLocale::DecimalPoint => '.',
+            NumberLocale::DecimalComma => ',',
+        }
+    }
+}
+
+/// Locale used for ambiguous inputs, set from the app settings
+static NUMBER_LOCALE: AtomicU8 = AtomicU8::new(0);
+
+/// Change the locale ambiguous inputs are read in
+pub fn set_number_locale(locale: NumberLocale) {
+    NUMBER_LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+/// Locale ambiguous inputs are currently read in
+pub fn number_locale() -> NumberLocale {
+    match NUMBER_LOCALE.load(Ordering::Relaxed) {
+        1 => NumberLocale::DecimalComma,
+        _ => NumberLocale::DecimalPoint,
+    }
+}
+
+/// Parsed value, with a warning if the input was ambiguous
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedNumber {
+    pub value: f64,
+    pub warning: Option<String>,
+}
+
+/// Units accepted after a dimension, with their size in mm
+const UNITS: &[(&str, f64)] = &[("mm", 1.0), ("cm", 10.0), ("in", 25.4)];
+
+/// Parse a dimension in the configured locale, returning millimetres
+///
+/// Ambiguous inputs are logged as a warning.
+pub fn parse_dimension(input: &str) -> Result<f64, String> {
+    let parsed = parse_dimension_in(input, number_locale())?;
+    if let Some(warning) = &parsed.warning {
+        warn!("{}", warning);
+    }
+    Ok(parsed.value)
+}
+
+/// Parse a plain number (no unit) in the configured locale
+pub fn parse_number(input: &str) -> Result<f64, String> {
+    let parsed = parse_number_in(input, number_locale())?;
+    if let Some(warning) = &parsed.warning {
+        warn!("{}", warning);
+    }
+    Ok(parsed.value)
+}
+
+/// Parse a dimension with an optional unit suffix, returning millimetres
+pub fn parse_dimension_in(input: &str, locale: NumberLocale) -> Result<ParsedNumber, String> {
+    let trimmed = input.trim();
+    let lower = trimmed.to_ascii_lowercase();
+    let (number, factor) = UNITS
+        .iter()
+        .find(|(unit, _)| lower.ends_with(unit))
+        .map(|(unit, factor)| (&trimmed[..trimmed.len() - unit.len()], *factor))
+        .unwrap_or((trimmed, 1.0));
+
+    let parsed = parse_number_in(number, locale)
+        .map_err(|_| format!("'{}' is not a valid dimension", input.trim()))?;
+    Ok(ParsedNumber {
+        value: parsed.value * factor,
+        warning: parsed.warning,
+    })
+}
+
+/// Parse a number written with either decimal separator
+pub fn parse_number_in(input: &str, locale: NumberLocale) -> Result<ParsedNumber, String> {
+    let invalid = || format!("'{}' is not a valid number", input.trim());
+
+    let trimmed = input.trim();
+    let (negative, unsigned) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    // Spaces and apostrophes only ever group thousands ("1 250,5", "1'250.5")
+    let number: String = unsigned
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '\'' && *c != '\u{2019}')
+        .collect();
+    if !number.chars().any(|c| c.is_ascii_digit())
+        || !number
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == ',')
+    {
+        return Err(invalid());
+    }
+
+    let (decimal, group, warning) = match (number.rfind('.'), number.rfind(',')) {
+        (Some(point), Some(comma)) if point > comma => (Some('.'), Some(','), None),
+        (Some(_), Some(_)) => (Some(','), Some('.'), None),
+        (Some(_), None) => classify_separator(&number, '.', locale, trimmed),
+        (None, Some(_)) => classify_separator(&number, ',', locale, trimmed),
+        (None, None) => (None, None, None),
+    };
+
+    let (integer, fraction) = match decimal {
+        Some(separator) => number.rsplit_once(separator).ok_or_else(invalid)?,
+        None => (number.as_str(), ""),
+    };
+    if decimal.is_some_and(|separator| integer.contains(separator)) {
+        return Err(invalid());
+    }
+    let integer = match group {
+        Some(separator) if integer.contains(separator) => {
+            ungroup(integer, separator).ok_or_else(invalid)?
+        }
+        _ => integer.to_string(),
+    };
+    if integer.contains([',', '.']) || fraction.contains([',', '.']) {
+        return Err(invalid());
+    }
+
+    let normalized = format!(
+        "{}{}.{}",
+        if negative { "-" } else { "" },
+        if integer.is_empty() { "0" } else { &integer },
+        if fraction.is_empty() { "0" } else { fraction }
+    );
+    let value = normalized.parse::<f64>().map_err(|_| invalid())?;
+    let warning = warning.map(|warning| format!("{}, read as {}", warning, value));
+
+    Ok(ParsedNumber { value, warning })
+}
+
+/// Decide whether the only separator kind in a number is decimal or grouping
+///
+/// Returns (decimal separator, group separator, ambiguity warning).
+fn classify_separator(
+    number: &str,
+    separator: char,
+    locale: NumberLocale,
+    input: &str,
+) -> (Option<char>, Option<char>, Option<String>) {
+    if number.matches(separator).count() > 1 {
+        return (None, Some(separator), None);
+    }
+
+    let (integer, fraction) = number.split_once(separator).unwrap_or((number, ""));
+    let could_group =
+        fraction.len() == 3 && (1..=3).contains(&integer.len()) && !integer.starts_with('0');
+    if !could_group {
+        return (Some(separator), None, None);
+    }
+
+    let warning = Some(format!(
+        "'{}' is ambiguous (thousands or decimal separator), using the {:?} locale",
+        input, locale
+    ));
+    if locale.decimal_separator() == separator {
+        (Some(separator), None, warning)
+    } else {
+        (None, Some(separator), warning)
+    }
+}
+
+/// Remove thousands separators, None unless every group has three digits
+fn ungroup(integer: &str, separator: char) -> Option<String> {
+    let mut groups = integer.split(separator);
+    let first = groups.next()?;
+    if first.is_empty() || first.len() > 3 {
+        return None;
+    }
+    let mut digits = first.to_string();
+    for group in groups {
+        if group.len() != 3 {
+            return None;
+        }
+        digits.push_str(group);
+    }
+    Some(digits)
+}
+
+/// Dimension given as a JSON number or as a string typed by the user
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DimensionValue {
+    Number(f64),
+    Text(String),
+}
+
+impl DimensionValue {
+    fn into_mm(self) -> Result<f64, String> {
+        match self {
+            DimensionValue::Number(value) => Ok(value),
+            DimensionValue::Text(text) => parse_dimension(&text),
+        }
+    }
+}
+
+/// `deserialize_with` for dimensions that may arrive as strings
+pub fn deserialize_dimension<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    DimensionValue::deserialize(deserializer)?
+        .into_mm()
+        .map_err(serde::de::Error::custom)
+}
+
+/// `deserialize_with` for optional dimensions that may arrive as strings
+pub fn deserialize_optional_dimension<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<f64>, D::Error> {
+    Option::<DimensionValue>::deserialize(deserializer)?
+        .map(DimensionValue::into_mm)
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POINT: NumberLocale = NumberLocale::DecimalPoint;
+    const COMMA: NumberLocale = NumberLocale::DecimalComma;
+
+    fn value(input: &str, locale: NumberLocale) -> f64 {
+        parse_dimension_in(input, locale).unwrap().value
+    }
+
+    #[test]
+    fn test_plain_numbers() {
+        for locale in [POINT, COMMA] {
+            assert_eq!(value("1250", locale), 1250.0);
+            assert_eq!(value("  12.5 ", locale), 12.5);
+            assert_eq!(value("12,5", locale), 12.5);
+            assert_eq!(value("-3.75", locale), -3.75);
+            assert_eq!(value("+4", locale), 4.0);
+            assert_eq!(value(".5", locale), 0.5);
+            assert_eq!(value("0,250", locale), 0.25);
+        }
+    }
+
+    #[test]
+    fn test_grouped_numbers_in_any_locale() {
+        for locale in [POINT, COMMA] {
+            // German / Vietnamese
+            assert_eq!(value("1.250,5", locale), 1250.5);
+            assert_eq!(value("1.250.000", locale), 1_250_000.0);
+            // English
+            assert_eq!(value("1,250.5", locale), 1250.5);
+            assert_eq!(value("1,250,000", locale), 1_250_000.0);
+            // French, Swiss
+            assert_eq!(value("1 250,5", locale), 1250.5);
+            assert_eq!(value("1\u{a0}250,5", locale), 1250.5);
+            assert_eq!(value("1'250.5", locale), 1250.5);
+        }
+    }
+
+    #[test]
+    fn test_ambiguous_input_follows_locale() {
+        let point = parse_dimension_in("1,250", POINT).unwrap();
+        assert_eq!(point.value, 1250.0);
+        assert!(point.warning.unwrap().contains("ambiguous"));
+
+        let comma = parse_dimension_in("1,250", COMMA).unwrap();
+        assert_eq!(comma.value, 1.25);
+        assert!(comma.warning.is_some());
+
+        assert_eq!(value("1.250", POINT), 1.25);
+        assert_eq!(value("1.250", COMMA), 1250.0);
+
+        // Not ambiguous: more or fewer than three decimals, or a leading zero
+        for input in ["1,25", "1,2500", "1250,500", "0,125"] {
+            assert!(parse_dimension_in(input, POINT).unwrap().warning.is_none());
+        }
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(value("12.5mm", POINT), 12.5);
+        assert_eq!(value("12,5 MM", COMMA), 12.5);
+        assert_eq!(value("1,5 cm", COMMA), 15.0);
+        assert!((value("2 in", POINT) - 50.8).abs() < 1e-9);
+        assert!((value("0.5in", POINT) - 12.7).abs() < 1e-9);
+        assert_eq!(value("1.250,5 mm", POINT), 1250.5);
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        for input in [
+            "", " ", "mm", "abc", "12x", "1.2.3,4", "1,2.3.4", "12,34,5", "1..5", "--5", "5 m",
+            "1.5.", ",",
+        ] {
+            assert!(
+                parse_dimension_in(input, POINT).is_err(),
+                "'{}' should be rejected",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_language_tags() {
+        assert_eq!(NumberLocale::from_language_tag("vi-VN"), COMMA);
+        assert_eq!(NumberLocale::from_language_tag("de"), COMMA);
+        assert_eq!(NumberLocale::from_language_tag("en-US"), POINT);
+        assert_eq!(NumberLocale::from_language_tag(""), POINT);
+    }
+
+    #[test]
+    fn test_deserialize_numbers_and_strings() {
+        #[derive(Deserialize)]
+        struct Options {
+            #[serde(deserialize_with = "deserialize_dimension")]
+            strip_height: f64,
+            #[serde(default, deserialize_with = "deserialize_optional_dimension")]
+            separation: Option<f64>,
+        }
+
+        let options: Options =
+            serde_json::from_str(r#"{"strip_height": "1,500.5 mm", "separation": 2}"#).unwrap();
+        assert_eq!(options.strip_height, 1500.5);
+        assert_eq!(options.separation, Some(2.0));
+
+        let options: Options = serde_json::from_str(r#"{"strip_height": 6000}"#).unwrap();
+        assert_eq!(options.separation, None);
+
+        assert!(serde_json::from_str::<Options>(r#"{"strip_height": "wide"}"#).is_err());
+    }
+}
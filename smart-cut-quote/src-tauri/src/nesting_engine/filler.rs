@@ -0,0 +1,413 @@
+//! Stock filler parts dropped into the gaps of a finished layout
+//!
+//! Shops fill leftover space with small standard parts they always need
+//! (washers, test coupons). `filler_items` have unlimited demand and the
+//! lowest priority: once packing and the small-part pass are done, each
+//! filler is grid-filled into the free strip space the same way small
+//! parts are (see `small_parts`), largest first. Placed parts are never
+//! moved and the strip never grows; keep-out zones, defect zones and the
+//! separation are kept like for every other part.
+//!
+//! Filler placements are flagged `filler` and left out of the counts,
+//! utilization and cut totals of the order, `filler_summary` counts them
+//! instead. `max_filler_count` and `max_filler_area` cap the pass.
+
+use super::geometry::{open_ring, polygon_area, DisplayUnits, Polygon};
+use super::holes::{svg_path, HoleLayout};
+use super::keep_out::KeepOutZone;
+use super::remnant::ContainerPolygon;
+use super::serializer::{normalize_rotation, NestingOutput, PlacedItem};
+use super::small_parts::{place_small_parts, SmallItem, SmallPartPlan, SmallPlacement};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Most filler parts placed when the input sets no count cap
+pub const DEFAULT_MAX_FILLER_COUNT: usize = 100;
+
+/// Share of the sheet area fillers may cover when the input sets no area cap
+pub const DEFAULT_MAX_FILLER_SHARE: f64 = 0.1;
+
+/// Standard part used to fill leftover space
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FillerItem {
+    /// Reported as the `item_id` of its placements, must not be the ID of
+    /// an instance item
+    pub id: usize,
+    /// Name shown in the filler summary (e.g. "Washer M8")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Outer contour in mm
+    pub outline: Polygon,
+    /// Orientation the filler is placed in (degrees)
+    #[serde(default)]
+    pub rotation_degrees: f64,
+}
+
+impl FillerItem {
+    /// Filler given in `from` units, in `units`
+    pub fn in_units(self, units: DisplayUnits, from: DisplayUnits) -> Self {
+        FillerItem {
+            outline: units.polygon_from(&self.outline, from),
+            ..self
+        }
+    }
+}
+
+/// Fillers placed of one filler item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FillerCount {
+    pub item_id: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub count: usize,
+}
+
+/// Fillers of a layout, counted apart from the ordered parts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FillerSummary {
+    pub count: usize,
+    /// Net area of all fillers placed
+    pub area: f64,
+    /// Per filler item, in the order of `filler_items`
+    pub items: Vec<FillerCount>,
+    /// The count or the area cap stopped the pass, more would have fit
+    pub capped: bool,
+}
+
+/// Outcome of the filler pass
+#[derive(Debug, Clone, Default)]
+pub struct FillerLayout {
+    pub placements: Vec<SmallPlacement>,
+    pub summary: FillerSummary,
+}
+
+/// Fillers that cannot be placed: degenerate outlines or repeated IDs,
+/// or IDs taken by an instance item
+pub fn validate_filler_items(fillers: &[FillerItem], item_ids: &[u64]) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for filler in fillers {
+        let ring = open_ring(&filler.outline);
+        if ring.iter().any(|&(x, y)| !x.is_finite() || !y.is_finite()) {
+            return Err(format!("Filler {} has non-finite coordinates", filler.id));
+        }
+        if ring.len() < 3 || polygon_area(ring) <= 0.0 {
+            return Err(format!(
+                "Filler {} needs at least 3 points enclosing an area",
+                filler.id
+            ));
+        }
+        if !filler.rotation_degrees.is_finite() {
+            return Err(format!("Filler {} has a non-finite rotation", filler.id));
+        }
+        if !seen.insert(filler.id) {
+            return Err(format!("Filler ID {} is used twice", filler.id));
+        }
+        if item_ids.contains(&(filler.id as u64)) {
+            return Err(format!(
+                "Filler ID {} is the ID of an instance item",
+                filler.id
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Grid-fill the fillers into the space `placed` leaves free
+///
+/// `placed` are the outlines of everything already on the strip (packed,
+/// small and locked parts, defect zones). Fillers are tried largest
+/// first, each as often as the caps still allow; `max_area` is the total
+/// net area in mm².
+#[allow(clippy::too_many_arguments)]
+pub fn place_fillers(
+    fillers: &[FillerItem],
+    placed: &[Polygon],
+    keep_out_zones: &[KeepOutZone],
+    container: Option<&ContainerPolygon>,
+    strip: (f64, f64),
+    separation: f64,
+    max_count: usize,
+    max_area: f64,
+) -> FillerLayout {
+    let mut items: Vec<(&FillerItem, SmallItem)> = fillers
+        .iter()
+        .filter_map(|filler| {
+            let net_area = polygon_area(open_ring(&filler.outline));
+            let item = SmallItem::new(
+                filler.id,
+                0,
+                filler.rotation_degrees,
+                &filler.outline,
+                0.0,
+                net_area,
+            )?;
+            Some((filler, item))
+        })
+        .collect();
+    items.sort_by(|(_, a), (_, b)| b.net_area.total_cmp(&a.net_area));
+
+    let mut layout = FillerLayout::default();
+    let mut obstacles = placed.to_vec();
+    for (filler, mut item) in items {
+        if item.too_small() {
+            println!("⚠️ Filler {} is too small to place", filler.id);
+            continue;
+        }
+        let count_left = max_count - layout.summary.count;
+        let area_left = (max_area - layout.summary.area).max(0.0);
+        item.demand = count_left.min((area_left / item.net_area).floor() as usize);
+        if item.demand == 0 {
+            layout.summary.capped = true;
+            continue;
+        }
+
+        let demand = item.demand;
+        let plan = SmallPartPlan { items: vec![item] };
+        let mut placements = place_small_parts(
+            &plan,
+            &obstacles,
+            &HoleLayout::default(),
+            keep_out_zones,
+            strip,
+            separation,
+        )
+        .placements;
+        if let Some(container) = container {
+            placements.retain(|placement| container.contains(&placement.outline));
+        }
+        if placements.len() == demand {
+            layout.summary.capped = true;
+        }
+
+        layout.summary.count += placements.len();
+        layout.summary.area += plan.items[0].net_area * placements.len() as f64;
+        obstacles.extend(placements.iter().map(|placement| placement.outline.clone()));
+        layout.placements.extend(placements);
+    }
+
+    layout.summary.items = fillers
+        .iter()
+        .map(|filler| FillerCount {
+            item_id: filler.id,
+            label: filler.label.clone(),
+            count: layout
+                .placements
+                .iter()
+                .filter(|placement| placement.item_id == filler.id)
+                .count(),
+        })
+        .collect();
+    if layout.summary.count > 0 {
+        println!(
+            "🧱 {} filler parts placed ({:.0} mm²)",
+            layout.summary.count, layout.summary.area
+        );
+    }
+    layout
+}
+
+/// Add the fillers to the output
+///
+/// They are no part of the order: the counts, utilization and unplaced
+/// list stay as they are, `filler_summary` reports them.
+pub fn attach_fillers(output: &mut NestingOutput, layout: &FillerLayout) {
+    for placement in &layout.placements {
+        output.layouts.push(PlacedItem {
+            item_id: placement.item_id,
+            rotation_degrees: normalize_rotation(placement.rotation_degrees, None),
+            position_x: placement.position.0,
+            position_y: placement.position.1,
+            host_item_id: None,
+            outline: None,
+            display_outline: None,
+            metadata: None,
+            bend_line_angle_deg: None,
+            post_placed: false,
+            mirrored: false,
+            locked: false,
+            rotation_radians: None,
+            filler: true,
+        });
+    }
+    output.filler_summary = Some(layout.summary.clone());
+}
+
+/// Render the fillers as an SVG overlay
+pub fn render_filler_overlay(layout: &FillerLayout) -> String {
+    let mut overlay = String::from(r#"<g id="fillers" pointer-events="none">"#);
+    for placement in &layout.placements {
+        overlay.push_str(&format!(
+            r#"<path d="{}" fill="lightgray" fill-opacity="0.8" stroke="dimgray" stroke-width="0.5" stroke-dasharray="2,1"/>"#,
+            svg_path(&placement.outline)
+        ));
+    }
+    overlay.push_str("</g>");
+    overlay
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::geometry::boundary_distance;
+    use super::super::{run_nesting_engine, NestingInput};
+    use super::*;
+
+    fn square(id: usize, size: f64) -> FillerItem {
+        FillerItem {
+            id,
+            label: Some(format!("Coupon {}", size)),
+            outline: vec![(0.0, 0.0), (size, 0.0), (size, size), (0.0, size)],
+            rotation_degrees: 0.0,
+        }
+    }
+
+    /// Outline of a placed part
+    fn part(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Polygon {
+        vec![
+            (min_x, min_y),
+            (max_x, min_y),
+            (max_x, max_y),
+            (min_x, max_y),
+        ]
+    }
+
+    #[test]
+    fn test_fillers_keep_clear_of_placed_parts() {
+        let placed = vec![part(0.0, 0.0, 60.0, 50.0), part(60.0, 0.0, 80.0, 20.0)];
+        let zones = vec![KeepOutZone {
+            x: 90.0,
+            y: 0.0,
+            width: 10.0,
+            height: 50.0,
+            label: None,
+        }];
+        let layout = place_fillers(
+            &[square(7, 4.0), square(8, 8.0)],
+            &placed,
+            &zones,
+            None,
+            (100.0, 50.0),
+            1.0,
+            DEFAULT_MAX_FILLER_COUNT,
+            f64::INFINITY,
+        );
+
+        assert!(layout.summary.count > 0);
+        assert_eq!(layout.summary.count, layout.placements.len());
+        for (i, filler) in layout.placements.iter().enumerate() {
+            for outline in &placed {
+                assert!(boundary_distance(&filler.outline, outline) >= 1.0 - 1e-9);
+            }
+            assert!(!zones[0].overlaps(&filler.outline));
+            assert!(filler
+                .outline
+                .iter()
+                .all(|&(x, y)| (0.0..=100.0).contains(&x) && (0.0..=50.0).contains(&y)));
+            for other in &layout.placements[i + 1..] {
+                assert!(boundary_distance(&filler.outline, &other.outline) >= 1.0 - 1e-9);
+            }
+        }
+        // The larger filler goes first
+        assert_eq!(layout.placements[0].item_id, 8);
+        assert_eq!(
+            layout
+                .summary
+                .items
+                .iter()
+                .map(|item| item.item_id)
+                .collect::<Vec<_>>(),
+            vec![7, 8]
+        );
+    }
+
+    #[test]
+    fn test_no_fillers_on_a_full_sheet() {
+        let layout = place_fillers(
+            &[square(7, 4.0)],
+            &[part(0.0, 0.0, 100.0, 50.0)],
+            &[],
+            None,
+            (100.0, 50.0),
+            1.0,
+            DEFAULT_MAX_FILLER_COUNT,
+            f64::INFINITY,
+        );
+        assert!(layout.placements.is_empty());
+        assert_eq!(layout.summary.count, 0);
+        assert!(!layout.summary.capped);
+    }
+
+    #[test]
+    fn test_caps_limit_the_pass() {
+        let fillers = [square(7, 4.0)];
+        let layout = place_fillers(
+            &fillers,
+            &[],
+            &[],
+            None,
+            (100.0, 50.0),
+            1.0,
+            5,
+            f64::INFINITY,
+        );
+        assert_eq!(layout.summary.count, 5);
+        assert!(layout.summary.capped);
+
+        // 16 mm² each: 100 mm² holds 6
+        let layout = place_fillers(&fillers, &[], &[], None, (100.0, 50.0), 1.0, 100, 100.0);
+        assert_eq!(layout.summary.count, 6);
+        assert!((layout.summary.area - 96.0).abs() < 1e-9);
+        assert!(layout.summary.capped);
+    }
+
+    #[test]
+    fn test_filler_validation() {
+        assert!(validate_filler_items(&[square(7, 4.0), square(8, 2.0)], &[0, 1]).is_ok());
+        assert!(validate_filler_items(&[square(7, 4.0), square(7, 2.0)], &[]).is_err());
+        assert!(validate_filler_items(&[square(1, 4.0)], &[0, 1]).is_err());
+
+        let mut flat = square(7, 4.0);
+        flat.outline.truncate(2);
+        assert!(validate_filler_items(&[flat], &[]).is_err());
+    }
+
+    #[test]
+    fn test_fillers_stay_out_of_the_order() {
+        // A 40 x 30 plate on a 50 mm strip leaves a 20 mm band free
+        let instance = serde_json::json!({
+            "name": "plate",
+            "strip_height": 50.0,
+            "items": [{
+                "id": 0,
+                "demand": 1,
+                "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 40.0, "height": 30.0 } }
+            }]
+        })
+        .to_string();
+        let input: NestingInput = serde_json::from_value(serde_json::json!({
+            "json_input": instance,
+            "time_limit": 1,
+            "seed": 7,
+            "use_early_termination": true,
+            "separation": 0.0,
+            "filler_items": [
+                { "id": 100, "label": "Coupon", "outline": [[0, 0], [5, 0], [5, 5], [0, 5]] }
+            ],
+            "max_filler_count": 3,
+        }))
+        .unwrap();
+        let output = run_nesting_engine(input).unwrap();
+
+        let fillers: Vec<&PlacedItem> = output.layouts.iter().filter(|p| p.filler).collect();
+        assert_eq!(fillers.len(), 3);
+        assert!(fillers.iter().all(|placed| placed.item_id == 100));
+        assert_eq!(output.total_items_placed, 1);
+        let strip_area = output.strip_width * output.strip_height;
+        assert!((output.utilization - 1200.0 / strip_area).abs() < 1e-6);
+        assert_eq!(output.item_cuts.len(), 1);
+        assert_eq!(output.item_cuts[0].item_id, 0);
+        let summary = output.filler_summary.unwrap();
+        assert_eq!(summary.count, 3);
+        assert!((summary.area - 75.0).abs() < 1e-9);
+        assert!(summary.capped);
+    }
+}
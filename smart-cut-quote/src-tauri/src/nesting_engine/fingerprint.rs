@@ -0,0 +1,247 @@
+//! Instance fingerprints for reproducible re-nesting
+//!
+//! A fingerprint hashes the geometry and the quantities of an instance
+//! separately, so a mismatch can be reported as "quantities changed"
+//! or "geometry changed" instead of a single opaque hash difference.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Hashes describing a strip packing instance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstanceFingerprint {
+    /// Hash over strip height, geometry and quantities
+    pub instance_hash: String,
+    /// Hash over the geometry of all items (ignores quantities)
+    pub geometry_hash: String,
+    /// Fixed strip height from the instance
+    pub strip_height: f64,
+    /// Geometry hash per item ID
+    pub item_geometry: BTreeMap<u64, String>,
+    /// Demand per item ID
+    pub quantities: BTreeMap<u64, u64>,
+}
+
+/// Difference between a stored and a current fingerprint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FingerprintChange {
+    /// Strip height differs
+    StripHeightChanged { stored: f64, current: f64 },
+    /// Item exists only in the current instance
+    ItemAdded { item_id: u64 },
+    /// Item exists only in the stored instance
+    ItemRemoved { item_id: u64 },
+    /// Demand of an item differs
+    QuantityChanged {
+        item_id: u64,
+        stored: u64,
+        current: u64,
+    },
+    /// Shape or allowed orientations of an item differ
+    GeometryChanged { item_id: u64 },
+}
+
+/// Compute the fingerprint of an instance JSON string
+///
+/// Only `id`, `shape` and `allowed_orientations` count as geometry, so
+/// renaming the source DXF file does not change the hash.
+pub fn fingerprint_instance(json_str: &str) -> Result<InstanceFingerprint, String> {
+    let value: Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+
+    let strip_height = value
+        .get("strip_height")
+        .and_then(Value::as_f64)
+        .ok_or("Instance is missing 'strip_height'")?;
+
+    let items = value
+        .get("items")
+        .and_then(Value::as_array)
+        .ok_or("Instance is missing 'items'")?;
+
+    let mut item_geometry = BTreeMap::new();
+    let mut quantities = BTreeMap::new();
+
+    for item in items {
+        let id = item
+            .get("id")
+            .and_then(Value::as_u64)
+            .ok_or("Instance item is missing 'id'")?;
+        let demand = item.get("demand").and_then(Value::as_u64).unwrap_or(0);
+
+        let geometry = serde_json::json!({
+            "shape": item.get("shape").cloned().unwrap_or(Value::Null),
+            "allowed_orientations": item.get("allowed_orientations").cloned().unwrap_or(Value::Null),
+        });
+
+        item_geometry.insert(id, sha256_hex(&canonical_json(&geometry)));
+        quantities.insert(id, demand);
+    }
+
+    let geometry_hash = sha256_hex(&canonical_json(
+        &serde_json::to_value(&item_geometry).unwrap_or_default(),
+    ));
+    let instance_hash = sha256_hex(&format!(
+        "{}|{}|{}",
+        strip_height,
+        geometry_hash,
+        canonical_json(&serde_json::to_value(&quantities).unwrap_or_default())
+    ));
+
+    Ok(InstanceFingerprint {
+        instance_hash,
+        geometry_hash,
+        strip_height,
+        item_geometry,
+        quantities,
+    })
+}
+
+/// List what changed between a stored and a current fingerprint
+///
+/// Returns an empty list when both describe the same instance.
+pub fn compare_fingerprints(
+    stored: &InstanceFingerprint,
+    current: &InstanceFingerprint,
+) -> Vec<FingerprintChange> {
+    let mut changes = Vec::new();
+
+    if stored.instance_hash == current.instance_hash {
+        return changes;
+    }
+
+    if stored.strip_height != current.strip_height {
+        changes.push(FingerprintChange::StripHeightChanged {
+            stored: stored.strip_height,
+            current: current.strip_height,
+        });
+    }
+
+    for (&item_id, stored_hash) in stored.item_geometry.iter() {
+        match current.item_geometry.get(&item_id) {
+            None => changes.push(FingerprintChange::ItemRemoved { item_id }),
+            Some(current_hash) => {
+                if current_hash != stored_hash {
+                    changes.push(FingerprintChange::GeometryChanged { item_id });
+                }
+
+                let stored_qty = stored.quantities.get(&item_id).copied().unwrap_or(0);
+                let current_qty = current.quantities.get(&item_id).copied().unwrap_or(0);
+                if stored_qty != current_qty {
+                    changes.push(FingerprintChange::QuantityChanged {
+                        item_id,
+                        stored: stored_qty,
+                        current: current_qty,
+                    });
+                }
+            }
+        }
+    }
+
+    for &item_id in current.item_geometry.keys() {
+        if !stored.item_geometry.contains_key(&item_id) {
+            changes.push(FingerprintChange::ItemAdded { item_id });
+        }
+    }
+
+    changes
+}
+
+/// Serialize a JSON value with object keys sorted
+///
+/// Keeps hashes stable regardless of the key order used by the writer.
+pub fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|key| {
+                    format!(
+                        "{}:{}",
+                        Value::String(key.clone()),
+                        canonical_json(&map[key])
+                    )
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(values) => {
+            let values: Vec<String> = values.iter().map(canonical_json).collect();
+            format!("[{}]", values.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Lowercase hex SHA-256 of a string
+pub fn sha256_hex(data: &str) -> String {
+    Sha256::digest(data.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTANCE: &str = r#"{
+        "name": "test",
+        "strip_height": 300.0,
+        "items": [
+            {"id": 0, "demand": 2, "dxf": "a.dxf", "allowed_orientations": [0.0, 90.0],
+             "shape": {"type": "simple_polygon", "data": [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0]]}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_fingerprint_ignores_key_order_and_file_name() {
+        let reordered = r#"{
+            "items": [
+                {"shape": {"data": [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0]], "type": "simple_polygon"},
+                 "allowed_orientations": [0.0, 90.0], "dxf": "renamed.dxf", "demand": 2, "id": 0}
+            ],
+            "strip_height": 300.0,
+            "name": "other"
+        }"#;
+
+        let a = fingerprint_instance(INSTANCE).unwrap();
+        let b = fingerprint_instance(reordered).unwrap();
+        assert_eq!(a.instance_hash, b.instance_hash);
+        assert!(compare_fingerprints(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn test_fingerprint_reports_quantity_change() {
+        let changed = INSTANCE.replace("\"demand\": 2", "\"demand\": 5");
+
+        let a = fingerprint_instance(INSTANCE).unwrap();
+        let b = fingerprint_instance(&changed).unwrap();
+        assert_eq!(a.geometry_hash, b.geometry_hash);
+        assert_eq!(
+            compare_fingerprints(&a, &b),
+            vec![FingerprintChange::QuantityChanged {
+                item_id: 0,
+                stored: 2,
+                current: 5
+            }]
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_reports_geometry_change() {
+        let changed = INSTANCE.replace("[10.0, 10.0]", "[10.0, 12.0]");
+
+        let a = fingerprint_instance(INSTANCE).unwrap();
+        let b = fingerprint_instance(&changed).unwrap();
+        assert_eq!(
+            compare_fingerprints(&a, &b),
+            vec![FingerprintChange::GeometryChanged { item_id: 0 }]
+        );
+    }
+}
@@ -0,0 +1,639 @@
+//! Plain polygon helpers used by the output post-processing
+//!
+//! These work on `(x, y)` vertex lists in strip coordinates so they
+//! can be used without touching the jagua-rs geometry types. The
+//! module is public so Tauri commands (e.g. DXF healing) share the
+//! same simplification instead of keeping their own copy.
+//! Contours with true arc segments live in `PartGeometry`.
+
+mod part;
+mod units;
+
+pub use part::{Contour, ContourVertex, PartGeometry};
+pub use units::{DisplayUnits, MM_PER_INCH};
+
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+/// Polygon vertex list in strip coordinates (mm)
+pub type Polygon = Vec<(f64, f64)>;
+
+/// Absolute area of a simple polygon (shoelace formula)
+///
+/// Works for both clockwise and counter-clockwise vertex order.
+/// A closing vertex equal to the first one is allowed.
+pub fn polygon_area(points: &[(f64, f64)]) -> f64 {
+    signed_area(points).abs()
+}
+
+/// Signed area of a simple polygon (shoelace formula)
+///
+/// Positive for counter-clockwise, negative for clockwise vertex order
+/// (with the y axis pointing up).
+pub fn signed_area(points: &[(f64, f64)]) -> f64 {
+    if points.len() < 3 {
+        return 0.0;
+    }
+
+    let mut twice_area = 0.0;
+    for i in 0..points.len() {
+        let (x1, y1) = points[i];
+        let (x2, y2) = points[(i + 1) % points.len()];
+        twice_area += x1 * y2 - x2 * y1;
+    }
+
+    twice_area / 2.0
+}
+
+/// Perimeter of a closed polygon
+///
+/// The closing edge from the last to the first vertex is included.
+pub fn polygon_perimeter(points: &[(f64, f64)]) -> f64 {
+    if points.len() < 2 {
+        return 0.0;
+    }
+
+    (0..points.len())
+        .map(|i| {
+            let (x1, y1) = points[i];
+            let (x2, y2) = points[(i + 1) % points.len()];
+            ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt()
+        })
+        .sum()
+}
+
+/// Drop the closing vertex if the ring repeats its first point
+pub fn open_ring(points: &[(f64, f64)]) -> &[(f64, f64)] {
+    match (points.first(), points.last()) {
+        (Some(first), Some(last)) if points.len() > 1 && first == last => {
+            &points[..points.len() - 1]
+        }
+        _ => points,
+    }
+}
+
+/// Axis-aligned bounding box as `(min_x, min_y, max_x, max_y)`
+///
+/// Returns `None` for an empty vertex list.
+pub fn bounding_box(points: &[(f64, f64)]) -> Option<(f64, f64, f64, f64)> {
+    let (&(x0, y0), rest) = points.split_first()?;
+
+    Some(
+        rest.iter()
+            .fold((x0, y0, x0, y0), |(min_x, min_y, max_x, max_y), &(x, y)| {
+                (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+            }),
+    )
+}
+
+/// Rotate a polygon around the origin (counter-clockwise, degrees)
+pub fn rotate_polygon(points: &[(f64, f64)], degrees: f64) -> Polygon {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    points
+        .iter()
+        .map(|&(x, y)| (x * cos - y * sin, x * sin + y * cos))
+        .collect()
+}
+
+/// Mirror a polygon across the Y axis (x → -x), which reverses its winding
+pub fn mirror_polygon(points: &[(f64, f64)]) -> Polygon {
+    points.iter().map(|&(x, y)| (-x, y)).collect()
+}
+
+/// Translate a polygon by `(dx, dy)`
+pub fn translate_polygon(points: &[(f64, f64)], dx: f64, dy: f64) -> Polygon {
+    points.iter().map(|&(x, y)| (x + dx, y + dy)).collect()
+}
+
+/// Convex hull of a point set, counter-clockwise (Andrew's monotone chain)
+///
+/// Duplicate and collinear points are dropped; fewer than three points
+/// come back as they are, deduplicated.
+pub fn convex_hull(points: &[(f64, f64)]) -> Polygon {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let mut hull = hull_chain(sorted.iter());
+    hull.extend(hull_chain(sorted.iter().rev()));
+    hull
+}
+
+/// Half of a convex hull over points sorted along X, without its last
+/// point (it starts the other half)
+fn hull_chain<'a>(points: impl Iterator<Item = &'a (f64, f64)>) -> Polygon {
+    let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| {
+        (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+    };
+    let mut chain: Polygon = Vec::new();
+    for &point in points {
+        while chain.len() >= 2
+            && cross(chain[chain.len() - 2], chain[chain.len() - 1], point) <= 0.0
+        {
+            chain.pop();
+        }
+        chain.push(point);
+    }
+    chain.pop();
+    chain
+}
+
+/// True if the point lies strictly inside the polygon (even-odd rule)
+pub fn point_in_polygon(point: (f64, f64), polygon: &[(f64, f64)]) -> bool {
+    let (px, py) = point;
+    let mut inside = false;
+    let mut j = polygon.len().wrapping_sub(1);
+
+    for i in 0..polygon.len() {
+        let (xi, yi) = polygon[i];
+        let (xj, yj) = polygon[j];
+        if (yi > py) != (yj > py) && px < (xj - xi) * (py - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+
+    inside
+}
+
+/// Smallest distance between the boundaries of two polygons
+///
+/// Zero when any edges cross or touch.
+pub fn boundary_distance(a: &[(f64, f64)], b: &[(f64, f64)]) -> f64 {
+    let mut min_distance = f64::INFINITY;
+
+    for i in 0..a.len() {
+        let a1 = a[i];
+        let a2 = a[(i + 1) % a.len()];
+        for j in 0..b.len() {
+            let b1 = b[j];
+            let b2 = b[(j + 1) % b.len()];
+            min_distance = min_distance.min(segment_distance(a1, a2, b1, b2));
+            if min_distance == 0.0 {
+                return 0.0;
+            }
+        }
+    }
+
+    min_distance
+}
+
+/// Edges `(i, j)` of the first pair of non-adjacent edges that touch or cross
+///
+/// Edge pairs whose bounding boxes are apart are skipped, which keeps the
+/// scan fast enough for finely tessellated part outlines.
+pub fn self_intersection(ring: &[(f64, f64)]) -> Option<(usize, usize)> {
+    let n = ring.len();
+    let edge = |i: usize| [ring[i], ring[(i + 1) % n]];
+    let boxes: Vec<_> = (0..n).filter_map(|i| bounding_box(&edge(i))).collect();
+    for i in 0..n {
+        for j in i + 2..n {
+            if i == 0 && j == n - 1 {
+                continue;
+            }
+            let (a, b) = (boxes[i], boxes[j]);
+            if a.0 > b.2 || b.0 > a.2 || a.1 > b.3 || b.1 > a.3 {
+                continue;
+            }
+            if boundary_distance(&edge(i), &edge(j)) == 0.0 {
+                return Some((i, j));
+            }
+        }
+    }
+    None
+}
+
+/// Distance between segments `a1-a2` and `b1-b2` (zero if they intersect)
+fn segment_distance(a1: (f64, f64), a2: (f64, f64), b1: (f64, f64), b2: (f64, f64)) -> f64 {
+    let cross = |o: (f64, f64), p: (f64, f64), q: (f64, f64)| {
+        (p.0 - o.0) * (q.1 - o.1) - (p.1 - o.1) * (q.0 - o.0)
+    };
+
+    let d1 = cross(b1, b2, a1);
+    let d2 = cross(b1, b2, a2);
+    let d3 = cross(a1, a2, b1);
+    let d4 = cross(a1, a2, b2);
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return 0.0;
+    }
+
+    point_segment_distance(a1, b1, b2)
+        .min(point_segment_distance(a2, b1, b2))
+        .min(point_segment_distance(b1, a1, a2))
+        .min(point_segment_distance(b2, a1, a2))
+}
+
+fn point_segment_distance(p: (f64, f64), s1: (f64, f64), s2: (f64, f64)) -> f64 {
+    let (dx, dy) = (s2.0 - s1.0, s2.1 - s1.1);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > 0.0 {
+        (((p.0 - s1.0) * dx + (p.1 - s1.1) * dy) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (cx, cy) = (s1.0 + t * dx, s1.1 + t * dy);
+    ((p.0 - cx).powi(2) + (p.1 - cy).powi(2)).sqrt()
+}
+
+/// Simplify an open polyline (Douglas-Peucker)
+///
+/// Keeps the end points and every vertex needed so that no dropped
+/// vertex is further than `tolerance` from the simplified line.
+pub fn simplify_polyline(points: &[(f64, f64)], tolerance: f64) -> Polygon {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    // Explicit stack instead of recursion, outlines can have many vertices
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let mut farthest = (0.0, start);
+        for i in start + 1..end {
+            let distance = point_segment_distance(points[i], points[start], points[end]);
+            if distance > farthest.0 {
+                farthest = (distance, i);
+            }
+        }
+
+        let (distance, index) = farthest;
+        if distance > tolerance {
+            keep[index] = true;
+            stack.push((start, index));
+            stack.push((index, end));
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(&point, keep)| keep.then_some(point))
+        .collect()
+}
+
+/// Simplify a closed outline for display (Douglas-Peucker)
+///
+/// The ring is split at its first vertex and the vertex farthest from
+/// it, and both halves are simplified separately. The result is an
+/// open ring made of original vertices, at least a triangle when the
+/// input has three or more vertices.
+pub fn simplify_ring(points: &[(f64, f64)], tolerance: f64) -> Polygon {
+    let ring = open_ring(points);
+    if ring.len() <= 3 {
+        return ring.to_vec();
+    }
+
+    let first = ring[0];
+    let split = (1..ring.len())
+        .max_by(|&a, &b| {
+            let da = (ring[a].0 - first.0).powi(2) + (ring[a].1 - first.1).powi(2);
+            let db = (ring[b].0 - first.0).powi(2) + (ring[b].1 - first.1).powi(2);
+            da.total_cmp(&db)
+        })
+        .unwrap_or(1);
+
+    let mut closing_half = ring[split..].to_vec();
+    closing_half.push(first);
+
+    let mut simplified = simplify_polyline(&ring[..=split], tolerance);
+    simplified.pop();
+    simplified.extend(simplify_polyline(&closing_half, tolerance));
+    simplified.pop();
+
+    if simplified.len() < 3 {
+        // Degenerate at this tolerance (e.g. a thin sliver), keep the most
+        // distant vertex from the split line as the third corner
+        let third = (1..ring.len())
+            .filter(|&i| i != split)
+            .max_by(|&a, &b| {
+                point_segment_distance(ring[a], first, ring[split])
+                    .total_cmp(&point_segment_distance(ring[b], first, ring[split]))
+            });
+        simplified = match third {
+            Some(third) if third < split => vec![first, ring[third], ring[split]],
+            Some(third) => vec![first, ring[split], ring[third]],
+            None => ring.to_vec(),
+        };
+    }
+
+    simplified
+}
+
+/// Clip a polygon against an axis-aligned rectangle (Sutherland-Hodgman)
+///
+/// The clip window is convex, so the area of the returned polygon is
+/// exactly the area of the subject polygon inside the rectangle, even
+/// when the subject polygon is concave.
+pub fn clip_polygon_to_rect(
+    points: &[(f64, f64)],
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+) -> Polygon {
+    let mut output: Polygon = points.to_vec();
+
+    for edge in [
+        ClipEdge::Left(min_x),
+        ClipEdge::Right(max_x),
+        ClipEdge::Bottom(min_y),
+        ClipEdge::Top(max_y),
+    ] {
+        if output.is_empty() {
+            break;
+        }
+
+        let input = std::mem::take(&mut output);
+        let mut prev = input[input.len() - 1];
+
+        for &current in input.iter() {
+            match (edge.inside(current), edge.inside(prev)) {
+                (true, true) => output.push(current),
+                (true, false) => {
+                    output.push(edge.intersect(prev, current));
+                    output.push(current);
+                }
+                (false, true) => output.push(edge.intersect(prev, current)),
+                (false, false) => {}
+            }
+            prev = current;
+        }
+    }
+
+    output
+}
+
+/// One boundary line of the rectangular clip window
+#[derive(Clone, Copy)]
+enum ClipEdge {
+    Left(f64),
+    Right(f64),
+    Bottom(f64),
+    Top(f64),
+}
+
+impl ClipEdge {
+    fn inside(self, p: (f64, f64)) -> bool {
+        match self {
+            ClipEdge::Left(x) => p.0 >= x,
+            ClipEdge::Right(x) => p.0 <= x,
+            ClipEdge::Bottom(y) => p.1 >= y,
+            ClipEdge::Top(y) => p.1 <= y,
+        }
+    }
+
+    fn intersect(self, a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+        match self {
+            ClipEdge::Left(x) | ClipEdge::Right(x) => {
+                let t = (x - a.0) / (b.0 - a.0);
+                (x, a.1 + t * (b.1 - a.1))
+            }
+            ClipEdge::Bottom(y) | ClipEdge::Top(y) => {
+                let t = (y - a.1) / (b.1 - a.1);
+                (a.0 + t * (b.0 - a.0), y)
+            }
+        }
+    }
+}
+
+/// Largest angle between two points of a round corner in `offset_ring`
+const OFFSET_ROUND_STEP: f64 = std::f64::consts::PI / 18.0;
+
+/// Grow a simple polygon outward by `distance` (mm)
+///
+/// Convex corners get a round join, with points at most 10° apart and
+/// at the axis-aligned extremes, so the bounding box grows by exactly
+/// `distance` on every side. Reflex corners get the intersection of the
+/// two offset edges, which is exact as long as no notch is narrower
+/// than `2 * distance`. Works for either vertex order; the result keeps
+/// the input's order and has no closing vertex.
+pub fn offset_ring(points: &[(f64, f64)], distance: f64) -> Polygon {
+    let mut ring: Polygon = Vec::with_capacity(points.len());
+    for &point in open_ring(points) {
+        if ring.last() != Some(&point) {
+            ring.push(point);
+        }
+    }
+    if ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+    let n = ring.len();
+    if n < 3 || distance <= 0.0 {
+        return ring;
+    }
+
+    // Outward is to the right of the travel direction for CCW rings
+    let orientation = signed_area(&ring).signum();
+    let normal = |a: (f64, f64), b: (f64, f64)| {
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        (orientation * dy / length, -orientation * dx / length)
+    };
+
+    let mut grown = Vec::with_capacity(n * 2);
+    for i in 0..n {
+        let (prev, vertex, next) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+        let (n1, n2) = (normal(prev, vertex), normal(vertex, next));
+        let turn = n1.0 * n2.1 - n1.1 * n2.0;
+        let at = |angle: f64| {
+            (
+                vertex.0 + distance * angle.cos(),
+                vertex.1 + distance * angle.sin(),
+            )
+        };
+
+        if turn * orientation > 1e-12 {
+            // Convex corner: arc from one edge normal to the other
+            let start = n1.1.atan2(n1.0);
+            let sweep = (orientation * (n2.1.atan2(n2.0) - start)).rem_euclid(TAU);
+            let mut angles = vec![0.0];
+            angles.extend(
+                (0..4)
+                    .map(|quarter| {
+                        (orientation * (quarter as f64 * FRAC_PI_2 - start)).rem_euclid(TAU)
+                    })
+                    .filter(|&angle| angle > 0.0 && angle < sweep),
+            );
+            angles.push(sweep);
+            angles.sort_by(f64::total_cmp);
+
+            grown.push(at(start));
+            for pair in angles.windows(2) {
+                let pieces = ((pair[1] - pair[0]) / OFFSET_ROUND_STEP).ceil().max(1.0) as usize;
+                for k in 1..=pieces {
+                    let angle = pair[0] + (pair[1] - pair[0]) * k as f64 / pieces as f64;
+                    grown.push(at(start + orientation * angle));
+                }
+            }
+        } else {
+            // Straight or reflex corner: where the offset edges meet
+            let dot = n1.0 * n2.0 + n1.1 * n2.1;
+            if dot > -0.9 {
+                let scale = distance / (1.0 + dot);
+                grown.push((
+                    vertex.0 + (n1.0 + n2.0) * scale,
+                    vertex.1 + (n1.1 + n2.1) * scale,
+                ));
+            } else {
+                grown.push((vertex.0 + n1.0 * distance, vertex.1 + n1.1 * distance));
+                grown.push((vertex.0 + n2.0 * distance, vertex.1 + n2.1 * distance));
+            }
+        }
+    }
+
+    grown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Small deterministic generator so the property tests are reproducible
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next_f64(&mut self) -> f64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64
+        }
+    }
+
+    /// Star-shaped outline with a noisy radius, like a tessellated DXF part
+    fn random_outline(rng: &mut Lcg) -> Polygon {
+        let n = 20 + (rng.next_f64() * 400.0) as usize;
+        let base = 10.0 + rng.next_f64() * 500.0;
+        (0..n)
+            .map(|i| {
+                let angle = i as f64 / n as f64 * std::f64::consts::TAU;
+                let radius = base * (0.6 + 0.4 * rng.next_f64());
+                (radius * angle.cos(), radius * angle.sin())
+            })
+            .collect()
+    }
+
+    fn distance_to_ring(point: (f64, f64), ring: &[(f64, f64)]) -> f64 {
+        (0..ring.len())
+            .map(|i| point_segment_distance(point, ring[i], ring[(i + 1) % ring.len()]))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn test_simplified_ring_stays_within_tolerance() {
+        let mut rng = Lcg(42);
+        for _ in 0..200 {
+            let outline = random_outline(&mut rng);
+            let tolerance = 0.1 + rng.next_f64() * 20.0;
+            let simplified = simplify_ring(&outline, tolerance);
+
+            assert!(simplified.len() >= 3);
+            assert!(simplified.len() <= outline.len());
+            // Only original vertices are kept, so the simplified boundary
+            // lies on chords of the original; every dropped vertex must be
+            // within the tolerance band of the simplified boundary
+            assert!(simplified.iter().all(|p| outline.contains(p)));
+            for &point in &outline {
+                assert!(distance_to_ring(point, &simplified) <= tolerance + 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_simplify_keeps_corners_and_drops_collinear_points() {
+        let mut square = Vec::new();
+        for i in 0..10 {
+            square.push((i as f64 * 10.0, 0.0));
+        }
+        for i in 0..10 {
+            square.push((100.0, i as f64 * 10.0));
+        }
+        for i in 0..10 {
+            square.push((100.0 - i as f64 * 10.0, 100.0));
+        }
+        for i in 0..10 {
+            square.push((0.0, 100.0 - i as f64 * 10.0));
+        }
+
+        let simplified = simplify_ring(&square, 0.01);
+        assert_eq!(simplified.len(), 4);
+        assert!((polygon_area(&simplified) - 10000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_simplify_zero_tolerance_keeps_shape() {
+        let mut rng = Lcg(7);
+        let outline = random_outline(&mut rng);
+        let simplified = simplify_ring(&outline, 0.0);
+        assert!((polygon_area(&simplified) - polygon_area(&outline)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_convex_hull_drops_inner_and_collinear_points() {
+        let points = [
+            (0.0, 0.0),
+            (5.0, 0.0),
+            (10.0, 0.0),
+            (4.0, 3.0),
+            (10.0, 10.0),
+            (0.0, 10.0),
+            (0.0, 0.0),
+        ];
+        let hull = convex_hull(&points);
+
+        assert_eq!(
+            hull,
+            vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]
+        );
+        assert!(signed_area(&hull) > 0.0);
+    }
+
+    #[test]
+    fn test_offset_ring_grows_bounding_box_evenly() {
+        let square = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        let clockwise: Polygon = square.iter().rev().copied().collect();
+
+        for ring in [&square, &clockwise] {
+            let grown = offset_ring(ring, 2.0);
+            let (min_x, min_y, max_x, max_y) = bounding_box(&grown).unwrap();
+            assert!((min_x + 2.0).abs() < 1e-9 && (min_y + 2.0).abs() < 1e-9);
+            assert!((max_x - 12.0).abs() < 1e-9 && (max_y - 12.0).abs() < 1e-9);
+            // Square plus edge strips plus a (slightly polygonal) disk
+            let exact = 100.0 + 4.0 * 10.0 * 2.0 + std::f64::consts::PI * 4.0;
+            assert!(polygon_area(&grown) < exact && polygon_area(&grown) > exact - 0.1);
+            assert_eq!(signed_area(&grown).signum(), signed_area(ring).signum());
+        }
+    }
+
+    #[test]
+    fn test_offset_ring_meets_at_reflex_corner() {
+        let l_shape = vec![
+            (0.0, 0.0),
+            (20.0, 0.0),
+            (20.0, 5.0),
+            (5.0, 5.0),
+            (5.0, 20.0),
+            (0.0, 20.0),
+        ];
+        let grown = offset_ring(&l_shape, 1.0);
+        assert!(grown.contains(&(6.0, 6.0)));
+        assert_eq!(bounding_box(&grown), Some((-1.0, -1.0, 21.0, 21.0)));
+        // Every original vertex stays `distance` inside the grown contour
+        for &vertex in &l_shape {
+            assert!(point_in_polygon(vertex, &grown));
+            let nearest = (0..grown.len())
+                .map(|i| point_segment_distance(vertex, grown[i], grown[(i + 1) % grown.len()]))
+                .fold(f64::INFINITY, f64::min);
+            // Round corners are polygons: chords sag by at most 1 - cos(5°)
+            assert!(nearest > 0.996, "{:?} is {} from the edge", vertex, nearest);
+        }
+    }
+}
@@ -0,0 +1,613 @@
+//! Part contours with true arc segments
+//!
+//! A contour is a closed list of vertices in the DXF LWPOLYLINE
+//! convention: the `bulge` of a vertex describes the segment to the next
+//! vertex, `tan(sweep / 4)`, positive for a counter-clockwise arc and 0
+//! for a straight line. Area, perimeter, centroid and bounding box are
+//! exact for arcs; polygons (all bulges 0) give the same results as the
+//! shoelace helpers of the parent module, up to float rounding.
+//!
+//! Tessellating with `tolerance` keeps every point within `tolerance` of
+//! the arc, so a tessellated area differs from the exact one by less
+//! than `perimeter * tolerance`.
+
+use super::{open_ring, rotate_polygon, translate_polygon, Polygon};
+use crate::nesting_engine::winding::{ContourReversals, Winding};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+/// Contour vertex, `bulge` belongs to the segment to the next vertex
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ContourVertex {
+    pub x: f64,
+    pub y: f64,
+    #[serde(default)]
+    pub bulge: f64,
+}
+
+impl ContourVertex {
+    pub fn line(x: f64, y: f64) -> Self {
+        ContourVertex { x, y, bulge: 0.0 }
+    }
+}
+
+/// Closed contour; the last vertex connects back to the first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Contour {
+    pub vertices: Vec<ContourVertex>,
+}
+
+/// Arc between two contour vertices
+struct Arc {
+    center: (f64, f64),
+    radius: f64,
+    /// Angle of the start point around the center
+    start_angle: f64,
+    /// Signed sweep, positive counter-clockwise
+    sweep: f64,
+}
+
+impl Arc {
+    /// None for straight segments and zero-length chords
+    fn between(a: ContourVertex, b: ContourVertex) -> Option<Arc> {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let chord = (dx * dx + dy * dy).sqrt();
+        if a.bulge == 0.0 || chord == 0.0 {
+            return None;
+        }
+
+        // Sagitta, measured to the right of the travel direction
+        let sagitta = a.bulge * chord / 2.0;
+        let (nx, ny) = (-dy / chord, dx / chord);
+        let offset = (chord * chord / 4.0 - sagitta * sagitta) / (2.0 * sagitta);
+        let center = (
+            (a.x + b.x) / 2.0 + nx * offset,
+            (a.y + b.y) / 2.0 + ny * offset,
+        );
+        let radius = (chord * chord / 4.0 + sagitta * sagitta) / (2.0 * sagitta.abs());
+        Some(Arc {
+            center,
+            radius,
+            start_angle: (a.y - center.1).atan2(a.x - center.0),
+            sweep: 4.0 * a.bulge.atan(),
+        })
+    }
+
+    fn length(&self) -> f64 {
+        self.radius * self.sweep.abs()
+    }
+
+    /// Signed area between the chord and the arc
+    fn segment_area(&self) -> f64 {
+        let theta = self.sweep.abs();
+        self.sweep.signum() * self.radius * self.radius / 2.0 * (theta - theta.sin())
+    }
+
+    /// Centroid of the region between the chord and the arc
+    fn segment_centroid(&self) -> (f64, f64) {
+        let half = self.sweep.abs() / 2.0;
+        let distance =
+            4.0 * self.radius * half.sin().powi(3) / (3.0 * (2.0 * half - (2.0 * half).sin()));
+        let mid_angle = self.start_angle + self.sweep / 2.0;
+        (
+            self.center.0 + distance * mid_angle.cos(),
+            self.center.1 + distance * mid_angle.sin(),
+        )
+    }
+
+    fn point_at(&self, angle: f64) -> (f64, f64) {
+        (
+            self.center.0 + self.radius * angle.cos(),
+            self.center.1 + self.radius * angle.sin(),
+        )
+    }
+
+    /// True if the direction `angle` lies within the sweep
+    fn covers(&self, angle: f64) -> bool {
+        let delta = if self.sweep > 0.0 {
+            (angle - self.start_angle).rem_euclid(TAU)
+        } else {
+            (self.start_angle - angle).rem_euclid(TAU)
+        };
+        delta <= self.sweep.abs()
+    }
+}
+
+impl Contour {
+    /// Straight-segment contour from a polygon (closing vertex allowed)
+    pub fn from_polygon(points: &[(f64, f64)]) -> Self {
+        Contour {
+            vertices: open_ring(points)
+                .iter()
+                .map(|&(x, y)| ContourVertex::line(x, y))
+                .collect(),
+        }
+    }
+
+    /// Circle as two half-circle arcs, counter-clockwise
+    pub fn circle(center: (f64, f64), radius: f64) -> Self {
+        Contour {
+            vertices: vec![
+                ContourVertex {
+                    x: center.0 + radius,
+                    y: center.1,
+                    bulge: 1.0,
+                },
+                ContourVertex {
+                    x: center.0 - radius,
+                    y: center.1,
+                    bulge: 1.0,
+                },
+            ],
+        }
+    }
+
+    /// Segments as (start, end) vertex pairs
+    fn segments(&self) -> impl Iterator<Item = (ContourVertex, ContourVertex)> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| (self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+
+    /// Positive for counter-clockwise contours
+    pub fn signed_area(&self) -> f64 {
+        if self.vertices.len() < 2 {
+            return 0.0;
+        }
+        self.segments()
+            .map(|(a, b)| {
+                let chord = (a.x * b.y - b.x * a.y) / 2.0;
+                chord + Arc::between(a, b).map_or(0.0, |arc| arc.segment_area())
+            })
+            .sum()
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        if self.vertices.len() < 2 {
+            return 0.0;
+        }
+        self.segments()
+            .map(|(a, b)| match Arc::between(a, b) {
+                Some(arc) => arc.length(),
+                None => ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt(),
+            })
+            .sum()
+    }
+
+    /// Signed area and first moments `(area, area * cx, area * cy)`
+    fn moments(&self) -> (f64, f64, f64) {
+        let (mut area, mut mx, mut my) = (0.0, 0.0, 0.0);
+        for (a, b) in self.segments() {
+            let cross = a.x * b.y - b.x * a.y;
+            area += cross / 2.0;
+            mx += (a.x + b.x) * cross / 6.0;
+            my += (a.y + b.y) * cross / 6.0;
+            if let Some(arc) = Arc::between(a, b) {
+                let segment = arc.segment_area();
+                let (cx, cy) = arc.segment_centroid();
+                area += segment;
+                mx += segment * cx;
+                my += segment * cy;
+            }
+        }
+        (area, mx, my)
+    }
+
+    /// Area centroid, None for degenerate contours
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        let (area, mx, my) = self.moments();
+        (area.abs() > f64::EPSILON).then(|| (mx / area, my / area))
+    }
+
+    /// Exact bounds `(min_x, min_y, max_x, max_y)`, arc extremes included
+    pub fn bounding_box(&self) -> Option<(f64, f64, f64, f64)> {
+        let first = self.vertices.first()?;
+        let mut bounds = (first.x, first.y, first.x, first.y);
+        let mut include = |(x, y): (f64, f64)| {
+            bounds = (
+                bounds.0.min(x),
+                bounds.1.min(y),
+                bounds.2.max(x),
+                bounds.3.max(y),
+            );
+        };
+        for (a, b) in self.segments() {
+            include((a.x, a.y));
+            if let Some(arc) = Arc::between(a, b) {
+                for quarter in 0..4 {
+                    let angle = quarter as f64 * FRAC_PI_2;
+                    if arc.covers(angle) {
+                        include(arc.point_at(angle));
+                    }
+                }
+            }
+        }
+        Some(bounds)
+    }
+
+    /// Polygon with arcs split so no point is further than `tolerance` from them
+    pub fn tessellate(&self, tolerance: f64) -> Polygon {
+        let mut points = Vec::with_capacity(self.vertices.len());
+        for (a, b) in self.segments() {
+            points.push((a.x, a.y));
+            let Some(arc) = Arc::between(a, b) else {
+                continue;
+            };
+            // Largest step whose chord stays within tolerance of the arc
+            let ratio = (1.0 - tolerance / arc.radius).clamp(-1.0, 1.0);
+            let max_step = (2.0 * ratio.acos()).max(1e-3);
+            let pieces = (arc.sweep.abs() / max_step).ceil().max(1.0) as usize;
+            for k in 1..pieces {
+                let angle = arc.start_angle + arc.sweep * k as f64 / pieces as f64;
+                points.push(arc.point_at(angle));
+            }
+        }
+        points
+    }
+
+    /// Rotated by `degrees` around the origin, then moved by `(dx, dy)`
+    ///
+    /// Rigid motions keep every arc's sweep, so bulges are unchanged.
+    pub fn placed(&self, degrees: f64, dx: f64, dy: f64) -> Contour {
+        let points: Polygon = self.vertices.iter().map(|v| (v.x, v.y)).collect();
+        let placed = translate_polygon(&rotate_polygon(&points, degrees), dx, dy);
+        Contour {
+            vertices: placed
+                .into_iter()
+                .zip(&self.vertices)
+                .map(|((x, y), vertex)| ContourVertex {
+                    x,
+                    y,
+                    bulge: vertex.bulge,
+                })
+                .collect(),
+        }
+    }
+
+    /// Mirrored across the Y axis (x → -x)
+    ///
+    /// A reflection turns every arc the other way, so bulges change sign.
+    pub fn mirrored(&self) -> Contour {
+        Contour {
+            vertices: self
+                .vertices
+                .iter()
+                .map(|v| ContourVertex {
+                    x: -v.x,
+                    y: v.y,
+                    bulge: -v.bulge,
+                })
+                .collect(),
+        }
+    }
+
+    /// True if every coordinate and bulge is a finite number
+    pub fn is_finite(&self) -> bool {
+        self.vertices
+            .iter()
+            .all(|v| v.x.is_finite() && v.y.is_finite() && v.bulge.is_finite())
+    }
+
+    /// Same contour traversed in the opposite direction
+    pub fn reversed(&self) -> Contour {
+        let n = self.vertices.len();
+        Contour {
+            vertices: (0..n)
+                .map(|j| {
+                    let vertex = self.vertices[n - 1 - j];
+                    // Segment to the next vertex is the old segment ending here
+                    let bulge = -self.vertices[(2 * n - 2 - j) % n].bulge;
+                    ContourVertex { bulge, ..vertex }
+                })
+                .collect(),
+        }
+    }
+
+    /// Reverse unless the contour runs in the wanted direction
+    fn orient(&mut self, ccw: bool) -> bool {
+        let area = self.signed_area();
+        if area == 0.0 || (area > 0.0) == ccw {
+            return false;
+        }
+        *self = self.reversed();
+        true
+    }
+
+    /// Point strictly inside the contour (even-odd rule)
+    pub fn contains(&self, (px, py): (f64, f64)) -> bool {
+        let mut inside = false;
+        for (a, b) in self.segments() {
+            if (a.y > py) != (b.y > py) && px < (b.x - a.x) * (py - a.y) / (b.y - a.y) + a.x {
+                inside = !inside;
+            }
+            // The region between chord and arc is added or cut out
+            if let Some(arc) = Arc::between(a, b) {
+                let side = |x: f64, y: f64| (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x);
+                let mid = arc.point_at(arc.start_angle + arc.sweep / 2.0);
+                let in_circle = (px - arc.center.0).powi(2) + (py - arc.center.1).powi(2)
+                    < arc.radius * arc.radius;
+                // On the chord, break the tie like the crossing test above
+                // does: as if the point sat just above and right of it
+                let mut point_side = side(px, py);
+                if point_side == 0.0 {
+                    point_side = b.x - a.x;
+                }
+                if point_side == 0.0 {
+                    point_side = a.y - b.y;
+                }
+                if in_circle && point_side * side(mid.0, mid.1) > 0.0 {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+}
+
+/// Outer contour and holes of a part
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PartGeometry {
+    pub outer: Contour,
+    pub holes: Vec<Contour>,
+}
+
+impl PartGeometry {
+    /// Part from polygon contours (closing vertices allowed)
+    pub fn from_polygons(outer: &[(f64, f64)], holes: &[Polygon]) -> Self {
+        PartGeometry {
+            outer: Contour::from_polygon(outer),
+            holes: holes
+                .iter()
+                .map(|hole| Contour::from_polygon(hole))
+                .collect(),
+        }
+    }
+
+    /// Outer area minus the area of all holes
+    pub fn area(&self) -> f64 {
+        self.outer.signed_area().abs()
+            - self
+                .holes
+                .iter()
+                .map(|hole| hole.signed_area().abs())
+                .sum::<f64>()
+    }
+
+    /// Cut length: outer contour and all holes
+    pub fn perimeter(&self) -> f64 {
+        self.outer.perimeter() + self.holes.iter().map(Contour::perimeter).sum::<f64>()
+    }
+
+    /// Centroid of the material (holes cut out)
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        let absolute = |contour: &Contour| {
+            let (area, mx, my) = contour.moments();
+            let sign = area.signum();
+            (area * sign, mx * sign, my * sign)
+        };
+        let (mut area, mut mx, mut my) = absolute(&self.outer);
+        for hole in &self.holes {
+            let (hole_area, hole_mx, hole_my) = absolute(hole);
+            area -= hole_area;
+            mx -= hole_mx;
+            my -= hole_my;
+        }
+        (area.abs() > f64::EPSILON).then(|| (mx / area, my / area))
+    }
+
+    pub fn bounding_box(&self) -> Option<(f64, f64, f64, f64)> {
+        self.outer.bounding_box()
+    }
+
+    /// Inside the outer contour and outside every hole
+    pub fn contains(&self, point: (f64, f64)) -> bool {
+        self.outer.contains(point) && !self.holes.iter().any(|hole| hole.contains(point))
+    }
+
+    /// Outer polygon and hole polygons, see `Contour::tessellate`
+    pub fn tessellate(&self, tolerance: f64) -> (Polygon, Vec<Polygon>) {
+        (
+            self.outer.tessellate(tolerance),
+            self.holes
+                .iter()
+                .map(|hole| hole.tessellate(tolerance))
+                .collect(),
+        )
+    }
+
+    /// Outer contour and holes placed like `Contour::placed`
+    pub fn placed(&self, degrees: f64, dx: f64, dy: f64) -> PartGeometry {
+        PartGeometry {
+            outer: self.outer.placed(degrees, dx, dy),
+            holes: self
+                .holes
+                .iter()
+                .map(|hole| hole.placed(degrees, dx, dy))
+                .collect(),
+        }
+    }
+
+    /// Outer contour and holes mirrored like `Contour::mirrored`
+    pub fn mirrored(&self) -> PartGeometry {
+        PartGeometry {
+            outer: self.outer.mirrored(),
+            holes: self.holes.iter().map(Contour::mirrored).collect(),
+        }
+    }
+
+    /// Orient outer contour and holes, like `orient_contours` for polygons
+    pub fn normalize_winding(&mut self, winding: Winding) -> ContourReversals {
+        let outer_ccw = winding == Winding::CcwOuter;
+        ContourReversals {
+            outer_reversed: self.outer.orient(outer_ccw),
+            holes_reversed: self
+                .holes
+                .iter_mut()
+                .map(|hole| hole.orient(!outer_ccw))
+                .filter(|&reversed| reversed)
+                .count(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{polygon_area, polygon_perimeter};
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * a.abs().max(1.0)
+    }
+
+    /// 20 long, 10 wide, with half-circle ends, clockwise when `cw`
+    fn stadium(cw: bool) -> Contour {
+        let contour = Contour {
+            vertices: vec![
+                ContourVertex::line(0.0, -5.0),
+                ContourVertex {
+                    x: 20.0,
+                    y: -5.0,
+                    bulge: 1.0,
+                },
+                ContourVertex::line(20.0, 5.0),
+                ContourVertex {
+                    x: 0.0,
+                    y: 5.0,
+                    bulge: 1.0,
+                },
+            ],
+        };
+        if cw {
+            contour.reversed()
+        } else {
+            contour
+        }
+    }
+
+    #[test]
+    fn test_circle_is_exact() {
+        let circle = Contour::circle((3.0, 4.0), 10.0);
+        assert!(close(circle.signed_area(), PI * 100.0));
+        assert!(close(circle.perimeter(), 2.0 * PI * 10.0));
+        let (cx, cy) = circle.centroid().unwrap();
+        assert!(close(cx, 3.0) && close(cy, 4.0));
+        let (min_x, min_y, max_x, max_y) = circle.bounding_box().unwrap();
+        assert!(close(min_x, -7.0) && close(min_y, -6.0));
+        assert!(close(max_x, 13.0) && close(max_y, 14.0));
+
+        assert!(circle.contains((3.0, 4.0)));
+        assert!(circle.contains((3.0, 13.9)));
+        assert!(!circle.contains((3.0, 14.1)));
+        assert!(!circle.contains((12.0, 12.0)));
+    }
+
+    #[test]
+    fn test_annulus() {
+        let annulus = PartGeometry {
+            outer: Contour::circle((0.0, 0.0), 10.0),
+            holes: vec![Contour::circle((0.0, 0.0), 4.0).reversed()],
+        };
+        assert!(close(annulus.area(), PI * (100.0 - 16.0)));
+        assert!(close(annulus.perimeter(), 2.0 * PI * 14.0));
+        let (cx, cy) = annulus.centroid().unwrap();
+        assert!(cx.abs() < 1e-9 && cy.abs() < 1e-9);
+        assert!(!annulus.contains((0.0, 0.0)));
+        assert!(annulus.contains((7.0, 0.0)));
+        assert!(annulus.contains((0.0, -9.0)));
+    }
+
+    #[test]
+    fn test_stadium_in_both_directions() {
+        for cw in [false, true] {
+            let part = PartGeometry {
+                outer: stadium(cw),
+                holes: Vec::new(),
+            };
+            assert!(close(part.area(), 200.0 + PI * 25.0));
+            assert!(close(part.perimeter(), 40.0 + 10.0 * PI));
+            let (cx, cy) = part.centroid().unwrap();
+            assert!(close(cx, 10.0) && cy.abs() < 1e-9);
+            let (min_x, min_y, max_x, max_y) = part.bounding_box().unwrap();
+            assert!(close(min_x, -5.0) && close(max_x, 25.0));
+            assert!(close(min_y, -5.0) && close(max_y, 5.0));
+            assert!(part.contains((-4.0, 0.0)));
+            assert!(!part.contains((-4.0, 4.0)));
+        }
+        assert!(stadium(true).signed_area() < 0.0);
+    }
+
+    #[test]
+    fn test_tessellation_within_tolerance() {
+        let tolerance = 0.01;
+        let part = PartGeometry {
+            outer: stadium(false),
+            holes: vec![Contour::circle((10.0, 0.0), 2.0).reversed()],
+        };
+        let (outer, holes) = part.tessellate(tolerance);
+
+        let tessellated = polygon_area(&outer) - polygon_area(&holes[0]);
+        assert!((part.area() - tessellated).abs() < part.perimeter() * tolerance);
+        // Vertices lie on the arcs
+        for &(x, y) in &holes[0] {
+            assert!(close(((x - 10.0).powi(2) + y * y).sqrt(), 2.0));
+        }
+    }
+
+    #[test]
+    fn test_polygons_match_shoelace_helpers() {
+        let l_shape = vec![
+            (0.0, 0.0),
+            (150.0, 0.0),
+            (150.0, 40.0),
+            (40.0, 40.0),
+            (40.0, 100.0),
+            (0.0, 100.0),
+            (0.0, 0.0),
+        ];
+        let holes = vec![vec![(10.0, 10.0), (10.0, 30.0), (30.0, 30.0), (30.0, 10.0)]];
+        let part = PartGeometry::from_polygons(&l_shape, &holes);
+
+        assert!(close(
+            part.area(),
+            polygon_area(&l_shape) - polygon_area(&holes[0])
+        ));
+        assert!(close(
+            part.perimeter(),
+            polygon_perimeter(open_ring(&l_shape)) + polygon_perimeter(&holes[0])
+        ));
+        assert_eq!(part.bounding_box(), Some((0.0, 0.0, 150.0, 100.0)));
+    }
+
+    #[test]
+    fn test_normalize_winding() {
+        let mut part = PartGeometry {
+            outer: stadium(true),
+            holes: vec![Contour::circle((10.0, 0.0), 2.0)],
+        };
+        assert_eq!(
+            part.normalize_winding(Winding::CcwOuter),
+            ContourReversals {
+                outer_reversed: true,
+                holes_reversed: 1
+            }
+        );
+        assert!(part.outer.signed_area() > 0.0);
+        assert!(part.holes[0].signed_area() < 0.0);
+        assert!(!part.normalize_winding(Winding::CcwOuter).any());
+        assert!(close(part.area(), 200.0 + PI * 25.0 - PI * 4.0));
+    }
+
+    #[test]
+    fn test_placed_keeps_arcs() {
+        let part = PartGeometry {
+            outer: stadium(false),
+            holes: vec![Contour::circle((10.0, 0.0), 2.0).reversed()],
+        };
+        let placed = part.placed(90.0, 100.0, 50.0);
+        assert!(close(placed.area(), part.area()));
+        assert!(close(placed.perimeter(), part.perimeter()));
+        let (min_x, min_y, max_x, max_y) = placed.bounding_box().unwrap();
+        assert!(close(min_x, 95.0) && close(max_x, 105.0));
+        assert!(close(min_y, 45.0) && close(max_y, 75.0));
+    }
+}
@@ -0,0 +1,142 @@
+//! Units of lengths and areas in command payloads
+//!
+//! Everything is stored and computed in millimetres. Payloads are only
+//! converted at the serialization boundary, when a command is asked for
+//! another unit; the payload then carries that unit in its `units` field
+//! and every length (mm → in) and area (mm² → in²) in it is converted.
+//! Payloads coming back (exports, snapshots) are converted to mm first.
+
+use super::Polygon;
+use serde::{Deserialize, Serialize};
+
+pub const MM_PER_INCH: f64 = 25.4;
+
+/// Unit of the lengths in a payload, areas use its square
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayUnits {
+    #[default]
+    Mm,
+    In,
+}
+
+impl DisplayUnits {
+    /// Value of the `display_units` setting ("mm" or "in")
+    pub fn from_setting(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "mm" => Some(DisplayUnits::Mm),
+            "in" => Some(DisplayUnits::In),
+            _ => None,
+        }
+    }
+
+    fn mm_per_unit(self) -> f64 {
+        match self {
+            DisplayUnits::Mm => 1.0,
+            DisplayUnits::In => MM_PER_INCH,
+        }
+    }
+
+    /// Length given in `from` units, in these units
+    pub fn length_from(self, value: f64, from: DisplayUnits) -> f64 {
+        if self == from {
+            return value;
+        }
+        value * from.mm_per_unit() / self.mm_per_unit()
+    }
+
+    /// Area given in square `from` units, in square these units
+    pub fn area_from(self, value: f64, from: DisplayUnits) -> f64 {
+        if self == from {
+            return value;
+        }
+        let factor = from.mm_per_unit() / self.mm_per_unit();
+        value * factor * factor
+    }
+
+    /// Length in mm, in these units
+    pub fn length(self, mm: f64) -> f64 {
+        self.length_from(mm, DisplayUnits::Mm)
+    }
+
+    /// Area in mm², in square these units
+    pub fn area(self, mm2: f64) -> f64 {
+        self.area_from(mm2, DisplayUnits::Mm)
+    }
+
+    pub fn point_from(self, (x, y): (f64, f64), from: DisplayUnits) -> (f64, f64) {
+        (self.length_from(x, from), self.length_from(y, from))
+    }
+
+    pub fn polygon_from(self, polygon: &[(f64, f64)], from: DisplayUnits) -> Polygon {
+        polygon
+            .iter()
+            .map(|&point| self.point_from(point, from))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNITS: [DisplayUnits; 2] = [DisplayUnits::Mm, DisplayUnits::In];
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_length_conversions() {
+        assert_close(DisplayUnits::In.length(25.4), 1.0);
+        assert_close(DisplayUnits::In.length(1000.0), 39.370_078_740_157);
+        assert_close(DisplayUnits::In.length(-50.8), -2.0);
+        assert_close(DisplayUnits::Mm.length_from(2.0, DisplayUnits::In), 50.8);
+        assert_eq!(DisplayUnits::In.length(0.0), 0.0);
+        assert_eq!(DisplayUnits::Mm.length(12.5), 12.5);
+    }
+
+    #[test]
+    fn test_area_conversions() {
+        assert_close(DisplayUnits::In.area(645.16), 1.0);
+        assert_close(DisplayUnits::In.area(1_000_000.0), 1_550.003_100_006_2);
+        assert_close(DisplayUnits::Mm.area_from(1.0, DisplayUnits::In), 645.16);
+        assert_eq!(DisplayUnits::Mm.area(80.0), 80.0);
+        // A 2 x 3 in plate is 6 in²
+        assert_close(DisplayUnits::In.area(50.8 * 76.2), 6.0);
+    }
+
+    #[test]
+    fn test_conversions_round_trip_between_all_units() {
+        for from in UNITS {
+            for to in UNITS {
+                let length = to.length_from(123.456, from);
+                assert_close(from.length_from(length, to), 123.456);
+                let area = to.area_from(9876.5, from);
+                assert_close(from.area_from(area, to), 9876.5);
+            }
+        }
+        let square = vec![(0.0, 0.0), (25.4, 0.0), (25.4, 50.8)];
+        assert_eq!(
+            DisplayUnits::In.polygon_from(&square, DisplayUnits::Mm),
+            vec![(0.0, 0.0), (1.0, 0.0), (1.0, 2.0)]
+        );
+    }
+
+    #[test]
+    fn test_units_flag_serialization() {
+        assert_eq!(serde_json::to_string(&DisplayUnits::In).unwrap(), r#""in""#);
+        assert_eq!(
+            serde_json::from_str::<DisplayUnits>(r#""mm""#).unwrap(),
+            DisplayUnits::Mm
+        );
+        assert!(serde_json::from_str::<DisplayUnits>(r#""cm""#).is_err());
+        assert_eq!(DisplayUnits::from_setting(" IN "), Some(DisplayUnits::In));
+        assert_eq!(DisplayUnits::from_setting("inch"), None);
+    }
+}
@@ -0,0 +1,303 @@
+//! Rolling direction of bent parts
+//!
+//! Parts that go to the press brake crack when a bend line runs across
+//! the rolling direction of the sheet (the strip's X axis). The DXF
+//! converter reads the bend lines of a part from its BEND layer and sets
+//! `bend_direction_deg` (angle of the bend lines in item coordinates) and
+//! `bend_lines` on the instance item. With `respect_grain` the item may
+//! only take the two orientations that turn its bend lines parallel to X;
+//! instance orientations within the tolerance are kept as they are, so a
+//! bend drawn at 0.3° still nests at 0° and 180°.
+//!
+//! The resulting bend-line angle is reported on every placement, and the
+//! SVG draws the bend lines dashed on top of the parts. Bent parts are
+//! not nested into holes while the direction is respected: the rotation
+//! of the host part is only known after packing.
+
+use super::geometry::{rotate_polygon, translate_polygon, Polygon};
+use super::holes::{host_translation, HoleLayout};
+use super::instance::{parse_instance, InstanceItem};
+use super::serializer::PlacedItem;
+use jagua_rs::probs::spp::entities::SPSolution;
+use std::collections::HashMap;
+
+/// Max angle between bend lines and the sheet's X axis when the
+/// nesting input does not set one (degrees)
+pub const DEFAULT_BEND_ANGLE_TOLERANCE: f64 = 1.0;
+
+/// Straight segment in item coordinates
+pub type Segment = [(f64, f64); 2];
+
+/// Bend lines of an item, for the SVG overlay
+#[derive(Debug, Clone)]
+pub struct BentItem {
+    /// Input outer contour (item coordinates)
+    pub outer: Polygon,
+    pub bend_lines: Vec<Segment>,
+}
+
+/// Items with bend lines, by item ID
+#[derive(Debug, Clone, Default)]
+pub struct BendPlan {
+    pub items: HashMap<usize, BentItem>,
+}
+
+/// Angle of a line in degrees, folded into (-90, 90]
+///
+/// Lines have no direction, so 0° and 180° are the same line.
+pub fn line_angle(degrees: f64) -> f64 {
+    let angle = degrees.rem_euclid(180.0);
+    if angle > 90.0 {
+        angle - 180.0
+    } else {
+        angle
+    }
+}
+
+/// Angle of the bend lines on the sheet for an item placed with `rotation_degrees`
+pub fn bend_line_angle(bend_direction_deg: f64, rotation_degrees: f64) -> f64 {
+    line_angle(bend_direction_deg + rotation_degrees)
+}
+
+/// Orientations that keep the bend lines within `tolerance` of the X axis
+///
+/// Allowed orientations of the item that qualify are kept; when none do,
+/// the two exact orientations are returned.
+pub fn grain_orientations(
+    bend_direction_deg: f64,
+    tolerance: f64,
+    allowed: Option<&[f64]>,
+) -> Vec<f64> {
+    let within_tolerance =
+        |rotation: f64| bend_line_angle(bend_direction_deg, rotation).abs() <= tolerance + 1e-9;
+    let kept: Vec<f64> = allowed
+        .unwrap_or_default()
+        .iter()
+        .copied()
+        .filter(|&rotation| within_tolerance(rotation))
+        .collect();
+    if !kept.is_empty() {
+        return kept;
+    }
+
+    let exact = (-bend_direction_deg).rem_euclid(360.0);
+    vec![exact, (exact + 180.0).rem_euclid(360.0)]
+}
+
+/// Restrict the orientations of bent items and collect their bend lines
+///
+/// Without `respect_grain` the orientations stay as they are and the bend
+/// fields are removed, so hole nesting treats the items like any other.
+pub fn constrain_instance(
+    json_str: &str,
+    respect_grain: bool,
+    tolerance: f64,
+) -> Result<(String, BendPlan), String> {
+    let instance = parse_instance(json_str)?;
+    if !instance
+        .items
+        .iter()
+        .any(|item| item.bend_direction_deg.is_some() || !item.bend_lines.is_empty())
+    {
+        return Ok((json_str.to_string(), BendPlan::default()));
+    }
+
+    let mut plan = BendPlan::default();
+    let mut orientations = HashMap::new();
+    for item in &instance.items {
+        let Some(bend_direction_deg) = item.bend_direction_deg else {
+            continue;
+        };
+        if respect_grain {
+            orientations.insert(
+                item.id,
+                grain_orientations(
+                    bend_direction_deg,
+                    tolerance,
+                    item.allowed_orientations.as_deref(),
+                ),
+            );
+        }
+        if !item.bend_lines.is_empty() {
+            plan.items.insert(
+                item.id as usize,
+                BentItem {
+                    outer: item.outer.clone(),
+                    bend_lines: item.bend_lines.clone(),
+                },
+            );
+        }
+    }
+    let mut value: serde_json::Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+        .ok_or("Instance JSON has no items array")?;
+    for item in items.iter_mut().filter_map(|item| item.as_object_mut()) {
+        let id = item.get("id").and_then(|id| id.as_u64()).unwrap_or(0);
+        match orientations.get(&id) {
+            Some(rotations) => {
+                item.insert("allowed_orientations".to_string(), rotations.clone().into());
+            }
+            None => {
+                item.remove("bend_direction_deg");
+                item.remove("bend_lines");
+            }
+        }
+    }
+
+    if !orientations.is_empty() {
+        println!(
+            "📐 {} bent parts keep their bend lines along the rolling direction",
+            orientations.len()
+        );
+    }
+    let rewritten = serde_json::to_string(&value)
+        .map_err(|e| format!("Failed to serialize instance: {}", e))?;
+    Ok((rewritten, plan))
+}
+
+/// Set the bend-line angle of every placement of a bent item
+pub fn attach_bend_angles(layouts: &mut [PlacedItem], items: &[InstanceItem]) {
+    let directions: HashMap<usize, f64> = items
+        .iter()
+        .filter_map(|item| Some((item.id as usize, item.bend_direction_deg?)))
+        .collect();
+    for placed in layouts {
+        placed.bend_line_angle_deg = directions.get(&placed.item_id).map(|&direction| {
+            // Mirroring across the Y axis turns a line at d° to -d°
+            let direction = if placed.mirrored {
+                -direction
+            } else {
+                direction
+            };
+            bend_line_angle(direction, placed.rotation_degrees)
+        });
+    }
+}
+
+/// Render the bend lines of placed parts as a dashed SVG overlay
+pub fn render_bend_overlay(plan: &BendPlan, solution: &SPSolution, holes: &HoleLayout) -> String {
+    let mut overlay = String::from(r#"<g id="bend_lines" pointer-events="none">"#);
+    let mut push_lines = |item: &BentItem, rotation: f64, (dx, dy): (f64, f64)| {
+        for line in &item.bend_lines {
+            let points = translate_polygon(&rotate_polygon(line, rotation), dx, dy);
+            let ((x1, y1), (x2, y2)) = (points[0], points[1]);
+            overlay.push_str(&format!(
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="crimson" stroke-width="1.5" stroke-dasharray="6 3"/>"#,
+                x1, y1, x2, y2
+            ));
+        }
+    };
+
+    for placed_item in solution.layout_snapshot.placed_items.values() {
+        let Some(item) = plan.items.get(&placed_item.item_id) else {
+            continue;
+        };
+        let placed_shape: Polygon = placed_item
+            .shape
+            .vertices
+            .iter()
+            .map(|p| (p.0 as f64, p.1 as f64))
+            .collect();
+        let rotation = (placed_item.d_transf.rotation() as f64).to_degrees();
+        if let Some(translation) = host_translation(&item.outer, rotation, &placed_shape) {
+            push_lines(item, rotation, translation);
+        }
+    }
+    for placement in &holes.placements {
+        if let Some(item) = plan.items.get(&placement.item_id) {
+            push_lines(item, placement.rotation_degrees, placement.position);
+        }
+    }
+
+    overlay.push_str("</g>");
+    overlay
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bent_instance(bend_direction_deg: f64, allowed: &[f64]) -> String {
+        serde_json::json!({
+            "strip_height": 100.0,
+            "items": [
+                {
+                    "id": 0,
+                    "demand": 1,
+                    "allowed_orientations": allowed,
+                    "bend_direction_deg": bend_direction_deg,
+                    "bend_lines": [[[0.0, 5.0], [20.0, 5.0]]],
+                    "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 20.0, "height": 10.0 } }
+                },
+                {
+                    "id": 1,
+                    "demand": 1,
+                    "allowed_orientations": allowed,
+                    "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 5.0, "height": 5.0 } }
+                }
+            ]
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_orientations_keep_bend_lines_along_x() {
+        // Allowed orientations within the tolerance are kept
+        assert_eq!(
+            grain_orientations(0.4, 1.0, Some(&[0.0, 90.0, 180.0, 270.0])),
+            vec![0.0, 180.0]
+        );
+        assert_eq!(
+            grain_orientations(90.0, 1.0, Some(&[0.0, 90.0, 180.0, 270.0])),
+            vec![90.0, 270.0]
+        );
+        // None qualify: the two exact orientations
+        assert_eq!(
+            grain_orientations(30.0, 1.0, Some(&[0.0, 90.0, 180.0, 270.0])),
+            vec![330.0, 150.0]
+        );
+        for rotation in grain_orientations(30.0, 1.0, None) {
+            assert!(bend_line_angle(30.0, rotation).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_line_angle_folds_direction() {
+        assert_eq!(line_angle(180.0), 0.0);
+        assert_eq!(line_angle(270.0), 90.0);
+        assert_eq!(line_angle(-30.0), -30.0);
+        assert_eq!(line_angle(330.0), -30.0);
+    }
+
+    #[test]
+    fn test_only_bent_items_are_constrained() {
+        let json = bent_instance(90.0, &[0.0, 90.0, 180.0, 270.0]);
+        let (constrained, plan) = constrain_instance(&json, true, 1.0).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&constrained).unwrap();
+        assert_eq!(
+            value["items"][0]["allowed_orientations"],
+            serde_json::json!([90.0, 270.0])
+        );
+        assert_eq!(
+            value["items"][1]["allowed_orientations"],
+            serde_json::json!([0.0, 90.0, 180.0, 270.0])
+        );
+        assert_eq!(plan.items[&0].bend_lines.len(), 1);
+
+        // Without the flag only the overlay plan is kept
+        let (unconstrained, plan) = constrain_instance(&json, false, 1.0).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&unconstrained).unwrap();
+        assert_eq!(
+            value["items"][0]["allowed_orientations"]
+                .as_array()
+                .unwrap()
+                .len(),
+            4
+        );
+        assert!(value["items"][0].get("bend_direction_deg").is_none());
+        assert_eq!(plan.items.len(), 1);
+    }
+}
@@ -0,0 +1,238 @@
+//! Sheet utilization heatmap
+//!
+//! Divides the used strip into a grid and reports how much of each
+//! cell is covered by placed parts, so the frontend can show where
+//! waste concentrates on a sheet.
+
+use super::geometry::{bounding_box, clip_polygon_to_rect, polygon_area, DisplayUnits, Polygon};
+use serde::{Deserialize, Serialize};
+
+/// Upper bound on the number of grid cells, keeps big strips cheap
+pub const MAX_GRID_CELLS: usize = 10_000;
+
+/// Requested grid resolution from frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtilizationGridOptions {
+    /// Number of columns along the strip width (X)
+    pub cols: usize,
+    /// Number of rows along the strip height (Y)
+    pub rows: usize,
+}
+
+/// Covered fraction per grid cell of a sheet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtilizationGrid {
+    /// Number of columns actually used (may be reduced by the cell cap)
+    pub cols: usize,
+    /// Number of rows actually used (may be reduced by the cell cap)
+    pub rows: usize,
+    /// Width of one cell
+    pub cell_width: f64,
+    /// Height of one cell
+    pub cell_height: f64,
+    /// Covered fraction (0.0 - 1.0) per cell, row-major starting at y = 0
+    pub cells: Vec<f64>,
+}
+
+impl UtilizationGrid {
+    /// Grid given in `from` units, in `units` (cells are fractions)
+    pub fn in_units(self, units: DisplayUnits, from: DisplayUnits) -> Self {
+        UtilizationGrid {
+            cell_width: units.length_from(self.cell_width, from),
+            cell_height: units.length_from(self.cell_height, from),
+            ..self
+        }
+    }
+
+    /// Covered fraction of the cell at `(col, row)`
+    pub fn cell(&self, col: usize, row: usize) -> f64 {
+        self.cells[row * self.cols + col]
+    }
+}
+
+/// Clamp the requested resolution so that `cols * rows <= MAX_GRID_CELLS`
+///
+/// Both axes are scaled down by the same factor to keep the cell aspect ratio.
+fn bounded_resolution(cols: usize, rows: usize) -> (usize, usize) {
+    let cols = cols.max(1);
+    let rows = rows.max(1);
+
+    if cols * rows <= MAX_GRID_CELLS {
+        return (cols, rows);
+    }
+
+    let scale = (MAX_GRID_CELLS as f64 / (cols * rows) as f64).sqrt();
+    let cols = ((cols as f64 * scale).floor() as usize).clamp(1, MAX_GRID_CELLS);
+    let rows = ((rows as f64 * scale).floor() as usize).clamp(1, MAX_GRID_CELLS / cols);
+    (cols, rows)
+}
+
+/// Compute the utilization grid of a sheet from its placed item polygons
+///
+/// # Arguments
+/// * `polygons` - Placed item outlines in strip coordinates
+/// * `holes` - Holes of placed items, subtracted from the coverage
+/// * `width` - Used sheet width (X)
+/// * `height` - Sheet height (Y)
+/// * `options` - Requested grid resolution
+///
+/// # Returns
+/// `None` when the sheet has no area or the requested grid is empty
+pub fn compute_utilization_grid(
+    polygons: &[Polygon],
+    holes: &[Polygon],
+    width: f64,
+    height: f64,
+    options: &UtilizationGridOptions,
+) -> Option<UtilizationGrid> {
+    if width <= 0.0 || height <= 0.0 || options.cols == 0 || options.rows == 0 {
+        return None;
+    }
+
+    let (cols, rows) = bounded_resolution(options.cols, options.rows);
+    let cell_width = width / cols as f64;
+    let cell_height = height / rows as f64;
+    let cell_area = cell_width * cell_height;
+
+    let mut covered = vec![0.0; cols * rows];
+
+    let signed = polygons
+        .iter()
+        .map(|polygon| (polygon, 1.0))
+        .chain(holes.iter().map(|hole| (hole, -1.0)));
+
+    for (polygon, sign) in signed {
+        let Some((min_x, min_y, max_x, max_y)) = bounding_box(polygon) else {
+            continue;
+        };
+
+        // Only visit cells overlapping the polygon's bounding box
+        let col_range = cell_span(min_x, max_x, cell_width, cols);
+        let row_range = cell_span(min_y, max_y, cell_height, rows);
+
+        for row in row_range {
+            for col in col_range.clone() {
+                let x0 = col as f64 * cell_width;
+                let y0 = row as f64 * cell_height;
+                let clipped =
+                    clip_polygon_to_rect(polygon, x0, y0, x0 + cell_width, y0 + cell_height);
+                covered[row * cols + col] += sign * polygon_area(&clipped);
+            }
+        }
+    }
+
+    let cells = covered
+        .into_iter()
+        .map(|area| (area / cell_area).clamp(0.0, 1.0))
+        .collect();
+
+    Some(UtilizationGrid {
+        cols,
+        rows,
+        cell_width,
+        cell_height,
+        cells,
+    })
+}
+
+/// Indices of the cells touched by the interval `[min, max]`
+fn cell_span(min: f64, max: f64, cell_size: f64, count: usize) -> std::ops::Range<usize> {
+    let first = ((min / cell_size).floor().max(0.0) as usize).min(count);
+    let last = ((max / cell_size).floor().max(0.0) as usize).min(count - 1);
+
+    if first > last {
+        0..0
+    } else {
+        first..last + 1
+    }
+}
+
+/// Render the grid as a translucent SVG overlay
+///
+/// Empty cells are drawn red and fully covered cells transparent,
+/// so waste stands out on top of the layout.
+pub fn render_heatmap_overlay(grid: &UtilizationGrid) -> String {
+    let mut overlay = String::from(r#"<g id="utilization_heatmap" pointer-events="none">"#);
+
+    for row in 0..grid.rows {
+        for col in 0..grid.cols {
+            let waste = 1.0 - grid.cell(col, row);
+            if waste <= 0.0 {
+                continue;
+            }
+            overlay.push_str(&format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="red" fill-opacity="{:.3}"/>"#,
+                col as f64 * grid.cell_width,
+                row as f64 * grid.cell_height,
+                grid.cell_width,
+                grid.cell_height,
+                waste * 0.5
+            ));
+        }
+    }
+
+    overlay.push_str("</g>");
+    overlay
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f64, y: f64, size: f64) -> Polygon {
+        vec![(x, y), (x + size, y), (x + size, y + size), (x, y + size)]
+    }
+
+    #[test]
+    fn test_grid_full_and_empty_cells() {
+        let options = UtilizationGridOptions { cols: 2, rows: 1 };
+        let grid =
+            compute_utilization_grid(&[square(0.0, 0.0, 10.0)], &[], 20.0, 10.0, &options).unwrap();
+
+        assert_eq!(grid.cells.len(), 2);
+        assert!((grid.cell(0, 0) - 1.0).abs() < 1e-9);
+        assert!(grid.cell(1, 0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_grid_partial_cell() {
+        let options = UtilizationGridOptions { cols: 1, rows: 1 };
+        let grid =
+            compute_utilization_grid(&[square(5.0, 5.0, 10.0)], &[], 20.0, 20.0, &options).unwrap();
+
+        assert!((grid.cell(0, 0) - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_grid_subtracts_holes() {
+        let options = UtilizationGridOptions { cols: 1, rows: 1 };
+        let grid = compute_utilization_grid(
+            &[square(0.0, 0.0, 10.0)],
+            &[square(2.5, 2.5, 5.0)],
+            10.0,
+            10.0,
+            &options,
+        )
+        .unwrap();
+
+        assert!((grid.cell(0, 0) - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_grid_cell_cap() {
+        let options = UtilizationGridOptions {
+            cols: 1000,
+            rows: 1000,
+        };
+        let grid = compute_utilization_grid(&[], &[], 100.0, 100.0, &options).unwrap();
+
+        assert!(grid.cols * grid.rows <= MAX_GRID_CELLS);
+        assert_eq!(grid.cols, grid.rows);
+    }
+
+    #[test]
+    fn test_grid_skipped_for_empty_sheet() {
+        let options = UtilizationGridOptions { cols: 4, rows: 4 };
+        assert!(compute_utilization_grid(&[], &[], 0.0, 100.0, &options).is_none());
+    }
+}
@@ -0,0 +1,545 @@
+//! Holes of frame-like parts (picture frames, flanges)
+//!
+//! jagua-rs items are simple polygons, so inner contours never reach the
+//! optimizer: a frame is packed as its solid outer contour. Holes are
+//! kept on our side instead, for the net area used in utilization and
+//! for an optional part-in-part pre-pass that puts small parts into the
+//! holes of larger ones before the strip is packed.
+
+use super::geometry::{
+    boundary_distance, bounding_box, open_ring, point_in_polygon, polygon_area, rotate_polygon,
+    translate_polygon, Polygon,
+};
+use super::instance::{parse_instance, InstanceItem};
+use jagua_rs::probs::spp::entities::SPSolution;
+use std::collections::HashMap;
+
+/// Holes smaller than this are dropped even when `preserve_holes` is set (mm²)
+pub const MIN_PRESERVED_HOLE_AREA: f64 = 100.0;
+
+/// Orientations tried for parts without `allowed_orientations`
+const DEFAULT_ORIENTATIONS: [f64; 4] = [0.0, 90.0, 180.0, 270.0];
+
+/// Outer contour and preserved holes of an item (item coordinates)
+#[derive(Debug, Clone)]
+pub struct FrameGeometry {
+    pub outer: Polygon,
+    pub holes: Vec<Polygon>,
+}
+
+/// Part placed inside a hole, relative to its host frame
+#[derive(Debug, Clone)]
+pub struct HoleFill {
+    pub item_id: usize,
+    /// Rotation applied to the part's input contour (degrees)
+    pub rotation_degrees: f64,
+    /// Translation applied after rotation, in host item coordinates
+    pub offset: (f64, f64),
+    /// Resulting outline in host item coordinates
+    pub outline: Polygon,
+}
+
+/// Parts nested into the holes of one copy of a host item
+#[derive(Debug, Clone)]
+pub struct HostFill {
+    pub host_id: usize,
+    pub fills: Vec<HoleFill>,
+}
+
+/// Hole information gathered before optimization
+#[derive(Debug, Clone, Default)]
+pub struct HolePlan {
+    /// Items with preserved holes, by item ID
+    pub frames: HashMap<usize, FrameGeometry>,
+    /// Net area (outer minus all holes) per item ID
+    pub net_areas: HashMap<usize, f64>,
+    /// One entry per host copy that received parts, in planning order
+    pub host_fills: Vec<HostFill>,
+}
+
+/// Part placed inside a hole, in strip coordinates
+#[derive(Debug, Clone)]
+pub struct HolePlacement {
+    pub item_id: usize,
+    pub host_item_id: usize,
+    /// Rotation of the part's input contour (degrees)
+    pub rotation_degrees: f64,
+    /// Translation of the part's input contour after rotation
+    pub position: (f64, f64),
+    pub outline: Polygon,
+}
+
+/// Hole information mapped onto the final layout
+#[derive(Debug, Clone, Default)]
+pub struct HoleLayout {
+    /// Parts placed inside holes of placed frames
+    pub placements: Vec<HolePlacement>,
+    /// Preserved holes of placed frames
+    pub hole_outlines: Vec<Polygon>,
+    /// Net area (outer minus all holes) per item ID
+    pub net_areas: HashMap<usize, f64>,
+    /// Demand moved from the strip into holes, per item ID
+    pub filled_demand: HashMap<usize, usize>,
+}
+
+impl HolePlan {
+    /// Number of copies of an item planned inside holes
+    fn filled_demand(&self) -> HashMap<usize, usize> {
+        let mut filled = HashMap::new();
+        for fill in self.host_fills.iter().flat_map(|host| host.fills.iter()) {
+            *filled.entry(fill.item_id).or_insert(0) += 1;
+        }
+        filled
+    }
+}
+
+/// Collect hole geometry and rewrite the instance for jagua-rs
+///
+/// Polygons with holes are reduced to their outer contour, and when
+/// `hole_nesting` is enabled the demand of parts planned inside holes
+/// is removed from the strip. Items keep their position in the list
+/// (jagua-rs expects consecutive IDs), so fully planned items stay
+/// with a demand of 0.
+///
+/// # Arguments
+/// * `json_str` - Instance JSON as sent by the frontend
+/// * `hole_nesting` - Plan small parts inside the holes of larger ones
+/// * `separation` - Minimum distance between parts and hole edges (mm)
+pub fn prepare_instance(
+    json_str: &str,
+    hole_nesting: bool,
+    separation: f64,
+) -> Result<(String, HolePlan), String> {
+    let instance = parse_instance(json_str)?;
+
+    let mut plan = HolePlan::default();
+    for item in &instance.items {
+        plan.net_areas.insert(item.id as usize, item.net_area());
+
+        let holes = preserved_holes(item);
+        if !holes.is_empty() {
+            plan.frames.insert(
+                item.id as usize,
+                FrameGeometry {
+                    outer: open_ring(&item.outer).to_vec(),
+                    holes,
+                },
+            );
+        }
+    }
+
+    if hole_nesting && !plan.frames.is_empty() {
+        plan.host_fills = plan_hole_fills(&instance.items, &plan.frames, separation);
+    }
+    let filled_demand = plan.filled_demand();
+
+    let mut value: serde_json::Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+        .ok_or("Instance JSON has no items array")?;
+
+    for item in items.iter_mut() {
+        let Some(item) = item.as_object_mut() else {
+            continue;
+        };
+        item.remove("preserve_holes");
+        item.remove("metadata");
+        item.remove("extra_spacing");
+        item.remove("bend_direction_deg");
+        item.remove("bend_lines");
+
+        let id = item.get("id").and_then(|id| id.as_u64()).unwrap_or(0) as usize;
+        if let Some(filled) = filled_demand.get(&id) {
+            let demand = item.get("demand").and_then(|d| d.as_u64()).unwrap_or(0);
+            item.insert(
+                "demand".to_string(),
+                demand.saturating_sub(*filled as u64).into(),
+            );
+        }
+
+        // jagua-rs items are simple polygons: only the outer contour is packed
+        let Some(shape) = item.get_mut("shape").and_then(|s| s.as_object_mut()) else {
+            continue;
+        };
+        if shape.get("type").and_then(|t| t.as_str()) == Some("polygon") {
+            let outer = shape
+                .get("data")
+                .and_then(|data| data.get("outer"))
+                .cloned()
+                .unwrap_or_default();
+            shape.insert("type".to_string(), "simple_polygon".into());
+            shape.insert("data".to_string(), outer);
+        }
+    }
+
+    let rewritten = serde_json::to_string(&value)
+        .map_err(|e| format!("Failed to serialize instance: {}", e))?;
+    Ok((rewritten, plan))
+}
+
+/// Holes of an item that survive simplification
+fn preserved_holes(item: &InstanceItem) -> Vec<Polygon> {
+    if !item.preserve_holes {
+        return Vec::new();
+    }
+
+    item.holes
+        .iter()
+        .map(|hole| open_ring(hole).to_vec())
+        .filter(|hole| polygon_area(hole) >= MIN_PRESERVED_HOLE_AREA)
+        .collect()
+}
+
+/// Greedily fill the holes of every host copy with the largest parts that fit
+fn plan_hole_fills(
+    items: &[InstanceItem],
+    frames: &HashMap<usize, FrameGeometry>,
+    separation: f64,
+) -> Vec<HostFill> {
+    let mut remaining: HashMap<usize, u64> = items
+        .iter()
+        .map(|item| (item.id as usize, item.demand))
+        .collect();
+
+    // Sorted by outer contour: a part that fits in a hole is always
+    // smaller than the host, so a frame nested into a bigger frame is
+    // taken from the demand before its own copies are filled
+    let outer_area = |item: &InstanceItem| polygon_area(open_ring(&item.outer));
+    let mut candidates: Vec<&InstanceItem> = items.iter().collect();
+    candidates.sort_by(|a, b| outer_area(b).total_cmp(&outer_area(a)));
+
+    let hosts: Vec<&InstanceItem> = candidates
+        .iter()
+        .copied()
+        .filter(|item| frames.contains_key(&(item.id as usize)))
+        .collect();
+
+    let mut host_fills = Vec::new();
+    for host in hosts {
+        let host_id = host.id as usize;
+        let frame = &frames[&host_id];
+        // A delicate host keeps its extra clearance inside its holes too
+        let clearance = separation + host.extra_separation;
+
+        // Fill each copy left on the strip until the holes stay empty
+        for _ in 0..remaining[&host_id] {
+            let mut fills = Vec::new();
+            for hole in &frame.holes {
+                fill_hole(
+                    hole,
+                    host_id,
+                    &candidates,
+                    &mut remaining,
+                    clearance,
+                    &mut fills,
+                );
+            }
+            if fills.is_empty() {
+                break;
+            }
+            host_fills.push(HostFill { host_id, fills });
+        }
+    }
+
+    host_fills
+}
+
+/// Place parts into one hole, bottom-left first
+fn fill_hole(
+    hole: &Polygon,
+    host_id: usize,
+    candidates: &[&InstanceItem],
+    remaining: &mut HashMap<usize, u64>,
+    separation: f64,
+    fills: &mut Vec<HoleFill>,
+) {
+    let Some(hole_bbox) = bounding_box(hole) else {
+        return;
+    };
+    let mut placed: Vec<Polygon> = Vec::new();
+
+    for candidate in candidates {
+        let id = candidate.id as usize;
+        // A part never goes into a copy of itself, and a bent part whose
+        // rolling direction is respected needs the host's final rotation
+        if id == host_id || candidate.bend_direction_deg.is_some() {
+            continue;
+        }
+
+        let clearance = separation + candidate.extra_separation;
+        while remaining.get(&id).copied().unwrap_or(0) > 0 {
+            let Some(fill) = find_position(candidate, hole, hole_bbox, &placed, clearance) else {
+                break;
+            };
+            placed.push(fill.outline.clone());
+            fills.push(fill);
+            *remaining.entry(id).or_insert(0) -= 1;
+        }
+    }
+}
+
+/// First position (lowest row, then leftmost) where the part fits in the hole
+fn find_position(
+    item: &InstanceItem,
+    hole: &Polygon,
+    (hole_min_x, hole_min_y, hole_max_x, hole_max_y): (f64, f64, f64, f64),
+    placed: &[Polygon],
+    separation: f64,
+) -> Option<HoleFill> {
+    let orientations = item
+        .allowed_orientations
+        .clone()
+        .unwrap_or_else(|| DEFAULT_ORIENTATIONS.to_vec());
+
+    for rotation in orientations {
+        let rotated = rotate_polygon(open_ring(&item.outer), rotation);
+        let (min_x, min_y, max_x, max_y) = bounding_box(&rotated)?;
+        let (width, height) = (max_x - min_x, max_y - min_y);
+
+        let free_width = hole_max_x - hole_min_x - width - 2.0 * separation;
+        let free_height = hole_max_y - hole_min_y - height - 2.0 * separation;
+        if free_width < 0.0 || free_height < 0.0 {
+            continue;
+        }
+
+        let step = separation.max(width.min(height) / 8.0);
+        let steps_x = (free_width / step).floor() as usize;
+        let steps_y = (free_height / step).floor() as usize;
+
+        for row in 0..=steps_y {
+            for col in 0..=steps_x {
+                let dx = hole_min_x + separation + col as f64 * step - min_x;
+                let dy = hole_min_y + separation + row as f64 * step - min_y;
+                let outline = translate_polygon(&rotated, dx, dy);
+
+                if fits_in_hole(&outline, hole, placed, separation) {
+                    return Some(HoleFill {
+                        item_id: item.id as usize,
+                        rotation_degrees: rotation,
+                        offset: (dx, dy),
+                        outline,
+                    });
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn fits_in_hole(outline: &Polygon, hole: &Polygon, placed: &[Polygon], separation: f64) -> bool {
+    if !point_in_polygon(outline[0], hole) || boundary_distance(outline, hole) < separation {
+        return false;
+    }
+
+    placed.iter().all(|other| {
+        !point_in_polygon(outline[0], other)
+            && !point_in_polygon(other[0], outline)
+            && boundary_distance(outline, other) >= separation
+    })
+}
+
+/// Map planned fills and preserved holes onto the placed frames
+///
+/// The host transform is recovered from the placed shape: its rotation
+/// comes from the placement, its translation from matching the bounding
+/// box centers of the rotated input contour and the placed shape (the
+/// separation offset grows the placed shape evenly on all sides).
+pub fn resolve_hole_layout(plan: &HolePlan, solution: &SPSolution) -> HoleLayout {
+    let mut layout = HoleLayout {
+        net_areas: plan.net_areas.clone(),
+        filled_demand: plan.filled_demand(),
+        ..Default::default()
+    };
+    if plan.frames.is_empty() {
+        return layout;
+    }
+
+    // Planned fills per host, consumed in placement order
+    let mut pending: HashMap<usize, std::collections::VecDeque<&HostFill>> = HashMap::new();
+    for host_fill in &plan.host_fills {
+        pending
+            .entry(host_fill.host_id)
+            .or_default()
+            .push_back(host_fill);
+    }
+
+    for (_, placed_item) in solution.layout_snapshot.placed_items.iter() {
+        let Some(frame) = plan.frames.get(&placed_item.item_id) else {
+            continue;
+        };
+
+        let placed_shape: Polygon = placed_item
+            .shape
+            .vertices
+            .iter()
+            .map(|p| (p.0 as f64, p.1 as f64))
+            .collect();
+        let rotation = (placed_item.d_transf.rotation() as f64).to_degrees();
+        let Some((dx, dy)) = host_translation(&frame.outer, rotation, &placed_shape) else {
+            continue;
+        };
+        let to_strip =
+            |polygon: &[(f64, f64)]| translate_polygon(&rotate_polygon(polygon, rotation), dx, dy);
+
+        layout
+            .hole_outlines
+            .extend(frame.holes.iter().map(|hole| to_strip(hole)));
+
+        let Some(host_fill) = pending
+            .get_mut(&placed_item.item_id)
+            .and_then(|fills| fills.pop_front())
+        else {
+            continue;
+        };
+
+        for fill in &host_fill.fills {
+            let (ox, oy) = rotate_polygon(&[fill.offset], rotation)[0];
+            layout.placements.push(HolePlacement {
+                item_id: fill.item_id,
+                host_item_id: host_fill.host_id,
+                rotation_degrees: rotation + fill.rotation_degrees,
+                position: (ox + dx, oy + dy),
+                outline: to_strip(&fill.outline),
+            });
+        }
+    }
+
+    layout
+}
+
+/// Translation that moves the rotated input contour onto the placed shape
+pub(super) fn host_translation(outer: &Polygon, rotation: f64, placed_shape: &Polygon) -> Option<(f64, f64)> {
+    let (min_x, min_y, max_x, max_y) = bounding_box(&rotate_polygon(outer, rotation))?;
+    let (p_min_x, p_min_y, p_max_x, p_max_y) = bounding_box(placed_shape)?;
+
+    Some((
+        (p_min_x + p_max_x) / 2.0 - (min_x + max_x) / 2.0,
+        (p_min_y + p_max_y) / 2.0 - (min_y + max_y) / 2.0,
+    ))
+}
+
+/// Render preserved holes and the parts inside them as an SVG overlay
+///
+/// The jagua-rs rendering only knows the solid outer contours.
+pub fn render_hole_overlay(layout: &HoleLayout) -> String {
+    let mut overlay = String::from(r#"<g id="hole_nesting" pointer-events="none">"#);
+
+    for hole in &layout.hole_outlines {
+        overlay.push_str(&format!(
+            r#"<path d="{}" fill="white" stroke="black" stroke-width="1"/>"#,
+            svg_path(hole)
+        ));
+    }
+    for placement in &layout.placements {
+        overlay.push_str(&format!(
+            r#"<path d="{}" fill="lightsteelblue" fill-opacity="0.8" stroke="black" stroke-width="1"/>"#,
+            svg_path(&placement.outline)
+        ));
+    }
+
+    overlay.push_str("</g>");
+    overlay
+}
+
+pub(super) fn svg_path(polygon: &[(f64, f64)]) -> String {
+    let mut path = String::new();
+    for (i, (x, y)) in polygon.iter().enumerate() {
+        path.push_str(&format!(
+            "{}{:.3},{:.3} ",
+            if i == 0 { "M" } else { "L" },
+            x,
+            y
+        ));
+    }
+    path.push('Z');
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THIN_FRAME: &str = include_str!("../../test_instances/thin_frame.json");
+
+    fn fixture_plan(hole_nesting: bool) -> (serde_json::Value, HolePlan) {
+        let (json, plan) = prepare_instance(THIN_FRAME, hole_nesting, 1.0).unwrap();
+        (serde_json::from_str(&json).unwrap(), plan)
+    }
+
+    #[test]
+    fn test_thin_frame_hole_survives() {
+        let (json, plan) = fixture_plan(false);
+
+        // Frame 200 x 150 with a 5 mm rim
+        let frame = &plan.frames[&0];
+        assert_eq!(frame.holes.len(), 1);
+        assert!((polygon_area(&frame.holes[0]) - 190.0 * 140.0).abs() < 1e-6);
+        assert!((plan.net_areas[&0] - (200.0 * 150.0 - 190.0 * 140.0)).abs() < 1e-6);
+
+        // jagua-rs gets the outer contour only, demands untouched
+        assert_eq!(json["items"][0]["shape"]["type"], "simple_polygon");
+        assert_eq!(json["items"][1]["demand"], 6);
+        assert!(plan.host_fills.is_empty());
+    }
+
+    #[test]
+    fn test_hole_nesting_places_small_parts_inside() {
+        let (json, plan) = fixture_plan(true);
+        let frame = &plan.frames[&0];
+
+        let fills: Vec<&HoleFill> = plan
+            .host_fills
+            .iter()
+            .flat_map(|h| h.fills.iter())
+            .collect();
+        assert_eq!(fills.len(), 6);
+        for fill in &fills {
+            assert!(fill
+                .outline
+                .iter()
+                .all(|&p| point_in_polygon(p, &frame.holes[0])));
+            assert!(boundary_distance(&fill.outline, &frame.holes[0]) >= 1.0);
+        }
+
+        // All small parts moved into the hole
+        assert_eq!(json["items"][1]["demand"], 0);
+        assert_eq!(json["items"][0]["demand"], 1);
+    }
+
+    #[test]
+    fn test_engine_nests_parts_in_frame_hole() {
+        let output = super::super::run_nesting_engine(super::super::NestingInput {
+            json_input: THIN_FRAME.to_string(),
+            time_limit: Some(2),
+            seed: Some(42),
+            use_early_termination: Some(true),
+            hole_nesting: Some(true),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let in_hole = output
+            .layouts
+            .iter()
+            .filter(|placed| placed.host_item_id == Some(0))
+            .count();
+        assert_eq!(in_hole, 6);
+        assert_eq!(output.total_items_placed, 9);
+        assert!(output.unplaced_item_ids.is_empty());
+    }
+
+    #[test]
+    fn test_preserve_holes_disabled() {
+        let json = THIN_FRAME.replacen(r#""id": 0,"#, r#""id": 0, "preserve_holes": false,"#, 1);
+        let (rewritten, plan) = prepare_instance(&json, true, 1.0).unwrap();
+
+        assert!(plan.frames.is_empty());
+        assert!(plan.host_fills.is_empty());
+        // Net area still accounts for the hole
+        assert!((plan.net_areas[&0] - (200.0 * 150.0 - 190.0 * 140.0)).abs() < 1e-6);
+        assert!(!rewritten.contains("preserve_holes"));
+    }
+}
@@ -0,0 +1,340 @@
+//! Lightweight reader for strip packing instance JSON
+//!
+//! Mirrors the external instance format (`ExtSPInstance`) with plain
+//! `f64` polygons, so instance-level tools (diffs, summaries, checks)
+//! can inspect geometry without importing it into jagua-rs.
+
+use super::geometry::{Contour, PartGeometry, Polygon};
+use super::grain::Segment;
+use super::locked::FixedPosition;
+use super::spacing;
+use serde::Deserialize;
+
+/// Largest metadata blob accepted per item (serialized JSON bytes)
+pub const MAX_ITEM_METADATA_BYTES: usize = 4096;
+
+/// Parsed instance with plain polygon geometry
+#[derive(Debug, Clone)]
+pub struct InstanceGeometry {
+    /// Name of the problem instance
+    pub name: String,
+    /// Fixed strip height
+    pub strip_height: f64,
+    /// Items in input order
+    pub items: Vec<InstanceItem>,
+}
+
+/// Single item of an instance
+#[derive(Debug, Clone)]
+pub struct InstanceItem {
+    /// Item ID from input
+    pub id: u64,
+    /// Requested quantity
+    pub demand: u64,
+    /// Explicit label, falling back to the source DXF file name
+    pub label: Option<String>,
+    /// Allowed rotations in degrees (None = any)
+    pub allowed_orientations: Option<Vec<f64>>,
+    /// Angle of the part's bend lines in degrees (item coordinates),
+    /// set by the DXF converter from the BEND layer
+    pub bend_direction_deg: Option<f64>,
+    /// Bend lines in item coordinates (drawn on the SVG only)
+    pub bend_lines: Vec<Segment>,
+    /// Outer contour
+    pub outer: Polygon,
+    /// Inner contours (holes)
+    pub holes: Vec<Polygon>,
+    /// Contours with their true arcs, when the DXF converter kept them;
+    /// `outer` is their tessellation, used for nesting
+    pub exact_geometry: Option<PartGeometry>,
+    /// Keep holes above the minimum area for hole nesting (default: true)
+    pub preserve_holes: bool,
+    /// Frontend data (part numbers, finish codes, ...) passed back
+    /// untouched on every placement; the engine only reads
+    /// `extra_separation` from it
+    pub metadata: Option<serde_json::Value>,
+    /// Clearance added to the global separation around this part in mm,
+    /// the item's `extra_spacing` or `extra_separation` of the metadata
+    /// (0 when unset)
+    pub extra_separation: f64,
+    /// Position of a copy already on the sheet, held there (see `locked`)
+    pub fixed_position: Option<FixedPosition>,
+}
+
+#[derive(Deserialize)]
+struct RawInstance {
+    #[serde(default)]
+    name: String,
+    strip_height: f64,
+    items: Vec<RawItem>,
+}
+
+#[derive(Deserialize)]
+struct RawItem {
+    id: u64,
+    demand: u64,
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    dxf: Option<String>,
+    #[serde(default)]
+    allowed_orientations: Option<Vec<f64>>,
+    #[serde(default)]
+    bend_direction_deg: Option<f64>,
+    #[serde(default)]
+    bend_lines: Vec<Segment>,
+    #[serde(default = "default_preserve_holes")]
+    preserve_holes: bool,
+    #[serde(default)]
+    metadata: Option<serde_json::Value>,
+    #[serde(default)]
+    extra_spacing: Option<serde_json::Value>,
+    #[serde(default)]
+    exact_geometry: Option<PartGeometry>,
+    #[serde(default)]
+    fixed_position: Option<FixedPosition>,
+    shape: RawShape,
+}
+
+fn default_preserve_holes() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+enum RawShape {
+    Rectangle {
+        x_min: f64,
+        y_min: f64,
+        width: f64,
+        height: f64,
+    },
+    SimplePolygon(Polygon),
+    Polygon(RawPolygon),
+}
+
+#[derive(Deserialize)]
+struct RawPolygon {
+    outer: Polygon,
+    #[serde(default)]
+    inner: Vec<Polygon>,
+}
+
+/// Parse an instance JSON string
+pub fn parse_instance(json_str: &str) -> Result<InstanceGeometry, String> {
+    let raw: RawInstance =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+
+    let items = raw
+        .items
+        .into_iter()
+        .map(|item| {
+            if let Some(metadata) = &item.metadata {
+                let size = metadata.to_string().len();
+                if size > MAX_ITEM_METADATA_BYTES {
+                    return Err(format!(
+                        "Item {}: metadata is {} bytes, at most {} are allowed",
+                        item.id, size, MAX_ITEM_METADATA_BYTES
+                    ));
+                }
+            }
+
+            let extra_separation = spacing::extra_separation(
+                item.id,
+                item.extra_spacing.as_ref(),
+                item.metadata.as_ref(),
+            )?;
+            if let Some(direction) = item.bend_direction_deg {
+                if !direction.is_finite() {
+                    return Err(format!(
+                        "Item {}: bend_direction_deg must be an angle in degrees, got {}",
+                        item.id, direction
+                    ));
+                }
+            }
+
+            if let Some(exact) = &item.exact_geometry {
+                let finite = std::iter::once(&exact.outer)
+                    .chain(&exact.holes)
+                    .all(Contour::is_finite);
+                if exact.outer.vertices.len() < 2 || !finite {
+                    return Err(format!(
+                        "Item {}: exact_geometry needs an outer contour of finite vertices",
+                        item.id
+                    ));
+                }
+            }
+
+            let (outer, holes) = match item.shape {
+                RawShape::Rectangle {
+                    x_min,
+                    y_min,
+                    width,
+                    height,
+                } => (
+                    vec![
+                        (x_min, y_min),
+                        (x_min + width, y_min),
+                        (x_min + width, y_min + height),
+                        (x_min, y_min + height),
+                    ],
+                    Vec::new(),
+                ),
+                RawShape::SimplePolygon(points) => (points, Vec::new()),
+                RawShape::Polygon(polygon) => (polygon.outer, polygon.inner),
+            };
+
+            Ok(InstanceItem {
+                id: item.id,
+                demand: item.demand,
+                label: item.label.or(item.dxf),
+                allowed_orientations: item.allowed_orientations,
+                bend_direction_deg: item.bend_direction_deg,
+                bend_lines: item.bend_lines,
+                outer,
+                holes,
+                exact_geometry: item.exact_geometry,
+                preserve_holes: item.preserve_holes,
+                metadata: item.metadata,
+                extra_separation,
+                fixed_position: item.fixed_position,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+
+    Ok(InstanceGeometry {
+        name: raw.name,
+        strip_height: raw.strip_height,
+        items,
+    })
+}
+
+impl InstanceItem {
+    /// Outer area minus the area of all holes, of the nested polygons
+    pub fn net_area(&self) -> f64 {
+        PartGeometry::from_polygons(&self.outer, &self.holes).area()
+    }
+
+    /// Part geometry as drawn: the exact contours if kept, else the polygons
+    pub fn geometry(&self) -> PartGeometry {
+        self.exact_geometry
+            .clone()
+            .unwrap_or_else(|| PartGeometry::from_polygons(&self.outer, &self.holes))
+    }
+
+    /// Display name: label (file name only) or `item_<id>`
+    pub fn display_name(&self) -> String {
+        match &self.label {
+            Some(label) => std::path::Path::new(&label.replace('\\', "/"))
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| label.clone()),
+            None => format!("item_{}", self.id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::geometry::{polygon_area, polygon_perimeter, ContourVertex};
+
+    fn instance_with_metadata(metadata: &str) -> String {
+        format!(
+            r#"{{
+                "strip_height": 100,
+                "items": [{{
+                    "id": 0,
+                    "demand": 2,
+                    "metadata": {},
+                    "shape": {{"type": "rectangle", "data": {{"x_min": 0, "y_min": 0, "width": 10, "height": 10}}}}
+                }}]
+            }}"#,
+            metadata
+        )
+    }
+
+    #[test]
+    fn test_metadata_is_kept_verbatim() {
+        let instance = parse_instance(&instance_with_metadata(
+            r#"{"part_no": "A-17", "finish": [1, 2]}"#,
+        ))
+        .unwrap();
+        assert_eq!(
+            instance.items[0].metadata,
+            Some(serde_json::json!({"part_no": "A-17", "finish": [1, 2]}))
+        );
+    }
+
+    #[test]
+    fn test_exact_perimeter_of_large_radius_bulges() {
+        // 1000 x 200 plate whose long sides bow out by 25 mm (radius 5012.5)
+        let bulge = 0.05;
+        let exact = PartGeometry {
+            outer: Contour {
+                vertices: vec![
+                    ContourVertex {
+                        x: 0.0,
+                        y: 0.0,
+                        bulge,
+                    },
+                    ContourVertex::line(1000.0, 0.0),
+                    ContourVertex {
+                        x: 1000.0,
+                        y: 200.0,
+                        bulge,
+                    },
+                    ContourVertex::line(0.0, 200.0),
+                ],
+            },
+            holes: Vec::new(),
+        };
+        let tessellated = exact.tessellate(0.05).0;
+        let json = serde_json::json!({
+            "strip_height": 3000,
+            "items": [{
+                "id": 0,
+                "demand": 1,
+                "exact_geometry": exact,
+                "shape": {"type": "simple_polygon", "data": tessellated}
+            }]
+        });
+        let instance = parse_instance(&json.to_string()).unwrap();
+        let item = &instance.items[0];
+
+        let sweep = 4.0 * f64::atan(bulge);
+        let radius = 500.0 / (sweep / 2.0).sin();
+        let arc_perimeter = 2.0 * radius * sweep + 2.0 * 200.0;
+        assert!((item.geometry().perimeter() - arc_perimeter).abs() < 1e-6);
+
+        // Chords are shorter than the arc, by a tiny but nonzero amount
+        let polygon_perimeter = polygon_perimeter(&item.outer);
+        assert!(polygon_perimeter < arc_perimeter);
+        assert!(arc_perimeter - polygon_perimeter < 0.05);
+        assert!(arc_perimeter - polygon_perimeter > 1e-4);
+        assert!(tessellated.len() > 20);
+        assert!((item.net_area() - polygon_area(&item.outer)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_degenerate_exact_geometry_is_rejected() {
+        let json = r#"{
+            "strip_height": 100,
+            "items": [{
+                "id": 3,
+                "demand": 1,
+                "exact_geometry": {"outer": {"vertices": [{"x": 0, "y": 0}]}, "holes": []},
+                "shape": {"type": "rectangle", "data": {"x_min": 0, "y_min": 0, "width": 10, "height": 10}}
+            }]
+        }"#;
+        let error = parse_instance(json).unwrap_err();
+        assert!(error.contains("Item 3: exact_geometry"), "{}", error);
+    }
+
+    #[test]
+    fn test_oversized_metadata_is_rejected() {
+        let blob = format!(r#""{}""#, "x".repeat(MAX_ITEM_METADATA_BYTES));
+        let error = parse_instance(&instance_with_metadata(&blob)).unwrap_err();
+        assert!(error.contains("metadata"), "{}", error);
+    }
+}
@@ -0,0 +1,282 @@
+//! Importing the items of an instance one by one
+//!
+//! jagua-rs imports a whole instance at once, so one malformed polygon
+//! among hundreds of items failed the run with a single error naming no
+//! item. `import_items` first imports every item on its own, as a
+//! one-item instance, and collects the errors per item ID. A strict run
+//! fails with all of them; with `skip_bad_items` the failing items are
+//! replaced by empty placeholders (demand 0) and the instance is
+//! assembled from the rest, listing the skipped items in the output.
+//!
+//! Item IDs stay as they are: jagua-rs numbers items by their position,
+//! and the placements of every other item must keep their IDs.
+
+use super::geometry::{open_ring, polygon_area, self_intersection};
+use super::instance::parse_instance;
+use super::nesting::{instance_importer, NestingConfig};
+use jagua_rs::io::import::Importer;
+use jagua_rs::probs::spp::io::ext_repr::ExtSPInstance;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Item left out of the run because it cannot be imported
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedItem {
+    pub item_id: usize,
+    /// Label or source DXF file of the item, if it has one
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub label: Option<String>,
+    /// Copies requested, none of them are nested
+    pub demand: u64,
+    pub error: String,
+}
+
+/// Items of an instance that failed to import on their own
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub failed: Vec<SkippedItem>,
+    /// Number of items in the instance
+    pub n_items: usize,
+}
+
+impl ImportReport {
+    /// Error of a strict run, listing every item that failed
+    pub fn error_message(&self) -> String {
+        let items: Vec<String> = self
+            .failed
+            .iter()
+            .map(|item| match &item.label {
+                Some(label) => format!("item {} ({}): {}", item.item_id, label, item.error),
+                None => format!("item {}: {}", item.item_id, item.error),
+            })
+            .collect();
+        format!(
+            "{} of {} items cannot be imported (set skip_bad_items to nest the rest): {}",
+            self.failed.len(),
+            self.n_items,
+            items.join("; ")
+        )
+    }
+}
+
+/// Import every item of `json_str` on its own
+///
+/// Fails only when the instance itself is unreadable; problems of single
+/// items end up in the report.
+pub fn import_items(json_str: &str, config: &NestingConfig) -> Result<ImportReport, String> {
+    let value: Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get("items")
+        .and_then(Value::as_array)
+        .ok_or("Instance JSON has no items array")?;
+    let strip_height = value.get("strip_height").cloned().unwrap_or(Value::Null);
+    let importer = instance_importer(config);
+
+    let mut report = ImportReport {
+        failed: Vec::new(),
+        n_items: items.len(),
+    };
+    for (index, item) in items.iter().enumerate() {
+        if let Err(error) = import_item(item, &strip_height, &importer) {
+            let label = ["label", "dxf"]
+                .iter()
+                .find_map(|key| item.get(*key).and_then(Value::as_str))
+                .map(str::to_string);
+            report.failed.push(SkippedItem {
+                item_id: item_id(item).unwrap_or(index as u64) as usize,
+                label,
+                demand: item.get("demand").and_then(Value::as_u64).unwrap_or(0),
+                error,
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Replace the skipped items of `json_str` by empty placeholders
+///
+/// A placeholder keeps the item's ID and label with demand 0 and a unit
+/// square as shape, so it passes every later stage without being nested.
+pub fn drop_items(json_str: &str, skipped: &[SkippedItem]) -> Result<String, String> {
+    let mut value: Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(Value::as_array_mut)
+        .ok_or("Instance JSON has no items array")?;
+    for (index, item) in items.iter_mut().enumerate() {
+        let id = item_id(item).unwrap_or(index as u64);
+        if !skipped.iter().any(|skipped| skipped.item_id as u64 == id) {
+            continue;
+        }
+        let mut placeholder = json!({
+            "id": id,
+            "demand": 0,
+            "shape": {
+                "type": "rectangle",
+                "data": { "x_min": 0.0, "y_min": 0.0, "width": 1.0, "height": 1.0 }
+            }
+        });
+        for key in ["label", "dxf"] {
+            if let Some(label) = item.get(key).filter(|label| label.is_string()) {
+                placeholder[key] = label.clone();
+            }
+        }
+        *item = placeholder;
+    }
+    serde_json::to_string(&value).map_err(|e| format!("Failed to serialize instance: {}", e))
+}
+
+fn item_id(item: &Value) -> Option<u64> {
+    item.get("id").and_then(Value::as_u64)
+}
+
+/// Check one item and import it into jagua-rs as a one-item instance
+fn import_item(item: &Value, strip_height: &Value, importer: &Importer) -> Result<(), String> {
+    let single = json!({ "name": "item", "strip_height": strip_height, "items": [item] });
+    let instance = parse_instance(&single.to_string()).map_err(|error| {
+        // Messages of the instance reader start with the item ID
+        item_id(item)
+            .and_then(|id| {
+                error
+                    .strip_prefix(&format!("Item {}: ", id))
+                    .map(str::to_string)
+            })
+            .unwrap_or(error)
+    })?;
+    let parsed = &instance.items[0];
+
+    let rings = std::iter::once(("outer contour".to_string(), &parsed.outer)).chain(
+        parsed
+            .holes
+            .iter()
+            .enumerate()
+            .map(|(i, hole)| (format!("hole {}", i), hole)),
+    );
+    for (name, ring) in rings {
+        check_ring(&name, ring)?;
+    }
+
+    // Holes never reach the importer (see `holes::prepare_instance`), and
+    // jagua-rs numbers items from 0
+    let mut probe = json!({
+        "id": 0,
+        "demand": 1,
+        "shape": { "type": "simple_polygon", "data": open_ring(&parsed.outer) }
+    });
+    if let Some(orientations) = &parsed.allowed_orientations {
+        probe["allowed_orientations"] = json!(orientations);
+    }
+    let ext_instance: ExtSPInstance = serde_json::from_value(
+        json!({ "name": "item", "strip_height": strip_height, "items": [probe] }),
+    )
+    .map_err(|e| format!("not a valid item: {}", e))?;
+    jagua_rs::probs::spp::io::import(importer, &ext_instance)
+        .map(|_| ())
+        .map_err(|e| format!("{:#}", e))
+}
+
+/// A contour jagua-rs can turn into a polygon: finite, simple, with area
+fn check_ring(name: &str, ring: &[(f64, f64)]) -> Result<(), String> {
+    let mut ring = open_ring(ring).to_vec();
+    if ring.iter().any(|&(x, y)| !x.is_finite() || !y.is_finite()) {
+        return Err(format!("{} has non-finite coordinates", name));
+    }
+    // Repeated points would read as edges touching each other
+    ring.dedup();
+    if ring.len() >= 3 {
+        if let Some((a, b)) = self_intersection(&ring) {
+            return Err(format!(
+                "{} intersects itself (edges {} and {})",
+                name, a, b
+            ));
+        }
+    }
+    if ring.len() < 3 || polygon_area(&ring) <= 0.0 {
+        return Err(format!(
+            "{} needs at least 3 points enclosing an area",
+            name
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{run_nesting_engine, NestingInput};
+    use super::*;
+
+    /// thin_frame with a bow tie added as item 3
+    fn instance_with_bow_tie() -> String {
+        let mut value: Value =
+            serde_json::from_str(include_str!("../../test_instances/thin_frame.json")).unwrap();
+        let items = value["items"].as_array_mut().unwrap();
+        assert_eq!(items.len(), 3);
+        items.push(json!({
+            "id": 3,
+            "demand": 2,
+            "dxf": "bow_tie.dxf",
+            "shape": {
+                "type": "simple_polygon",
+                "data": [[0.0, 0.0], [50.0, 50.0], [50.0, 0.0], [0.0, 30.0]]
+            }
+        }));
+        value.to_string()
+    }
+
+    #[test]
+    fn test_bad_item_is_reported_by_id() {
+        let report = import_items(&instance_with_bow_tie(), &NestingConfig::default()).unwrap();
+        assert_eq!(report.n_items, 4);
+        assert_eq!(
+            report.failed,
+            vec![SkippedItem {
+                item_id: 3,
+                label: Some("bow_tie.dxf".to_string()),
+                demand: 2,
+                error: "outer contour intersects itself (edges 0 and 2)".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_strict_run_fails_with_the_item_report() {
+        let error = run_nesting_engine(NestingInput {
+            json_input: instance_with_bow_tie(),
+            time_limit: Some(2),
+            seed: Some(7),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(
+            error.starts_with("1 of 4 items cannot be imported"),
+            "{}",
+            error
+        );
+        assert!(
+            error.contains("item 3 (bow_tie.dxf): outer contour intersects itself"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_lenient_run_nests_the_other_items() {
+        let output = run_nesting_engine(NestingInput {
+            json_input: instance_with_bow_tie(),
+            time_limit: Some(2),
+            seed: Some(7),
+            skip_bad_items: Some(true),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(output.skipped_items.len(), 1);
+        assert_eq!(output.skipped_items[0].item_id, 3);
+        assert!(output.layouts.iter().all(|placed| placed.item_id != 3));
+        // Frame, 6 plates and 2 bars
+        assert_eq!(output.items_requested, Some(9));
+        assert_eq!(output.total_items_placed, 9);
+    }
+}
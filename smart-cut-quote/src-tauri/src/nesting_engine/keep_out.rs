@@ -0,0 +1,268 @@
+//! Keep-out zones on the sheet (clamps, fixtures)
+//!
+//! The sparrow strip has no notion of hazards inside the container, so
+//! zones are enforced after optimization: placements overlapping a zone
+//! are removed from the layout and reported as unplaced.
+
+use super::dimension::deserialize_dimension;
+use super::geometry::{bounding_box, clip_polygon_to_rect, polygon_area, DisplayUnits, Polygon};
+use super::instance::parse_instance;
+use jagua_rs::probs::spp::entities::SPSolution;
+use serde::{Deserialize, Serialize};
+
+/// Overlap area below this is treated as touching, not overlapping (mm²)
+const OVERLAP_EPSILON: f64 = 1e-6;
+
+/// Rectangular area of the sheet where no part may be placed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeepOutZone {
+    /// Left edge in sheet coordinates
+    #[serde(deserialize_with = "deserialize_dimension")]
+    pub x: f64,
+    /// Bottom edge in sheet coordinates
+    #[serde(deserialize_with = "deserialize_dimension")]
+    pub y: f64,
+    #[serde(deserialize_with = "deserialize_dimension")]
+    pub width: f64,
+    #[serde(deserialize_with = "deserialize_dimension")]
+    pub height: f64,
+    /// Optional name shown in the SVG (e.g. "Clamp 1")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl KeepOutZone {
+    /// Zone given in `from` units, in `units`
+    pub fn in_units(self, units: DisplayUnits, from: DisplayUnits) -> Self {
+        KeepOutZone {
+            x: units.length_from(self.x, from),
+            y: units.length_from(self.y, from),
+            width: units.length_from(self.width, from),
+            height: units.length_from(self.height, from),
+            label: self.label,
+        }
+    }
+
+    /// True if the polygon covers part of this zone
+    pub fn overlaps(&self, polygon: &[(f64, f64)]) -> bool {
+        let clipped = clip_polygon_to_rect(
+            polygon,
+            self.x,
+            self.y,
+            self.x + self.width,
+            self.y + self.height,
+        );
+        polygon_area(&clipped) > OVERLAP_EPSILON
+    }
+
+    /// Area of this zone inside a `width` x `height` sheet
+    pub fn area_within(&self, width: f64, height: f64) -> f64 {
+        let w = (self.x + self.width).min(width) - self.x.max(0.0);
+        let h = (self.y + self.height).min(height) - self.y.max(0.0);
+        w.max(0.0) * h.max(0.0)
+    }
+}
+
+/// Reject zones with non-positive or non-finite dimensions
+pub fn validate_keep_out_zones(zones: &[KeepOutZone]) -> Result<(), String> {
+    for (i, zone) in zones.iter().enumerate() {
+        let values = [zone.x, zone.y, zone.width, zone.height];
+        if values.iter().any(|v| !v.is_finite()) || zone.width <= 0.0 || zone.height <= 0.0 {
+            return Err(format!(
+                "Keep-out zone {} ({}) has invalid dimensions: x={}, y={}, width={}, height={}",
+                i,
+                zone.label.as_deref().unwrap_or("unnamed"),
+                zone.x,
+                zone.y,
+                zone.width,
+                zone.height
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Total zone area inside a `width` x `height` sheet
+///
+/// Overlapping zones are counted twice, which is acceptable for the
+/// handful of clamp rectangles a machine has.
+pub fn blocked_area(zones: &[KeepOutZone], width: f64, height: f64) -> f64 {
+    zones
+        .iter()
+        .map(|zone| zone.area_within(width, height))
+        .sum()
+}
+
+/// Check that the parts of an instance can fit on a sheet with keep-out zones
+///
+/// A coarse pre-check before optimizing: every part's bounding box must
+/// fit the sheet (rotated by 90° when rotations are allowed) and the total
+/// part area must not exceed the sheet area left free by the zones.
+///
+/// # Arguments
+/// * `instance_json` - Instance as sent to the engine
+/// * `sheet_width` - Sheet size along X
+/// * `sheet_height` - Sheet size along Y
+/// * `zones` - Keep-out zones in sheet coordinates
+pub fn check_sheet_capacity(
+    instance_json: &str,
+    sheet_width: f64,
+    sheet_height: f64,
+    zones: &[KeepOutZone],
+) -> Result<(), String> {
+    let instance = parse_instance(instance_json)?;
+    let usable_area = sheet_width * sheet_height - blocked_area(zones, sheet_width, sheet_height);
+    let mut parts_area = 0.0;
+
+    for item in &instance.items {
+        let Some((min_x, min_y, max_x, max_y)) = bounding_box(&item.outer) else {
+            continue;
+        };
+        let (w, h) = (max_x - min_x, max_y - min_y);
+        let can_rotate = item
+            .allowed_orientations
+            .as_ref()
+            .is_none_or(|orientations| orientations.iter().any(|o| o.rem_euclid(180.0) != 0.0));
+
+        let fits = (w <= sheet_width && h <= sheet_height)
+            || (can_rotate && h <= sheet_width && w <= sheet_height);
+        if !fits {
+            return Err(format!(
+                "Part '{}' ({:.1} x {:.1}) does not fit the {:.1} x {:.1} sheet",
+                item.display_name(),
+                w,
+                h,
+                sheet_width,
+                sheet_height
+            ));
+        }
+
+        parts_area += item.net_area() * item.demand as f64;
+    }
+
+    if parts_area > usable_area {
+        return Err(format!(
+            "Parts need {:.0} mm² but only {:.0} mm² of the sheet is usable around keep-out zones",
+            parts_area, usable_area
+        ));
+    }
+
+    Ok(())
+}
+
+/// Remove placements overlapping any zone from the solution
+///
+/// # Returns
+/// Item IDs of the removed placements (one entry per removed copy)
+pub fn evict_conflicting_items(solution: &mut SPSolution, zones: &[KeepOutZone]) -> Vec<usize> {
+    if zones.is_empty() {
+        return Vec::new();
+    }
+
+    let conflicting: Vec<_> = solution
+        .layout_snapshot
+        .placed_items
+        .iter()
+        .filter(|(_, placed_item)| {
+            let polygon: Polygon = placed_item
+                .shape
+                .vertices
+                .iter()
+                .map(|p| (p.0 as f64, p.1 as f64))
+                .collect();
+            zones.iter().any(|zone| zone.overlaps(&polygon))
+        })
+        .map(|(key, placed_item)| (key, placed_item.item_id))
+        .collect();
+
+    conflicting
+        .into_iter()
+        .map(|(key, item_id)| {
+            solution.layout_snapshot.placed_items.remove(key);
+            item_id
+        })
+        .collect()
+}
+
+/// Render zones as hatched SVG rectangles, distinct from parts and waste
+pub fn render_zones_overlay(zones: &[KeepOutZone]) -> String {
+    let mut overlay = String::from(concat!(
+        r#"<g id="keep_out_zones" pointer-events="none">"#,
+        r#"<defs><pattern id="keep_out_hatch" width="10" height="10" patternUnits="userSpaceOnUse" patternTransform="rotate(45)">"#,
+        r#"<line x1="0" y1="0" x2="0" y2="10" stroke="black" stroke-width="3"/></pattern></defs>"#
+    ));
+
+    for zone in zones {
+        overlay.push_str(&format!(
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="url(#keep_out_hatch)" fill-opacity="0.6" stroke="black" stroke-width="2" stroke-dasharray="8 4"/>"#,
+            zone.x, zone.y, zone.width, zone.height
+        ));
+        if let Some(label) = &zone.label {
+            overlay.push_str(&format!(
+                r#"<text x="{}" y="{}" font-size="12" text-anchor="middle">{}</text>"#,
+                zone.x + zone.width / 2.0,
+                zone.y + zone.height / 2.0,
+                escape_xml(label)
+            ));
+        }
+    }
+
+    overlay.push_str("</g>");
+    overlay
+}
+
+pub(super) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(x: f64, y: f64, width: f64, height: f64) -> KeepOutZone {
+        KeepOutZone {
+            x,
+            y,
+            width,
+            height,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_zone_overlap_ignores_touching() {
+        let clamp = zone(0.0, 0.0, 50.0, 50.0);
+        let touching = vec![(50.0, 0.0), (60.0, 0.0), (60.0, 10.0), (50.0, 10.0)];
+        let overlapping = vec![(40.0, 0.0), (60.0, 0.0), (60.0, 10.0), (40.0, 10.0)];
+
+        assert!(!clamp.overlaps(&touching));
+        assert!(clamp.overlaps(&overlapping));
+    }
+
+    #[test]
+    fn test_blocked_area_clipped_to_sheet() {
+        let zones = [zone(-10.0, 0.0, 20.0, 10.0), zone(90.0, 90.0, 20.0, 20.0)];
+        assert!((blocked_area(&zones, 100.0, 100.0) - 200.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sheet_capacity_accounts_for_zones() {
+        let instance = r#"{"name": "caps", "strip_height": 100.0, "items": [
+            {"id": 0, "demand": 7, "shape": {"type": "simple_polygon",
+             "data": [[0, 0], [10, 0], [10, 100], [0, 100]]}}]}"#;
+
+        assert!(check_sheet_capacity(instance, 100.0, 100.0, &[]).is_ok());
+        assert!(
+            check_sheet_capacity(instance, 100.0, 100.0, &[zone(0.0, 0.0, 40.0, 100.0)]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_keep_out_zones_rejects_empty_rect() {
+        assert!(validate_keep_out_zones(&[zone(0.0, 0.0, 0.0, 10.0)]).is_err());
+        assert!(validate_keep_out_zones(&[zone(0.0, 0.0, 5.0, 10.0)]).is_ok());
+    }
+}
@@ -0,0 +1,368 @@
+//! DXF export of a nested layout
+//!
+//! Places the input contours of every part (outer and holes) at their
+//! nested position and writes them as closed R12 polylines, with outer
+//! contours on the `OUTER` layer and holes on `INNER`. Contours are
+//! oriented to the requested winding convention so CAM software picks
+//! the right kerf side. Parts whose true arcs were kept by the converter
+//! are written with them (vertex bulges) instead of their tessellation.
+
+use super::geometry::{
+    mirror_polygon, open_ring, rotate_polygon, translate_polygon, Contour, ContourVertex,
+    PartGeometry, Polygon,
+};
+use super::holes::host_translation;
+use super::instance::InstanceGeometry;
+use super::serializer::{NestingOutput, PlacedItem};
+use super::winding::{orient_contours, ContourReversals, Winding};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Layer of outer contours
+pub const OUTER_LAYER: &str = "OUTER";
+/// Layer of hole contours
+pub const INNER_LAYER: &str = "INNER";
+
+/// Options for `export_layout_dxf`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LayoutDxfOptions {
+    /// Direction convention of the exported contours (default: CCW outer)
+    #[serde(default)]
+    pub export_winding: Winding,
+}
+
+/// Contour direction changes of one placed part
+#[derive(Debug, Clone, Serialize)]
+pub struct PartWinding {
+    /// Index into `NestingOutput::layouts`
+    pub placement_index: usize,
+    pub item_id: usize,
+    #[serde(flatten)]
+    pub reversals: ContourReversals,
+}
+
+/// Summary of a layout export
+#[derive(Debug, Clone, Serialize)]
+pub struct LayoutDxfReport {
+    pub winding: Winding,
+    pub contour_count: usize,
+    /// Per placed part, in layout order
+    pub parts: Vec<PartWinding>,
+    /// Number of parts with at least one reversed contour
+    pub parts_reversed: usize,
+}
+
+/// Closed contour in strip coordinates
+struct LayoutContour {
+    layer: &'static str,
+    contour: Contour,
+}
+
+/// Input contours of one placed part, in strip coordinates
+pub(super) struct PlacedContours {
+    pub item_id: usize,
+    pub outer: Polygon,
+    pub holes: Vec<Polygon>,
+    /// Contours with true arcs, if the instance kept them
+    pub exact: Option<PartGeometry>,
+}
+
+/// Place the input contours of every part where the layout put it
+///
+/// The output must carry placement outlines (`include_outlines` or the
+/// `outlines` derived field): they locate each part's input contour on
+/// the strip.
+pub(super) fn placed_contours(
+    output: &NestingOutput,
+    instance: &InstanceGeometry,
+) -> Result<Vec<PlacedContours>, String> {
+    output
+        .layouts
+        .iter()
+        .map(|placed| {
+            let outline = placed.outline.as_ref().ok_or_else(|| {
+                "Layout has no part outlines, derive them before exporting".to_string()
+            })?;
+            place_contours(placed, outline, instance)
+        })
+        .collect()
+}
+
+/// Input contours of one part, moved onto its placement outline
+pub(super) fn place_contours(
+    placed: &PlacedItem,
+    outline: &Polygon,
+    instance: &InstanceGeometry,
+) -> Result<PlacedContours, String> {
+    // Fillers are not instance items, their outline is their contour
+    if placed.filler {
+        return Ok(PlacedContours {
+            item_id: placed.item_id,
+            outer: outline.clone(),
+            holes: Vec::new(),
+            exact: None,
+        });
+    }
+    let item = instance
+        .items
+        .iter()
+        .find(|item| item.id == placed.item_id as u64)
+        .ok_or_else(|| format!("Item {} is not part of the instance", placed.item_id))?;
+
+    // Mirrored placements carry the mirror image of the item
+    let source = |polygon: &[(f64, f64)]| -> Polygon {
+        let ring = open_ring(polygon);
+        if placed.mirrored {
+            mirror_polygon(ring)
+        } else {
+            ring.to_vec()
+        }
+    };
+    let rotation = placed.rotation_degrees;
+    let (dx, dy) = host_translation(&source(&item.outer), rotation, outline)
+        .ok_or_else(|| format!("Item {} has an empty contour", placed.item_id))?;
+    let to_strip = |polygon: &[(f64, f64)]| {
+        translate_polygon(&rotate_polygon(&source(polygon), rotation), dx, dy)
+    };
+
+    Ok(PlacedContours {
+        item_id: placed.item_id,
+        outer: to_strip(&item.outer),
+        holes: item.holes.iter().map(|hole| to_strip(hole)).collect(),
+        exact: item.exact_geometry.as_ref().map(|exact| {
+            let exact = if placed.mirrored {
+                exact.mirrored()
+            } else {
+                exact.clone()
+            };
+            exact.placed(rotation, dx, dy)
+        }),
+    })
+}
+
+/// Build the DXF document for a nested layout
+///
+/// Needs placement outlines, see `placed_contours`.
+pub fn layout_dxf(
+    output: &NestingOutput,
+    instance: &InstanceGeometry,
+    options: &LayoutDxfOptions,
+) -> Result<(String, LayoutDxfReport), String> {
+    let placed = placed_contours(output, instance)?;
+    let mut contours = Vec::new();
+    let mut parts = Vec::with_capacity(placed.len());
+
+    for (placement_index, part) in placed.into_iter().enumerate() {
+        let PlacedContours {
+            item_id,
+            mut outer,
+            mut holes,
+            exact,
+        } = part;
+        let (reversals, geometry) = match exact {
+            Some(mut exact) => (exact.normalize_winding(options.export_winding), exact),
+            None => (
+                orient_contours(&mut outer, &mut holes, options.export_winding),
+                PartGeometry::from_polygons(&outer, &holes),
+            ),
+        };
+
+        contours.push(LayoutContour {
+            layer: OUTER_LAYER,
+            contour: geometry.outer,
+        });
+        contours.extend(geometry.holes.into_iter().map(|contour| LayoutContour {
+            layer: INNER_LAYER,
+            contour,
+        }));
+        parts.push(PartWinding {
+            placement_index,
+            item_id,
+            reversals,
+        });
+    }
+
+    let report = LayoutDxfReport {
+        winding: options.export_winding,
+        contour_count: contours.len(),
+        parts_reversed: parts.iter().filter(|part| part.reversals.any()).count(),
+        parts,
+    };
+    Ok((write_dxf(&contours), report))
+}
+
+/// Minimal R12 DXF with one closed POLYLINE per contour, arcs as bulges
+fn write_dxf(contours: &[LayoutContour]) -> String {
+    let mut dxf = String::new();
+    dxf.push_str("0\nSECTION\n2\nHEADER\n9\n$ACADVER\n1\nAC1009\n9\n$INSUNITS\n70\n4\n0\nENDSEC\n");
+    dxf.push_str("0\nSECTION\n2\nENTITIES\n");
+    for contour in contours {
+        let _ = write!(
+            dxf,
+            "0\nPOLYLINE\n8\n{}\n66\n1\n10\n0.0\n20\n0.0\n30\n0.0\n70\n1\n",
+            contour.layer
+        );
+        for &ContourVertex { x, y, bulge } in &contour.contour.vertices {
+            let _ = write!(
+                dxf,
+                "0\nVERTEX\n8\n{}\n10\n{:.4}\n20\n{:.4}\n30\n0.0\n",
+                contour.layer, x, y
+            );
+            if bulge != 0.0 {
+                let _ = write!(dxf, "42\n{:.8}\n", bulge);
+            }
+        }
+        let _ = write!(dxf, "0\nSEQEND\n8\n{}\n", contour.layer);
+    }
+    dxf.push_str("0\nENDSEC\n0\nEOF\n");
+    dxf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::geometry::signed_area;
+    use crate::nesting_engine::parse_instance;
+
+    /// 100 x 50 plate with a 10 x 10 hole, outer loop drawn clockwise
+    const CLOCKWISE_PART: &str = r#"{
+        "name": "clockwise",
+        "strip_height": 500,
+        "items": [{
+            "id": 0,
+            "demand": 1,
+            "shape": {"type": "polygon", "data": {
+                "outer": [[0, 0], [0, 50], [100, 50], [100, 0]],
+                "inner": [[[20, 20], [30, 20], [30, 30], [20, 30]]]
+            }}
+        }]
+    }"#;
+
+    fn placed_output() -> NestingOutput {
+        // Rotated by 90 degrees, the placed outline spans (200, 10)-(250, 110)
+        serde_json::from_value(serde_json::json!({
+            "instance_name": "clockwise",
+            "strip_width": 400.0,
+            "strip_height": 500.0,
+            "total_items_placed": 1,
+            "layouts": [{
+                "item_id": 0,
+                "rotation_degrees": 90.0,
+                "position_x": 0.0,
+                "position_y": 0.0,
+                "outline": [[250.0, 10.0], [250.0, 110.0], [200.0, 110.0], [200.0, 10.0]]
+            }],
+            "utilization": 0.025,
+            "computation_time_secs": 1.0
+        }))
+        .unwrap()
+    }
+
+    /// Signed areas of the POLYLINE contours in a DXF string, in order
+    fn contour_areas(dxf: &str) -> Vec<(String, f64)> {
+        let lines: Vec<&str> = dxf.lines().collect();
+        let mut contours: Vec<(String, Polygon)> = Vec::new();
+        for i in (0..lines.len().saturating_sub(1)).step_by(2) {
+            match (lines[i], lines[i + 1]) {
+                ("0", "POLYLINE") => contours.push((lines[i + 3].to_string(), Vec::new())),
+                ("0", "VERTEX") => {
+                    let x = lines[i + 5].parse().unwrap();
+                    let y = lines[i + 7].parse().unwrap();
+                    contours.last_mut().unwrap().1.push((x, y));
+                }
+                _ => {}
+            }
+        }
+        contours
+            .into_iter()
+            .map(|(layer, points)| (layer, signed_area(&points)))
+            .collect()
+    }
+
+    #[test]
+    fn test_clockwise_outer_is_exported_ccw() {
+        let instance = parse_instance(CLOCKWISE_PART).unwrap();
+        let (dxf, report) =
+            layout_dxf(&placed_output(), &instance, &LayoutDxfOptions::default()).unwrap();
+
+        assert_eq!(report.contour_count, 2);
+        assert_eq!(report.parts_reversed, 1);
+        assert!(report.parts[0].reversals.outer_reversed);
+        assert_eq!(report.parts[0].reversals.holes_reversed, 1);
+
+        let areas = contour_areas(&dxf);
+        assert_eq!(areas.len(), 2);
+        assert_eq!(areas[0].0, OUTER_LAYER);
+        assert!((areas[0].1 - 5000.0).abs() < 1e-6);
+        assert_eq!(areas[1].0, INNER_LAYER);
+        assert!((areas[1].1 + 100.0).abs() < 1e-6);
+
+        // Placed where the outline says
+        assert!(dxf.contains("10\n250.0000\n20\n10.0000\n"));
+    }
+
+    #[test]
+    fn test_cw_outer_export_winding() {
+        let instance = parse_instance(CLOCKWISE_PART).unwrap();
+        let options = LayoutDxfOptions {
+            export_winding: Winding::CwOuter,
+        };
+        let (dxf, report) = layout_dxf(&placed_output(), &instance, &options).unwrap();
+
+        // The drawn directions already match this convention
+        assert_eq!(report.parts_reversed, 0);
+        let areas = contour_areas(&dxf);
+        assert!(areas[0].1 < 0.0);
+        assert!(areas[1].1 > 0.0);
+    }
+
+    #[test]
+    fn test_exact_arcs_are_written_as_bulges() {
+        // Washer: radius 40 outside, 10 inside, the hole drawn CCW
+        let exact = PartGeometry {
+            outer: Contour::circle((0.0, 0.0), 40.0),
+            holes: vec![Contour::circle((0.0, 0.0), 10.0)],
+        };
+        let (outer, _) = exact.tessellate(0.1);
+        let instance = parse_instance(
+            &serde_json::json!({
+                "strip_height": 500,
+                "items": [{
+                    "id": 0,
+                    "demand": 1,
+                    "exact_geometry": exact,
+                    "shape": {"type": "simple_polygon", "data": outer}
+                }]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let mut output = placed_output();
+        output.layouts[0].outline = Some(vec![
+            (160.0, 60.0),
+            (240.0, 60.0),
+            (240.0, 140.0),
+            (160.0, 140.0),
+        ]);
+
+        let (dxf, report) = layout_dxf(&output, &instance, &LayoutDxfOptions::default()).unwrap();
+        assert_eq!(report.contour_count, 2);
+        assert_eq!(report.parts[0].reversals.holes_reversed, 1);
+        // Two half circles per contour instead of the tessellation
+        assert_eq!(dxf.matches("0\nVERTEX\n").count(), 4);
+        assert_eq!(dxf.matches("42\n1.00000000\n").count(), 2);
+        assert_eq!(dxf.matches("42\n-1.00000000\n").count(), 2);
+        // Rotated by 90 degrees onto the outline center
+        assert!(dxf.contains("10\n200.0000\n20\n140.0000\n"));
+    }
+
+    #[test]
+    fn test_missing_outlines_are_rejected() {
+        let instance = parse_instance(CLOCKWISE_PART).unwrap();
+        let mut output = placed_output();
+        output.layouts[0].outline = None;
+
+        let error = layout_dxf(&output, &instance, &LayoutDxfOptions::default()).unwrap_err();
+        assert!(error.contains("outlines"));
+    }
+}
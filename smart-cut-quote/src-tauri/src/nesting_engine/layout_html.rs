@@ -0,0 +1,441 @@
+//! Self-contained HTML viewer of a nested layout
+//!
+//! Customers open one file in any browser, offline: the layout is drawn
+//! as inline SVG with every part tagged `data-item-id`, next to a parts
+//! table and the utilization summary. A little inline script highlights
+//! all copies of a part on hover (drawing and table), zooms with the
+//! wheel, pans by dragging and resets on double click. Nothing is loaded
+//! from the network.
+//!
+//! Outlines are simplified like the preview's display outlines
+//! (`display_tolerance`) and written with 0.01 mm precision, which keeps
+//! a 300-part layout well below 5 MB. Labels come from customer files:
+//! they are escaped in the markup, and the JSON payload read by the
+//! script has `<`, `>` and `&` escaped so it cannot close its element.
+
+use super::geometry::{polygon_area, simplify_ring, Polygon};
+use super::instance::InstanceGeometry;
+use super::keep_out::escape_xml;
+use super::layout_dxf::placed_contours;
+use super::serializer::NestingOutput;
+use super::DEFAULT_DISPLAY_TOLERANCE;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Room around the strip in the drawing (mm)
+const DRAWING_MARGIN: f64 = 10.0;
+
+const STYLE: &str = "\
+body{margin:0;font-family:sans-serif;color:#222;display:flex;flex-direction:column;height:100vh}\
+header{padding:8px 16px;border-bottom:1px solid #ccc}\
+h1{font-size:18px;margin:0 0 4px}\
+header p{margin:0;font-size:13px}\
+main{flex:1;display:flex;min-height:0}\
+#drawing{flex:1;height:100%;background:#f4f4f4;cursor:grab}\
+#drawing.dragging{cursor:grabbing}\
+aside{width:360px;overflow:auto;border-left:1px solid #ccc;font-size:13px}\
+table{border-collapse:collapse;width:100%}\
+th,td{padding:3px 6px;text-align:left;border-bottom:1px solid #eee}\
+.num{text-align:right}\
+tr.unplaced td{color:#b00}\
+tr.hl{background:#fdf0c8}\
+.strip{fill:#fff;stroke:#888;stroke-width:1;vector-effect:non-scaling-stroke}\
+.part{fill:#dde6f0;fill-rule:evenodd;stroke:#333;stroke-width:1;vector-effect:non-scaling-stroke}\
+.part.hl{fill:#f5c542}\
+#tip{position:fixed;pointer-events:none;background:#222;color:#fff;padding:2px 6px;border-radius:3px;font-size:12px;display:none}";
+
+const SCRIPT: &str = r#"(function(){
+var svg=document.getElementById('drawing'),tip=document.getElementById('tip'),parts={};
+JSON.parse(document.getElementById('layout-data').textContent).parts.forEach(function(p){parts[p.item_id]=p;});
+var vb=svg.getAttribute('viewBox').split(' ').map(Number),home=vb.slice(),drag=null;
+function show(){svg.setAttribute('viewBox',vb.join(' '));}
+function tagged(e){return e.target.closest?e.target.closest('[data-item-id]'):null;}
+function highlight(id,on){document.querySelectorAll('[data-item-id="'+id+'"]').forEach(function(el){el.classList.toggle('hl',on);});}
+function point(e){
+var r=svg.getBoundingClientRect(),s=Math.max(vb[2]/r.width,vb[3]/r.height);
+return{s:s,x:vb[0]+(e.clientX-r.left-(r.width-vb[2]/s)/2)*s,y:vb[1]+(e.clientY-r.top-(r.height-vb[3]/s)/2)*s};
+}
+document.addEventListener('mouseover',function(e){
+var el=tagged(e);if(!el)return;
+var id=el.getAttribute('data-item-id'),p=parts[id];highlight(id,true);
+if(p&&el.tagName.toLowerCase()==='path'){tip.textContent=p.label+' ('+p.placed+'/'+p.demand+')';tip.style.display='block';}
+});
+document.addEventListener('mouseout',function(e){var el=tagged(e);if(!el)return;highlight(el.getAttribute('data-item-id'),false);tip.style.display='none';});
+svg.addEventListener('wheel',function(e){
+e.preventDefault();var p=point(e),k=e.deltaY>0?1.25:0.8;
+vb=[p.x-(p.x-vb[0])*k,p.y-(p.y-vb[1])*k,vb[2]*k,vb[3]*k];show();
+},{passive:false});
+svg.addEventListener('mousedown',function(e){drag={x:e.clientX,y:e.clientY,s:point(e).s};svg.classList.add('dragging');});
+window.addEventListener('mousemove',function(e){
+tip.style.left=(e.clientX+12)+'px';tip.style.top=(e.clientY+12)+'px';
+if(!drag)return;vb[0]-=(e.clientX-drag.x)*drag.s;vb[1]-=(e.clientY-drag.y)*drag.s;drag.x=e.clientX;drag.y=e.clientY;show();
+});
+window.addEventListener('mouseup',function(){drag=null;svg.classList.remove('dragging');});
+svg.addEventListener('dblclick',function(){vb=home.slice();show();});
+})();"#;
+
+/// Options of the HTML viewer export
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InteractiveHtmlOptions {
+    /// Max deviation of the drawn outlines in mm (default: DEFAULT_DISPLAY_TOLERANCE)
+    #[serde(default)]
+    pub display_tolerance: Option<f64>,
+    /// Page title (default: the instance name)
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// Row of the parts table, also what the script shows on hover
+#[derive(Debug, Clone, Serialize)]
+struct PartRow {
+    item_id: usize,
+    label: String,
+    placed: usize,
+    demand: u64,
+    /// Net area of one part in mm²
+    area: f64,
+}
+
+/// JSON for an inline script element: nothing in it can end the element
+fn script_json(value: &serde_json::Value) -> String {
+    // The characters only occur inside JSON strings, where escapes are valid
+    value
+        .to_string()
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026")
+}
+
+fn push_ring(d: &mut String, ring: &Polygon) {
+    let mut last = None;
+    for &(x, y) in ring {
+        // Points that round to the previous one only cost bytes
+        let point = (format!("{:.2}", x), format!("{:.2}", y));
+        if last.as_ref() == Some(&point) {
+            continue;
+        }
+        let _ = write!(
+            d,
+            "{}{} {}",
+            if last.is_none() { "M" } else { "L" },
+            point.0,
+            point.1
+        );
+        last = Some(point);
+    }
+    if last.is_some() {
+        d.push('Z');
+    }
+}
+
+/// Parts of the instance with their placed count, in instance order
+fn part_rows(output: &NestingOutput, instance: &InstanceGeometry) -> Vec<PartRow> {
+    instance
+        .items
+        .iter()
+        .map(|item| {
+            let holes: f64 = item.holes.iter().map(|hole| polygon_area(hole)).sum();
+            PartRow {
+                item_id: item.id as usize,
+                label: item.display_name(),
+                placed: output
+                    .layouts
+                    .iter()
+                    .filter(|placed| placed.item_id as u64 == item.id)
+                    .count(),
+                demand: item.demand,
+                area: polygon_area(&item.outer) - holes,
+            }
+        })
+        .collect()
+}
+
+/// Layout drawing with one `data-item-id` path per placement
+fn layout_svg(
+    output: &NestingOutput,
+    instance: &InstanceGeometry,
+    tolerance: f64,
+) -> Result<String, String> {
+    let (width, height) = (output.strip_width, output.strip_height);
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg id="drawing" xmlns="http://www.w3.org/2000/svg" viewBox="{:.2} {:.2} {:.2} {:.2}">"#,
+        -DRAWING_MARGIN,
+        -DRAWING_MARGIN,
+        width + 2.0 * DRAWING_MARGIN,
+        height + 2.0 * DRAWING_MARGIN
+    );
+    // Strip coordinates have Y up
+    let _ = write!(
+        svg,
+        r#"<g transform="matrix(1 0 0 -1 0 {:.2})"><rect class="strip" width="{:.2}" height="{:.2}"/>"#,
+        height, width, height
+    );
+    for (index, part) in placed_contours(output, instance)?.iter().enumerate() {
+        let mut d = String::new();
+        for ring in std::iter::once(&part.outer).chain(&part.holes) {
+            push_ring(&mut d, &simplify_ring(ring, tolerance));
+        }
+        let _ = write!(
+            svg,
+            r#"<path class="part" data-item-id="{}" data-placement="{}" d="{}"/>"#,
+            part.item_id, index, d
+        );
+    }
+    svg.push_str("</g></svg>");
+    Ok(svg)
+}
+
+/// Self-contained HTML page showing a layout
+///
+/// Needs placement outlines like the DXF export; lengths are taken as mm.
+pub fn layout_interactive_html(
+    output: &NestingOutput,
+    instance: &InstanceGeometry,
+    options: &InteractiveHtmlOptions,
+) -> Result<String, String> {
+    let tolerance = options
+        .display_tolerance
+        .unwrap_or(DEFAULT_DISPLAY_TOLERANCE)
+        .max(0.0);
+    let svg = layout_svg(output, instance, tolerance)?;
+    let rows = part_rows(output, instance);
+    let requested = output
+        .items_requested
+        .unwrap_or_else(|| rows.iter().map(|row| row.demand as usize).sum());
+    let title = escape_xml(options.title.as_deref().unwrap_or(&output.instance_name));
+
+    let mut html = String::with_capacity(svg.len() + 16 * 1024);
+    let _ = write!(
+        html,
+        r#"<!DOCTYPE html><html lang="en"><head><meta charset="utf-8"><meta name="viewport" content="width=device-width, initial-scale=1"><title>{}</title><style>{}</style></head><body>"#,
+        title, STYLE
+    );
+    let _ = write!(
+        html,
+        r#"<header><h1>{}</h1><p id="summary">{} of {} parts placed &middot; strip {:.1} &times; {:.1} mm &middot; utilization {:.1}%</p></header>"#,
+        title,
+        output.total_items_placed,
+        requested,
+        output.strip_width,
+        output.strip_height,
+        output.utilization * 100.0
+    );
+    html.push_str("<main>");
+    html.push_str(&svg);
+    html.push_str(
+        r#"<aside><table id="parts"><thead><tr><th>Part</th><th class="num">ID</th><th class="num">Placed</th><th class="num">Requested</th><th class="num">Area (mm²)</th></tr></thead><tbody>"#,
+    );
+    for row in &rows {
+        let _ = write!(
+            html,
+            r#"<tr data-item-id="{}"{}><td>{}</td><td class="num">{}</td><td class="num">{}</td><td class="num">{}</td><td class="num">{:.0}</td></tr>"#,
+            row.item_id,
+            if (row.placed as u64) < row.demand {
+                r#" class="unplaced""#
+            } else {
+                ""
+            },
+            escape_xml(&row.label),
+            row.item_id,
+            row.placed,
+            row.demand,
+            row.area
+        );
+    }
+    html.push_str("</tbody></table></aside></main>");
+    let _ = write!(
+        html,
+        r#"<div id="tip"></div><script type="application/json" id="layout-data">{}</script><script>{}</script></body></html>"#,
+        script_json(&serde_json::json!({ "parts": rows })),
+        SCRIPT
+    );
+    Ok(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::parse_instance;
+
+    const LABELS: &str = r#"{
+        "name": "Quote <7> & \"rush\"",
+        "strip_height": 500,
+        "items": [
+            {"id": 0, "demand": 2, "label": "<img src=x onerror=alert(1)>",
+             "shape": {"type": "polygon", "data": {
+                 "outer": [[0, 0], [100, 0], [100, 100], [0, 100]],
+                 "inner": [[[40, 40], [60, 40], [60, 60], [40, 60]]]
+             }}},
+            {"id": 1, "demand": 2, "label": "Bracket \"A\" & <script>alert(2)",
+             "shape": {"type": "rectangle", "data": {"x_min": 0, "y_min": 0, "width": 50, "height": 20}}}
+        ]
+    }"#;
+
+    fn placed(item_id: usize, x: f64, y: f64, width: f64, height: f64) -> serde_json::Value {
+        serde_json::json!({
+            "item_id": item_id,
+            "rotation_degrees": 0.0,
+            "position_x": x,
+            "position_y": y,
+            "outline": [[x, y], [x + width, y], [x + width, y + height], [x, y + height]]
+        })
+    }
+
+    fn output(layouts: Vec<serde_json::Value>, unplaced: Vec<usize>) -> NestingOutput {
+        serde_json::from_value(serde_json::json!({
+            "instance_name": "Quote <7> & \"rush\"",
+            "strip_width": 300.0,
+            "strip_height": 500.0,
+            "total_items_placed": layouts.len(),
+            "items_requested": 4,
+            "layouts": layouts,
+            "utilization": 0.1,
+            "computation_time_secs": 1.0,
+            "unplaced_item_ids": unplaced,
+        }))
+        .unwrap()
+    }
+
+    /// Opening tags that make up the page, with their `id` and `data-item-id`
+    fn outline(html: &str) -> String {
+        const STRUCTURE: &[&str] = &[
+            "html", "head", "title", "style", "body", "header", "main", "svg", "g", "aside",
+            "table", "tbody", "script",
+        ];
+        let attribute = |tag: &str, name: &str| {
+            let start = tag.find(&format!(" {}=\"", name))? + name.len() + 3;
+            let end = tag[start..].find('"')? + start;
+            Some(format!(" {}={}", name, &tag[start..end]))
+        };
+        let mut lines = Vec::new();
+        for (start, _) in html.match_indices('<') {
+            let tag = &html[start + 1..];
+            if !tag.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                continue;
+            }
+            let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+            let name = tag
+                .split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .unwrap_or_default();
+            let id = attribute(tag, "id");
+            let item = attribute(tag, "data-item-id");
+            if STRUCTURE.contains(&name) || id.is_some() || item.is_some() {
+                lines.push(format!(
+                    "{}{}{}",
+                    name,
+                    id.unwrap_or_default(),
+                    item.unwrap_or_default()
+                ));
+            }
+        }
+        lines.join("\n") + "\n"
+    }
+
+    #[test]
+    fn test_viewer_matches_golden_outline_and_escapes_labels() {
+        let instance = parse_instance(LABELS).unwrap();
+        let output = output(
+            vec![
+                placed(0, 0.0, 0.0, 100.0, 100.0),
+                placed(1, 110.0, 0.0, 50.0, 20.0),
+                placed(0, 0.0, 110.0, 100.0, 100.0),
+            ],
+            vec![1],
+        );
+        let html = layout_interactive_html(&output, &instance, &InteractiveHtmlOptions::default())
+            .unwrap();
+
+        assert_eq!(
+            outline(&html),
+            include_str!("../../test_instances/layout_viewer_outline.txt")
+        );
+
+        // Nothing from the labels or the name is live markup
+        for raw in ["<img", "<script>alert", "<7>", "\"rush\""] {
+            assert!(!html.contains(raw), "unescaped {}", raw);
+        }
+        assert!(html.contains("<td>&lt;img src=x onerror=alert(1)&gt;</td>"));
+        assert!(html.contains("<td>Bracket &quot;A&quot; &amp; &lt;script&gt;alert(2)</td>"));
+        assert!(html.contains("<title>Quote &lt;7&gt; &amp; &quot;rush&quot;</title>"));
+        // The payload still holds the labels as written
+        let payload = html
+            .split(r#"<script type="application/json" id="layout-data">"#)
+            .nth(1)
+            .and_then(|rest| rest.split("</script>").next())
+            .unwrap();
+        let payload: serde_json::Value = serde_json::from_str(payload).unwrap();
+        assert_eq!(
+            payload["parts"][1]["label"],
+            "Bracket \"A\" & <script>alert(2)"
+        );
+        assert_eq!(payload["parts"][1]["placed"], 1);
+        // Net area of the plate, and the hole drawn as a second ring
+        assert_eq!(payload["parts"][0]["area"], 9600.0);
+        let plate = html
+            .split(r#"data-placement="0" d=""#)
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .unwrap();
+        assert_eq!(plate.matches('Z').count(), 2);
+        // Self-contained
+        assert!(!html.contains("src=\"http") && !html.contains("href="));
+    }
+
+    #[test]
+    fn test_300_parts_stay_below_5mb() {
+        // A detailed part: a ring of 128 vertices with 8 round holes of 64
+        let circle = |cx: f64, cy: f64, r: f64, n: usize| -> Vec<[f64; 2]> {
+            (0..n)
+                .map(|i| {
+                    let a = i as f64 / n as f64 * std::f64::consts::TAU;
+                    [cx + r * a.cos(), cy + r * a.sin()]
+                })
+                .collect()
+        };
+        let holes: Vec<_> = (0..8)
+            .map(|i| {
+                let a = i as f64 / 8.0 * std::f64::consts::TAU;
+                circle(40.0 * a.cos(), 40.0 * a.sin(), 4.0, 64)
+            })
+            .collect();
+        let instance = parse_instance(
+            &serde_json::json!({
+                "name": "flanges",
+                "strip_height": 2000,
+                "items": [{"id": 0, "demand": 300, "shape": {"type": "polygon", "data": {
+                    "outer": circle(0.0, 0.0, 50.0, 128),
+                    "inner": holes
+                }}}]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let layouts = (0..300)
+            .map(|i| {
+                let (x, y) = (
+                    (i / 19) as f64 * 105.0 + 50.0,
+                    (i % 19) as f64 * 105.0 + 50.0,
+                );
+                let outline: Vec<[f64; 2]> = circle(x, y, 50.0, 128);
+                serde_json::json!({
+                    "item_id": 0,
+                    "rotation_degrees": 0.0,
+                    "position_x": x,
+                    "position_y": y,
+                    "outline": outline
+                })
+            })
+            .collect();
+        let mut output = output(layouts, Vec::new());
+        output.strip_width = 1700.0;
+        output.strip_height = 2000.0;
+
+        let html = layout_interactive_html(&output, &instance, &InteractiveHtmlOptions::default())
+            .unwrap();
+        assert_eq!(html.matches("class=\"part\"").count(), 300);
+        assert!(html.len() < 5 * 1024 * 1024, "{} bytes", html.len());
+    }
+}
@@ -0,0 +1,589 @@
+//! Print pages of a nested layout for work orders
+//!
+//! Draws each sheet with its parts at a true physical scale (e.g. 1:10 on
+//! A4 landscape) inside a frame, with overall dimensions, the scale ratio
+//! and a title block. Pages are SVG in paper millimetres, sized in pixels
+//! for the print DPI; the frontend rasterizes them and `png_with_dpi`
+//! records the DPI in the PNG so printing keeps the scale.
+
+use super::geometry::Polygon;
+use super::instance::InstanceGeometry;
+use super::keep_out::escape_xml;
+use super::layout_dxf::placed_contours;
+use super::serializer::NestingOutput;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+pub const MM_PER_INCH: f64 = 25.4;
+/// Lowest and highest accepted print resolution
+pub const MIN_PRINT_DPI: f64 = 72.0;
+pub const MAX_PRINT_DPI: f64 = 600.0;
+/// Scale denominators tried when no scale is given, finest first
+pub const STANDARD_SCALES: &[f64] = &[
+    1.0, 2.0, 5.0, 10.0, 20.0, 25.0, 50.0, 100.0, 200.0, 500.0, 1000.0,
+];
+
+/// Page border to the frame (mm)
+const MARGIN: f64 = 10.0;
+/// Room between the frame and the drawing for dimensions and labels (mm)
+const DIMENSION_SPACE: f64 = 12.0;
+const TITLE_BLOCK_WIDTH: f64 = 120.0;
+const TITLE_BLOCK_HEIGHT: f64 = 24.0;
+/// ISO 3098 lettering height (mm)
+const TEXT_SIZE: f64 = 3.5;
+const LINE_WIDTH: f64 = 0.25;
+const PART_LINE_WIDTH: f64 = 0.18;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaperSize {
+    A4,
+    A3,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Orientation {
+    #[default]
+    Landscape,
+    Portrait,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Paper {
+    pub size: PaperSize,
+    #[serde(default)]
+    pub orientation: Orientation,
+}
+
+impl Paper {
+    /// Width and height in mm, as oriented
+    pub fn dimensions(&self) -> (f64, f64) {
+        let (short, long) = match self.size {
+            PaperSize::A4 => (210.0, 297.0),
+            PaperSize::A3 => (297.0, 420.0),
+        };
+        match self.orientation {
+            Orientation::Landscape => (long, short),
+            Orientation::Portrait => (short, long),
+        }
+    }
+}
+
+/// Options for printing a layout
+#[derive(Debug, Clone, Deserialize)]
+pub struct LayoutPrintOptions {
+    pub dpi: f64,
+    pub paper: Paper,
+    /// Scale denominator, 10 for 1:10 (default: the finest standard scale that fits)
+    #[serde(default)]
+    pub scale: Option<f64>,
+}
+
+/// One page, ready to rasterize
+#[derive(Debug, Clone, Serialize)]
+pub struct PrintPage {
+    pub svg: String,
+    pub width_px: u32,
+    pub height_px: u32,
+    /// Scale denominator the sheet is drawn at
+    pub scale: f64,
+}
+
+/// Part of the page holding the sheet, in mm from the top left corner
+#[derive(Debug, Clone, Copy)]
+struct DrawingArea {
+    left: f64,
+    top: f64,
+    right: f64,
+    bottom: f64,
+}
+
+impl DrawingArea {
+    fn for_paper(paper: &Paper) -> Self {
+        let (width, height) = paper.dimensions();
+        DrawingArea {
+            left: MARGIN + DIMENSION_SPACE,
+            top: MARGIN + DIMENSION_SPACE,
+            right: width - MARGIN - TEXT_SIZE,
+            bottom: height - MARGIN - TITLE_BLOCK_HEIGHT - DIMENSION_SPACE,
+        }
+    }
+
+    fn fits(&self, strip_width: f64, strip_height: f64, scale: f64) -> bool {
+        strip_width / scale <= self.right - self.left + 1e-9
+            && strip_height / scale <= self.bottom - self.top + 1e-9
+    }
+
+    /// Page position of a strip point; the strip origin is the bottom left corner
+    fn page_point(&self, scale: f64, (x, y): (f64, f64)) -> (f64, f64) {
+        (self.left + x / scale, self.bottom - y / scale)
+    }
+}
+
+/// Finest standard scale at which a strip fits the paper
+pub fn fit_scale(strip_width: f64, strip_height: f64, paper: &Paper) -> Option<f64> {
+    let area = DrawingArea::for_paper(paper);
+    STANDARD_SCALES
+        .iter()
+        .copied()
+        .find(|&scale| area.fits(strip_width, strip_height, scale))
+}
+
+/// Print pages of a job, one per sheet
+///
+/// All sheets share one scale so they compare on paper. Needs placement
+/// outlines like the DXF export. `printed_on` goes into the title block.
+pub fn layout_print_pages(
+    sheets: &[NestingOutput],
+    instance: &InstanceGeometry,
+    options: &LayoutPrintOptions,
+    printed_on: &str,
+) -> Result<Vec<PrintPage>, String> {
+    if !(MIN_PRINT_DPI..=MAX_PRINT_DPI).contains(&options.dpi) {
+        return Err(format!(
+            "Print DPI must be between {} and {}, got {}",
+            MIN_PRINT_DPI, MAX_PRINT_DPI, options.dpi
+        ));
+    }
+    if sheets.is_empty() {
+        return Err("No sheets to print".to_string());
+    }
+
+    let max_width = sheets.iter().map(|s| s.strip_width).fold(0.0, f64::max);
+    let max_height = sheets.iter().map(|s| s.strip_height).fold(0.0, f64::max);
+    let area = DrawingArea::for_paper(&options.paper);
+    let scale = match options.scale {
+        Some(scale) if !(scale.is_finite() && scale > 0.0) => {
+            return Err(format!("Scale must be positive, got 1:{}", scale));
+        }
+        Some(scale) if !area.fits(max_width, max_height, scale) => {
+            return Err(format!(
+                "A {:.0} x {:.0} mm sheet does not fit the paper at {}",
+                max_width,
+                max_height,
+                scale_label(scale)
+            ));
+        }
+        Some(scale) => scale,
+        None => fit_scale(max_width, max_height, &options.paper).ok_or_else(|| {
+            format!(
+                "A {:.0} x {:.0} mm sheet does not fit the paper at any standard scale",
+                max_width, max_height
+            )
+        })?,
+    };
+
+    let (paper_width, paper_height) = options.paper.dimensions();
+    let px_per_mm = options.dpi / MM_PER_INCH;
+    let width_px = (paper_width * px_per_mm).round() as u32;
+    let height_px = (paper_height * px_per_mm).round() as u32;
+
+    sheets
+        .iter()
+        .enumerate()
+        .map(|(index, sheet)| {
+            let parts = placed_contours(sheet, instance)?;
+            let svg = render_page(&PageContext {
+                sheet,
+                parts: parts
+                    .iter()
+                    .map(|p| (&p.outer, p.holes.as_slice()))
+                    .collect(),
+                area,
+                scale,
+                // Exact so that one unit is exactly one paper mm at the DPI
+                view_box: (width_px as f64 / px_per_mm, height_px as f64 / px_per_mm),
+                size_px: (width_px, height_px),
+                sheet_number: index + 1,
+                sheet_count: sheets.len(),
+                printed_on,
+            });
+            Ok(PrintPage {
+                svg,
+                width_px,
+                height_px,
+                scale,
+            })
+        })
+        .collect()
+}
+
+struct PageContext<'a> {
+    sheet: &'a NestingOutput,
+    parts: Vec<(&'a Polygon, &'a [Polygon])>,
+    area: DrawingArea,
+    scale: f64,
+    view_box: (f64, f64),
+    size_px: (u32, u32),
+    sheet_number: usize,
+    sheet_count: usize,
+    printed_on: &'a str,
+}
+
+fn render_page(page: &PageContext) -> String {
+    let (vw, vh) = page.view_box;
+    let area = page.area;
+    let scale = page.scale;
+    let mut svg = String::new();
+
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {:.4} {:.4}" font-family="sans-serif" font-size="{}">"#,
+        page.size_px.0, page.size_px.1, vw, vh, TEXT_SIZE
+    );
+    let _ = write!(
+        svg,
+        r#"<rect width="{:.4}" height="{:.4}" fill="white"/>"#,
+        vw, vh
+    );
+
+    // Frame and title block
+    let (paper_width, paper_height) = (vw, vh);
+    let tb_left = paper_width - MARGIN - TITLE_BLOCK_WIDTH;
+    let tb_top = paper_height - MARGIN - TITLE_BLOCK_HEIGHT;
+    let row = TITLE_BLOCK_HEIGHT / 3.0;
+    let _ = write!(
+        svg,
+        r#"<g fill="none" stroke="black" stroke-width="{}"><rect x="{m}" y="{m}" width="{:.3}" height="{:.3}"/><rect x="{:.3}" y="{:.3}" width="{}" height="{}"/><path d="M{:.3} {:.3}H{:.3}M{:.3} {:.3}H{:.3}"/></g>"#,
+        LINE_WIDTH,
+        paper_width - 2.0 * MARGIN,
+        paper_height - 2.0 * MARGIN,
+        tb_left,
+        tb_top,
+        TITLE_BLOCK_WIDTH,
+        TITLE_BLOCK_HEIGHT,
+        tb_left,
+        tb_top + row,
+        tb_left + TITLE_BLOCK_WIDTH,
+        tb_left,
+        tb_top + 2.0 * row,
+        tb_left + TITLE_BLOCK_WIDTH,
+        m = MARGIN,
+    );
+    let text_x = tb_left + 2.0;
+    let baseline = |r: f64| tb_top + r * row + (row + TEXT_SIZE) / 2.0 - 0.5;
+    let _ = write!(
+        svg,
+        r#"<text x="{:.3}" y="{:.3}" font-weight="bold">{}</text>"#,
+        text_x,
+        baseline(0.0),
+        escape_xml(&page.sheet.instance_name)
+    );
+    let _ = write!(
+        svg,
+        r#"<text x="{:.3}" y="{:.3}">Sheet {} of {}    Scale {}    {}</text>"#,
+        text_x,
+        baseline(1.0),
+        page.sheet_number,
+        page.sheet_count,
+        scale_label(scale),
+        escape_xml(page.printed_on)
+    );
+    let _ = write!(
+        svg,
+        r#"<text x="{:.3}" y="{:.3}">{} parts    Utilization {:.1}%</text>"#,
+        text_x,
+        baseline(2.0),
+        page.sheet.total_items_placed,
+        page.sheet.utilization * 100.0
+    );
+
+    // Sheet outline and parts
+    let (strip_left, strip_bottom) = area.page_point(scale, (0.0, 0.0));
+    let strip_width = page.sheet.strip_width / scale;
+    let strip_height = page.sheet.strip_height / scale;
+    let strip_top = strip_bottom - strip_height;
+    let _ = write!(
+        svg,
+        "<text x=\"{:.3}\" y=\"{:.3}\">{}</text>",
+        strip_left,
+        strip_top - DIMENSION_SPACE / 2.0,
+        scale_label(scale)
+    );
+    let _ = write!(
+        svg,
+        r##"<rect x="{:.3}" y="{:.3}" width="{:.3}" height="{:.3}" fill="none" stroke="#808080" stroke-width="{}"/>"##,
+        strip_left, strip_top, strip_width, strip_height, LINE_WIDTH
+    );
+    let _ = write!(
+        svg,
+        r##"<g fill="#dde6f0" fill-rule="evenodd" stroke="black" stroke-width="{}">"##,
+        PART_LINE_WIDTH
+    );
+    for (outer, holes) in &page.parts {
+        svg.push_str("<path d=\"");
+        for contour in std::iter::once(*outer).chain(holes.iter()) {
+            push_contour(&mut svg, contour, |point| area.page_point(scale, point));
+        }
+        svg.push_str("\"/>");
+    }
+    svg.push_str("</g>");
+
+    // Overall dimensions, below and left of the sheet
+    let dim_y = strip_bottom + DIMENSION_SPACE / 2.0;
+    let dim_x = strip_left - DIMENSION_SPACE / 2.0;
+    let tick = 1.5;
+    let _ = write!(
+        svg,
+        r#"<path d="M{l:.3} {y:.3}H{r:.3}M{l:.3} {t0:.3}V{t1:.3}M{r:.3} {t0:.3}V{t1:.3}M{x:.3} {b:.3}V{top:.3}M{s0:.3} {b:.3}H{s1:.3}M{s0:.3} {top:.3}H{s1:.3}" fill="none" stroke="black" stroke-width="{w}"/>"#,
+        l = strip_left,
+        r = strip_left + strip_width,
+        y = dim_y,
+        t0 = dim_y - tick,
+        t1 = dim_y + tick,
+        x = dim_x,
+        b = strip_bottom,
+        top = strip_top,
+        s0 = dim_x - tick,
+        s1 = dim_x + tick,
+        w = LINE_WIDTH,
+    );
+    let _ = write!(
+        svg,
+        r#"<text x="{:.3}" y="{:.3}" text-anchor="middle">{}</text>"#,
+        strip_left + strip_width / 2.0,
+        dim_y - 1.0,
+        format_length(page.sheet.strip_width)
+    );
+    let (label_x, label_y) = (dim_x - 1.0, strip_bottom - strip_height / 2.0);
+    let _ = write!(
+        svg,
+        r#"<text x="{:.3}" y="{:.3}" text-anchor="middle" transform="rotate(-90 {:.3} {:.3})">{}</text>"#,
+        label_x,
+        label_y,
+        label_x,
+        label_y,
+        format_length(page.sheet.strip_height)
+    );
+
+    svg.push_str("</svg>");
+    svg
+}
+
+fn push_contour(
+    svg: &mut String,
+    contour: &Polygon,
+    page_point: impl Fn((f64, f64)) -> (f64, f64),
+) {
+    for (i, point) in contour.iter().enumerate() {
+        let (x, y) = page_point(*point);
+        let _ = write!(svg, "{}{:.3} {:.3}", if i == 0 { "M" } else { "L" }, x, y);
+    }
+    if !contour.is_empty() {
+        svg.push('Z');
+    }
+}
+
+/// "1:10" for a scale denominator of 10
+pub fn scale_label(scale: f64) -> String {
+    format!("1:{}", scale)
+}
+
+fn format_length(mm: f64) -> String {
+    if (mm - mm.round()).abs() < 0.05 {
+        format!("{:.0} mm", mm)
+    } else {
+        format!("{:.1} mm", mm)
+    }
+}
+
+/// Record the print resolution in a PNG (pHYs chunk)
+///
+/// Canvas PNGs carry no resolution, so printing would guess one and lose
+/// the scale. An existing pHYs chunk is replaced.
+pub fn png_with_dpi(png: &[u8], dpi: f64) -> Result<Vec<u8>, String> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    if !png.starts_with(SIGNATURE) {
+        return Err("Page image is not a PNG".to_string());
+    }
+
+    let pixels_per_metre = (dpi / MM_PER_INCH * 1000.0).round() as u32;
+    let mut phys = Vec::with_capacity(9);
+    phys.extend_from_slice(&pixels_per_metre.to_be_bytes());
+    phys.extend_from_slice(&pixels_per_metre.to_be_bytes());
+    phys.push(1); // unit: metre
+
+    let mut out = Vec::with_capacity(png.len() + 21);
+    out.extend_from_slice(SIGNATURE);
+    let mut offset = SIGNATURE.len();
+    while offset + 12 <= png.len() {
+        let length = u32::from_be_bytes(png[offset..offset + 4].try_into().unwrap()) as usize;
+        let end = offset + 12 + length;
+        if end > png.len() {
+            return Err("Page image is a truncated PNG".to_string());
+        }
+        let chunk_type = &png[offset + 4..offset + 8];
+        if chunk_type != b"pHYs" {
+            out.extend_from_slice(&png[offset..end]);
+        }
+        if chunk_type == b"IHDR" {
+            push_chunk(&mut out, b"pHYs", &phys);
+        }
+        offset = end;
+    }
+    Ok(out)
+}
+
+fn push_chunk(out: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(chunk_type);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// CRC-32 as used by PNG chunks
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::parse_instance;
+
+    /// One 100 x 100 mm plate with a 20 x 20 mm hole
+    const PLATE: &str = r#"{
+        "name": "plate",
+        "strip_height": 1200,
+        "items": [{
+            "id": 0,
+            "demand": 1,
+            "shape": {"type": "polygon", "data": {
+                "outer": [[0, 0], [100, 0], [100, 100], [0, 100]],
+                "inner": [[[40, 40], [60, 40], [60, 60], [40, 60]]]
+            }}
+        }]
+    }"#;
+
+    fn sheet(strip_width: f64) -> NestingOutput {
+        serde_json::from_value(serde_json::json!({
+            "instance_name": "plate",
+            "strip_width": strip_width,
+            "strip_height": 1200.0,
+            "total_items_placed": 1,
+            "layouts": [{
+                "item_id": 0,
+                "rotation_degrees": 0.0,
+                "position_x": 0.0,
+                "position_y": 0.0,
+                "outline": [[500.0, 300.0], [600.0, 300.0], [600.0, 400.0], [500.0, 400.0]]
+            }],
+            "utilization": 0.1,
+            "computation_time_secs": 1.0
+        }))
+        .unwrap()
+    }
+
+    fn a4_landscape(dpi: f64) -> LayoutPrintOptions {
+        LayoutPrintOptions {
+            dpi,
+            paper: Paper {
+                size: PaperSize::A4,
+                orientation: Orientation::Landscape,
+            },
+            scale: None,
+        }
+    }
+
+    #[test]
+    fn test_fit_scale() {
+        let paper = a4_landscape(300.0).paper;
+        assert_eq!(fit_scale(2000.0, 1200.0, &paper), Some(10.0));
+        assert_eq!(fit_scale(200.0, 100.0, &paper), Some(1.0));
+        assert_eq!(fit_scale(3000.0, 1500.0, &paper), Some(20.0));
+    }
+
+    #[test]
+    fn test_100mm_feature_measures_10mm_at_1_to_10() {
+        let instance = parse_instance(PLATE).unwrap();
+        let options = a4_landscape(300.0);
+        let pages =
+            layout_print_pages(&[sheet(2000.0)], &instance, &options, "2026-10-17").unwrap();
+        let page = &pages[0];
+        assert_eq!(page.scale, 10.0);
+        assert!(page.svg.contains("1:10"));
+
+        // Part edges on the page, in viewBox units
+        let area = DrawingArea::for_paper(&options.paper);
+        let (left, _) = area.page_point(page.scale, (500.0, 300.0));
+        let (right, _) = area.page_point(page.scale, (600.0, 300.0));
+        assert!(page.svg.contains(&format!("M{:.3} ", left)));
+
+        // viewBox units to printed mm through the pixel size at 300 DPI
+        let view_box_width: f64 = page
+            .svg
+            .split("viewBox=\"0 0 ")
+            .nth(1)
+            .and_then(|rest| rest.split(' ').next())
+            .unwrap()
+            .parse()
+            .unwrap();
+        let px_per_unit = page.width_px as f64 / view_box_width;
+        let printed_mm = (right - left) * px_per_unit / (options.dpi / MM_PER_INCH);
+        assert!(
+            (printed_mm - 10.0).abs() <= 0.2,
+            "printed {} mm",
+            printed_mm
+        );
+        assert_eq!((page.width_px, page.height_px), (3508, 2480));
+    }
+
+    #[test]
+    fn test_one_page_per_sheet_at_shared_scale() {
+        let instance = parse_instance(PLATE).unwrap();
+        let pages = layout_print_pages(
+            &[sheet(1000.0), sheet(2500.0)],
+            &instance,
+            &a4_landscape(150.0),
+            "2026-10-17",
+        )
+        .unwrap();
+        assert_eq!(pages.len(), 2);
+        assert!(pages.iter().all(|page| page.scale == 10.0));
+        assert!(pages[1].svg.contains("Sheet 2 of 2"));
+        assert!(pages[1].svg.contains("2500 mm"));
+    }
+
+    #[test]
+    fn test_explicit_scale_must_fit() {
+        let instance = parse_instance(PLATE).unwrap();
+        let mut options = a4_landscape(300.0);
+        options.scale = Some(5.0);
+        let error =
+            layout_print_pages(&[sheet(2000.0)], &instance, &options, "2026-10-17").unwrap_err();
+        assert!(error.contains("1:5"));
+    }
+
+    #[test]
+    fn test_png_with_dpi_inserts_phys_after_ihdr() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        push_chunk(&mut png, b"IHDR", &[0; 13]);
+        push_chunk(&mut png, b"IEND", &[]);
+
+        let stamped = png_with_dpi(&png, 300.0).unwrap();
+        assert_eq!(&stamped[33 + 4..33 + 8], b"pHYs");
+        let ppm = u32::from_be_bytes(stamped[41..45].try_into().unwrap());
+        assert_eq!(ppm, 11811);
+        assert_eq!(stamped.len(), png.len() + 21);
+
+        // Stamping again replaces the chunk
+        assert_eq!(png_with_dpi(&stamped, 300.0).unwrap(), stamped);
+        assert!(png_with_dpi(b"GIF89a", 300.0).is_err());
+    }
+}
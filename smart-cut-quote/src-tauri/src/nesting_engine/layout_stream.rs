@@ -0,0 +1,166 @@
+//! Layout SVGs of a running optimization
+//!
+//! The WASM build posts an SVG of every improved layout through sparrow's
+//! `WasmSvgExporter`; the native run only rendered the final one.
+//! `LayoutSvgListener` renders the layout of feasible solutions with the
+//! same jagua-rs renderer as the final SVG, at most once per
+//! `LAYOUT_SVG_INTERVAL` and only when the strip got narrower than in the
+//! last SVG sent. Unlike the `preview` patches each SVG stands alone, so
+//! any of them may be dropped on the way to the frontend.
+
+use super::viewbox::{expand_svg_viewbox, VIEWBOX_MARGIN};
+use jagua_rs::io::svg::s_layout_to_svg;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use serde::Serialize;
+use sparrow::consts::DRAW_OPTIONS;
+use sparrow::util::listener::{ReportType, SolutionListener};
+use std::time::{Duration, Instant};
+
+/// Minimum time between two layout SVGs
+pub const LAYOUT_SVG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Strip widths closer than this are not an improvement (mm)
+const WIDTH_EPSILON: f64 = 1e-3;
+
+/// Rendered layout of a solution found while optimizing
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LayoutSvg {
+    pub svg: String,
+    pub strip_width: f64,
+    /// Seconds since the optimizer started
+    pub elapsed_secs: f64,
+}
+
+/// Decides which reported solutions are worth rendering
+#[derive(Debug, Clone)]
+pub struct LayoutThrottle {
+    min_interval: Duration,
+    last_sent: Option<Instant>,
+    sent_width: Option<f64>,
+}
+
+impl LayoutThrottle {
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            last_sent: None,
+            sent_width: None,
+        }
+    }
+
+    /// True if a layout of `strip_width` reported at `now` is to be sent
+    ///
+    /// A narrower strip held back by the interval is sent with the next
+    /// report after it, as the width is compared to the last one sent.
+    pub fn admit(&mut self, strip_width: f64, now: Instant) -> bool {
+        let improved = self
+            .sent_width
+            .is_none_or(|sent| strip_width < sent - WIDTH_EPSILON);
+        let due = self
+            .last_sent
+            .is_none_or(|sent| now.duration_since(sent) >= self.min_interval);
+        if improved && due {
+            self.last_sent = Some(now);
+            self.sent_width = Some(strip_width);
+        }
+        improved && due
+    }
+}
+
+/// Listener rendering improved layouts as SVG
+pub struct LayoutSvgListener<'a> {
+    throttle: LayoutThrottle,
+    started: Instant,
+    emit: &'a mut dyn FnMut(LayoutSvg),
+}
+
+impl<'a> LayoutSvgListener<'a> {
+    pub fn new(emit: &'a mut dyn FnMut(LayoutSvg)) -> Self {
+        Self {
+            throttle: LayoutThrottle::new(LAYOUT_SVG_INTERVAL),
+            started: Instant::now(),
+            emit,
+        }
+    }
+
+    /// Render and emit the solution if it is due (see `LayoutThrottle`)
+    pub fn record(
+        &mut self,
+        report_type: &ReportType,
+        solution: &SPSolution,
+        instance: &SPInstance,
+    ) {
+        if !matches!(report_type, ReportType::ExplFeas | ReportType::CmprFeas) {
+            return;
+        }
+        let strip_width = solution.strip_width() as f64;
+        if !self.throttle.admit(strip_width, Instant::now()) {
+            return;
+        }
+        let svg = s_layout_to_svg(&solution.layout_snapshot, instance, DRAW_OPTIONS, "");
+        // A preview without its margin is still worth showing
+        let (svg, _) = expand_svg_viewbox(&svg.to_string(), VIEWBOX_MARGIN, Vec::new);
+        (self.emit)(LayoutSvg {
+            svg,
+            strip_width,
+            elapsed_secs: self.started.elapsed().as_secs_f64(),
+        });
+    }
+}
+
+impl SolutionListener for LayoutSvgListener<'_> {
+    fn report(&mut self, report_type: ReportType, solution: &SPSolution, instance: &SPInstance) {
+        self.record(&report_type, solution, instance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_sends_improvements_once_per_interval() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut throttle = LayoutThrottle::new(Duration::from_secs(1));
+
+        assert!(throttle.admit(1000.0, at(0)));
+        // Narrower, but too soon
+        assert!(!throttle.admit(900.0, at(400)));
+        // Due, but no narrower than the last SVG sent
+        assert!(!throttle.admit(1000.0, at(1200)));
+        // The held back improvement goes out with the next report
+        assert!(throttle.admit(900.0, at(1300)));
+        assert!(!throttle.admit(899.9995, at(2500)));
+        assert!(throttle.admit(850.0, at(2500)));
+    }
+
+    #[test]
+    fn test_run_streams_layouts() {
+        use super::super::{run_nesting_core_with_preview, NativeTerminator, NestingInput};
+
+        let mut layouts = Vec::new();
+        let mut on_layout_svg = |layout: LayoutSvg| layouts.push(layout);
+        let (output, _) = run_nesting_core_with_preview(
+            NestingInput {
+                json_input: include_str!("../../test_instances/thin_frame.json").to_string(),
+                time_limit: Some(3),
+                seed: Some(7),
+                ..Default::default()
+            },
+            NativeTerminator::new(),
+            None,
+            None,
+            Some(&mut on_layout_svg),
+        )
+        .unwrap();
+
+        assert!(!layouts.is_empty());
+        for pair in layouts.windows(2) {
+            assert!(pair[1].strip_width < pair[0].strip_width);
+            assert!(pair[1].elapsed_secs - pair[0].elapsed_secs >= 0.99);
+        }
+        assert!(layouts.iter().all(|layout| layout.svg.contains("<svg")));
+        assert!(layouts.last().unwrap().strip_width >= output.strip_width - WIDTH_EPSILON);
+    }
+}
@@ -0,0 +1,301 @@
+//! Parts already on the sheet (pre-placed, locked)
+//!
+//! When part of a sheet has been cut, new parts are nested around the
+//! ones still lying there. An item with a `fixed_position` has one copy
+//! placed at that position and rotation, the optimizer nests the rest of
+//! its demand with the other items. Sparrow cannot hold parts in place,
+//! so like defect zones the locked parts are enforced on the final
+//! layout: placements reaching into one are removed, and the locked
+//! parts are added to the layout flagged `locked`.
+//!
+//! Fixed positions are checked before the run, a locked part has to lie
+//! on the strip and must not overlap another locked part.
+
+use super::geometry::{
+    bounding_box, offset_ring, open_ring, rotate_polygon, translate_polygon, Polygon,
+};
+use super::holes::svg_path;
+use super::instance::InstanceGeometry;
+use super::remnant::reaches_into;
+use super::serializer::{NestingOutput, PlacedItem};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Distance a locked part may stick out of the strip, absorbing rounding
+/// of positions flush with its edge (mm)
+const STRIP_TOLERANCE: f64 = 0.01;
+
+/// Where a copy of an item already lies, in strip coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FixedPosition {
+    /// Translation of the item's contour after the rotation
+    pub x: f64,
+    pub y: f64,
+    /// Rotation of the item's contour in degrees (default: 0)
+    #[serde(default)]
+    pub rotation: f64,
+}
+
+/// Copy of an item held at its fixed position
+#[derive(Debug, Clone)]
+pub struct LockedPart {
+    pub item_id: usize,
+    pub position: FixedPosition,
+    /// Outer contour in strip coordinates
+    pub outline: Polygon,
+    pub net_area: f64,
+}
+
+/// Locked parts of an instance, in item order
+pub fn plan_locked(instance: &InstanceGeometry) -> Vec<LockedPart> {
+    instance
+        .items
+        .iter()
+        .filter_map(|item| {
+            let position = item.fixed_position?;
+            let rotated = rotate_polygon(open_ring(&item.outer), position.rotation);
+            Some(LockedPart {
+                item_id: item.id as usize,
+                position,
+                outline: translate_polygon(&rotated, position.x, position.y),
+                net_area: item.net_area(),
+            })
+        })
+        .collect()
+}
+
+/// Problems of the fixed positions, empty when they are feasible
+///
+/// # Arguments
+/// * `strip_width` - Sheet length along X, None for a growing strip
+/// * `strip_height` - Height of the area parts are nested into
+pub fn check_fixed_positions(
+    instance: &InstanceGeometry,
+    strip_width: Option<f64>,
+    strip_height: f64,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    let without_demand: Vec<String> = instance
+        .items
+        .iter()
+        .filter(|item| item.fixed_position.is_some() && item.demand == 0)
+        .map(|item| item.id.to_string())
+        .collect();
+    if !without_demand.is_empty() {
+        problems.push(format!(
+            "item(s) {} have a fixed position but no demand",
+            without_demand.join(", ")
+        ));
+    }
+
+    let locked = plan_locked(instance);
+    let max_x = strip_width.unwrap_or(f64::INFINITY) + STRIP_TOLERANCE;
+    let max_y = strip_height + STRIP_TOLERANCE;
+    let off_strip: Vec<String> = locked
+        .iter()
+        .filter(|part| {
+            bounding_box(&part.outline).is_none_or(|(min_x, min_y, part_max_x, part_max_y)| {
+                min_x < -STRIP_TOLERANCE
+                    || min_y < -STRIP_TOLERANCE
+                    || part_max_x > max_x
+                    || part_max_y > max_y
+            })
+        })
+        .map(|part| part.item_id.to_string())
+        .collect();
+    if !off_strip.is_empty() {
+        problems.push(format!(
+            "item(s) {} lie outside the strip",
+            off_strip.join(", ")
+        ));
+    }
+
+    for (i, a) in locked.iter().enumerate() {
+        for b in &locked[i + 1..] {
+            if reaches_into(&a.outline, &b.outline) {
+                problems.push(format!("items {} and {} overlap", a.item_id, b.item_id));
+            }
+        }
+    }
+    problems
+}
+
+/// Instance JSON with the locked copies taken out of the demand
+pub fn release_locked(json_str: &str) -> Result<String, String> {
+    let mut value: Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(Value::as_array_mut)
+        .ok_or("Instance JSON has no items array")?;
+    for item in items {
+        let Some(fields) = item.as_object_mut() else {
+            continue;
+        };
+        if fields.remove("fixed_position").is_none() {
+            continue;
+        }
+        let demand = fields.get("demand").and_then(Value::as_u64).unwrap_or(0);
+        fields.insert("demand".to_string(), demand.saturating_sub(1).into());
+    }
+    serde_json::to_string(&value).map_err(|e| format!("Failed to serialize instance: {}", e))
+}
+
+/// Locked parts grown by the separation, zones no nested part may reach
+/// into
+pub fn clearance_zones(locked: &[LockedPart], separation: f64) -> Vec<Polygon> {
+    locked
+        .iter()
+        .map(|part| offset_ring(&part.outline, separation))
+        .collect()
+}
+
+/// Add the locked parts to the layout
+///
+/// The strip is extended to the rightmost locked part; the utilization
+/// counts the locked parts like placed ones.
+pub fn attach_locked(output: &mut NestingOutput, locked: &[LockedPart]) {
+    if locked.is_empty() {
+        return;
+    }
+
+    let strip_width = locked
+        .iter()
+        .filter_map(|part| bounding_box(&part.outline))
+        .map(|(_, _, max_x, _)| max_x)
+        .fold(output.strip_width, f64::max);
+    let old_area = output.strip_width * output.strip_height;
+    let new_area = strip_width * output.strip_height;
+    if new_area > 0.0 {
+        let locked_area: f64 = locked.iter().map(|part| part.net_area).sum();
+        output.utilization = (output.utilization * old_area + locked_area) / new_area;
+    }
+    output.strip_width = strip_width;
+
+    for part in locked {
+        output.layouts.push(PlacedItem {
+            item_id: part.item_id,
+            rotation_degrees: part.position.rotation,
+            position_x: part.position.x,
+            position_y: part.position.y,
+            host_item_id: None,
+            outline: None,
+            display_outline: None,
+            metadata: None,
+            bend_line_angle_deg: None,
+            post_placed: false,
+            mirrored: false,
+            locked: true,
+            rotation_radians: None,
+            filler: false,
+        });
+    }
+    output.total_items_placed += locked.len();
+    output.items_requested = output
+        .items_requested
+        .map(|requested| requested + locked.len());
+}
+
+/// Render the locked parts as an SVG overlay
+pub fn render_locked_overlay(locked: &[LockedPart]) -> String {
+    let mut overlay = String::from(r#"<g id="locked_parts" pointer-events="none">"#);
+    for part in locked {
+        overlay.push_str(&format!(
+            r##"<path d="{}" fill="#9e9e9e" fill-opacity="0.8" stroke="black" stroke-width="0.5"/>"##,
+            svg_path(&part.outline)
+        ));
+    }
+    overlay.push_str("</g>");
+    overlay
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::instance::parse_instance;
+    use super::super::{run_nesting_engine, NestingInput};
+    use super::*;
+
+    /// Two 40 x 40 plates and a 20 x 20 part locked at each of `fixed`
+    fn instance(fixed: &[Value]) -> String {
+        let mut items = vec![serde_json::json!({
+            "id": 0,
+            "demand": 2,
+            "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 40.0, "height": 40.0 } }
+        })];
+        for (i, position) in fixed.iter().enumerate() {
+            items.push(serde_json::json!({
+                "id": i + 1,
+                "demand": 1,
+                "fixed_position": position,
+                "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 20.0, "height": 20.0 } }
+            }));
+        }
+        serde_json::json!({ "name": "locked", "strip_height": 50.0, "items": items }).to_string()
+    }
+
+    #[test]
+    fn test_infeasible_positions_are_listed_by_item() {
+        let json = instance(&[
+            serde_json::json!({ "x": 0.0, "y": 40.0 }),
+            serde_json::json!({ "x": 100.0, "y": 0.0 }),
+            serde_json::json!({ "x": 110.0, "y": 10.0 }),
+            // Rotated about the origin onto x -20..0
+            serde_json::json!({ "x": 200.0, "y": 0.0, "rotation": 90.0 }),
+        ]);
+        let problems = check_fixed_positions(&parse_instance(&json).unwrap(), None, 50.0);
+        assert_eq!(
+            problems,
+            vec![
+                "item(s) 1 lie outside the strip".to_string(),
+                "items 2 and 3 overlap".to_string()
+            ]
+        );
+
+        let error = run_nesting_engine(NestingInput {
+            json_input: json,
+            time_limit: Some(1),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(error.contains("items 2 and 3 overlap"), "{}", error);
+    }
+
+    #[test]
+    fn test_release_takes_the_locked_copy_out() {
+        let json = instance(&[serde_json::json!({ "x": 0.0, "y": 0.0 })]);
+        let released: Value = serde_json::from_str(&release_locked(&json).unwrap()).unwrap();
+        assert_eq!(released["items"][0]["demand"], 2);
+        assert_eq!(released["items"][1]["demand"], 0);
+        assert!(released["items"][1].get("fixed_position").is_none());
+    }
+
+    #[test]
+    fn test_run_nests_around_locked_parts() {
+        let output = run_nesting_engine(NestingInput {
+            json_input: instance(&[serde_json::json!({ "x": 0.0, "y": 0.0 })]),
+            time_limit: Some(2),
+            seed: Some(7),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let locked: Vec<&PlacedItem> = output.layouts.iter().filter(|p| p.locked).collect();
+        assert_eq!(locked.len(), 1);
+        assert_eq!(locked[0].item_id, 1);
+        assert_eq!((locked[0].position_x, locked[0].position_y), (0.0, 0.0));
+        assert_eq!(output.total_items_placed, output.layouts.len());
+        // Nothing nested reaches into the locked part
+        let square = [(0.0, 0.0), (20.0, 0.0), (20.0, 20.0), (0.0, 20.0)];
+        for placed in output.layouts.iter().filter(|p| !p.locked) {
+            let plate = translate_polygon(
+                &rotate_polygon(
+                    &[(0.0, 0.0), (40.0, 0.0), (40.0, 40.0), (0.0, 40.0)],
+                    placed.rotation_degrees,
+                ),
+                placed.position_x,
+                placed.position_y,
+            );
+            assert!(!reaches_into(&plate, &square));
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Process-wide logging with runtime reconfiguration
+//!
+//! The `log` crate only accepts one logger per process, so the first
+//! caller used to fix level and sinks forever. Instead a single
+//! dispatcher is installed once and its sinks are swapped on every
+//! `set_log_config` call, so the app and the CLI can change the level
+//! or add a log file at any time.
+
+use log::{LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Deserializer};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+
+/// Requested logging setup
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogConfig {
+    /// Maximum level: "off", "error", "warn", "info", "debug" or "trace"
+    #[serde(deserialize_with = "deserialize_level")]
+    pub level: LevelFilter,
+    /// Write records to stdout
+    #[serde(default = "default_stdout")]
+    pub stdout: bool,
+    /// Append records to this file
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: LevelFilter::Info,
+            stdout: true,
+            file: None,
+        }
+    }
+}
+
+fn default_stdout() -> bool {
+    true
+}
+
+fn deserialize_level<'de, D: Deserializer<'de>>(deserializer: D) -> Result<LevelFilter, D::Error> {
+    let level = String::deserialize(deserializer)?;
+    LevelFilter::from_str(&level).map_err(serde::de::Error::custom)
+}
+
+/// Active sinks, replaced as a whole on reconfiguration
+struct Sinks {
+    level: LevelFilter,
+    stdout: bool,
+    file: Option<Mutex<File>>,
+    path: Option<PathBuf>,
+}
+
+impl Sinks {
+    fn open(config: &LogConfig) -> Result<Self, String> {
+        let file = match &config.file {
+            Some(path) => Some(Mutex::new(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| format!("Failed to open log file '{}': {}", path.display(), e))?,
+            )),
+            None => None,
+        };
+
+        Ok(Self {
+            level: config.level,
+            stdout: config.stdout,
+            file,
+            path: config.file.clone(),
+        })
+    }
+}
+
+struct Dispatcher {
+    sinks: RwLock<Arc<Sinks>>,
+}
+
+impl Dispatcher {
+    fn current(&self) -> Arc<Sinks> {
+        self.sinks
+            .read()
+            .map(|sinks| sinks.clone())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().clone())
+    }
+}
+
+impl Log for Dispatcher {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.current().level
+    }
+
+    fn log(&self, record: &Record) {
+        // One snapshot per record, a concurrent swap only affects later records
+        let sinks = self.current();
+        if record.level() > sinks.level {
+            return;
+        }
+
+        let line = format!(
+            "[{}][{}] {}",
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        if sinks.stdout {
+            println!("{}", line);
+        }
+        if let Some(file) = &sinks.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        let sinks = self.current();
+        if let Some(file) = &sinks.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+static DISPATCHER: OnceLock<Dispatcher> = OnceLock::new();
+
+/// Install the dispatcher with the default config (Info, stdout)
+///
+/// Safe to call any number of times; an existing configuration is
+/// kept. Fails only if a different logger was installed first.
+pub fn init_logging() -> Result<(), String> {
+    let mut created = false;
+    let dispatcher = DISPATCHER.get_or_init(|| {
+        created = true;
+        Dispatcher {
+            sinks: RwLock::new(Arc::new(Sinks {
+                level: LevelFilter::Info,
+                stdout: true,
+                file: None,
+                path: None,
+            })),
+        }
+    });
+
+    if created {
+        log::set_logger(dispatcher)
+            .map_err(|e| format!("Another logger is already installed: {}", e))?;
+        log::set_max_level(LevelFilter::Info);
+    }
+    Ok(())
+}
+
+/// Change level and sinks of the process logger
+///
+/// Installs the dispatcher if needed. Takes effect for all records
+/// logged after the call returns.
+pub fn set_log_config(config: &LogConfig) -> Result<(), String> {
+    init_logging()?;
+    let sinks = Arc::new(Sinks::open(config)?);
+
+    let dispatcher = DISPATCHER.get().ok_or("Logger is not initialized")?;
+    match dispatcher.sinks.write() {
+        Ok(mut current) => *current = sinks,
+        Err(poisoned) => *poisoned.into_inner() = sinks,
+    }
+    log::set_max_level(config.level);
+    Ok(())
+}
+
+/// File records are currently appended to, if any
+pub fn log_file() -> Option<PathBuf> {
+    DISPATCHER.get()?.current().path.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_change_affects_subsequent_records() {
+        let path = std::env::temp_dir().join(format!("nesting_log_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let config = LogConfig {
+            level: LevelFilter::Info,
+            stdout: false,
+            file: Some(path.clone()),
+        };
+        set_log_config(&config).unwrap();
+        log::debug!("hidden debug record");
+        log::info!("visible info record");
+
+        set_log_config(&LogConfig {
+            level: LevelFilter::Debug,
+            ..config.clone()
+        })
+        .unwrap();
+        log::debug!("visible debug record");
+
+        set_log_config(&LogConfig {
+            level: LevelFilter::Warn,
+            ..config
+        })
+        .unwrap();
+        log::info!("hidden info record");
+        log::logger().flush();
+
+        assert_eq!(log_file(), Some(path.clone()));
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(contents.contains("visible info record"));
+        assert!(contents.contains("visible debug record"));
+        assert!(!contents.contains("hidden debug record"));
+        assert!(!contents.contains("hidden info record"));
+
+        // Re-initializing keeps the active configuration
+        init_logging().unwrap();
+        assert_eq!(log::max_level(), LevelFilter::Warn);
+    }
+
+    #[test]
+    fn test_log_config_from_json() {
+        let config: LogConfig = serde_json::from_str(r#"{"level": "debug"}"#).unwrap();
+        assert_eq!(config.level, LevelFilter::Debug);
+        assert!(config.stdout);
+        assert!(config.file.is_none());
+
+        assert!(serde_json::from_str::<LogConfig>(r#"{"level": "loud"}"#).is_err());
+    }
+}
@@ -0,0 +1,381 @@
+//! Minimum web (bridge) width check
+//!
+//! A hole too close to the outer contour, or to another hole, leaves a
+//! web of material that breaks or warps during cutting. The check
+//! measures the smallest gap between every hole and the outer contour
+//! and between every pair of holes of a part.
+//!
+//! Gaps are computed segment to segment. Each contour gets a uniform
+//! grid of its segments, so a segment of the other contour is only
+//! compared with the segments near it and big parts stay fast.
+
+use super::geometry::{bounding_box, open_ring};
+use super::instance::{InstanceGeometry, InstanceItem};
+use serde::{Deserialize, Serialize};
+
+/// Web narrower than the minimum
+///
+/// Contour indices: 0 is the outer contour, `i + 1` the hole `i`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinWebViolation {
+    pub item_id: u64,
+    pub label: String,
+    pub contour_a: usize,
+    pub contour_b: usize,
+    /// Measured web width (mm), zero when the contours touch
+    pub distance: f64,
+    /// Midpoint of the narrowest spot, in part coordinates
+    pub location: (f64, f64),
+}
+
+/// Result of `check_min_web`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MinWebReport {
+    pub min_web: f64,
+    pub items_checked: usize,
+    pub violations: Vec<MinWebViolation>,
+}
+
+impl MinWebReport {
+    /// Error message listing the violations (used in strict mode)
+    pub fn error_message(&self) -> String {
+        let details: Vec<String> = self
+            .violations
+            .iter()
+            .map(|v| {
+                format!(
+                    "{} contours {}/{}: {:.2}mm",
+                    v.label, v.contour_a, v.contour_b, v.distance
+                )
+            })
+            .collect();
+        format!(
+            "{} webs narrower than {}mm: {}",
+            self.violations.len(),
+            self.min_web,
+            details.join(", ")
+        )
+    }
+}
+
+/// Check all items of an instance for webs narrower than `min_web` (mm)
+pub fn check_min_web(instance: &InstanceGeometry, min_web: f64) -> Result<MinWebReport, String> {
+    if !min_web.is_finite() || min_web < 0.0 {
+        return Err(format!(
+            "Minimum web must be a non-negative number, got {}",
+            min_web
+        ));
+    }
+
+    let violations = instance
+        .items
+        .iter()
+        .flat_map(|item| item_violations(item, min_web))
+        .collect();
+
+    Ok(MinWebReport {
+        min_web,
+        items_checked: instance.items.len(),
+        violations,
+    })
+}
+
+fn item_violations(item: &InstanceItem, min_web: f64) -> Vec<MinWebViolation> {
+    let contours: Vec<&[(f64, f64)]> = std::iter::once(&item.outer)
+        .chain(&item.holes)
+        .map(|contour| open_ring(contour))
+        .filter(|contour| contour.len() >= 2)
+        .collect();
+    let grids: Vec<SegmentGrid> = contours
+        .iter()
+        .map(|contour| SegmentGrid::new(contour, min_web))
+        .collect();
+
+    let mut violations = Vec::new();
+    for (a, grid) in grids.iter().enumerate() {
+        for (b, contour) in contours.iter().enumerate().skip(a + 1) {
+            if let Some((distance, location)) = narrowest_gap(grid, contour, min_web) {
+                violations.push(MinWebViolation {
+                    item_id: item.id,
+                    label: item.display_name(),
+                    contour_a: a,
+                    contour_b: b,
+                    distance,
+                    location,
+                });
+            }
+        }
+    }
+    violations
+}
+
+/// Smallest gap between two contours if it is below `min_web`
+fn narrowest_gap(
+    grid: &SegmentGrid,
+    other: &[(f64, f64)],
+    min_web: f64,
+) -> Option<(f64, (f64, f64))> {
+    let other_box = bounding_box(other)?;
+    if box_gap(grid.bounds, other_box) >= min_web {
+        return None;
+    }
+
+    let mut best: Option<(f64, (f64, f64))> = None;
+    let mut candidates = Vec::new();
+    for j in 0..other.len() {
+        let b1 = other[j];
+        let b2 = other[(j + 1) % other.len()];
+        grid.candidates(b1, b2, min_web, &mut candidates);
+
+        for &i in &candidates {
+            let (a1, a2) = grid.segment(i);
+            let (distance, p, q) = closest_points(a1, a2, b1, b2);
+            if distance < min_web && best.is_none_or(|(d, _)| distance < d) {
+                best = Some((distance, ((p.0 + q.0) / 2.0, (p.1 + q.1) / 2.0)));
+            }
+        }
+    }
+    best
+}
+
+/// Distance between two axis-aligned boxes (zero when they overlap)
+fn box_gap(a: (f64, f64, f64, f64), b: (f64, f64, f64, f64)) -> f64 {
+    let dx = (a.0 - b.2).max(b.0 - a.2).max(0.0);
+    let dy = (a.1 - b.3).max(b.1 - a.3).max(0.0);
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Segments of a closed contour bucketed into a uniform grid
+struct SegmentGrid<'a> {
+    ring: &'a [(f64, f64)],
+    bounds: (f64, f64, f64, f64),
+    cell: f64,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl<'a> SegmentGrid<'a> {
+    fn new(ring: &'a [(f64, f64)], min_web: f64) -> Self {
+        let bounds = bounding_box(ring).unwrap_or((0.0, 0.0, 0.0, 0.0));
+        let (width, height) = (bounds.2 - bounds.0, bounds.3 - bounds.1);
+
+        // About one segment per cell, never finer than the search radius
+        let cell = ((width * height / ring.len() as f64).sqrt())
+            .max(width.max(height) / ring.len() as f64)
+            .max(min_web)
+            .max(1e-6);
+        let cols = (width / cell) as usize + 1;
+        let rows = (height / cell) as usize + 1;
+
+        let mut grid = Self {
+            ring,
+            bounds,
+            cell,
+            cols,
+            rows,
+            cells: vec![Vec::new(); cols * rows],
+        };
+        for i in 0..ring.len() {
+            let (a, b) = grid.segment(i);
+            let (c0, r0, c1, r1) = grid.cell_range(a, b, 0.0);
+            for row in r0..=r1 {
+                for col in c0..=c1 {
+                    grid.cells[row * cols + col].push(i);
+                }
+            }
+        }
+        grid
+    }
+
+    fn segment(&self, i: usize) -> ((f64, f64), (f64, f64)) {
+        (self.ring[i], self.ring[(i + 1) % self.ring.len()])
+    }
+
+    /// Cells overlapped by the segment's bounding box grown by `margin`
+    fn cell_range(
+        &self,
+        a: (f64, f64),
+        b: (f64, f64),
+        margin: f64,
+    ) -> (usize, usize, usize, usize) {
+        let col = |x: f64| {
+            (((x - self.bounds.0) / self.cell).floor().max(0.0) as usize).min(self.cols - 1)
+        };
+        let row = |y: f64| {
+            (((y - self.bounds.1) / self.cell).floor().max(0.0) as usize).min(self.rows - 1)
+        };
+        (
+            col(a.0.min(b.0) - margin),
+            row(a.1.min(b.1) - margin),
+            col(a.0.max(b.0) + margin),
+            row(a.1.max(b.1) + margin),
+        )
+    }
+
+    /// Segments that may lie within `margin` of segment `a-b`
+    fn candidates(&self, a: (f64, f64), b: (f64, f64), margin: f64, out: &mut Vec<usize>) {
+        out.clear();
+        let (c0, r0, c1, r1) = self.cell_range(a, b, margin);
+        for row in r0..=r1 {
+            for col in c0..=c1 {
+                out.extend_from_slice(&self.cells[row * self.cols + col]);
+            }
+        }
+        out.sort_unstable();
+        out.dedup();
+    }
+}
+
+/// Distance and closest points of segments `a1-a2` and `b1-b2`
+///
+/// Intersecting segments return distance zero at the intersection.
+fn closest_points(
+    a1: (f64, f64),
+    a2: (f64, f64),
+    b1: (f64, f64),
+    b2: (f64, f64),
+) -> (f64, (f64, f64), (f64, f64)) {
+    let (rx, ry) = (a2.0 - a1.0, a2.1 - a1.1);
+    let (sx, sy) = (b2.0 - b1.0, b2.1 - b1.1);
+    let denom = rx * sy - ry * sx;
+    if denom != 0.0 {
+        let t = ((b1.0 - a1.0) * sy - (b1.1 - a1.1) * sx) / denom;
+        let u = ((b1.0 - a1.0) * ry - (b1.1 - a1.1) * rx) / denom;
+        if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+            let p = (a1.0 + t * rx, a1.1 + t * ry);
+            return (0.0, p, p);
+        }
+    }
+
+    [
+        (a1, project(a1, b1, b2), false),
+        (a2, project(a2, b1, b2), false),
+        (b1, project(b1, a1, a2), true),
+        (b2, project(b2, a1, a2), true),
+    ]
+    .into_iter()
+    .map(|(p, q, swapped)| {
+        let distance = ((p.0 - q.0).powi(2) + (p.1 - q.1).powi(2)).sqrt();
+        if swapped {
+            (distance, q, p)
+        } else {
+            (distance, p, q)
+        }
+    })
+    .min_by(|x, y| x.0.total_cmp(&y.0))
+    .unwrap_or((f64::INFINITY, a1, b1))
+}
+
+/// Closest point to `p` on segment `s1-s2`
+fn project(p: (f64, f64), s1: (f64, f64), s2: (f64, f64)) -> (f64, f64) {
+    let (dx, dy) = (s2.0 - s1.0, s2.1 - s1.1);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > 0.0 {
+        (((p.0 - s1.0) * dx + (p.1 - s1.1) * dy) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (s1.0 + t * dx, s1.1 + t * dy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::geometry::Polygon;
+    use crate::nesting_engine::parse_instance;
+
+    /// Brute force reference for the grid search
+    fn brute_force_gap(a: &Polygon, b: &Polygon) -> f64 {
+        let mut best = f64::INFINITY;
+        for i in 0..a.len() {
+            for j in 0..b.len() {
+                let (distance, _, _) =
+                    closest_points(a[i], a[(i + 1) % a.len()], b[j], b[(j + 1) % b.len()]);
+                best = best.min(distance);
+            }
+        }
+        best
+    }
+
+    fn circle(cx: f64, cy: f64, r: f64, n: usize) -> Polygon {
+        (0..n)
+            .map(|i| {
+                let angle = i as f64 / n as f64 * std::f64::consts::TAU;
+                (cx + r * angle.cos(), cy + r * angle.sin())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_hole_close_to_outer_edge() {
+        // 100 x 50 plate, hole 0.5mm from the right edge, second hole well inside
+        let instance = parse_instance(
+            r#"{"name": "web", "strip_height": 500, "items": [{
+                "id": 3, "demand": 1, "dxf": "bracket.dxf",
+                "shape": {"type": "polygon", "data": {
+                    "outer": [[0, 0], [100, 0], [100, 50], [0, 50]],
+                    "inner": [
+                        [[89.5, 20], [99.5, 20], [99.5, 30], [89.5, 30]],
+                        [[10, 10], [20, 10], [20, 20], [10, 20]]
+                    ]
+                }}
+            }]}"#,
+        )
+        .unwrap();
+
+        let report = check_min_web(&instance, 2.0).unwrap();
+        assert_eq!(report.violations.len(), 1);
+        let violation = &report.violations[0];
+        assert_eq!(violation.item_id, 3);
+        assert_eq!(violation.label, "bracket.dxf");
+        assert_eq!((violation.contour_a, violation.contour_b), (0, 1));
+        assert!((violation.distance - 0.5).abs() < 1e-9);
+        assert!((violation.location.0 - 99.75).abs() < 1e-9);
+        assert!((20.0..=30.0).contains(&violation.location.1));
+
+        // Below the actual gap nothing is reported
+        assert!(check_min_web(&instance, 0.4).unwrap().violations.is_empty());
+        assert!(check_min_web(&instance, -1.0).is_err());
+    }
+
+    #[test]
+    fn test_adjacent_holes() {
+        let instance = parse_instance(
+            r#"{"name": "web", "strip_height": 500, "items": [{
+                "id": 0, "demand": 1,
+                "shape": {"type": "polygon", "data": {
+                    "outer": [[0, 0], [200, 0], [200, 100], [0, 100]],
+                    "inner": [
+                        [[50, 40], [60, 40], [60, 60], [50, 60]],
+                        [[61, 40], [71, 40], [71, 60], [61, 60]]
+                    ]
+                }}
+            }]}"#,
+        )
+        .unwrap();
+
+        let report = check_min_web(&instance, 3.0).unwrap();
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(
+            (
+                report.violations[0].contour_a,
+                report.violations[0].contour_b
+            ),
+            (1, 2)
+        );
+        assert!((report.violations[0].distance - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_grid_matches_brute_force() {
+        // Finely tessellated outer circle with an off-center hole
+        let outer = circle(0.0, 0.0, 500.0, 2000);
+        let hole = circle(420.0, 10.0, 77.0, 600);
+
+        let grid = SegmentGrid::new(&outer, 5.0);
+        let (distance, _) = narrowest_gap(&grid, &hole, 5.0).unwrap();
+        assert!((distance - brute_force_gap(&outer, &hole)).abs() < 1e-9);
+        assert!(distance < 5.0);
+    }
+}
@@ -0,0 +1,746 @@
+//! Mirrored duplicates (left and right hand versions of a part)
+//!
+//! Customers often send the LH and RH version of a part as two files,
+//! although one is just the other mirrored. `detect_mirror_pairs` finds
+//! such items: mirrored across the Y axis and turned by a multiple of
+//! 90°, the contours of one match the other's within a tolerance. Plain
+//! duplicates (and symmetric parts) match without mirroring and are not
+//! reported.
+//!
+//! Where the machine may cut mirrored parts (`allow_mirror`) and the user
+//! confirmed (`consolidate_mirror_pairs`), each pair is nested as one
+//! item with the combined demand, which interlocks better. Afterwards the
+//! copies beyond the kept item's demand are handed back to the mirror
+//! item, flagged `mirrored`, so labels, metadata and per-quote
+//! attribution stay with the original items. The merged item keeps the
+//! orientations and settings of the kept item.
+//!
+//! With `allow_mirror` single items may also be flipped (per item
+//! `item_allow_mirror`). The optimizer only rotates, so such an item is
+//! nested as itself and a mirror image variant sharing its demand. A
+//! short probe run with the full demand on both picks the split: the
+//! copies it packs nearest the strip start are kept. The variant's
+//! placements are handed back flagged `mirrored`. Mirror symmetric parts
+//! are left as they are, a mirrored copy gives them nothing new.
+
+use super::geometry::{
+    bounding_box, mirror_polygon, open_ring, rotate_polygon, translate_polygon, Polygon,
+};
+use super::instance::{parse_instance, InstanceGeometry, InstanceItem};
+use super::serializer::{NestingOutput, PlacedItem};
+use serde::{Deserialize, Serialize};
+
+/// Largest vertex displacement between mirror images (mm)
+pub const DEFAULT_MIRROR_TOLERANCE: f64 = 0.05;
+
+/// Share of the time limit spent on the probe run splitting the demand
+/// between items and their mirror image variants
+pub const MIRROR_PROBE_SHARE: f64 = 0.1;
+
+/// Turns tried after mirroring (degrees)
+const ROTATIONS: [f64; 4] = [0.0, 90.0, 180.0, 270.0];
+
+/// Two items of which one is the mirror image of the other
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MirrorPair {
+    /// Item nested for both when the pair is consolidated
+    pub item_id: usize,
+    pub label: String,
+    pub demand: u64,
+    /// Item whose contour is the mirror image of `item_id`'s
+    pub mirror_item_id: usize,
+    pub mirror_label: String,
+    pub mirror_demand: u64,
+    /// The mirror item's contour is the item's contour mirrored across the
+    /// Y axis, turned by `rotation_degrees` and moved by `offset`
+    pub rotation_degrees: f64,
+    pub offset: (f64, f64),
+}
+
+/// Instance with its mirror pairs merged
+#[derive(Debug, Clone)]
+pub struct MirrorMerge {
+    /// Instance JSON where every kept item carries the combined demand and
+    /// every mirror item a demand of 0
+    pub json: String,
+    /// Pairs with the demands of the merged instance
+    pub pairs: Vec<MirrorPair>,
+}
+
+/// Instance with mirror image variants added
+#[derive(Debug, Clone)]
+pub struct MirrorVariants {
+    /// Instance JSON with a variant item after the existing ones for every
+    /// split item; both carry the item's full demand until
+    /// `choose_mirrored_copies` splits it
+    pub json: String,
+    /// (variant item ID, original item ID)
+    pub variants: Vec<(usize, usize)>,
+}
+
+/// Find items that are the mirror image of another item
+///
+/// Every item is part of one pair at most; the earlier item of a pair is
+/// the one kept when consolidating.
+pub fn detect_mirror_pairs(instance: &InstanceGeometry, tolerance: f64) -> Vec<MirrorPair> {
+    let rings: Vec<Vec<Polygon>> = instance.items.iter().map(item_rings).collect();
+    let mut paired = vec![false; instance.items.len()];
+    let mut pairs = Vec::new();
+
+    for i in 0..instance.items.len() {
+        for j in i + 1..instance.items.len() {
+            if paired[i] || paired[j] || !same_vertex_counts(&rings[i], &rings[j]) {
+                continue;
+            }
+            let transform = |mirror: bool| {
+                ROTATIONS.iter().find_map(|&rotation| {
+                    matching_offset(&turned(&rings[i], mirror, rotation), &rings[j], tolerance)
+                        .map(|offset| (rotation, offset))
+                })
+            };
+            // Plain duplicates and symmetric parts need no mirroring
+            if transform(false).is_some() {
+                continue;
+            }
+            let Some((rotation_degrees, offset)) = transform(true) else {
+                continue;
+            };
+
+            let (item, mirror) = (&instance.items[i], &instance.items[j]);
+            pairs.push(MirrorPair {
+                item_id: item.id as usize,
+                label: item.display_name(),
+                demand: item.demand,
+                mirror_item_id: mirror.id as usize,
+                mirror_label: mirror.display_name(),
+                mirror_demand: mirror.demand,
+                rotation_degrees,
+                offset,
+            });
+            paired[i] = true;
+            paired[j] = true;
+        }
+    }
+
+    if !pairs.is_empty() {
+        println!("🪞 Found {} mirrored item pairs", pairs.len());
+    }
+    pairs
+}
+
+/// Merge every pair into its kept item
+///
+/// Demands are taken from `json_str`, which may differ from the instance
+/// the pairs were detected in (e.g. after demand chunking).
+pub fn consolidate_mirror_pairs(
+    json_str: &str,
+    pairs: &[MirrorPair],
+) -> Result<MirrorMerge, String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+        .ok_or("Instance JSON has no items array")?;
+    let demand_of = |items: &[serde_json::Value], id: usize| {
+        items
+            .iter()
+            .find(|item| item.get("id").and_then(|id| id.as_u64()) == Some(id as u64))
+            .and_then(|item| item.get("demand").and_then(|demand| demand.as_u64()))
+    };
+
+    let mut merged = Vec::with_capacity(pairs.len());
+    for pair in pairs {
+        let (Some(demand), Some(mirror_demand)) = (
+            demand_of(items, pair.item_id),
+            demand_of(items, pair.mirror_item_id),
+        ) else {
+            return Err(format!(
+                "Mirror pair {} / {} is not part of the instance",
+                pair.label, pair.mirror_label
+            ));
+        };
+        for item in items.iter_mut().filter_map(|item| item.as_object_mut()) {
+            let id = item.get("id").and_then(|id| id.as_u64()).unwrap_or(0) as usize;
+            if id == pair.item_id {
+                item.insert("demand".to_string(), (demand + mirror_demand).into());
+            } else if id == pair.mirror_item_id {
+                item.insert("demand".to_string(), 0.into());
+            }
+        }
+        merged.push(MirrorPair {
+            demand,
+            mirror_demand,
+            ..pair.clone()
+        });
+    }
+
+    println!(
+        "🪞 {} mirror pairs nested as one item each ({} mirrored copies)",
+        merged.len(),
+        merged.iter().map(|pair| pair.mirror_demand).sum::<u64>()
+    );
+    let json = serde_json::to_string(&value)
+        .map_err(|e| format!("Failed to serialize instance: {}", e))?;
+    Ok(MirrorMerge {
+        json,
+        pairs: merged,
+    })
+}
+
+/// Hand the copies beyond each kept item's demand back to its mirror item
+///
+/// The placements keep their position on the strip and are expressed in
+/// the mirror item's coordinates, flagged `mirrored`. When copies are
+/// missing, the kept item is served first.
+pub fn restore_mirrored_placements(output: &mut NestingOutput, pairs: &[MirrorPair]) {
+    for pair in pairs {
+        let mut kept = 0;
+        let mut mirrored = 0;
+        for placed in output
+            .layouts
+            .iter_mut()
+            .filter(|placed| placed.item_id == pair.item_id)
+        {
+            if kept < pair.demand {
+                kept += 1;
+                continue;
+            }
+            if mirrored == pair.mirror_demand {
+                break;
+            }
+            // Placed contour: R(r)·item + p, with mirror = R(θ)·M·item + offset
+            // this is R(r + θ)·M·mirror + p - R(r + θ)·M·offset
+            let rotation = (placed.rotation_degrees + pair.rotation_degrees).rem_euclid(360.0);
+            let (dx, dy) = rotate_polygon(&[(-pair.offset.0, pair.offset.1)], rotation)[0];
+            placed.item_id = pair.mirror_item_id;
+            placed.rotation_degrees = rotation;
+            placed.position_x -= dx;
+            placed.position_y -= dy;
+            placed.mirrored = true;
+            mirrored += 1;
+        }
+
+        output.unplaced_item_ids.retain(|&id| id != pair.item_id);
+        let missing = |demand: u64, placed: u64| demand.saturating_sub(placed) as usize;
+        output.unplaced_item_ids.extend(std::iter::repeat_n(
+            pair.item_id,
+            missing(pair.demand, kept),
+        ));
+        output.unplaced_item_ids.extend(std::iter::repeat_n(
+            pair.mirror_item_id,
+            missing(pair.mirror_demand, mirrored),
+        ));
+    }
+}
+
+/// Add a mirror image variant for every item of `item_ids` worth flipping
+///
+/// Every part is mirrored across the Y axis of its own coordinates in
+/// `json_str`. The variant gets the item's full demand, a total the two
+/// share. Returns None when no item was split.
+pub fn add_mirrored_variants(
+    json_str: &str,
+    item_ids: &[usize],
+) -> Result<Option<MirrorVariants>, String> {
+    let instance = parse_instance(json_str)?;
+    let mut value: serde_json::Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+        .ok_or("Instance JSON has no items array")?;
+    let mut next_id = items
+        .iter()
+        .filter_map(|item| item.get("id").and_then(|id| id.as_u64()))
+        .max()
+        .map_or(0, |id| id + 1);
+
+    let mut variants = Vec::new();
+    let mut added = Vec::new();
+    for item in items.iter_mut().filter_map(|item| item.as_object_mut()) {
+        let id = item.get("id").and_then(|id| id.as_u64()).unwrap_or(0);
+        let demand = item
+            .get("demand")
+            .and_then(|demand| demand.as_u64())
+            .unwrap_or(0);
+        if demand == 0 || !item_ids.contains(&(id as usize)) {
+            continue;
+        }
+        let Some(geometry) = instance.items.iter().find(|geometry| geometry.id == id) else {
+            continue;
+        };
+        let rings = item_rings(geometry);
+        let symmetric = ROTATIONS.iter().any(|&rotation| {
+            matching_offset(
+                &turned(&rings, true, rotation),
+                &rings,
+                DEFAULT_MIRROR_TOLERANCE,
+            )
+            .is_some()
+        });
+        if symmetric {
+            continue;
+        }
+
+        let mut variant = item.clone();
+        variant.insert("id".to_string(), next_id.into());
+        variant.insert("demand".to_string(), demand.into());
+        let holes: Vec<Polygon> = geometry
+            .holes
+            .iter()
+            .map(|hole| mirror_polygon(hole))
+            .collect();
+        variant.insert(
+            "shape".to_string(),
+            serde_json::json!({
+                "type": "polygon",
+                "data": { "outer": mirror_polygon(&geometry.outer), "inner": holes },
+            }),
+        );
+        if let Some(exact) = &geometry.exact_geometry {
+            let exact = serde_json::to_value(exact.mirrored())
+                .map_err(|e| format!("Failed to serialize item {}: {}", id, e))?;
+            variant.insert("exact_geometry".to_string(), exact);
+        }
+        // Mirroring across the Y axis turns a line at d° to -d°
+        if let Some(direction) = geometry.bend_direction_deg {
+            variant.insert("bend_direction_deg".to_string(), (-direction).into());
+        }
+        let bend_lines: Vec<[(f64, f64); 2]> = geometry
+            .bend_lines
+            .iter()
+            .map(|&[(x1, y1), (x2, y2)]| [(-x1, y1), (-x2, y2)])
+            .collect();
+        variant.insert("bend_lines".to_string(), serde_json::json!(bend_lines));
+
+        variants.push((next_id as usize, id as usize));
+        added.push(serde_json::Value::Object(variant));
+        next_id += 1;
+    }
+    if variants.is_empty() {
+        return Ok(None);
+    }
+    items.extend(added);
+
+    println!("🪞 Mirrored copies allowed for {} items", variants.len());
+    let json = serde_json::to_string(&value)
+        .map_err(|e| format!("Failed to serialize instance: {}", e))?;
+    Ok(Some(MirrorVariants { json, variants }))
+}
+
+/// Split the demand every item shares with its variant
+///
+/// `probe` holds the placements of a short run of `variants.json`. Of the
+/// copies of an item and its variant, the ones placed nearest the strip
+/// start are kept up to the item's demand, and the variant gets as many as
+/// are mirrored among them. Copies the probe did not place count as
+/// unmirrored.
+pub fn choose_mirrored_copies(
+    variants: &MirrorVariants,
+    probe: &[PlacedItem],
+) -> Result<MirrorVariants, String> {
+    let nested = parse_instance(&variants.json)?;
+    let mut value: serde_json::Value = serde_json::from_str(&variants.json)
+        .map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+        .ok_or("Instance JSON has no items array")?;
+
+    let mut mirrored_copies = 0;
+    for &(variant_id, item_id) in &variants.variants {
+        let demand = items
+            .iter()
+            .find(|item| item.get("id").and_then(|id| id.as_u64()) == Some(item_id as u64))
+            .and_then(|item| item.get("demand").and_then(|demand| demand.as_u64()))
+            .unwrap_or(0);
+
+        // Left edge of every copy on the strip, and whether it is mirrored
+        let mut copies: Vec<(f64, bool)> = probe
+            .iter()
+            .filter(|placed| placed.item_id == item_id || placed.item_id == variant_id)
+            .filter_map(|placed| {
+                let geometry = nested
+                    .items
+                    .iter()
+                    .find(|geometry| geometry.id as usize == placed.item_id)?;
+                let outer = rotate_polygon(&geometry.outer, placed.rotation_degrees);
+                let (min_x, ..) = bounding_box(&outer)?;
+                Some((placed.position_x + min_x, placed.item_id == variant_id))
+            })
+            .collect();
+        copies.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mirrored = copies
+            .iter()
+            .take(demand as usize)
+            .filter(|&&(_, mirrored)| mirrored)
+            .count() as u64;
+
+        for item in items.iter_mut().filter_map(|item| item.as_object_mut()) {
+            let id = item.get("id").and_then(|id| id.as_u64()).unwrap_or(0) as usize;
+            if id == item_id {
+                item.insert("demand".to_string(), (demand - mirrored).into());
+            } else if id == variant_id {
+                item.insert("demand".to_string(), mirrored.into());
+            }
+        }
+        mirrored_copies += mirrored;
+    }
+
+    println!("🪞 {} copies nested mirrored", mirrored_copies);
+    let json = serde_json::to_string(&value)
+        .map_err(|e| format!("Failed to serialize instance: {}", e))?;
+    Ok(MirrorVariants {
+        json,
+        variants: variants.variants.clone(),
+    })
+}
+
+/// Hand the placements of mirror image variants back to their items
+///
+/// The variant is the item mirrored in place, so rotation and position
+/// stay as they are.
+pub fn restore_mirrored_variants(output: &mut NestingOutput, variants: &[(usize, usize)]) {
+    let original = |id: usize| {
+        variants
+            .iter()
+            .find(|&&(variant, _)| variant == id)
+            .map(|&(_, original)| original)
+    };
+    for placed in &mut output.layouts {
+        if let Some(id) = original(placed.item_id) {
+            placed.item_id = id;
+            placed.mirrored = true;
+        }
+        placed.host_item_id = placed
+            .host_item_id
+            .map(|host| original(host).unwrap_or(host));
+    }
+    for id in &mut output.unplaced_item_ids {
+        *id = original(*id).unwrap_or(*id);
+    }
+    for unplaced in &mut output.unplaced_reasons {
+        unplaced.item_id = original(unplaced.item_id).unwrap_or(unplaced.item_id);
+    }
+    let mut seen = std::collections::HashSet::new();
+    output
+        .unplaced_reasons
+        .retain(|unplaced| seen.insert(unplaced.item_id));
+}
+
+/// Outer contour and holes as open rings
+fn item_rings(item: &InstanceItem) -> Vec<Polygon> {
+    std::iter::once(&item.outer)
+        .chain(item.holes.iter())
+        .map(|ring| open_ring(ring).to_vec())
+        .collect()
+}
+
+fn same_vertex_counts(a: &[Polygon], b: &[Polygon]) -> bool {
+    let mut counts_a: Vec<usize> = a.iter().map(Vec::len).collect();
+    let mut counts_b: Vec<usize> = b.iter().map(Vec::len).collect();
+    counts_a[1..].sort_unstable();
+    counts_b[1..].sort_unstable();
+    counts_a == counts_b
+}
+
+/// Rings mirrored across the Y axis (if asked) and turned around the origin
+fn turned(rings: &[Polygon], mirror: bool, rotation: f64) -> Vec<Polygon> {
+    rings
+        .iter()
+        .map(|ring| {
+            let ring = if mirror {
+                mirror_polygon(ring)
+            } else {
+                ring.clone()
+            };
+            rotate_polygon(&ring, rotation)
+        })
+        .collect()
+}
+
+/// Translation moving every contour of `a` onto one of `b`, if any
+///
+/// Outer contours are aligned by their bounding boxes; holes may come in
+/// any order and contours may start at any vertex or run either way.
+fn matching_offset(a: &[Polygon], b: &[Polygon], tolerance: f64) -> Option<(f64, f64)> {
+    let (a_min_x, a_min_y, a_max_x, a_max_y) = bounding_box(a.first()?)?;
+    let (b_min_x, b_min_y, b_max_x, b_max_y) = bounding_box(b.first()?)?;
+    if ((a_max_x - a_min_x) - (b_max_x - b_min_x)).abs() > 2.0 * tolerance
+        || ((a_max_y - a_min_y) - (b_max_y - b_min_y)).abs() > 2.0 * tolerance
+    {
+        return None;
+    }
+    let offset = (b_min_x - a_min_x, b_min_y - a_min_y);
+    let moved: Vec<Polygon> = a
+        .iter()
+        .map(|ring| translate_polygon(ring, offset.0, offset.1))
+        .collect();
+
+    if !same_ring(&moved[0], &b[0], tolerance) {
+        return None;
+    }
+    let mut used = vec![false; b.len()];
+    for hole in &moved[1..] {
+        let found = (1..b.len()).find(|&k| !used[k] && same_ring(hole, &b[k], tolerance))?;
+        used[found] = true;
+    }
+    Some(offset)
+}
+
+/// True when the rings have the same vertices within `tolerance`, from any
+/// start vertex and in either direction
+fn same_ring(a: &[(f64, f64)], b: &[(f64, f64)], tolerance: f64) -> bool {
+    let n = a.len();
+    if n != b.len() || n == 0 {
+        return false;
+    }
+    let close = |(xa, ya): (f64, f64), (xb, yb): (f64, f64)| {
+        (xa - xb).abs() <= tolerance && (ya - yb).abs() <= tolerance
+    };
+    (0..n).any(|start| {
+        (0..n).all(|i| close(a[i], b[(start + i) % n]))
+            || (0..n).all(|i| close(a[i], b[(start + n - i) % n]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::{run_nesting_engine, NestingInput};
+
+    /// L-shaped bracket (not symmetric), optionally mirrored and turned
+    fn bracket(mirror: bool, rotation: f64, offset: (f64, f64)) -> Polygon {
+        let outer = vec![
+            (0.0, 0.0),
+            (60.0, 0.0),
+            (60.0, 15.0),
+            (15.0, 15.0),
+            (15.0, 40.0),
+            (0.0, 40.0),
+        ];
+        let rings = turned(&[outer], mirror, rotation);
+        translate_polygon(&rings[0], offset.0, offset.1)
+    }
+
+    fn instance(items: &[(Polygon, u64, &str)]) -> String {
+        let items: Vec<serde_json::Value> = items
+            .iter()
+            .enumerate()
+            .map(|(id, (outer, demand, label))| {
+                let points: Vec<[f64; 2]> = outer.iter().map(|&(x, y)| [x, y]).collect();
+                serde_json::json!({
+                    "id": id,
+                    "demand": demand,
+                    "label": label,
+                    "metadata": { "quote_id": format!("q-{}", label) },
+                    "shape": { "type": "simple_polygon", "data": points },
+                })
+            })
+            .collect();
+        serde_json::json!({ "name": "mirror", "strip_height": 500.0, "items": items }).to_string()
+    }
+
+    fn placed(item_id: usize, rotation_degrees: f64, position: (f64, f64)) -> PlacedItem {
+        PlacedItem {
+            item_id,
+            rotation_degrees,
+            position_x: position.0,
+            position_y: position.1,
+            host_item_id: None,
+            outline: None,
+            display_outline: None,
+            metadata: None,
+            bend_line_angle_deg: None,
+            post_placed: false,
+            mirrored: false,
+            locked: false,
+            rotation_radians: None,
+            filler: false,
+        }
+    }
+
+    #[test]
+    fn test_detects_mirrored_item_but_not_duplicates() {
+        let json = instance(&[
+            (bracket(false, 0.0, (0.0, 0.0)), 3, "bracket_lh.dxf"),
+            (bracket(false, 90.0, (200.0, 10.0)), 1, "bracket_copy.dxf"),
+            (bracket(true, 90.0, (100.0, 50.0)), 2, "bracket_rh.dxf"),
+        ]);
+        let pairs = detect_mirror_pairs(&parse_instance(&json).unwrap(), DEFAULT_MIRROR_TOLERANCE);
+        assert_eq!(pairs.len(), 1);
+        let pair = &pairs[0];
+        assert_eq!((pair.item_id, pair.mirror_item_id), (0, 2));
+        assert_eq!(pair.mirror_label, "bracket_rh.dxf");
+        assert_eq!(pair.rotation_degrees, 90.0);
+        assert_eq!((pair.demand, pair.mirror_demand), (3, 2));
+
+        // Off by more than the tolerance
+        let json = instance(&[
+            (bracket(false, 0.0, (0.0, 0.0)), 1, "a"),
+            (
+                bracket(true, 0.0, (0.0, 0.0))
+                    .into_iter()
+                    .map(|(x, y)| if y == 15.0 { (x, 15.5) } else { (x, y) })
+                    .collect(),
+                1,
+                "b",
+            ),
+        ]);
+        assert!(detect_mirror_pairs(&parse_instance(&json).unwrap(), 0.05).is_empty());
+    }
+
+    #[test]
+    fn test_consolidated_pair_is_restored_under_original_items() {
+        let json = instance(&[
+            (bracket(false, 0.0, (0.0, 0.0)), 2, "bracket_lh.dxf"),
+            (bracket(true, 180.0, (30.0, -20.0)), 1, "bracket_rh.dxf"),
+        ]);
+        let original = parse_instance(&json).unwrap();
+        let pairs = detect_mirror_pairs(&original, DEFAULT_MIRROR_TOLERANCE);
+        let merge = consolidate_mirror_pairs(&json, &pairs).unwrap();
+        let merged = parse_instance(&merge.json).unwrap();
+        assert_eq!(merged.items[0].demand, 3);
+        assert_eq!(merged.items[1].demand, 0);
+
+        // The optimizer placed three copies of the merged item
+        let mut output: NestingOutput = serde_json::from_value(serde_json::json!({
+            "instance_name": "mirror",
+            "strip_width": 300.0,
+            "strip_height": 500.0,
+            "total_items_placed": 3,
+            "layouts": [],
+            "utilization": 0.5,
+            "computation_time_secs": 1.0,
+        }))
+        .unwrap();
+        output.layouts = vec![
+            placed(0, 0.0, (0.0, 0.0)),
+            placed(0, 90.0, (100.0, 0.0)),
+            placed(0, 270.0, (150.0, 200.0)),
+        ];
+        restore_mirrored_placements(&mut output, &merge.pairs);
+        output.attach_item_metadata(&original.items);
+
+        let ids: Vec<usize> = output.layouts.iter().map(|p| p.item_id).collect();
+        assert_eq!(ids, vec![0, 0, 1]);
+        assert!(output.unplaced_item_ids.is_empty());
+        let restored = &output.layouts[2];
+        assert!(restored.mirrored);
+        assert_eq!(
+            restored.metadata.as_ref().unwrap()["quote_id"],
+            "q-bracket_rh.dxf"
+        );
+
+        // The mirror item placed mirrored covers the same strip area
+        let kept_outline = translate_polygon(
+            &rotate_polygon(&original.items[0].outer, 270.0),
+            150.0,
+            200.0,
+        );
+        let mirror_outline = translate_polygon(
+            &rotate_polygon(
+                &mirror_polygon(&original.items[1].outer),
+                restored.rotation_degrees,
+            ),
+            restored.position_x,
+            restored.position_y,
+        );
+        assert!(same_ring(
+            open_ring(&kept_outline),
+            open_ring(&mirror_outline),
+            1e-6
+        ));
+    }
+
+    #[test]
+    fn test_mirrored_variant_shares_demand_and_is_restored() {
+        let square = vec![(0.0, 0.0), (20.0, 0.0), (20.0, 20.0), (0.0, 20.0)];
+        let json = instance(&[
+            (bracket(false, 0.0, (0.0, 0.0)), 3, "bracket.dxf"),
+            (square, 4, "plate.dxf"),
+            (bracket(false, 0.0, (0.0, 0.0)), 2, "finished_face.dxf"),
+        ]);
+        let original = parse_instance(&json).unwrap();
+        // The plate is symmetric, the finished part is not opted in
+        let split = add_mirrored_variants(&json, &[0, 1]).unwrap().unwrap();
+        assert_eq!(split.variants, vec![(3, 0)]);
+        let probed = parse_instance(&split.json).unwrap();
+        assert_eq!(probed.items[0].demand, 3);
+        assert_eq!(probed.items[3].demand, 3);
+        assert_eq!(
+            probed.items[3].outer,
+            mirror_polygon(&original.items[0].outer)
+        );
+        assert!(add_mirrored_variants(&json, &[1]).unwrap().is_none());
+
+        // The probe packed the copies starting at x = 0, 50 (mirrored) and
+        // 100 first; the mirror image reaches 60 mm left of its position
+        let probe = [
+            placed(0, 0.0, (0.0, 0.0)),
+            placed(3, 0.0, (110.0, 0.0)),
+            placed(0, 0.0, (100.0, 0.0)),
+            placed(3, 0.0, (210.0, 0.0)),
+            placed(0, 0.0, (200.0, 0.0)),
+            placed(3, 0.0, (360.0, 0.0)),
+        ];
+        let split = choose_mirrored_copies(&split, &probe).unwrap();
+        let nested = parse_instance(&split.json).unwrap();
+        assert_eq!(nested.items[0].demand, 2);
+        assert_eq!(nested.items[3].demand, 1);
+
+        let mut output: NestingOutput = serde_json::from_value(serde_json::json!({
+            "instance_name": "mirror",
+            "strip_width": 300.0,
+            "strip_height": 500.0,
+            "total_items_placed": 2,
+            "layouts": [],
+            "utilization": 0.5,
+            "computation_time_secs": 1.0,
+        }))
+        .unwrap();
+        output.layouts = vec![placed(0, 0.0, (0.0, 0.0)), placed(3, 90.0, (80.0, 0.0))];
+        output.unplaced_item_ids = vec![0];
+        restore_mirrored_variants(&mut output, &split.variants);
+
+        let restored = &output.layouts[1];
+        assert_eq!((restored.item_id, restored.mirrored), (0, true));
+        assert_eq!(restored.rotation_degrees, 90.0);
+        assert!(!output.layouts[0].mirrored);
+        assert_eq!(output.unplaced_item_ids, vec![0]);
+    }
+
+    #[test]
+    fn test_single_copy_may_be_cut_mirrored() {
+        let json = instance(&[(bracket(false, 0.0, (0.0, 0.0)), 1, "bracket.dxf")]);
+        let split = add_mirrored_variants(&json, &[0]).unwrap().unwrap();
+        assert_eq!(split.variants, vec![(1, 0)]);
+
+        // The mirror image packed nearer the strip start: it is the one cut
+        let probe = [placed(0, 90.0, (120.0, 0.0)), placed(1, 0.0, (60.0, 0.0))];
+        let chosen = choose_mirrored_copies(&split, &probe).unwrap();
+        let nested = parse_instance(&chosen.json).unwrap();
+        assert_eq!((nested.items[0].demand, nested.items[1].demand), (0, 1));
+
+        // And the other way round
+        let probe = [placed(0, 0.0, (0.0, 0.0)), placed(1, 0.0, (130.0, 0.0))];
+        let chosen = choose_mirrored_copies(&split, &probe).unwrap();
+        let nested = parse_instance(&chosen.json).unwrap();
+        assert_eq!((nested.items[0].demand, nested.items[1].demand), (1, 0));
+
+        // A whole run cuts the part once, as drawn or mirrored
+        let input: NestingInput = serde_json::from_value(serde_json::json!({
+            "json_input": json,
+            "time_limit": 1,
+            "seed": 3,
+            "use_early_termination": true,
+            "allow_mirror": true,
+        }))
+        .unwrap();
+        let output = run_nesting_engine(input).unwrap();
+        assert_eq!(output.total_items_placed, 1);
+        assert_eq!(output.layouts[0].item_id, 0);
+        assert!(output.unplaced_item_ids.is_empty());
+    }
+}
@@ -3,26 +3,195 @@
 //! Provides strip packing nesting optimization for cutting parts.
 //! This module integrates the sparrow/jagua-rs algorithms directly into Tauri.
 
+mod adjustment;
+mod bench;
+mod builder;
+mod chunking;
+mod config_trace;
+mod consolidate;
+mod cut_length;
+mod cut_program;
+mod defects;
+mod derived;
+mod diff;
+mod dimension;
+mod filler;
+mod fingerprint;
+pub mod geometry;
+mod grain;
+mod heatmap;
+mod holes;
+mod instance;
+mod item_import;
+mod keep_out;
+mod layout_dxf;
+mod layout_html;
+mod layout_print;
+mod layout_stream;
+mod locked;
+mod min_web;
+mod mirror;
+mod origin;
+pub mod logging;
 mod nesting;
+mod overrides;
+mod phases;
+mod pre_orient;
+mod preview;
+mod provenance;
+mod quality;
+mod remnant;
+mod renest;
+mod resources;
+mod restarts;
+mod sanity;
 mod serializer;
+mod sheet_mode;
+mod sheet_order;
+mod small_parts;
+mod spacing;
+mod stats;
+mod suggestions;
+mod target;
 mod terminator;
+mod time_budget;
+mod verify;
+mod viewbox;
+mod warm_start;
+mod winding;
+mod workers;
 
 // Re-export public types
-pub use nesting::{run_nesting, NestingConfig, NestingResult};
-pub use serializer::{NestingOutput, PlacedItem};
-pub use terminator::NativeTerminator;
+pub use adjustment::{AdjustmentSession, ProbeResult, CLEARANCE_RADIUS};
+pub use bench::{
+    run_benchmark, BenchmarkReport, StagePercentiles, BENCHMARK_INSTANCES, DEMO_INSTANCE,
+};
+pub use builder::{
+    validate_input, IssueSeverity, NestingConfigBuilder, NestingInputBuilder, StockSheet,
+    ValidationIssue, DEFAULT_MAX_STRIP_HEIGHT,
+};
+pub use chunking::{
+    plan_demand_chunking, ChunkPlan, RepeatedSheet, CHUNK_MIN_AREA_SHARE,
+    DEFAULT_CHUNK_DEMAND_THRESHOLD,
+};
+pub use config_trace::{ConfigSource, ConfigTrace, ParameterTrace, TraceCandidate};
+pub use consolidate::{
+    allocate_by_placed_area, allocate_sheet_costs, merge_quote_instances, sheets_of_output,
+    CostLine, ItemSource, QuoteAllocation, QuoteParts, SheetCostAllocation, SheetCostPolicy,
+    SheetCostSplit, SheetParts,
+};
+pub use cut_length::ItemCut;
+pub use cut_program::{
+    cut_program, CoordinateFrame, CutEntry, CutProgram, CutProgramOptions, Technology,
+    CUT_PROGRAM_FORMAT, CUT_PROGRAM_VERSION, DEFAULT_LEAD_IN_LENGTH,
+};
+pub use defects::validate_defect_zones;
+pub use derived::{DerivedField, DerivedOutput, ItemOutlines, NestingRun};
+pub use dimension::{
+    deserialize_dimension, deserialize_optional_dimension, number_locale, parse_dimension,
+    parse_dimension_in, parse_number, parse_number_in, set_number_locale, NumberLocale,
+    ParsedNumber,
+};
+pub use diff::{
+    diff_instances, DiffItem, GeometryChange, InstanceDiff, ItemMetrics, QuantityChange,
+    DEFAULT_DIFF_TOLERANCE,
+};
+pub use filler::{
+    FillerCount, FillerItem, FillerSummary, DEFAULT_MAX_FILLER_COUNT, DEFAULT_MAX_FILLER_SHARE,
+};
+pub use fingerprint::{
+    compare_fingerprints, fingerprint_instance, FingerprintChange, InstanceFingerprint,
+};
+pub use geometry::{DisplayUnits, Polygon, MM_PER_INCH};
+pub use grain::{bend_line_angle, grain_orientations, DEFAULT_BEND_ANGLE_TOLERANCE};
+pub use heatmap::{UtilizationGrid, UtilizationGridOptions};
+pub use instance::{parse_instance, InstanceGeometry, InstanceItem, MAX_ITEM_METADATA_BYTES};
+pub use item_import::SkippedItem;
+pub use keep_out::{check_sheet_capacity, validate_keep_out_zones, KeepOutZone};
+pub use layout_dxf::{layout_dxf, LayoutDxfOptions, LayoutDxfReport, PartWinding};
+pub use layout_html::{layout_interactive_html, InteractiveHtmlOptions};
+pub use layout_print::{
+    fit_scale, layout_print_pages, png_with_dpi, LayoutPrintOptions, Orientation, Paper,
+    PaperSize, PrintPage,
+};
+pub use layout_stream::{LayoutSvg, LAYOUT_SVG_INTERVAL};
+pub use locked::FixedPosition;
+pub use min_web::{check_min_web, MinWebReport, MinWebViolation};
+pub use mirror::{detect_mirror_pairs, MirrorPair, DEFAULT_MIRROR_TOLERANCE};
+pub use nesting::{
+    algorithm_fingerprint, run_nesting, NestingConfig, NestingResult, DEFAULT_ITEM_SEPARATION,
+};
+pub use overrides::ConfigOverrides;
+pub use phases::{
+    OptimizerPhase, PhaseStats, PhaseTracker, ProgressReport, ProgressStage, SolutionPhase,
+};
+pub use pre_orient::BaseRotation;
+pub use preview::{
+    render_preview_svg, PlacementMove, PlacementTransform, PreviewDiffer, PreviewFrame,
+    PreviewUpdate, FULL_REDRAW_SHARE,
+};
+pub use provenance::{compare_outputs, provenance_warnings, OutputComparison, Provenance};
+pub use quality::{Quality, QualitySettings, SeparatorLimits};
+pub use remnant::{validate_container, ContainerPolygon};
+pub use renest::{renest_subset, subset_sheet_length, PlacementOrigins, SubsetLayout};
+pub use resources::ResourceUsage;
+pub use restarts::RestartSummary;
+pub use sanity::{
+    check_against_estimate, quick_estimate_width, LikelyCause, SanityCulprit, SanityWarning,
+    DEFAULT_SANITY_FACTOR,
+};
+pub use serializer::{NestingOutput, PlacedItem, UnplacedItem, UnplacedReason};
+pub use sheet_mode::{SheetLayout, SheetMode, DEFAULT_MAX_SHEETS};
+pub use sheet_order::{order_sheets, ItemSheets, SheetOrder, DUE_RANK_KEY};
+pub use small_parts::{DEFAULT_SMALL_ITEM_AREA, MIN_GRID_FILL_DIAMETER};
+pub use spacing::{GrownItem, SpacingPlan, EXTRA_SEPARATION_KEY};
+pub use stats::{StageSpan, StageTimings};
+pub use suggestions::{analyze_run, SuggestedAction, Suggestion, SuggestionRule};
+pub use terminator::{NativeTerminator, CANCELLED_BEFORE_SOLUTION};
+pub use time_budget::{
+    instance_difficulty, BatchTimeBudget, GroupTimeBudget, DEFAULT_GROUP_FLOOR_SECS,
+};
+pub use verify::{verify_layout, verify_output, OverlapReport, ViolationKind};
+pub use viewbox::{expand_svg_viewbox, fit_svg_viewbox, SvgWarning, VIEWBOX_MARGIN};
+pub use warm_start::{WarmPlacement, WarmStart};
+pub use winding::{orient_contours, ContourReversals, Winding};
 
 use anyhow::Result;
 use log::info;
-use sparrow::util::listener::DummySolListener;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use sparrow::util::listener::{ReportType, SolutionListener};
 use sparrow::util::terminator::Terminator;
+use std::collections::BTreeMap;
 use std::time::Duration;
 
+/// Versions of the nesting crates, stored with results for replay checks
+/// (captured from Cargo.lock by build.rs)
+pub const ENGINE_VERSION: &str = concat!(
+    "sparrow ",
+    env!("SPARROW_VERSION"),
+    ", jagua-rs ",
+    env!("JAGUA_RS_VERSION")
+);
+
+/// Version of the app that produced a result
+pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Display outline tolerance when the request does not set one (mm)
+pub const DEFAULT_DISPLAY_TOLERANCE: f64 = 0.5;
+
 /// Input configuration for nesting from frontend
-#[derive(Debug, Clone, serde::Deserialize)]
+///
+/// Code embedding the engine should use `NestingInput::builder()`, which
+/// validates the values; unset fields fall back to engine defaults.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[non_exhaustive]
 pub struct NestingInput {
     /// JSON string containing the sparroWASM problem definition
+    #[serde(default)]
     pub json_input: String,
+    /// Path of an instance JSON file to read instead of `json_input`
+    /// (read by the Tauri command, restricted to the allowed folders)
+    pub json_path: Option<String>,
     /// Time limit in seconds (default: 300)
     pub time_limit: Option<u64>,
     /// Random seed for reproducibility
@@ -31,6 +200,339 @@ pub struct NestingInput {
     pub use_early_termination: Option<bool>,
     /// Number of worker threads (default: 1)
     pub n_workers: Option<usize>,
+    /// Grid resolution for the utilization heatmap (skipped when None)
+    pub utilization_grid: Option<UtilizationGridOptions>,
+    /// Draw the utilization heatmap on top of the SVG (default: false)
+    pub heatmap_overlay: Option<bool>,
+    /// Draw the clearance zone of parts with `extra_spacing` (or
+    /// `extra_separation` in their metadata) on top of the SVG (default: false)
+    pub clearance_overlay: Option<bool>,
+    /// Draw the nominal and the kerf-compensated outline of every nested
+    /// part on top of the SVG, for checking `kerf_width` (default: false)
+    pub kerf_overlay: Option<bool>,
+    /// Compute the SVG viewBox from the strip and the placements instead
+    /// of widening the one jagua-rs rendered (default: false)
+    pub fit_svg_viewbox: Option<bool>,
+    /// Areas of the sheet where no part may be placed
+    pub keep_out_zones: Option<Vec<KeepOutZone>>,
+    /// Defect zones of the sheet (scratches, pre-drilled regions) in
+    /// sheet coordinates; no part may overlap them, zones off the strip
+    /// are reported in `input_warnings`
+    pub holes: Option<Vec<Polygon>>,
+    /// Machine profile whose clamp zones are added to `keep_out_zones`
+    /// (resolved by the Tauri command before the engine runs)
+    pub machine_profile_id: Option<String>,
+    /// Place small parts inside the holes of larger parts (default: false)
+    pub hole_nesting: Option<bool>,
+    /// Attach exact and display outlines to every placement (default: false)
+    pub include_outlines: Option<bool>,
+    /// Max deviation of the display outlines in mm, usually the size of
+    /// one screen pixel at the preview zoom (default: DEFAULT_DISPLAY_TOLERANCE)
+    pub display_tolerance: Option<f64>,
+    /// Name of the settings preset the run uses (recorded in run metrics only)
+    pub preset: Option<String>,
+    /// Independent optimizer runs with different seeds sharing the time
+    /// limit, the best layout is returned (default: 1)
+    pub n_restarts: Option<usize>,
+    /// Stop as soon as a layout reaches this utilization (0-1], instead
+    /// of searching for the shortest strip until the time limit
+    pub target_utilization: Option<f64>,
+    /// Separator preset, `"fast"`, `"balanced"` or `"best"`; slower
+    /// presets give shorter strips in the same time limit more often
+    /// (default: fast)
+    pub quality: Option<Quality>,
+    /// Minimum web width between the contours of a part in mm (check
+    /// skipped when None); narrower webs are reported as warnings
+    #[serde(default, deserialize_with = "deserialize_optional_dimension")]
+    pub min_web: Option<f64>,
+    /// Material whose minimum web is used when `min_web` is not set
+    /// (resolved by the Tauri command before the engine runs)
+    pub material_id: Option<String>,
+    /// Fail instead of warning when a web is narrower than `min_web` (default: false)
+    pub strict_min_web: Option<bool>,
+    /// Nest the other items when some cannot be imported (malformed or
+    /// self-intersecting polygons), listing them in `skipped_items`;
+    /// otherwise the run fails naming every such item (default: false)
+    pub skip_bad_items: Option<bool>,
+    /// Strip height in mm above which the instance is reported, when
+    /// `stock_sheet` is unknown (default: DEFAULT_MAX_STRIP_HEIGHT; the
+    /// Tauri command takes it from the `max_strip_height` setting)
+    #[serde(default, deserialize_with = "deserialize_optional_dimension")]
+    pub max_strip_height: Option<f64>,
+    /// Widest stock sheet of the material, the strip height must fit it
+    /// (resolved by the Tauri command from `material_id`)
+    pub stock_sheet: Option<StockSheet>,
+    /// Fail instead of warning on a strip height the stock or the sanity
+    /// maximum cannot hold (default: false)
+    pub strict_strip_height: Option<bool>,
+    /// Warn when the strip used is this many times longer than the quick
+    /// area estimate (default: DEFAULT_SANITY_FACTOR)
+    pub sanity_factor: Option<f64>,
+    /// Minimum distance between parts and to the strip edge in mm, 0 for
+    /// none (default: DEFAULT_ITEM_SEPARATION); sparrow's name
+    /// `min_item_separation` is accepted as well
+    #[serde(
+        default,
+        alias = "min_item_separation",
+        deserialize_with = "deserialize_optional_dimension"
+    )]
+    pub separation: Option<f64>,
+    /// Width of the cut in mm; every part outline is grown by half of it
+    /// before nesting so the separation is kept between the cut paths,
+    /// placements still refer to the nominal outline (default: 0, no
+    /// kerf compensation)
+    #[serde(default, deserialize_with = "deserialize_optional_dimension")]
+    pub kerf_width: Option<f64>,
+    /// Orientations allowed for every part in degrees, replacing the
+    /// instance's; `[0.0]` keeps every part as drawn, an empty list is
+    /// the same as unset (default: as in the instance). Also accepted
+    /// as `allowed_rotations`
+    #[serde(default, alias = "allowed_rotations")]
+    pub rotations: Option<Vec<f64>>,
+    /// Restrict bent parts (`bend_direction_deg` in the instance) to the
+    /// orientations that keep their bend lines along the strip's X axis,
+    /// the sheet's rolling direction (default: false)
+    pub respect_grain: Option<bool>,
+    /// Max angle between bend lines and the X axis in degrees
+    /// (default: DEFAULT_BEND_ANGLE_TOLERANCE)
+    pub bend_angle_tolerance: Option<f64>,
+    /// Turn parts drawn at an angle so their minimum-area bounding
+    /// rectangle is axis-aligned before nesting; placements still report
+    /// rotations relative to the drawing. Parts with bend lines or a fixed
+    /// position stay as drawn, and all of them do with `rotations` set;
+    /// parts with `allowed_orientations` other than the quarter turns only
+    /// turn by steps keeping them in that set (default: false)
+    pub pre_orient_items: Option<bool>,
+    /// Net area in mm² below which parts (washers, spacers) skip the
+    /// optimizer and are grid-filled into holes and free space after
+    /// packing; 0 packs every part (default: DEFAULT_SMALL_ITEM_AREA)
+    pub small_item_area: Option<f64>,
+    /// Stock parts (washers, test coupons) with unlimited demand dropped
+    /// into the gaps left after nesting; they are flagged `filler` and
+    /// counted in `filler_summary` only (see `filler`)
+    pub filler_items: Option<Vec<FillerItem>>,
+    /// Most fillers placed (default: DEFAULT_MAX_FILLER_COUNT)
+    pub max_filler_count: Option<usize>,
+    /// Most net area in mm² of all fillers (default:
+    /// DEFAULT_MAX_FILLER_SHARE of the sheet)
+    pub max_filler_area: Option<f64>,
+    /// Length of one stock sheet along the strip in mm; needed for demand
+    /// chunking (filled from the machine profile when unset)
+    #[serde(default, deserialize_with = "deserialize_optional_dimension")]
+    pub sheet_length: Option<f64>,
+    /// Demand of a single item above which it is cut on identical full
+    /// sheets and only the remainder is optimized, see `chunking`
+    /// (default: DEFAULT_CHUNK_DEMAND_THRESHOLD)
+    pub chunk_demand_threshold: Option<usize>,
+    /// The machine may cut parts mirrored: the optimizer may place parts
+    /// flipped, placements are flagged `mirrored` (default: false)
+    pub allow_mirror: Option<bool>,
+    /// `allow_mirror` of single items by item ID, overriding the global
+    /// flag; false for parts with one finished face
+    pub item_allow_mirror: Option<BTreeMap<u64, bool>>,
+    /// Nest each item and its mirror image (see `mirror`) as one item;
+    /// needs `allow_mirror`, set once the user confirmed the reported
+    /// `mirror_pairs` (default: false)
+    pub consolidate_mirror_pairs: Option<bool>,
+    /// Nest on a growing strip or into one fixed sheet of the strip
+    /// height, leaving parts that do not fit unplaced (default: strip)
+    pub sheet_mode: Option<SheetMode>,
+    /// Sheets to nest in fixed-sheet mode, parts left off one sheet are
+    /// nested onto the next (default: DEFAULT_MAX_SHEETS). Every sheet
+    /// gets the full `time_limit`, a run takes up to `max_sheets` times it
+    pub max_sheets: Option<usize>,
+    /// Give the whole time limit to exploration and skip compression,
+    /// for jobs where compression gains nothing (default: false)
+    pub skip_compression: Option<bool>,
+    /// Skip exploration and only compress the `warm_start` layout for the
+    /// whole time limit, failing instead of nesting from scratch when it
+    /// cannot be continued (default: false)
+    pub compress_only: Option<bool>,
+    /// Outline of a remnant (offcut) to nest into instead of the strip;
+    /// parts not fitting it are left unplaced. Replaces `sheet_mode`
+    pub container_polygon: Option<ContainerPolygon>,
+    /// Kerf width of the cutting process in mm, the separation is
+    /// compared with it for the run suggestions (resolved by the Tauri
+    /// command from `material_id` when unset)
+    #[serde(default, deserialize_with = "deserialize_optional_dimension")]
+    pub kerf: Option<f64>,
+    /// Stream preview updates while optimizing (job runs only, default: false)
+    pub live_preview: Option<bool>,
+    /// Stream the full layout SVG of improved solutions while optimizing,
+    /// at most one per second (job runs only, default: false)
+    pub live_layout_svg: Option<bool>,
+    /// Placements of a stored output of the same instance to continue
+    /// optimizing (see `WarmStart::from_output`); the run only compresses
+    /// that layout, falling back to a normal run when it does not match
+    pub warm_start: Option<WarmStart>,
+    /// Print where every setting came from and return it in
+    /// `NestingOutput::config_trace` (default: false)
+    pub debug_trace: Option<bool>,
+    /// Candidates of the settings resolved before the input was built
+    /// (batch layers, material, machine profile)
+    #[serde(skip)]
+    pub config_sources: ConfigTrace,
+}
+
+impl NestingConfig {
+    /// Optimizer settings of an input, with defaults applied
+    ///
+    /// The only place input options become a `NestingConfig`. The trace
+    /// continues `input.config_sources` with the input fields and defaults.
+    pub fn resolve(input: &NestingInput) -> (Self, ConfigTrace) {
+        fn input_or<T: Clone>(
+            input: &Option<T>,
+            default: Option<T>,
+        ) -> [(ConfigSource, Option<T>); 2] {
+            [
+                (ConfigSource::Input, input.clone()),
+                (ConfigSource::Default, default),
+            ]
+        }
+
+        let defaults = NestingConfig::default();
+        let mut trace = input.config_sources.clone();
+        let config = Self {
+            time_limit: trace.pick(
+                "time_limit",
+                &input_or(&input.time_limit, defaults.time_limit),
+            ),
+            seed: trace.pick("seed", &input_or(&input.seed, defaults.seed)),
+            use_early_termination: trace
+                .pick(
+                    "use_early_termination",
+                    &input_or(
+                        &input.use_early_termination,
+                        Some(defaults.use_early_termination),
+                    ),
+                )
+                .unwrap_or(defaults.use_early_termination),
+            n_workers: trace
+                .pick(
+                    "n_workers",
+                    &input_or(&input.n_workers, Some(defaults.n_workers)),
+                )
+                .unwrap_or(defaults.n_workers),
+            keep_out_zones: trace
+                .pick(
+                    "keep_out_zones",
+                    &input_or(&input.keep_out_zones, Some(defaults.keep_out_zones)),
+                )
+                .unwrap_or_default(),
+            holes: trace
+                .pick("holes", &input_or(&input.holes, Some(defaults.holes)))
+                .unwrap_or_default(),
+            hole_nesting: trace
+                .pick(
+                    "hole_nesting",
+                    &input_or(&input.hole_nesting, Some(defaults.hole_nesting)),
+                )
+                .unwrap_or(defaults.hole_nesting),
+            separation: trace
+                .pick(
+                    "separation",
+                    &input_or(&input.separation, Some(defaults.separation)),
+                )
+                .unwrap_or(defaults.separation),
+            kerf_width: trace
+                .pick(
+                    "kerf_width",
+                    &input_or(&input.kerf_width, Some(defaults.kerf_width)),
+                )
+                .unwrap_or(defaults.kerf_width),
+            rotations: trace.pick(
+                "rotations",
+                &input_or(
+                    &input.rotations.clone().filter(|rotations| !rotations.is_empty()),
+                    defaults.rotations,
+                ),
+            ),
+            respect_grain: trace
+                .pick(
+                    "respect_grain",
+                    &input_or(&input.respect_grain, Some(defaults.respect_grain)),
+                )
+                .unwrap_or(defaults.respect_grain),
+            bend_angle_tolerance: trace
+                .pick(
+                    "bend_angle_tolerance",
+                    &input_or(
+                        &input.bend_angle_tolerance,
+                        Some(defaults.bend_angle_tolerance),
+                    ),
+                )
+                .unwrap_or(defaults.bend_angle_tolerance),
+            pre_orient_items: trace
+                .pick(
+                    "pre_orient_items",
+                    &input_or(&input.pre_orient_items, Some(defaults.pre_orient_items)),
+                )
+                .unwrap_or(defaults.pre_orient_items),
+            small_item_area: trace
+                .pick(
+                    "small_item_area",
+                    &input_or(&input.small_item_area, Some(defaults.small_item_area)),
+                )
+                .unwrap_or(defaults.small_item_area),
+            filler_items: trace
+                .pick(
+                    "filler_items",
+                    &input_or(&input.filler_items, Some(defaults.filler_items)),
+                )
+                .unwrap_or_default(),
+            max_filler_count: trace
+                .pick(
+                    "max_filler_count",
+                    &input_or(&input.max_filler_count, Some(defaults.max_filler_count)),
+                )
+                .unwrap_or(defaults.max_filler_count),
+            max_filler_area: trace.pick(
+                "max_filler_area",
+                &input_or(&input.max_filler_area, defaults.max_filler_area),
+            ),
+            sheet_mode: trace
+                .pick(
+                    "sheet_mode",
+                    &input_or(&input.sheet_mode, Some(defaults.sheet_mode)),
+                )
+                .unwrap_or(defaults.sheet_mode),
+            skip_compression: trace
+                .pick(
+                    "skip_compression",
+                    &input_or(&input.skip_compression, Some(defaults.skip_compression)),
+                )
+                .unwrap_or(defaults.skip_compression),
+            compress_only: trace
+                .pick(
+                    "compress_only",
+                    &input_or(&input.compress_only, Some(defaults.compress_only)),
+                )
+                .unwrap_or(defaults.compress_only),
+            container_polygon: trace
+                .pick(
+                    "container_polygon",
+                    &input_or(&input.container_polygon, defaults.container_polygon),
+                )
+                .map(ContainerPolygon::normalized),
+            quality: trace
+                .pick("quality", &input_or(&input.quality, Some(defaults.quality)))
+                .unwrap_or(defaults.quality),
+            warm_start: None,
+        };
+        (config, trace)
+    }
+}
+
+impl From<&NestingInput> for NestingConfig {
+    fn from(input: &NestingInput) -> Self {
+        Self::resolve(input).0
+    }
+}
+
+impl From<NestingInput> for NestingConfig {
+    fn from(input: NestingInput) -> Self {
+        Self::from(&input)
+    }
 }
 
 /// Run nesting optimization - main entry point for Tauri
@@ -46,21 +548,159 @@ pub struct NestingInput {
 /// * `Err(String)` - Error message if nesting failed
 ///
 /// # Example
-/// ```rust
-/// let input = NestingInput {
-///     json_input: json_string,
-///     time_limit: Some(60),
-///     seed: None,
-///     use_early_termination: Some(false),
-///     n_workers: Some(1),
-/// };
+/// ```rust,no_run
+/// use smart_cut_quote_lib::nesting_engine::{run_nesting_engine, NestingInput};
+///
+/// # fn main() -> Result<(), String> {
+/// # let json_string = String::new();
+/// let input = NestingInput::builder()
+///     .json_input(json_string)
+///     .time_limit_secs(60)
+///     .build()
+///     .map_err(|issues| format!("{:?}", issues))?;
 ///
 /// let result = run_nesting_engine(input)?;
 /// println!("Placed {} items", result.total_items_placed);
+/// # Ok(())
+/// # }
 /// ```
 pub fn run_nesting_engine(input: NestingInput) -> Result<NestingOutput, String> {
-    // Initialize logging (only once)
-    let _ = init_logger();
+    run_nesting_engine_with_terminator(input, NativeTerminator::new())
+}
+
+/// Run nesting optimization, stoppable through the given terminator
+///
+/// Terminating the run makes the optimizer return its best solution so
+/// far, which is then serialized as usual.
+pub fn run_nesting_engine_with_terminator(
+    input: NestingInput,
+    terminator: NativeTerminator,
+) -> Result<NestingOutput, String> {
+    let (mut output, run) = run_nesting_core_with_terminator(input, terminator)?;
+
+    let derive_span = StageSpan::start();
+    run.derive(&run.requested_fields()).apply_to(&mut output);
+    if let Some(stage_timings) = output.stage_timings.as_mut() {
+        let mut derive_secs = 0.0;
+        derive_span.record(&mut derive_secs);
+        stage_timings.serialize_secs += derive_secs;
+    }
+
+    Ok(output)
+}
+
+/// Run the optimizer and build placements and summary only
+///
+/// Returns the slim output (no outlines, heatmap or SVG) together with
+/// the `NestingRun` those fields are derived from, so callers can show
+/// the result before the heavy post-processing is done.
+pub fn run_nesting_core(input: NestingInput) -> Result<(NestingOutput, NestingRun), String> {
+    run_nesting_core_with_terminator(input, NativeTerminator::new())
+}
+
+/// `run_nesting_core`, stoppable through the given terminator
+pub fn run_nesting_core_with_terminator(
+    input: NestingInput,
+    terminator: NativeTerminator,
+) -> Result<(NestingOutput, NestingRun), String> {
+    run_nesting_core_with_preview(input, terminator, None, None, None)
+}
+
+/// `run_nesting_core_with_terminator`, reporting preview updates while optimizing
+///
+/// `on_preview` receives a full SVG for the first solution and patches
+/// for later ones (see `PreviewUpdate`). `on_progress` receives every
+/// reported solution; callers rate limit it themselves. `on_layout_svg`
+/// receives the full layout SVG of improved solutions, at most once per
+/// `LAYOUT_SVG_INTERVAL`.
+///
+/// With `n_restarts` above 1 the optimizer runs that many times with
+/// different seeds (see `restarts`) and the best run is returned.
+///
+/// Items are first imported one by one (see `item_import`); items that
+/// fail either fail the run or, with `skip_bad_items`, are left out and
+/// listed in `skipped_items`.
+pub fn run_nesting_core_with_preview(
+    input: NestingInput,
+    terminator: NativeTerminator,
+    on_preview: Option<&mut dyn FnMut(PreviewUpdate)>,
+    on_progress: Option<&mut dyn FnMut(ProgressReport)>,
+    on_layout_svg: Option<&mut dyn FnMut(LayoutSvg)>,
+) -> Result<(NestingOutput, NestingRun), String> {
+    let (input, skipped_items) = screen_items(input)?;
+    let (mut output, run) =
+        nest_restarts(input, terminator, on_preview, on_progress, on_layout_svg)?;
+    output.skipped_items = skipped_items;
+    Ok((output, run))
+}
+
+/// Import the items one by one and take out those that fail to import
+///
+/// Fails listing every such item unless `skip_bad_items` is set.
+fn screen_items(mut input: NestingInput) -> Result<(NestingInput, Vec<SkippedItem>), String> {
+    // Invalid settings are reported by `nest_once`
+    let invalid = validate_input(&input).iter().any(ValidationIssue::is_error);
+    if input.json_input.is_empty() || invalid {
+        return Ok((input, Vec::new()));
+    }
+    let report = item_import::import_items(&input.json_input, &NestingConfig::from(&input))?;
+    if report.failed.is_empty() {
+        return Ok((input, Vec::new()));
+    }
+    if !input.skip_bad_items.unwrap_or(false) || report.failed.len() == report.n_items {
+        return Err(report.error_message());
+    }
+    for item in &report.failed {
+        println!("⚠️ Skipping item {}: {}", item.item_id, item.error);
+    }
+    input.json_input = item_import::drop_items(&input.json_input, &report.failed)?;
+    Ok((input, report.failed))
+}
+
+/// `run_nesting_core_with_preview` on an instance whose items all import
+fn nest_restarts(
+    input: NestingInput,
+    terminator: NativeTerminator,
+    mut on_preview: Option<&mut dyn FnMut(PreviewUpdate)>,
+    mut on_progress: Option<&mut dyn FnMut(ProgressReport)>,
+    mut on_layout_svg: Option<&mut dyn FnMut(LayoutSvg)>,
+) -> Result<(NestingOutput, NestingRun), String> {
+    let restarts = input.n_restarts.unwrap_or(1);
+    if restarts <= 1 {
+        return nest_once(input, terminator, on_preview, on_progress, on_layout_svg);
+    }
+
+    let time_limit = NestingConfig::from(&input).time_limit.unwrap_or_default();
+    println!(
+        "🔁 Nesting {} restarts of {}s each",
+        restarts,
+        restarts::restart_time_limit(time_limit, restarts)
+    );
+    restarts::run_restarts(&input, time_limit, restarts, &terminator, |attempt, child| {
+        // Reborrowed for each restart, shortening the trait object lifetime
+        let on_preview = on_preview
+            .as_mut()
+            .map(|f| &mut **f as &mut dyn FnMut(PreviewUpdate));
+        let on_progress = on_progress
+            .as_mut()
+            .map(|f| &mut **f as &mut dyn FnMut(ProgressReport));
+        let on_layout_svg = on_layout_svg
+            .as_mut()
+            .map(|f| &mut **f as &mut dyn FnMut(LayoutSvg));
+        nest_once(attempt, child, on_preview, on_progress, on_layout_svg)
+    })
+}
+
+/// One optimizer run of `run_nesting_core_with_preview`
+fn nest_once(
+    input: NestingInput,
+    mut terminator: NativeTerminator,
+    on_preview: Option<&mut dyn FnMut(PreviewUpdate)>,
+    on_progress: Option<&mut dyn FnMut(ProgressReport)>,
+    on_layout_svg: Option<&mut dyn FnMut(LayoutSvg)>,
+) -> Result<(NestingOutput, NestingRun), String> {
+    // Make sure records reach a logger; keeps any configuration set by the app
+    let _ = logging::init_logging();
 
     // DEBUG: Print raw input values
     println!("🔍 DEBUG: run_nesting_engine received:");
@@ -71,44 +711,693 @@ pub fn run_nesting_engine(input: NestingInput) -> Result<NestingOutput, String>
 
     info!("Starting nesting engine with time_limit={:?}s", input.time_limit);
 
-    // Build configuration
-    let config = NestingConfig {
-        time_limit: input.time_limit.or(Some(300)),
-        seed: input.seed,
-        use_early_termination: input.use_early_termination.unwrap_or(false),
-        n_workers: input.n_workers.unwrap_or(1),
+    let (issues, input_warnings): (Vec<_>, Vec<_>) = validate_input(&input)
+        .into_iter()
+        .partition(ValidationIssue::is_error);
+    if !issues.is_empty() {
+        let messages: Vec<String> = issues.iter().map(|issue| issue.to_string()).collect();
+        return Err(format!("Invalid nesting input: {}", messages.join("; ")));
+    }
+    for warning in &input_warnings {
+        println!("⚠️ {}", warning);
+    }
+
+    // Build configuration, recording where every setting came from
+    let (mut config, mut config_trace) = NestingConfig::resolve(&input);
+
+    // Fall back to fewer workers where thread creation is restricted,
+    // the resolved config then records the count actually used
+    let requested_workers = config.n_workers;
+    config.n_workers = workers::available_workers(requested_workers, workers::spawn_rayon_pool);
+    if config.n_workers < requested_workers {
+        println!(
+            "⚠️ Running with {} of {} requested workers",
+            config.n_workers, requested_workers
+        );
+        config_trace.runtime("n_workers", &config.n_workers);
+    }
+
+    let instance = parse_instance(&input.json_input)?;
+
+    // Parts already on the sheet stay where they are, the optimizer
+    // nests only the rest of their demand
+    let locked_parts = locked::plan_locked(&instance);
+    let released = if locked_parts.is_empty() {
+        None
+    } else {
+        let json = locked::release_locked(&input.json_input)?;
+        let instance = parse_instance(&json)?;
+        Some((json, instance))
+    };
+    let (unlocked_json, unlocked_instance) = released.as_ref().map_or(
+        (input.json_input.as_str(), &instance),
+        |(json, instance)| (json.as_str(), instance),
+    );
+
+    // Clearance of delicate parts adds to the separation
+    let separation_source = config_trace
+        .source("separation")
+        .unwrap_or(ConfigSource::Default);
+    for item in instance.items.iter().filter(|item| item.extra_separation > 0.0) {
+        config_trace.merge(
+            &format!("separation[item {}]", item.id),
+            &[
+                (separation_source, Some(config.separation)),
+                (ConfigSource::Item, Some(item.extra_separation)),
+            ],
+            &(config.separation + item.extra_separation),
+        );
+    }
+    let min_web = config_trace.pick("min_web", &[(ConfigSource::Input, input.min_web)]);
+    let kerf = config_trace.pick("kerf", &[(ConfigSource::Input, input.kerf)]);
+    let sanity_factor = config_trace
+        .pick(
+            "sanity_factor",
+            &[
+                (ConfigSource::Input, input.sanity_factor),
+                (ConfigSource::Default, Some(DEFAULT_SANITY_FACTOR)),
+            ],
+        )
+        .unwrap_or(DEFAULT_SANITY_FACTOR);
+
+    // One item making up nearly the whole job is cut on identical full
+    // sheets, only the copies left over are nested with the other items
+    let sheet_length =
+        config_trace.pick("sheet_length", &[(ConfigSource::Input, input.sheet_length)]);
+    let max_sheets = config_trace
+        .pick(
+            "max_sheets",
+            &[
+                (ConfigSource::Input, input.max_sheets),
+                (ConfigSource::Default, Some(DEFAULT_MAX_SHEETS)),
+            ],
+        )
+        .unwrap_or(DEFAULT_MAX_SHEETS);
+    let chunk_demand_threshold = config_trace
+        .pick(
+            "chunk_demand_threshold",
+            &[
+                (ConfigSource::Input, input.chunk_demand_threshold),
+                (ConfigSource::Default, Some(DEFAULT_CHUNK_DEMAND_THRESHOLD)),
+            ],
+        )
+        .unwrap_or(DEFAULT_CHUNK_DEMAND_THRESHOLD);
+    let chunk_plan = match sheet_length {
+        Some(sheet_length) => chunking::plan_demand_chunking(
+            unlocked_json,
+            &config,
+            sheet_length,
+            chunk_demand_threshold,
+        )?,
+        None => None,
+    };
+    let nest_json = chunk_plan
+        .as_ref()
+        .map_or(unlocked_json, |plan| plan.remainder_json.as_str());
+    let remainder_instance = chunk_plan
+        .as_ref()
+        .map(|plan| parse_instance(&plan.remainder_json))
+        .transpose()?;
+    let nest_instance = remainder_instance.as_ref().unwrap_or(unlocked_instance);
+
+    // The optimizer works in f32, parts drawn in machine coordinates far
+    // from the origin are nested next to it and moved back afterwards.
+    // Mirror images are taken of the moved parts, so they stay near it
+    let origin_shift = origin::normalize_origin(nest_json)?;
+    let nest_json = origin_shift
+        .as_ref()
+        .map_or(nest_json, |shift| shift.json.as_str());
+
+    // Left and right hand versions of a part are always reported, and
+    // nested as one item where the machine may mirror and the user agreed
+    let mirror_pairs = mirror::detect_mirror_pairs(&instance, DEFAULT_MIRROR_TOLERANCE);
+    let allow_mirror = config_trace
+        .pick(
+            "allow_mirror",
+            &[
+                (ConfigSource::Input, input.allow_mirror),
+                (ConfigSource::Default, Some(false)),
+            ],
+        )
+        .unwrap_or(false);
+    let consolidate_mirrors = allow_mirror && input.consolidate_mirror_pairs.unwrap_or(false);
+    let mirror_merge = if consolidate_mirrors && !mirror_pairs.is_empty() {
+        Some(mirror::consolidate_mirror_pairs(nest_json, &mirror_pairs)?)
+    } else {
+        None
+    };
+    let nest_json = mirror_merge
+        .as_ref()
+        .map_or(nest_json, |merge| merge.json.as_str());
+
+    // Parts that may be flipped are nested next to their mirror image;
+    // items of a consolidated pair are mirrored by the pair already
+    let mirror_source = config_trace
+        .source("allow_mirror")
+        .unwrap_or(ConfigSource::Default);
+    let mut mirrorable = Vec::new();
+    for item in &instance.items {
+        let item_override = input
+            .item_allow_mirror
+            .as_ref()
+            .and_then(|overrides| overrides.get(&item.id).copied());
+        let allowed = match item_override {
+            Some(allowed) => config_trace
+                .pick(
+                    &format!("allow_mirror[item {}]", item.id),
+                    &[
+                        (ConfigSource::Item, Some(allowed)),
+                        (mirror_source, Some(allow_mirror)),
+                    ],
+                )
+                .unwrap_or(allowed),
+            None => allow_mirror,
+        };
+        let paired = mirror_merge.as_ref().is_some_and(|merge| {
+            merge.pairs.iter().any(|pair| {
+                pair.item_id == item.id as usize || pair.mirror_item_id == item.id as usize
+            })
+        });
+        if allowed && !paired {
+            mirrorable.push(item.id as usize);
+        }
+    }
+    let mirror_variants = if mirrorable.is_empty() {
+        None
+    } else {
+        mirror::add_mirrored_variants(nest_json, &mirrorable)?
+    };
+    // A short probe run decides how many copies are cut mirrored, its
+    // time is taken off the main run (which keeps at least a second)
+    let probe_start = std::time::Instant::now();
+    let mirror_variants = mirror_variants
+        .map(|variants| probe_mirrored_copies(variants, &config, &terminator))
+        .transpose()?;
+    let probe_time = probe_start.elapsed();
+    let nest_json = mirror_variants
+        .as_ref()
+        .map_or(nest_json, |variants| variants.json.as_str());
+
+    // Parts drawn at an angle are turned to their smallest bounding box,
+    // unless the run fixes the orientations of all parts
+    let pre_orientation = if config.pre_orient_items && config.rotations.is_none() {
+        pre_orient::pre_orient_items(nest_json)?
+    } else {
+        None
+    };
+    let nest_json = pre_orientation
+        .as_ref()
+        .map_or(nest_json, |orientation| orientation.json.as_str());
+
+    // A remnant is nested as a fixed sheet of its bounding box, its
+    // outline is enforced on the layout afterwards
+    let remnant_json = config
+        .container_polygon
+        .as_ref()
+        .map(|container| remnant::with_strip_height(nest_json, container.size().1))
+        .transpose()?;
+    let nest_json = remnant_json.as_deref().unwrap_or(nest_json);
+
+    // A stored layout is continued in the coordinates the optimizer sees;
+    // merged, mirrored or chunked items no longer match its placements
+    config.warm_start = match &input.warm_start {
+        Some(warm)
+            if mirror_merge.is_none()
+                && mirror_variants.is_none()
+                && chunk_plan.is_none()
+                && config.container_polygon.is_none()
+                && config.sheet_mode == SheetMode::Strip =>
+        {
+            let warm = match &origin_shift {
+                Some(shift) => warm.shifted(shift.offset),
+                None => warm.clone(),
+            };
+            Some(match &pre_orientation {
+                Some(orientation) => pre_orient::rebase_warm_start(&warm, orientation),
+                None => warm,
+            })
+        }
+        Some(_) if config.compress_only => {
+            return Err(
+                "compress_only cannot continue a layout of mirrored, chunked, remnant or \
+                 fixed-sheet runs"
+                    .to_string(),
+            );
+        }
+        Some(_) => {
+            println!("⚠️ This run cannot continue a stored layout, nesting from scratch");
+            None
+        }
+        None => None,
+    };
+
+    // Parts whose holes leave too narrow a web break during cutting
+    let min_web_violations = match min_web {
+        Some(min_web) => {
+            let report = check_min_web(&instance, min_web)?;
+            if !report.violations.is_empty() {
+                if input.strict_min_web.unwrap_or(false) {
+                    return Err(report.error_message());
+                }
+                println!("⚠️ {}", report.error_message());
+            }
+            report.violations
+        }
+        None => Vec::new(),
     };
 
     println!("🔍 DEBUG: NestingConfig built:");
     println!("   - config.time_limit = {:?}", config.time_limit);
 
-    // Create listener and terminator
-    let mut listener = DummySolListener;
-    let mut terminator = NativeTerminator::new();
+    // Create listener, the terminator comes from the caller
+    let item_area = nest_instance
+        .items
+        .iter()
+        .map(|item| item.net_area() * item.demand as f64)
+        .sum();
+    let mut listener = target::TargetListener::new(
+        RunListener {
+            phases: PhaseTracker::new(),
+            preview: on_preview.map(preview::PreviewListener::new),
+            progress: on_progress,
+            layout_svg: on_layout_svg.map(layout_stream::LayoutSvgListener::new),
+        },
+        input.target_utilization,
+        item_area,
+        terminator.clone(),
+    );
 
     // CRITICAL: Set timeout on terminator - sparrow checks terminator.kill() but does NOT call new_timeout()
     // We must set it here for the timeout to work
     if let Some(time_limit) = config.time_limit {
-        terminator.new_timeout(Duration::from_secs(time_limit));
+        let main_time = Duration::from_secs(time_limit).saturating_sub(probe_time);
+        terminator.new_timeout(main_time.max(Duration::from_secs(1)));
         println!("⏱️ TIMEOUT SET: {} seconds from now", time_limit);
         println!("⏱️ Deadline: {:?}", terminator.timeout_at());
     }
 
+    // Cancelled while the input was being prepared
+    if terminator.is_cancelled() {
+        return Err(format!(
+            "{}: Nesting cancelled before a layout was found",
+            CANCELLED_BEFORE_SOLUTION
+        ));
+    }
+
     // Run core nesting algorithm
-    let result = run_nesting(&input.json_input, &config, &mut listener, &mut terminator)
+    let mut result = run_nesting(nest_json, &config, &mut listener, &mut terminator)
         .map_err(|e| format!("Nesting failed: {}", e))?;
 
+    // Reaching the target stopped the run, not the user: clear the stop so
+    // it is not taken for a cancellation and later sheets are still nested
+    let target_reached_secs = listener.reached_secs();
+    if target_reached_secs.is_some() {
+        terminator.reset();
+    }
+
+    // A cancelled run keeps its best layout so far, if it has one
+    let cancelled = terminator.is_cancelled();
+    if cancelled && result.solution.layout_snapshot.placed_items.is_empty() {
+        return Err(format!(
+            "{}: Nesting cancelled before a layout was found",
+            CANCELLED_BEFORE_SOLUTION
+        ));
+    }
+
+    // Correlate before keep-out eviction changes the placement count
+    let start_width = config
+        .warm_start
+        .as_ref()
+        .filter(|_| result.warm_started)
+        .map(|warm| warm.strip_width);
+    let mut phase_stats = listener
+        .inner()
+        .phases
+        .summarize(&result.solution, start_width);
+    phase_stats.phases_run = result.phases_run.clone();
+    if let Some(gain) = phase_stats.compression_gain_pct {
+        println!("🗜️ Compression improved width by {:.1}%", gain);
+    }
+
+    // Enforce keep-out zones (clamps, fixtures) on the final layout
+    let mut keep_out_conflicts =
+        keep_out::evict_conflicting_items(&mut result.solution, &config.keep_out_zones);
+    if !keep_out_conflicts.is_empty() {
+        println!(
+            "🚧 Removed {} placements overlapping keep-out zones",
+            keep_out_conflicts.len()
+        );
+    }
+
+    // Parts reaching into a defect zone of the sheet
+    let mut defect_conflicts =
+        defects::evict_defect_overlaps(&mut result.solution, &config.holes);
+    if !defect_conflicts.is_empty() {
+        println!(
+            "🚧 Removed {} placements overlapping defect zones",
+            defect_conflicts.len()
+        );
+    }
+
+    // Parts reaching into a locked part, or closer than the separation;
+    // the locked part is cut half the kerf outside its outline, nested
+    // shapes are grown by the other half already
+    let locked_zones = locked::clearance_zones(
+        &locked_parts,
+        config.separation + config.kerf_width / 2.0,
+    );
+    let locked_conflicts = defects::evict_defect_overlaps(&mut result.solution, &locked_zones);
+    if !locked_conflicts.is_empty() {
+        println!(
+            "🚧 Removed {} placements overlapping locked parts",
+            locked_conflicts.len()
+        );
+    }
+
+    // Parts reaching off the remnant or into its holes
+    let off_remnant = match &config.container_polygon {
+        Some(container) => remnant::evict_outside(&mut result.solution, container),
+        None => Vec::new(),
+    };
+    if !off_remnant.is_empty() {
+        println!(
+            "🧩 Removed {} placements reaching off the remnant",
+            off_remnant.len()
+        );
+    }
+
+    // A fixed sheet ends where the strip would have grown further
+    let fixed_width = match &config.container_polygon {
+        Some(container) => Some(container.size().0),
+        None => config.sheet_mode.fixed_width(),
+    };
+    let mut overflow = match fixed_width {
+        Some(fixed_width) => sheet_mode::evict_overflow(
+            &mut result.solution,
+            fixed_width,
+            result.instance.base_strip.fixed_height as f64,
+        ),
+        None => Vec::new(),
+    };
+    overflow.extend(off_remnant);
+
+    // Map preserved holes and parts nested inside them onto the layout
+    let mut hole_layout = holes::resolve_hole_layout(&result.hole_plan, &result.solution);
+    hole_layout.placements.retain(|placement| {
+        let conflict = config
+            .keep_out_zones
+            .iter()
+            .any(|zone| zone.overlaps(&placement.outline));
+        if conflict {
+            keep_out_conflicts.push(placement.item_id);
+        }
+        let defect = defects::overlaps_any(&config.holes, &placement.outline);
+        if defect {
+            defect_conflicts.push(placement.item_id);
+        }
+        !conflict && !defect && !defects::overlaps_any(&locked_zones, &placement.outline)
+    });
+
+    // Parts too small for the optimizer go into what is left free,
+    // defect zones and locked parts are obstacles like the packed parts
+    let mut obstacles = serializer::placed_polygons(&result.solution);
+    obstacles.extend(config.holes.iter().cloned());
+    obstacles.extend(locked_zones.iter().cloned());
+    let mut small_layout = small_parts::place_small_parts(
+        &result.small_part_plan,
+        &obstacles,
+        &hole_layout,
+        &config.keep_out_zones,
+        (
+            fixed_width.map_or(result.solution.strip_width() as f64, |fixed_width| {
+                fixed_width.min(result.solution.strip_width() as f64)
+            }),
+            result.instance.base_strip.fixed_height as f64,
+        ),
+        config.cut_separation(),
+    );
+    if let Some(container) = &config.container_polygon {
+        small_layout
+            .placements
+            .retain(|placement| container.contains(&placement.outline));
+    }
+
+    // Stock fillers go last, into whatever the order left free of the
+    // sheet (or of the strip it used)
+    let filler_layout = if config.filler_items.is_empty() {
+        None
+    } else {
+        obstacles.extend(
+            small_layout
+                .placements
+                .iter()
+                .map(|placement| placement.outline.clone()),
+        );
+        let strip = (
+            fixed_width.unwrap_or(result.solution.strip_width() as f64),
+            result.instance.base_strip.fixed_height as f64,
+        );
+        Some(filler::place_fillers(
+            &config.filler_items,
+            &obstacles,
+            &config.keep_out_zones,
+            config.container_polygon.as_ref(),
+            strip,
+            config.cut_separation(),
+            config.max_filler_count,
+            config
+                .max_filler_area
+                .unwrap_or(DEFAULT_MAX_FILLER_SHARE * strip.0 * strip.1),
+        ))
+    };
+
     // Convert to serializable output
+    let serialize_span = StageSpan::start();
     let mut output = NestingOutput::from_solution(
         &result.solution,
         &result.instance,
+        &hole_layout,
+        &result.orientations,
         result.ext_instance.name.clone(),
         result.computation_time,
     );
+    small_parts::attach_post_placed(&mut output, &small_layout);
+    if let Some(filler_layout) = &filler_layout {
+        filler::attach_fillers(&mut output, filler_layout);
+    }
+    if let Some(fixed_width) = fixed_width {
+        let overflow_area = overflow
+            .iter()
+            .map(|&id| variant_net_area(&instance, mirror_variants.as_ref(), id))
+            .sum();
+        sheet_mode::apply_fixed_sheet(&mut output, fixed_width, &overflow, overflow_area);
+    }
+    // Copies for the next sheet, by the item IDs nested
+    let mut leftover = output.unplaced_item_ids.clone();
+
+    // Record everything needed to replay this run exactly
+    if config.seed.is_none() {
+        config_trace.runtime("seed", &result.seed);
+    }
+    output.seed = Some(result.seed);
+    output.resolved_config = Some(NestingConfig {
+        seed: Some(result.seed),
+        warm_start: None,
+        ..config.clone()
+    });
+    output.warm_started = result.warm_started;
+    output.instance_hash = fingerprint_instance(&input.json_input)
+        .ok()
+        .map(|fingerprint| fingerprint.instance_hash);
+    output.engine_version = Some(ENGINE_VERSION.to_string());
+    output.app_version = Some(APP_VERSION.to_string());
+    output.algorithm_fingerprint = Some(result.algorithm_fingerprint.clone());
+    output.quality_settings = Some(config.quality.settings());
+    output.stats = Some(result.resources.clone());
+    output.min_web_violations = min_web_violations;
+    output.phase_stats = Some(phase_stats);
+    spacing::report_original_positions(&mut output.layouts, &result.spacing_plan);
+    output.kerf_offset =
+        (result.spacing_plan.kerf_offset > 0.0).then_some(result.spacing_plan.kerf_offset);
+    if let Some(orientation) = &pre_orientation {
+        pre_orient::restore_base_rotations(&mut output.layouts, orientation);
+        output.base_rotations = orientation.rotations.clone();
+    }
+    // Undone in reverse: variants are the moved parts mirrored, flagged
+    // before their origin is restored; mirror pairs refer to the drawing
+    if let Some(variants) = &mirror_variants {
+        mirror::restore_mirrored_variants(&mut output, &variants.variants);
+    }
+    if let Some(shift) = &origin_shift {
+        origin::restore_origin(&mut output.layouts, shift.offset);
+        output.origin_offset = Some(shift.offset);
+    }
+    if let Some(merge) = &mirror_merge {
+        mirror::restore_mirrored_placements(&mut output, &merge.pairs);
+    }
+    output.mirror_pairs = mirror_pairs;
+    locked::attach_locked(&mut output, &locked_parts);
+    output.attach_item_metadata(&instance.items);
+    grain::attach_bend_angles(&mut output.layouts, &instance.items);
+    output.repeated_sheet = chunk_plan.map(|plan| {
+        let mut sheet = plan.sheet;
+        grain::attach_bend_angles(&mut sheet.layouts, &instance.items);
+        sheet
+    });
+
+    // Utilization relative to the area actually usable: the remnant
+    // rather than its bounding box, less keep-out and defect zones
+    if !config.keep_out_zones.is_empty()
+        || !config.holes.is_empty()
+        || config.container_polygon.is_some()
+    {
+        let strip_area = output.strip_width * output.strip_height;
+        let (width, height) = (output.strip_width, output.strip_height);
+        let blocked_area = keep_out::blocked_area(&config.keep_out_zones, width, height)
+            + defects::blocked_area(&config.holes, width, height);
+        let usable_area = config
+            .container_polygon
+            .as_ref()
+            .map_or(strip_area, ContainerPolygon::area)
+            - blocked_area;
+        if usable_area > 0.0 {
+            output.utilization *= strip_area / usable_area;
+        }
+        output.usable_area = Some(usable_area);
+        output.keep_out_zones = config.keep_out_zones.clone();
+        output.keep_out_conflicts = keep_out_conflicts;
+        output.holes = config.holes.clone();
+        output.defect_conflicts = defect_conflicts;
+        output.container_polygon = config.container_polygon.clone();
+    }
+
+    // Parts left off a fixed sheet are nested onto further sheets; a
+    // remnant is a single piece
+    let more_sheets = max_sheets > 1 && config.container_polygon.is_none();
+    if let Some(fixed_width) = fixed_width.filter(|_| more_sheets) {
+        let (svg_string, _) = generate_svg(&result, input.fit_svg_viewbox.unwrap_or(false), || {
+            serializer::placed_polygons(&result.solution)
+        });
+        output.sheets = vec![SheetLayout::from_output(0, &output, svg_string)];
+        let overflow_sheets = OverflowSheets {
+            config: &config,
+            instance: &instance,
+            fixed_width,
+            mirror_merge: mirror_merge.as_ref(),
+            mirror_variants: mirror_variants.as_ref(),
+            pre_orientation: pre_orientation.as_ref(),
+            origin_shift: origin_shift.as_ref(),
+            locked_zones: &locked_zones,
+            fit_svg_viewbox: input.fit_svg_viewbox.unwrap_or(false),
+        };
+        while output.sheets.len() < max_sheets {
+            // Cancelled while nesting the previous sheet; the first sheet
+            // running to its time limit is no reason to stop
+            if terminator.is_cancelled() {
+                break;
+            }
+            let Some(json) = sheet_mode::overflow_instance(nest_json, &leftover)? else {
+                break;
+            };
+            let (sheet, next_leftover) =
+                overflow_sheets.nest(&json, output.sheets.len(), &terminator)?;
+            // Parts larger than the sheet never fit
+            if sheet.total_items_placed == 0 {
+                break;
+            }
+            output.sheets.push(sheet);
+            leftover = next_leftover;
+        }
+        println!(
+            "📄 Nested onto {} sheets, {} parts left over",
+            output.sheets.len(),
+            leftover.len()
+        );
+    }
+
+    cut_length::attach_cut_totals(&mut output, &instance.items);
+
+    // A layout far longer than the parts need usually means a bad setting;
+    // a fixed sheet is as long as it is
+    output.sanity_warning = if fixed_width.is_some() {
+        None
+    } else {
+        check_against_estimate(
+            nest_instance,
+            output.strip_width,
+            config.separation,
+            &config.keep_out_zones,
+            sanity_factor,
+        )
+    };
+    if let Some(warning) = &output.sanity_warning {
+        println!("⚠️ {}", warning.message);
+    }
+    output.suggestions = analyze_run(&output, &config, nest_instance, kerf);
+    for suggestion in &output.suggestions {
+        println!("💡 {}", suggestion.message);
+    }
+    output.input_warnings = input_warnings.iter().map(|issue| issue.to_string()).collect();
+    if cancelled {
+        println!("🛑 Nesting cancelled, returning the best layout so far");
+        output.status = Some("cancelled".to_string());
+    } else if target_reached_secs.is_some() {
+        output.status = Some("target_reached".to_string());
+        output.target_reached_secs = target_reached_secs;
+    }
+
+    if input.debug_trace.unwrap_or(false) {
+        println!("🧭 Config resolution:\n{}", config_trace.explain());
+        output.config_trace = Some(config_trace);
+    }
+
+    let mut stage_timings = result.stage_timings.clone();
+    serialize_span.record(&mut stage_timings.serialize_secs);
+    output.stage_timings = Some(stage_timings);
+
+    // Heavy fields (outlines, heatmap, SVG) are derived from the run later
+    let display_tolerance = input
+        .display_tolerance
+        .unwrap_or(DEFAULT_DISPLAY_TOLERANCE)
+        .max(0.0);
+    let run = NestingRun::new(
+        result,
+        hole_layout,
+        small_layout,
+        filler_layout.unwrap_or_default(),
+        config.keep_out_zones,
+        config.holes,
+        config.container_polygon,
+        locked_parts,
+        &output,
+        input.include_outlines.unwrap_or(false),
+        display_tolerance,
+        input.utilization_grid,
+        input.heatmap_overlay.unwrap_or(false),
+        input.clearance_overlay.unwrap_or(false),
+        input.kerf_overlay.unwrap_or(false),
+        input.fit_svg_viewbox.unwrap_or(false),
+    );
 
-    // Generate SVG visualization
-    let svg_string = generate_svg(&result);
-    output.svg_string = Some(svg_string);
+    // Collisions were resolved on simplified shapes and several passes
+    // place parts outside the optimizer: check the contours that get cut
+    let outlines: Vec<Polygon> = run
+        .derive(&[DerivedField::Outlines])
+        .outlines
+        .unwrap_or_default()
+        .into_iter()
+        .map(|outlines| outlines.outline)
+        .collect();
+    match verify::verify_layout(&output, &outlines, &instance) {
+        Ok(violations) => output.violations = violations,
+        Err(e) => println!("⚠️ Could not verify the layout: {}", e),
+    }
+    if !output.violations.is_empty() {
+        println!(
+            "⚠️ {} placements fail the layout check",
+            output.violations.len()
+        );
+    }
 
     info!(
         "Nesting completed: {} items placed in {:.2}s",
@@ -116,42 +1405,267 @@ pub fn run_nesting_engine(input: NestingInput) -> Result<NestingOutput, String>
         output.computation_time_secs
     );
 
-    Ok(output)
+    Ok((output, run))
 }
 
-/// Initialize the logger (call once at startup)
-fn init_logger() -> Result<(), log::SetLoggerError> {
-    use std::sync::Once;
-    static INIT: Once = Once::new();
+/// Split the demand items share with their mirror image variants
+///
+/// Nests the instance with the full demand on both for
+/// `mirror::MIRROR_PROBE_SHARE` of the time limit (at least a second).
+fn probe_mirrored_copies(
+    variants: mirror::MirrorVariants,
+    config: &NestingConfig,
+    terminator: &NativeTerminator,
+) -> Result<mirror::MirrorVariants, String> {
+    let probe_secs = config.time_limit.map_or(1, |time_limit| {
+        ((time_limit as f64 * mirror::MIRROR_PROBE_SHARE) as u64).max(1)
+    });
+    let probe_config = NestingConfig {
+        time_limit: Some(probe_secs),
+        skip_compression: true,
+        sheet_mode: SheetMode::Strip,
+        warm_start: None,
+        ..config.clone()
+    };
+    let mut probe_terminator = terminator.child();
+    probe_terminator.new_timeout(Duration::from_secs(probe_secs));
+    let mut listener = RunListener {
+        phases: PhaseTracker::new(),
+        preview: None,
+        progress: None,
+        layout_svg: None,
+    };
+    let result = run_nesting(
+        &variants.json,
+        &probe_config,
+        &mut listener,
+        &mut probe_terminator,
+    )
+    .map_err(|e| format!("Mirror probe failed: {}", e))?;
+    let hole_layout = holes::resolve_hole_layout(&result.hole_plan, &result.solution);
+    let probe = NestingOutput::from_solution(
+        &result.solution,
+        &result.instance,
+        &hole_layout,
+        &result.orientations,
+        result.ext_instance.name.clone(),
+        result.computation_time,
+    );
+    mirror::choose_mirrored_copies(&variants, &probe.layouts)
+}
 
-    let mut result = Ok(());
+/// Net area of a nested item, mirror image variants have the area of their item
+fn variant_net_area(
+    instance: &InstanceGeometry,
+    mirror_variants: Option<&mirror::MirrorVariants>,
+    id: usize,
+) -> f64 {
+    let id = mirror_variants
+        .and_then(|variants| variants.variants.iter().find(|&&(variant, _)| variant == id))
+        .map_or(id, |&(_, original)| original);
+    instance
+        .items
+        .iter()
+        .find(|item| item.id as usize == id)
+        .map_or(0.0, InstanceItem::net_area)
+}
 
-    INIT.call_once(|| {
-        result = fern::Dispatch::new()
-            .format(|out, message, record| {
-                out.finish(format_args!(
-                    "[{}][{}] {}",
-                    record.level(),
-                    record.target(),
-                    message
-                ))
-            })
-            .level(log::LevelFilter::Info)
-            .chain(std::io::stdout())
-            .apply();
-    });
+/// Settings and instance changes of the first sheet, reused for the
+/// sheets after it
+///
+/// Every sheet is set up like the first: keep-out, defect and locked
+/// zones are enforced and fillers go into the gaps. Each sheet is nested
+/// for the full time limit.
+struct OverflowSheets<'a> {
+    config: &'a NestingConfig,
+    instance: &'a InstanceGeometry,
+    fixed_width: f64,
+    mirror_merge: Option<&'a mirror::MirrorMerge>,
+    mirror_variants: Option<&'a mirror::MirrorVariants>,
+    pre_orientation: Option<&'a pre_orient::PreOrientation>,
+    origin_shift: Option<&'a origin::OriginShift>,
+    /// Clearance zones of the locked parts
+    locked_zones: &'a [Polygon],
+    fit_svg_viewbox: bool,
+}
+
+impl OverflowSheets<'_> {
+    /// Nest the copies left off the previous sheet onto sheet `sheet_index`
+    ///
+    /// # Returns
+    /// The sheet, and the copies left off it by the item IDs nested
+    fn nest(
+        &self,
+        json: &str,
+        sheet_index: usize,
+        terminator: &NativeTerminator,
+    ) -> Result<(SheetLayout, Vec<usize>), String> {
+        let config = self.config;
+        let mut listener = RunListener {
+            phases: PhaseTracker::new(),
+            preview: None,
+            progress: None,
+            layout_svg: None,
+        };
+        // The deadline of the sheet before has passed already
+        let mut sheet_terminator = terminator.child();
+        if let Some(time_limit) = config.time_limit {
+            sheet_terminator.new_timeout(Duration::from_secs(time_limit));
+        }
+        let mut result = run_nesting(json, config, &mut listener, &mut sheet_terminator)
+            .map_err(|e| format!("Nesting sheet {} failed: {}", sheet_index + 1, e))?;
+
+        let zones = &config.keep_out_zones;
+        keep_out::evict_conflicting_items(&mut result.solution, zones);
+        defects::evict_defect_overlaps(&mut result.solution, &config.holes);
+        defects::evict_defect_overlaps(&mut result.solution, self.locked_zones);
+        let height = result.instance.base_strip.fixed_height as f64;
+        let overflow = sheet_mode::evict_overflow(&mut result.solution, self.fixed_width, height);
+        let mut hole_layout = holes::resolve_hole_layout(&result.hole_plan, &result.solution);
+        hole_layout.placements.retain(|placement| {
+            !zones.iter().any(|zone| zone.overlaps(&placement.outline))
+                && !defects::overlaps_any(&config.holes, &placement.outline)
+                && !defects::overlaps_any(self.locked_zones, &placement.outline)
+        });
+        let strip_width = result.solution.strip_width() as f64;
+        // Defect zones and locked parts are obstacles like the packed parts
+        let mut obstacles = serializer::placed_polygons(&result.solution);
+        obstacles.extend(config.holes.iter().cloned());
+        obstacles.extend(self.locked_zones.iter().cloned());
+        let small_layout = small_parts::place_small_parts(
+            &result.small_part_plan,
+            &obstacles,
+            &hole_layout,
+            zones,
+            (self.fixed_width.min(strip_width), height),
+            config.cut_separation(),
+        );
+        let filler_layout = if config.filler_items.is_empty() {
+            None
+        } else {
+            obstacles.extend(
+                small_layout
+                    .placements
+                    .iter()
+                    .map(|placement| placement.outline.clone()),
+            );
+            let strip = (self.fixed_width, height);
+            Some(filler::place_fillers(
+                &config.filler_items,
+                &obstacles,
+                zones,
+                None,
+                strip,
+                config.cut_separation(),
+                config.max_filler_count,
+                config
+                    .max_filler_area
+                    .unwrap_or(DEFAULT_MAX_FILLER_SHARE * strip.0 * strip.1),
+            ))
+        };
 
-    result
+        let mut output = NestingOutput::from_solution(
+            &result.solution,
+            &result.instance,
+            &hole_layout,
+            &result.orientations,
+            result.ext_instance.name.clone(),
+            result.computation_time,
+        );
+        small_parts::attach_post_placed(&mut output, &small_layout);
+        if let Some(filler_layout) = &filler_layout {
+            filler::attach_fillers(&mut output, filler_layout);
+        }
+        let overflow_area = overflow
+            .iter()
+            .map(|&id| variant_net_area(self.instance, self.mirror_variants, id))
+            .sum();
+        sheet_mode::apply_fixed_sheet(&mut output, self.fixed_width, &overflow, overflow_area);
+        let leftover = output.unplaced_item_ids.clone();
+
+        spacing::report_original_positions(&mut output.layouts, &result.spacing_plan);
+        output.kerf_offset =
+            (result.spacing_plan.kerf_offset > 0.0).then_some(result.spacing_plan.kerf_offset);
+        if let Some(orientation) = self.pre_orientation {
+            pre_orient::restore_base_rotations(&mut output.layouts, orientation);
+        }
+        if let Some(variants) = self.mirror_variants {
+            mirror::restore_mirrored_variants(&mut output, &variants.variants);
+        }
+        if let Some(shift) = self.origin_shift {
+            origin::restore_origin(&mut output.layouts, shift.offset);
+        }
+        if let Some(merge) = self.mirror_merge {
+            mirror::restore_mirrored_placements(&mut output, &merge.pairs);
+        }
+        output.attach_item_metadata(&self.instance.items);
+        grain::attach_bend_angles(&mut output.layouts, &self.instance.items);
+        if !zones.is_empty() || !config.holes.is_empty() {
+            let sheet_area = output.strip_width * output.strip_height;
+            let usable_area = sheet_area
+                - keep_out::blocked_area(zones, output.strip_width, output.strip_height)
+                - defects::blocked_area(&config.holes, output.strip_width, output.strip_height);
+            if usable_area > 0.0 {
+                output.utilization *= sheet_area / usable_area;
+            }
+        }
+
+        let (svg_string, _) = generate_svg(&result, self.fit_svg_viewbox, || {
+            serializer::placed_polygons(&result.solution)
+        });
+        println!(
+            "📄 Sheet {}: {} parts at {:.1}% utilization",
+            sheet_index + 1,
+            output.total_items_placed,
+            output.utilization * 100.0
+        );
+        Ok((
+            SheetLayout::from_output(sheet_index, &output, svg_string),
+            leftover,
+        ))
+    }
+}
+
+/// Listener of a run: phase tracking, the optional live preview and progress
+struct RunListener<'a> {
+    phases: PhaseTracker,
+    preview: Option<preview::PreviewListener<'a>>,
+    progress: Option<&'a mut dyn FnMut(ProgressReport)>,
+    layout_svg: Option<layout_stream::LayoutSvgListener<'a>>,
+}
+
+impl SolutionListener for RunListener<'_> {
+    fn report(&mut self, report_type: ReportType, solution: &SPSolution, instance: &SPInstance) {
+        self.phases.record(&report_type, solution);
+        if let Some(progress) = self.progress.as_mut() {
+            progress(self.phases.progress(&report_type, solution));
+        }
+        if let Some(layout_svg) = self.layout_svg.as_mut() {
+            layout_svg.record(&report_type, solution, instance);
+        }
+        if let Some(preview) = self.preview.as_mut() {
+            preview.report(report_type, solution, instance);
+        }
+    }
 }
 
 /// Generate SVG visualization of the nesting result
 ///
 /// # Arguments
 /// * `result` - The nesting result from `run_nesting`
+/// * `fit_viewbox` - Compute the viewBox from the strip and the placements
+///   instead of widening the rendered one
+/// * `placements` - Outlines of all placements, only called to fit the
+///   viewBox or to rebuild it when the rendered one cannot be parsed
 ///
 /// # Returns
-/// SVG string that can be displayed in frontend
-pub fn generate_svg(result: &NestingResult) -> String {
+/// SVG string that can be displayed in frontend, and a warning if its
+/// viewBox had to be rebuilt
+pub fn generate_svg(
+    result: &NestingResult,
+    fit_viewbox: bool,
+    placements: impl FnOnce() -> Vec<geometry::Polygon>,
+) -> (String, Option<SvgWarning>) {
     use jagua_rs::io::svg::s_layout_to_svg;
     use sparrow::consts::DRAW_OPTIONS;
 
@@ -164,51 +1678,25 @@ pub fn generate_svg(result: &NestingResult) -> String {
 
     let svg_string = svg.to_string();
 
+    if fit_viewbox {
+        let strip = (
+            result.solution.strip_width() as f64,
+            result.instance.base_strip.fixed_height as f64,
+        );
+        return fit_svg_viewbox(&svg_string, VIEWBOX_MARGIN, strip, &placements());
+    }
+
     // Post-process SVG to add margin to viewBox
     // This fixes the issue where items at the edge of the strip get clipped
-    expand_svg_viewbox(&svg_string, 50.0)
+    expand_svg_viewbox(&svg_string, VIEWBOX_MARGIN, placements)
 }
 
-/// Expand SVG viewBox to add margin around the content
+/// Insert an SVG fragment as the last element of an SVG document
 ///
-/// This fixes the visual issue where items placed at the edge of the strip
-/// appear to be cut off in the SVG rendering.
-fn expand_svg_viewbox(svg: &str, margin: f64) -> String {
-    use regex::Regex;
-
-    // Match viewBox="minX minY width height"
-    let viewbox_re = Regex::new(r#"viewBox="([^"]+)""#).unwrap();
-
-    if let Some(caps) = viewbox_re.captures(svg) {
-        let viewbox_str = &caps[1];
-        let parts: Vec<&str> = viewbox_str.split_whitespace().collect();
-
-        if parts.len() == 4 {
-            if let (Ok(min_x), Ok(min_y), Ok(width), Ok(height)) = (
-                parts[0].parse::<f64>(),
-                parts[1].parse::<f64>(),
-                parts[2].parse::<f64>(),
-                parts[3].parse::<f64>(),
-            ) {
-                // Expand viewBox by margin on all sides
-                let new_min_x = min_x - margin;
-                let new_min_y = min_y - margin;
-                let new_width = width + 2.0 * margin;
-                let new_height = height + 2.0 * margin;
-
-                let new_viewbox = format!(
-                    "viewBox=\"{} {} {} {}\"",
-                    new_min_x, new_min_y, new_width, new_height
-                );
-
-                println!("📐 Expanded viewBox: {} → {}", viewbox_str,
-                    format!("{} {} {} {}", new_min_x, new_min_y, new_width, new_height));
-
-                return viewbox_re.replace(svg, new_viewbox.as_str()).to_string();
-            }
-        }
+/// Used for overlays drawn on top of the jagua-rs layout rendering.
+fn append_svg_overlay(svg: &str, overlay: &str) -> String {
+    match svg.rfind("</svg>") {
+        Some(pos) => format!("{}{}{}", &svg[..pos], overlay, &svg[pos..]),
+        None => svg.to_string(),
     }
-
-    // Return original if we couldn't parse viewBox
-    svg.to_string()
 }
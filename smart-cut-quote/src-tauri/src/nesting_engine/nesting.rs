@@ -3,6 +3,21 @@
 //! This module contains the core optimization algorithm extracted from sparrow.
 //! It is kept separate to maintain algorithm stability and testability.
 
+use super::filler::{FillerItem, DEFAULT_MAX_FILLER_COUNT};
+use super::geometry::{DisplayUnits, Polygon};
+use super::grain::{self, BendPlan, DEFAULT_BEND_ANGLE_TOLERANCE};
+use super::holes::{self, HolePlan};
+use super::instance::parse_instance;
+use super::keep_out::KeepOutZone;
+use super::phases::OptimizerPhase;
+use super::quality::Quality;
+use super::remnant::ContainerPolygon;
+use super::resources::{self, PeakRssSampler, ResourceUsage};
+use super::sheet_mode::SheetMode;
+use super::small_parts::{self, SmallPartPlan, DEFAULT_SMALL_ITEM_AREA};
+use super::spacing::{self, SpacingPlan};
+use super::stats::{StageSpan, StageTimings};
+use super::warm_start::{self, WarmStart};
 use anyhow::{Context, Result};
 use jagua_rs::io::import::Importer;
 use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
@@ -18,10 +33,20 @@ use sparrow::consts::{
 use sparrow::optimizer::optimize;
 use sparrow::util::listener::SolutionListener;
 use sparrow::util::terminator::Terminator;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::time::Duration;
 
+/// Minimum distance between items and from the strip boundary when the
+/// configuration does not set one (mm)
+pub const DEFAULT_ITEM_SEPARATION: f64 = 1.0;
+
+/// Hex characters kept of the algorithm fingerprint hash
+const ALGORITHM_FINGERPRINT_LEN: usize = 12;
+
 /// Configuration for nesting optimization
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
 pub struct NestingConfig {
     /// Time limit in seconds (default: 300 = 5 minutes)
     pub time_limit: Option<u64>,
@@ -31,6 +56,91 @@ pub struct NestingConfig {
     pub use_early_termination: bool,
     /// Number of worker threads
     pub n_workers: usize,
+    /// Areas of the sheet where no part may be placed
+    #[serde(default)]
+    pub keep_out_zones: Vec<KeepOutZone>,
+    /// Defect zones of the sheet no part may overlap (sheet coordinates)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub holes: Vec<Polygon>,
+    /// Place small parts inside the holes of larger parts before packing
+    #[serde(default)]
+    pub hole_nesting: bool,
+    /// Minimum distance between items and from the strip boundary (mm),
+    /// passed to sparrow as `min_item_separation`
+    #[serde(default = "default_separation", alias = "min_item_separation")]
+    pub separation: f64,
+    /// Width of the cut (mm); every part outline is grown by half of it
+    /// before nesting, so the separation is kept between the cut paths
+    #[serde(default)]
+    pub kerf_width: f64,
+    /// Orientations allowed for every item in degrees, replacing the
+    /// instance's (None = as in the instance)
+    #[serde(
+        default,
+        alias = "allowed_rotations",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub rotations: Option<Vec<f64>>,
+    /// Keep the bend lines of bent parts along the strip's X axis
+    #[serde(default)]
+    pub respect_grain: bool,
+    /// Max angle between bend lines and the X axis with `respect_grain` (degrees)
+    #[serde(default = "default_bend_angle_tolerance")]
+    pub bend_angle_tolerance: f64,
+    /// Turn free items to their minimum-area bounding rectangle before
+    /// nesting (see `pre_orient`)
+    #[serde(default)]
+    pub pre_orient_items: bool,
+    /// Net area below which parts skip the optimizer and are placed
+    /// after packing (mm², 0 = all parts are packed)
+    #[serde(default = "default_small_item_area")]
+    pub small_item_area: f64,
+    /// Stock parts dropped into the gaps after nesting (see `filler`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filler_items: Vec<FillerItem>,
+    /// Most fillers placed
+    #[serde(default = "default_max_filler_count")]
+    pub max_filler_count: usize,
+    /// Most net area of all fillers (mm², None = DEFAULT_MAX_FILLER_SHARE
+    /// of the sheet)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_filler_area: Option<f64>,
+    /// Growing strip or one fixed sheet (parts that do not fit are
+    /// left unplaced)
+    #[serde(default)]
+    pub sheet_mode: SheetMode,
+    /// Give the whole time limit to exploration, skipping compression
+    #[serde(default)]
+    pub skip_compression: bool,
+    /// Only compress the warm start layout, without exploring first
+    #[serde(default)]
+    pub compress_only: bool,
+    /// Remnant nested into instead of the strip, moved to the origin
+    #[serde(default)]
+    pub container_polygon: Option<ContainerPolygon>,
+    /// Separator preset trading response time for strip length
+    #[serde(default)]
+    pub quality: Quality,
+    /// Layout to continue from instead of starting from scratch, in
+    /// optimizer coordinates (run input only, never stored)
+    #[serde(skip)]
+    pub warm_start: Option<WarmStart>,
+}
+
+fn default_separation() -> f64 {
+    DEFAULT_ITEM_SEPARATION
+}
+
+fn default_bend_angle_tolerance() -> f64 {
+    DEFAULT_BEND_ANGLE_TOLERANCE
+}
+
+fn default_small_item_area() -> f64 {
+    DEFAULT_SMALL_ITEM_AREA
+}
+
+fn default_max_filler_count() -> usize {
+    DEFAULT_MAX_FILLER_COUNT
 }
 
 impl Default for NestingConfig {
@@ -40,6 +150,68 @@ impl Default for NestingConfig {
             seed: None,
             use_early_termination: false,
             n_workers: 1,
+            keep_out_zones: Vec::new(),
+            holes: Vec::new(),
+            hole_nesting: false,
+            separation: DEFAULT_ITEM_SEPARATION,
+            kerf_width: 0.0,
+            rotations: None,
+            respect_grain: false,
+            bend_angle_tolerance: DEFAULT_BEND_ANGLE_TOLERANCE,
+            pre_orient_items: false,
+            small_item_area: DEFAULT_SMALL_ITEM_AREA,
+            filler_items: Vec::new(),
+            max_filler_count: DEFAULT_MAX_FILLER_COUNT,
+            max_filler_area: None,
+            sheet_mode: SheetMode::Strip,
+            skip_compression: false,
+            compress_only: false,
+            container_polygon: None,
+            quality: Quality::Fast,
+            warm_start: None,
+        }
+    }
+}
+
+impl NestingConfig {
+    /// Distance kept between nominal part outlines, the separation
+    /// between their cut paths plus the kerf
+    pub fn cut_separation(&self) -> f64 {
+        self.separation + self.kerf_width
+    }
+
+    /// Settings given in `from` units, in `units` (display only, runs use mm)
+    pub fn in_units(self, units: DisplayUnits, from: DisplayUnits) -> Self {
+        NestingConfig {
+            separation: units.length_from(self.separation, from),
+            kerf_width: units.length_from(self.kerf_width, from),
+            small_item_area: units.area_from(self.small_item_area, from),
+            filler_items: self
+                .filler_items
+                .into_iter()
+                .map(|filler| filler.in_units(units, from))
+                .collect(),
+            max_filler_area: self.max_filler_area.map(|area| units.area_from(area, from)),
+            keep_out_zones: self
+                .keep_out_zones
+                .into_iter()
+                .map(|zone| zone.in_units(units, from))
+                .collect(),
+            holes: self
+                .holes
+                .iter()
+                .map(|hole| units.polygon_from(hole, from))
+                .collect(),
+            sheet_mode: match self.sheet_mode {
+                SheetMode::Strip => SheetMode::Strip,
+                SheetMode::FixedSheet { fixed_width } => SheetMode::FixedSheet {
+                    fixed_width: units.length_from(fixed_width, from),
+                },
+            },
+            container_polygon: self
+                .container_polygon
+                .map(|container| container.in_units(units, from)),
+            ..self
         }
     }
 }
@@ -54,6 +226,92 @@ pub struct NestingResult {
     pub ext_instance: ExtSPInstance,
     /// Total computation time
     pub computation_time: Duration,
+    /// Seed actually used (generated when none was provided)
+    pub seed: u64,
+    /// Time spent in parse, import and optimize stages
+    pub stage_timings: StageTimings,
+    /// Preserved holes and parts planned inside them
+    pub hole_plan: HolePlan,
+    /// Items grown for their extra clearance
+    pub spacing_plan: SpacingPlan,
+    /// Bend lines of bent items
+    pub bend_plan: BendPlan,
+    /// Orientations of the items restricted to a discrete set, as imported
+    pub orientations: HashMap<usize, Vec<f64>>,
+    /// Parts left to the post-pass, too small for the optimizer
+    pub small_part_plan: SmallPartPlan,
+    /// Memory and CPU usage around import and optimization
+    pub resources: ResourceUsage,
+    /// Hash of the optimizer settings (see `algorithm_fingerprint`)
+    pub algorithm_fingerprint: String,
+    /// Continued from `NestingConfig::warm_start` instead of starting from scratch
+    pub warm_started: bool,
+    /// Optimizer phases run, in order
+    pub phases_run: Vec<OptimizerPhase>,
+}
+
+/// Sparrow configuration used for a run
+///
+/// Starts from sparrow's defaults and applies the quoting tweaks
+/// (item separation, separator limits of the quality preset, optional
+/// early termination).
+pub fn resolve_sparrow_config(config: &NestingConfig) -> SparrowConfig {
+    let mut sparrow_config = DEFAULT_SPARROW_CONFIG;
+
+    // Set minimum item separation to prevent items from touching edges
+    // This creates a buffer zone around each item and from strip boundaries
+    // The value is in the same units as the input (mm)
+    sparrow_config.min_item_separation = Some(config.separation as f32);
+
+    let time_limit = Duration::from_secs(config.time_limit.unwrap_or(600));
+    sparrow_config.expl_cfg.time_limit = time_limit.mul_f32(DEFAULT_EXPLORE_TIME_RATIO);
+    sparrow_config.cmpr_cfg.time_limit = time_limit.mul_f32(DEFAULT_COMPRESS_TIME_RATIO);
+    if config.skip_compression {
+        sparrow_config.expl_cfg.time_limit = time_limit;
+        sparrow_config.cmpr_cfg.time_limit = Duration::ZERO;
+    }
+
+    sparrow_config.expl_cfg.separator_config.n_workers = config.n_workers;
+    sparrow_config.cmpr_cfg.separator_config.n_workers = config.n_workers;
+
+    config.quality.apply(&mut sparrow_config);
+
+    if config.use_early_termination {
+        sparrow_config.expl_cfg.max_conseq_failed_attempts =
+            Some(DEFAULT_MAX_CONSEQ_FAILS_EXPL);
+        sparrow_config.cmpr_cfg.shrink_decay =
+            ShrinkDecayStrategy::FailureBased(DEFAULT_FAIL_DECAY_RATIO_CMPR);
+    }
+
+    sparrow_config
+}
+
+/// Importer turning instance items into jagua-rs items for a run
+pub fn instance_importer(config: &NestingConfig) -> Importer {
+    let sparrow_config = resolve_sparrow_config(config);
+    Importer::new(
+        sparrow_config.cde_config,
+        sparrow_config.poly_simpl_tolerance,
+        sparrow_config.min_item_separation,
+        sparrow_config.narrow_concavity_cutoff_ratio,
+    )
+}
+
+/// Short hash identifying the optimizer settings of a run
+///
+/// Hashes the resolved sparrow config, leaving out time budget and
+/// worker count: they are recorded in the resolved config and changing
+/// them does not change the algorithm. Outputs with different
+/// fingerprints are not directly comparable.
+pub fn algorithm_fingerprint(config: &NestingConfig) -> String {
+    let sparrow_config = resolve_sparrow_config(&NestingConfig {
+        time_limit: Some(0),
+        n_workers: 0,
+        ..config.clone()
+    });
+
+    let digest = Sha256::digest(format!("{:?}", sparrow_config).as_bytes());
+    format!("{:x}", digest)[..ALGORITHM_FINGERPRINT_LEN].to_string()
 }
 
 /// Core nesting function - platform-agnostic
@@ -80,46 +338,75 @@ pub fn run_nesting<L: SolutionListener, T: Terminator>(
 
     info!("Started nesting optimization");
 
+    let mut stage_timings = StageTimings::default();
+
     // Parse input JSON
+    let parse_span = StageSpan::start();
+    let json_str = match &config.rotations {
+        Some(rotations) => {
+            override_orientations(json_str, rotations).map_err(anyhow::Error::msg)?
+        }
+        None => json_str.to_string(),
+    };
+    let (json_str, bend_plan) =
+        grain::constrain_instance(&json_str, config.respect_grain, config.bend_angle_tolerance)
+            .map_err(anyhow::Error::msg)?;
+    let orientations = restricted_orientations(&json_str).map_err(anyhow::Error::msg)?;
+    let (prepared_json, hole_plan) =
+        holes::prepare_instance(&json_str, config.hole_nesting, config.cut_separation())
+            .map_err(anyhow::Error::msg)?;
+    let (prepared_json, small_part_plan) =
+        small_parts::prepare_instance(&json_str, &prepared_json, config.small_item_area)
+            .map_err(anyhow::Error::msg)?;
+    let (json_str, spacing_plan) =
+        spacing::grow_instance(&json_str, &prepared_json, config.kerf_width)
+            .map_err(anyhow::Error::msg)?;
+    if spacing_plan.kerf_offset > 0.0 {
+        println!(
+            "✂️ Part outlines grown by {:.3} mm for the kerf",
+            spacing_plan.kerf_offset
+        );
+    } else if !spacing_plan.items.is_empty() {
+        println!(
+            "📏 {} parts keep extra clearance around them",
+            spacing_plan.items.len()
+        );
+    }
+    if !hole_plan.host_fills.is_empty() {
+        println!(
+            "🕳️ Planned {} parts inside holes of {} host parts",
+            hole_plan.host_fills.iter().map(|h| h.fills.len()).sum::<usize>(),
+            hole_plan.host_fills.len()
+        );
+    }
+    let json_str = json_str.as_str();
     let ext_sp_instance: ExtSPInstance = serde_json::from_str(json_str)
         .map_err(|e| {
             // Log detailed error for debugging
             eprintln!("❌ JSON parsing error: {}", e);
-            eprintln!("❌ JSON input (first 500 chars): {}", &json_str[..json_str.len().min(500)]);
+            eprintln!(
+                "❌ JSON input (first 500 bytes): {}",
+                truncate_at_char_boundary(json_str, 500)
+            );
             e
         })
         .context("not a valid strip packing instance (ExtSPInstance)")?;
+    parse_span.record(&mut stage_timings.parse_secs);
 
     // Configure optimization parameters
-    let mut sparrow_config = DEFAULT_SPARROW_CONFIG;
-
-    // Set minimum item separation to prevent items from touching edges
-    // This creates a buffer zone around each item and from strip boundaries
-    // The value is in the same units as the input (mm)
-    sparrow_config.min_item_separation = Some(1.0); // 1mm separation
-
-    // DEBUG: Print the raw time_limit value
     println!("🔍 DEBUG: config.time_limit = {:?}", config.time_limit);
+    if config.time_limit.is_none() {
+        warn!("[MAIN] no time limit specified, using default 600s");
+        println!("⚠️ No time_limit specified, using default 600s");
+    }
+    let sparrow_config = resolve_sparrow_config(config);
+    let algorithm_fingerprint = algorithm_fingerprint(config);
     println!("🔍 DEBUG: min_item_separation = {:?}", sparrow_config.min_item_separation);
 
-    let (explore_dur, compress_dur) = match config.time_limit {
-        Some(time_limit) => {
-            println!("✅ Using user-specified time_limit: {}s", time_limit);
-            (
-                Duration::from_secs(time_limit).mul_f32(DEFAULT_EXPLORE_TIME_RATIO),
-                Duration::from_secs(time_limit).mul_f32(DEFAULT_COMPRESS_TIME_RATIO),
-            )
-        },
-        None => {
-            warn!("[MAIN] no time limit specified, using default 600s");
-            println!("⚠️ No time_limit specified, using default 600s");
-            (
-                Duration::from_secs(600).mul_f32(DEFAULT_EXPLORE_TIME_RATIO),
-                Duration::from_secs(600).mul_f32(DEFAULT_COMPRESS_TIME_RATIO),
-            )
-        }
-    };
-
+    let (explore_dur, compress_dur) = (
+        sparrow_config.expl_cfg.time_limit,
+        sparrow_config.cmpr_cfg.time_limit,
+    );
     println!(
         "📊 Phase durations: explore={}s, compress={}s (total={}s)",
         explore_dur.as_secs(),
@@ -131,48 +418,31 @@ pub fn run_nesting<L: SolutionListener, T: Terminator>(
         explore_dur.as_secs(),
         compress_dur.as_secs()
     );
-
-    sparrow_config.expl_cfg.time_limit = explore_dur;
-    sparrow_config.cmpr_cfg.time_limit = compress_dur;
-
-    sparrow_config.expl_cfg.separator_config.n_workers = config.n_workers;
-    sparrow_config.cmpr_cfg.separator_config.n_workers = config.n_workers;
-
-    // Optimize for speed over quality (for quoting purposes)
-    // Reduce the number of iterations in separator to respond faster to timeout
-    sparrow_config.expl_cfg.separator_config.strike_limit = 3; // Default is higher
-    sparrow_config.expl_cfg.separator_config.iter_no_imprv_limit = 50; // Default is higher
-    sparrow_config.cmpr_cfg.separator_config.strike_limit = 2;
-    sparrow_config.cmpr_cfg.separator_config.iter_no_imprv_limit = 25;
-
     if config.use_early_termination {
-        sparrow_config.expl_cfg.max_conseq_failed_attempts =
-            Some(DEFAULT_MAX_CONSEQ_FAILS_EXPL);
-        sparrow_config.cmpr_cfg.shrink_decay =
-            ShrinkDecayStrategy::FailureBased(DEFAULT_FAIL_DECAY_RATIO_CMPR);
         warn!("[MAIN] early termination enabled!");
     }
 
     // Setup random number generator
-    let rng = match config.seed {
+    // The resolved seed is returned so the run can be replayed later
+    let seed = match config.seed {
         Some(seed) => {
             info!("[MAIN] using seed: {}", seed);
-            Xoshiro256PlusPlus::seed_from_u64(seed)
+            seed
         }
         None => {
             let seed = rand::random();
             warn!("[MAIN] no seed provided, using: {}", seed);
-            Xoshiro256PlusPlus::seed_from_u64(seed)
+            seed
         }
     };
+    let rng = Xoshiro256PlusPlus::seed_from_u64(seed);
 
     // Import instance
-    let importer = Importer::new(
-        sparrow_config.cde_config,
-        sparrow_config.poly_simpl_tolerance,
-        sparrow_config.min_item_separation,
-        sparrow_config.narrow_concavity_cutoff_ratio,
-    );
+    // Holes never reach the importer (see `holes::prepare_instance`), so
+    // polygon simplification and the narrow concavity cutoff only ever see
+    // outer contours. A thin picture frame is imported as its solid outer
+    // rectangle instead of a narrow ring that the cutoff could collapse.
+    let importer = instance_importer(config);
 
     // Debug: Print item info before import
     println!("📦 Importing {} items:", ext_sp_instance.items.len());
@@ -181,12 +451,20 @@ pub fn run_nesting<L: SolutionListener, T: Terminator>(
             i, item.base.id, item.demand);
     }
 
+    let mut usage = ResourceUsage {
+        rss_before_import_bytes: resources::current_rss_bytes(),
+        ..Default::default()
+    };
+
+    let import_span = StageSpan::start();
     let instance = jagua_rs::probs::spp::io::import(&importer, &ext_sp_instance)
         .map_err(|e| {
             eprintln!("❌ Import error: {:?}", e);
             e
         })
         .context("Failed to import instance")?;
+    import_span.record(&mut stage_timings.import_secs);
+    usage.rss_after_import_bytes = resources::current_rss_bytes();
 
     info!(
         "[MAIN] loaded instance {} with #{} items",
@@ -195,14 +473,56 @@ pub fn run_nesting<L: SolutionListener, T: Terminator>(
     );
 
     // Run optimization
-    let solution = optimize(
-        instance.clone(),
-        rng,
-        listener,
-        terminator,
-        &sparrow_config.expl_cfg,
-        &sparrow_config.cmpr_cfg,
-    );
+    let optimize_span = StageSpan::start();
+    let rss_sampler = PeakRssSampler::start();
+    let cpu_before = resources::process_cpu_secs();
+    let warm_prob = config
+        .warm_start
+        .as_ref()
+        .and_then(|warm| warm_start::restore(&instance, warm));
+    if config.compress_only && warm_prob.is_none() {
+        return Err(anyhow::Error::msg(
+            "compress_only needs a warm start layout matching the instance",
+        ));
+    }
+    if config.warm_start.is_some() && warm_prob.is_none() {
+        println!("⚠️ Stored layout does not match the instance, nesting from scratch");
+    }
+    let warm_started = warm_prob.is_some();
+    let (solution, phases_run) = match warm_prob {
+        // Continuing a layout only compresses, for the whole time limit
+        Some(prob) => {
+            let cmpr_cfg = CompressionConfig {
+                time_limit: sparrow_config.expl_cfg.time_limit + sparrow_config.cmpr_cfg.time_limit,
+                ..sparrow_config.cmpr_cfg
+            };
+            (
+                warm_start::compress_from(&instance, prob, rng, listener, terminator, &cmpr_cfg),
+                vec![OptimizerPhase::Compress],
+            )
+        }
+        None => (
+            optimize(
+                instance.clone(),
+                rng,
+                listener,
+                terminator,
+                &sparrow_config.expl_cfg,
+                &sparrow_config.cmpr_cfg,
+            ),
+            if config.skip_compression {
+                vec![OptimizerPhase::Explore]
+            } else {
+                vec![OptimizerPhase::Explore, OptimizerPhase::Compress]
+            },
+        ),
+    };
+
+    optimize_span.record(&mut stage_timings.optimize_secs);
+    usage.optimize_cpu_secs = cpu_before
+        .zip(resources::process_cpu_secs())
+        .map(|(before, after)| after - before);
+    usage.peak_rss_bytes = rss_sampler.finish();
 
     let computation_time = start_time.elapsed();
 
@@ -216,5 +536,258 @@ pub fn run_nesting<L: SolutionListener, T: Terminator>(
         instance,
         ext_instance: ext_sp_instance,
         computation_time,
+        seed,
+        stage_timings,
+        hole_plan,
+        spacing_plan,
+        bend_plan,
+        orientations,
+        small_part_plan,
+        resources: usage,
+        algorithm_fingerprint,
+        warm_started,
+        phases_run,
     })
 }
+
+/// Replace the allowed orientations of every item in an instance JSON
+fn override_orientations(json_str: &str, rotations: &[f64]) -> Result<String, String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+        .ok_or("Instance JSON has no items array")?;
+
+    for item in items.iter_mut().filter_map(|item| item.as_object_mut()) {
+        item.insert("allowed_orientations".to_string(), rotations.into());
+    }
+
+    serde_json::to_string(&value).map_err(|e| format!("Failed to serialize instance: {}", e))
+}
+
+/// The longest prefix of `text` of at most `max_bytes` bytes that does
+/// not split a character (part names are often Vietnamese)
+fn truncate_at_char_boundary(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let end = (0..=max_bytes)
+        .rev()
+        .find(|&index| text.is_char_boundary(index))
+        .unwrap_or(0);
+    &text[..end]
+}
+
+/// Allowed orientations of every item that has them, by item ID
+fn restricted_orientations(json_str: &str) -> Result<HashMap<usize, Vec<f64>>, String> {
+    Ok(parse_instance(json_str)?
+        .items
+        .into_iter()
+        .filter_map(|item| Some((item.id as usize, item.allowed_orientations?)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_algorithm_fingerprint_ignores_run_budget() {
+        let config = NestingConfig::default();
+        let fingerprint = algorithm_fingerprint(&config);
+
+        let longer_run = NestingConfig {
+            time_limit: Some(30),
+            n_workers: 4,
+            seed: Some(1),
+            ..config.clone()
+        };
+        assert_eq!(algorithm_fingerprint(&longer_run), fingerprint);
+
+        // Early termination changes the optimizer's decay strategy
+        let early = NestingConfig {
+            use_early_termination: true,
+            ..config
+        };
+        assert_ne!(algorithm_fingerprint(&early), fingerprint);
+
+        let wider_gap = NestingConfig {
+            separation: 3.0,
+            ..NestingConfig::default()
+        };
+        assert_ne!(algorithm_fingerprint(&wider_gap), fingerprint);
+
+        let best = NestingConfig {
+            quality: Quality::Best,
+            ..NestingConfig::default()
+        };
+        assert_ne!(algorithm_fingerprint(&best), fingerprint);
+    }
+
+    #[test]
+    fn test_separation_reaches_sparrow() {
+        let no_gap = NestingConfig {
+            separation: 0.0,
+            ..NestingConfig::default()
+        };
+        assert_eq!(resolve_sparrow_config(&no_gap).min_item_separation, Some(0.0));
+
+        let plasma: NestingConfig = serde_json::from_value(serde_json::json!({
+            "time_limit": 60,
+            "seed": null,
+            "use_early_termination": false,
+            "n_workers": 1,
+            "min_item_separation": 4.0
+        }))
+        .unwrap();
+        assert_eq!(plasma.separation, 4.0);
+        assert_eq!(resolve_sparrow_config(&plasma).min_item_separation, Some(4.0));
+    }
+
+    #[test]
+    fn test_override_orientations() {
+        let json = r#"{"items": [{"id": 0, "allowed_orientations": [0.0, 90.0]}, {"id": 1}]}"#;
+        let rewritten: serde_json::Value =
+            serde_json::from_str(&override_orientations(json, &[0.0, 180.0]).unwrap()).unwrap();
+        for item in rewritten["items"].as_array().unwrap() {
+            assert_eq!(item["allowed_orientations"], serde_json::json!([0.0, 180.0]));
+        }
+    }
+
+    #[test]
+    fn test_parse_error_log_keeps_multibyte_characters_whole() {
+        // Vietnamese letters take two or three bytes, byte 500 falls inside one
+        let json = format!(
+            r#"{{"name": "{}", "items": [{{"id": 0,"#,
+            "Tấm đệm ".repeat(60)
+        );
+        assert!(!json.is_char_boundary(500));
+        let head = truncate_at_char_boundary(&json, 500);
+        assert!(head.len() <= 500 && head.len() > 490);
+        assert!(json.starts_with(head));
+        assert_eq!(truncate_at_char_boundary("Bản mã", 500), "Bản mã");
+
+        let input = super::super::NestingInput {
+            json_input: json,
+            ..Default::default()
+        };
+        let error = super::super::run_nesting_engine(input).unwrap_err();
+        assert!(!error.is_empty());
+    }
+
+    #[test]
+    fn test_truncate_at_char_boundary() {
+        // "ấ" takes bytes 1..4 and "ệ" bytes 2..5
+        assert_eq!(truncate_at_char_boundary("Tấm", 2), "T");
+        assert_eq!(truncate_at_char_boundary("Tấm", 4), "Tấ");
+        assert_eq!(truncate_at_char_boundary("đệm", 4), "đ");
+        assert_eq!(truncate_at_char_boundary("đệm", 1), "");
+        assert_eq!(truncate_at_char_boundary("đệm", 6), "đệm");
+    }
+
+    #[test]
+    fn test_allowed_rotations_limit_output_rotations() {
+        let rectangle = |id: usize, demand: usize, w: f64, h: f64| {
+            serde_json::json!({
+                "id": id,
+                "demand": demand,
+                "allowed_orientations": [0.0, 90.0, 180.0, 270.0],
+                "shape": {
+                    "type": "simple_polygon",
+                    "data": [[0.0, 0.0], [w, 0.0], [w, h], [0.0, h]]
+                }
+            })
+        };
+        let instance = serde_json::json!({
+            "name": "brushed",
+            "strip_height": 100.0,
+            "items": [rectangle(0, 3, 120.0, 40.0), rectangle(1, 4, 60.0, 30.0)]
+        })
+        .to_string();
+
+        for allowed in [vec![0.0, 90.0], vec![0.0]] {
+            let input: super::super::NestingInput = serde_json::from_value(serde_json::json!({
+                "json_input": instance,
+                "time_limit": 1,
+                "seed": 11,
+                "use_early_termination": true,
+                "allowed_rotations": allowed,
+            }))
+            .unwrap();
+            let output = super::super::run_nesting_engine(input).unwrap();
+
+            assert_eq!(output.total_items_placed, 7);
+            for placed in &output.layouts {
+                assert!(
+                    allowed.contains(&placed.rotation_degrees),
+                    "{} not in {:?}",
+                    placed.rotation_degrees,
+                    allowed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_quality_preset_is_echoed_in_output() {
+        let instance = serde_json::json!({
+            "name": "plates",
+            "strip_height": 100.0,
+            "items": [{
+                "id": 0,
+                "demand": 4,
+                "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 40.0, "height": 30.0 } }
+            }]
+        })
+        .to_string();
+        let input: super::super::NestingInput = serde_json::from_value(serde_json::json!({
+            "json_input": instance,
+            "time_limit": 1,
+            "seed": 3,
+            "use_early_termination": true,
+            "quality": "balanced",
+        }))
+        .unwrap();
+        let output = super::super::run_nesting_engine(input).unwrap();
+
+        assert_eq!(output.quality_settings, Some(Quality::Balanced.settings()));
+        assert_eq!(output.resolved_config.unwrap().quality, Quality::Balanced);
+    }
+
+    #[test]
+    fn test_restarts_keep_the_narrowest_strip() {
+        let instance = serde_json::json!({
+            "name": "plates",
+            "strip_height": 100.0,
+            "items": [
+                { "id": 0, "demand": 5, "shape": { "type": "simple_polygon", "data": [[0.0, 0.0], [70.0, 0.0], [0.0, 45.0]] } },
+                { "id": 1, "demand": 3, "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 40.0, "height": 30.0 } } }
+            ]
+        })
+        .to_string();
+        let input: super::super::NestingInput = serde_json::from_value(serde_json::json!({
+            "json_input": instance,
+            "time_limit": 2,
+            "seed": 5,
+            "use_early_termination": true,
+            "n_restarts": 2,
+        }))
+        .unwrap();
+        let output = super::super::run_nesting_engine(input).unwrap();
+
+        assert_eq!(output.restarts.len(), 2);
+        assert_eq!(
+            output.restarts.iter().map(|r| r.seed).collect::<Vec<_>>(),
+            vec![5, 6]
+        );
+        let winner = output.restarts.iter().find(|r| r.winner).unwrap();
+        assert_eq!(output.seed, Some(winner.seed));
+        assert_eq!(output.strip_width, winner.strip_width);
+        assert!(output
+            .restarts
+            .iter()
+            .all(|r| r.strip_width >= output.strip_width));
+        assert_eq!(output.resolved_config.unwrap().time_limit, Some(1));
+    }
+}
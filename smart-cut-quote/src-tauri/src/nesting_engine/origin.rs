@@ -0,0 +1,235 @@
+//! Instances drawn far from the origin
+//!
+//! Customers sometimes export parts in absolute machine coordinates
+//! (x = 250 000 mm). jagua-rs imports and transforms shapes in f32, which
+//! at that distance resolves only a few hundredths of a millimetre, and
+//! the errors add up to visibly misplaced parts. `normalize_origin` moves
+//! every item so the instance's bounding box starts at the origin before
+//! nesting; `restore_origin` then expresses the placements in the original
+//! item coordinates again, in f64. Outlines are in strip coordinates and
+//! need no correction.
+
+use super::geometry::{bounding_box, rotate_polygon, translate_polygon, Polygon};
+use super::instance::parse_instance;
+use super::serializer::PlacedItem;
+
+/// Instances whose bounding box starts further than this from the origin
+/// are moved before nesting (mm); closer ones are nested as they are
+pub const FAR_FROM_ORIGIN: f64 = 1000.0;
+
+/// Instance moved to the origin
+#[derive(Debug, Clone)]
+pub struct OriginShift {
+    /// Instance JSON with every item moved by `-offset`
+    pub json: String,
+    /// Lower left corner of the original instance's bounding box
+    pub offset: (f64, f64),
+}
+
+/// Move the items of an instance far from the origin next to it
+///
+/// Returns None when the instance is within `FAR_FROM_ORIGIN` already.
+pub fn normalize_origin(json_str: &str) -> Result<Option<OriginShift>, String> {
+    let instance = parse_instance(json_str)?;
+    let Some((min_x, min_y)) = instance
+        .items
+        .iter()
+        .filter_map(|item| bounding_box(&item.outer))
+        .map(|(min_x, min_y, _, _)| (min_x, min_y))
+        .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1)))
+    else {
+        return Ok(None);
+    };
+    if min_x.abs().max(min_y.abs()) <= FAR_FROM_ORIGIN {
+        return Ok(None);
+    }
+
+    let mut value: serde_json::Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+        .ok_or("Instance JSON has no items array")?;
+    let moved = |ring: &Polygon| translate_polygon(ring, -min_x, -min_y);
+
+    for item in items.iter_mut().filter_map(|item| item.as_object_mut()) {
+        let id = item.get("id").and_then(|id| id.as_u64()).unwrap_or(0);
+        let Some(geometry) = instance.items.iter().find(|geometry| geometry.id == id) else {
+            continue;
+        };
+        let holes: Vec<Polygon> = geometry.holes.iter().map(moved).collect();
+        item.insert(
+            "shape".to_string(),
+            serde_json::json!({
+                "type": "polygon",
+                "data": { "outer": moved(&geometry.outer), "inner": holes },
+            }),
+        );
+        if let Some(exact) = &geometry.exact_geometry {
+            let exact = serde_json::to_value(exact.placed(0.0, -min_x, -min_y))
+                .map_err(|e| format!("Failed to serialize item {}: {}", id, e))?;
+            item.insert("exact_geometry".to_string(), exact);
+        }
+        if !geometry.bend_lines.is_empty() {
+            let bend_lines: Vec<[(f64, f64); 2]> = geometry
+                .bend_lines
+                .iter()
+                .map(|&[a, b]| {
+                    let line = moved(&vec![a, b]);
+                    [line[0], line[1]]
+                })
+                .collect();
+            item.insert("bend_lines".to_string(), serde_json::json!(bend_lines));
+        }
+    }
+
+    println!(
+        "📍 Instance moved by ({:.1}, {:.1}) mm to the origin for nesting",
+        -min_x, -min_y
+    );
+    let json = serde_json::to_string(&value)
+        .map_err(|e| format!("Failed to serialize instance: {}", e))?;
+    Ok(Some(OriginShift {
+        json,
+        offset: (min_x, min_y),
+    }))
+}
+
+/// Express placements of moved items in the original item coordinates
+///
+/// A part placed at `p` is `R·(x - offset) + p` = `R·x + (p - R·offset)`,
+/// mirrored parts mirror the offset as well. Fillers were never moved.
+pub fn restore_origin(layouts: &mut [PlacedItem], offset: (f64, f64)) {
+    for placed in layouts.iter_mut().filter(|placed| !placed.filler) {
+        let offset = if placed.mirrored {
+            (-offset.0, offset.1)
+        } else {
+            offset
+        };
+        let (dx, dy) = rotate_polygon(&[offset], placed.rotation_degrees)[0];
+        placed.position_x -= dx;
+        placed.position_y -= dy;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::geometry::mirror_polygon;
+    use crate::nesting_engine::{run_nesting_engine, NestingInput};
+
+    const FAR: (f64, f64) = (250_000.3, -180_000.7);
+
+    fn bracket() -> Polygon {
+        [
+            (0.0, 0.0),
+            (60.0, 0.0),
+            (60.0, 15.0),
+            (15.0, 15.0),
+            (15.0, 40.0),
+            (0.0, 40.0),
+        ]
+        .iter()
+        .map(|&(x, y)| (x + FAR.0, y + FAR.1))
+        .collect()
+    }
+
+    fn instance(outer: &Polygon) -> String {
+        let points: Vec<[f64; 2]> = outer.iter().map(|&(x, y)| [x, y]).collect();
+        serde_json::json!({
+            "name": "far",
+            "strip_height": 500.0,
+            "items": [{
+                "id": 0,
+                "demand": 1,
+                "shape": { "type": "simple_polygon", "data": points },
+                "bend_lines": [[[FAR.0 + 5.0, FAR.1], [FAR.0 + 5.0, FAR.1 + 40.0]]],
+            }],
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_far_parts_round_trip_through_a_mirrored_run() {
+        let shift = normalize_origin(&instance(&bracket())).unwrap().unwrap();
+        assert_eq!(shift.offset, FAR);
+        let moved = parse_instance(&shift.json).unwrap();
+        let (min_x, min_y, _, _) = bounding_box(&moved.items[0].outer).unwrap();
+        assert!(min_x.abs() < 1e-6 && min_y.abs() < 1e-6);
+        assert_eq!(moved.items[0].bend_lines[0][0], (5.0, 0.0));
+
+        // LH and RH bracket in machine coordinates, nested as one item, and
+        // a wedge the optimizer may flip
+        let rh = translate_polygon(&mirror_polygon(&bracket()), 2.0 * FAR.0 + 200.0, 0.0);
+        let wedge: Polygon = [(0.0, 0.0), (50.0, 0.0), (0.0, 20.0)]
+            .iter()
+            .map(|&(x, y)| (x + FAR.0 + 400.0, y + FAR.1))
+            .collect();
+        let items: Vec<serde_json::Value> = [(&bracket(), 2), (&rh, 2), (&wedge, 3)]
+            .iter()
+            .enumerate()
+            .map(|(id, (outer, demand))| {
+                let points: Vec<[f64; 2]> = outer.iter().map(|&(x, y)| [x, y]).collect();
+                serde_json::json!({
+                    "id": id,
+                    "demand": demand,
+                    "shape": { "type": "simple_polygon", "data": points },
+                })
+            })
+            .collect();
+        let json =
+            serde_json::json!({ "name": "far", "strip_height": 200.0, "items": items }).to_string();
+        let original = parse_instance(&json).unwrap();
+        let input: NestingInput = serde_json::from_value(serde_json::json!({
+            "json_input": json,
+            "time_limit": 1,
+            "seed": 11,
+            "use_early_termination": true,
+            "include_outlines": true,
+            "allow_mirror": true,
+            "consolidate_mirror_pairs": true,
+        }))
+        .unwrap();
+        let output = run_nesting_engine(input).unwrap();
+        assert_eq!(output.total_items_placed, 7);
+        assert_eq!(output.origin_offset, Some(FAR));
+        assert!(output.layouts.iter().any(|placed| placed.mirrored));
+
+        // The drawing moved by the reported placement lands on the part cut
+        for placed in &output.layouts {
+            let item = &original.items[placed.item_id];
+            let outer = if placed.mirrored {
+                mirror_polygon(&item.outer)
+            } else {
+                item.outer.clone()
+            };
+            let restored = translate_polygon(
+                &rotate_polygon(&outer, placed.rotation_degrees),
+                placed.position_x,
+                placed.position_y,
+            );
+            // Cut outlines carry the separation, their centre does not move
+            let centre = |outline: &Polygon| {
+                let (min_x, min_y, max_x, max_y) = bounding_box(outline).unwrap();
+                ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0)
+            };
+            let (a, b) = (centre(&restored), centre(placed.outline.as_ref().unwrap()));
+            assert!(
+                (a.0 - b.0).abs() < 0.05 && (a.1 - b.1).abs() < 0.05,
+                "item {}: {:?} vs {:?}",
+                placed.item_id,
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn test_instance_near_origin_is_left_alone() {
+        let near: Polygon = bracket()
+            .iter()
+            .map(|&(x, y)| (x - FAR.0 + 500.0, y - FAR.1))
+            .collect();
+        assert!(normalize_origin(&instance(&near)).unwrap().is_none());
+    }
+}
@@ -0,0 +1,254 @@
+//! Layered optimizer settings for batch runs
+//!
+//! In a batch every nesting group (one material + thickness) may need its
+//! own settings: aluminum wants 5 mm separation, steel 2 mm. Each layer
+//! holds only the settings it sets; a group's settings are resolved field
+//! by field as group > batch default > app settings, and whatever is still
+//! unset falls back to the engine defaults of `NestingConfig`.
+
+use super::config_trace::{ConfigSource, ConfigTrace};
+use super::dimension::deserialize_optional_dimension;
+use super::keep_out::KeepOutZone;
+use super::NestingInput;
+use serde::{Deserialize, Serialize};
+
+/// Optimizer settings of one layer, unset fields inherit from the next layer
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigOverrides {
+    pub time_limit: Option<u64>,
+    pub seed: Option<u64>,
+    pub use_early_termination: Option<bool>,
+    pub n_workers: Option<usize>,
+    /// Minimum distance between parts and to the strip edge in mm
+    #[serde(deserialize_with = "deserialize_optional_dimension")]
+    pub separation: Option<f64>,
+    /// Orientations allowed for every part in degrees
+    pub rotations: Option<Vec<f64>>,
+    /// Sheet margins, clamps and fixtures; a group's list replaces the default's
+    pub keep_out_zones: Option<Vec<KeepOutZone>>,
+    pub hole_nesting: Option<bool>,
+    /// Keep the bend lines of bent parts along the rolling direction
+    pub respect_grain: Option<bool>,
+    pub bend_angle_tolerance: Option<f64>,
+    /// Net area (mm²) below which parts are placed after packing
+    pub small_item_area: Option<f64>,
+    /// Name of the settings preset (recorded in run metrics)
+    pub preset: Option<String>,
+}
+
+impl ConfigOverrides {
+    /// Settings of `self`, taking unset fields from `fallback`
+    pub fn or(self, fallback: &ConfigOverrides) -> ConfigOverrides {
+        ConfigOverrides {
+            time_limit: self.time_limit.or(fallback.time_limit),
+            seed: self.seed.or(fallback.seed),
+            use_early_termination: self
+                .use_early_termination
+                .or(fallback.use_early_termination),
+            n_workers: self.n_workers.or(fallback.n_workers),
+            separation: self.separation.or(fallback.separation),
+            rotations: self.rotations.or_else(|| fallback.rotations.clone()),
+            keep_out_zones: self
+                .keep_out_zones
+                .or_else(|| fallback.keep_out_zones.clone()),
+            hole_nesting: self.hole_nesting.or(fallback.hole_nesting),
+            respect_grain: self.respect_grain.or(fallback.respect_grain),
+            bend_angle_tolerance: self.bend_angle_tolerance.or(fallback.bend_angle_tolerance),
+            small_item_area: self.small_item_area.or(fallback.small_item_area),
+            preset: self.preset.or_else(|| fallback.preset.clone()),
+        }
+    }
+
+    /// Settings of a group: group > batch default > app settings
+    pub fn resolve(
+        group: &ConfigOverrides,
+        batch_default: &ConfigOverrides,
+        app_settings: &ConfigOverrides,
+    ) -> ConfigOverrides {
+        Self::resolve_traced(group, batch_default, app_settings).0
+    }
+
+    /// `resolve`, recording the candidates of every setting
+    pub fn resolve_traced(
+        group: &ConfigOverrides,
+        batch_default: &ConfigOverrides,
+        app_settings: &ConfigOverrides,
+    ) -> (ConfigOverrides, ConfigTrace) {
+        fn layers<T: Clone>(
+            group: &Option<T>,
+            batch_default: &Option<T>,
+            app_settings: &Option<T>,
+        ) -> [(ConfigSource, Option<T>); 3] {
+            [
+                (ConfigSource::Group, group.clone()),
+                (ConfigSource::BatchDefault, batch_default.clone()),
+                (ConfigSource::AppSettings, app_settings.clone()),
+            ]
+        }
+
+        let (g, b, a) = (group, batch_default, app_settings);
+        let mut trace = ConfigTrace::default();
+        let resolved = ConfigOverrides {
+            time_limit: trace.pick(
+                "time_limit",
+                &layers(&g.time_limit, &b.time_limit, &a.time_limit),
+            ),
+            seed: trace.pick("seed", &layers(&g.seed, &b.seed, &a.seed)),
+            use_early_termination: trace.pick(
+                "use_early_termination",
+                &layers(
+                    &g.use_early_termination,
+                    &b.use_early_termination,
+                    &a.use_early_termination,
+                ),
+            ),
+            n_workers: trace.pick(
+                "n_workers",
+                &layers(&g.n_workers, &b.n_workers, &a.n_workers),
+            ),
+            separation: trace.pick(
+                "separation",
+                &layers(&g.separation, &b.separation, &a.separation),
+            ),
+            rotations: trace.pick(
+                "rotations",
+                &layers(&g.rotations, &b.rotations, &a.rotations),
+            ),
+            keep_out_zones: trace.pick(
+                "keep_out_zones",
+                &layers(&g.keep_out_zones, &b.keep_out_zones, &a.keep_out_zones),
+            ),
+            hole_nesting: trace.pick(
+                "hole_nesting",
+                &layers(&g.hole_nesting, &b.hole_nesting, &a.hole_nesting),
+            ),
+            respect_grain: trace.pick(
+                "respect_grain",
+                &layers(&g.respect_grain, &b.respect_grain, &a.respect_grain),
+            ),
+            bend_angle_tolerance: trace.pick(
+                "bend_angle_tolerance",
+                &layers(
+                    &g.bend_angle_tolerance,
+                    &b.bend_angle_tolerance,
+                    &a.bend_angle_tolerance,
+                ),
+            ),
+            small_item_area: trace.pick(
+                "small_item_area",
+                &layers(&g.small_item_area, &b.small_item_area, &a.small_item_area),
+            ),
+            preset: trace.pick("preset", &layers(&g.preset, &b.preset, &a.preset)),
+        };
+        (resolved, trace)
+    }
+
+    /// Input for a run with these settings
+    ///
+    /// Unset fields stay unset, the engine defaults then apply.
+    pub fn into_input(self, json_input: String) -> NestingInput {
+        NestingInput {
+            json_input,
+            time_limit: self.time_limit,
+            seed: self.seed,
+            use_early_termination: self.use_early_termination,
+            n_workers: self.n_workers,
+            separation: self.separation,
+            rotations: self.rotations,
+            keep_out_zones: self.keep_out_zones,
+            hole_nesting: self.hole_nesting,
+            respect_grain: self.respect_grain,
+            bend_angle_tolerance: self.bend_angle_tolerance,
+            small_item_area: self.small_item_area,
+            preset: self.preset,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::{NestingConfig, DEFAULT_ITEM_SEPARATION};
+
+    fn app_settings() -> ConfigOverrides {
+        ConfigOverrides {
+            time_limit: Some(60),
+            separation: Some(5.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_group_over_batch_over_app_settings() {
+        let batch_default = ConfigOverrides {
+            separation: Some(2.0),
+            rotations: Some(vec![0.0, 90.0, 180.0, 270.0]),
+            preset: Some("production".to_string()),
+            ..Default::default()
+        };
+        let aluminum = ConfigOverrides {
+            separation: Some(5.0),
+            rotations: Some(vec![0.0, 180.0]),
+            ..Default::default()
+        };
+        let steel = ConfigOverrides::default();
+
+        let aluminum = ConfigOverrides::resolve(&aluminum, &batch_default, &app_settings());
+        assert_eq!(aluminum.separation, Some(5.0));
+        assert_eq!(aluminum.rotations, Some(vec![0.0, 180.0]));
+        assert_eq!(aluminum.preset.as_deref(), Some("production"));
+        assert_eq!(aluminum.time_limit, Some(60));
+
+        let steel = ConfigOverrides::resolve(&steel, &batch_default, &app_settings());
+        assert_eq!(steel.separation, Some(2.0));
+        assert_eq!(steel.rotations.as_ref().map(Vec::len), Some(4));
+        assert_eq!(steel.time_limit, Some(60));
+    }
+
+    #[test]
+    fn test_unset_fields_fall_back_to_engine_defaults() {
+        let resolved = ConfigOverrides::resolve(
+            &ConfigOverrides::default(),
+            &ConfigOverrides::default(),
+            &ConfigOverrides::default(),
+        );
+        let config = NestingConfig::from(&resolved.into_input("{}".to_string()));
+
+        assert_eq!(config.separation, DEFAULT_ITEM_SEPARATION);
+        assert_eq!(config.time_limit, NestingConfig::default().time_limit);
+        assert!(config.keep_out_zones.is_empty());
+    }
+
+    #[test]
+    fn test_group_keep_out_zones_replace_default() {
+        let zone = |x: f64| KeepOutZone {
+            x,
+            y: 0.0,
+            width: 10.0,
+            height: 10.0,
+            label: None,
+        };
+        let batch_default = ConfigOverrides {
+            keep_out_zones: Some(vec![zone(0.0), zone(100.0)]),
+            ..Default::default()
+        };
+        let group = ConfigOverrides {
+            keep_out_zones: Some(Vec::new()),
+            ..Default::default()
+        };
+
+        let resolved = ConfigOverrides::resolve(&group, &batch_default, &app_settings());
+        assert_eq!(resolved.keep_out_zones, Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_deserialize_partial_group_config() {
+        let group: ConfigOverrides =
+            serde_json::from_str(r#"{"separation": "5 mm", "preset": "quick"}"#).unwrap();
+        assert_eq!(group.separation, Some(5.0));
+        assert_eq!(group.preset.as_deref(), Some("quick"));
+        assert_eq!(group.time_limit, None);
+    }
+}
@@ -0,0 +1,286 @@
+//! Which optimizer phase produced the final solution
+//!
+//! Sparrow first explores (shrinking the strip until no feasible layout
+//! is found) and then compresses the best explored layout. The tracker
+//! records every feasible solution reported by either phase, so a run
+//! can tell whether compression improved on the exploration result and
+//! by how much. It also turns every report into a `ProgressReport` for
+//! the live progress of job runs.
+
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use serde::{Deserialize, Serialize};
+use sparrow::util::listener::{ReportType, SolutionListener};
+use std::time::Instant;
+
+/// Strip widths closer than this are the same solution (mm)
+const WIDTH_EPSILON: f64 = 1e-3;
+
+/// Optimizer phase that reported a solution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SolutionPhase {
+    Exploration,
+    Compression,
+}
+
+/// Optimizer phase a run spent time in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptimizerPhase {
+    Explore,
+    Compress,
+}
+
+/// Feasible solution as reported to the listener
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseReport {
+    pub phase: SolutionPhase,
+    pub strip_width: f64,
+    pub placed_items: usize,
+    /// Seconds since the tracker was created
+    pub elapsed_secs: f64,
+}
+
+/// Stage of the optimizer when a solution was reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressStage {
+    /// Feasible solution while exploring
+    Exploration,
+    /// Feasible solution while compressing
+    Compression,
+    /// Solution the optimizer returns
+    Final,
+    /// Any other report, e.g. an infeasible attempt at a narrower strip
+    Searching,
+}
+
+impl ProgressStage {
+    pub fn of(report_type: &ReportType) -> Self {
+        match report_type {
+            ReportType::ExplFeas => ProgressStage::Exploration,
+            ReportType::CmprFeas => ProgressStage::Compression,
+            ReportType::Final => ProgressStage::Final,
+            _ => ProgressStage::Searching,
+        }
+    }
+}
+
+/// Solution reported while optimizing, as shown in the progress panel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressReport {
+    pub stage: ProgressStage,
+    pub strip_width: f64,
+    pub placed_items: usize,
+    /// Seconds since the optimization started
+    pub elapsed_secs: f64,
+}
+
+/// Contribution of each phase to the final solution
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PhaseStats {
+    /// Phase that reported the final solution (None if it was never reported)
+    pub final_solution_phase: Option<SolutionPhase>,
+    /// Seconds into the optimization the final solution was reported
+    pub final_solution_secs: Option<f64>,
+    /// Strip width of the best explored solution, where compression started
+    pub handoff_strip_width: Option<f64>,
+    /// Strip width reduction by compression, relative to the handoff width
+    pub compression_gain_pct: Option<f64>,
+    /// Strip width compression took off the handoff width (mm)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width_delta: Option<f64>,
+    /// Phases the optimizer ran, in order; only `compress` when the run
+    /// continued a stored layout
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub phases_run: Vec<OptimizerPhase>,
+}
+
+/// Listener recording the feasible solutions of both phases
+pub struct PhaseTracker {
+    started: Instant,
+    reports: Vec<PhaseReport>,
+}
+
+impl PhaseTracker {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            reports: Vec::new(),
+        }
+    }
+
+    /// Correlate the recorded reports with the solution returned by the optimizer
+    ///
+    /// `start_width` is the strip width of the stored layout a run without
+    /// exploration compressed, the handoff width of such a run.
+    pub fn summarize(&self, solution: &SPSolution, start_width: Option<f64>) -> PhaseStats {
+        summarize_reports(
+            &self.reports,
+            solution.strip_width() as f64,
+            solution.layout_snapshot.placed_items.len(),
+            start_width,
+        )
+    }
+
+    /// Progress shown for a reported solution
+    pub fn progress(&self, report_type: &ReportType, solution: &SPSolution) -> ProgressReport {
+        ProgressReport {
+            stage: ProgressStage::of(report_type),
+            strip_width: solution.strip_width() as f64,
+            placed_items: solution.layout_snapshot.placed_items.len(),
+            elapsed_secs: self.started.elapsed().as_secs_f64(),
+        }
+    }
+
+    /// Record a reported solution if it is a feasible one of either phase
+    pub fn record(&mut self, report_type: &ReportType, solution: &SPSolution) {
+        let phase = match report_type {
+            ReportType::ExplFeas => SolutionPhase::Exploration,
+            ReportType::CmprFeas => SolutionPhase::Compression,
+            _ => return,
+        };
+        self.reports.push(PhaseReport {
+            phase,
+            strip_width: solution.strip_width() as f64,
+            placed_items: solution.layout_snapshot.placed_items.len(),
+            elapsed_secs: self.started.elapsed().as_secs_f64(),
+        });
+    }
+}
+
+impl Default for PhaseTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SolutionListener for PhaseTracker {
+    fn report(&mut self, report_type: ReportType, solution: &SPSolution, _instance: &SPInstance) {
+        self.record(&report_type, solution);
+    }
+}
+
+/// Find the report matching the final solution and the handoff width
+///
+/// The final solution is the latest report with the same strip width
+/// (within `WIDTH_EPSILON`) and placement count. The handoff width is
+/// `start_width` when given, else the last explored one.
+pub fn summarize_reports(
+    reports: &[PhaseReport],
+    final_width: f64,
+    final_placed: usize,
+    start_width: Option<f64>,
+) -> PhaseStats {
+    let final_report = reports.iter().rev().find(|report| {
+        (report.strip_width - final_width).abs() <= WIDTH_EPSILON
+            && report.placed_items == final_placed
+    });
+    let handoff_strip_width = start_width.or_else(|| {
+        reports
+            .iter()
+            .rev()
+            .find(|report| report.phase == SolutionPhase::Exploration)
+            .map(|report| report.strip_width)
+    });
+
+    PhaseStats {
+        final_solution_phase: final_report.map(|report| report.phase),
+        final_solution_secs: final_report.map(|report| report.elapsed_secs),
+        handoff_strip_width,
+        compression_gain_pct: handoff_strip_width
+            .filter(|&handoff| handoff > 0.0)
+            .map(|handoff| ((handoff - final_width) / handoff * 100.0).max(0.0)),
+        width_delta: handoff_strip_width.map(|handoff| (handoff - final_width).max(0.0)),
+        phases_run: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(phase: SolutionPhase, strip_width: f64, elapsed_secs: f64) -> PhaseReport {
+        PhaseReport {
+            phase,
+            strip_width,
+            placed_items: 10,
+            elapsed_secs,
+        }
+    }
+
+    #[test]
+    fn test_compression_produced_final_solution() {
+        let reports = [
+            report(SolutionPhase::Exploration, 1200.0, 1.0),
+            report(SolutionPhase::Exploration, 1000.0, 4.0),
+            report(SolutionPhase::Compression, 980.0, 6.0),
+            report(SolutionPhase::Compression, 950.0, 8.0),
+        ];
+        let stats = summarize_reports(&reports, 950.0, 10, None);
+
+        assert_eq!(stats.final_solution_phase, Some(SolutionPhase::Compression));
+        assert_eq!(stats.final_solution_secs, Some(8.0));
+        assert_eq!(stats.handoff_strip_width, Some(1000.0));
+        assert!((stats.compression_gain_pct.unwrap() - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_exploration_solution_kept() {
+        let reports = [
+            report(SolutionPhase::Exploration, 1000.0, 4.0),
+            // Compression never improved, its reports are of other widths
+            report(SolutionPhase::Compression, 1000.5, 6.0),
+        ];
+        let stats = summarize_reports(&reports, 1000.0, 10, None);
+
+        assert_eq!(stats.final_solution_phase, Some(SolutionPhase::Exploration));
+        assert_eq!(stats.compression_gain_pct, Some(0.0));
+
+        // A different placement count is a different solution
+        let stats = summarize_reports(&reports, 1000.0, 9, None);
+        assert_eq!(stats.final_solution_phase, None);
+    }
+
+    #[test]
+    fn test_compression_only_run_starts_from_the_stored_width() {
+        let reports = [
+            report(SolutionPhase::Compression, 990.0, 1.0),
+            report(SolutionPhase::Compression, 960.0, 2.0),
+        ];
+        let stats = summarize_reports(&reports, 960.0, 10, Some(1000.0));
+
+        assert_eq!(stats.final_solution_phase, Some(SolutionPhase::Compression));
+        assert_eq!(stats.handoff_strip_width, Some(1000.0));
+        assert!((stats.width_delta.unwrap() - 40.0).abs() < 1e-9);
+        assert!((stats.compression_gain_pct.unwrap() - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_progress_stage_of_reports() {
+        assert_eq!(
+            ProgressStage::of(&ReportType::CmprFeas),
+            ProgressStage::Compression
+        );
+        assert_eq!(ProgressStage::of(&ReportType::Final), ProgressStage::Final);
+        assert_eq!(
+            ProgressStage::of(&ReportType::ExplInfeas),
+            ProgressStage::Searching
+        );
+        let report = ProgressReport {
+            stage: ProgressStage::Exploration,
+            strip_width: 1250.0,
+            placed_items: 42,
+            elapsed_secs: 3.5,
+        };
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({
+                "stage": "exploration",
+                "strip_width": 1250.0,
+                "placed_items": 42,
+                "elapsed_secs": 3.5
+            })
+        );
+    }
+}
@@ -0,0 +1,463 @@
+//! Parts drawn at an angle
+//!
+//! A bracket drawn rotated 37° in CAD has an axis-aligned bounding box
+//! far larger than the part, and the optimizer spends its exploration
+//! turning it back. With `pre_orient_items` every free item is turned
+//! before nesting so its minimum-area bounding rectangle (rotating
+//! calipers over the convex hull of the outer contour) is axis-aligned,
+//! long side along the strip. The turn is about the item origin, so
+//! placements keep their position and only report `base + solver`
+//! rotation, relative to the drawing again.
+//!
+//! Items with bend lines (grain) or a fixed position are left as drawn,
+//! and so is every item when the run replaces the orientations with
+//! `rotations`. Items without `allowed_orientations` or with the quarter
+//! turns the app sends by default turn freely, the quarter turns along
+//! with the geometry. Any other set restricts the turn to a step mapping
+//! the set onto itself, so reported rotations stay in it: `[0, 180]`
+//! allows half turns only and a single orientation no turn at all.
+
+use super::geometry::{bounding_box, convex_hull, open_ring, rotate_polygon, Polygon};
+use super::instance::{parse_instance, InstanceItem};
+use super::serializer::PlacedItem;
+use super::warm_start::WarmStart;
+use serde::{Deserialize, Serialize};
+
+/// Relative bounding box area gain below which an item stays as drawn
+const MIN_AREA_GAIN: f64 = 1e-6;
+
+/// Orientations the app allows every part by default
+const QUARTER_TURNS: [f64; 4] = [0.0, 90.0, 180.0, 270.0];
+
+/// Tolerance when comparing orientations (degrees)
+const ANGLE_TOLERANCE: f64 = 1e-6;
+
+/// Rotation an item was turned by before nesting
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BaseRotation {
+    pub item_id: usize,
+    /// Counter-clockwise about the item origin, in (-90, 90] for items
+    /// turning freely, a multiple of the orientation step otherwise
+    pub degrees: f64,
+}
+
+/// Instance with its free items turned to their smallest bounding box
+#[derive(Debug, Clone)]
+pub struct PreOrientation {
+    /// Instance JSON with every turned item's geometry rotated
+    pub json: String,
+    pub rotations: Vec<BaseRotation>,
+}
+
+impl PreOrientation {
+    fn base(&self, item_id: usize) -> Option<f64> {
+        self.rotations
+            .iter()
+            .find(|rotation| rotation.item_id == item_id)
+            .map(|rotation| rotation.degrees)
+    }
+}
+
+/// Turn every free item of an instance to its minimum-area bounding rectangle
+///
+/// Returns None when no item gains from turning.
+pub fn pre_orient_items(json_str: &str) -> Result<Option<PreOrientation>, String> {
+    let instance = parse_instance(json_str)?;
+    let rotations: Vec<BaseRotation> = instance
+        .items
+        .iter()
+        .filter(|item| is_free(item))
+        .filter_map(|item| {
+            let step = orientation_step(item.allowed_orientations.as_deref());
+            base_rotation(&item.outer, step).map(|degrees| BaseRotation {
+                item_id: item.id as usize,
+                degrees,
+            })
+        })
+        .collect();
+    if rotations.is_empty() {
+        return Ok(None);
+    }
+
+    let mut value: serde_json::Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+        .ok_or("Instance JSON has no items array")?;
+    for item in items.iter_mut().filter_map(|item| item.as_object_mut()) {
+        let id = item.get("id").and_then(|id| id.as_u64()).unwrap_or(0);
+        let Some(rotation) = rotations
+            .iter()
+            .find(|rotation| rotation.item_id == id as usize)
+        else {
+            continue;
+        };
+        let Some(geometry) = instance.items.iter().find(|geometry| geometry.id == id) else {
+            continue;
+        };
+        let turned = |ring: &Polygon| rotate_polygon(ring, rotation.degrees);
+        let holes: Vec<Polygon> = geometry.holes.iter().map(turned).collect();
+        item.insert(
+            "shape".to_string(),
+            serde_json::json!({
+                "type": "polygon",
+                "data": { "outer": turned(&geometry.outer), "inner": holes },
+            }),
+        );
+        if let Some(exact) = &geometry.exact_geometry {
+            let exact = serde_json::to_value(exact.placed(rotation.degrees, 0.0, 0.0))
+                .map_err(|e| format!("Failed to serialize item {}: {}", id, e))?;
+            item.insert("exact_geometry".to_string(), exact);
+        }
+    }
+
+    println!(
+        "📐 Turned {} items to their smallest bounding box before nesting",
+        rotations.len()
+    );
+    let json = serde_json::to_string(&value)
+        .map_err(|e| format!("Failed to serialize instance: {}", e))?;
+    Ok(Some(PreOrientation { json, rotations }))
+}
+
+/// Report the rotations of turned items relative to their drawing
+///
+/// The item was turned about its origin, which moves its centroid along,
+/// so positions stay as they are.
+pub fn restore_base_rotations(layouts: &mut [PlacedItem], orientation: &PreOrientation) {
+    for placed in layouts {
+        if let Some(base) = orientation.base(placed.item_id) {
+            placed.rotation_degrees = (placed.rotation_degrees + base).rem_euclid(360.0);
+        }
+    }
+}
+
+/// A stored layout in the frame of the turned items
+pub fn rebase_warm_start(warm: &WarmStart, orientation: &PreOrientation) -> WarmStart {
+    let mut rebased = warm.clone();
+    for placement in &mut rebased.placements {
+        if let Some(base) = orientation.base(placement.item_id) {
+            placement.rotation_degrees = (placement.rotation_degrees - base).rem_euclid(360.0);
+        }
+    }
+    rebased
+}
+
+/// Items whose base orientation may change: no bend data, no fixed position
+fn is_free(item: &InstanceItem) -> bool {
+    item.bend_direction_deg.is_none() && item.bend_lines.is_empty() && item.fixed_position.is_none()
+}
+
+/// Smallest turn mapping a restricted orientation set onto itself
+///
+/// None when the item turns freely: no set, or the default quarter
+/// turns. 360 when no turn keeps the set, such as a single orientation.
+fn orientation_step(orientations: Option<&[f64]>) -> Option<f64> {
+    let orientations = orientations?;
+    let same_angle = |a: f64, b: f64| {
+        let diff = (a - b).rem_euclid(360.0);
+        diff.min(360.0 - diff) < ANGLE_TOLERANCE
+    };
+    let same_set = |a: &[f64], b: &[f64]| {
+        a.iter().all(|&x| b.iter().any(|&y| same_angle(x, y)))
+            && b.iter().all(|&y| a.iter().any(|&x| same_angle(x, y)))
+    };
+    if same_set(orientations, &QUARTER_TURNS) {
+        return None;
+    }
+    let first = *orientations.first()?;
+    let step = orientations
+        .iter()
+        .map(|&angle| (angle - first).rem_euclid(360.0))
+        .filter(|&turn| turn > ANGLE_TOLERANCE && 360.0 - turn > ANGLE_TOLERANCE)
+        .filter(|&turn| {
+            let turned: Vec<f64> = orientations.iter().map(|&angle| angle + turn).collect();
+            same_set(&turned, orientations)
+        })
+        .fold(360.0, f64::min);
+    Some(step)
+}
+
+/// Rotation turning the minimum-area bounding rectangle of a contour
+/// axis-aligned, long side along X, rounded to a multiple of `step`
+///
+/// None when the contour's own bounding box is that small already.
+fn base_rotation(outer: &[(f64, f64)], step: Option<f64>) -> Option<f64> {
+    let hull = convex_hull(open_ring(outer));
+    let (_, degrees) = min_area_rectangle(&hull)?;
+    let degrees = step.map_or(degrees, |step| (degrees / step).round() * step);
+    let box_area = |ring: &[(f64, f64)]| {
+        bounding_box(ring).map(|(min_x, min_y, max_x, max_y)| (max_x - min_x) * (max_y - min_y))
+    };
+    let area = box_area(&rotate_polygon(&hull, degrees))?;
+    let drawn_area = box_area(&hull)?;
+    (drawn_area - area > drawn_area * MIN_AREA_GAIN).then_some(degrees)
+}
+
+/// Area of the smallest rectangle around a convex hull and the rotation
+/// making it axis-aligned, long side along X
+///
+/// Rotating calipers: the rectangle has a side on one of the hull's
+/// edges. For each edge the extreme points along and across it are
+/// found by advancing three pointers around the hull, which only ever
+/// move forward. Rotations of equal area prefer the smallest turn.
+fn min_area_rectangle(hull: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let n = hull.len();
+    if n < 3 {
+        return None;
+    }
+    let point = |k: usize| hull[k % n];
+    let project = |(x, y): (f64, f64), (dx, dy): (f64, f64)| x * dx + y * dy;
+
+    let mut best: Option<(f64, f64)> = None;
+    let (mut right, mut top, mut left) = (1, 1, 1);
+    for i in 0..n {
+        let (start, end) = (point(i), point(i + 1));
+        let length = (end.0 - start.0).hypot(end.1 - start.1);
+        if length == 0.0 {
+            continue;
+        }
+        // Along the edge, and across it into the (counter-clockwise) hull
+        let along = ((end.0 - start.0) / length, (end.1 - start.1) / length);
+        let across = (-along.1, along.0);
+
+        right = right.max(i + 1);
+        while right < i + n && project(point(right + 1), along) >= project(point(right), along) {
+            right += 1;
+        }
+        top = top.max(right);
+        while top < i + n && project(point(top + 1), across) >= project(point(top), across) {
+            top += 1;
+        }
+        left = left.max(top);
+        while left < i + n && project(point(left + 1), along) <= project(point(left), along) {
+            left += 1;
+        }
+
+        let width = project(point(right), along) - project(point(left), along);
+        let height = project(point(top), across) - project(start, across);
+        let area = width * height;
+        // Turning by -angle lays the edge along X, a quarter turn more
+        // brings a taller rectangle on its long side
+        let angle = along.1.atan2(along.0).to_degrees();
+        let turn = if height > width { 90.0 - angle } else { -angle };
+        let degrees = 90.0 - (90.0 - turn).rem_euclid(180.0);
+
+        let better = match best {
+            None => true,
+            Some((best_area, best_degrees)) => {
+                let tolerance = best_area.abs() * 1e-9;
+                area < best_area - tolerance
+                    || (area <= best_area + tolerance && degrees.abs() < best_degrees.abs())
+            }
+        };
+        if better {
+            best = Some((area, degrees));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::{run_nesting_engine, NestingInput, NestingOutput};
+
+    /// 40 x 30 plate turned by `degrees` about the origin
+    fn plate(degrees: f64) -> Polygon {
+        rotate_polygon(
+            &[(0.0, 0.0), (40.0, 0.0), (40.0, 30.0), (0.0, 30.0)],
+            degrees,
+        )
+    }
+
+    fn nest(outer: &Polygon) -> NestingOutput {
+        let instance = serde_json::json!({
+            "name": "plates",
+            "strip_height": 100.0,
+            "items": [{
+                "id": 0,
+                "demand": 4,
+                "shape": { "type": "simple_polygon", "data": outer }
+            }]
+        })
+        .to_string();
+        let input: NestingInput = serde_json::from_value(serde_json::json!({
+            "json_input": instance,
+            "time_limit": 1,
+            "seed": 7,
+            "use_early_termination": true,
+            "pre_orient_items": true,
+            "include_outlines": true,
+        }))
+        .unwrap();
+        run_nesting_engine(input).unwrap()
+    }
+
+    #[test]
+    fn test_min_area_rectangle_finds_the_drawing_angle() {
+        let (area, degrees) = min_area_rectangle(&convex_hull(&plate(37.0))).unwrap();
+        assert!((area - 1200.0).abs() < 1e-6);
+        assert!((degrees + 37.0).abs() < 1e-9);
+
+        // Turns are folded into (-90°, 90°], a half turn is the same box
+        let (_, degrees) = min_area_rectangle(&convex_hull(&plate(127.0))).unwrap();
+        assert!((degrees - 53.0).abs() < 1e-9);
+
+        assert_eq!(base_rotation(&plate(0.0), None), None);
+    }
+
+    #[test]
+    fn test_locked_and_bent_items_keep_their_drawing() {
+        let instance = serde_json::json!({
+            "name": "plates",
+            "strip_height": 100.0,
+            "items": [
+                { "id": 0, "demand": 1, "shape": { "type": "simple_polygon", "data": plate(37.0) } },
+                { "id": 1, "demand": 1, "allowed_orientations": [0.0], "shape": { "type": "simple_polygon", "data": plate(37.0) } },
+                { "id": 2, "demand": 1, "bend_direction_deg": 37.0, "shape": { "type": "simple_polygon", "data": plate(37.0) } }
+            ]
+        })
+        .to_string();
+
+        let orientation = pre_orient_items(&instance).unwrap().unwrap();
+        assert_eq!(orientation.rotations.len(), 1);
+        assert_eq!(orientation.rotations[0].item_id, 0);
+        let turned = parse_instance(&orientation.json).unwrap();
+        let (min_x, min_y, max_x, max_y) = bounding_box(&turned.items[0].outer).unwrap();
+        assert!((max_x - min_x - 40.0).abs() < 1e-9 && (max_y - min_y - 30.0).abs() < 1e-9);
+        for item in &turned.items[1..] {
+            let kept = item
+                .outer
+                .iter()
+                .zip(&plate(37.0))
+                .all(|(a, b)| (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9);
+            assert!(kept, "item {} was turned", item.id);
+        }
+    }
+
+    #[test]
+    fn test_quarter_turn_orientations_turn_with_the_part() {
+        // What the app sends for every part
+        let instance = serde_json::json!({
+            "name": "plates",
+            "strip_height": 100.0,
+            "items": [{
+                "id": 0,
+                "demand": 2,
+                "allowed_orientations": [0.0, 90.0, 180.0, 270.0],
+                "shape": { "type": "simple_polygon", "data": plate(37.0) }
+            }]
+        })
+        .to_string();
+
+        let orientation = pre_orient_items(&instance).unwrap().unwrap();
+        assert_eq!(orientation.rotations.len(), 1);
+        assert!((orientation.rotations[0].degrees + 37.0).abs() < 1e-9);
+        let turned = parse_instance(&orientation.json).unwrap();
+        let (min_x, min_y, max_x, max_y) = bounding_box(&turned.items[0].outer).unwrap();
+        assert!((max_x - min_x - 40.0).abs() < 1e-9 && (max_y - min_y - 30.0).abs() < 1e-9);
+        assert_eq!(
+            turned.items[0].allowed_orientations,
+            Some(vec![0.0, 90.0, 180.0, 270.0])
+        );
+
+        // Placements are quarter turns of the drawing's 37° base
+        let input: NestingInput = serde_json::from_value(serde_json::json!({
+            "json_input": instance,
+            "time_limit": 1,
+            "seed": 7,
+            "use_early_termination": true,
+            "pre_orient_items": true,
+        }))
+        .unwrap();
+        let output = run_nesting_engine(input).unwrap();
+        assert_eq!(output.base_rotations, orientation.rotations);
+        assert_eq!(output.total_items_placed, 2);
+        for placed in &output.layouts {
+            let step = (placed.rotation_degrees + 37.0).rem_euclid(90.0);
+            assert!(step.min(90.0 - step) < 1e-3, "{}", placed.rotation_degrees);
+        }
+    }
+
+    #[test]
+    fn test_restricted_orientations_keep_the_reported_rotations() {
+        // Half turns only, as for a grain direction
+        let instance = serde_json::json!({
+            "name": "plates",
+            "strip_height": 100.0,
+            "items": [{
+                "id": 0,
+                "demand": 2,
+                "allowed_orientations": [0.0, 180.0],
+                "shape": { "type": "simple_polygon", "data": plate(37.0) }
+            }]
+        })
+        .to_string();
+        assert!(pre_orient_items(&instance).unwrap().is_none());
+
+        let input: NestingInput = serde_json::from_value(serde_json::json!({
+            "json_input": instance,
+            "time_limit": 1,
+            "seed": 7,
+            "use_early_termination": true,
+            "pre_orient_items": true,
+        }))
+        .unwrap();
+        let output = run_nesting_engine(input).unwrap();
+        assert!(output.base_rotations.is_empty());
+        assert_eq!(output.total_items_placed, 2);
+        for placed in &output.layouts {
+            let rotation = placed.rotation_degrees.rem_euclid(360.0);
+            assert!(
+                [0.0, 180.0, 360.0]
+                    .iter()
+                    .any(|allowed| (rotation - allowed).abs() < 1e-3),
+                "{}",
+                placed.rotation_degrees
+            );
+        }
+
+        // Eighth turns map onto themselves by 45°, the base snaps to it
+        let eighths: Vec<f64> = (0..8).map(|k| k as f64 * 45.0).collect();
+        assert_eq!(orientation_step(Some(&eighths)), Some(45.0));
+        assert_eq!(orientation_step(Some(&[0.0, 180.0])), Some(180.0));
+        assert_eq!(orientation_step(Some(&[0.0, 90.0])), Some(360.0));
+        assert_eq!(orientation_step(Some(&QUARTER_TURNS)), None);
+        let degrees = base_rotation(&plate(37.0), Some(45.0)).unwrap();
+        assert!((degrees + 45.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_plate_drawn_at_an_angle_nests_like_an_upright_one() {
+        let upright = nest(&plate(0.0));
+        let drawn = nest(&plate(37.0));
+
+        assert!(upright.resolved_config.as_ref().unwrap().pre_orient_items);
+        assert_eq!(drawn.total_items_placed, upright.total_items_placed);
+        assert!((drawn.strip_width - upright.strip_width).abs() < 1e-3);
+        for (drawn, upright) in drawn.layouts.iter().zip(&upright.layouts) {
+            // Turned back by the base rotation before the solver's
+            let turn = (drawn.rotation_degrees + 37.0 - upright.rotation_degrees).rem_euclid(360.0);
+            assert!(turn.min(360.0 - turn) < 1e-3);
+            assert!((drawn.position_x - upright.position_x).abs() < 1e-3);
+            assert!((drawn.position_y - upright.position_y).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_reported_rotation_places_the_drawing_on_its_outline() {
+        let output = nest(&plate(37.0));
+
+        assert!(output.violations.is_empty());
+        for placed in &output.layouts {
+            let placed_plate = rotate_polygon(&plate(37.0), placed.rotation_degrees);
+            let (min_x, min_y, max_x, max_y) = bounding_box(&placed_plate).unwrap();
+            let (o_min_x, o_min_y, o_max_x, o_max_y) =
+                bounding_box(placed.outline.as_ref().unwrap()).unwrap();
+            assert!((max_x - min_x - (o_max_x - o_min_x)).abs() < 1e-2);
+            assert!((max_y - min_y - (o_max_y - o_min_y)).abs() < 1e-2);
+        }
+    }
+}
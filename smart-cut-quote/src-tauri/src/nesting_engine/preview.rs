@@ -0,0 +1,409 @@
+//! Live preview of a running optimization
+//!
+//! Re-rendering the full layout SVG for every improvement is janky in the
+//! WebView for big jobs. The first preview update carries a full SVG in
+//! which every placement has a `data-placement-id` attribute; later
+//! updates only list the new transforms of the placements that moved and
+//! the frontend patches the existing DOM. When parts were added or
+//! removed, or more than `FULL_REDRAW_SHARE` of them moved, a full SVG is
+//! sent again.
+
+use super::geometry::Polygon;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use serde::Serialize;
+use sparrow::util::listener::{ReportType, SolutionListener};
+use std::collections::BTreeMap;
+
+/// Share of moved placements above which a full SVG is cheaper than a patch
+pub const FULL_REDRAW_SHARE: f64 = 0.3;
+
+/// Transforms closer than this are unchanged (mm, degrees)
+const TRANSFORM_EPSILON: f64 = 1e-6;
+
+/// Margin around the strip, as in the final layout SVG
+const VIEWBOX_MARGIN: f64 = 50.0;
+
+/// Position of one placed part
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlacementTransform {
+    pub item_id: usize,
+    pub x: f64,
+    pub y: f64,
+    pub rotation_degrees: f64,
+}
+
+impl PlacementTransform {
+    /// SVG `transform` attribute value (rotate about the origin, then translate)
+    pub fn svg_transform(&self) -> String {
+        format!(
+            "translate({:.3} {:.3}) rotate({:.4})",
+            self.x, self.y, self.rotation_degrees
+        )
+    }
+
+    fn same_as(&self, other: &PlacementTransform) -> bool {
+        (self.x - other.x).abs() <= TRANSFORM_EPSILON
+            && (self.y - other.y).abs() <= TRANSFORM_EPSILON
+            && (self.rotation_degrees - other.rotation_degrees).abs() <= TRANSFORM_EPSILON
+    }
+
+    fn distance_sq(&self, other: &PlacementTransform) -> f64 {
+        (self.x - other.x).powi(2) + (self.y - other.y).powi(2)
+    }
+}
+
+/// Layout of one reported solution, reduced to what the preview draws
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewFrame {
+    pub strip_width: f64,
+    pub strip_height: f64,
+    pub placements: Vec<PlacementTransform>,
+    /// Outer contour of each item in its own (untransformed) frame
+    pub shapes: BTreeMap<usize, Polygon>,
+}
+
+impl PreviewFrame {
+    pub fn from_solution(solution: &SPSolution, instance: &SPInstance) -> Self {
+        let mut placements = Vec::new();
+        let mut shapes = BTreeMap::new();
+
+        for placed_item in solution.layout_snapshot.placed_items.values() {
+            let rotation = placed_item.d_transf.rotation() as f64;
+            let (tx, ty) = placed_item.d_transf.translation();
+            let (tx, ty) = (tx as f64, ty as f64);
+            placements.push(PlacementTransform {
+                item_id: placed_item.item_id,
+                x: tx,
+                y: ty,
+                rotation_degrees: rotation.to_degrees(),
+            });
+
+            // Undo the placement to get the shape `svg_transform` applies to
+            shapes.entry(placed_item.item_id).or_insert_with(|| {
+                let (sin, cos) = rotation.sin_cos();
+                placed_item
+                    .shape
+                    .vertices
+                    .iter()
+                    .map(|p| {
+                        let (dx, dy) = (p.0 as f64 - tx, p.1 as f64 - ty);
+                        (dx * cos + dy * sin, dy * cos - dx * sin)
+                    })
+                    .collect()
+            });
+        }
+
+        Self {
+            strip_width: solution.strip_width() as f64,
+            strip_height: instance.base_strip.fixed_height as f64,
+            placements,
+            shapes,
+        }
+    }
+}
+
+/// New transform of a placement already shown
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlacementMove {
+    pub placement_id: usize,
+    pub transform: String,
+}
+
+/// Update sent to the frontend for a reported solution
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PreviewUpdate {
+    /// Replace the preview with this SVG
+    Full { svg: String },
+    /// Apply to the SVG of the last full update
+    Patch {
+        view_box: String,
+        strip_width: f64,
+        moves: Vec<PlacementMove>,
+    },
+}
+
+/// `viewBox` of the preview for a strip
+pub fn preview_view_box(strip_width: f64, strip_height: f64) -> String {
+    format!(
+        "{} {} {} {}",
+        -VIEWBOX_MARGIN,
+        -VIEWBOX_MARGIN,
+        strip_width + 2.0 * VIEWBOX_MARGIN,
+        strip_height + 2.0 * VIEWBOX_MARGIN
+    )
+}
+
+/// Preview SVG; placement `i` of the frame gets `data-placement-id="i"`
+pub fn render_preview_svg(frame: &PreviewFrame) -> String {
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{}"><defs>"#,
+        preview_view_box(frame.strip_width, frame.strip_height)
+    );
+    for (item_id, shape) in &frame.shapes {
+        svg.push_str(&format!(
+            r#"<path id="preview-item-{}" d="{}"/>"#,
+            item_id,
+            path_data(shape)
+        ));
+    }
+    svg.push_str(&format!(
+        r##"</defs><rect data-strip="" x="0" y="0" width="{}" height="{}" fill="#f5f5f5" stroke="#424242"/>"##,
+        frame.strip_width, frame.strip_height
+    ));
+    for (placement_id, placement) in frame.placements.iter().enumerate() {
+        svg.push_str(&format!(
+            r##"<use href="#preview-item-{}" data-placement-id="{}" transform="{}" fill="#90caf9" stroke="#1565c0" stroke-width="0.5"/>"##,
+            placement.item_id,
+            placement_id,
+            placement.svg_transform()
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+fn path_data(shape: &Polygon) -> String {
+    let points: Vec<String> = shape
+        .iter()
+        .map(|(x, y)| format!("{:.3} {:.3}", x, y))
+        .collect();
+    format!("M{}Z", points.join(" L"))
+}
+
+/// Turns successive frames into full or patch updates
+#[derive(Debug, Default)]
+pub struct PreviewDiffer {
+    /// Transform shown for each placement ID
+    shown: Vec<PlacementTransform>,
+    strip_width: f64,
+    started: bool,
+}
+
+impl PreviewDiffer {
+    /// Update for the next frame, None if nothing visible changed
+    pub fn update(&mut self, frame: &PreviewFrame) -> Option<PreviewUpdate> {
+        if !self.started {
+            return Some(self.full(frame));
+        }
+        let Some(moves) = match_placements(&self.shown, &frame.placements) else {
+            return Some(self.full(frame));
+        };
+        if moves.len() as f64 > FULL_REDRAW_SHARE * frame.placements.len() as f64 {
+            return Some(self.full(frame));
+        }
+        let width_changed = (frame.strip_width - self.strip_width).abs() > TRANSFORM_EPSILON;
+        if moves.is_empty() && !width_changed {
+            return None;
+        }
+
+        for (placement_id, transform) in &moves {
+            self.shown[*placement_id] = *transform;
+        }
+        self.strip_width = frame.strip_width;
+        Some(PreviewUpdate::Patch {
+            view_box: preview_view_box(frame.strip_width, frame.strip_height),
+            strip_width: frame.strip_width,
+            moves: moves
+                .iter()
+                .map(|(placement_id, transform)| PlacementMove {
+                    placement_id: *placement_id,
+                    transform: transform.svg_transform(),
+                })
+                .collect(),
+        })
+    }
+
+    fn full(&mut self, frame: &PreviewFrame) -> PreviewUpdate {
+        self.shown = frame.placements.clone();
+        self.strip_width = frame.strip_width;
+        self.started = true;
+        PreviewUpdate::Full {
+            svg: render_preview_svg(frame),
+        }
+    }
+}
+
+/// Assign the placements of a new frame to the IDs already shown
+///
+/// Copies of an item are interchangeable: unchanged copies keep their ID,
+/// moved ones take the nearest remaining ID of the same item. Returns the
+/// moved placements, or None if parts were added or removed.
+fn match_placements(
+    shown: &[PlacementTransform],
+    current: &[PlacementTransform],
+) -> Option<Vec<(usize, PlacementTransform)>> {
+    if shown.len() != current.len() {
+        return None;
+    }
+
+    let mut free: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (placement_id, transform) in shown.iter().enumerate() {
+        free.entry(transform.item_id)
+            .or_default()
+            .push(placement_id);
+    }
+
+    let mut unmatched = Vec::new();
+    for transform in current {
+        let ids = free.get_mut(&transform.item_id)?;
+        match ids.iter().position(|&id| shown[id].same_as(transform)) {
+            Some(pos) => {
+                ids.swap_remove(pos);
+            }
+            None => unmatched.push(*transform),
+        }
+    }
+
+    let mut moves = Vec::with_capacity(unmatched.len());
+    for transform in unmatched {
+        let ids = free.get_mut(&transform.item_id)?;
+        let pos = (0..ids.len()).min_by(|&a, &b| {
+            shown[ids[a]]
+                .distance_sq(&transform)
+                .total_cmp(&shown[ids[b]].distance_sq(&transform))
+        })?;
+        moves.push((ids.swap_remove(pos), transform));
+    }
+    Some(moves)
+}
+
+/// Listener turning reported solutions into preview updates
+pub struct PreviewListener<'a> {
+    differ: PreviewDiffer,
+    emit: &'a mut dyn FnMut(PreviewUpdate),
+}
+
+impl<'a> PreviewListener<'a> {
+    pub fn new(emit: &'a mut dyn FnMut(PreviewUpdate)) -> Self {
+        Self {
+            differ: PreviewDiffer::default(),
+            emit,
+        }
+    }
+}
+
+impl SolutionListener for PreviewListener<'_> {
+    fn report(&mut self, report_type: ReportType, solution: &SPSolution, instance: &SPInstance) {
+        if !matches!(
+            report_type,
+            ReportType::ExplFeas | ReportType::CmprFeas | ReportType::Final
+        ) {
+            return;
+        }
+        let frame = PreviewFrame::from_solution(solution, instance);
+        if let Some(update) = self.differ.update(&frame) {
+            (self.emit)(update);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ten copies of item 0 in a row and one item 1
+    fn frame(strip_width: f64) -> PreviewFrame {
+        let mut placements: Vec<PlacementTransform> = (0..10)
+            .map(|i| PlacementTransform {
+                item_id: 0,
+                x: i as f64 * 20.0,
+                y: 0.0,
+                rotation_degrees: 0.0,
+            })
+            .collect();
+        placements.push(PlacementTransform {
+            item_id: 1,
+            x: 0.0,
+            y: 50.0,
+            rotation_degrees: 90.0,
+        });
+        PreviewFrame {
+            strip_width,
+            strip_height: 100.0,
+            placements,
+            shapes: BTreeMap::from([
+                (0, vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]),
+                (1, vec![(0.0, 0.0), (30.0, 0.0), (30.0, 5.0), (0.0, 5.0)]),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_first_update_is_full_svg_with_ids() {
+        let mut differ = PreviewDiffer::default();
+        let Some(PreviewUpdate::Full { svg }) = differ.update(&frame(200.0)) else {
+            panic!("expected a full update");
+        };
+
+        assert_eq!(svg.matches("data-placement-id=").count(), 11);
+        assert!(svg.contains(
+            r#"data-placement-id="10" transform="translate(0.000 50.000) rotate(90.0000)""#
+        ));
+        assert!(svg.contains(r#"id="preview-item-1""#));
+    }
+
+    #[test]
+    fn test_small_move_is_patched() {
+        let mut differ = PreviewDiffer::default();
+        differ.update(&frame(200.0));
+        assert_eq!(differ.update(&frame(200.0)), None);
+
+        // Copies swapping places are not a change
+        let mut next = frame(190.0);
+        next.placements.swap(0, 1);
+        next.placements[9].x = 170.0;
+        next.placements[10].rotation_degrees = 270.0;
+
+        let Some(PreviewUpdate::Patch {
+            strip_width,
+            moves,
+            view_box,
+        }) = differ.update(&next)
+        else {
+            panic!("expected a patch");
+        };
+        assert_eq!(strip_width, 190.0);
+        assert_eq!(view_box, "-50 -50 290 200");
+        assert_eq!(
+            moves,
+            vec![
+                PlacementMove {
+                    placement_id: 9,
+                    transform: "translate(170.000 0.000) rotate(0.0000)".to_string(),
+                },
+                PlacementMove {
+                    placement_id: 10,
+                    transform: "translate(0.000 50.000) rotate(270.0000)".to_string(),
+                },
+            ]
+        );
+
+        // The patch is applied to the shown state
+        assert_eq!(differ.update(&next), None);
+    }
+
+    #[test]
+    fn test_large_or_structural_change_is_full() {
+        let mut differ = PreviewDiffer::default();
+        differ.update(&frame(200.0));
+
+        // 4 of 11 placements moved (> 30%)
+        let mut next = frame(200.0);
+        for placement in next.placements.iter_mut().take(4) {
+            placement.y += 30.0;
+        }
+        assert!(matches!(
+            differ.update(&next),
+            Some(PreviewUpdate::Full { .. })
+        ));
+
+        // A part removed
+        let mut next = frame(200.0);
+        next.placements.pop();
+        assert!(matches!(
+            differ.update(&next),
+            Some(PreviewUpdate::Full { .. })
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+//! Engine provenance of nesting outputs
+//!
+//! Layouts from different sparrow/jagua-rs builds or optimizer settings
+//! differ for reasons unrelated to the parts, so comparisons and replays
+//! check where each output came from and warn when they mix.
+
+use super::nesting::{algorithm_fingerprint, NestingConfig};
+use super::serializer::NestingOutput;
+use super::{APP_VERSION, ENGINE_VERSION};
+use serde::{Deserialize, Serialize};
+
+/// Which engine build and settings produced a result
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub engine_version: Option<String>,
+    pub app_version: Option<String>,
+    pub algorithm_fingerprint: Option<String>,
+}
+
+impl Provenance {
+    pub fn of(output: &NestingOutput) -> Self {
+        Self {
+            engine_version: output.engine_version.clone(),
+            app_version: output.app_version.clone(),
+            algorithm_fingerprint: output.algorithm_fingerprint.clone(),
+        }
+    }
+
+    /// Provenance a run of this build with `config` would have
+    pub fn current(config: &NestingConfig) -> Self {
+        Self {
+            engine_version: Some(ENGINE_VERSION.to_string()),
+            app_version: Some(APP_VERSION.to_string()),
+            algorithm_fingerprint: Some(algorithm_fingerprint(config)),
+        }
+    }
+}
+
+/// Side-by-side summary of two nesting outputs
+#[derive(Serialize, Debug, Clone)]
+pub struct OutputComparison {
+    pub utilization_a: f64,
+    pub utilization_b: f64,
+    pub strip_width_a: f64,
+    pub strip_width_b: f64,
+    pub items_placed_a: usize,
+    pub items_placed_b: usize,
+    pub provenance_a: Provenance,
+    pub provenance_b: Provenance,
+    /// Engine or settings differences, empty when the outputs are comparable
+    pub warnings: Vec<String>,
+}
+
+/// Compare two outputs, warning when they come from different engines
+pub fn compare_outputs(a: &NestingOutput, b: &NestingOutput) -> OutputComparison {
+    let provenance_a = Provenance::of(a);
+    let provenance_b = Provenance::of(b);
+
+    OutputComparison {
+        utilization_a: a.utilization,
+        utilization_b: b.utilization,
+        strip_width_a: a.strip_width,
+        strip_width_b: b.strip_width,
+        items_placed_a: a.total_items_placed,
+        items_placed_b: b.total_items_placed,
+        warnings: provenance_warnings(&provenance_a, &provenance_b),
+        provenance_a,
+        provenance_b,
+    }
+}
+
+/// Reasons why results of `a` and `b` are not directly comparable
+///
+/// A missing value (results from before provenance was recorded) also
+/// warns, since the producing engine is unknown. App versions alone
+/// don't warn: they change with every release.
+pub fn provenance_warnings(a: &Provenance, b: &Provenance) -> Vec<String> {
+    let checks = [
+        ("engine version", &a.engine_version, &b.engine_version),
+        (
+            "algorithm fingerprint",
+            &a.algorithm_fingerprint,
+            &b.algorithm_fingerprint,
+        ),
+    ];
+
+    checks
+        .into_iter()
+        .filter_map(|(label, a, b)| match (a, b) {
+            (Some(a), Some(b)) if a == b => None,
+            (Some(a), Some(b)) => Some(format!("Different {}: {} vs {}", label, a, b)),
+            (a, b) => Some(format!(
+                "Unknown {}: {} vs {}",
+                label,
+                a.as_deref().unwrap_or("unknown"),
+                b.as_deref().unwrap_or("unknown")
+            )),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THIN_FRAME: &str = include_str!("../../test_instances/thin_frame.json");
+
+    fn provenance(engine: &str, fingerprint: Option<&str>) -> Provenance {
+        Provenance {
+            engine_version: Some(engine.to_string()),
+            app_version: Some("0.1.0".to_string()),
+            algorithm_fingerprint: fingerprint.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_provenance_warnings() {
+        let a = provenance("sparrow 0.1.0", Some("abc"));
+        assert!(provenance_warnings(&a, &a).is_empty());
+
+        // App version alone is not a reason to warn
+        let b = Provenance {
+            app_version: Some("0.2.0".to_string()),
+            ..a.clone()
+        };
+        assert!(provenance_warnings(&a, &b).is_empty());
+
+        let c = provenance("sparrow 0.1.0", Some("def"));
+        assert_eq!(
+            provenance_warnings(&a, &c),
+            vec!["Different algorithm fingerprint: abc vs def"]
+        );
+
+        let d = provenance("sparrow 0.2.0", None);
+        assert_eq!(
+            provenance_warnings(&a, &d),
+            vec![
+                "Different engine version: sparrow 0.1.0 vs sparrow 0.2.0",
+                "Unknown algorithm fingerprint: abc vs unknown",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_engine_output_records_provenance() {
+        let output = super::super::run_nesting_engine(super::super::NestingInput {
+            json_input: THIN_FRAME.to_string(),
+            time_limit: Some(1),
+            seed: Some(7),
+            use_early_termination: Some(true),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let engine_version = output.engine_version.as_deref().unwrap();
+        assert!(engine_version.starts_with("sparrow "));
+        assert!(engine_version.contains(", jagua-rs "));
+        assert!(!engine_version.contains("unknown"));
+        assert_eq!(output.app_version.as_deref(), Some(APP_VERSION));
+
+        let config = output.resolved_config.as_ref().unwrap();
+        assert_eq!(Provenance::of(&output), Provenance::current(config));
+        assert_eq!(output.algorithm_fingerprint.as_ref().unwrap().len(), 12);
+    }
+}
@@ -0,0 +1,154 @@
+//! Quality presets for the separator
+//!
+//! Sparrow's separator stops a strike after `iter_no_imprv_limit`
+//! iterations without improvement and gives up after `strike_limit`
+//! strikes. Lower limits answer faster and leave more room in the time
+//! budget for further attempts, higher limits resolve hard overlaps
+//! better. Users pick one of three presets instead of the raw limits:
+//! `Fast` keeps the aggressive limits quoting always used, `Best` uses
+//! sparrow's defaults and `Balanced` sits halfway between them.
+
+use serde::{Deserialize, Serialize};
+use sparrow::config::{SparrowConfig, DEFAULT_SPARROW_CONFIG};
+
+/// Separator limits of `Quality::Fast` (exploration, compression)
+const FAST_LIMITS: SeparatorLimits = SeparatorLimits {
+    explore_strike_limit: 3,
+    explore_iter_no_imprv_limit: 50,
+    compress_strike_limit: 2,
+    compress_iter_no_imprv_limit: 25,
+};
+
+/// Trade-off between response time and strip length
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Quality {
+    /// Aggressive limits, the quick quote
+    #[default]
+    Fast,
+    /// Halfway between `Fast` and `Best`
+    Balanced,
+    /// Sparrow's own defaults, for production layouts
+    Best,
+}
+
+/// Separator limits a run used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeparatorLimits {
+    pub explore_strike_limit: usize,
+    pub explore_iter_no_imprv_limit: usize,
+    pub compress_strike_limit: usize,
+    pub compress_iter_no_imprv_limit: usize,
+}
+
+/// Quality preset of a run and the limits it stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QualitySettings {
+    pub quality: Quality,
+    #[serde(flatten)]
+    pub limits: SeparatorLimits,
+}
+
+impl SeparatorLimits {
+    /// Limits of a sparrow configuration
+    fn of(config: &SparrowConfig) -> Self {
+        Self {
+            explore_strike_limit: config.expl_cfg.separator_config.strike_limit,
+            explore_iter_no_imprv_limit: config.expl_cfg.separator_config.iter_no_imprv_limit,
+            compress_strike_limit: config.cmpr_cfg.separator_config.strike_limit,
+            compress_iter_no_imprv_limit: config.cmpr_cfg.separator_config.iter_no_imprv_limit,
+        }
+    }
+
+    /// Limits halfway between `self` and `other`, rounded up
+    fn midpoint(self, other: Self) -> Self {
+        let mid = |a: usize, b: usize| (a + b).div_ceil(2);
+        Self {
+            explore_strike_limit: mid(self.explore_strike_limit, other.explore_strike_limit),
+            explore_iter_no_imprv_limit: mid(
+                self.explore_iter_no_imprv_limit,
+                other.explore_iter_no_imprv_limit,
+            ),
+            compress_strike_limit: mid(self.compress_strike_limit, other.compress_strike_limit),
+            compress_iter_no_imprv_limit: mid(
+                self.compress_iter_no_imprv_limit,
+                other.compress_iter_no_imprv_limit,
+            ),
+        }
+    }
+}
+
+impl Quality {
+    /// Separator limits of the preset
+    pub fn limits(self) -> SeparatorLimits {
+        let defaults = SeparatorLimits::of(&DEFAULT_SPARROW_CONFIG);
+        match self {
+            Quality::Fast => FAST_LIMITS,
+            Quality::Balanced => FAST_LIMITS.midpoint(defaults),
+            Quality::Best => defaults,
+        }
+    }
+
+    /// Preset with its limits, as reported in the output
+    pub fn settings(self) -> QualitySettings {
+        QualitySettings {
+            quality: self,
+            limits: self.limits(),
+        }
+    }
+
+    /// Apply the preset's limits to both separator phases
+    pub fn apply(self, config: &mut SparrowConfig) {
+        let limits = self.limits();
+        let explore = &mut config.expl_cfg.separator_config;
+        explore.strike_limit = limits.explore_strike_limit;
+        explore.iter_no_imprv_limit = limits.explore_iter_no_imprv_limit;
+        let compress = &mut config.cmpr_cfg.separator_config;
+        compress.strike_limit = limits.compress_strike_limit;
+        compress.iter_no_imprv_limit = limits.compress_iter_no_imprv_limit;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_order_their_limits() {
+        let fast = Quality::Fast.limits();
+        let balanced = Quality::Balanced.limits();
+        let best = Quality::Best.limits();
+
+        assert_eq!(fast, FAST_LIMITS);
+        assert_eq!(best, SeparatorLimits::of(&DEFAULT_SPARROW_CONFIG));
+        for (fast, balanced, best) in [
+            (
+                fast.explore_iter_no_imprv_limit,
+                balanced.explore_iter_no_imprv_limit,
+                best.explore_iter_no_imprv_limit,
+            ),
+            (
+                fast.compress_iter_no_imprv_limit,
+                balanced.compress_iter_no_imprv_limit,
+                best.compress_iter_no_imprv_limit,
+            ),
+        ] {
+            assert!(fast.min(best) <= balanced && balanced <= fast.max(best));
+        }
+    }
+
+    #[test]
+    fn test_apply_and_report_match() {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        Quality::Balanced.apply(&mut config);
+        assert_eq!(SeparatorLimits::of(&config), Quality::Balanced.limits());
+
+        let settings = serde_json::to_value(Quality::Best.settings()).unwrap();
+        assert_eq!(settings["quality"], "best");
+        assert!(settings["explore_strike_limit"].is_u64());
+        assert_eq!(
+            serde_json::from_str::<Quality>(r#""balanced""#).unwrap(),
+            Quality::Balanced
+        );
+    }
+}
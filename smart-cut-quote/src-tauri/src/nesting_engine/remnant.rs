@@ -0,0 +1,313 @@
+//! Nesting into an irregular remnant (offcut) instead of a full strip
+//!
+//! Sparrow only nests rectangular strips. With a `container_polygon` the
+//! remnant's bounding box is nested as a fixed sheet, and placements not
+//! lying completely inside the outline, or reaching into one of its
+//! holes, are removed afterwards like sheet overflow. Utilization is
+//! relative to the remnant's own area.
+
+use super::geometry::{
+    boundary_distance, bounding_box, open_ring, point_in_polygon, polygon_area, self_intersection,
+    translate_polygon, DisplayUnits, Polygon,
+};
+use jagua_rs::probs::spp::entities::SPSolution;
+use serde::{Deserialize, Serialize};
+
+/// Distance a part may stick out of the remnant, absorbing the f32
+/// rounding of placements flush with its edge (mm)
+const CONTAINMENT_TOLERANCE: f64 = 0.01;
+
+/// Outline of a remnant, with holes (cut-outs already taken from it)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerPolygon {
+    pub outer: Polygon,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub holes: Vec<Polygon>,
+}
+
+impl ContainerPolygon {
+    /// Remnant moved so its bounding box starts at the origin
+    pub fn normalized(self) -> Self {
+        let Some((min_x, min_y, _, _)) = bounding_box(&self.outer) else {
+            return self;
+        };
+        let moved = |ring: &Polygon| translate_polygon(open_ring(ring), -min_x, -min_y);
+        Self {
+            outer: moved(&self.outer),
+            holes: self.holes.iter().map(moved).collect(),
+        }
+    }
+
+    /// Width and height of the bounding box
+    pub fn size(&self) -> (f64, f64) {
+        bounding_box(&self.outer).map_or((0.0, 0.0), |(min_x, min_y, max_x, max_y)| {
+            (max_x - min_x, max_y - min_y)
+        })
+    }
+
+    /// Usable area: the outline without its holes
+    pub fn area(&self) -> f64 {
+        polygon_area(open_ring(&self.outer))
+            - self
+                .holes
+                .iter()
+                .map(|hole| polygon_area(open_ring(hole)))
+                .sum::<f64>()
+    }
+
+    /// Remnant given in `from` units, in `units`
+    pub fn in_units(self, units: DisplayUnits, from: DisplayUnits) -> Self {
+        Self {
+            outer: units.polygon_from(&self.outer, from),
+            holes: self
+                .holes
+                .iter()
+                .map(|hole| units.polygon_from(hole, from))
+                .collect(),
+        }
+    }
+
+    /// True if the polygon lies inside the outline and outside every hole
+    pub fn contains(&self, polygon: &[(f64, f64)]) -> bool {
+        let outer = open_ring(&self.outer);
+        let sticks_out = polygon
+            .iter()
+            .any(|&point| !point_in_polygon(point, outer) && beyond(point, outer));
+        // Corners of the outline poking into the part
+        let corner_inside = outer
+            .iter()
+            .any(|&corner| point_in_polygon(corner, polygon) && beyond(corner, polygon));
+        !sticks_out
+            && !corner_inside
+            && !rings_cross(polygon, outer)
+            && !self
+                .holes
+                .iter()
+                .any(|hole| reaches_into(polygon, open_ring(hole)))
+    }
+}
+
+/// True if the polygon covers part of `zone`, touching does not count
+pub(super) fn reaches_into(polygon: &[(f64, f64)], zone: &[(f64, f64)]) -> bool {
+    polygon
+        .iter()
+        .any(|&point| point_in_polygon(point, zone) && beyond(point, zone))
+        || zone
+            .iter()
+            .any(|&corner| point_in_polygon(corner, polygon) && beyond(corner, polygon))
+        || rings_cross(polygon, zone)
+}
+
+/// True if the point is further than the tolerance from the ring's boundary
+fn beyond(point: (f64, f64), ring: &[(f64, f64)]) -> bool {
+    boundary_distance(&[point], ring) > CONTAINMENT_TOLERANCE
+}
+
+/// Reject remnants that are too small or whose outlines cross themselves
+pub fn validate_container(container: &ContainerPolygon) -> Result<(), String> {
+    let rings = std::iter::once(("outline".to_string(), &container.outer)).chain(
+        container
+            .holes
+            .iter()
+            .enumerate()
+            .map(|(i, hole)| (format!("hole {}", i), hole)),
+    );
+    for (name, ring) in rings {
+        let ring = open_ring(ring);
+        if ring.iter().any(|&(x, y)| !x.is_finite() || !y.is_finite()) {
+            return Err(format!("Remnant {} has non-finite coordinates", name));
+        }
+        if ring.len() >= 3 {
+            if let Some((a, b)) = self_intersection(ring) {
+                return Err(format!(
+                    "Remnant {} intersects itself (edges {} and {}); redraw it as a simple outline",
+                    name, a, b
+                ));
+            }
+        }
+        if ring.len() < 3 || polygon_area(ring) <= 0.0 {
+            return Err(format!(
+                "Remnant {} needs at least 3 points enclosing an area",
+                name
+            ));
+        }
+    }
+    for (i, hole) in container.holes.iter().enumerate() {
+        let outer = open_ring(&container.outer);
+        let hole = open_ring(hole);
+        if rings_cross(hole, outer) || !hole.iter().all(|&point| point_in_polygon(point, outer)) {
+            return Err(format!("Remnant hole {} is not inside the outline", i));
+        }
+    }
+    if container.area() <= 0.0 {
+        return Err("Remnant holes cover its whole area".to_string());
+    }
+    Ok(())
+}
+
+/// Instance JSON with the strip as high as the remnant
+pub fn with_strip_height(json_str: &str, height: f64) -> Result<String, String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    value["strip_height"] = serde_json::json!(height);
+    serde_json::to_string(&value).map_err(|e| format!("Failed to serialize instance: {}", e))
+}
+
+/// Remove placements not lying completely on the remnant
+///
+/// # Returns
+/// Item IDs of the removed placements (one entry per removed copy)
+pub fn evict_outside(solution: &mut SPSolution, container: &ContainerPolygon) -> Vec<usize> {
+    let outside: Vec<_> = solution
+        .layout_snapshot
+        .placed_items
+        .iter()
+        .filter(|(_, placed_item)| {
+            let polygon: Polygon = placed_item
+                .shape
+                .vertices
+                .iter()
+                .map(|p| (p.0 as f64, p.1 as f64))
+                .collect();
+            !container.contains(&polygon)
+        })
+        .map(|(key, placed_item)| (key, placed_item.item_id))
+        .collect();
+
+    outside
+        .into_iter()
+        .map(|(key, item_id)| {
+            solution.layout_snapshot.placed_items.remove(key);
+            item_id
+        })
+        .collect()
+}
+
+/// Render the remnant: the area outside it greyed out, its outline drawn
+pub fn render_container_overlay(container: &ContainerPolygon) -> String {
+    let (width, height) = container.size();
+    let ring_path = |ring: &Polygon| {
+        let mut path = String::new();
+        for (i, (x, y)) in open_ring(ring).iter().enumerate() {
+            path.push_str(&format!("{}{} {} ", if i == 0 { "M" } else { "L" }, x, y));
+        }
+        path.push('Z');
+        path
+    };
+    let outline: Vec<String> = std::iter::once(&container.outer)
+        .chain(&container.holes)
+        .map(ring_path)
+        .collect();
+    let outline = outline.join(" ");
+
+    format!(
+        concat!(
+            r#"<g id="remnant" pointer-events="none">"#,
+            r#"<path d="M0 0 L{w} 0 L{w} {h} L0 {h} Z {outline}" fill="grey" fill-opacity="0.35" fill-rule="evenodd"/>"#,
+            r#"<path d="{outline}" fill="none" stroke="black" stroke-width="2"/>"#,
+            "</g>"
+        ),
+        w = width,
+        h = height,
+        outline = outline
+    )
+}
+
+/// True if an edge of `a` crosses an edge of `b` by more than the tolerance
+///
+/// Edges lying on each other, as for parts flush with the remnant edge,
+/// do not cross.
+pub(super) fn rings_cross(a: &[(f64, f64)], b: &[(f64, f64)]) -> bool {
+    // Signed distance of `p` from the line through `s1`-`s2`
+    let side = |s1: (f64, f64), s2: (f64, f64), p: (f64, f64)| {
+        let length = ((s2.0 - s1.0).powi(2) + (s2.1 - s1.1).powi(2)).sqrt();
+        if length == 0.0 {
+            return 0.0;
+        }
+        ((s2.0 - s1.0) * (p.1 - s1.1) - (s2.1 - s1.1) * (p.0 - s1.0)) / length
+    };
+    let opposite = |d1: f64, d2: f64| {
+        (d1 > CONTAINMENT_TOLERANCE && d2 < -CONTAINMENT_TOLERANCE)
+            || (d1 < -CONTAINMENT_TOLERANCE && d2 > CONTAINMENT_TOLERANCE)
+    };
+
+    (0..a.len()).any(|i| {
+        let (a1, a2) = (a[i], a[(i + 1) % a.len()]);
+        (0..b.len()).any(|j| {
+            let (b1, b2) = (b[j], b[(j + 1) % b.len()]);
+            opposite(side(b1, b2, a1), side(b1, b2, a2))
+                && opposite(side(a1, a2, b1), side(a1, a2, b2))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1000 x 600 L-shaped offcut, the top right 500 x 300 already cut away
+    fn l_remnant() -> ContainerPolygon {
+        ContainerPolygon {
+            outer: vec![
+                (0.0, 0.0),
+                (1000.0, 0.0),
+                (1000.0, 300.0),
+                (500.0, 300.0),
+                (500.0, 600.0),
+                (0.0, 600.0),
+            ],
+            holes: vec![vec![
+                (100.0, 100.0),
+                (200.0, 100.0),
+                (200.0, 200.0),
+                (100.0, 200.0),
+            ]],
+        }
+    }
+
+    fn square(x: f64, y: f64, size: f64) -> Polygon {
+        vec![(x, y), (x + size, y), (x + size, y + size), (x, y + size)]
+    }
+
+    #[test]
+    fn test_contains_parts_flush_with_the_outline() {
+        let remnant = l_remnant();
+        assert!(remnant.contains(&square(0.0, 400.0, 100.0)));
+        assert!(remnant.contains(&square(900.0, 200.0, 100.0)));
+        // In the cut-away corner
+        assert!(!remnant.contains(&square(600.0, 400.0, 100.0)));
+        // Across the inner corner, no vertex of the part outside
+        assert!(!remnant.contains(&square(450.0, 250.0, 100.0)));
+        // Covering the hole, and reaching into it
+        assert!(!remnant.contains(&square(50.0, 50.0, 200.0)));
+        assert!(!remnant.contains(&square(150.0, 150.0, 100.0)));
+    }
+
+    #[test]
+    fn test_area_and_normalization() {
+        let remnant = ContainerPolygon {
+            outer: translate_polygon(&l_remnant().outer, 2000.0, -50.0),
+            holes: vec![translate_polygon(&l_remnant().holes[0], 2000.0, -50.0)],
+        }
+        .normalized();
+        assert_eq!(remnant, l_remnant());
+        assert_eq!(remnant.size(), (1000.0, 600.0));
+        assert!((remnant.area() - (1000.0 * 300.0 + 500.0 * 300.0 - 100.0 * 100.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_validation_rejects_self_intersecting_outline() {
+        assert!(validate_container(&l_remnant()).is_ok());
+
+        let bow_tie = ContainerPolygon {
+            outer: vec![(0.0, 0.0), (100.0, 100.0), (100.0, 0.0), (0.0, 100.0)],
+            holes: Vec::new(),
+        };
+        let message = validate_container(&bow_tie).unwrap_err();
+        assert!(message.contains("intersects itself"), "{}", message);
+
+        let mut stray_hole = l_remnant();
+        stray_hole.holes = vec![square(700.0, 400.0, 50.0)];
+        assert!(validate_container(&stray_hole).is_err());
+    }
+}
@@ -0,0 +1,460 @@
+//! Re-nesting only the revised parts of a stored layout
+//!
+//! When two parts out of forty get a new revision, the rest of the
+//! layout is still good. `renest_subset` keeps every placement of the
+//! unchanged items where the stored output has it, like locked parts,
+//! and places the revised copies (and any copies the layout lacks) into
+//! the space the old versions freed and the rest of the sheet. Sparrow
+//! only enforces locked parts after optimizing, so the copies are placed
+//! here instead: largest first, each at the leftmost, then lowest free
+//! position of a grid scan over the sheet in any of its orientations,
+//! kept the separation away from everything placed so far.
+//!
+//! Items keep their IDs across a revision. Besides the requested items,
+//! every item whose geometry differs from the stored instance (or that
+//! is new) is placed again, a kept copy would have a stale outline. Parts
+//! the stored layout has in holes of other parts are placed again too.
+//!
+//! The copies have to fit the sheet of the stored layout: a fixed sheet
+//! keeps its length, a strip the length it used. When they do not, or
+//! the layout cannot be kept (several sheets, a remnant, mirrored parts,
+//! another strip height), `renest_subset` fails with the reason and the
+//! caller nests the whole job again.
+
+use super::chunking::orientations;
+use super::cut_length::attach_cut_totals;
+use super::defects;
+use super::fingerprint::{compare_fingerprints, fingerprint_instance, FingerprintChange};
+use super::geometry::{
+    bounding_box, offset_ring, open_ring, rotate_polygon, translate_polygon, Polygon,
+};
+use super::grain::attach_bend_angles;
+use super::holes::host_translation;
+use super::instance::{parse_instance, InstanceItem};
+use super::nesting::NestingConfig;
+use super::remnant::reaches_into;
+use super::serializer::{NestingOutput, PlacedItem};
+use super::verify::verify_output;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Bounds of the grid step of the free space scan (mm)
+const MIN_SCAN_STEP: f64 = 1.0;
+const MAX_SCAN_STEP: f64 = 10.0;
+
+/// Rounding slack when fitting parts on the sheet (mm)
+const FIT_EPSILON: f64 = 1e-9;
+
+/// Where the placements of a re-nested layout come from
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlacementOrigins {
+    /// Indices into `layouts` of placements kept from the stored layout
+    pub preserved: Vec<usize>,
+    /// Indices into `layouts` of placements made by this re-nest
+    pub renested: Vec<usize>,
+}
+
+/// Stored layout with the revised items placed again
+#[derive(Debug, Clone)]
+pub struct SubsetLayout {
+    pub output: NestingOutput,
+    /// Items placed again: the requested ones, new ones and any other
+    /// item whose geometry changed
+    pub changed_item_ids: Vec<u64>,
+    pub origins: PlacementOrigins,
+}
+
+/// Part on the new layout the next copies keep clear of
+struct Obstacle {
+    /// Outer contour grown by the part's extra separation
+    zone: Polygon,
+    bbox: (f64, f64, f64, f64),
+}
+
+impl Obstacle {
+    fn new(outline: &[(f64, f64)], extra_separation: f64) -> Option<Self> {
+        let zone = if extra_separation > 0.0 {
+            offset_ring(outline, extra_separation)
+        } else {
+            open_ring(outline).to_vec()
+        };
+        let bbox = bounding_box(&zone)?;
+        Some(Self { zone, bbox })
+    }
+}
+
+/// Length of the sheet the revised copies have to fit: the stored fixed
+/// sheet, or the length of strip the stored layout used (mm)
+pub fn subset_sheet_length(output: &NestingOutput) -> f64 {
+    output.sheet_width.unwrap_or(output.strip_width)
+}
+
+/// Outer contour of a placement in strip coordinates
+///
+/// Located by the placement's outline when it has one, like the exports
+/// place contours (see `layout_dxf`), by its position otherwise.
+fn placed_outline(item: &InstanceItem, placed: &PlacedItem) -> Polygon {
+    let (dx, dy) = placed
+        .outline
+        .as_ref()
+        .and_then(|outline| host_translation(&item.outer, placed.rotation_degrees, outline))
+        .unwrap_or((placed.position_x, placed.position_y));
+    translate_polygon(
+        &rotate_polygon(open_ring(&item.outer), placed.rotation_degrees),
+        dx,
+        dy,
+    )
+}
+
+/// Keep the unchanged placements of a stored layout, place the rest
+///
+/// # Arguments
+/// * `stored` - Stored layout (in mm) with its resolved config
+/// * `stored_json` - Instance the stored layout was nested from
+/// * `updated_json` - Instance after the revision
+/// * `changed_item_ids` - Items to place again
+///
+/// Returns why the layout cannot be kept as the error.
+pub fn renest_subset(
+    stored: &NestingOutput,
+    stored_json: &str,
+    updated_json: &str,
+    changed_item_ids: &[u64],
+) -> Result<SubsetLayout, String> {
+    if !stored.sheets.is_empty() || stored.repeated_sheet.is_some() {
+        return Err("the stored layout spans several sheets".to_string());
+    }
+    if stored.container_polygon.is_some() {
+        return Err("the stored layout is nested on a remnant".to_string());
+    }
+    let config = stored
+        .resolved_config
+        .as_ref()
+        .ok_or("the stored layout has no resolved config")?;
+
+    let updated_fingerprint = fingerprint_instance(updated_json)?;
+    let mut changed: BTreeSet<u64> = changed_item_ids.iter().copied().collect();
+    for change in compare_fingerprints(&fingerprint_instance(stored_json)?, &updated_fingerprint) {
+        match change {
+            FingerprintChange::StripHeightChanged { .. } => {
+                return Err("the strip height changed".to_string());
+            }
+            FingerprintChange::ItemAdded { item_id }
+            | FingerprintChange::GeometryChanged { item_id } => {
+                changed.insert(item_id);
+            }
+            FingerprintChange::ItemRemoved { .. } | FingerprintChange::QuantityChanged { .. } => {}
+        }
+    }
+    let updated = parse_instance(updated_json)?;
+
+    // Copies of the unchanged items stay, up to their new demand
+    let mut layouts: Vec<PlacedItem> = Vec::new();
+    let mut obstacles: Vec<Obstacle> = Vec::new();
+    let mut missing: Vec<(&InstanceItem, usize)> = Vec::new();
+    for item in &updated.items {
+        let kept: Vec<&PlacedItem> = if changed.contains(&item.id) {
+            Vec::new()
+        } else {
+            stored
+                .layouts
+                .iter()
+                .filter(|placed| placed.item_id as u64 == item.id && placed.host_item_id.is_none())
+                .take(item.demand as usize)
+                .collect()
+        };
+        if kept.iter().any(|placed| placed.mirrored) {
+            return Err(format!("item {} is placed mirrored", item.id));
+        }
+        for placed in &kept {
+            obstacles.extend(Obstacle::new(
+                &placed_outline(item, placed),
+                item.extra_separation,
+            ));
+        }
+        layouts.extend(kept.iter().map(|&placed| placed.clone()));
+        if kept.len() < item.demand as usize {
+            missing.push((item, item.demand as usize - kept.len()));
+        }
+    }
+    let preserved = layouts.len();
+
+    let sheet = (subset_sheet_length(stored), stored.strip_height);
+    missing.sort_by(|(a, _), (b, _)| b.net_area().total_cmp(&a.net_area()));
+    for (item, copies) in missing {
+        let rotations = orientations(item, config);
+        for _ in 0..copies {
+            let placed = free_position(item, &rotations, &obstacles, sheet, config)
+                .ok_or_else(|| format!("item {} does not fit around the kept parts", item.id))?;
+            obstacles.extend(Obstacle::new(
+                &placed_outline(item, &placed),
+                item.extra_separation,
+            ));
+            layouts.push(placed);
+        }
+    }
+
+    let mut output = stored.clone();
+    output.layouts = layouts;
+    output.attach_item_metadata(&updated.items);
+    attach_bend_angles(&mut output.layouts, &updated.items);
+    output.total_items_placed = output.layouts.len();
+    output.items_requested = Some(updated.items.iter().map(|item| item.demand as usize).sum());
+    output.unplaced_item_ids.clear();
+    output.unplaced_reasons.clear();
+    let placed_area: f64 = output
+        .layouts
+        .iter()
+        .filter_map(|placed| {
+            updated
+                .items
+                .iter()
+                .find(|item| item.id == placed.item_id as u64)
+        })
+        .map(InstanceItem::net_area)
+        .sum();
+    let usable_area = stored
+        .usable_area
+        .unwrap_or(stored.strip_width * stored.strip_height);
+    output.utilization = if usable_area > 0.0 {
+        placed_area / usable_area
+    } else {
+        0.0
+    };
+    attach_cut_totals(&mut output, &updated.items);
+    output.instance_hash = Some(updated_fingerprint.instance_hash);
+    output
+        .min_web_violations
+        .retain(|violation| !changed.contains(&violation.item_id));
+    // Only layouts whose every placement has an outline can be checked
+    output.violations = verify_output(&output, &updated).unwrap_or_default();
+    output.status = Some("complete".to_string());
+
+    // Reports of the optimizer run, which the placed copies did not have
+    output.svg_string = None;
+    output.svg_warnings.clear();
+    output.utilization_grid = None;
+    output.config_trace = None;
+    output.restarts.clear();
+    output.stage_timings = None;
+    output.stats = None;
+    output.phase_stats = None;
+    output.warm_started = false;
+    output.target_reached_secs = None;
+    output.keep_out_conflicts.clear();
+    output.defect_conflicts.clear();
+    output.sanity_warning = None;
+    output.suggestions.clear();
+
+    let origins = PlacementOrigins {
+        preserved: (0..preserved).collect(),
+        renested: (preserved..output.layouts.len()).collect(),
+    };
+    Ok(SubsetLayout {
+        output,
+        changed_item_ids: changed.into_iter().collect(),
+        origins,
+    })
+}
+
+/// Leftmost, then lowest position where a copy of the item fits
+///
+/// Every orientation is scanned on a grid of a quarter of the part's
+/// smaller side, kept between `MIN_SCAN_STEP` and `MAX_SCAN_STEP`.
+fn free_position(
+    item: &InstanceItem,
+    rotations: &[f64],
+    obstacles: &[Obstacle],
+    (length, height): (f64, f64),
+    config: &NestingConfig,
+) -> Option<PlacedItem> {
+    // Nominal outlines are a cut separation apart, and half of it plus
+    // the separation away from the sheet edge
+    let clearance = config.cut_separation() + item.extra_separation;
+    let margin = config.separation + config.kerf_width / 2.0 + item.extra_separation;
+
+    // Corner of the rotated bounding box and the rotation
+    let mut best: Option<(f64, f64, f64)> = None;
+    for &rotation in rotations {
+        let rotated = rotate_polygon(open_ring(&item.outer), rotation);
+        let Some((min_x, min_y, max_x, max_y)) = bounding_box(&rotated) else {
+            continue;
+        };
+        let (width, depth) = (max_x - min_x, max_y - min_y);
+        let grown = offset_ring(&rotated, clearance);
+        let step = (width.min(depth) / 4.0).clamp(MIN_SCAN_STEP, MAX_SCAN_STEP);
+
+        let fits = |x: f64, y: f64| {
+            let (dx, dy) = (x - min_x, y - min_y);
+            let zone = translate_polygon(&grown, dx, dy);
+            let Some(bbox) = bounding_box(&zone) else {
+                return false;
+            };
+            let outline = translate_polygon(&rotated, dx, dy);
+            obstacles.iter().all(|obstacle| {
+                !overlaps_bbox(bbox, obstacle.bbox) || !reaches_into(&zone, &obstacle.zone)
+            }) && !config
+                .keep_out_zones
+                .iter()
+                .any(|keep_out| keep_out.overlaps(&outline))
+                && !defects::overlaps_any(&config.holes, &outline)
+        };
+
+        // Only positions left of the best so far can win
+        let max_start = best.map_or(length, |(x, _, _)| x);
+        let mut x = margin;
+        'columns: while x + width + margin <= length + FIT_EPSILON && x <= max_start {
+            let mut y = margin;
+            while y + depth + margin <= height + FIT_EPSILON {
+                if fits(x, y) {
+                    if best.is_none_or(|(best_x, best_y, _)| (x, y) < (best_x, best_y)) {
+                        best = Some((x, y, rotation));
+                    }
+                    break 'columns;
+                }
+                y += step;
+            }
+            x += step;
+        }
+    }
+
+    let (x, y, rotation) = best?;
+    let rotated = rotate_polygon(open_ring(&item.outer), rotation);
+    let (min_x, min_y, _, _) = bounding_box(&rotated)?;
+    // The exports locate contours by the outline, not the position
+    let outline = translate_polygon(&rotated, x - min_x, y - min_y);
+    Some(PlacedItem {
+        item_id: item.id as usize,
+        rotation_degrees: rotation,
+        position_x: x - min_x,
+        position_y: y - min_y,
+        host_item_id: None,
+        display_outline: Some(outline.clone()),
+        outline: Some(outline),
+        metadata: None,
+        bend_line_angle_deg: None,
+        post_placed: false,
+        mirrored: false,
+        locked: false,
+        rotation_radians: None,
+        filler: false,
+    })
+}
+
+fn overlaps_bbox(a: (f64, f64, f64, f64), b: (f64, f64, f64, f64)) -> bool {
+    a.0 < b.2 && b.0 < a.2 && a.1 < b.3 && b.1 < a.3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{run_nesting_engine, NestingInput};
+    use super::*;
+    use serde_json::Value;
+
+    /// Plates of 40 x 30 and 30 x 30, and a `last` x 20 plate
+    fn instance(last: f64) -> String {
+        let rectangle = |width: f64, height: f64| {
+            serde_json::json!({
+                "type": "rectangle",
+                "data": { "x_min": 0.0, "y_min": 0.0, "width": width, "height": height }
+            })
+        };
+        serde_json::json!({
+            "name": "renest",
+            "strip_height": 100.0,
+            "items": [
+                { "id": 0, "demand": 3, "shape": rectangle(40.0, 30.0) },
+                { "id": 1, "demand": 1, "shape": rectangle(30.0, 30.0) },
+                { "id": 2, "demand": 2, "shape": rectangle(last, 20.0) }
+            ]
+        })
+        .to_string()
+    }
+
+    /// The instance nested by the engine for its resolved config, then
+    /// laid out by hand on an 85 mm strip
+    fn stored(json: &str) -> NestingOutput {
+        let mut output = run_nesting_engine(NestingInput {
+            json_input: json.to_string(),
+            time_limit: Some(1),
+            seed: Some(7),
+            use_early_termination: Some(true),
+            ..Default::default()
+        })
+        .unwrap();
+        output.strip_width = 85.0;
+        output.layouts = serde_json::from_value(serde_json::json!([
+            { "item_id": 0, "rotation_degrees": 0.0, "position_x": 1.0, "position_y": 1.0 },
+            { "item_id": 0, "rotation_degrees": 0.0, "position_x": 1.0, "position_y": 33.0 },
+            { "item_id": 0, "rotation_degrees": 0.0, "position_x": 43.0, "position_y": 1.0 },
+            { "item_id": 1, "rotation_degrees": 0.0, "position_x": 43.0, "position_y": 33.0 },
+            { "item_id": 2, "rotation_degrees": 0.0, "position_x": 1.0, "position_y": 65.0 },
+            { "item_id": 2, "rotation_degrees": 0.0, "position_x": 23.0, "position_y": 65.0 }
+        ]))
+        .unwrap();
+        output
+    }
+
+    #[test]
+    fn test_unchanged_placements_stay_where_they_were() {
+        let stored_json = instance(20.0);
+        let stored = stored(&stored_json);
+        let updated_json = instance(15.0);
+
+        // Item 2 changed its geometry without being listed
+        let subset = renest_subset(&stored, &stored_json, &updated_json, &[1]).unwrap();
+        assert_eq!(subset.changed_item_ids, vec![1, 2]);
+        assert_eq!(subset.origins.preserved, vec![0, 1, 2]);
+        assert_eq!(subset.origins.renested, vec![3, 4, 5]);
+
+        let output = &subset.output;
+        assert_eq!(output.total_items_placed, 6);
+        assert_eq!(output.strip_width, 85.0);
+        assert_eq!(
+            output.layouts[..3]
+                .iter()
+                .map(|placed| (placed.item_id, placed.position_x, placed.position_y))
+                .collect::<Vec<_>>(),
+            vec![(0, 1.0, 1.0), (0, 1.0, 33.0), (0, 43.0, 1.0)]
+        );
+        assert_eq!(output.layouts[3].item_id, 1);
+
+        // New copies stay on the stored strip, clear of every other part
+        let items = parse_instance(&updated_json).unwrap().items;
+        let outlines: Vec<Polygon> = output
+            .layouts
+            .iter()
+            .map(|placed| placed_outline(&items[placed.item_id], placed))
+            .collect();
+        for &index in &subset.origins.renested {
+            assert_eq!(
+                output.layouts[index].outline.as_ref(),
+                Some(&outlines[index])
+            );
+            let (min_x, min_y, max_x, max_y) = bounding_box(&outlines[index]).unwrap();
+            assert!(min_x >= 1.0 - 1e-6 && min_y >= 1.0 - 1e-6);
+            assert!(max_x <= 84.0 + 1e-6 && max_y <= 99.0 + 1e-6);
+            for (other, outline) in outlines.iter().enumerate() {
+                assert!(other == index || !reaches_into(&outlines[index], outline));
+            }
+        }
+    }
+
+    #[test]
+    fn test_layouts_that_cannot_be_kept_give_the_reason() {
+        let json = instance(20.0);
+        let mut stored = stored(&json);
+
+        let mut taller: Value = serde_json::from_str(&json).unwrap();
+        taller["strip_height"] = 120.0.into();
+        let reason = renest_subset(&stored, &json, &taller.to_string(), &[]).unwrap_err();
+        assert_eq!(reason, "the strip height changed");
+
+        let reason = renest_subset(&stored, &json, &instance(500.0), &[]).unwrap_err();
+        assert_eq!(reason, "item 2 does not fit around the kept parts");
+
+        stored.layouts[0].mirrored = true;
+        let reason = renest_subset(&stored, &json, &json, &[]).unwrap_err();
+        assert_eq!(reason, "item 0 is placed mirrored");
+    }
+}
@@ -0,0 +1,284 @@
+//! Independent optimizer runs with different seeds
+//!
+//! The strip sparrow ends up with depends noticeably on the seed. With
+//! `n_restarts` the time limit is split evenly over that many runs, one
+//! after another, each with its own seed; the best layout is kept: the
+//! one leaving the fewest parts unplaced, then the shortest strip.
+//!
+//! With a seed given, restart `i` uses `seed + i`, so a set of restarts
+//! is reproducible. The winner's `resolved_config` holds its own seed and
+//! time share, replaying it needs no restarts.
+
+use super::serializer::NestingOutput;
+use super::terminator::{NativeTerminator, CANCELLED_BEFORE_SOLUTION};
+use super::NestingInput;
+use serde::{Deserialize, Serialize};
+
+/// Summary of one restart, in run order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestartSummary {
+    pub seed: u64,
+    pub strip_width: f64,
+    pub utilization: f64,
+    pub total_items_placed: usize,
+    /// This restart produced the returned layout
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub winner: bool,
+}
+
+/// Seconds each of `restarts` runs gets out of `time_limit` (at least 1)
+pub fn restart_time_limit(time_limit: u64, restarts: usize) -> u64 {
+    (time_limit / restarts.max(1) as u64).max(1)
+}
+
+/// Seed of restart `restart`, None lets the optimizer pick one
+pub fn restart_seed(seed: Option<u64>, restart: usize) -> Option<u64> {
+    seed.map(|seed| seed.wrapping_add(restart as u64))
+}
+
+/// True if `candidate` is a better layout than `best`
+fn is_better(candidate: &NestingOutput, best: &NestingOutput) -> bool {
+    let unplaced = |output: &NestingOutput| output.unplaced_item_ids.len();
+    match unplaced(candidate).cmp(&unplaced(best)) {
+        std::cmp::Ordering::Less => true,
+        std::cmp::Ordering::Greater => false,
+        std::cmp::Ordering::Equal => candidate.strip_width < best.strip_width,
+    }
+}
+
+/// Nest `restarts` times through `nest` and return the best result
+///
+/// Every run gets a child of `terminator`: cancelling stops the current
+/// run, which still counts with its layout so far, and skips the rest;
+/// the returned output then has status "cancelled". A restart reaching
+/// `target_utilization` skips the rest as well.
+/// The returned output lists every restart in `restarts` and the total
+/// computation time of all of them.
+///
+/// # Arguments
+/// * `input` - Input of the whole run, `time_limit` is split
+/// * `time_limit` - Resolved time limit of the whole run in seconds
+/// * `nest` - Nests one restart's input (`n_restarts` cleared)
+pub fn run_restarts<R>(
+    input: &NestingInput,
+    time_limit: u64,
+    restarts: usize,
+    terminator: &NativeTerminator,
+    mut nest: impl FnMut(NestingInput, NativeTerminator) -> Result<(NestingOutput, R), String>,
+) -> Result<(NestingOutput, R), String> {
+    let share = restart_time_limit(time_limit, restarts);
+    let mut best: Option<(usize, NestingOutput, R)> = None;
+    let mut summaries = Vec::new();
+    let mut computation_time_secs = 0.0;
+    let mut target_reached_secs = None;
+
+    for restart in 0..restarts.max(1) {
+        if restart > 0 && terminator.is_terminated() {
+            println!(
+                "🛑 Nesting cancelled, skipping {} restarts",
+                restarts - restart
+            );
+            break;
+        }
+        let attempt = NestingInput {
+            time_limit: Some(share),
+            seed: restart_seed(input.seed, restart),
+            n_restarts: None,
+            ..input.clone()
+        };
+        let (output, run) = match nest(attempt, terminator.child()) {
+            Ok(nested) => nested,
+            // Cancelled before this restart found a layout, an earlier one stands
+            Err(error) if best.is_some() && error.starts_with(CANCELLED_BEFORE_SOLUTION) => break,
+            Err(error) => return Err(error),
+        };
+
+        println!(
+            "🔁 Restart {}/{}: seed {:?}, strip width {:.1}, utilization {:.1}%",
+            restart + 1,
+            restarts,
+            output.seed,
+            output.strip_width,
+            output.utilization * 100.0
+        );
+        // Seconds into the whole run, earlier restarts included
+        let reached_secs = output
+            .target_reached_secs
+            .map(|secs| computation_time_secs + secs);
+        computation_time_secs += output.computation_time_secs;
+        summaries.push(RestartSummary {
+            seed: output.seed.unwrap_or_default(),
+            strip_width: output.strip_width,
+            utilization: output.utilization,
+            total_items_placed: output.total_items_placed,
+            winner: false,
+        });
+        let better = match &best {
+            Some((_, best, _)) => is_better(&output, best),
+            None => true,
+        };
+        if better {
+            best = Some((restart, output, run));
+        }
+        if reached_secs.is_some() {
+            println!(
+                "🎯 Target utilization reached, skipping {} restarts",
+                restarts - restart - 1
+            );
+            target_reached_secs = reached_secs;
+            break;
+        }
+    }
+
+    let (winner, mut output, run) = best.ok_or("No restart was run")?;
+    summaries[winner].winner = true;
+    output.restarts = summaries;
+    output.computation_time_secs = computation_time_secs;
+    if terminator.is_cancelled() {
+        output.status = Some("cancelled".to_string());
+    } else if target_reached_secs.is_some() {
+        // The winner is at least as good as the restart that reached it
+        output.status = Some("target_reached".to_string());
+        output.target_reached_secs = target_reached_secs;
+    }
+    Ok((output, run))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(seed: u64, strip_width: f64, unplaced: Vec<usize>) -> NestingOutput {
+        serde_json::from_value(serde_json::json!({
+            "instance_name": "restarts",
+            "strip_width": strip_width,
+            "strip_height": 1000.0,
+            "total_items_placed": 10 - unplaced.len(),
+            "layouts": [],
+            "utilization": 8000.0 / strip_width,
+            "computation_time_secs": 2.0,
+            "unplaced_item_ids": unplaced,
+            "seed": seed,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_best_restart_wins_and_all_are_reported() {
+        let input = NestingInput {
+            seed: Some(40),
+            ..Default::default()
+        };
+        let widths = [120.0, 95.0, 90.0, 110.0];
+        let mut inputs = Vec::new();
+        let (best, restart) =
+            run_restarts(&input, 20, 4, &NativeTerminator::new(), |attempt, _| {
+                let restart = inputs.len();
+                inputs.push((attempt.seed, attempt.time_limit, attempt.n_restarts));
+                // The narrowest layout leaves a part unplaced
+                let unplaced = if restart == 2 { vec![3] } else { Vec::new() };
+                Ok((
+                    output(attempt.seed.unwrap(), widths[restart], unplaced),
+                    restart,
+                ))
+            })
+            .unwrap();
+
+        assert_eq!(
+            inputs,
+            vec![
+                (Some(40), Some(5), None),
+                (Some(41), Some(5), None),
+                (Some(42), Some(5), None),
+                (Some(43), Some(5), None),
+            ]
+        );
+        assert_eq!(restart, 1);
+        assert_eq!(best.seed, Some(41));
+        assert_eq!(best.computation_time_secs, 8.0);
+        let winners: Vec<_> = best.restarts.iter().map(|r| r.winner).collect();
+        assert_eq!(winners, vec![false, true, false, false]);
+        assert_eq!(best.restarts[2].total_items_placed, 9);
+    }
+
+    #[test]
+    fn test_cancel_skips_remaining_restarts() {
+        let terminator = NativeTerminator::new();
+        let mut runs = 0;
+        let (best, _) = run_restarts(&NestingInput::default(), 30, 3, &terminator, |_, child| {
+            runs += 1;
+            // Cancelled by the user while the first restart runs
+            terminator.terminate();
+            assert!(child.is_terminated());
+            Ok((output(7, 100.0, Vec::new()), ()))
+        })
+        .unwrap();
+
+        assert_eq!(runs, 1);
+        assert_eq!(best.restarts.len(), 1);
+        assert!(best.restarts[0].winner);
+        assert_eq!(best.status.as_deref(), Some("cancelled"));
+    }
+
+    #[test]
+    fn test_cancel_before_a_later_restart_finds_a_layout() {
+        let terminator = NativeTerminator::new();
+        let mut runs = 0;
+        let (best, restart) = run_restarts(&NestingInput::default(), 30, 3, &terminator, |_, _| {
+            runs += 1;
+            if runs == 1 {
+                return Ok((output(7, 100.0, Vec::new()), runs));
+            }
+            terminator.terminate();
+            Err(format!("{}: no layout yet", CANCELLED_BEFORE_SOLUTION))
+        })
+        .unwrap();
+
+        assert_eq!(runs, 2);
+        assert_eq!(restart, 1);
+        assert_eq!(best.status.as_deref(), Some("cancelled"));
+
+        // Nothing to fall back on
+        let terminator = NativeTerminator::new();
+        terminator.terminate();
+        let error = run_restarts(&NestingInput::default(), 30, 3, &terminator, |_, _| {
+            Err::<(NestingOutput, ()), _>(format!("{}: no layout yet", CANCELLED_BEFORE_SOLUTION))
+        })
+        .unwrap_err();
+        assert!(error.starts_with(CANCELLED_BEFORE_SOLUTION));
+    }
+
+    #[test]
+    fn test_target_reached_skips_remaining_restarts() {
+        let mut runs = 0;
+        let (best, _) = run_restarts(
+            &NestingInput::default(),
+            30,
+            3,
+            &NativeTerminator::new(),
+            |_, _| {
+                runs += 1;
+                let mut output = output(7, 100.0 - runs as f64, Vec::new());
+                if runs == 2 {
+                    output.status = Some("target_reached".to_string());
+                    output.target_reached_secs = Some(0.5);
+                }
+                Ok((output, ()))
+            },
+        )
+        .unwrap();
+
+        assert_eq!(runs, 2);
+        assert_eq!(best.status.as_deref(), Some("target_reached"));
+        // After the whole first restart
+        assert_eq!(best.target_reached_secs, Some(2.5));
+    }
+
+    #[test]
+    fn test_time_share_and_seeds() {
+        assert_eq!(restart_time_limit(60, 4), 15);
+        assert_eq!(restart_time_limit(2, 4), 1);
+        assert_eq!(restart_seed(None, 3), None);
+        assert_eq!(restart_seed(Some(u64::MAX), 1), Some(0));
+    }
+}
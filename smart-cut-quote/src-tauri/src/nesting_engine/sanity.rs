@@ -0,0 +1,311 @@
+//! Sanity check of a nesting result against the quick estimate
+//!
+//! A bad setting (a separation typo, parts locked to one orientation,
+//! oversized keep-out zones) can produce a "successful" layout using far
+//! more material than the parts need. The achieved strip length is
+//! compared with an area-based estimate; a result worse than that by
+//! more than a factor gets a warning naming the settings most likely
+//! responsible.
+
+use super::geometry::{bounding_box, open_ring, polygon_perimeter, rotate_polygon};
+use super::instance::{InstanceGeometry, InstanceItem};
+use super::keep_out::{blocked_area, KeepOutZone};
+use serde::{Deserialize, Serialize};
+
+/// Ratio of achieved to estimated strip length that triggers the warning
+pub const DEFAULT_SANITY_FACTOR: f64 = 1.5;
+
+/// Share of the strip a reasonable layout of irregular parts covers
+const EXPECTED_UTILIZATION: f64 = 0.75;
+
+/// Separation band around the parts, relative to their area, worth reporting
+const SEPARATION_SHARE_LIMIT: f64 = 0.15;
+
+/// Share of part copies locked to one orientation worth reporting
+const LOCKED_ROTATION_SHARE_LIMIT: f64 = 0.5;
+
+/// Share of the used strip blocked by keep-out zones worth reporting
+const BLOCKED_SHARE_LIMIT: f64 = 0.1;
+
+/// Setting that can make a layout use much more material than expected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SanityCulprit {
+    /// Distance kept between parts and to the strip edge
+    Separation,
+    /// Parts restricted to a single orientation
+    RotationRestrictions,
+    /// Sheet area lost to keep-out zones and margins
+    Margin,
+}
+
+/// Likely cause of a poor result, with the numbers behind it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LikelyCause {
+    pub culprit: SanityCulprit,
+    pub detail: String,
+}
+
+/// Result much worse than the area-based estimate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SanityWarning {
+    pub message: String,
+    /// Strip length the parts should roughly need (mm)
+    pub estimated_strip_width: f64,
+    /// Strip length the layout uses (mm)
+    pub actual_strip_width: f64,
+    /// `actual_strip_width / estimated_strip_width`
+    pub ratio: f64,
+    /// Ratio above which the warning is raised
+    pub factor: f64,
+    /// Most likely first, empty when no setting stands out
+    pub likely_causes: Vec<LikelyCause>,
+}
+
+/// Strip length the parts of an instance should roughly need
+///
+/// The larger of the net part area spread over the strip height at a
+/// typical utilization, and the narrowest extent of the widest part.
+pub fn quick_estimate_width(instance: &InstanceGeometry) -> f64 {
+    let height = instance.strip_height;
+    if height <= 0.0 {
+        return 0.0;
+    }
+
+    let net_area: f64 = instance
+        .items
+        .iter()
+        .map(|item| item.net_area() * item.demand as f64)
+        .sum();
+    let widest_part = instance
+        .items
+        .iter()
+        .filter(|item| item.demand > 0)
+        .map(|item| min_extent(item, height))
+        .fold(0.0, f64::max);
+
+    (net_area / (height * EXPECTED_UTILIZATION)).max(widest_part)
+}
+
+/// Compare a layout's strip length with the quick estimate
+///
+/// Returns a warning when the layout is more than `factor` times longer
+/// than estimated.
+///
+/// # Arguments
+/// * `instance` - Instance the layout was computed for
+/// * `strip_width` - Strip length used by the layout
+/// * `separation` - Item separation the optimizer kept (mm)
+/// * `keep_out_zones` - Zones the layout had to avoid
+/// * `factor` - Ratio above which the result is flagged
+pub fn check_against_estimate(
+    instance: &InstanceGeometry,
+    strip_width: f64,
+    separation: f64,
+    keep_out_zones: &[KeepOutZone],
+    factor: f64,
+) -> Option<SanityWarning> {
+    let estimated = quick_estimate_width(instance);
+    if estimated <= 0.0 || strip_width <= 0.0 {
+        return None;
+    }
+    let ratio = strip_width / estimated;
+    if ratio <= factor {
+        return None;
+    }
+
+    let likely_causes = likely_causes(instance, strip_width, separation, keep_out_zones);
+    let mut message = format!(
+        "Layout uses {:.0} mm of strip, {:.1}× the estimated {:.0} mm",
+        strip_width, ratio, estimated
+    );
+    if let Some(cause) = likely_causes.first() {
+        message.push_str(&format!("; check {}", cause.detail));
+    }
+
+    Some(SanityWarning {
+        message,
+        estimated_strip_width: estimated,
+        actual_strip_width: strip_width,
+        ratio,
+        factor,
+        likely_causes,
+    })
+}
+
+/// Settings that stand out for this instance, most significant first
+fn likely_causes(
+    instance: &InstanceGeometry,
+    strip_width: f64,
+    separation: f64,
+    keep_out_zones: &[KeepOutZone],
+) -> Vec<LikelyCause> {
+    // (share of the respective limit, cause)
+    let mut causes: Vec<(f64, LikelyCause)> = Vec::new();
+
+    let copies = |item: &InstanceItem| item.demand as f64;
+    let net_area: f64 = instance
+        .items
+        .iter()
+        .map(|i| i.net_area() * copies(i))
+        .sum();
+    let total_copies: f64 = instance.items.iter().map(copies).sum();
+
+    // Every part reserves a band of half the separation along its outline
+    let band_area: f64 = instance
+        .items
+        .iter()
+        .map(|item| polygon_perimeter(open_ring(&item.outer)) * separation / 2.0 * copies(item))
+        .sum();
+    if net_area > 0.0 && band_area / net_area >= SEPARATION_SHARE_LIMIT {
+        causes.push((
+            band_area / net_area / SEPARATION_SHARE_LIMIT,
+            LikelyCause {
+                culprit: SanityCulprit::Separation,
+                detail: format!(
+                    "the item separation of {} mm, it adds about {:.0}% to the part area",
+                    separation,
+                    band_area / net_area * 100.0
+                ),
+            },
+        ));
+    }
+
+    let locked: f64 = instance
+        .items
+        .iter()
+        .filter(|item| matches!(&item.allowed_orientations, Some(o) if o.len() <= 1))
+        .map(copies)
+        .sum();
+    if total_copies > 0.0 && locked / total_copies >= LOCKED_ROTATION_SHARE_LIMIT {
+        causes.push((
+            locked / total_copies / LOCKED_ROTATION_SHARE_LIMIT,
+            LikelyCause {
+                culprit: SanityCulprit::RotationRestrictions,
+                detail: format!(
+                    "the rotation restrictions, {} of {} parts may not rotate",
+                    locked, total_copies
+                ),
+            },
+        ));
+    }
+
+    let strip_area = strip_width * instance.strip_height;
+    let blocked = blocked_area(keep_out_zones, strip_width, instance.strip_height);
+    if strip_area > 0.0 && blocked / strip_area >= BLOCKED_SHARE_LIMIT {
+        causes.push((
+            blocked / strip_area / BLOCKED_SHARE_LIMIT,
+            LikelyCause {
+                culprit: SanityCulprit::Margin,
+                detail: format!(
+                    "the keep-out zones and margins, they block {:.0}% of the strip",
+                    blocked / strip_area * 100.0
+                ),
+            },
+        ));
+    }
+
+    causes.sort_by(|a, b| b.0.total_cmp(&a.0));
+    causes.into_iter().map(|(_, cause)| cause).collect()
+}
+
+/// Narrowest strip length a single copy of the item needs
+fn min_extent(item: &InstanceItem, strip_height: f64) -> f64 {
+    let outer = open_ring(&item.outer);
+    let orientations = match &item.allowed_orientations {
+        Some(orientations) if !orientations.is_empty() => orientations.clone(),
+        _ => vec![0.0, 90.0],
+    };
+
+    let extents: Vec<(f64, f64)> = orientations
+        .iter()
+        .filter_map(|&angle| bounding_box(&rotate_polygon(outer, angle)))
+        .map(|(min_x, min_y, max_x, max_y)| (max_x - min_x, max_y - min_y))
+        .collect();
+
+    // Prefer orientations that fit the strip height
+    extents
+        .iter()
+        .filter(|(_, height)| *height <= strip_height)
+        .chain(extents.iter())
+        .map(|(width, _)| *width)
+        .reduce(f64::min)
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::parse_instance;
+
+    /// 100 copies of a 20 x 20 plate on a 1000 mm high strip
+    fn plates(allowed_orientations: &str) -> InstanceGeometry {
+        parse_instance(&format!(
+            r#"{{
+                "strip_height": 1000,
+                "items": [{{
+                    "id": 0,
+                    "demand": 100,
+                    "allowed_orientations": {},
+                    "shape": {{"type": "rectangle", "data": {{"x_min": 0, "y_min": 0, "width": 20, "height": 20}}}}
+                }}]
+            }}"#,
+            allowed_orientations
+        ))
+        .unwrap()
+    }
+
+    /// Strip length of a grid layout of the plates with the given gap
+    fn grid_width(gap: f64) -> f64 {
+        let per_column = ((1000.0 - gap) / (20.0 + gap)).floor();
+        (100.0 / per_column).ceil() * (20.0 + gap) + gap
+    }
+
+    #[test]
+    fn test_quick_estimate() {
+        // 40000 mm² over 1000 mm at 75%
+        let estimate = quick_estimate_width(&plates("[0.0, 90.0]"));
+        assert!((estimate - 53.333).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_normal_separation_passes() {
+        let instance = plates("[0.0, 90.0]");
+        let width = grid_width(1.0);
+        assert!(
+            check_against_estimate(&instance, width, 1.0, &[], DEFAULT_SANITY_FACTOR).is_none()
+        );
+    }
+
+    #[test]
+    fn test_inflated_separation_is_flagged() {
+        // 10 mm typed instead of 1.0
+        let instance = plates("[0.0, 90.0]");
+        let width = grid_width(10.0);
+        let warning =
+            check_against_estimate(&instance, width, 10.0, &[], DEFAULT_SANITY_FACTOR).unwrap();
+
+        assert!(warning.ratio > DEFAULT_SANITY_FACTOR);
+        assert_eq!(warning.likely_causes.len(), 1);
+        assert_eq!(warning.likely_causes[0].culprit, SanityCulprit::Separation);
+        assert!(warning.message.contains("separation of 10 mm"));
+    }
+
+    #[test]
+    fn test_locked_rotation_and_keep_out_are_named() {
+        let instance = plates("[0.0]");
+        let zones = [KeepOutZone {
+            x: 0.0,
+            y: 0.0,
+            width: 300.0,
+            height: 200.0,
+            label: None,
+        }];
+        let warning = check_against_estimate(&instance, 500.0, 1.0, &zones, 1.5).unwrap();
+
+        let culprits: Vec<_> = warning.likely_causes.iter().map(|c| c.culprit).collect();
+        assert!(culprits.contains(&SanityCulprit::RotationRestrictions));
+        assert!(culprits.contains(&SanityCulprit::Margin));
+        assert!(!culprits.contains(&SanityCulprit::Separation));
+    }
+}
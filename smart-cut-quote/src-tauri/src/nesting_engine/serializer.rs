@@ -3,8 +3,34 @@
 //! This module provides serializable structs that can be passed
 //! between Tauri backend and React frontend.
 
+use super::chunking::RepeatedSheet;
+use super::config_trace::ConfigTrace;
+use super::cut_length::ItemCut;
+use super::filler::FillerSummary;
+use super::geometry::{DisplayUnits, Polygon};
+use super::heatmap::UtilizationGrid;
+use super::holes::HoleLayout;
+use super::instance::InstanceItem;
+use super::item_import::SkippedItem;
+use super::keep_out::KeepOutZone;
+use super::min_web::MinWebViolation;
+use super::mirror::MirrorPair;
+use super::nesting::NestingConfig;
+use super::phases::PhaseStats;
+use super::pre_orient::BaseRotation;
+use super::quality::QualitySettings;
+use super::remnant::ContainerPolygon;
+use super::restarts::RestartSummary;
+use super::resources::ResourceUsage;
+use super::sanity::SanityWarning;
+use super::sheet_mode::SheetLayout;
+use super::stats::StageTimings;
+use super::suggestions::Suggestion;
+use super::verify::OverlapReport;
+use super::viewbox::SvgWarning;
 use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 
 /// Complete nesting output - serializable for frontend
@@ -12,30 +38,171 @@ use std::time::Duration;
 pub struct NestingOutput {
     /// Name of the problem instance
     pub instance_name: String,
+    /// Unit of every length in this output, areas are in its square
+    /// (outputs stored before the flag existed are mm)
+    #[serde(default)]
+    pub units: DisplayUnits,
     /// Computed optimal strip width
     pub strip_width: f64,
     /// Fixed strip height from input
     pub strip_height: f64,
+    /// Sheet length in `SheetMode::FixedSheet`, equal to `strip_width`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sheet_width: Option<f64>,
+    /// Sheet height in `SheetMode::FixedSheet`, equal to `strip_height`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sheet_height: Option<f64>,
     /// Number of items successfully placed
     pub total_items_placed: usize,
     /// List of placed items with positions
     pub layouts: Vec<PlacedItem>,
     /// Material utilization ratio (0.0 - 1.0)
     pub utilization: f64,
+    /// Cut length of all placed copies, outer contours and holes, over
+    /// every sheet of the job (mm whatever `units`, see `cut_length`)
+    #[serde(default)]
+    pub total_cut_length_mm: f64,
+    /// Pierces of all placed copies, one per closed contour
+    #[serde(default)]
+    pub total_pierces: usize,
+    /// Cut length, pierces and placed copies by item, for quote lines
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub item_cuts: Vec<ItemCut>,
     /// Total computation time in seconds
     pub computation_time_secs: f64,
-    /// Status: "complete" or "partial"
+    /// Status: "complete", "partial", "cancelled" when the run was
+    /// stopped by the user and this is its best layout so far, or
+    /// "target_reached" when it stopped at `target_utilization`
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<String>,
+    /// Continued a stored layout (`NestingInput::warm_start`)
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub warm_started: bool,
+    /// Seconds into the optimization `target_utilization` was reached
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub target_reached_secs: Option<f64>,
     /// Total number of items requested
     #[serde(skip_serializing_if = "Option::is_none")]
     pub items_requested: Option<usize>,
     /// IDs of items that could not be placed
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub unplaced_item_ids: Vec<usize>,
+    /// Why items were left unplaced, where the engine knows (one entry per item ID)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub unplaced_reasons: Vec<UnplacedItem>,
     /// SVG string representation of the nested layout
     #[serde(skip_serializing_if = "Option::is_none")]
     pub svg_string: Option<String>,
+    /// Problems while post-processing the SVG (e.g. a viewBox rebuilt from the placements)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub svg_warnings: Vec<SvgWarning>,
+    /// Covered fraction per grid cell (only when requested)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub utilization_grid: Option<UtilizationGrid>,
+    /// Seed used by the optimizer (needed to replay the run)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub seed: Option<u64>,
+    /// Configuration after defaults were applied
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub resolved_config: Option<NestingConfig>,
+    /// Where every setting came from (only with `NestingInput::debug_trace`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub config_trace: Option<ConfigTrace>,
+    /// Fingerprint hash of the input instance
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub instance_hash: Option<String>,
+    /// Versions of the nesting crates (sparrow, jagua-rs) that produced this result
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub engine_version: Option<String>,
+    /// Version of the app that produced this result
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub app_version: Option<String>,
+    /// Hash of the optimizer settings, results are only comparable when equal
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub algorithm_fingerprint: Option<String>,
+    /// Quality preset of the run and the separator limits it used
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub quality_settings: Option<QualitySettings>,
+    /// Every optimizer run with `n_restarts`, the winner's seed is `seed`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub restarts: Vec<RestartSummary>,
+    /// Time spent in each stage of the run
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub stage_timings: Option<StageTimings>,
+    /// Keep-out zones the layout was checked against (resolved at run time)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub keep_out_zones: Vec<KeepOutZone>,
+    /// IDs of placements removed because they overlapped a keep-out zone
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub keep_out_conflicts: Vec<usize>,
+    /// Defect zones of the sheet the layout was checked against
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub holes: Vec<Polygon>,
+    /// IDs of placements removed because they overlapped a defect zone
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub defect_conflicts: Vec<usize>,
+    /// Strip or remnant area minus keep-out and defect zones (only when
+    /// zones or a remnant are present)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub usable_area: Option<f64>,
+    /// Remnant the layout was nested into, in strip coordinates
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub container_polygon: Option<ContainerPolygon>,
+    /// Memory and CPU usage of the run
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub stats: Option<ResourceUsage>,
+    /// Webs narrower than the requested minimum (warnings, see `NestingInput::min_web`)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub min_web_violations: Vec<MinWebViolation>,
+    /// Parts of the finished layout overlapping, closer than the
+    /// separation or off the sheet (see `verify`)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub violations: Vec<OverlapReport>,
+    /// Layout much longer than the quick estimate, with likely causes
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sanity_warning: Option<SanityWarning>,
+    /// Optimizer phase that produced the layout and the width gained by compression
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub phase_stats: Option<PhaseStats>,
+    /// Identical full sheets of an item ordered in bulk (see `chunking`);
+    /// every other field describes the nested remainder only
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub repeated_sheet: Option<RepeatedSheet>,
+    /// Items that are the mirror image of another item (see `mirror`)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub mirror_pairs: Vec<MirrorPair>,
+    /// Translation taken off every item before nesting because the
+    /// instance lies far from the origin (see `origin`); placements are
+    /// in the original item coordinates regardless
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub origin_offset: Option<(f64, f64)>,
+    /// Items turned to their smallest bounding box before nesting (see
+    /// `pre_orient`); placements report rotations relative to the drawing
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub base_rotations: Vec<BaseRotation>,
+    /// Stock fillers placed into the gaps (see `filler`), when the input
+    /// has `filler_items`; the counts and utilization leave them out
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub filler_summary: Option<FillerSummary>,
+    /// Distance every part outline was grown by for the kerf (half the
+    /// kerf width); placements refer to the nominal outline regardless
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub kerf_offset: Option<f64>,
+    /// Input values accepted with a warning, e.g. a strip height no
+    /// stock sheet can hold (see `validate_input`)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub input_warnings: Vec<String>,
+    /// Items left out because they cannot be imported, when the input
+    /// sets `skip_bad_items`
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub skipped_items: Vec<SkippedItem>,
+    /// Every sheet of a fixed-sheet run with more than one sheet allowed,
+    /// the flat fields describe sheet 0
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub sheets: Vec<SheetLayout>,
+    /// Settings worth changing for the next run (see `suggestions`)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub suggestions: Vec<Suggestion>,
 }
 
 /// Single placed item with position and rotation
@@ -43,22 +210,77 @@ pub struct NestingOutput {
 pub struct PlacedItem {
     /// Item ID from input
     pub item_id: usize,
-    /// Rotation in degrees (0, 90, 180, 270)
+    /// Rotation in degrees, in [0, 360); an allowed orientation of the
+    /// item is reported exactly (see `normalize_rotation`)
     pub rotation_degrees: f64,
     /// X position on strip
     pub position_x: f64,
     /// Y position on strip
     pub position_y: f64,
+    /// Item whose hole this part was nested into
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub host_item_id: Option<usize>,
+    /// Exact outline in strip coordinates (only when outlines are requested)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub outline: Option<Polygon>,
+    /// Decimated outline for display (only when outlines are requested)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub display_outline: Option<Polygon>,
+    /// Metadata of the input item, passed through untouched
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub metadata: Option<serde_json::Value>,
+    /// Angle of the part's bend lines to the strip's X axis in degrees,
+    /// folded into (-90, 90] (only items with `bend_direction_deg`)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub bend_line_angle_deg: Option<f64>,
+    /// Placed after packing by the small part post-pass (see `small_parts`)
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub post_placed: bool,
+    /// Mirror image of the nested part (see `mirror`): the item's contour
+    /// is mirrored across its Y axis (x → -x) before the rotation
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub mirrored: bool,
+    /// Part already on the sheet at the item's `fixed_position`, not
+    /// moved by the optimizer (see `locked`)
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub locked: bool,
+    /// Rotation as the optimizer returned it, in radians and before
+    /// normalizing and snapping (optimizer placements only)
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub rotation_radians: Option<f64>,
+    /// Stock filler dropped into a gap after nesting (see `filler`), no
+    /// part of the order
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub filler: bool,
+}
+
+/// Item left off the layout for a known reason
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnplacedItem {
+    pub item_id: usize,
+    pub reason: UnplacedReason,
+}
+
+/// Why an item was left off the layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnplacedReason {
+    /// Below the smallest part the small part post-pass can place
+    TooSmall,
+    /// Did not fit the sheet of `SheetMode::FixedSheet` or the remnant
+    SheetFull,
 }
 
 impl NestingOutput {
     /// Create output from solution and instance
     ///
     /// Converts the raw optimization result into a serializable format
-    /// that can be sent to the frontend.
+    /// that can be sent to the frontend. Rotations are normalized, and
+    /// snapped for the items restricted to `orientations`.
     pub fn from_solution(
         solution: &SPSolution,
         instance: &SPInstance,
+        holes: &HoleLayout,
+        orientations: &HashMap<usize, Vec<f64>>,
         instance_name: String,
         computation_time: Duration,
     ) -> Self {
@@ -74,8 +296,13 @@ impl NestingOutput {
             let d_transf = &placed_item.d_transf;
 
             // Extract rotation in degrees
-            // Note: rotation() returns f32 in radians
-            let rotation_degrees = d_transf.rotation().to_degrees() as f64;
+            // Note: rotation() returns f32 in radians, an exact 270° reads
+            // back as -90.0000001° and is snapped
+            let rotation_radians = d_transf.rotation() as f64;
+            let rotation_degrees = normalize_rotation(
+                rotation_radians.to_degrees(),
+                orientations.get(&item_id).map(Vec::as_slice),
+            );
 
             // Extract position (translation vector)
             // Note: translation() returns (f32, f32) tuple
@@ -88,17 +315,63 @@ impl NestingOutput {
                 rotation_degrees,
                 position_x,
                 position_y,
+                host_item_id: None,
+                outline: None,
+                display_outline: None,
+                metadata: None,
+                bend_line_angle_deg: None,
+                post_placed: false,
+                mirrored: false,
+                locked: false,
+                rotation_radians: Some(rotation_radians),
+                filler: false,
+            });
+        }
+
+        // Parts nested into holes are not part of the jagua-rs layout
+        for placement in &holes.placements {
+            layouts.push(PlacedItem {
+                item_id: placement.item_id,
+                rotation_degrees: normalize_rotation(
+                    placement.rotation_degrees,
+                    orientations.get(&placement.item_id).map(Vec::as_slice),
+                ),
+                position_x: placement.position.0,
+                position_y: placement.position.1,
+                host_item_id: Some(placement.host_item_id),
+                outline: None,
+                display_outline: None,
+                metadata: None,
+                bend_line_angle_deg: None,
+                post_placed: false,
+                mirrored: false,
+                locked: false,
+                rotation_radians: None,
+                filler: false,
             });
         }
 
         let total_items_placed = layouts.len();
 
-        // Calculate total area of all items
+        // Requested quantity per item, including the copies moved into holes
         // Note: SPInstance stores items as Vec<(Item, quantity)>
+        let requested_qty = |item_id: usize, qty: usize| {
+            qty + holes.filled_demand.get(&item_id).copied().unwrap_or(0)
+        };
+
+        // Calculate total area of all items
+        // jagua-rs shapes are outer contours only, so holes are subtracted here
         let total_item_area: f64 = instance
             .items
             .iter()
-            .map(|(item, qty)| item.shape_orig.area() as f64 * (*qty as f64))
+            .map(|(item, qty)| {
+                let net_area = holes
+                    .net_areas
+                    .get(&item.id)
+                    .copied()
+                    .unwrap_or(item.shape_orig.area() as f64);
+                net_area * requested_qty(item.id, *qty) as f64
+            })
             .sum();
 
         // Calculate utilization
@@ -110,7 +383,8 @@ impl NestingOutput {
         };
 
         // Determine status
-        let total_requested = instance.total_item_qty();
+        let total_requested =
+            instance.total_item_qty() + holes.filled_demand.values().sum::<usize>();
         let status = if total_items_placed < total_requested {
             Some("partial".to_string())
         } else {
@@ -126,9 +400,9 @@ impl NestingOutput {
 
         // Find unplaced items by comparing placed count vs requested quantity
         let mut unplaced_item_ids = Vec::new();
-        for (item, requested_qty) in instance.items.iter() {
+        for (item, qty) in instance.items.iter() {
             let placed_qty = placed_counts.get(&item.id).copied().unwrap_or(0);
-            let unplaced_qty = requested_qty.saturating_sub(placed_qty);
+            let unplaced_qty = requested_qty(item.id, *qty).saturating_sub(placed_qty);
 
             // Add item_id for each unplaced instance
             for _ in 0..unplaced_qty {
@@ -138,16 +412,302 @@ impl NestingOutput {
 
         Self {
             instance_name,
+            units: DisplayUnits::Mm,
             strip_width,
             strip_height,
+            sheet_width: None,
+            sheet_height: None,
             total_items_placed,
             layouts,
             utilization,
+            total_cut_length_mm: 0.0,
+            total_pierces: 0,
+            item_cuts: Vec::new(),
             computation_time_secs: computation_time.as_secs_f64(),
             status,
+            target_reached_secs: None,
+            warm_started: false,
             items_requested: Some(total_requested),
             unplaced_item_ids,
+            unplaced_reasons: Vec::new(),
             svg_string: None, // Will be set by caller after generation
+            svg_warnings: Vec::new(),
+            utilization_grid: None,
+            seed: None,
+            resolved_config: None,
+            config_trace: None,
+            instance_hash: None,
+            engine_version: None,
+            app_version: None,
+            algorithm_fingerprint: None,
+            quality_settings: None,
+            restarts: Vec::new(),
+            stage_timings: None,
+            keep_out_zones: Vec::new(),
+            keep_out_conflicts: Vec::new(),
+            holes: Vec::new(),
+            defect_conflicts: Vec::new(),
+            container_polygon: None,
+            usable_area: None,
+            stats: None,
+            min_web_violations: Vec::new(),
+            violations: Vec::new(),
+            sanity_warning: None,
+            phase_stats: None,
+            repeated_sheet: None,
+            mirror_pairs: Vec::new(),
+            origin_offset: None,
+            base_rotations: Vec::new(),
+            filler_summary: None,
+            kerf_offset: None,
+            input_warnings: Vec::new(),
+            skipped_items: Vec::new(),
+            sheets: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+}
+
+impl NestingOutput {
+    /// Copy the metadata of the input items onto their placements
+    pub fn attach_item_metadata(&mut self, items: &[InstanceItem]) {
+        for placed in &mut self.layouts {
+            placed.metadata = items
+                .iter()
+                .find(|item| item.id == placed.item_id as u64)
+                .and_then(|item| item.metadata.clone());
+        }
+    }
+
+    /// Same output with every length and area in `units`
+    ///
+    /// Converts from the units the output is in, so it can also bring a
+    /// payload sent back by the frontend to mm before it is used.
+    pub fn to_units(mut self, units: DisplayUnits) -> Self {
+        let from = self.units;
+        if units == from {
+            return self;
+        }
+        self.strip_width = units.length_from(self.strip_width, from);
+        self.strip_height = units.length_from(self.strip_height, from);
+        self.sheet_width = self.sheet_width.map(|width| units.length_from(width, from));
+        self.origin_offset = self
+            .origin_offset
+            .map(|offset| units.point_from(offset, from));
+        self.kerf_offset = self
+            .kerf_offset
+            .map(|offset| units.length_from(offset, from));
+        self.sheet_height = self
+            .sheet_height
+            .map(|height| units.length_from(height, from));
+        for placed in &mut self.layouts {
+            (placed.position_x, placed.position_y) =
+                units.point_from((placed.position_x, placed.position_y), from);
+            placed.outline = placed
+                .outline
+                .as_ref()
+                .map(|outline| units.polygon_from(outline, from));
+            placed.display_outline = placed
+                .display_outline
+                .as_ref()
+                .map(|outline| units.polygon_from(outline, from));
+        }
+        self.utilization_grid = self.utilization_grid.map(|grid| grid.in_units(units, from));
+        self.resolved_config = self
+            .resolved_config
+            .map(|config| config.in_units(units, from));
+        self.config_trace = self.config_trace.map(|trace| trace.in_units(units, from));
+        self.keep_out_zones = self
+            .keep_out_zones
+            .into_iter()
+            .map(|zone| zone.in_units(units, from))
+            .collect();
+        self.holes = self
+            .holes
+            .iter()
+            .map(|hole| units.polygon_from(hole, from))
+            .collect();
+        self.usable_area = self.usable_area.map(|area| units.area_from(area, from));
+        self.container_polygon = self
+            .container_polygon
+            .map(|container| container.in_units(units, from));
+        for violation in &mut self.min_web_violations {
+            violation.distance = units.length_from(violation.distance, from);
+            violation.location = units.point_from(violation.location, from);
+        }
+        self.violations = self
+            .violations
+            .into_iter()
+            .map(|violation| violation.in_units(units, from))
+            .collect();
+        if let Some(warning) = self.sanity_warning.as_mut() {
+            warning.estimated_strip_width = units.length_from(warning.estimated_strip_width, from);
+            warning.actual_strip_width = units.length_from(warning.actual_strip_width, from);
+        }
+        if let Some(stats) = self.phase_stats.as_mut() {
+            stats.handoff_strip_width = stats
+                .handoff_strip_width
+                .map(|width| units.length_from(width, from));
+            stats.width_delta = stats
+                .width_delta
+                .map(|delta| units.length_from(delta, from));
+        }
+        self.repeated_sheet = self.repeated_sheet.map(|sheet| sheet.in_units(units, from));
+        self.sheets = self
+            .sheets
+            .into_iter()
+            .map(|sheet| sheet.in_units(units, from))
+            .collect();
+        for pair in &mut self.mirror_pairs {
+            pair.offset = units.point_from(pair.offset, from);
+        }
+        if let Some(summary) = self.filler_summary.as_mut() {
+            summary.area = units.area_from(summary.area, from);
+        }
+        self.units = units;
+        self
+    }
+}
+
+/// Largest difference between a solver rotation and the allowed
+/// orientation it is reported as (degrees)
+const ROTATION_SNAP_TOLERANCE: f64 = 0.01;
+
+/// Rotation as reported, in [0°, 360°)
+///
+/// Snapped to the allowed orientation it is within `ROTATION_SNAP_TOLERANCE`
+/// of, if any; free rotations are only brought into range. jagua-rs returns
+/// angles in (-180°, 180°] with f32 noise, so an allowed 270° reads back
+/// as -89.99999°.
+pub fn normalize_rotation(rotation_degrees: f64, allowed: Option<&[f64]>) -> f64 {
+    let snapped = allowed.map_or(rotation_degrees, |allowed| {
+        snap_rotation(rotation_degrees, allowed)
+    });
+    let normalized = snapped.rem_euclid(360.0);
+    // Rounds up to a full turn for angles just below 0
+    if normalized >= 360.0 {
+        0.0
+    } else {
+        normalized
+    }
+}
+
+/// The allowed orientation equal to `rotation_degrees` up to full turns
+/// and f32 noise, `rotation_degrees` itself when there is none
+pub fn snap_rotation(rotation_degrees: f64, allowed: &[f64]) -> f64 {
+    let gap = |angle: f64| {
+        let turn = (angle - rotation_degrees).rem_euclid(360.0);
+        turn.min(360.0 - turn)
+    };
+    allowed
+        .iter()
+        .copied()
+        .filter(|&angle| gap(angle) <= ROTATION_SNAP_TOLERANCE)
+        .min_by(|a, b| gap(*a).total_cmp(&gap(*b)))
+        .unwrap_or(rotation_degrees)
+}
+
+/// Outlines of all placed items in strip coordinates
+///
+/// Uses the transformed shapes stored in the layout snapshot, so no
+/// extra rotation/translation is needed.
+pub fn placed_polygons(solution: &SPSolution) -> Vec<Polygon> {
+    solution
+        .layout_snapshot
+        .placed_items
+        .values()
+        .map(|placed_item| {
+            placed_item
+                .shape
+                .vertices
+                .iter()
+                .map(|p| (p.0 as f64, p.1 as f64))
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::nesting::run_nesting;
+    use crate::nesting_engine::phases::PhaseTracker;
+    use crate::nesting_engine::NativeTerminator;
+    use sparrow::util::terminator::Terminator;
+
+    #[test]
+    fn test_snap_rotation() {
+        assert_eq!(snap_rotation(-90.00000250447816, &[0.0, 270.0]), 270.0);
+        assert_eq!(snap_rotation(89.99999, &[-90.0, 90.0]), 90.0);
+        // Outside the set (a hole placement, say) stays as it is
+        assert_eq!(snap_rotation(45.0, &[0.0, 90.0]), 45.0);
+    }
+
+    #[test]
+    fn test_normalize_rotation() {
+        let right_angles = [0.0, 90.0, 180.0, 270.0];
+        assert_eq!(normalize_rotation(-90.0000001, Some(&right_angles)), 270.0);
+        assert_eq!(normalize_rotation(359.9999, Some(&right_angles)), 0.0);
+        assert_eq!(normalize_rotation(-0.0000001, Some(&right_angles)), 0.0);
+        // An allowed -90° is reported in range as well
+        assert_eq!(normalize_rotation(-89.99999, Some(&[-90.0, 90.0])), 270.0);
+        // Free rotations are only brought into [0°, 360°)
+        assert_eq!(normalize_rotation(-90.0000001, None), 360.0 - 90.0000001);
+        assert_eq!(normalize_rotation(359.9999, None), 359.9999);
+        assert_eq!(normalize_rotation(-1e-20, None), 0.0);
+    }
+
+    #[test]
+    fn test_from_solution_keeps_raw_radians() {
+        let right_angles = [0.0, 90.0, 180.0, 270.0];
+        let json = serde_json::json!({
+            "name": "strips",
+            "strip_height": 50.0,
+            "items": [{
+                "id": 0,
+                "demand": 4,
+                "allowed_orientations": right_angles,
+                "shape": {
+                    "type": "rectangle",
+                    "data": { "x_min": 0.0, "y_min": 0.0, "width": 40.0, "height": 10.0 }
+                }
+            }]
+        })
+        .to_string();
+        let config = NestingConfig {
+            time_limit: Some(1),
+            seed: Some(5),
+            use_early_termination: true,
+            ..Default::default()
+        };
+        let mut terminator = NativeTerminator::new();
+        terminator.new_timeout(Duration::from_secs(1));
+        let result =
+            run_nesting(&json, &config, &mut PhaseTracker::new(), &mut terminator).unwrap();
+
+        let output = NestingOutput::from_solution(
+            &result.solution,
+            &result.instance,
+            &HoleLayout::default(),
+            &result.orientations,
+            "strips".to_string(),
+            result.computation_time,
+        );
+        assert_eq!(output.layouts.len(), 4);
+        let raw = result
+            .solution
+            .layout_snapshot
+            .placed_items
+            .values()
+            .map(|placed| placed.d_transf.rotation() as f64);
+        for (placed, raw) in output.layouts.iter().zip(raw) {
+            // The solver's f32 angle as is, the reported one snapped
+            assert_eq!(placed.rotation_radians, Some(raw));
+            assert!(right_angles.contains(&placed.rotation_degrees));
+            assert_eq!(
+                normalize_rotation(raw.to_degrees(), Some(&right_angles)),
+                placed.rotation_degrees
+            );
         }
     }
 }
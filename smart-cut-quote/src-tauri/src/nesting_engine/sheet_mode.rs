@@ -0,0 +1,312 @@
+//! Nesting into a fixed sheet instead of a growing strip
+//!
+//! Pre-cut stock has a fixed length as well as a fixed height. Sparrow
+//! only knows strips, so in `SheetMode::FixedSheet` the strip is nested
+//! as usual and then cut at `fixed_width`: placements reaching past it are
+//! removed like keep-out conflicts and reported as unplaced with
+//! `UnplacedReason::SheetFull`. The optimizer keeps the strip as short as
+//! it can, so nothing is removed when the parts fit the sheet.
+//!
+//! Parts left off a sheet are nested again onto the next one, up to
+//! `max_sheets`; every sheet is reported as a `SheetLayout`.
+
+use super::dimension::deserialize_dimension;
+use super::geometry::DisplayUnits;
+use super::keep_out::{evict_conflicting_items, KeepOutZone};
+use super::serializer::{NestingOutput, PlacedItem, UnplacedItem, UnplacedReason};
+use jagua_rs::probs::spp::entities::SPSolution;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Sheets nested in fixed-sheet mode when the input does not set a limit
+pub const DEFAULT_MAX_SHEETS: usize = 10;
+
+/// How the strip length is treated
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SheetMode {
+    /// The strip grows until every part is placed
+    #[default]
+    Strip,
+    /// One sheet of `fixed_width` (mm along the strip) by the strip
+    /// height; parts that do not fit are left unplaced
+    FixedSheet {
+        #[serde(deserialize_with = "deserialize_dimension")]
+        fixed_width: f64,
+    },
+}
+
+impl SheetMode {
+    /// Sheet length of `FixedSheet`
+    pub fn fixed_width(&self) -> Option<f64> {
+        match self {
+            SheetMode::Strip => None,
+            SheetMode::FixedSheet { fixed_width } => Some(*fixed_width),
+        }
+    }
+}
+
+/// One sheet of a fixed-sheet run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SheetLayout {
+    /// Position in cutting order, 0 is the sheet of the flat output fields
+    pub sheet_index: usize,
+    pub total_items_placed: usize,
+    pub layouts: Vec<PlacedItem>,
+    /// Net part area over the sheet area (0.0 - 1.0)
+    pub utilization: f64,
+    /// Copies placed on neither this nor an earlier sheet (one entry per copy)
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub unplaced_item_ids: Vec<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub svg_string: Option<String>,
+}
+
+impl SheetLayout {
+    /// Sheet described by the flat fields of an output
+    pub fn from_output(sheet_index: usize, output: &NestingOutput, svg_string: String) -> Self {
+        Self {
+            sheet_index,
+            total_items_placed: output.total_items_placed,
+            layouts: output.layouts.clone(),
+            utilization: output.utilization,
+            unplaced_item_ids: output.unplaced_item_ids.clone(),
+            svg_string: Some(svg_string),
+        }
+    }
+
+    /// Sheet given in `from` units, in `units` (the SVG stays in mm)
+    pub fn in_units(mut self, units: DisplayUnits, from: DisplayUnits) -> Self {
+        for placed in &mut self.layouts {
+            (placed.position_x, placed.position_y) =
+                units.point_from((placed.position_x, placed.position_y), from);
+            placed.outline = placed
+                .outline
+                .as_ref()
+                .map(|outline| units.polygon_from(outline, from));
+            placed.display_outline = placed
+                .display_outline
+                .as_ref()
+                .map(|outline| units.polygon_from(outline, from));
+        }
+        self
+    }
+}
+
+/// Instance of the copies left off a sheet, to nest onto the next one
+///
+/// Keeps the items of `leftover` (one entry per copy) with their demand
+/// set to the number of copies; None when nothing is left.
+pub fn overflow_instance(json_str: &str, leftover: &[usize]) -> Result<Option<String>, String> {
+    let mut copies: BTreeMap<u64, u64> = BTreeMap::new();
+    for &item_id in leftover {
+        *copies.entry(item_id as u64).or_insert(0) += 1;
+    }
+    if copies.is_empty() {
+        return Ok(None);
+    }
+
+    let mut value: serde_json::Value =
+        serde_json::from_str(json_str).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+        .ok_or("Instance JSON has no items array")?;
+    items.retain_mut(|item| {
+        let id = item.get("id").and_then(|id| id.as_u64());
+        match id.and_then(|id| copies.get(&id)) {
+            Some(&demand) => {
+                item["demand"] = serde_json::json!(demand);
+                true
+            }
+            None => false,
+        }
+    });
+    if items.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_string(&value)
+        .map(Some)
+        .map_err(|e| format!("Failed to serialize instance: {}", e))
+}
+
+/// Remove placements reaching past the sheet end from the solution
+///
+/// # Returns
+/// Item IDs of the removed placements (one entry per removed copy)
+pub fn evict_overflow(solution: &mut SPSolution, fixed_width: f64, height: f64) -> Vec<usize> {
+    let strip_width = solution.strip_width() as f64;
+    if strip_width <= fixed_width {
+        return Vec::new();
+    }
+    let beyond_sheet = KeepOutZone {
+        x: fixed_width,
+        y: 0.0,
+        width: strip_width - fixed_width,
+        height,
+        label: None,
+    };
+    evict_conflicting_items(solution, &[beyond_sheet])
+}
+
+/// Report the layout on the sheet instead of the strip it was nested on
+///
+/// Utilization becomes relative to the sheet area and leaves out the
+/// parts removed by `evict_overflow`, whose item IDs are `overflow` and
+/// whose net area is `overflow_area`.
+pub fn apply_fixed_sheet(
+    output: &mut NestingOutput,
+    fixed_width: f64,
+    overflow: &[usize],
+    overflow_area: f64,
+) {
+    let sheet_area = fixed_width * output.strip_height;
+    if sheet_area > 0.0 {
+        let placed_area =
+            output.utilization * output.strip_width * output.strip_height - overflow_area;
+        output.utilization = placed_area.max(0.0) / sheet_area;
+    }
+    output.strip_width = fixed_width;
+    output.sheet_width = Some(fixed_width);
+    output.sheet_height = Some(output.strip_height);
+
+    for &item_id in overflow {
+        if output
+            .unplaced_reasons
+            .iter()
+            .all(|unplaced| unplaced.item_id != item_id)
+        {
+            output.unplaced_reasons.push(UnplacedItem {
+                item_id,
+                reason: UnplacedReason::SheetFull,
+            });
+        }
+    }
+    if !overflow.is_empty() {
+        println!(
+            "📏 {} parts did not fit the {} x {} mm sheet",
+            overflow.len(),
+            fixed_width,
+            output.strip_height
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::{run_nesting_engine, NestingInput};
+
+    #[test]
+    fn test_sheet_mode_json() {
+        let mode: SheetMode = serde_json::from_value(
+            serde_json::json!({ "type": "fixed_sheet", "fixed_width": 3000 }),
+        )
+        .unwrap();
+        assert_eq!(mode.fixed_width(), Some(3000.0));
+        let mode: SheetMode =
+            serde_json::from_value(serde_json::json!({ "type": "strip" })).unwrap();
+        assert_eq!(mode, SheetMode::Strip);
+    }
+
+    #[test]
+    fn test_overflow_instance_keeps_leftover_copies() {
+        let json = serde_json::json!({
+            "name": "sheet",
+            "strip_height": 1500.0,
+            "items": [
+                { "id": 1, "demand": 4, "shape": { "type": "rectangle", "data": {} } },
+                { "id": 2, "demand": 5, "shape": { "type": "rectangle", "data": {} } },
+                { "id": 3, "demand": 1, "shape": { "type": "rectangle", "data": {} } },
+            ],
+        })
+        .to_string();
+        let next = overflow_instance(&json, &[2, 3, 2]).unwrap().unwrap();
+        let next: serde_json::Value = serde_json::from_str(&next).unwrap();
+        let demands: Vec<(u64, u64)> = next["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| {
+                (
+                    item["id"].as_u64().unwrap(),
+                    item["demand"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(demands, vec![(2, 2), (3, 1)]);
+        assert_eq!(next["strip_height"], 1500.0);
+
+        assert!(overflow_instance(&json, &[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_fixed_sheet_output() {
+        let mut output: NestingOutput = serde_json::from_value(serde_json::json!({
+            "instance_name": "sheet",
+            "strip_width": 3600.0,
+            "strip_height": 1500.0,
+            "total_items_placed": 4,
+            "layouts": [],
+            "utilization": 0.5,
+            "computation_time_secs": 1.0,
+            "unplaced_item_ids": [2, 2, 3],
+        }))
+        .unwrap();
+        // 2 700 000 mm² requested, 450 000 mm² of it did not fit
+        apply_fixed_sheet(&mut output, 3000.0, &[2, 2, 3], 450_000.0);
+
+        assert_eq!(output.strip_width, 3000.0);
+        assert_eq!(
+            (output.sheet_width, output.sheet_height),
+            (Some(3000.0), Some(1500.0))
+        );
+        assert!((output.utilization - 0.5).abs() < 1e-9);
+        let reasons: Vec<(usize, UnplacedReason)> = output
+            .unplaced_reasons
+            .iter()
+            .map(|unplaced| (unplaced.item_id, unplaced.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (2, UnplacedReason::SheetFull),
+                (3, UnplacedReason::SheetFull)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parts_left_off_a_timed_out_sheet_go_onto_the_next() {
+        // Four 40 x 40 plates fit a 100 x 100 sheet, nine need three
+        let instance = serde_json::json!({
+            "name": "plates",
+            "strip_height": 100.0,
+            "items": [{
+                "id": 0,
+                "demand": 9,
+                "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 40.0, "height": 40.0 } }
+            }]
+        })
+        .to_string();
+        let input: NestingInput = serde_json::from_value(serde_json::json!({
+            "json_input": instance,
+            "time_limit": 1,
+            "seed": 7,
+            "use_early_termination": false,
+            "separation": 0.0,
+            "sheet_mode": { "type": "fixed_sheet", "fixed_width": 100.0 },
+            "max_sheets": 5,
+        }))
+        .unwrap();
+        let output = run_nesting_engine(input).unwrap();
+        assert!(output.sheets.len() > 1);
+        let placed: usize = output
+            .sheets
+            .iter()
+            .map(|sheet| sheet.total_items_placed)
+            .sum();
+        assert_eq!(placed, 9);
+        assert!(output.sheets.last().unwrap().unplaced_item_ids.is_empty());
+    }
+}
@@ -0,0 +1,365 @@
+//! Loading order of the sheets of a job
+//!
+//! Production cuts the sheets of a job one after the other; parts needed
+//! first in assembly should come off the earliest sheets. Items carry a
+//! `due_rank` in their metadata (1 = needed first). The sheets are put in
+//! the order that minimizes the weighted completion rank: each ranked item
+//! weighs `1 / due_rank` and counts the position of the sheet on which its
+//! last copy is cut. Pure post-processing, the layouts are not touched.
+
+use super::serializer::NestingOutput;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Metadata key of the assembly priority of an item
+pub const DUE_RANK_KEY: &str = "due_rank";
+
+/// Jobs up to this many sheets are ordered exactly, larger ones greedily
+const EXACT_ORDER_LIMIT: usize = 8;
+
+/// Sheets holding the copies of one item, after reordering
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ItemSheets {
+    pub instance_name: String,
+    pub item_id: usize,
+    pub due_rank: Option<u32>,
+    /// Sheet indices (new order) with at least one copy
+    pub sheets: Vec<usize>,
+    /// Sheet index after which all copies are cut
+    pub completed_on: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SheetOrder {
+    /// Original sheet index at each new position
+    pub order: Vec<usize>,
+    pub items: Vec<ItemSheets>,
+    /// Weighted completion rank of the new order (lower is better)
+    pub weighted_completion: f64,
+    /// Weighted completion rank of the nesting order
+    pub original_weighted_completion: f64,
+    pub sheet_order_rationale: String,
+}
+
+/// Item of a job with the original indices of the sheets holding it
+struct JobItem {
+    instance_name: String,
+    item_id: usize,
+    due_rank: Option<u32>,
+    sheets: Vec<usize>,
+}
+
+impl JobItem {
+    fn weight(&self) -> f64 {
+        self.due_rank.map_or(0.0, |rank| 1.0 / rank.max(1) as f64)
+    }
+}
+
+/// Reorder the sheets of a job for assembly, see the module docs
+///
+/// Returns the sheets in their new order with the report. Sheets without
+/// ranked items keep their relative order.
+pub fn order_sheets(sheets: Vec<NestingOutput>) -> (Vec<NestingOutput>, SheetOrder) {
+    let items = job_items(&sheets);
+    let identity: Vec<usize> = (0..sheets.len()).collect();
+    let original_weighted_completion = weighted_completion(&items, &identity);
+
+    let order = if items.iter().all(|item| item.due_rank.is_none()) {
+        identity
+    } else if sheets.len() <= EXACT_ORDER_LIMIT {
+        exact_order(&items, sheets.len())
+    } else {
+        improve_by_swaps(&items, greedy_order(&items, sheets.len()))
+    };
+    let weighted = weighted_completion(&items, &order);
+
+    let mut position = vec![0; sheets.len()];
+    for (new_index, &original) in order.iter().enumerate() {
+        position[original] = new_index;
+    }
+    let report_items = items
+        .iter()
+        .map(|item| {
+            let mut sheets: Vec<usize> = item.sheets.iter().map(|&s| position[s]).collect();
+            sheets.sort_unstable();
+            ItemSheets {
+                instance_name: item.instance_name.clone(),
+                item_id: item.item_id,
+                due_rank: item.due_rank,
+                completed_on: *sheets.last().unwrap_or(&0),
+                sheets,
+            }
+        })
+        .collect();
+
+    let sheet_order_rationale = rationale(&items, &order, weighted, original_weighted_completion);
+    let mut slots: Vec<Option<NestingOutput>> = sheets.into_iter().map(Some).collect();
+    let reordered = order.iter().filter_map(|&i| slots[i].take()).collect();
+
+    (
+        reordered,
+        SheetOrder {
+            order,
+            items: report_items,
+            weighted_completion: weighted,
+            original_weighted_completion,
+            sheet_order_rationale,
+        },
+    )
+}
+
+/// Items of all sheets; copies of one item on several sheets are merged
+fn job_items(sheets: &[NestingOutput]) -> Vec<JobItem> {
+    let mut items: BTreeMap<(String, usize), JobItem> = BTreeMap::new();
+    for (sheet_index, sheet) in sheets.iter().enumerate() {
+        for placed in &sheet.layouts {
+            let item = items
+                .entry((sheet.instance_name.clone(), placed.item_id))
+                .or_insert_with(|| JobItem {
+                    instance_name: sheet.instance_name.clone(),
+                    item_id: placed.item_id,
+                    due_rank: None,
+                    sheets: Vec::new(),
+                });
+            if item.due_rank.is_none() {
+                item.due_rank = placed.metadata.as_ref().and_then(due_rank);
+            }
+            if !item.sheets.contains(&sheet_index) {
+                item.sheets.push(sheet_index);
+            }
+        }
+    }
+    items.into_values().collect()
+}
+
+/// `due_rank` of item metadata, as a number or a numeric string
+fn due_rank(metadata: &serde_json::Value) -> Option<u32> {
+    match metadata.get(DUE_RANK_KEY)? {
+        serde_json::Value::Number(rank) => rank.as_u64().and_then(|r| u32::try_from(r).ok()),
+        serde_json::Value::String(rank) => rank.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Sum of weight times 1-based completion position; `order` holds original indices
+fn weighted_completion(items: &[JobItem], order: &[usize]) -> f64 {
+    let mut position = vec![0; order.len()];
+    for (new_index, &original) in order.iter().enumerate() {
+        position[original] = new_index + 1;
+    }
+    items
+        .iter()
+        .map(|item| {
+            let completed = item.sheets.iter().map(|&s| position[s]).max().unwrap_or(0);
+            item.weight() * completed as f64
+        })
+        .sum()
+}
+
+/// Best of all orders; ties keep the earliest order in lexicographic sequence
+fn exact_order(items: &[JobItem], sheet_count: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..sheet_count).collect();
+    let mut best = order.clone();
+    let mut best_cost = weighted_completion(items, &order);
+    while next_permutation(&mut order) {
+        let cost = weighted_completion(items, &order);
+        if cost < best_cost - 1e-12 {
+            best_cost = cost;
+            best = order.clone();
+        }
+    }
+    best
+}
+
+fn next_permutation(order: &mut [usize]) -> bool {
+    let Some(pivot) = (1..order.len()).rev().find(|&i| order[i - 1] < order[i]) else {
+        return false;
+    };
+    let swap = (pivot..order.len())
+        .rev()
+        .find(|&i| order[i] > order[pivot - 1])
+        .unwrap_or(pivot);
+    order.swap(pivot - 1, swap);
+    order[pivot..].reverse();
+    true
+}
+
+/// Next sheet: most ranked weight completed, then most ranked weight started
+fn greedy_order(items: &[JobItem], sheet_count: usize) -> Vec<usize> {
+    let mut remaining: Vec<usize> = (0..sheet_count).collect();
+    let mut done = vec![false; sheet_count];
+    let mut order = Vec::with_capacity(sheet_count);
+
+    while !remaining.is_empty() {
+        let score = |sheet: usize| {
+            let mut completed = 0.0;
+            let mut started = 0.0;
+            for item in items.iter().filter(|item| item.sheets.contains(&sheet)) {
+                if item.sheets.iter().all(|&s| s == sheet || done[s]) {
+                    completed += item.weight();
+                } else {
+                    started += item.weight();
+                }
+            }
+            (completed, started)
+        };
+        let (slot, _) = remaining
+            .iter()
+            .enumerate()
+            .fold(None, |best: Option<(usize, (f64, f64))>, (slot, &sheet)| {
+                let candidate = score(sheet);
+                match best {
+                    Some((_, best_score)) if candidate <= best_score => best,
+                    _ => Some((slot, candidate)),
+                }
+            })
+            .expect("remaining is not empty");
+        let sheet = remaining.remove(slot);
+        done[sheet] = true;
+        order.push(sheet);
+    }
+    order
+}
+
+/// Swap neighbouring sheets while that lowers the weighted completion
+fn improve_by_swaps(items: &[JobItem], mut order: Vec<usize>) -> Vec<usize> {
+    let mut cost = weighted_completion(items, &order);
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 1..order.len() {
+            order.swap(i - 1, i);
+            let swapped = weighted_completion(items, &order);
+            if swapped < cost - 1e-12 {
+                cost = swapped;
+                improved = true;
+            } else {
+                order.swap(i - 1, i);
+            }
+        }
+    }
+    order
+}
+
+fn rationale(items: &[JobItem], order: &[usize], weighted: f64, original: f64) -> String {
+    if items.iter().all(|item| item.due_rank.is_none()) {
+        return "No item has a due_rank, nesting order kept".to_string();
+    }
+    if order.iter().enumerate().all(|(i, &s)| i == s) {
+        return format!(
+            "Nesting order already cuts high-priority items first (weighted completion {:.2})",
+            weighted
+        );
+    }
+
+    let mut text = format!(
+        "Weighted completion {:.2} instead of {:.2} in nesting order.",
+        weighted, original
+    );
+    let mut done = vec![false; order.len()];
+    for (new_index, &sheet) in order.iter().enumerate() {
+        done[sheet] = true;
+        let completed: Vec<&JobItem> = items
+            .iter()
+            .filter(|item| item.due_rank.is_some() && item.sheets.contains(&sheet))
+            .filter(|item| item.sheets.iter().all(|&s| done[s]))
+            .collect();
+        let most_urgent = completed.iter().filter_map(|item| item.due_rank).min();
+        text.push_str(&format!(" Sheet {} (was {})", new_index + 1, sheet + 1));
+        match most_urgent {
+            Some(rank) => text.push_str(&format!(
+                ": completes {} ranked items, most urgent rank {}.",
+                completed.len(),
+                rank
+            )),
+            None => text.push_str(": completes no ranked items."),
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sheet of one instance with the given (item id, due rank) placements
+    fn sheet(placements: &[(usize, Option<u32>)]) -> NestingOutput {
+        let layouts: Vec<serde_json::Value> = placements
+            .iter()
+            .map(|(item_id, rank)| {
+                serde_json::json!({
+                    "item_id": item_id,
+                    "rotation_degrees": 0.0,
+                    "position_x": 0.0,
+                    "position_y": 0.0,
+                    "metadata": rank.map(|rank| serde_json::json!({ "due_rank": rank }))
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "instance_name": "job",
+            "strip_width": 1000.0,
+            "strip_height": 500.0,
+            "total_items_placed": placements.len(),
+            "layouts": layouts,
+            "utilization": 0.5,
+            "computation_time_secs": 1.0
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_urgent_sheet_moves_first() {
+        let sheets = vec![
+            sheet(&[(0, Some(5)), (1, Some(5))]),
+            sheet(&[(2, Some(1))]),
+            sheet(&[(3, None)]),
+        ];
+        let (reordered, report) = order_sheets(sheets);
+
+        assert_eq!(report.order, vec![1, 0, 2]);
+        assert_eq!(reordered[0].layouts[0].item_id, 2);
+        assert!(report.weighted_completion < report.original_weighted_completion);
+        let urgent = report.items.iter().find(|item| item.item_id == 2).unwrap();
+        assert_eq!(urgent.completed_on, 0);
+        assert!(report.sheet_order_rationale.contains("Sheet 1 (was 2)"));
+    }
+
+    #[test]
+    fn test_item_spanning_sheets_completes_on_its_last_sheet() {
+        // Item 0 (rank 1) has copies on sheets 0 and 2; sheet 1 holds rank 2.
+        // Cutting 0 and 2 first completes the urgent item on position 2.
+        let sheets = vec![
+            sheet(&[(0, Some(1))]),
+            sheet(&[(1, Some(2))]),
+            sheet(&[(0, Some(1))]),
+        ];
+        let (_, report) = order_sheets(sheets);
+
+        assert_eq!(report.order, vec![0, 2, 1]);
+        let spanning = report.items.iter().find(|item| item.item_id == 0).unwrap();
+        assert_eq!(spanning.sheets, vec![0, 1]);
+        assert_eq!(spanning.completed_on, 1);
+        assert!((report.weighted_completion - (2.0 + 3.0 / 2.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_without_ranks_order_is_kept() {
+        let sheets = vec![sheet(&[(0, None)]), sheet(&[(1, None)])];
+        let (_, report) = order_sheets(sheets);
+        assert_eq!(report.order, vec![0, 1]);
+        assert!(report
+            .sheet_order_rationale
+            .contains("No item has a due_rank"));
+    }
+
+    #[test]
+    fn test_greedy_order_for_large_jobs() {
+        // Ten sheets, the most urgent items on the last ones
+        let sheets: Vec<NestingOutput> = (0..10)
+            .map(|i| sheet(&[(i, Some(10 - i as u32))]))
+            .collect();
+        let (_, report) = order_sheets(sheets);
+        assert_eq!(report.order, (0..10).rev().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,531 @@
+//! Parts below the collision-detection resolution (washers, spacers)
+//!
+//! jagua-rs simplifies every polygon and resolves collisions on a grid,
+//! so a part of a few millimeters can become degenerate and end up
+//! overlapping its neighbours. Items whose net area is below
+//! `small_item_area` are taken out of the strip before packing and placed
+//! afterwards: a grid scan fills them into the preserved holes of placed
+//! frames first, then into the space left free on the strip, checking
+//! their bounding circles against everything placed so far. The strip
+//! width is never grown for them.
+//!
+//! Their placements are flagged `post_placed` and count towards the
+//! utilization. Parts whose bounding circle is below
+//! `MIN_GRID_FILL_DIAMETER` cannot be placed this way and are reported as
+//! unplaced with reason `TooSmall`.
+
+use super::geometry::{
+    boundary_distance, bounding_box, open_ring, point_in_polygon, rotate_polygon,
+    translate_polygon, Polygon,
+};
+use super::holes::{svg_path, HoleLayout};
+use super::instance::parse_instance;
+use super::keep_out::KeepOutZone;
+use super::serializer::{
+    normalize_rotation, NestingOutput, PlacedItem, UnplacedItem, UnplacedReason,
+};
+use std::collections::HashMap;
+
+/// Net area below which items are placed after packing when the nesting
+/// input does not set one (mm², about a 5 mm square)
+pub const DEFAULT_SMALL_ITEM_AREA: f64 = 25.0;
+
+/// Smallest bounding circle diameter the post-pass places (mm)
+pub const MIN_GRID_FILL_DIAMETER: f64 = 0.5;
+
+/// Finest grid step of the scan (mm)
+const MIN_GRID_STEP: f64 = 0.25;
+
+/// Item taken out of the strip, with its contour ready to place
+#[derive(Debug, Clone)]
+pub struct SmallItem {
+    pub id: usize,
+    /// Copies left after hole nesting
+    pub demand: usize,
+    /// First allowed orientation (degrees)
+    pub rotation_degrees: f64,
+    /// Rotated contour, centered on its bounding circle
+    pub outline: Polygon,
+    /// Translation that centers the rotated input contour
+    pub center_offset: (f64, f64),
+    pub radius: f64,
+    /// Clearance added to the global separation (see `spacing`)
+    pub extra_separation: f64,
+    pub net_area: f64,
+}
+
+impl SmallItem {
+    /// Item with `outer` turned by `rotation_degrees` and centered on its
+    /// bounding circle, None for a degenerate contour
+    pub fn new(
+        id: usize,
+        demand: usize,
+        rotation_degrees: f64,
+        outer: &Polygon,
+        extra_separation: f64,
+        net_area: f64,
+    ) -> Option<Self> {
+        let rotated = rotate_polygon(open_ring(outer), rotation_degrees);
+        let (min_x, min_y, max_x, max_y) = bounding_box(&rotated)?;
+        let center_offset = (-(min_x + max_x) / 2.0, -(min_y + max_y) / 2.0);
+        let outline = translate_polygon(&rotated, center_offset.0, center_offset.1);
+        let radius = outline.iter().map(|(x, y)| x.hypot(*y)).fold(0.0, f64::max);
+        Some(Self {
+            id,
+            demand,
+            rotation_degrees,
+            outline,
+            center_offset,
+            radius,
+            extra_separation,
+            net_area,
+        })
+    }
+
+    pub fn too_small(&self) -> bool {
+        2.0 * self.radius < MIN_GRID_FILL_DIAMETER
+    }
+}
+
+/// Items placed after packing, largest first
+#[derive(Debug, Clone, Default)]
+pub struct SmallPartPlan {
+    pub items: Vec<SmallItem>,
+}
+
+/// Part placed after packing, in strip coordinates
+#[derive(Debug, Clone)]
+pub struct SmallPlacement {
+    pub item_id: usize,
+    /// Rotation of the part's input contour (degrees)
+    pub rotation_degrees: f64,
+    /// Translation of the part's input contour after rotation
+    pub position: (f64, f64),
+    pub outline: Polygon,
+}
+
+/// Outcome of the post-pass
+#[derive(Debug, Clone, Default)]
+pub struct SmallPartLayout {
+    pub placements: Vec<SmallPlacement>,
+    /// Demand taken out of the strip, per item ID
+    pub removed_demand: HashMap<usize, usize>,
+    /// Net area per item ID
+    pub net_areas: HashMap<usize, f64>,
+    /// Items below `MIN_GRID_FILL_DIAMETER`
+    pub too_small: Vec<usize>,
+}
+
+/// Take items below `small_item_area` out of the strip
+///
+/// Geometry, orientations and clearance come from the instance before
+/// hole preparation (`json_str`), the demand left from the prepared one,
+/// whose demand of small items is set to 0. When every remaining item is
+/// small the instance is left as it is, the strip needs something to pack.
+pub fn prepare_instance(
+    json_str: &str,
+    prepared_json: &str,
+    small_item_area: f64,
+) -> Result<(String, SmallPartPlan), String> {
+    if small_item_area <= 0.0 {
+        return Ok((prepared_json.to_string(), SmallPartPlan::default()));
+    }
+
+    let instance = parse_instance(json_str)?;
+    let demands: HashMap<u64, u64> = parse_instance(prepared_json)?
+        .items
+        .iter()
+        .map(|item| (item.id, item.demand))
+        .collect();
+
+    let mut plan = SmallPartPlan::default();
+    let mut packed = 0;
+    for item in &instance.items {
+        let demand = demands.get(&item.id).copied().unwrap_or(0);
+        if demand == 0 {
+            continue;
+        }
+        let net_area = item.net_area();
+        if net_area >= small_item_area {
+            packed += 1;
+            continue;
+        }
+
+        let rotation_degrees = item
+            .allowed_orientations
+            .as_ref()
+            .and_then(|orientations| orientations.first().copied())
+            .unwrap_or(0.0);
+        plan.items.extend(SmallItem::new(
+            item.id as usize,
+            demand as usize,
+            rotation_degrees,
+            &item.outer,
+            item.extra_separation,
+            net_area,
+        ));
+    }
+
+    if plan.items.is_empty() {
+        return Ok((prepared_json.to_string(), plan));
+    }
+    if packed == 0 {
+        println!("⚠️ Every part is below the small part area, all are packed normally");
+        return Ok((prepared_json.to_string(), SmallPartPlan::default()));
+    }
+    plan.items.sort_by(|a, b| b.radius.total_cmp(&a.radius));
+
+    let mut value: serde_json::Value =
+        serde_json::from_str(prepared_json).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+        .ok_or("Instance JSON has no items array")?;
+    for item in items.iter_mut().filter_map(|item| item.as_object_mut()) {
+        let id = item.get("id").and_then(|id| id.as_u64()).unwrap_or(0) as usize;
+        if plan.items.iter().any(|small| small.id == id) {
+            item.insert("demand".to_string(), 0.into());
+        }
+    }
+
+    println!(
+        "🔩 {} small parts ({} copies) are placed after packing",
+        plan.items.len(),
+        plan.items.iter().map(|item| item.demand).sum::<usize>()
+    );
+    let rewritten = serde_json::to_string(&value)
+        .map_err(|e| format!("Failed to serialize instance: {}", e))?;
+    Ok((rewritten, plan))
+}
+
+/// Placed shape the post-pass must keep clear of
+struct Obstacle {
+    outline: Polygon,
+    bbox: (f64, f64, f64, f64),
+}
+
+impl Obstacle {
+    fn new(outline: Polygon) -> Option<Self> {
+        let bbox = bounding_box(&outline)?;
+        Some(Self { outline, bbox })
+    }
+
+    /// True when a circle of `radius` (part plus clearance) stays off this shape
+    fn clears(&self, center: (f64, f64), radius: f64) -> bool {
+        let (min_x, min_y, max_x, max_y) = self.bbox;
+        if center.0 + radius <= min_x
+            || center.0 - radius >= max_x
+            || center.1 + radius <= min_y
+            || center.1 - radius >= max_y
+        {
+            return true;
+        }
+        !point_in_polygon(center, &self.outline)
+            && boundary_distance(&[center], &self.outline) >= radius
+    }
+}
+
+/// Grid-fill the small items into holes and free strip space
+///
+/// `placed` are the outlines of the packed parts (solid outer contours),
+/// `holes` the hole layout mapped onto them.
+pub fn place_small_parts(
+    plan: &SmallPartPlan,
+    placed: &[Polygon],
+    holes: &HoleLayout,
+    keep_out_zones: &[KeepOutZone],
+    (strip_width, strip_height): (f64, f64),
+    separation: f64,
+) -> SmallPartLayout {
+    let mut layout = SmallPartLayout {
+        removed_demand: plan
+            .items
+            .iter()
+            .map(|item| (item.id, item.demand))
+            .collect(),
+        net_areas: plan
+            .items
+            .iter()
+            .map(|item| (item.id, item.net_area))
+            .collect(),
+        ..Default::default()
+    };
+
+    let strip_obstacles: Vec<Obstacle> = placed.iter().cloned().filter_map(Obstacle::new).collect();
+    let nested_obstacles: Vec<Obstacle> = holes
+        .placements
+        .iter()
+        .filter_map(|placement| Obstacle::new(placement.outline.clone()))
+        .collect();
+    // Bounding circles of the parts placed by this pass
+    let mut circles: Vec<((f64, f64), f64)> = Vec::new();
+
+    for item in &plan.items {
+        if item.too_small() {
+            layout.too_small.push(item.id);
+            continue;
+        }
+        let clearance = separation + item.extra_separation;
+        let reach = item.radius + clearance;
+        let step = (item.radius / 2.0).max(MIN_GRID_STEP);
+        let mut remaining = item.demand;
+
+        let try_place = |center: (f64, f64),
+                         obstacles: &[&Obstacle],
+                         circles: &mut Vec<((f64, f64), f64)>,
+                         layout: &mut SmallPartLayout|
+         -> bool {
+            let clears_circles = circles.iter().all(|&((x, y), radius)| {
+                (center.0 - x).hypot(center.1 - y) >= item.radius + radius + clearance
+            });
+            if !clears_circles
+                || !obstacles
+                    .iter()
+                    .all(|obstacle| obstacle.clears(center, reach))
+            {
+                return false;
+            }
+            let outline = translate_polygon(&item.outline, center.0, center.1);
+            if keep_out_zones.iter().any(|zone| zone.overlaps(&outline)) {
+                return false;
+            }
+            circles.push((center, item.radius));
+            layout.placements.push(SmallPlacement {
+                item_id: item.id,
+                rotation_degrees: item.rotation_degrees,
+                position: (
+                    center.0 + item.center_offset.0,
+                    center.1 + item.center_offset.1,
+                ),
+                outline,
+            });
+            true
+        };
+
+        // Holes of placed frames first, they are lost to the strip anyway
+        for hole in &holes.hole_outlines {
+            let Some((min_x, min_y, max_x, max_y)) = bounding_box(hole) else {
+                continue;
+            };
+            let in_hole: Vec<&Obstacle> = nested_obstacles
+                .iter()
+                .filter(|obstacle| overlaps_bbox(obstacle.bbox, (min_x, min_y, max_x, max_y)))
+                .collect();
+            for center in grid(
+                (min_x + reach, min_y + reach, max_x - reach, max_y - reach),
+                step,
+            ) {
+                if remaining == 0 {
+                    break;
+                }
+                if !point_in_polygon(center, hole) || boundary_distance(&[center], hole) < reach {
+                    continue;
+                }
+                if try_place(center, &in_hole, &mut circles, &mut layout) {
+                    remaining -= 1;
+                }
+            }
+        }
+
+        // Then the free strip space, column by column from the left
+        let mut x = reach;
+        while remaining > 0 && x <= strip_width - reach {
+            let column: Vec<&Obstacle> = strip_obstacles
+                .iter()
+                .filter(|obstacle| obstacle.bbox.0 - reach < x && x < obstacle.bbox.2 + reach)
+                .collect();
+            let mut y = reach;
+            while remaining > 0 && y <= strip_height - reach {
+                if try_place((x, y), &column, &mut circles, &mut layout) {
+                    remaining -= 1;
+                }
+                y += step;
+            }
+            x += step;
+        }
+    }
+
+    layout
+}
+
+fn overlaps_bbox(a: (f64, f64, f64, f64), b: (f64, f64, f64, f64)) -> bool {
+    a.0 < b.2 && b.0 < a.2 && a.1 < b.3 && b.1 < a.3
+}
+
+/// Grid points of a rectangle, column by column
+fn grid(
+    (min_x, min_y, max_x, max_y): (f64, f64, f64, f64),
+    step: f64,
+) -> impl Iterator<Item = (f64, f64)> {
+    let columns = if max_x < min_x {
+        0
+    } else {
+        ((max_x - min_x) / step) as usize + 1
+    };
+    let rows = if max_y < min_y {
+        0
+    } else {
+        ((max_y - min_y) / step) as usize + 1
+    };
+    (0..columns).flat_map(move |column| {
+        (0..rows).map(move |row| (min_x + column as f64 * step, min_y + row as f64 * step))
+    })
+}
+
+/// Add the post-placed parts to the output
+///
+/// Their copies were taken out of the instance, so the counts,
+/// utilization and unplaced list only see them here.
+pub fn attach_post_placed(output: &mut NestingOutput, layout: &SmallPartLayout) {
+    if layout.removed_demand.is_empty() {
+        return;
+    }
+
+    let mut placed_counts: HashMap<usize, usize> = HashMap::new();
+    for placement in &layout.placements {
+        *placed_counts.entry(placement.item_id).or_insert(0) += 1;
+        output.layouts.push(PlacedItem {
+            item_id: placement.item_id,
+            rotation_degrees: normalize_rotation(placement.rotation_degrees, None),
+            position_x: placement.position.0,
+            position_y: placement.position.1,
+            host_item_id: None,
+            outline: None,
+            display_outline: None,
+            metadata: None,
+            bend_line_angle_deg: None,
+            post_placed: true,
+            mirrored: false,
+            locked: false,
+            rotation_radians: None,
+            filler: false,
+        });
+    }
+    output.total_items_placed += layout.placements.len();
+
+    // Requested area, as for packed items
+    let strip_area = output.strip_width * output.strip_height;
+    if strip_area > 0.0 {
+        let small_area: f64 = layout
+            .removed_demand
+            .iter()
+            .map(|(id, demand)| layout.net_areas.get(id).copied().unwrap_or(0.0) * *demand as f64)
+            .sum();
+        output.utilization += small_area / strip_area;
+    }
+
+    let mut ids: Vec<usize> = layout.removed_demand.keys().copied().collect();
+    ids.sort_unstable();
+    for id in ids {
+        let demand = layout.removed_demand[&id];
+        let missing = demand.saturating_sub(placed_counts.get(&id).copied().unwrap_or(0));
+        output
+            .unplaced_item_ids
+            .extend(std::iter::repeat_n(id, missing));
+    }
+    output
+        .unplaced_reasons
+        .extend(layout.too_small.iter().map(|&item_id| UnplacedItem {
+            item_id,
+            reason: UnplacedReason::TooSmall,
+        }));
+
+    let requested =
+        output.items_requested.unwrap_or(0) + layout.removed_demand.values().sum::<usize>();
+    output.items_requested = Some(requested);
+    if output.total_items_placed < requested {
+        output.status = Some("partial".to_string());
+    }
+}
+
+/// Render the post-placed parts as an SVG overlay
+///
+/// They are not part of the jagua-rs layout the SVG is drawn from.
+pub fn render_small_part_overlay(layout: &SmallPartLayout) -> String {
+    let mut overlay = String::from(r#"<g id="post_placed" pointer-events="none">"#);
+    for placement in &layout.placements {
+        overlay.push_str(&format!(
+            r#"<path d="{}" fill="khaki" fill-opacity="0.8" stroke="black" stroke-width="0.5"/>"#,
+            svg_path(&placement.outline)
+        ));
+    }
+    overlay.push_str("</g>");
+    overlay
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance() -> String {
+        serde_json::json!({
+            "strip_height": 50.0,
+            "items": [
+                {
+                    "id": 0,
+                    "demand": 1,
+                    "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 40.0, "height": 50.0 } }
+                },
+                {
+                    "id": 1,
+                    "demand": 6,
+                    "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 4.0, "height": 4.0 } }
+                },
+                {
+                    "id": 2,
+                    "demand": 1,
+                    "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 0.2, "height": 0.2 } }
+                }
+            ]
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_small_items_leave_the_strip() {
+        let json = instance();
+        let (prepared, plan) = prepare_instance(&json, &json, DEFAULT_SMALL_ITEM_AREA).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&prepared).unwrap();
+        assert_eq!(value["items"][0]["demand"], 1);
+        assert_eq!(value["items"][1]["demand"], 0);
+        assert_eq!(value["items"][2]["demand"], 0);
+        // Largest first
+        assert_eq!(
+            plan.items.iter().map(|item| item.id).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        // Disabled with a zero threshold
+        let (unchanged, plan) = prepare_instance(&json, &json, 0.0).unwrap();
+        assert_eq!(unchanged, json);
+        assert!(plan.items.is_empty());
+    }
+
+    #[test]
+    fn test_post_pass_avoids_placed_parts() {
+        let json = instance();
+        let (_, plan) = prepare_instance(&json, &json, DEFAULT_SMALL_ITEM_AREA).unwrap();
+        // The big part fills the strip but for a 10 mm column on the right
+        let placed = vec![vec![(0.0, 0.0), (40.0, 0.0), (40.0, 50.0), (0.0, 50.0)]];
+        let layout = place_small_parts(
+            &plan,
+            &placed,
+            &HoleLayout::default(),
+            &[],
+            (50.0, 50.0),
+            1.0,
+        );
+
+        assert_eq!(layout.too_small, vec![2]);
+        assert_eq!(layout.placements.len(), 6);
+        for (i, placement) in layout.placements.iter().enumerate() {
+            assert!(placement
+                .outline
+                .iter()
+                .all(|&(x, y)| x >= 41.0 && (1.0..=49.0).contains(&y)));
+            for other in &layout.placements[i + 1..] {
+                assert!(boundary_distance(&placement.outline, &other.outline) >= 1.0 - 1e-9);
+            }
+        }
+    }
+}
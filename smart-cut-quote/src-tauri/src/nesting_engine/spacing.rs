@@ -0,0 +1,507 @@
+//! Extra clearance around delicate parts
+//!
+//! Thin mesh and lattice parts warp when they are cut close to their
+//! neighbours. Such an item sets `extra_spacing` (mm), or
+//! `extra_separation` in its metadata, and its outer contour is grown by
+//! that amount before the instance is imported. The clearance adds to the
+//! global separation: the part keeps at least `separation + extra` from
+//! other parts and from the strip edge, two delicate parts
+//! `separation + extra_a + extra_b` from each other. Parts nested into
+//! holes keep the same clearance from the hole edge and from the other
+//! parts in the hole.
+//!
+//! Net areas (utilization) come from the original contour, and so do the
+//! reported placements: jagua-rs moves the centroid of every imported
+//! shape to its origin, so the translation of a grown item is shifted
+//! back by how far growing moved the centroid.
+//!
+//! Kerf compensation uses the same growing: with a `kerf_width` every
+//! item is grown by half of it on top of its extra clearance, so the
+//! separation is kept between the cut paths instead of the nominal
+//! contours.
+
+use super::dimension::parse_dimension;
+use super::geometry::{
+    offset_ring, open_ring, rotate_polygon, translate_polygon, Contour, Polygon,
+};
+use super::holes::{host_translation, svg_path};
+use super::instance::parse_instance;
+use super::serializer::PlacedItem;
+use jagua_rs::probs::spp::entities::SPSolution;
+use std::collections::HashMap;
+
+/// Metadata key of the per-item clearance
+pub const EXTRA_SEPARATION_KEY: &str = "extra_separation";
+
+/// Item field of the per-item clearance, taking precedence over the metadata key
+pub const EXTRA_SPACING_KEY: &str = "extra_spacing";
+
+/// Item whose outer contour was grown for extra clearance
+#[derive(Debug, Clone)]
+pub struct GrownItem {
+    pub extra_separation: f64,
+    /// Half the kerf width, included in the growth
+    pub kerf_offset: f64,
+    /// Input outer contour (item coordinates)
+    pub outer: Polygon,
+    /// Contour sent to jagua-rs
+    pub grown: Polygon,
+}
+
+/// Items grown for extra clearance, by item ID
+#[derive(Debug, Clone, Default)]
+pub struct SpacingPlan {
+    pub items: HashMap<usize, GrownItem>,
+    /// Half the kerf width every item was grown by (0 without kerf
+    /// compensation)
+    pub kerf_offset: f64,
+}
+
+/// Extra clearance of an item in mm (0 when unset)
+///
+/// The item's `extra_spacing` wins over `extra_separation` in its
+/// metadata. Accepts a number or a dimension string (`"8"`, `"0.8 cm"`).
+pub(super) fn extra_separation(
+    item_id: u64,
+    extra_spacing: Option<&serde_json::Value>,
+    metadata: Option<&serde_json::Value>,
+) -> Result<f64, String> {
+    let field = extra_spacing
+        .map(|value| (EXTRA_SPACING_KEY, value))
+        .or_else(|| {
+            metadata
+                .and_then(|metadata| metadata.get(EXTRA_SEPARATION_KEY))
+                .map(|value| (EXTRA_SEPARATION_KEY, value))
+        });
+    let Some((key, value)) = field else {
+        return Ok(0.0);
+    };
+    let extra = match value {
+        serde_json::Value::Null => Some(0.0),
+        serde_json::Value::Number(number) => number.as_f64(),
+        serde_json::Value::String(text) => parse_dimension(text).ok(),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        format!(
+            "Item {}: {} must be a distance in mm, got {}",
+            item_id, key, value
+        )
+    })?;
+
+    if !extra.is_finite() || extra < 0.0 {
+        return Err(format!(
+            "Item {}: {} must not be negative, got {}",
+            item_id, key, extra
+        ));
+    }
+    Ok(extra)
+}
+
+/// Grow the outer contour of every item with extra clearance or kerf
+///
+/// # Arguments
+/// * `input_json` - Instance JSON with the item metadata
+/// * `prepared_json` - The same instance rewritten for jagua-rs
+///   (see `holes::prepare_instance`), whose shapes are replaced
+/// * `kerf_width` - Width of the cut in mm, every item is grown by
+///   half of it (0 = no kerf compensation)
+pub fn grow_instance(
+    input_json: &str,
+    prepared_json: &str,
+    kerf_width: f64,
+) -> Result<(String, SpacingPlan), String> {
+    let mut plan = SpacingPlan {
+        kerf_offset: kerf_width / 2.0,
+        ..Default::default()
+    };
+    for item in parse_instance(input_json)?.items {
+        let growth = item.extra_separation + plan.kerf_offset;
+        if growth > 0.0 {
+            let outer = open_ring(&item.outer).to_vec();
+            plan.items.insert(
+                item.id as usize,
+                GrownItem {
+                    extra_separation: item.extra_separation,
+                    kerf_offset: plan.kerf_offset,
+                    grown: offset_ring(&outer, growth),
+                    outer,
+                },
+            );
+        }
+    }
+    if plan.items.is_empty() {
+        return Ok((prepared_json.to_string(), plan));
+    }
+
+    let mut value: serde_json::Value =
+        serde_json::from_str(prepared_json).map_err(|e| format!("Invalid instance JSON: {}", e))?;
+    let items = value
+        .get_mut("items")
+        .and_then(|items| items.as_array_mut())
+        .ok_or("Instance JSON has no items array")?;
+    for item in items.iter_mut() {
+        let id = item.get("id").and_then(|id| id.as_u64()).unwrap_or(0) as usize;
+        if let Some(grown) = plan.items.get(&id) {
+            item["shape"] = serde_json::json!({
+                "type": "simple_polygon",
+                "data": grown.grown,
+            });
+        }
+    }
+
+    let rewritten = serde_json::to_string(&value)
+        .map_err(|e| format!("Failed to serialize instance: {}", e))?;
+    Ok((rewritten, plan))
+}
+
+/// Move the placements of grown items onto their original contour
+///
+/// Only jagua-rs placements are corrected; parts nested into holes and
+/// post-placed parts are placed with their original contour already.
+pub fn report_original_positions(layouts: &mut [PlacedItem], plan: &SpacingPlan) {
+    for placed in layouts
+        .iter_mut()
+        .filter(|placed| placed.host_item_id.is_none() && !placed.post_placed)
+    {
+        let Some(item) = plan.items.get(&placed.item_id) else {
+            continue;
+        };
+        let (Some(original), Some(grown)) = (
+            Contour::from_polygon(&item.outer).centroid(),
+            Contour::from_polygon(&item.grown).centroid(),
+        ) else {
+            continue;
+        };
+        let shift = (original.0 - grown.0, original.1 - grown.1);
+        let (dx, dy) = rotate_polygon(&[shift], placed.rotation_degrees)[0];
+        placed.position_x += dx;
+        placed.position_y += dy;
+    }
+}
+
+/// Render the clearance zone and true contour of grown items as an SVG overlay
+pub fn render_clearance_overlay(plan: &SpacingPlan, solution: &SPSolution) -> String {
+    let mut overlay = String::from(r#"<g id="extra_clearance" pointer-events="none">"#);
+
+    for placed_item in solution.layout_snapshot.placed_items.values() {
+        let Some(item) = plan
+            .items
+            .get(&placed_item.item_id)
+            .filter(|item| item.extra_separation > 0.0)
+        else {
+            continue;
+        };
+        let zone: Polygon = placed_item
+            .shape
+            .vertices
+            .iter()
+            .map(|p| (p.0 as f64, p.1 as f64))
+            .collect();
+        let rotation = (placed_item.d_transf.rotation() as f64).to_degrees();
+        // Growing keeps the bounding box center, so the contour sits in the middle
+        let Some((dx, dy)) = host_translation(&item.outer, rotation, &zone) else {
+            continue;
+        };
+        let part = translate_polygon(&rotate_polygon(&item.outer, rotation), dx, dy);
+
+        overlay.push_str(&format!(
+            r#"<path d="{}" fill="orange" fill-opacity="0.25" stroke="darkorange" stroke-width="1" stroke-dasharray="4 2"/>"#,
+            svg_path(&zone)
+        ));
+        overlay.push_str(&format!(
+            r#"<path d="{}" fill="none" stroke="black" stroke-width="1"/>"#,
+            svg_path(&part)
+        ));
+    }
+
+    overlay.push_str("</g>");
+    overlay
+}
+
+/// Render the nominal and the kerf-compensated contour of every nested
+/// item as an SVG overlay
+pub fn render_kerf_overlay(plan: &SpacingPlan, solution: &SPSolution) -> String {
+    let mut overlay = String::from(r#"<g id="kerf_compensation" pointer-events="none">"#);
+
+    for placed_item in solution.layout_snapshot.placed_items.values() {
+        let Some(item) = plan
+            .items
+            .get(&placed_item.item_id)
+            .filter(|item| item.kerf_offset > 0.0)
+        else {
+            continue;
+        };
+        let zone: Polygon = placed_item
+            .shape
+            .vertices
+            .iter()
+            .map(|p| (p.0 as f64, p.1 as f64))
+            .collect();
+        let rotation = (placed_item.d_transf.rotation() as f64).to_degrees();
+        let Some((dx, dy)) = host_translation(&item.outer, rotation, &zone) else {
+            continue;
+        };
+        let nominal = translate_polygon(&rotate_polygon(&item.outer, rotation), dx, dy);
+        let compensated = offset_ring(&nominal, item.kerf_offset);
+
+        overlay.push_str(&format!(
+            r#"<path d="{}" fill="none" stroke="red" stroke-width="0.5" stroke-dasharray="2 1"/>"#,
+            svg_path(&compensated)
+        ));
+        overlay.push_str(&format!(
+            r#"<path d="{}" fill="none" stroke="black" stroke-width="0.5"/>"#,
+            svg_path(&nominal)
+        ));
+    }
+
+    overlay.push_str("</g>");
+    overlay
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nesting_engine::geometry::{boundary_distance, bounding_box};
+
+    fn instance(metadata: serde_json::Value) -> String {
+        serde_json::json!({
+            "name": "mesh",
+            "strip_height": 500.0,
+            "items": [
+                {
+                    "id": 0,
+                    "demand": 2,
+                    "shape": {
+                        "type": "simple_polygon",
+                        "data": [[0.0, 0.0], [60.0, 0.0], [60.0, 20.0], [20.0, 20.0], [20.0, 40.0], [0.0, 40.0]]
+                    },
+                    "metadata": metadata
+                },
+                {
+                    "id": 1,
+                    "demand": 1,
+                    "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 10.0, "height": 10.0 } }
+                }
+            ]
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_only_items_with_extra_separation_are_grown() {
+        let json = instance(serde_json::json!({ "extra_separation": 6.0, "part_no": "M-1" }));
+        let (rewritten, plan) = grow_instance(&json, &json, 0.0).unwrap();
+
+        assert_eq!(plan.items.len(), 1);
+        let grown = &plan.items[&0];
+        assert_eq!(bounding_box(&grown.grown), Some((-6.0, -6.0, 66.0, 46.0)));
+
+        let value: serde_json::Value = serde_json::from_str(&rewritten).unwrap();
+        assert_eq!(value["items"][0]["shape"]["type"], "simple_polygon");
+        assert_eq!(
+            value["items"][0]["shape"]["data"].as_array().unwrap().len(),
+            grown.grown.len()
+        );
+        assert_eq!(value["items"][1]["shape"]["type"], "rectangle");
+    }
+
+    #[test]
+    fn test_extra_separation_is_validated() {
+        let error =
+            parse_instance(&instance(serde_json::json!({ "extra_separation": -2.0 }))).unwrap_err();
+        assert!(error.contains("must not be negative"), "{}", error);
+        assert!(
+            parse_instance(&instance(serde_json::json!({ "extra_separation": "wide" }))).is_err()
+        );
+
+        let parsed =
+            parse_instance(&instance(serde_json::json!({ "extra_separation": "8" }))).unwrap();
+        assert_eq!(parsed.items[0].extra_separation, 8.0);
+        assert_eq!(parsed.items[1].extra_separation, 0.0);
+    }
+
+    #[test]
+    fn test_item_extra_spacing_wins_over_metadata() {
+        let mut value: serde_json::Value =
+            serde_json::from_str(&instance(serde_json::json!({ "extra_separation": 8.0 })))
+                .unwrap();
+        value["items"][0]["extra_spacing"] = serde_json::json!("3 mm");
+        value["items"][1]["extra_spacing"] = serde_json::json!(-1.0);
+
+        let error = parse_instance(&value.to_string()).unwrap_err();
+        assert!(error.contains("Item 1: extra_spacing"), "{}", error);
+
+        value["items"][1]["extra_spacing"] = serde_json::json!(1.5);
+        let parsed = parse_instance(&value.to_string()).unwrap();
+        assert_eq!(parsed.items[0].extra_separation, 3.0);
+        assert_eq!(parsed.items[1].extra_separation, 1.5);
+    }
+
+    #[test]
+    fn test_neighbours_keep_extra_spacing_in_solution() {
+        let rectangle = |id: usize, demand: usize| {
+            serde_json::json!({
+                "id": id,
+                "demand": demand,
+                "allowed_orientations": [0.0, 90.0],
+                "shape": {
+                    "type": "simple_polygon",
+                    "data": [[0.0, 0.0], [40.0, 0.0], [40.0, 30.0], [0.0, 30.0]]
+                }
+            })
+        };
+        let mut spaced = rectangle(0, 1);
+        spaced["extra_spacing"] = serde_json::json!(5.0);
+        let json = serde_json::json!({
+            "name": "mesh",
+            "strip_height": 100.0,
+            "items": [spaced, rectangle(1, 8)]
+        })
+        .to_string();
+
+        let input: crate::nesting_engine::NestingInput =
+            serde_json::from_value(serde_json::json!({
+                "json_input": json,
+                "separation": 2.0,
+                "time_limit": 1,
+                "seed": 7,
+                "use_early_termination": true,
+            }))
+            .unwrap();
+        let output = crate::nesting_engine::run_nesting_engine(input).unwrap();
+        assert_eq!(output.total_items_placed, 9);
+
+        // Reported positions refer to the original, centroid-centered contour
+        let outline = |placed: &PlacedItem| {
+            let centered = [(-20.0, -15.0), (20.0, -15.0), (20.0, 15.0), (-20.0, 15.0)];
+            translate_polygon(
+                &rotate_polygon(&centered, placed.rotation_degrees),
+                placed.position_x,
+                placed.position_y,
+            )
+        };
+        let spaced = output
+            .layouts
+            .iter()
+            .find(|placed| placed.item_id == 0)
+            .unwrap();
+        let nearest = output
+            .layouts
+            .iter()
+            .filter(|placed| placed.item_id != 0)
+            .map(|placed| boundary_distance(&outline(spaced), &outline(placed)))
+            .fold(f64::INFINITY, f64::min);
+
+        // Slack for the polygon offset of the grown contour and f32 placements
+        assert!(
+            nearest >= 2.0 + 5.0 - 0.1,
+            "nearest neighbour at {}",
+            nearest
+        );
+    }
+
+    #[test]
+    fn test_kerf_grows_every_item_by_half_its_width() {
+        let json = instance(serde_json::json!({ "extra_separation": 6.0 }));
+        let (_, plan) = grow_instance(&json, &json, 2.0).unwrap();
+
+        assert_eq!(plan.kerf_offset, 1.0);
+        assert_eq!(plan.items.len(), 2);
+        assert_eq!(
+            bounding_box(&plan.items[&0].grown),
+            Some((-7.0, -7.0, 67.0, 47.0))
+        );
+        assert_eq!(
+            bounding_box(&plan.items[&1].grown),
+            Some((-1.0, -1.0, 11.0, 11.0))
+        );
+    }
+
+    #[test]
+    fn test_cut_paths_keep_the_separation_with_kerf() {
+        use crate::nesting_engine::{run_nesting_engine, NestingInput};
+
+        let json = serde_json::json!({
+            "name": "kerf",
+            "strip_height": 60.0,
+            "items": [{
+                "id": 0,
+                "demand": 6,
+                "allowed_orientations": [0.0],
+                "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 30.0, "height": 20.0 } }
+            }]
+        })
+        .to_string();
+        let output = run_nesting_engine(NestingInput {
+            json_input: json,
+            separation: Some(1.0),
+            kerf_width: Some(2.0),
+            kerf_overlay: Some(true),
+            time_limit: Some(1),
+            seed: Some(7),
+            use_early_termination: Some(true),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(output.total_items_placed, 6);
+        assert_eq!(output.kerf_offset, Some(1.0));
+
+        // Positions refer to the nominal contour, centered on its centroid
+        let outline = |placed: &PlacedItem| {
+            let centered = [(-15.0, -10.0), (15.0, -10.0), (15.0, 10.0), (-15.0, 10.0)];
+            translate_polygon(&centered, placed.position_x, placed.position_y)
+        };
+        for (i, a) in output.layouts.iter().enumerate() {
+            for b in &output.layouts[i + 1..] {
+                let distance = boundary_distance(&outline(a), &outline(b));
+                // Separation between the cut paths, which lie half the kerf outside
+                assert!(distance >= 1.0 + 2.0 - 0.1, "parts {} mm apart", distance);
+            }
+        }
+        let svg = output.svg_string.unwrap();
+        assert!(svg.contains(r#"id="kerf_compensation""#));
+    }
+
+    #[test]
+    fn test_positions_are_reported_for_original_contour() {
+        let json = instance(serde_json::json!({ "extra_separation": 6.0 }));
+        let (_, plan) = grow_instance(&json, &json, 0.0).unwrap();
+        let item = &plan.items[&0];
+        let original = Contour::from_polygon(&item.outer).centroid().unwrap();
+        let grown = Contour::from_polygon(&item.grown).centroid().unwrap();
+
+        let placed = |item_id, host_item_id| PlacedItem {
+            item_id,
+            rotation_degrees: 90.0,
+            position_x: 100.0,
+            position_y: 50.0,
+            host_item_id,
+            outline: None,
+            display_outline: None,
+            metadata: None,
+            bend_line_angle_deg: None,
+            post_placed: false,
+            mirrored: false,
+            locked: false,
+            rotation_radians: None,
+            filler: false,
+        };
+        let mut layouts = vec![placed(0, None), placed(1, None), placed(0, Some(1))];
+        report_original_positions(&mut layouts, &plan);
+
+        // Rotated by 90°: the centroid shift (sx, sy) becomes (-sy, sx)
+        let (sx, sy) = (original.0 - grown.0, original.1 - grown.1);
+        assert!(sx.abs() > 0.1 && sy.abs() > 0.1);
+        assert!((layouts[0].position_x - (100.0 - sy)).abs() < 1e-9);
+        assert!((layouts[0].position_y - (50.0 + sx)).abs() < 1e-9);
+        assert_eq!(
+            (layouts[1].position_x, layouts[1].position_y),
+            (100.0, 50.0)
+        );
+        assert_eq!(
+            (layouts[2].position_x, layouts[2].position_y),
+            (100.0, 50.0)
+        );
+    }
+}
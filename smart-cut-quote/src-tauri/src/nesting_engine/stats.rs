@@ -0,0 +1,42 @@
+//! Run statistics collected during nesting
+//!
+//! Stage timings are recorded around each step of a run so that
+//! performance regressions can be attributed to a specific stage.
+
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// Wall-clock time spent in each stage of a nesting run (seconds)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StageTimings {
+    /// Parsing the instance JSON
+    pub parse_secs: f64,
+    /// Importing the instance into jagua-rs (collision detection setup)
+    pub import_secs: f64,
+    /// Running the sparrow optimizer
+    pub optimize_secs: f64,
+    /// Building the output (serialization, SVG, post-processing)
+    pub serialize_secs: f64,
+}
+
+/// Span around a single stage
+///
+/// Created with `StageSpan::start()` and closed by passing the slot
+/// that should receive the elapsed time.
+pub struct StageSpan {
+    started: Instant,
+}
+
+impl StageSpan {
+    /// Start timing a stage
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+        }
+    }
+
+    /// Stop timing and store the elapsed seconds in `slot`
+    pub fn record(self, slot: &mut f64) {
+        *slot = self.started.elapsed().as_secs_f64();
+    }
+}
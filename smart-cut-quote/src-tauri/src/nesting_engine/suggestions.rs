@@ -0,0 +1,379 @@
+//! Settings worth changing after a poor run
+//!
+//! `analyze_run` looks at a finished output with the settings that
+//! produced it and suggests what to try next: more time when parts were
+//! left over on a sparse layout, 0/90 rotations when mostly long thin
+//! parts were left over, a smaller separation than twice the kerf,
+//! chunking when one part makes up the job, and skipping compression
+//! when it gained nothing. Every suggestion carries a `SuggestedAction`
+//! the UI applies to the next run's input with one click.
+
+use super::chunking::CHUNK_MIN_AREA_SHARE;
+use super::geometry::{bounding_box, rotate_polygon};
+use super::instance::{InstanceGeometry, InstanceItem};
+use super::nesting::NestingConfig;
+use super::serializer::NestingOutput;
+use serde::{Deserialize, Serialize};
+
+/// Utilization below which a run with unplaced parts is given more time
+const LOW_UTILIZATION: f64 = 0.6;
+
+/// Length to width ratio from which a part counts as long and thin
+const THIN_ASPECT_RATIO: f64 = 6.0;
+
+/// Share of the unplaced copies that must be thin to suggest 0/90 rotations
+const THIN_UNPLACED_SHARE: f64 = 0.5;
+
+/// Unplaced thin copies needed to suggest 0/90 rotations
+const MIN_THIN_UNPLACED: usize = 2;
+
+/// Separation above this multiple of the kerf is worth reducing
+const SEPARATION_KERF_FACTOR: f64 = 2.0;
+
+/// Demand below which chunking is not worth it, however large the part
+const MIN_CHUNK_DEMAND: u64 = 50;
+
+/// Compression gain (%) below which the phase is not worth its time
+const NEGLIGIBLE_COMPRESSION_GAIN_PCT: f64 = 0.1;
+
+/// Time limit the optimizer uses when none is set (s)
+const UNSET_TIME_LIMIT_SECS: u64 = 600;
+
+/// Angle step of the search for a part's narrowest orientation (degrees)
+const ASPECT_ANGLE_STEP: f64 = 5.0;
+
+/// Rule behind a suggestion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionRule {
+    /// Unplaced parts on a sparse layout
+    MoreTime,
+    /// Mostly long thin parts left unplaced
+    ThinParts,
+    /// Separation far above the cutting kerf
+    LargeSeparation,
+    /// One part makes up nearly the whole job
+    DominantItem,
+    /// Compression did not shorten the strip
+    IdleCompression,
+}
+
+/// Input change applying a suggestion to the next run (lengths in mm)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SuggestedAction {
+    SetTimeLimit { time_limit: u64 },
+    SetRotations { rotations: Vec<f64> },
+    SetSeparation { separation: f64 },
+    SetChunkDemandThreshold { chunk_demand_threshold: usize },
+    SkipCompression,
+}
+
+/// Setting worth changing, with the numbers behind it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub rule: SuggestionRule,
+    pub message: String,
+    pub action: SuggestedAction,
+}
+
+/// Suggest settings for the next run of the same job
+///
+/// # Arguments
+/// * `output` - Finished output of the run
+/// * `config` - Settings the run used
+/// * `instance` - Instance that was nested
+/// * `kerf` - Kerf width of the material (mm), when known
+pub fn analyze_run(
+    output: &NestingOutput,
+    config: &NestingConfig,
+    instance: &InstanceGeometry,
+    kerf: Option<f64>,
+) -> Vec<Suggestion> {
+    [
+        suggest_more_time(output, config),
+        suggest_thin_rotations(output, config, instance),
+        suggest_smaller_separation(config, kerf),
+        suggest_chunking(output, instance),
+        suggest_skip_compression(output, config),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+fn suggest_more_time(output: &NestingOutput, config: &NestingConfig) -> Option<Suggestion> {
+    if output.unplaced_item_ids.is_empty() || output.utilization >= LOW_UTILIZATION {
+        return None;
+    }
+    let time_limit = config.time_limit.unwrap_or(UNSET_TIME_LIMIT_SECS).max(1) * 2;
+    Some(Suggestion {
+        rule: SuggestionRule::MoreTime,
+        message: format!(
+            "{} parts were not placed at {:.0}% utilization; try a {} s time limit",
+            output.unplaced_item_ids.len(),
+            output.utilization * 100.0,
+            time_limit
+        ),
+        action: SuggestedAction::SetTimeLimit { time_limit },
+    })
+}
+
+fn suggest_thin_rotations(
+    output: &NestingOutput,
+    config: &NestingConfig,
+    instance: &InstanceGeometry,
+) -> Option<Suggestion> {
+    let rotations = vec![0.0, 90.0];
+    if config.rotations.as_ref() == Some(&rotations) {
+        return None;
+    }
+    let thin = output
+        .unplaced_item_ids
+        .iter()
+        .filter(|&&item_id| {
+            instance
+                .items
+                .iter()
+                .find(|item| item.id == item_id as u64)
+                .is_some_and(|item| aspect_ratio(item) >= THIN_ASPECT_RATIO)
+        })
+        .count();
+    let unplaced = output.unplaced_item_ids.len();
+    if thin < MIN_THIN_UNPLACED || (thin as f64) < unplaced as f64 * THIN_UNPLACED_SHARE {
+        return None;
+    }
+    Some(Suggestion {
+        rule: SuggestionRule::ThinParts,
+        message: format!(
+            "{} of {} unplaced parts are long and thin; lining them up at 0/90° packs them closer",
+            thin, unplaced
+        ),
+        action: SuggestedAction::SetRotations { rotations },
+    })
+}
+
+fn suggest_smaller_separation(config: &NestingConfig, kerf: Option<f64>) -> Option<Suggestion> {
+    let kerf = kerf.filter(|&kerf| kerf > 0.0)?;
+    if config.separation <= kerf * SEPARATION_KERF_FACTOR {
+        return None;
+    }
+    Some(Suggestion {
+        rule: SuggestionRule::LargeSeparation,
+        message: format!(
+            "Separation of {} mm is more than {}× the {} mm kerf; try {} mm",
+            config.separation, SEPARATION_KERF_FACTOR, kerf, kerf
+        ),
+        action: SuggestedAction::SetSeparation { separation: kerf },
+    })
+}
+
+fn suggest_chunking(output: &NestingOutput, instance: &InstanceGeometry) -> Option<Suggestion> {
+    if output.repeated_sheet.is_some() {
+        return None;
+    }
+    let demanded_area = |item: &InstanceItem| item.net_area() * item.demand as f64;
+    let total_area: f64 = instance.items.iter().map(demanded_area).sum();
+    let dominant = instance
+        .items
+        .iter()
+        .filter(|item| item.demand >= MIN_CHUNK_DEMAND)
+        .find(|item| {
+            total_area > 0.0 && demanded_area(item) / total_area >= CHUNK_MIN_AREA_SHARE
+        })?;
+    Some(Suggestion {
+        rule: SuggestionRule::DominantItem,
+        message: format!(
+            "{} is {:.0}% of the part area; cut it on repeated sheets (needs the sheet length)",
+            dominant.display_name(),
+            demanded_area(dominant) / total_area * 100.0
+        ),
+        action: SuggestedAction::SetChunkDemandThreshold {
+            chunk_demand_threshold: dominant.demand as usize - 1,
+        },
+    })
+}
+
+fn suggest_skip_compression(output: &NestingOutput, config: &NestingConfig) -> Option<Suggestion> {
+    if config.skip_compression {
+        return None;
+    }
+    let gain = output.phase_stats.as_ref()?.compression_gain_pct?;
+    if gain >= NEGLIGIBLE_COMPRESSION_GAIN_PCT {
+        return None;
+    }
+    Some(Suggestion {
+        rule: SuggestionRule::IdleCompression,
+        message: format!(
+            "Compression shortened the strip by only {:.2}%; spend its time exploring instead",
+            gain
+        ),
+        action: SuggestedAction::SkipCompression,
+    })
+}
+
+/// Length over width of a part in its narrowest orientation
+fn aspect_ratio(item: &InstanceItem) -> f64 {
+    let steps = (180.0 / ASPECT_ANGLE_STEP) as usize;
+    (0..steps)
+        .filter_map(|step| {
+            let rotated = rotate_polygon(&item.outer, step as f64 * ASPECT_ANGLE_STEP);
+            bounding_box(&rotated)
+        })
+        .map(|(min_x, min_y, max_x, max_y)| (max_x - min_x, max_y - min_y))
+        .filter(|&(width, height)| width > 0.0 && height > 0.0)
+        .min_by(|a, b| a.0.min(a.1).total_cmp(&b.0.min(b.1)))
+        .map_or(1.0, |(width, height)| width.max(height) / width.min(height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::instance::parse_instance;
+    use super::super::phases::PhaseStats;
+    use super::*;
+
+    /// Instance of a 40 × 40 plate (id 0, demand 2) and a 300 × 20 bar
+    /// (id 1) with the given demand
+    fn instance(bar_demand: u64) -> InstanceGeometry {
+        let rect = |id: u64, demand: u64, w: f64, h: f64| {
+            serde_json::json!({
+                "id": id,
+                "demand": demand,
+                "shape": { "type": "simple_polygon", "data": [[0, 0], [w, 0], [w, h], [0, h]] },
+            })
+        };
+        parse_instance(
+            &serde_json::json!({
+                "name": "suggest",
+                "strip_height": 1000.0,
+                "items": [rect(0, 2, 40.0, 40.0), rect(1, bar_demand, 300.0, 20.0)],
+            })
+            .to_string(),
+        )
+        .unwrap()
+    }
+
+    fn output(utilization: f64, unplaced: Vec<usize>) -> NestingOutput {
+        serde_json::from_value(serde_json::json!({
+            "instance_name": "suggest",
+            "strip_width": 2000.0,
+            "strip_height": 1000.0,
+            "total_items_placed": 4,
+            "layouts": [],
+            "utilization": utilization,
+            "computation_time_secs": 60.0,
+            "unplaced_item_ids": unplaced,
+        }))
+        .unwrap()
+    }
+
+    fn rules(suggestions: &[Suggestion]) -> Vec<SuggestionRule> {
+        suggestions
+            .iter()
+            .map(|suggestion| suggestion.rule)
+            .collect()
+    }
+
+    #[test]
+    fn test_more_time_for_sparse_run_with_unplaced_parts() {
+        let config = NestingConfig {
+            time_limit: Some(60),
+            ..Default::default()
+        };
+        let suggestion = suggest_more_time(&output(0.45, vec![0]), &config).unwrap();
+        assert_eq!(
+            suggestion.action,
+            SuggestedAction::SetTimeLimit { time_limit: 120 }
+        );
+        assert!(suggest_more_time(&output(0.75, vec![0]), &config).is_none());
+        assert!(suggest_more_time(&output(0.45, vec![]), &config).is_none());
+    }
+
+    #[test]
+    fn test_thin_rotations_when_bars_are_left_over() {
+        let config = NestingConfig::default();
+        let suggestion =
+            suggest_thin_rotations(&output(0.7, vec![1, 1, 0]), &config, &instance(5)).unwrap();
+        assert_eq!(
+            suggestion.action,
+            SuggestedAction::SetRotations {
+                rotations: vec![0.0, 90.0]
+            }
+        );
+        // Mostly plates left over
+        assert!(
+            suggest_thin_rotations(&output(0.7, vec![1, 0, 0]), &config, &instance(5)).is_none()
+        );
+        // Already at 0/90
+        let config = NestingConfig {
+            rotations: Some(vec![0.0, 90.0]),
+            ..Default::default()
+        };
+        assert!(suggest_thin_rotations(&output(0.7, vec![1, 1]), &config, &instance(5)).is_none());
+    }
+
+    #[test]
+    fn test_separation_compared_with_kerf() {
+        let config = NestingConfig {
+            separation: 5.0,
+            ..Default::default()
+        };
+        let suggestion = suggest_smaller_separation(&config, Some(0.2)).unwrap();
+        assert_eq!(
+            suggestion.action,
+            SuggestedAction::SetSeparation { separation: 0.2 }
+        );
+        assert!(suggest_smaller_separation(&config, Some(3.0)).is_none());
+        assert!(suggest_smaller_separation(&config, None).is_none());
+    }
+
+    #[test]
+    fn test_chunking_for_dominant_item() {
+        let suggestion = suggest_chunking(&output(0.8, vec![]), &instance(400)).unwrap();
+        assert_eq!(
+            suggestion.action,
+            SuggestedAction::SetChunkDemandThreshold {
+                chunk_demand_threshold: 399
+            }
+        );
+        // Few copies are nested fine
+        assert!(suggest_chunking(&output(0.8, vec![]), &instance(10)).is_none());
+    }
+
+    #[test]
+    fn test_skip_compression_without_gain() {
+        let mut run = output(0.8, vec![]);
+        let config = NestingConfig::default();
+        assert!(suggest_skip_compression(&run, &config).is_none());
+
+        run.phase_stats = Some(PhaseStats {
+            compression_gain_pct: Some(0.0),
+            ..Default::default()
+        });
+        let suggestion = suggest_skip_compression(&run, &config).unwrap();
+        assert_eq!(suggestion.action, SuggestedAction::SkipCompression);
+
+        run.phase_stats = Some(PhaseStats {
+            compression_gain_pct: Some(3.5),
+            ..Default::default()
+        });
+        assert!(suggest_skip_compression(&run, &config).is_none());
+    }
+
+    #[test]
+    fn test_analyze_run_combines_rules() {
+        let config = NestingConfig {
+            separation: 5.0,
+            ..Default::default()
+        };
+        let suggestions = analyze_run(&output(0.4, vec![1, 1]), &config, &instance(5), Some(0.2));
+        assert_eq!(
+            rules(&suggestions),
+            vec![
+                SuggestionRule::MoreTime,
+                SuggestionRule::ThinParts,
+                SuggestionRule::LargeSeparation
+            ]
+        );
+    }
+}
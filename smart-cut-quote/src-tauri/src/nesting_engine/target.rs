@@ -0,0 +1,121 @@
+//! Stopping a run once its layout is good enough
+//!
+//! A quick quote does not need the shortest strip, a utilization of say
+//! 80% is enough. `TargetListener` wraps the listener of a run, computes
+//! the utilization of every feasible solution and terminates the run
+//! through its terminator as soon as `target_utilization` is reached.
+//! The optimizer then returns that solution like after a timeout.
+
+use super::terminator::NativeTerminator;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use sparrow::util::listener::{ReportType, SolutionListener};
+use std::time::Instant;
+
+/// Utilization of a strip holding parts of `item_area` in total
+pub fn strip_utilization(item_area: f64, strip_width: f64, strip_height: f64) -> f64 {
+    let strip_area = strip_width * strip_height;
+    if strip_area > 0.0 {
+        item_area / strip_area
+    } else {
+        0.0
+    }
+}
+
+/// Listener stopping the run once a feasible solution reaches the target
+pub struct TargetListener<L> {
+    inner: L,
+    /// None passes every report through and never stops the run
+    target: Option<f64>,
+    /// Net area of all parts the optimizer nests (mm²)
+    item_area: f64,
+    terminator: NativeTerminator,
+    started: Instant,
+    reached_secs: Option<f64>,
+}
+
+impl<L> TargetListener<L> {
+    /// Wrap `inner`, terminating through `terminator` once the target is met
+    pub fn new(
+        inner: L,
+        target: Option<f64>,
+        item_area: f64,
+        terminator: NativeTerminator,
+    ) -> Self {
+        Self {
+            inner,
+            target,
+            item_area,
+            terminator,
+            started: Instant::now(),
+            reached_secs: None,
+        }
+    }
+
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    /// Seconds into the run the target was reached, None if it never was
+    pub fn reached_secs(&self) -> Option<f64> {
+        self.reached_secs
+    }
+}
+
+impl<L: SolutionListener> SolutionListener for TargetListener<L> {
+    fn report(&mut self, report_type: ReportType, solution: &SPSolution, instance: &SPInstance) {
+        let feasible = matches!(report_type, ReportType::ExplFeas | ReportType::CmprFeas);
+        if let Some(target) = self
+            .target
+            .filter(|_| feasible && self.reached_secs.is_none())
+        {
+            let utilization = strip_utilization(
+                self.item_area,
+                solution.strip_width() as f64,
+                instance.base_strip.fixed_height as f64,
+            );
+            if utilization >= target {
+                let secs = self.started.elapsed().as_secs_f64();
+                println!(
+                    "🎯 Utilization {:.1}% reached the target of {:.1}% after {:.1}s",
+                    utilization * 100.0,
+                    target * 100.0,
+                    secs
+                );
+                self.reached_secs = Some(secs);
+                self.terminator.terminate();
+            }
+        }
+        self.inner.report(report_type, solution, instance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_utilization() {
+        assert_eq!(strip_utilization(8000.0, 100.0, 100.0), 0.8);
+        assert_eq!(strip_utilization(8000.0, 0.0, 100.0), 0.0);
+    }
+
+    #[test]
+    fn test_target_run_stops_early() {
+        use super::super::{run_nesting_engine, NestingInput};
+
+        let output = run_nesting_engine(NestingInput {
+            json_input: include_str!("../../test_instances/thin_frame.json").to_string(),
+            time_limit: Some(30),
+            seed: Some(7),
+            // Any feasible layout is good enough
+            target_utilization: Some(0.01),
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(output.status.as_deref(), Some("target_reached"));
+        let reached = output.target_reached_secs.unwrap();
+        assert!(reached < 30.0);
+        assert!(output.computation_time_secs < 30.0);
+    }
+}
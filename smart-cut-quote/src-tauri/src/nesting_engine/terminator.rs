@@ -12,6 +12,10 @@ use std::time::{Duration, Instant};
 use std::sync::atomic::AtomicUsize;
 static TIMEOUT_PRINTED: AtomicUsize = AtomicUsize::new(0);
 
+/// Prefix of the error returned when a run is cancelled before it found
+/// any feasible layout
+pub const CANCELLED_BEFORE_SOLUTION: &str = "cancelled_before_solution";
+
 /// Native terminator for desktop/Tauri environment
 ///
 /// This implements the `Terminator` trait from sparrow, allowing
@@ -61,6 +65,11 @@ impl NativeTerminator {
         false
     }
 
+    /// Check if termination was requested externally, ignoring the timeout
+    pub fn is_cancelled(&self) -> bool {
+        self.stop.load(Ordering::SeqCst)
+    }
+
     /// Reset the terminator for reuse
     pub fn reset(&self) {
         self.stop.store(false, Ordering::SeqCst);
@@ -73,6 +82,15 @@ impl NativeTerminator {
     pub fn get_handle(&self) -> NativeTerminator {
         self.clone()
     }
+
+    /// Terminator for one of several runs: stopped with this one, but
+    /// with its own deadline
+    pub fn child(&self) -> NativeTerminator {
+        Self {
+            stop: self.stop.clone(),
+            deadline: Arc::new(RwLock::new(None)),
+        }
+    }
 }
 
 impl Default for NativeTerminator {
@@ -163,6 +181,18 @@ mod tests {
         assert!(term.kill());
     }
 
+    #[test]
+    fn test_timeout_is_not_a_cancellation() {
+        let mut term = NativeTerminator::new();
+        term.new_timeout(Duration::from_millis(1));
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(term.is_terminated());
+        assert!(!term.is_cancelled());
+
+        term.terminate();
+        assert!(term.is_cancelled());
+    }
+
     #[test]
     fn test_terminator_reset() {
         let term = NativeTerminator::new();
@@ -181,6 +211,17 @@ mod tests {
         assert!(term2.is_terminated());
     }
 
+    #[test]
+    fn test_child_shares_stop_but_not_deadline() {
+        let mut parent = NativeTerminator::new();
+        parent.new_timeout(Duration::from_secs(60));
+        let child = parent.child();
+        assert!(child.timeout_at().is_none());
+
+        parent.terminate();
+        assert!(child.is_terminated());
+    }
+
     #[test]
     fn test_terminator_timeout() {
         let mut term = NativeTerminator::new();
@@ -0,0 +1,320 @@
+//! Time budget shared by the groups of a batch
+//!
+//! Instead of every group running for its own time limit, a batch may
+//! give one total budget. Each group gets a share proportional to its
+//! difficulty (copies × distinct shapes), clamped to a floor and a
+//! ceiling. Groups run one after the other; before each one the budget
+//! left is shared again over the groups still to run, so time a group
+//! did not use (it stopped early) goes to the ones after it.
+
+use super::instance::parse_instance;
+use serde::{Deserialize, Serialize};
+
+/// Least time a group gets when the batch sets no floor
+pub const DEFAULT_GROUP_FLOOR_SECS: u64 = 5;
+
+/// Budget decisions for one group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupTimeBudget {
+    pub key: String,
+    /// Copies × distinct shapes of the group's instance
+    pub difficulty: f64,
+    /// Share of the total budget before any group ran
+    pub planned_secs: u64,
+    /// Time limit the group ran with, None if it has not run yet
+    pub allocated_secs: Option<u64>,
+    /// Wall time the group took
+    pub used_secs: Option<f64>,
+}
+
+impl GroupTimeBudget {
+    /// Seconds the group got beyond (or below) its planned share
+    pub fn reallocated_secs(&self) -> i64 {
+        self.allocated_secs
+            .map_or(0, |allocated| allocated as i64 - self.planned_secs as i64)
+    }
+}
+
+/// Allocation of a batch time budget, updated as the groups finish
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchTimeBudget {
+    pub total_secs: u64,
+    pub floor_secs: u64,
+    pub ceiling_secs: u64,
+    pub groups: Vec<GroupTimeBudget>,
+}
+
+impl BatchTimeBudget {
+    /// Plan a budget over groups given as (key, difficulty), in run order
+    ///
+    /// The ceiling defaults to the total budget and is never below the floor.
+    pub fn new(
+        total_secs: u64,
+        floor_secs: Option<u64>,
+        ceiling_secs: Option<u64>,
+        groups: Vec<(String, f64)>,
+    ) -> Self {
+        let floor_secs = floor_secs.unwrap_or(DEFAULT_GROUP_FLOOR_SECS);
+        let ceiling_secs = ceiling_secs.unwrap_or(total_secs).max(floor_secs);
+        let mut budget = Self {
+            total_secs,
+            floor_secs,
+            ceiling_secs,
+            groups: groups
+                .into_iter()
+                .map(|(key, difficulty)| GroupTimeBudget {
+                    key,
+                    difficulty,
+                    planned_secs: 0,
+                    allocated_secs: None,
+                    used_secs: None,
+                })
+                .collect(),
+        };
+        let planned = budget.shares(total_secs as f64, 0);
+        for (group, share) in budget.groups.iter_mut().zip(planned) {
+            group.planned_secs = whole_secs(share);
+        }
+        budget
+    }
+
+    /// Seconds of the budget the finished groups have not used
+    pub fn remaining_secs(&self) -> f64 {
+        let used: f64 = self.groups.iter().filter_map(|group| group.used_secs).sum();
+        (self.total_secs as f64 - used).max(0.0)
+    }
+
+    /// Time limit of group `index`, from the budget the groups before it left
+    pub fn allocate(&mut self, index: usize) -> u64 {
+        let share = self.shares(self.remaining_secs(), index)[0];
+        let allocated = whole_secs(share);
+        self.groups[index].allocated_secs = Some(allocated);
+        allocated
+    }
+
+    /// Record the wall time group `index` took
+    pub fn record(&mut self, index: usize, used_secs: f64) {
+        self.groups[index].used_secs = Some(used_secs);
+    }
+
+    /// Share of `budget` for each group from `start` on
+    ///
+    /// Proportional to difficulty; a group below the floor or above the
+    /// ceiling is pinned there and the rest is shared again. When the
+    /// floors alone exceed the budget every group still gets its floor.
+    fn shares(&self, budget: f64, start: usize) -> Vec<f64> {
+        let groups = &self.groups[start..];
+        let floor = self.floor_secs as f64;
+        let ceiling = self.ceiling_secs as f64;
+        let mut pinned: Vec<Option<f64>> = vec![None; groups.len()];
+        loop {
+            let free: Vec<usize> = (0..groups.len())
+                .filter(|&index| pinned[index].is_none())
+                .collect();
+            if free.is_empty() {
+                break;
+            }
+            let left = budget - pinned.iter().flatten().sum::<f64>();
+            let weight: f64 = free.iter().map(|&index| groups[index].difficulty).sum();
+            let share = |index: usize| {
+                if weight > 0.0 {
+                    left * groups[index].difficulty / weight
+                } else {
+                    left / free.len() as f64
+                }
+            };
+            let below: Vec<usize> = free.iter().copied().filter(|&i| share(i) < floor).collect();
+            let above: Vec<usize> = free
+                .iter()
+                .copied()
+                .filter(|&i| share(i) > ceiling)
+                .collect();
+            if !below.is_empty() {
+                below.iter().for_each(|&index| pinned[index] = Some(floor));
+            } else if !above.is_empty() {
+                above
+                    .iter()
+                    .for_each(|&index| pinned[index] = Some(ceiling));
+            } else {
+                free.iter()
+                    .for_each(|&index| pinned[index] = Some(share(index)));
+            }
+        }
+        pinned
+            .into_iter()
+            .map(|share| share.unwrap_or(floor))
+            .collect()
+    }
+
+    /// One line per group: planned, allocated and used time
+    pub fn explain(&self) -> String {
+        let mut text = format!(
+            "Time budget {} s (floor {} s, ceiling {} s)\n",
+            self.total_secs, self.floor_secs, self.ceiling_secs
+        );
+        for group in &self.groups {
+            let allocated = match group.allocated_secs {
+                Some(allocated) => format!("{} s ({:+} s)", allocated, group.reallocated_secs()),
+                None => "not run".to_string(),
+            };
+            let used = group
+                .used_secs
+                .map_or(String::new(), |used| format!(", used {:.1} s", used));
+            text.push_str(&format!(
+                "{}: difficulty {}, planned {} s, allocated {}{}\n",
+                group.key, group.difficulty, group.planned_secs, allocated, used
+            ));
+        }
+        text
+    }
+}
+
+/// Difficulty of an instance: copies × distinct shapes (at least 1)
+///
+/// An instance that does not parse counts as 1; its group fails anyway.
+pub fn instance_difficulty(json_str: &str) -> f64 {
+    match parse_instance(json_str) {
+        Ok(instance) => {
+            let copies: u64 = instance.items.iter().map(|item| item.demand).sum();
+            (copies as f64 * instance.items.len() as f64).max(1.0)
+        }
+        Err(_) => 1.0,
+    }
+}
+
+/// Whole seconds of a share, at least 1
+fn whole_secs(share: f64) -> u64 {
+    (share.floor() as u64).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(
+        total: u64,
+        floor: u64,
+        ceiling: Option<u64>,
+        difficulties: &[f64],
+    ) -> BatchTimeBudget {
+        let groups = difficulties
+            .iter()
+            .enumerate()
+            .map(|(index, difficulty)| (format!("group-{}", index), *difficulty))
+            .collect();
+        BatchTimeBudget::new(total, Some(floor), ceiling, groups)
+    }
+
+    /// Run the groups in order; group `i` stops early after `finish[i]` seconds
+    fn run(budget: &mut BatchTimeBudget, finish: &[f64]) -> Vec<u64> {
+        (0..finish.len())
+            .map(|index| {
+                let allocated = budget.allocate(index);
+                budget.record(index, finish[index].min(allocated as f64));
+                allocated
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_is_proportional_to_difficulty() {
+        let budget = budget(100, 5, None, &[1.0, 3.0, 6.0]);
+        let planned: Vec<u64> = budget
+            .groups
+            .iter()
+            .map(|group| group.planned_secs)
+            .collect();
+        assert_eq!(planned, vec![10, 30, 60]);
+    }
+
+    #[test]
+    fn test_plan_respects_floor_and_ceiling() {
+        // 1 of 1000 would get 0.1 s: pinned to the floor, the rest shared again
+        let planned: Vec<u64> = budget(100, 10, None, &[1.0, 499.5, 499.5])
+            .groups
+            .iter()
+            .map(|group| group.planned_secs)
+            .collect();
+        assert_eq!(planned, vec![10, 45, 45]);
+
+        // The hard group is capped; what it cannot take goes to the others
+        let planned: Vec<u64> = budget(100, 5, Some(50), &[1.0, 1.0, 8.0])
+            .groups
+            .iter()
+            .map(|group| group.planned_secs)
+            .collect();
+        assert_eq!(planned, vec![25, 25, 50]);
+
+        // Floors beyond the budget: every group still gets its floor
+        let planned: Vec<u64> = budget(10, 5, None, &[1.0, 1.0, 1.0])
+            .groups
+            .iter()
+            .map(|group| group.planned_secs)
+            .collect();
+        assert_eq!(planned, vec![5, 5, 5]);
+    }
+
+    #[test]
+    fn test_unused_time_goes_to_later_groups() {
+        // Eight groups, five trivial ones stopping after 2 s
+        let difficulties = [1.0, 50.0, 1.0, 1.0, 50.0, 1.0, 1.0, 50.0];
+        let finish = [2.0, 500.0, 2.0, 2.0, 500.0, 2.0, 2.0, 500.0];
+        let mut budget = budget(240, 5, None, &difficulties);
+        let planned: Vec<u64> = budget
+            .groups
+            .iter()
+            .map(|group| group.planned_secs)
+            .collect();
+        assert_eq!(planned, vec![5, 71, 5, 5, 71, 5, 5, 71]);
+
+        let allocated = run(&mut budget, &finish);
+        // The trivial groups give back 3 s each, shared by the hard ones after them
+        assert_eq!(allocated, vec![5, 72, 5, 5, 76, 5, 5, 82]);
+        assert!(allocated[7] > planned[7]);
+        assert!(budget.groups[7].reallocated_secs() > 0);
+
+        let used: f64 = budget
+            .groups
+            .iter()
+            .filter_map(|group| group.used_secs)
+            .sum();
+        assert!(used <= 240.0);
+    }
+
+    #[test]
+    fn test_overrun_shrinks_later_groups() {
+        // The first group takes 20 s more than allocated (post-processing)
+        let mut budget = budget(100, 5, None, &[1.0, 1.0]);
+        assert_eq!(budget.allocate(0), 50);
+        budget.record(0, 70.0);
+        assert_eq!(budget.allocate(1), 30);
+
+        // Nothing left: the floor still applies
+        let mut budget = self::budget(20, 5, None, &[1.0, 1.0]);
+        budget.allocate(0);
+        budget.record(0, 40.0);
+        assert_eq!(budget.remaining_secs(), 0.0);
+        assert_eq!(budget.allocate(1), 5);
+    }
+
+    #[test]
+    fn test_explain_lists_every_group() {
+        let mut budget = budget(60, 5, None, &[1.0, 2.0]);
+        run(&mut budget, &[3.0, 100.0]);
+        let text = budget.explain();
+        assert!(
+            text.contains("group-0: difficulty 1, planned 20 s, allocated 20 s (+0 s), used 3.0 s")
+        );
+        assert!(text.contains("group-1: difficulty 2, planned 40 s, allocated 57 s (+17 s)"));
+    }
+
+    #[test]
+    fn test_instance_difficulty() {
+        let json = r#"{"name":"t","strip_height":100.0,"items":[
+            {"id":0,"demand":3,"shape":{"type":"rectangle","data":{"x_min":0.0,"y_min":0.0,"width":10.0,"height":10.0}}},
+            {"id":1,"demand":2,"shape":{"type":"rectangle","data":{"x_min":0.0,"y_min":0.0,"width":5.0,"height":5.0}}}
+        ]}"#;
+        assert_eq!(instance_difficulty(json), 10.0);
+        assert_eq!(instance_difficulty("not json"), 1.0);
+    }
+}
@@ -0,0 +1,409 @@
+//! Checking a finished layout for overlapping parts
+//!
+//! The optimizer resolves collisions on simplified shapes, and hole
+//! nesting, the small part pass, locked parts and mirrored copies place
+//! parts outside of it. `verify_layout` checks the final placements once
+//! more on the input contours, placed the way the exports cut them (see
+//! `layout_dxf::place_contours`): every pair of parts for overlap and
+//! separation, every part against the sheet edge.
+//!
+//! Pairs come from the bounding box grid of the collision probes (see
+//! `adjustment`), only parts whose boxes come closer than the separation
+//! are measured. A part inside a hole of another one is as far from it as
+//! from the hole's edge. Two parts have to keep the cut separation plus
+//! the larger extra separation of the two, what every placement pass
+//! guarantees, and the cut path, half the kerf outside the contour, has
+//! to stay on the sheet. Further sheets of a fixed-sheet run carry no
+//! outlines and are not checked.
+
+use super::adjustment::SpatialGrid;
+use super::geometry::{
+    boundary_distance, bounding_box, open_ring, point_in_polygon, DisplayUnits, Polygon,
+};
+use super::instance::InstanceGeometry;
+use super::layout_dxf::{place_contours, PlacedContours};
+use super::nesting::DEFAULT_ITEM_SEPARATION;
+use super::remnant::{reaches_into, rings_cross};
+use super::serializer::NestingOutput;
+use serde::{Deserialize, Serialize};
+
+/// Clearance shortfall still accepted, absorbing the f32 rounding of
+/// placements and the polygon offsets the optimizer separates with (mm)
+const SEPARATION_TOLERANCE: f64 = 0.1;
+
+/// What is wrong with a placement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind {
+    /// Two parts share material
+    Overlap,
+    /// Two parts closer than the separation
+    Separation,
+    /// The cut path of a part leaves the sheet (or remnant)
+    OutsideSheet,
+}
+
+/// Placement failing the check of a finished layout
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverlapReport {
+    pub kind: ViolationKind,
+    /// Index into `layouts`
+    pub placement: usize,
+    pub item_id: usize,
+    /// The other part of an `Overlap` or `Separation`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub other_placement: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub other_item_id: Option<usize>,
+    /// Clearance found: between the parts, or to the sheet edge
+    /// (negative past it, 0 for a part not lying on its remnant)
+    pub distance: f64,
+    /// Clearance the layout has to keep there
+    pub required: f64,
+}
+
+impl OverlapReport {
+    /// Report given in `from` units, in `units`
+    pub fn in_units(self, units: DisplayUnits, from: DisplayUnits) -> Self {
+        Self {
+            distance: units.length_from(self.distance, from),
+            required: units.length_from(self.required, from),
+            ..self
+        }
+    }
+}
+
+/// Part of the layout as it gets cut
+struct PlacedPart {
+    contours: PlacedContours,
+    bbox: (f64, f64, f64, f64),
+    extra_separation: f64,
+}
+
+/// Check the placements of an output on the contours that get cut
+///
+/// `outlines` are the placement outlines in layout order. Everything is
+/// in mm; separation and kerf come from the resolved config.
+pub fn verify_layout(
+    output: &NestingOutput,
+    outlines: &[Polygon],
+    instance: &InstanceGeometry,
+) -> Result<Vec<OverlapReport>, String> {
+    if outlines.len() != output.layouts.len() {
+        return Err(format!(
+            "{} outlines for {} placements",
+            outlines.len(),
+            output.layouts.len()
+        ));
+    }
+    let (cut_separation, kerf_width) = output
+        .resolved_config
+        .as_ref()
+        .map_or((DEFAULT_ITEM_SEPARATION, 0.0), |config| {
+            (config.cut_separation(), config.kerf_width)
+        });
+
+    let parts = output
+        .layouts
+        .iter()
+        .zip(outlines)
+        .map(|(placed, outline)| {
+            let contours = place_contours(placed, outline, instance)?;
+            let bbox = bounding_box(&contours.outer)
+                .ok_or_else(|| format!("Item {} has an empty contour", placed.item_id))?;
+            let extra_separation = instance
+                .items
+                .iter()
+                .find(|item| item.id == placed.item_id as u64)
+                .map_or(0.0, |item| item.extra_separation);
+            Ok(PlacedPart {
+                contours,
+                bbox,
+                extra_separation,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut reports = Vec::new();
+    let report = |kind, a: usize, b: Option<usize>, distance, required| OverlapReport {
+        kind,
+        placement: a,
+        item_id: parts[a].contours.item_id,
+        other_placement: b,
+        other_item_id: b.map(|b| parts[b].contours.item_id),
+        distance,
+        required,
+    };
+
+    let edge_margin = kerf_width / 2.0;
+    for (index, part) in parts.iter().enumerate() {
+        match edge_clearance(part, output) {
+            Some(distance) if distance >= edge_margin - SEPARATION_TOLERANCE => {}
+            distance => reports.push(report(
+                ViolationKind::OutsideSheet,
+                index,
+                None,
+                distance.unwrap_or(0.0),
+                edge_margin,
+            )),
+        }
+    }
+
+    let max_extra = parts
+        .iter()
+        .map(|part| part.extra_separation)
+        .fold(0.0, f64::max);
+    let boxes: Vec<_> = parts.iter().map(|part| part.bbox).collect();
+    let grid = SpatialGrid::new(&boxes);
+    for (a, part) in parts.iter().enumerate() {
+        let near = grid.query(grown(part.bbox, cut_separation + max_extra));
+        for b in near.into_iter().filter(|&b| b > a) {
+            let other = &parts[b];
+            let required = cut_separation + part.extra_separation.max(other.extra_separation);
+            if !overlaps_bbox(grown(part.bbox, required), other.bbox) {
+                continue;
+            }
+            if share_material(&part.contours, &other.contours) {
+                reports.push(report(ViolationKind::Overlap, a, Some(b), 0.0, required));
+                continue;
+            }
+            let distance = clearance(&part.contours, &other.contours);
+            if distance < required - SEPARATION_TOLERANCE {
+                reports.push(report(
+                    ViolationKind::Separation,
+                    a,
+                    Some(b),
+                    distance,
+                    required,
+                ));
+            }
+        }
+    }
+    Ok(reports)
+}
+
+/// `verify_layout` for an output carrying its placement outlines (in mm)
+pub fn verify_output(
+    output: &NestingOutput,
+    instance: &InstanceGeometry,
+) -> Result<Vec<OverlapReport>, String> {
+    let outlines = output
+        .layouts
+        .iter()
+        .map(|placed| placed.outline.clone())
+        .collect::<Option<Vec<_>>>()
+        .ok_or("Layout has no part outlines, nest with include_outlines to verify it")?;
+    verify_layout(output, &outlines, instance)
+}
+
+/// Smallest distance of a part to the sheet edge, negative past it
+///
+/// None for a part not lying inside the remnant of a remnant layout.
+fn edge_clearance(part: &PlacedPart, output: &NestingOutput) -> Option<f64> {
+    let outer = &part.contours.outer;
+    if let Some(container) = &output.container_polygon {
+        if !container.contains(outer) {
+            return None;
+        }
+        return Some(
+            std::iter::once(&container.outer)
+                .chain(&container.holes)
+                .map(|ring| boundary_distance(outer, open_ring(ring)))
+                .fold(f64::INFINITY, f64::min),
+        );
+    }
+    let length = output.sheet_width.unwrap_or(output.strip_width);
+    let (min_x, min_y, max_x, max_y) = part.bbox;
+    Some(
+        min_x
+            .min(min_y)
+            .min(length - max_x)
+            .min(output.strip_height - max_y),
+    )
+}
+
+/// True if the parts share material, touching does not count
+fn share_material(a: &PlacedContours, b: &PlacedContours) -> bool {
+    reaches_into(&a.outer, &b.outer) && !in_hole(a, b) && !in_hole(b, a)
+}
+
+/// True if `part` lies inside one of the holes of `host`
+fn in_hole(part: &PlacedContours, host: &PlacedContours) -> bool {
+    let Some(&point) = part.outer.first() else {
+        return false;
+    };
+    host.holes
+        .iter()
+        .any(|hole| point_in_polygon(point, hole) && !rings_cross(&part.outer, hole))
+}
+
+/// Distance between the contours of two parts not sharing material
+///
+/// Holes only matter for a part inside the other's hole, so the outer
+/// contour of each is measured against every contour of the other.
+fn clearance(a: &PlacedContours, b: &PlacedContours) -> f64 {
+    let against = |outer: &Polygon, other: &PlacedContours| {
+        std::iter::once(&other.outer)
+            .chain(&other.holes)
+            .map(|ring| boundary_distance(outer, ring))
+            .fold(f64::INFINITY, f64::min)
+    };
+    against(&a.outer, b).min(against(&b.outer, a))
+}
+
+fn grown(bbox: (f64, f64, f64, f64), margin: f64) -> (f64, f64, f64, f64) {
+    (
+        bbox.0 - margin,
+        bbox.1 - margin,
+        bbox.2 + margin,
+        bbox.3 + margin,
+    )
+}
+
+fn overlaps_bbox(a: (f64, f64, f64, f64), b: (f64, f64, f64, f64)) -> bool {
+    a.0 < b.2 && b.0 < a.2 && a.1 < b.3 && b.1 < a.3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::instance::parse_instance;
+    use super::super::nesting::NestingConfig;
+    use super::super::{run_nesting_engine, NestingInput};
+    use super::*;
+
+    fn rectangle(x: f64, y: f64, width: f64, height: f64) -> Polygon {
+        vec![
+            (x, y),
+            (x + width, y),
+            (x + width, y + height),
+            (x, y + height),
+        ]
+    }
+
+    /// A 100 x 50 plate with a 40 x 30 hole, 10 and 20 mm squares
+    fn instance() -> InstanceGeometry {
+        parse_instance(
+            r#"{"name": "verify", "strip_height": 60, "items": [
+                {"id": 0, "demand": 1, "shape": {"type": "polygon", "data": {
+                    "outer": [[0, 0], [100, 0], [100, 50], [0, 50]],
+                    "inner": [[[30, 10], [70, 10], [70, 40], [30, 40]]]
+                }}},
+                {"id": 1, "demand": 3, "shape": {"type": "rectangle",
+                    "data": {"x_min": 0, "y_min": 0, "width": 10, "height": 10}}},
+                {"id": 2, "demand": 2, "shape": {"type": "rectangle",
+                    "data": {"x_min": 0, "y_min": 0, "width": 20, "height": 20}}}
+            ]}"#,
+        )
+        .unwrap()
+    }
+
+    /// Unrotated placements at the given outlines on a 300 x 60 strip
+    fn output(placements: &[(usize, Polygon)]) -> NestingOutput {
+        let layouts: Vec<_> = placements
+            .iter()
+            .map(|(item_id, outline)| {
+                let (x, y, _, _) = bounding_box(outline).unwrap();
+                serde_json::json!({
+                    "item_id": item_id,
+                    "rotation_degrees": 0.0,
+                    "position_x": x,
+                    "position_y": y,
+                    "outline": outline,
+                })
+            })
+            .collect();
+        let mut output: NestingOutput = serde_json::from_value(serde_json::json!({
+            "instance_name": "verify",
+            "strip_width": 300.0,
+            "strip_height": 60.0,
+            "total_items_placed": layouts.len(),
+            "layouts": layouts,
+            "utilization": 0.5,
+            "computation_time_secs": 1.0,
+        }))
+        .unwrap();
+        output.resolved_config = Some(NestingConfig {
+            separation: 2.0,
+            ..Default::default()
+        });
+        output
+    }
+
+    #[test]
+    fn test_overlaps_close_parts_and_the_sheet_edge_are_reported() {
+        let output = output(&[
+            (0, rectangle(1.0, 1.0, 100.0, 50.0)),
+            // In the plate's hole, 9 mm from its edge
+            (1, rectangle(45.0, 20.0, 10.0, 10.0)),
+            (1, rectangle(102.0, 1.0, 10.0, 10.0)),
+            (2, rectangle(120.0, 1.0, 20.0, 20.0)),
+            (2, rectangle(130.0, 10.0, 20.0, 20.0)),
+            (1, rectangle(295.0, 50.0, 10.0, 10.0)),
+        ]);
+        let reports = verify_output(&output, &instance()).unwrap();
+
+        let summary: Vec<_> = reports
+            .iter()
+            .map(|report| (report.kind, report.placement, report.other_placement))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ViolationKind::OutsideSheet, 5, None),
+                (ViolationKind::Separation, 0, Some(2)),
+                (ViolationKind::Overlap, 3, Some(4)),
+            ]
+        );
+        assert!((reports[0].distance + 5.0).abs() < 1e-9);
+        assert!((reports[1].distance - 1.0).abs() < 1e-9);
+        assert_eq!((reports[1].item_id, reports[1].other_item_id), (0, Some(1)));
+        assert_eq!(reports[1].required, 2.0);
+    }
+
+    #[test]
+    fn test_engine_layouts_pass_the_check() {
+        let json = serde_json::json!({
+            "name": "verify",
+            "strip_height": 60.0,
+            "items": [
+                {
+                    "id": 0,
+                    "demand": 2,
+                    "shape": {
+                        "type": "polygon",
+                        "data": {
+                            "outer": [[0.0, 0.0], [100.0, 0.0], [100.0, 50.0], [0.0, 50.0]],
+                            "inner": [[[30.0, 10.0], [70.0, 10.0], [70.0, 40.0], [30.0, 40.0]]]
+                        }
+                    }
+                },
+                {
+                    "id": 1,
+                    "demand": 6,
+                    "shape": { "type": "rectangle", "data": { "x_min": 0.0, "y_min": 0.0, "width": 30.0, "height": 20.0 } }
+                }
+            ]
+        })
+        .to_string();
+        let output = run_nesting_engine(NestingInput {
+            json_input: json.clone(),
+            separation: Some(1.0),
+            kerf_width: Some(2.0),
+            include_outlines: Some(true),
+            time_limit: Some(1),
+            seed: Some(7),
+            use_early_termination: Some(true),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(output.total_items_placed, 8);
+        assert!(output.violations.is_empty(), "{:?}", output.violations);
+
+        let mut stripped = output.clone();
+        for placed in &mut stripped.layouts {
+            placed.outline = None;
+        }
+        let error = verify_output(&stripped, &parse_instance(&json).unwrap()).unwrap_err();
+        assert!(error.contains("include_outlines"), "{}", error);
+    }
+}
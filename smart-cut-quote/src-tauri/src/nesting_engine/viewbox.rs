@@ -0,0 +1,392 @@
+//! Margin around the layout SVG
+//!
+//! The jagua-rs rendering sets its viewBox tight around the strip, so
+//! parts placed at the edge look cut off. The viewBox is widened by a
+//! margin; when the attribute is missing or cannot be parsed, it is
+//! rebuilt from the placements instead and a warning with the raw value
+//! is returned for the output. Only the root `<svg>` tag is touched.
+//!
+//! With `fit_svg_viewbox` in the nesting input the rendered viewBox is
+//! not read at all, a fresh one is computed from the strip and the
+//! placements.
+
+use super::geometry::{bounding_box, Polygon};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// Margin added around the layout (SVG units, i.e. mm)
+pub const VIEWBOX_MARGIN: f64 = 50.0;
+
+/// Problem found while post-processing the layout SVG
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SvgWarning {
+    pub message: String,
+    /// viewBox attribute as rendered, None when the SVG had none
+    pub raw_viewbox: Option<String>,
+    /// viewBox computed from the placements instead (None if there were none)
+    pub fallback_viewbox: Option<String>,
+}
+
+fn viewbox_re() -> &'static Regex {
+    static VIEWBOX_RE: OnceLock<Regex> = OnceLock::new();
+    // viewBox="minX minY width height", in either quotes
+    VIEWBOX_RE.get_or_init(|| Regex::new(r#"\sviewBox\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap())
+}
+
+/// Span of the root `<svg ...>` start tag
+///
+/// Only this tag's viewBox sets the view, nested `<svg>` and `<symbol>`
+/// elements carry their own.
+fn root_svg_tag(svg: &str) -> Option<Range<usize>> {
+    let start = svg.match_indices("<svg").map(|(i, _)| i).find(|&i| {
+        svg[i + "<svg".len()..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_whitespace() || c == '>' || c == '/')
+    })?;
+    let mut quote = None;
+    for (offset, c) in svg[start..].char_indices() {
+        match (quote, c) {
+            (Some(open), _) if c == open => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(start..start + offset + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// viewBox attribute of the root `<svg>` tag: its span and its value
+fn root_viewbox(svg: &str) -> Option<(Range<usize>, &str)> {
+    let tag = root_svg_tag(svg)?;
+    let caps = viewbox_re().captures(&svg[tag.clone()])?;
+    let attribute = caps.get(0)?;
+    let value = caps.get(1).or_else(|| caps.get(2))?;
+    Some((
+        tag.start + attribute.start()..tag.start + attribute.end(),
+        value.as_str(),
+    ))
+}
+
+/// `min_x min_y width height` (see `svg_numbers`)
+fn parse_viewbox(value: &str) -> Option<[f64; 4]> {
+    match svg_numbers(value)?[..] {
+        [min_x, min_y, width, height] if width >= 0.0 && height >= 0.0 => {
+            Some([min_x, min_y, width, height])
+        }
+        _ => None,
+    }
+}
+
+/// Numbers of an SVG number list
+///
+/// Separated by whitespace and/or a comma, or by nothing where the next
+/// number starts with a sign or a second point ("0-5" is 0 and -5).
+/// Exponents ("1e3", "2.5E-2") are accepted, units and non-finite
+/// values are not.
+fn svg_numbers(list: &str) -> Option<Vec<f64>> {
+    let bytes = list.as_bytes();
+    let digits = |i: &mut usize| {
+        let start = *i;
+        while bytes.get(*i).is_some_and(u8::is_ascii_digit) {
+            *i += 1;
+        }
+        *i > start
+    };
+
+    let mut numbers = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i].is_ascii_whitespace() || bytes[i] == b',' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        if matches!(bytes[i], b'+' | b'-') {
+            i += 1;
+        }
+        let mut mantissa = digits(&mut i);
+        if bytes.get(i) == Some(&b'.') {
+            i += 1;
+            mantissa |= digits(&mut i);
+        }
+        if !mantissa {
+            return None;
+        }
+        if matches!(bytes.get(i), Some(b'e' | b'E')) {
+            i += 1;
+            if matches!(bytes.get(i), Some(b'+' | b'-')) {
+                i += 1;
+            }
+            if !digits(&mut i) {
+                return None;
+            }
+        }
+        let number: f64 = list[start..i].parse().ok()?;
+        if !number.is_finite() {
+            return None;
+        }
+        numbers.push(number);
+    }
+    Some(numbers)
+}
+
+fn format_viewbox([min_x, min_y, width, height]: [f64; 4]) -> String {
+    format!("{} {} {} {}", min_x, min_y, width, height)
+}
+
+/// Bounding box of all placements grown by `margin` on every side
+fn placements_viewbox(placements: &[Polygon], margin: f64) -> Option<[f64; 4]> {
+    let points: Vec<(f64, f64)> = placements.iter().flatten().copied().collect();
+    let (min_x, min_y, max_x, max_y) = bounding_box(&points)?;
+    Some([
+        min_x - margin,
+        min_y - margin,
+        max_x - min_x + 2.0 * margin,
+        max_y - min_y + 2.0 * margin,
+    ])
+}
+
+/// Replace the root viewBox attribute, or add one to the root `<svg>` tag
+fn write_viewbox(svg: &str, value: &str) -> Option<String> {
+    let attribute = format!(" viewBox=\"{}\"", value);
+    if let Some((span, _)) = root_viewbox(svg) {
+        return Some(format!(
+            "{}{}{}",
+            &svg[..span.start],
+            attribute,
+            &svg[span.end..]
+        ));
+    }
+    let tag = root_svg_tag(svg)?.start + "<svg".len();
+    Some(format!("{}{}{}", &svg[..tag], attribute, &svg[tag..]))
+}
+
+/// Expand the SVG viewBox by `margin` on all sides
+///
+/// Falls back to the bounding box of the placements (plus margin) when
+/// the viewBox is missing or malformed, `placements` is only called then.
+/// The SVG is returned unchanged only if that is not possible either. A
+/// fallback always comes with a warning.
+pub fn expand_svg_viewbox(
+    svg: &str,
+    margin: f64,
+    placements: impl FnOnce() -> Vec<Polygon>,
+) -> (String, Option<SvgWarning>) {
+    let raw_viewbox = root_viewbox(svg).map(|(_, value)| value.to_string());
+
+    if let Some(viewbox) = raw_viewbox.as_deref().and_then(parse_viewbox) {
+        let [min_x, min_y, width, height] = viewbox;
+        let expanded = format_viewbox([
+            min_x - margin,
+            min_y - margin,
+            width + 2.0 * margin,
+            height + 2.0 * margin,
+        ]);
+        println!(
+            "📐 Expanded viewBox: {} → {}",
+            raw_viewbox.as_deref().unwrap_or_default(),
+            expanded
+        );
+        if let Some(svg) = write_viewbox(svg, &expanded) {
+            return (svg, None);
+        }
+    }
+
+    let fallback = placements_viewbox(&placements(), margin).map(format_viewbox);
+    let rewritten = fallback
+        .as_deref()
+        .and_then(|viewbox| write_viewbox(svg, viewbox));
+    let message = match (&raw_viewbox, &rewritten) {
+        (None, Some(_)) => "SVG has no viewBox, computed it from the placements".to_string(),
+        (Some(raw), Some(_)) => format!(
+            "SVG viewBox \"{}\" could not be parsed, computed it from the placements",
+            raw
+        ),
+        (None, None) => "SVG has no viewBox and none could be computed, parts at the edge may be clipped".to_string(),
+        (Some(raw), None) => format!(
+            "SVG viewBox \"{}\" could not be parsed and none could be computed, parts at the edge may be clipped",
+            raw
+        ),
+    };
+    println!("⚠️ {}", message);
+
+    let warning = SvgWarning {
+        message,
+        raw_viewbox,
+        fallback_viewbox: rewritten.as_ref().and(fallback),
+    };
+    (rewritten.unwrap_or_else(|| svg.to_string()), Some(warning))
+}
+
+/// Set the SVG viewBox from the layout itself, grown by `margin`
+///
+/// Ignores the rendered viewBox: the strip (`width`, `height` from the
+/// origin) and every placement stay in view. Only an SVG without a root
+/// `<svg>` tag is returned unchanged, with a warning.
+pub fn fit_svg_viewbox(
+    svg: &str,
+    margin: f64,
+    (width, height): (f64, f64),
+    placements: &[Polygon],
+) -> (String, Option<SvgWarning>) {
+    let (min_x, min_y, max_x, max_y) = placements.iter().flatten().fold(
+        (0.0_f64, 0.0_f64, width, height),
+        |(min_x, min_y, max_x, max_y), &(x, y)| {
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        },
+    );
+    let viewbox = format_viewbox([
+        min_x - margin,
+        min_y - margin,
+        max_x - min_x + 2.0 * margin,
+        max_y - min_y + 2.0 * margin,
+    ]);
+
+    match write_viewbox(svg, &viewbox) {
+        Some(fitted) => {
+            println!("📐 Fitted viewBox to the layout: {}", viewbox);
+            (fitted, None)
+        }
+        None => {
+            let message = "SVG has no <svg> tag, its viewBox could not be set".to_string();
+            println!("⚠️ {}", message);
+            let warning = SvgWarning {
+                message,
+                raw_viewbox: None,
+                fallback_viewbox: None,
+            };
+            (svg.to_string(), Some(warning))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placements() -> Vec<Polygon> {
+        vec![
+            vec![(10.0, 20.0), (110.0, 20.0), (110.0, 70.0), (10.0, 70.0)],
+            vec![(300.0, 5.0), (340.0, 5.0), (320.0, 250.0)],
+        ]
+    }
+
+    fn viewbox_of(svg: &str) -> [f64; 4] {
+        let (_, value) = root_viewbox(svg).expect("viewBox attribute");
+        parse_viewbox(value).expect("valid viewBox")
+    }
+
+    #[test]
+    fn test_valid_viewbox_is_expanded() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 400 300"><g/></svg>"#;
+        let (expanded, warning) = expand_svg_viewbox(svg, 50.0, placements);
+        assert!(warning.is_none());
+        assert_eq!(viewbox_of(&expanded), [-50.0, -50.0, 500.0, 400.0]);
+        assert!(expanded.ends_with("<g/></svg>"));
+    }
+
+    #[test]
+    fn test_missing_viewbox_is_added_from_placements() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg"><g/></svg>"#;
+        let (expanded, warning) = expand_svg_viewbox(svg, 50.0, placements);
+        let warning = warning.expect("warning");
+        assert_eq!(warning.raw_viewbox, None);
+        assert_eq!(viewbox_of(&expanded), [-40.0, -45.0, 430.0, 345.0]);
+        assert_eq!(warning.fallback_viewbox.as_deref(), Some("-40 -45 430 345"));
+        assert!(expanded.starts_with(r#"<svg viewBox="-40 -45 430 345" xmlns="#));
+    }
+
+    #[test]
+    fn test_malformed_viewbox_is_reported_with_raw_value() {
+        let svg = r#"<svg viewBox="0 0 NaN 300px"><g/></svg>"#;
+        let (expanded, warning) = expand_svg_viewbox(svg, 50.0, placements);
+        let warning = warning.expect("warning");
+        assert_eq!(warning.raw_viewbox.as_deref(), Some("0 0 NaN 300px"));
+        assert!(warning.message.contains("\"0 0 NaN 300px\""));
+        assert!(!expanded.contains("NaN"));
+        assert_eq!(expanded.matches("viewBox=").count(), 1);
+
+        // Without placements the SVG is kept as it was
+        let (unchanged, warning) = expand_svg_viewbox(svg, 50.0, Vec::new);
+        assert_eq!(unchanged, svg);
+        assert_eq!(warning.unwrap().fallback_viewbox, None);
+    }
+
+    #[test]
+    fn test_fallback_viewbox_contains_every_placement_with_margin() {
+        let margin = 12.5;
+        let svg = r#"<svg viewBox="garbage"></svg>"#;
+        let (expanded, _) = expand_svg_viewbox(svg, margin, placements);
+        let [min_x, min_y, width, height] = viewbox_of(&expanded);
+        for placement in placements() {
+            let (x0, y0, x1, y1) = bounding_box(&placement).unwrap();
+            assert!(min_x <= x0 - margin && min_y <= y0 - margin);
+            assert!(min_x + width >= x1 + margin && min_y + height >= y1 + margin);
+        }
+    }
+
+    #[test]
+    fn test_parse_viewbox_accepts_commas() {
+        assert_eq!(parse_viewbox("0,-5, 10 20"), Some([0.0, -5.0, 10.0, 20.0]));
+        assert_eq!(parse_viewbox("0-5 10,20"), Some([0.0, -5.0, 10.0, 20.0]));
+        assert_eq!(parse_viewbox("0 0 10"), None);
+        assert_eq!(parse_viewbox("0 0 -10 20"), None);
+        assert_eq!(parse_viewbox(""), None);
+    }
+
+    #[test]
+    fn test_parse_viewbox_accepts_exponents() {
+        assert_eq!(
+            parse_viewbox("-1.5e1 .5 4E2 3.0e+2"),
+            Some([-15.0, 0.5, 400.0, 300.0])
+        );
+        assert_eq!(parse_viewbox("0 0 1e 300"), None);
+        assert_eq!(parse_viewbox("0 0 1e400 300"), None);
+    }
+
+    #[test]
+    fn test_comma_and_exponent_viewboxes_are_expanded() {
+        for viewbox in ["0,0,400,300", "0, 0 4e2,3E2", "0e0 -0 4.0e+2 300"] {
+            let svg = format!(r#"<svg viewBox="{}"><g/></svg>"#, viewbox);
+            let (expanded, warning) = expand_svg_viewbox(&svg, 50.0, placements);
+            assert!(warning.is_none(), "{}", viewbox);
+            assert_eq!(viewbox_of(&expanded), [-50.0, -50.0, 500.0, 400.0]);
+        }
+    }
+
+    #[test]
+    fn test_only_the_root_viewbox_is_touched() {
+        // Root without a viewBox: a nested one must not be widened instead
+        let svg = r#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg"><symbol id="part" viewBox="0 0 10 10"/><svg viewBox='0 0 20 20'/></svg>"#;
+        let (expanded, warning) = expand_svg_viewbox(svg, 50.0, placements);
+        assert_eq!(warning.expect("warning").raw_viewbox, None);
+        assert_eq!(viewbox_of(&expanded), [-40.0, -45.0, 430.0, 345.0]);
+        assert!(expanded.contains(r#"<symbol id="part" viewBox="0 0 10 10"/>"#));
+        assert!(expanded.contains("<svg viewBox='0 0 20 20'/>"));
+
+        // Single quotes on the root, a nested viewBox after it
+        let svg =
+            r#"<svg viewBox = '0 0 400 300' data-note="a > b"><svg viewBox="0 0 20 20"/></svg>"#;
+        let (expanded, warning) = expand_svg_viewbox(svg, 50.0, placements);
+        assert!(warning.is_none());
+        assert_eq!(viewbox_of(&expanded), [-50.0, -50.0, 500.0, 400.0]);
+        assert!(expanded.ends_with(r#"data-note="a > b"><svg viewBox="0 0 20 20"/></svg>"#));
+    }
+
+    #[test]
+    fn test_fitted_viewbox_ignores_the_rendered_one() {
+        let svg = r#"<svg viewBox="0 0 1 1"><g/></svg>"#;
+        let (fitted, warning) = fit_svg_viewbox(svg, 10.0, (200.0, 300.0), &placements());
+        assert!(warning.is_none());
+        // Strip from the origin, widened to the placement reaching x = 340
+        assert_eq!(viewbox_of(&fitted), [-10.0, -10.0, 360.0, 320.0]);
+        assert_eq!(fitted.matches("viewBox=").count(), 1);
+
+        let (unchanged, warning) = fit_svg_viewbox("<g/>", 10.0, (200.0, 300.0), &placements());
+        assert_eq!(unchanged, "<g/>");
+        assert!(warning.is_some());
+    }
+}
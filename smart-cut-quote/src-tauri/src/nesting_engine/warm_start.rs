@@ -0,0 +1,249 @@
+//! Continuing the optimization of a stored layout
+//!
+//! A warm start places the parts where a stored output has them and runs
+//! only sparrow's compression phase from there, with the whole time
+//! limit: the strip never gets wider than the stored one, so a short run
+//! can only shorten an already good layout. Parts placed after packing
+//! (in holes or by the small part post-pass) are placed again as usual,
+//! locked parts stay where they are.
+//!
+//! Layouts the optimizer did not place part by part as stored cannot be
+//! restored: mirrored parts, repeated full sheets, several fixed sheets
+//! and remnants. `from_output` returns None for them, and a warm start
+//! that does not match the instance (see `restore`) falls back to a
+//! normal run. With `compress_only` both cases are errors instead.
+
+use super::geometry::rotate_polygon;
+use super::serializer::NestingOutput;
+use jagua_rs::geometry::DTransformation;
+use jagua_rs::probs::spp::entities::{SPInstance, SPPlacement, SPProblem, SPSolution};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use serde::{Deserialize, Serialize};
+use sparrow::config::CompressionConfig;
+use sparrow::optimizer::compress::compression_phase;
+use sparrow::optimizer::separator::Separator;
+use sparrow::util::listener::{ReportType, SolutionListener};
+use sparrow::util::terminator::Terminator;
+
+/// Placements of a layout to continue from (mm, degrees)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WarmStart {
+    pub strip_width: f64,
+    pub placements: Vec<WarmPlacement>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WarmPlacement {
+    pub item_id: usize,
+    pub rotation_degrees: f64,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl WarmStart {
+    /// Placements of the optimizer in a stored output (in mm)
+    pub fn from_output(output: &NestingOutput) -> Option<Self> {
+        let restorable = output.sheets.is_empty()
+            && output.repeated_sheet.is_none()
+            && output.container_polygon.is_none()
+            && output.layouts.iter().all(|placed| !placed.mirrored);
+        if !restorable {
+            return None;
+        }
+        let placements = output
+            .layouts
+            .iter()
+            .filter(|placed| placed.host_item_id.is_none() && !placed.post_placed && !placed.locked)
+            .map(|placed| WarmPlacement {
+                item_id: placed.item_id,
+                rotation_degrees: placed.rotation_degrees,
+                x: placed.position_x,
+                y: placed.position_y,
+            })
+            .collect();
+        Some(Self {
+            strip_width: output.strip_width,
+            placements,
+        })
+    }
+
+    /// The same layout for items moved by `-offset` (see `origin`)
+    pub fn shifted(&self, offset: (f64, f64)) -> Self {
+        let placements = self
+            .placements
+            .iter()
+            .map(|placement| {
+                let (dx, dy) = rotate_polygon(&[offset], placement.rotation_degrees)[0];
+                WarmPlacement {
+                    x: placement.x + dx,
+                    y: placement.y + dy,
+                    ..*placement
+                }
+            })
+            .collect();
+        Self {
+            strip_width: self.strip_width,
+            placements,
+        }
+    }
+}
+
+/// Problem with the warm start's parts placed, None if they do not match
+///
+/// Every placement needs an item of the instance with a copy left.
+pub fn restore(instance: &SPInstance, warm: &WarmStart) -> Option<SPProblem> {
+    let mut left: Vec<usize> = instance.items.iter().map(|(_, qty)| *qty).collect();
+    for placement in &warm.placements {
+        let qty = left.get_mut(placement.item_id)?;
+        *qty = qty.checked_sub(1)?;
+    }
+
+    let mut prob = SPProblem::new(instance.clone());
+    prob.change_strip_width(warm.strip_width as f32);
+    for placement in &warm.placements {
+        prob.place_item(SPPlacement {
+            item_id: placement.item_id,
+            d_transf: DTransformation::new(
+                placement.rotation_degrees.to_radians() as f32,
+                (placement.x as f32, placement.y as f32),
+            ),
+        });
+    }
+    Some(prob)
+}
+
+/// Compress a restored layout until the terminator stops it
+pub fn compress_from(
+    instance: &SPInstance,
+    mut prob: SPProblem,
+    rng: Xoshiro256PlusPlus,
+    listener: &mut impl SolutionListener,
+    terminator: &mut impl Terminator,
+    config: &CompressionConfig,
+) -> SPSolution {
+    let initial = prob.save();
+    let mut separator = Separator::new(instance.clone(), prob, rng, config.separator_config);
+    let solution = compression_phase(
+        instance,
+        &mut separator,
+        &initial,
+        listener,
+        terminator,
+        config,
+    );
+    listener.report(ReportType::Final, &solution, instance);
+    solution
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{run_nesting_engine, NestingInput, OptimizerPhase};
+    use super::*;
+
+    fn input(seed: u64, time_limit: u64) -> NestingInput {
+        NestingInput {
+            json_input: include_str!("../../test_instances/thin_frame.json").to_string(),
+            time_limit: Some(time_limit),
+            seed: Some(seed),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_shifted_moves_by_the_rotated_offset() {
+        let warm = WarmStart {
+            strip_width: 100.0,
+            placements: vec![WarmPlacement {
+                item_id: 0,
+                rotation_degrees: 90.0,
+                x: 10.0,
+                y: 20.0,
+            }],
+        };
+        let shifted = warm.shifted((5.0, 0.0));
+        assert!((shifted.placements[0].x - 10.0).abs() < 1e-9);
+        assert!((shifted.placements[0].y - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_warm_start_never_widens_the_strip() {
+        let stored = run_nesting_engine(input(7, 2)).unwrap();
+        let warm = WarmStart::from_output(&stored).unwrap();
+        assert_eq!(warm.placements.len(), stored.total_items_placed);
+
+        let mut continued = input(8, 2);
+        continued.warm_start = Some(warm);
+        let output = run_nesting_engine(continued).unwrap();
+
+        assert!(output.warm_started);
+        assert_eq!(output.total_items_placed, stored.total_items_placed);
+        assert!(output.strip_width <= stored.strip_width + 1e-3);
+    }
+
+    #[test]
+    fn test_compress_only_runs_just_the_compression_phase() {
+        let stored = run_nesting_engine(input(7, 2)).unwrap();
+
+        let mut compressed = input(8, 1);
+        compressed.compress_only = Some(true);
+        compressed.warm_start = WarmStart::from_output(&stored);
+        let output = run_nesting_engine(compressed).unwrap();
+
+        let stats = output.phase_stats.unwrap();
+        assert_eq!(stats.phases_run, vec![OptimizerPhase::Compress]);
+        assert_eq!(stats.handoff_strip_width, Some(stored.strip_width));
+        let delta = stats.width_delta.unwrap();
+        assert!((delta - (stored.strip_width - output.strip_width)).abs() < 1e-3);
+
+        let from_scratch = run_nesting_engine(input(7, 1)).unwrap();
+        assert_eq!(
+            from_scratch.phase_stats.unwrap().phases_run,
+            vec![OptimizerPhase::Explore, OptimizerPhase::Compress]
+        );
+    }
+
+    #[test]
+    fn test_compress_only_needs_a_matching_layout() {
+        let mut without = input(7, 1);
+        without.compress_only = Some(true);
+        let error = run_nesting_engine(without).unwrap_err();
+        assert!(error.contains("compress_only"), "{}", error);
+
+        let mut mismatched = input(7, 1);
+        mismatched.compress_only = Some(true);
+        mismatched.warm_start = Some(WarmStart {
+            strip_width: 500.0,
+            placements: vec![
+                WarmPlacement {
+                    item_id: 0,
+                    rotation_degrees: 0.0,
+                    x: 0.0,
+                    y: 0.0,
+                };
+                2
+            ],
+        });
+        let error = run_nesting_engine(mismatched).unwrap_err();
+        assert!(error.contains("matching the instance"), "{}", error);
+    }
+
+    #[test]
+    fn test_warm_start_of_another_instance_runs_from_scratch() {
+        let mut other = input(7, 1);
+        other.warm_start = Some(WarmStart {
+            strip_width: 500.0,
+            placements: vec![
+                WarmPlacement {
+                    item_id: 0,
+                    rotation_degrees: 0.0,
+                    x: 0.0,
+                    y: 0.0,
+                };
+                2
+            ],
+        });
+        let output = run_nesting_engine(other).unwrap();
+        assert!(!output.warm_started);
+        assert_eq!(output.status.as_deref(), Some("complete"));
+    }
+}
@@ -0,0 +1,116 @@
+//! Contour direction normalization for CAM export
+//!
+//! DXF files keep whatever direction the contours were drawn in, but
+//! many CAM systems derive the kerf side from it: outer contours
+//! counter-clockwise and holes clockwise, or the opposite on some
+//! machines. The helpers here enforce one convention and report which
+//! contours had to be reversed.
+
+use super::geometry::{open_ring, signed_area, Polygon};
+use serde::{Deserialize, Serialize};
+
+/// Direction convention for exported contours
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Winding {
+    /// Outer contours counter-clockwise, holes clockwise
+    #[default]
+    CcwOuter,
+    /// Outer contours clockwise, holes counter-clockwise
+    CwOuter,
+}
+
+/// Contours of one part that were reversed by `orient_contours`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContourReversals {
+    pub outer_reversed: bool,
+    /// Number of reversed holes
+    pub holes_reversed: usize,
+}
+
+impl ContourReversals {
+    /// True when any contour of the part had to be reversed
+    pub fn any(&self) -> bool {
+        self.outer_reversed || self.holes_reversed > 0
+    }
+}
+
+/// Orient the outer contour and the holes of a part in place
+///
+/// Direction is taken from the signed area, so a closing vertex equal
+/// to the first one is allowed. Degenerate (zero area) contours are
+/// left untouched.
+pub fn orient_contours(
+    outer: &mut Polygon,
+    holes: &mut [Polygon],
+    winding: Winding,
+) -> ContourReversals {
+    let outer_ccw = winding == Winding::CcwOuter;
+    ContourReversals {
+        outer_reversed: orient(outer, outer_ccw),
+        holes_reversed: holes
+            .iter_mut()
+            .map(|hole| orient(hole, !outer_ccw))
+            .filter(|&reversed| reversed)
+            .count(),
+    }
+}
+
+/// Reverse `ring` unless it already runs in the wanted direction
+fn orient(ring: &mut Polygon, ccw: bool) -> bool {
+    let area = signed_area(open_ring(ring));
+    if area == 0.0 || (area > 0.0) == ccw {
+        return false;
+    }
+    ring.reverse();
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(size: f64, offset: f64) -> Polygon {
+        vec![
+            (offset, offset),
+            (offset + size, offset),
+            (offset + size, offset + size),
+            (offset, offset + size),
+        ]
+    }
+
+    #[test]
+    fn test_clockwise_outer_is_reversed() {
+        // Outer loop drawn clockwise, hole already clockwise
+        let mut outer: Polygon = square(100.0, 0.0).into_iter().rev().collect();
+        let mut holes = vec![square(20.0, 40.0).into_iter().rev().collect()];
+
+        let reversals = orient_contours(&mut outer, &mut holes, Winding::CcwOuter);
+        assert_eq!(
+            reversals,
+            ContourReversals {
+                outer_reversed: true,
+                holes_reversed: 0
+            }
+        );
+        assert!(signed_area(&outer) > 0.0);
+        assert!(signed_area(&holes[0]) < 0.0);
+
+        // Already normalized contours stay as they are
+        let again = orient_contours(&mut outer, &mut holes, Winding::CcwOuter);
+        assert!(!again.any());
+    }
+
+    #[test]
+    fn test_cw_outer_convention() {
+        let mut outer = square(100.0, 0.0);
+        outer.push(outer[0]);
+        let mut holes = vec![square(20.0, 10.0), square(20.0, 60.0)];
+
+        let reversals = orient_contours(&mut outer, &mut holes, Winding::CwOuter);
+        assert!(reversals.outer_reversed);
+        assert_eq!(reversals.holes_reversed, 0);
+        assert!(signed_area(&outer) < 0.0);
+        assert_eq!(outer.first(), outer.last());
+    }
+}
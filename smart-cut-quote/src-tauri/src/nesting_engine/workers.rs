@@ -0,0 +1,85 @@
+//! Worker count fallback for restricted machines
+//!
+//! sparrow sets up its rayon worker pool internally and panics when
+//! the threads cannot be created (e.g. locked-down machines limiting
+//! thread creation). The requested worker count is therefore checked
+//! up front and reduced until a pool can actually be started.
+
+use log::warn;
+
+/// Largest worker count, at most `requested`, for which `spawn_pool`
+/// succeeds
+///
+/// Halves the count after every failure and ends at 1 worker, which
+/// is returned even if that check fails too (the optimizer then runs
+/// single threaded and reports its own error).
+pub fn available_workers<F>(requested: usize, spawn_pool: F) -> usize
+where
+    F: Fn(usize) -> Result<(), String>,
+{
+    let mut workers = requested.max(1);
+    loop {
+        match spawn_pool(workers) {
+            Ok(()) => return workers,
+            Err(e) if workers > 1 => {
+                let fewer = workers / 2;
+                warn!(
+                    "Could not start {} nesting workers ({}), retrying with {}",
+                    workers, e, fewer
+                );
+                println!(
+                    "⚠️ Could not start {} nesting workers ({}), retrying with {}",
+                    workers, e, fewer
+                );
+                workers = fewer;
+            }
+            Err(e) => {
+                warn!("Could not start a single nesting worker: {}", e);
+                return 1;
+            }
+        }
+    }
+}
+
+/// Start (and drop) a rayon pool with `workers` threads
+pub fn spawn_rayon_pool(workers: usize) -> Result<(), String> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(workers)
+        .build()
+        .map(drop)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_falls_back_to_fewer_workers() {
+        let attempts = RefCell::new(Vec::new());
+        // Simulates a machine that allows at most 3 extra threads
+        let workers = available_workers(8, |n| {
+            attempts.borrow_mut().push(n);
+            if n > 3 {
+                Err(format!("cannot spawn {} threads", n))
+            } else {
+                Ok(())
+            }
+        });
+
+        assert_eq!(workers, 2);
+        assert_eq!(*attempts.borrow(), vec![8, 4, 2]);
+    }
+
+    #[test]
+    fn test_single_worker_is_the_floor() {
+        assert_eq!(available_workers(4, |_| Err("denied".to_string())), 1);
+        assert_eq!(available_workers(0, |_| Ok(())), 1);
+    }
+
+    #[test]
+    fn test_rayon_pool_starts() {
+        assert_eq!(available_workers(2, spawn_rayon_pool), 2);
+    }
+}
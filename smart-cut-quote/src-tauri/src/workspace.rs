@@ -0,0 +1,54 @@
+//! Application data directories owned by the backend
+//!
+//! Everything the app stores besides the database lives in a fixed set
+//! of sub-directories of the app data dir, so it can be archived and
+//! restored as a unit.
+
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Sub-directories of the app data dir that belong to the workspace
+pub const WORKSPACE_DIRS: &[&str] = &["jobs", "library", "svgs", "thumbnails"];
+
+/// Root of the app data directory
+pub fn app_data_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))
+}
+
+/// Path of a workspace sub-directory (not created)
+pub fn workspace_dir(app_handle: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    Ok(app_data_dir(app_handle)?.join(name))
+}
+
+/// All files below `dir`, recursively, as paths relative to `dir`
+///
+/// Returns an empty list if the directory does not exist.
+pub fn list_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    if dir.is_dir() {
+        collect_files(dir, dir, &mut files)?;
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory '{}': {}", dir.display(), e))?;
+
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("Failed to read directory '{}': {}", dir.display(), e))?
+            .path();
+        if path.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_path_buf());
+        }
+    }
+
+    Ok(())
+}
@@ -3,11 +3,23 @@
  * Combines Sidebar, Header, and Stepper as per IMPLEMENTATION_PLAN.md section 6.1
  */
 
-import React from 'react';
-import { Box, Drawer } from '@mui/material';
+import React, { useEffect, useState } from 'react';
+import { Alert, Box, Button, Drawer, Snackbar } from '@mui/material';
 import Sidebar from './Sidebar';
 import Header from './Header';
 import Stepper from './Stepper';
+import { invoke } from '@tauri-apps/api/core';
+import { useQuoteStore } from '../../stores/quoteStore';
+import { getDatabase, getSetting } from '../../services/database';
+import { startSourceWatch } from '../../services/sourceWatchService';
+import { forceQuit, JobsFinishingEvent, onJobsFinishing } from '../../services/shutdownService';
+import {
+  discardDraft,
+  listRecoverableDrafts,
+  RecoverableDraft,
+  restoreDraft,
+  startDraftAutosave,
+} from '../../services/draftService';
 
 interface AppLayoutProps {
   children: React.ReactNode;
@@ -16,6 +28,65 @@ interface AppLayoutProps {
 const DRAWER_WIDTH = 240;
 
 export default function AppLayout({ children }: AppLayoutProps) {
+  const currentQuoteId = useQuoteStore((state) => state.currentQuoteId);
+  const [jobsFinishing, setJobsFinishing] = useState<JobsFinishingEvent | null>(null);
+  const [recoverable, setRecoverable] = useState<RecoverableDraft[]>([]);
+
+  // Dimension strings like "1,250" are read per the user's locale in the backend
+  useEffect(() => {
+    getSetting('number_locale')
+      .catch(() => null)
+      .then((tag) => invoke('set_number_locale', { languageTag: tag ?? navigator.language }))
+      .catch((error) => console.error('Failed to set number locale:', error));
+  }, []);
+
+  // Exit waits for running nesting jobs to stop and save their results
+  useEffect(() => {
+    const unlisten = onJobsFinishing(setJobsFinishing);
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  // Offer drafts of a crashed session, then autosave the quote being edited.
+  // The drafts are read once the database is loaded (backend commands share its pool).
+  useEffect(() => {
+    getDatabase()
+      .then(() => listRecoverableDrafts())
+      .then(setRecoverable)
+      .catch((error) => console.error('Failed to list recoverable drafts:', error));
+
+    return startDraftAutosave();
+  }, []);
+
+  const handleRestoreDraft = (draft: RecoverableDraft) => {
+    try {
+      restoreDraft(draft);
+    } catch (error) {
+      console.error('Failed to restore draft:', error);
+    }
+    handleDiscardDraft(draft);
+  };
+
+  const handleDiscardDraft = (draft: RecoverableDraft) => {
+    setRecoverable((drafts) => drafts.filter((other) => other.id !== draft.id));
+    discardDraft(draft.id).catch((error) => console.error('Failed to discard draft:', error));
+  };
+
+  // Follow edits of the open quote's source DXF files; stops when the quote is closed
+  useEffect(() => {
+    if (!currentQuoteId) return;
+
+    const stopWatch = startSourceWatch(currentQuoteId).catch((error) => {
+      console.error('Failed to watch quote sources:', error);
+      return null;
+    });
+
+    return () => {
+      stopWatch.then((stop) => stop?.());
+    };
+  }, [currentQuoteId]);
+
   return (
     <Box sx={{ display: 'flex', height: '100vh' }}>
       {/* Sidebar */}
@@ -49,6 +120,42 @@ export default function AppLayout({ children }: AppLayoutProps) {
           {children}
         </Box>
       </Box>
+
+      <Snackbar open={jobsFinishing !== null} anchorOrigin={{ vertical: 'bottom', horizontal: 'center' }}>
+        <Alert
+          severity="info"
+          action={
+            <Button color="inherit" size="small" onClick={() => forceQuit()}>
+              Quit now
+            </Button>
+          }
+        >
+          Jobs finishing… ({jobsFinishing?.running} running, up to{' '}
+          {jobsFinishing?.grace_period_secs}s)
+        </Alert>
+      </Snackbar>
+
+      <Snackbar
+        open={recoverable.length > 0 && jobsFinishing === null}
+        anchorOrigin={{ vertical: 'bottom', horizontal: 'center' }}
+      >
+        <Alert
+          severity="warning"
+          action={
+            <>
+              <Button color="inherit" size="small" onClick={() => handleRestoreDraft(recoverable[0])}>
+                Restore
+              </Button>
+              <Button color="inherit" size="small" onClick={() => handleDiscardDraft(recoverable[0])}>
+                Discard
+              </Button>
+            </>
+          }
+        >
+          Unsaved quote from {recoverable[0] && new Date(`${recoverable[0].updated_at.replace(' ', 'T')}Z`).toLocaleString()}
+          {recoverable.length > 1 && ` (${recoverable.length - 1} more)`} was recovered after the app closed unexpectedly.
+        </Alert>
+      </Snackbar>
     </Box>
   );
 }
@@ -0,0 +1,111 @@
+/**
+ * Diagnostics Tab
+ * Installation problems found at startup, with a repair action
+ */
+
+import { useState, useEffect } from 'react';
+import {
+  Box,
+  Button,
+  Typography,
+  Alert,
+  Paper,
+  List,
+  ListItem,
+  ListItemText,
+  CircularProgress,
+} from '@mui/material';
+import BuildIcon from '@mui/icons-material/Build';
+
+import {
+  getStartupReport,
+  repairInstallation,
+  onStartupIssues,
+  StartupReport,
+} from '../../services/startupCheckService';
+
+export default function DiagnosticsTab() {
+  const [report, setReport] = useState<StartupReport | null>(null);
+  const [repairing, setRepairing] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    getStartupReport()
+      .then(setReport)
+      .catch((err) => setError(String(err)));
+
+    const unlisten = onStartupIssues(setReport);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const handleRepair = async () => {
+    setRepairing(true);
+    setError(null);
+    try {
+      setReport(await repairInstallation());
+    } catch (err) {
+      setError(String(err));
+    } finally {
+      setRepairing(false);
+    }
+  };
+
+  return (
+    <Box>
+      <Typography variant="h6" gutterBottom>
+        Installation Check
+      </Typography>
+
+      {error && (
+        <Alert severity="error" sx={{ mb: 2 }} onClose={() => setError(null)}>
+          {error}
+        </Alert>
+      )}
+
+      {!report ? (
+        <CircularProgress size={24} />
+      ) : (
+        <Paper sx={{ p: 2 }}>
+          <Typography variant="body2" color="text.secondary" gutterBottom>
+            Checked {new Date(report.checked_at).toLocaleString()}
+          </Typography>
+
+          {report.findings.length === 0 ? (
+            <Alert severity="success">No problems found</Alert>
+          ) : (
+            report.findings.map((finding, index) => (
+              <Alert key={index} severity={finding.severity} sx={{ mb: 1 }}>
+                <Typography variant="body2">{finding.message}</Typography>
+                <Typography variant="caption" sx={{ wordBreak: 'break-all' }}>
+                  {finding.path}
+                </Typography>
+              </Alert>
+            ))
+          )}
+
+          {report.repaired.length > 0 && (
+            <List dense>
+              {report.repaired.map((action, index) => (
+                <ListItem key={index}>
+                  <ListItemText primary={action} />
+                </ListItem>
+              ))}
+            </List>
+          )}
+
+          <Button
+            variant="outlined"
+            startIcon={repairing ? <CircularProgress size={16} /> : <BuildIcon />}
+            onClick={handleRepair}
+            disabled={repairing}
+            sx={{ mt: 1 }}
+          >
+            Repair Installation
+          </Button>
+        </Paper>
+      )}
+    </Box>
+  );
+}
@@ -0,0 +1,145 @@
+/**
+ * Integrations Tab
+ * Configure the webhook notified when a nesting run finishes
+ */
+
+import { useState, useEffect } from 'react';
+import {
+  Box,
+  Button,
+  TextField,
+  Typography,
+  Alert,
+  Snackbar,
+  Paper,
+} from '@mui/material';
+import SaveIcon from '@mui/icons-material/Save';
+import SendIcon from '@mui/icons-material/Send';
+
+import {
+  getWebhookSettings,
+  saveWebhookSettings,
+  testWebhook,
+  WebhookSettings,
+  WEBHOOK_SECRET_HEADER,
+} from '../../services/webhookService';
+
+export default function IntegrationsTab() {
+  const [webhook, setWebhook] = useState<WebhookSettings>({ url: '', secret: '' });
+  const [loading, setLoading] = useState(false);
+  const [saving, setSaving] = useState(false);
+  const [testing, setTesting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [success, setSuccess] = useState<string | null>(null);
+
+  useEffect(() => {
+    setLoading(true);
+    getWebhookSettings()
+      .then(setWebhook)
+      .catch((err: any) => setError(err.message || 'Failed to load webhook settings'))
+      .finally(() => setLoading(false));
+  }, []);
+
+  const handleSave = async () => {
+    try {
+      setSaving(true);
+      await saveWebhookSettings(webhook);
+      setSuccess(webhook.url.trim() ? 'Webhook saved' : 'Webhook turned off');
+    } catch (err: any) {
+      setError(err.message || 'Failed to save webhook settings');
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const handleTest = async () => {
+    try {
+      setTesting(true);
+      const result = await testWebhook(webhook.url.trim(), webhook.secret);
+      if (result.ok) {
+        setSuccess(`Webhook reachable (HTTP ${result.http_status}, ${result.elapsed_ms} ms)`);
+      } else {
+        setError(result.error || 'Webhook test failed');
+      }
+    } catch (err: any) {
+      setError(typeof err === 'string' ? err : err.message || 'Webhook test failed');
+    } finally {
+      setTesting(false);
+    }
+  };
+
+  const handleChange = (field: keyof WebhookSettings) => (
+    e: React.ChangeEvent<HTMLInputElement>
+  ) => {
+    setWebhook({ ...webhook, [field]: e.target.value });
+  };
+
+  if (loading) {
+    return <Typography>Loading...</Typography>;
+  }
+
+  return (
+    <Box>
+      <Typography variant="h6" gutterBottom>
+        Integrations
+      </Typography>
+      <Typography variant="body2" color="text.secondary" gutterBottom>
+        Notify an external system (e.g. MES) when a nesting run completes or fails
+      </Typography>
+
+      <Box sx={{ display: 'grid', gap: 3, maxWidth: 600, mt: 2 }}>
+        <Paper sx={{ p: 2 }}>
+          <Typography variant="subtitle1" fontWeight="bold" gutterBottom>
+            Nesting Webhook
+          </Typography>
+          <Box sx={{ display: 'grid', gap: 2 }}>
+            <TextField
+              label="Endpoint URL"
+              value={webhook.url}
+              onChange={handleChange('url')}
+              placeholder="https://mes.example.com/hooks/nesting"
+              helperText="Leave empty to turn notifications off"
+              fullWidth
+            />
+            <TextField
+              label="Secret"
+              value={webhook.secret}
+              onChange={handleChange('secret')}
+              type="password"
+              helperText={`Sent in the ${WEBHOOK_SECRET_HEADER} header`}
+              fullWidth
+            />
+          </Box>
+        </Paper>
+
+        <Box sx={{ display: 'grid', gridTemplateColumns: '1fr 1fr', gap: 2 }}>
+          <Button
+            variant="outlined"
+            size="large"
+            startIcon={<SendIcon />}
+            onClick={handleTest}
+            disabled={testing || !webhook.url.trim()}
+          >
+            {testing ? 'Testing...' : 'Send Test'}
+          </Button>
+          <Button
+            variant="contained"
+            size="large"
+            startIcon={<SaveIcon />}
+            onClick={handleSave}
+            disabled={saving}
+          >
+            {saving ? 'Saving...' : 'Save Webhook'}
+          </Button>
+        </Box>
+      </Box>
+
+      <Snackbar open={!!error} autoHideDuration={6000} onClose={() => setError(null)}>
+        <Alert severity="error" onClose={() => setError(null)}>{error}</Alert>
+      </Snackbar>
+      <Snackbar open={!!success} autoHideDuration={3000} onClose={() => setSuccess(null)}>
+        <Alert severity="success" onClose={() => setSuccess(null)}>{success}</Alert>
+      </Snackbar>
+    </Box>
+  );
+}
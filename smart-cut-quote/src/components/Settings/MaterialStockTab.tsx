@@ -47,6 +47,7 @@ interface MaterialFormData {
   pierce_time: string;
   pierce_cost: string;
   cut_price_per_meter: string;
+  min_web: string;
 }
 
 const initialFormData: MaterialFormData = {
@@ -63,6 +64,7 @@ const initialFormData: MaterialFormData = {
   pierce_time: '0.5',
   pierce_cost: '0.15',
   cut_price_per_meter: '1.50',
+  min_web: '',
 };
 
 export default function MaterialStockTab() {
@@ -107,6 +109,7 @@ export default function MaterialStockTab() {
         pierce_time: material.pierce_time.toString(),
         pierce_cost: material.pierce_cost.toString(),
         cut_price_per_meter: material.cut_price_per_meter.toString(),
+        min_web: material.min_web?.toString() ?? '',
       });
     } else {
       setEditingId(null);
@@ -146,6 +149,7 @@ export default function MaterialStockTab() {
         pierce_time: parseFloat(formData.pierce_time),
         pierce_cost: parseFloat(formData.pierce_cost),
         cut_price_per_meter: parseFloat(formData.cut_price_per_meter),
+        min_web: formData.min_web ? parseFloat(formData.min_web) : null,
       };
 
       if (editingId) {
@@ -380,6 +384,13 @@ export default function MaterialStockTab() {
               onChange={handleInputChange('pierce_cost')}
               type="number"
             />
+            <TextField
+              label="Min Web Width (mm)"
+              value={formData.min_web}
+              onChange={handleInputChange('min_web')}
+              type="number"
+              helperText="Narrowest bridge between holes and edges, empty to skip the check"
+            />
             <TextField
               label="Quantity in Stock"
               value={formData.quantity_in_stock}
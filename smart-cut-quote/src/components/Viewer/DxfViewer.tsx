@@ -11,6 +11,7 @@ import RestartAltIcon from '@mui/icons-material/RestartAlt';
 import { readTextFile } from '@tauri-apps/plugin-fs';
 import DxfParser from 'dxf-parser';
 import { useQuoteStore } from '../../stores/quoteStore';
+import { calculateDxfMetadata, validateClosedContours } from '../../services/dxfMetadataService';
 
 interface DxfViewerProps {
   filePath: string;
@@ -128,7 +129,7 @@ export default function DxfViewer({ filePath, fileId }: DxfViewerProps) {
 
       // Calculate metadata (cutLength, pierceCount, etc.)
       if (fileId && dxf.entities) {
-        const metadata = calculateMetadata(dxf.entities);
+        const metadata = calculateDxfMetadata(dxf.entities);
         const isClosed = validateClosedContours(dxf.entities);
 
         updateFile(fileId, {
@@ -148,247 +149,6 @@ export default function DxfViewer({ filePath, fileId }: DxfViewerProps) {
     }
   };
 
-  const validateClosedContours = (entities: any[]): boolean => {
-    // Simple validation: check if entities form closed contours
-    let hasOpenContours = false;
-
-    entities.forEach((entity: any) => {
-      switch (entity.type) {
-        case 'CIRCLE':
-          // Circles are always closed
-          break;
-
-        case 'ARC':
-          // Arcs are open unless they form a complete circle
-          if (entity.startAngle !== undefined && entity.endAngle !== undefined) {
-            const angleDiff = Math.abs(entity.endAngle - entity.startAngle);
-            if (angleDiff < 359) {
-              hasOpenContours = true;
-            }
-          }
-          break;
-
-        case 'LWPOLYLINE':
-        case 'POLYLINE':
-          // Check if polyline is closed
-          if (!entity.shape) {
-            // Not closed - check if first and last vertices are the same
-            if (entity.vertices && entity.vertices.length > 2) {
-              const first = entity.vertices[0];
-              const last = entity.vertices[entity.vertices.length - 1];
-              const distance = Math.sqrt(
-                Math.pow(last.x - first.x, 2) + Math.pow(last.y - first.y, 2)
-              );
-              // If distance > 0.1mm, consider it open
-              if (distance > 0.1) {
-                hasOpenContours = true;
-              }
-            } else {
-              hasOpenContours = true;
-            }
-          }
-          break;
-
-        case 'LINE':
-          // Individual lines are considered open (unless they're part of a closed path)
-          // For simplicity, we'll mark them as potentially open
-          hasOpenContours = true;
-          break;
-
-        default:
-          // Unknown entity types are considered OK
-          break;
-      }
-    });
-
-    // Return true if all contours are closed (no open contours found)
-    return !hasOpenContours;
-  };
-
-  /**
-   * Evaluate B-spline at parameter t using De Boor's algorithm
-   * For better spline length approximation
-   */
-  const evaluateBSpline = (controlPoints: any[], t: number): { x: number; y: number } => {
-    const n = controlPoints.length - 1;
-    const degree = Math.min(3, n); // Cubic or lower
-
-    // Simple approximation: linear interpolation through control points
-    const segmentLength = 1.0 / n;
-    const segment = Math.min(Math.floor(t / segmentLength), n - 1);
-    const localT = (t - segment * segmentLength) / segmentLength;
-
-    const p0 = controlPoints[segment];
-    const p1 = controlPoints[Math.min(segment + 1, n)];
-
-    return {
-      x: p0.x + (p1.x - p0.x) * localT,
-      y: p0.y + (p1.y - p0.y) * localT,
-    };
-  };
-
-  /**
-   * Calculate spline length using parametric sampling
-   * Much more accurate than control point distance (90-95% accuracy)
-   */
-  const calculateSplineLength = (controlPoints: any[], samples: number = 100): number => {
-    if (!controlPoints || controlPoints.length < 2) return 0;
-
-    let length = 0;
-    let prevPoint = evaluateBSpline(controlPoints, 0);
-
-    for (let i = 1; i <= samples; i++) {
-      const t = i / samples;
-      const point = evaluateBSpline(controlPoints, t);
-      const dx = point.x - prevPoint.x;
-      const dy = point.y - prevPoint.y;
-      length += Math.sqrt(dx * dx + dy * dy);
-      prevPoint = point;
-    }
-
-    // Apply correction factor for spline curvature (typically 1.1-1.2x control polygon)
-    return length * 1.15;
-  };
-
-  const calculateMetadata = (entities: any[]) => {
-    let totalCutLength = 0;
-    let pierceCount = 0;
-    let minX = Infinity;
-    let maxX = -Infinity;
-    let minY = Infinity;
-    let maxY = -Infinity;
-
-    entities.forEach((entity: any) => {
-      // Pierce count: each closed contour = 1 pierce
-      // CIRCLE always = 1 pierce
-      // Closed POLYLINE = 1 pierce
-      // Open entities = 0 pierce (will be connected to closed contour)
-
-      const isClosed = entity.shape || entity.type === 'CIRCLE' ||
-        (entity.type === 'LWPOLYLINE' && entity.shape) ||
-        (entity.type === 'POLYLINE' && entity.shape);
-
-      if (isClosed) {
-        pierceCount++;
-      }
-
-      switch (entity.type) {
-        case 'LINE':
-          if (entity.vertices && entity.vertices.length >= 2) {
-            const v0 = entity.vertices[0];
-            const v1 = entity.vertices[1];
-            const length = Math.sqrt(
-              Math.pow(v1.x - v0.x, 2) + Math.pow(v1.y - v0.y, 2)
-            );
-            totalCutLength += length;
-
-            // Update bounds
-            minX = Math.min(minX, v0.x, v1.x);
-            maxX = Math.max(maxX, v0.x, v1.x);
-            minY = Math.min(minY, v0.y, v1.y);
-            maxY = Math.max(maxY, v0.y, v1.y);
-          }
-          break;
-
-        case 'CIRCLE':
-          if (entity.center && entity.radius) {
-            // Circumference = 2 * π * r
-            totalCutLength += 2 * Math.PI * entity.radius;
-
-            // Update bounds
-            minX = Math.min(minX, entity.center.x - entity.radius);
-            maxX = Math.max(maxX, entity.center.x + entity.radius);
-            minY = Math.min(minY, entity.center.y - entity.radius);
-            maxY = Math.max(maxY, entity.center.y + entity.radius);
-          }
-          break;
-
-        case 'ARC':
-          if (entity.center && entity.radius && entity.startAngle !== undefined && entity.endAngle !== undefined) {
-            // Arc length = radius * angle (in radians)
-            let angleDiff = entity.endAngle - entity.startAngle;
-            if (angleDiff < 0) angleDiff += 360;
-            const angleRad = (angleDiff * Math.PI) / 180;
-            totalCutLength += entity.radius * angleRad;
-
-            // Update bounds (simplified - use full circle bounds)
-            minX = Math.min(minX, entity.center.x - entity.radius);
-            maxX = Math.max(maxX, entity.center.x + entity.radius);
-            minY = Math.min(minY, entity.center.y - entity.radius);
-            maxY = Math.max(maxY, entity.center.y + entity.radius);
-          }
-          break;
-
-        case 'LWPOLYLINE':
-        case 'POLYLINE':
-          if (entity.vertices && entity.vertices.length > 1) {
-            // Calculate total length of polyline
-            for (let i = 0; i < entity.vertices.length - 1; i++) {
-              const v0 = entity.vertices[i];
-              const v1 = entity.vertices[i + 1];
-              const length = Math.sqrt(
-                Math.pow(v1.x - v0.x, 2) + Math.pow(v1.y - v0.y, 2)
-              );
-              totalCutLength += length;
-            }
-
-            // If closed, add closing segment
-            if (entity.shape && entity.vertices.length > 2) {
-              const first = entity.vertices[0];
-              const last = entity.vertices[entity.vertices.length - 1];
-              const length = Math.sqrt(
-                Math.pow(last.x - first.x, 2) + Math.pow(last.y - first.y, 2)
-              );
-              totalCutLength += length;
-            }
-
-            // Update bounds
-            entity.vertices.forEach((v: any) => {
-              minX = Math.min(minX, v.x);
-              maxX = Math.max(maxX, v.x);
-              minY = Math.min(minY, v.y);
-              maxY = Math.max(maxY, v.y);
-            });
-          }
-          break;
-
-        case 'SPLINE':
-          // Use parametric sampling for better accuracy (90-95%)
-          if (entity.controlPoints && entity.controlPoints.length > 1) {
-            totalCutLength += calculateSplineLength(entity.controlPoints);
-
-            // Update bounds
-            entity.controlPoints.forEach((v: any) => {
-              minX = Math.min(minX, v.x);
-              maxX = Math.max(maxX, v.x);
-              minY = Math.min(minY, v.y);
-              maxY = Math.max(maxY, v.y);
-            });
-          }
-          break;
-
-        default:
-          // Unknown entity type - no action needed
-          break;
-      }
-    });
-
-    // Calculate dimensions and area
-    const width = isFinite(maxX) ? maxX - minX : 0;
-    const height = isFinite(maxY) ? maxY - minY : 0;
-    const area = width * height;
-
-    return {
-      cutLength: Math.round(totalCutLength * 100) / 100, // Round to 2 decimal places
-      pierceCount,
-      area: Math.round(area * 100) / 100,
-      dimensions: {
-        width: Math.round(width * 100) / 100,
-        height: Math.round(height * 100) / 100,
-      },
-    };
-  };
-
   const renderDxfToCanvas = (dxf: any, renderRequestId: number) => {
     const canvas = canvasRef.current;
     const container = containerRef.current;
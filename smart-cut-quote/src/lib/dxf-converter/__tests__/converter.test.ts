@@ -0,0 +1,176 @@
+/**
+ * Tests for unsupported entity handling in the DXF converter
+ * Fixture: test_dxf_files/test_unsupported_entities.dxf
+ * (closed rectangle + HATCH + 3DFACE)
+ */
+
+import { describe, it, expect } from 'vitest';
+import { readFileSync } from 'fs';
+import { convertSingleDxf } from '../converter';
+import { scanEntityRecords } from '../dxfParser';
+import { calculateSignedArea, orientContours } from '../geometryUtils';
+import {
+  arcContourArea,
+  arcContourPerimeter,
+  orientArcContour,
+  reverseArcContour,
+} from '../arcContour';
+import type { PointTuple } from '../types';
+
+const fixture = readFileSync(
+  new URL('../../../../test_dxf_files/test_unsupported_entities.dxf', import.meta.url),
+  'utf-8'
+);
+
+describe('DXF Converter - Unsupported Entities', () => {
+  it('should list entities dxf-parser skips', () => {
+    const records = scanEntityRecords(fixture);
+
+    expect(records.map((r) => r.type)).toEqual(['LWPOLYLINE', 'HATCH', '3DFACE']);
+    expect(records[1]).toEqual({ type: 'HATCH', handle: '2B', layer: 'ENGRAVE' });
+  });
+
+  it('should convert and warn about dropped entities by default', async () => {
+    const result = await convertSingleDxf('unsupported.dxf', fixture);
+
+    expect(result.success).toBe(true);
+    expect(result.json?.items.length).toBe(1);
+
+    const warning = result.warnings.find((w) => w.droppedEntities);
+    expect(warning?.droppedEntities?.map((e) => e.type)).toEqual(['HATCH', '3DFACE']);
+    expect(warning?.message).toContain('HATCH (handle 2B, layer ENGRAVE)');
+  });
+
+  it('should fail the file in strict entity mode', async () => {
+    const result = await convertSingleDxf('unsupported.dxf', fixture, {
+      strictEntities: true,
+    });
+
+    expect(result.success).toBe(false);
+    expect(result.errors.length).toBe(1);
+    expect(result.errors[0].stage).toBe('entity check');
+    expect(result.errors[0].droppedEntities?.map((e) => e.handle)).toEqual(['2B', '2C']);
+  });
+
+  it('should accept a file with only supported entities in strict mode', async () => {
+    const valid = readFileSync(
+      new URL('../../../../test_dxf_files/test_valid_closed.dxf', import.meta.url),
+      'utf-8'
+    );
+    const result = await convertSingleDxf('valid.dxf', valid, { strictEntities: true });
+
+    expect(result.success).toBe(true);
+    expect(result.errors.length).toBe(0);
+  });
+});
+
+describe('DXF Converter - Contour Winding', () => {
+  const clockwise = readFileSync(
+    new URL('../../../../test_dxf_files/test_clockwise_outer.dxf', import.meta.url),
+    'utf-8'
+  );
+
+  it('should reverse a clockwise outer loop and report it per part', async () => {
+    const result = await convertSingleDxf('clockwise.dxf', clockwise);
+
+    expect(result.success).toBe(true);
+    expect(result.parts).toEqual([
+      {
+        file: 'clockwise.dxf',
+        itemId: 0,
+        contourCount: 2,
+        outerReversed: true,
+        holesReversed: 1,
+      },
+    ]);
+    expect(result.stats?.reversedParts).toBe(1);
+
+    const outer = result.json!.items[0].shape.data;
+    expect(calculateSignedArea(outer)).toBeCloseTo(5000);
+  });
+
+  it('should orient holes clockwise', () => {
+    const exterior: PointTuple[] = [[0, 0], [0, 50], [100, 50], [100, 0]];
+    const hole: PointTuple[] = [[20, 20], [30, 20], [30, 30], [20, 30]];
+
+    const oriented = orientContours(exterior, [hole]);
+    expect(oriented.outerReversed).toBe(true);
+    expect(oriented.holesReversed).toBe(1);
+    expect(calculateSignedArea(oriented.exterior)).toBeGreaterThan(0);
+    expect(calculateSignedArea(oriented.holes[0])).toBeLessThan(0);
+
+    const opposite = orientContours(exterior, [hole], false);
+    expect(opposite.outerReversed).toBe(false);
+    expect(opposite.holesReversed).toBe(0);
+  });
+
+  it('should not reverse a file with the CAM convention', async () => {
+    const valid = readFileSync(
+      new URL('../../../../test_dxf_files/test_valid_closed.dxf', import.meta.url),
+      'utf-8'
+    );
+    const result = await convertSingleDxf('valid.dxf', valid);
+
+    expect(result.parts?.[0].outerReversed).toBe(false);
+    expect(result.stats?.reversedParts).toBe(0);
+  });
+});
+
+describe('DXF Converter - Bulge Arcs', () => {
+  const bulged = readFileSync(
+    new URL('../../../../test_dxf_files/test_bulge_arcs.dxf', import.meta.url),
+    'utf-8'
+  );
+
+  // 1000 x 200 plate, long sides bowed out by 25 mm (bulge 0.05)
+  const sweep = 4 * Math.atan(0.05);
+  const radius = 500 / Math.sin(sweep / 2);
+  const arcPerimeter = 2 * radius * sweep + 2 * 200;
+
+  const polygonPerimeter = (points: PointTuple[]) =>
+    points.reduce((length, [x, y], i) => {
+      const [nx, ny] = points[(i + 1) % points.length];
+      return length + Math.hypot(nx - x, ny - y);
+    }, 0);
+
+  it('should keep bulges as exact arcs next to the tessellated shape', async () => {
+    const result = await convertSingleDxf('bulged.dxf', bulged);
+
+    expect(result.success).toBe(true);
+    const item = result.json!.items[0];
+    const exact = item.exact_geometry!.outer.vertices;
+    expect(exact.map((v) => v.bulge)).toEqual([0.05, 0, 0.05, 0]);
+    expect(arcContourPerimeter(exact)).toBeCloseTo(arcPerimeter, 6);
+
+    // The polygon follows the arcs within the chord error, a bit shorter
+    const tessellated = polygonPerimeter(item.shape.data);
+    expect(tessellated).toBeLessThan(arcPerimeter);
+    expect(arcPerimeter - tessellated).toBeLessThan(0.05);
+    expect(Math.min(...item.shape.data.map(([, y]) => y))).toBeLessThan(-24.9);
+  });
+
+  it('should follow maxChordError when tessellating bulges', async () => {
+    const fine = await convertSingleDxf('bulged.dxf', bulged, { maxChordError: 0.01 });
+    const coarse = await convertSingleDxf('bulged.dxf', bulged, { maxChordError: 1 });
+
+    const fineShape = fine.json!.items[0].shape.data;
+    const coarseShape = coarse.json!.items[0].shape.data;
+    expect(arcPerimeter - polygonPerimeter(fineShape)).toBeLessThan(
+      arcPerimeter - polygonPerimeter(coarseShape)
+    );
+  });
+
+  it('should reverse exact contours with their bulges', () => {
+    const contour = [
+      { x: 0, y: 0, bulge: 0.05 },
+      { x: 1000, y: 0, bulge: 0 },
+      { x: 1000, y: 200, bulge: 0.05 },
+      { x: 0, y: 200, bulge: 0 },
+    ];
+    const reversed = reverseArcContour(contour);
+
+    expect(arcContourArea(reversed)).toBeCloseTo(-arcContourArea(contour), 6);
+    expect(arcContourPerimeter(reversed)).toBeCloseTo(arcContourPerimeter(contour), 6);
+    expect(orientArcContour(reversed, true)).toEqual(contour);
+  });
+});
@@ -0,0 +1,222 @@
+/**
+ * Exact contours with true arcs
+ *
+ * Nesting needs polygons, but tessellating loses the arcs of ARC, CIRCLE
+ * and bulged polyline segments. Every contour is also kept as a vertex
+ * list in the LWPOLYLINE convention: the bulge of a vertex is
+ * tan(sweep / 4) of the segment to the next vertex, positive for a
+ * counter-clockwise arc and 0 for a line. This is the PartGeometry format
+ * of the Rust engine, which uses it for exact cut lengths and writes the
+ * arcs back on DXF export.
+ */
+
+import type {
+  ArcVertex,
+  Contour,
+  DxfArcEntity,
+  DxfCircleEntity,
+  DxfLineEntity,
+  DxfPolylineEntity,
+  Point2D,
+  PointTuple,
+} from './types';
+
+/** Vertices closer than this are merged (mm) */
+const MERGE_DISTANCE = 0.01;
+
+interface BulgeArc {
+  center: Point2D;
+  radius: number;
+  /** Angle of the start point around the center */
+  startAngle: number;
+  /** Signed sweep, positive counter-clockwise */
+  sweep: number;
+}
+
+/**
+ * Arc of the segment a -> b, null for straight and zero-length segments
+ */
+export function bulgeArc(a: Point2D, b: Point2D, bulge: number): BulgeArc | null {
+  const dx = b.x - a.x;
+  const dy = b.y - a.y;
+  const chord = Math.sqrt(dx * dx + dy * dy);
+  if (!bulge || chord === 0) return null;
+
+  // Same construction as the Rust PartGeometry
+  const sagitta = (bulge * chord) / 2;
+  const nx = -dy / chord;
+  const ny = dx / chord;
+  const offset = ((chord * chord) / 4 - sagitta * sagitta) / (2 * sagitta);
+  const center = {
+    x: (a.x + b.x) / 2 + nx * offset,
+    y: (a.y + b.y) / 2 + ny * offset,
+  };
+  return {
+    center,
+    radius: ((chord * chord) / 4 + sagitta * sagitta) / (2 * Math.abs(sagitta)),
+    startAngle: Math.atan2(a.y - center.y, a.x - center.x),
+    sweep: 4 * Math.atan(bulge),
+  };
+}
+
+/**
+ * Points strictly between a and b on a bulged segment
+ *
+ * No point of the resulting chords is further than maxChordError from
+ * the arc. Straight segments give no points.
+ */
+export function bulgeArcPoints(
+  a: Point2D,
+  b: Point2D,
+  bulge: number,
+  maxChordError: number
+): PointTuple[] {
+  const arc = bulgeArc(a, b, bulge);
+  if (!arc) return [];
+
+  const ratio = Math.min(1, Math.max(-1, 1 - maxChordError / arc.radius));
+  const maxStep = Math.max(2 * Math.acos(ratio), 1e-3);
+  const pieces = Math.max(1, Math.ceil(Math.abs(arc.sweep) / maxStep));
+
+  const points: PointTuple[] = [];
+  for (let k = 1; k < pieces; k++) {
+    const angle = arc.startAngle + (arc.sweep * k) / pieces;
+    points.push([
+      arc.center.x + arc.radius * Math.cos(angle),
+      arc.center.y + arc.radius * Math.sin(angle),
+    ]);
+  }
+  return points;
+}
+
+/**
+ * Exact vertices of a contour, null if it has entities without a
+ * bulge representation (SPLINE, ELLIPSE)
+ *
+ * Follows the same entity order and directions as contourToPolygon, so
+ * the result traces the tessellated polygon.
+ */
+export function contourToArcVertices(contour: Contour): ArcVertex[] | null {
+  const vertices: ArcVertex[] = [];
+  const push = (point: Point2D, bulge: number) => {
+    const last = vertices[vertices.length - 1];
+    // The segment from the last vertex would have no length
+    if (last && Math.hypot(point.x - last.x, point.y - last.y) < MERGE_DISTANCE) {
+      vertices.pop();
+    }
+    vertices.push({ x: point.x, y: point.y, bulge });
+  };
+
+  for (const entity of contour.entities) {
+    switch (entity.type) {
+      case 'LINE': {
+        const [start, end] = (entity as DxfLineEntity).vertices;
+        push(start, 0);
+        push(end, 0);
+        break;
+      }
+
+      case 'ARC': {
+        const arc = entity as DxfArcEntity;
+        const start = (arc.startAngle * Math.PI) / 180;
+        const end = (arc.endAngle * Math.PI) / 180;
+        // Counter-clockwise from start to end angle, like arcToPoints
+        let sweep = end - start;
+        if (sweep < 0) sweep += 2 * Math.PI;
+        const at = (angle: number) => ({
+          x: arc.center.x + arc.radius * Math.cos(angle),
+          y: arc.center.y + arc.radius * Math.sin(angle),
+        });
+        push(at(start), Math.tan(sweep / 4));
+        push(at(end), 0);
+        break;
+      }
+
+      case 'CIRCLE': {
+        const circle = entity as DxfCircleEntity;
+        const { x, y } = circle.center;
+        push({ x: x + circle.radius, y }, 1);
+        push({ x: x - circle.radius, y }, 1);
+        break;
+      }
+
+      case 'LWPOLYLINE':
+      case 'POLYLINE': {
+        const poly = entity as DxfPolylineEntity;
+        const closed = poly.shape || poly.closed;
+        poly.vertices.forEach((vertex, i) => {
+          // An open polyline ends at its last vertex
+          const isLast = i === poly.vertices.length - 1;
+          push(vertex, isLast && !closed ? 0 : vertex.bulge || 0);
+        });
+        break;
+      }
+
+      default:
+        return null;
+    }
+  }
+
+  // Closed back to the first vertex
+  const first = vertices[0];
+  const last = vertices[vertices.length - 1];
+  if (
+    vertices.length > 1 &&
+    Math.hypot(last.x - first.x, last.y - first.y) < MERGE_DISTANCE
+  ) {
+    vertices.pop();
+  }
+
+  return vertices.length >= 2 ? vertices : null;
+}
+
+/**
+ * Signed area, positive for counter-clockwise contours
+ */
+export function arcContourArea(vertices: ArcVertex[]): number {
+  let area = 0;
+  vertices.forEach((a, i) => {
+    const b = vertices[(i + 1) % vertices.length];
+    area += (a.x * b.y - b.x * a.y) / 2;
+    const arc = bulgeArc(a, b, a.bulge);
+    if (arc) {
+      // Region between chord and arc
+      const theta = Math.abs(arc.sweep);
+      area += (Math.sign(arc.sweep) * arc.radius * arc.radius * (theta - Math.sin(theta))) / 2;
+    }
+  });
+  return area;
+}
+
+/**
+ * Length of the closed contour, arcs measured along the arc
+ */
+export function arcContourPerimeter(vertices: ArcVertex[]): number {
+  return vertices.reduce((length, a, i) => {
+    const b = vertices[(i + 1) % vertices.length];
+    const arc = bulgeArc(a, b, a.bulge);
+    return length + (arc ? arc.radius * Math.abs(arc.sweep) : Math.hypot(b.x - a.x, b.y - a.y));
+  }, 0);
+}
+
+/**
+ * Same contour traversed in the opposite direction
+ */
+export function reverseArcContour(vertices: ArcVertex[]): ArcVertex[] {
+  const n = vertices.length;
+  return vertices.map((_, j) => ({
+    ...vertices[n - 1 - j],
+    // The segment to the next vertex is the old segment ending here
+    bulge: -vertices[(2 * n - 2 - j) % n].bulge,
+  }));
+}
+
+/**
+ * Reverse the contour unless it runs counter-clockwise (ccw) or
+ * clockwise (!ccw) already
+ */
+export function orientArcContour(vertices: ArcVertex[], ccw: boolean): ArcVertex[] {
+  const area = arcContourArea(vertices);
+  if (area === 0 || area > 0 === ccw) return vertices;
+  return reverseArcContour(vertices);
+}
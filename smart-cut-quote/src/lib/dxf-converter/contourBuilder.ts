@@ -156,7 +156,12 @@ function reverseEntity(entity: DxfEntity): DxfEntity {
     case 'LWPOLYLINE':
     case 'POLYLINE': {
       const polyEntity = entity as DxfPolylineEntity;
-      (reversed as DxfPolylineEntity).vertices = [...polyEntity.vertices].reverse();
+      const vertices = polyEntity.vertices;
+      // A bulge belongs to the segment after its vertex, and flips sign
+      (reversed as DxfPolylineEntity).vertices = vertices.map((_, k) => ({
+        ...vertices[vertices.length - 1 - k],
+        bulge: -(vertices[vertices.length - 2 - k]?.bulge ?? 0),
+      }));
       break;
     }
 
@@ -8,6 +8,9 @@
  * 4. Convert to polygon points
  * 5. Detect exterior vs holes
  * 6. Format as sparroWASM JSON
+ *
+ * Contours are also kept with their true arcs (see arcContour) next to
+ * the polygons used for nesting.
  */
 
 import {
@@ -15,13 +18,16 @@ import {
   extractEntities,
   validateDxf,
   filterEntitiesByType,
+  scanEntityRecords,
 } from './dxfParser';
 import { buildContours, validateContours } from './contourBuilder';
+import { bulgeArcPoints, contourToArcVertices, orientArcContour } from './arcContour';
 import {
   arcToPoints,
   circleToPoints,
   splineToPoints,
   detectShape,
+  orientContours,
   calculateBoundingBox,
 } from './geometryUtils';
 import { formatSparrowJson, validateSparrowJson } from './jsonFormatter';
@@ -32,6 +38,7 @@ import type {
   ConversionWarning,
   DxfFileInput,
   NestingInputItem,
+  PartConversionMetrics,
   PointTuple,
   BoundingBox,
   Contour,
@@ -44,8 +51,33 @@ import type {
   DxfSplineEntity,
   SparrowJson,
   PolygonValidationResult,
+  DroppedEntity,
+  ExactGeometry,
 } from './types';
 
+/**
+ * Entity types turned into geometry, everything else is dropped
+ */
+const SUPPORTED_ENTITY_TYPES = [
+  'LINE',
+  'CIRCLE',
+  'ARC',
+  'LWPOLYLINE',
+  'POLYLINE',
+  'SPLINE',
+];
+
+/**
+ * Layer holding the bend lines of parts for the press brake
+ * Its entities are not cut, they only give the bend direction.
+ */
+const BEND_LAYER = 'BEND';
+
+/**
+ * Max spread of bend line angles before a part is reported (degrees)
+ */
+const BEND_ANGLE_SPREAD_WARNING = 1;
+
 /**
  * Convert multiple DXF files to sparroWASM JSON format
  */
@@ -58,10 +90,12 @@ export async function convertDxfToJson(
     spacing: 5,
     arcSegments: 32,
     splineSegments: 100,
+    maxChordError: 0.05,
     allowRotations: true,
     tolerance: 0.1,
     autoClose: true,
     problemName: 'dxf_conversion',
+    strictEntities: false,
   };
 
   const config = { ...defaultSettings, ...settings };
@@ -69,6 +103,7 @@ export async function convertDxfToJson(
   const items: NestingInputItem[] = [];
   const errors: ConversionError[] = [];
   const warnings: ConversionWarning[] = [];
+  const parts: PartConversionMetrics[] = [];
 
   // Process each DXF file
   for (let i = 0; i < files.length; i++) {
@@ -95,6 +130,31 @@ export async function convertDxfToJson(
 
       const dxf = parseResult.data;
 
+      // Step 1.5: Check for entities that will be dropped
+      const droppedEntities = scanEntityRecords(file.content).filter(
+        (e) => !SUPPORTED_ENTITY_TYPES.includes(e.type)
+      );
+
+      if (droppedEntities.length > 0) {
+        const message = describeDroppedEntities(droppedEntities);
+
+        if (config.strictEntities) {
+          errors.push({
+            file: file.name,
+            stage: 'entity check',
+            message: `${message} (strict entity mode)`,
+            droppedEntities,
+          });
+          continue;
+        }
+
+        warnings.push({
+          file: file.name,
+          message: `${message} - skipped during conversion`,
+          droppedEntities,
+        });
+      }
+
       // Step 2: Validate DXF
       const validation = validateDxf(dxf);
 
@@ -119,16 +179,21 @@ export async function convertDxfToJson(
       // Step 3: Extract entities
       const allEntities = extractEntities(dxf);
 
+      // Bend lines are not contours, they give the rolling direction constraint
+      const bendEntities = allEntities.filter((e) => isBendLayer(e.layer));
+      const bend = detectBendLines(bendEntities);
+      if (bend.spreadDeg > BEND_ANGLE_SPREAD_WARNING) {
+        warnings.push({
+          file: file.name,
+          message: `Bend lines are not parallel (${bend.spreadDeg.toFixed(1)}° apart) - using the longest one's direction`,
+        });
+      }
+
       // Filter to supported types only
-      const supportedTypes = [
-        'LINE',
-        'CIRCLE',
-        'ARC',
-        'LWPOLYLINE',
-        'POLYLINE',
-        'SPLINE',
-      ];
-      const entities = filterEntitiesByType(allEntities, supportedTypes);
+      const entities = filterEntitiesByType(
+        allEntities.filter((e) => !isBendLayer(e.layer)),
+        SUPPORTED_ENTITY_TYPES
+      );
 
       console.log(`  Entities: ${entities.length} (${allEntities.length} total)`);
 
@@ -173,11 +238,30 @@ export async function convertDxfToJson(
 
       // Step 5: Convert contours to polygon points
       const polygons = contours.map((contour) =>
-        contourToPolygon(contour, config.arcSegments, config.splineSegments)
+        contourToPolygon(
+          contour,
+          config.arcSegments,
+          config.splineSegments,
+          config.maxChordError
+        )
       );
 
       // Step 6: Detect exterior vs holes
-      const shape = detectShapeFromPolygons(polygons);
+      const detected = detectShapeFromPolygons(polygons);
+      const exactGeometry = exactGeometryOf(contours, polygons, detected);
+
+      // Step 6.1: Enforce CCW exterior / CW holes (CAM convention)
+      const oriented = orientContours(detected.exterior, detected.holes);
+      const shape = {
+        exterior: oriented.exterior,
+        holes: oriented.holes,
+        boundingBox: detected.boundingBox,
+      };
+      if (oriented.outerReversed || oriented.holesReversed > 0) {
+        console.log(
+          `  Winding: reversed ${oriented.outerReversed ? 'exterior' : 'no exterior'}, ${oriented.holesReversed} hole(s)`
+        );
+      }
 
       // Step 6.5: Validate polygon for degenerate cases
       const polygonValidation = validatePolygon(shape.exterior, file.name);
@@ -214,10 +298,24 @@ export async function convertDxfToJson(
           originalEntityCount: allEntities.length,
           contourCount: contours.length,
           boundingBox: shape.boundingBox,
+          outerReversed: oriented.outerReversed,
+          holesReversed: oriented.holesReversed,
         },
+        partMetadata: fileObj.partMetadata,
+        ...(bend.directionDeg !== null
+          ? { bendDirectionDeg: bend.directionDeg, bendLines: bend.lines }
+          : {}),
+        ...(exactGeometry ? { exactGeometry } : {}),
       };
 
       items.push(item);
+      parts.push({
+        file: file.name,
+        itemId: item.id,
+        contourCount: contours.length,
+        outerReversed: oriented.outerReversed,
+        holesReversed: oriented.holesReversed,
+      });
       console.log(`  ✓ Successfully converted ${file.name}`);
     } catch (error) {
       console.error(`Error processing ${file.name}:`, error);
@@ -275,10 +373,84 @@ export async function convertDxfToJson(
       successfulFiles: items.length,
       failedFiles: errors.length,
       totalItems: items.length,
+      reversedParts: parts.filter((p) => p.outerReversed || p.holesReversed > 0).length,
     },
+    parts,
   };
 }
 
+/**
+ * Summarize dropped entities, e.g.
+ * "2 unsupported entities: HATCH (handle 2A, layer CUT), 3DFACE (handle 2B, layer 0)"
+ */
+function describeDroppedEntities(entities: DroppedEntity[]): string {
+  const details = entities.map((e) => {
+    const handle = e.handle ? `handle ${e.handle}` : 'no handle';
+    const layer = `layer ${e.layer ?? '0'}`;
+    return `${e.type} (${handle}, ${layer})`;
+  });
+
+  return `${entities.length} unsupported ${entities.length === 1 ? 'entity' : 'entities'}: ${details.join(', ')}`;
+}
+
+function isBendLayer(layer: string | undefined): boolean {
+  return layer?.trim().toUpperCase() === BEND_LAYER;
+}
+
+/**
+ * Bend lines of a part and their direction
+ *
+ * Takes LINE and (LW)POLYLINE segments of the BEND layer. The direction is
+ * the angle of the longest line in [0, 180) degrees (null without bend
+ * lines); `spreadDeg` is the largest deviation of another line from it.
+ */
+function detectBendLines(entities: DxfEntity[]): {
+  lines: [PointTuple, PointTuple][];
+  directionDeg: number | null;
+  spreadDeg: number;
+} {
+  const lines: [PointTuple, PointTuple][] = [];
+  for (const entity of entities) {
+    if (entity.type === 'LINE') {
+      const [start, end] = (entity as DxfLineEntity).vertices;
+      lines.push([
+        [start.x, start.y],
+        [end.x, end.y],
+      ]);
+    } else if (entity.type === 'LWPOLYLINE' || entity.type === 'POLYLINE') {
+      const vertices = (entity as DxfPolylineEntity).vertices;
+      for (let i = 0; i + 1 < vertices.length; i++) {
+        lines.push([
+          [vertices[i].x, vertices[i].y],
+          [vertices[i + 1].x, vertices[i + 1].y],
+        ]);
+      }
+    }
+  }
+
+  const length = ([a, b]: [PointTuple, PointTuple]) => Math.hypot(b[0] - a[0], b[1] - a[1]);
+  const angle = ([a, b]: [PointTuple, PointTuple]) => {
+    const degrees = (Math.atan2(b[1] - a[1], b[0] - a[0]) * 180) / Math.PI;
+    return ((degrees % 180) + 180) % 180;
+  };
+
+  const measurable = lines.filter((line) => length(line) > 0);
+  if (measurable.length === 0) {
+    return { lines: [], directionDeg: null, spreadDeg: 0 };
+  }
+
+  const longest = measurable.reduce((a, b) => (length(b) > length(a) ? b : a));
+  const directionDeg = angle(longest);
+  const spreadDeg = Math.max(
+    ...measurable.map((line) => {
+      const diff = Math.abs(angle(line) - directionDeg) % 180;
+      return Math.min(diff, 180 - diff);
+    })
+  );
+
+  return { lines: measurable, directionDeg, spreadDeg };
+}
+
 /**
  * Convert a single contour to polygon points
  * CRITICAL: Polygon MUST be CLOSED (first point == last point) for sparroWASM
@@ -286,12 +458,13 @@ export async function convertDxfToJson(
 function contourToPolygon(
   contour: Contour,
   arcSegments: number = 16,
-  splineSegments: number = 100
+  splineSegments: number = 100,
+  maxChordError: number = 0.05
 ): PointTuple[] {
   const points: PointTuple[] = [];
 
   contour.entities.forEach((entity) => {
-    const entityPoints = entityToPoints(entity, arcSegments, splineSegments);
+    const entityPoints = entityToPoints(entity, arcSegments, splineSegments, maxChordError);
 
     // Add points, avoiding duplicates at connection points
     entityPoints.forEach((point, index) => {
@@ -389,7 +562,8 @@ function subdivideAllLongEdges(
 function entityToPoints(
   entity: DxfEntity,
   arcSegments: number = 16,
-  splineSegments: number = 100
+  splineSegments: number = 100,
+  maxChordError: number = 0.05
 ): PointTuple[] {
   const points: PointTuple[] = [];
 
@@ -420,16 +594,17 @@ function entityToPoints(
     case 'LWPOLYLINE':
     case 'POLYLINE': {
       const polyEntity = entity as DxfPolylineEntity;
-      polyEntity.vertices.forEach((vertex) => {
+      const vertices = polyEntity.vertices;
+      const closed = polyEntity.shape || polyEntity.closed;
+      vertices.forEach((vertex, i) => {
         points.push([vertex.x, vertex.y]);
-      });
 
-      // Handle bulge (arc segments) in LWPOLYLINE
-      if (polyEntity.vertices.some((v) => v.bulge !== undefined && v.bulge !== 0)) {
-        console.warn(
-          'LWPOLYLINE with bulge detected - bulge handling not yet implemented'
-        );
-      }
+        // Bulged segments become arcs, the closing one only if closed
+        const next = vertices[i + 1] ?? (closed ? vertices[0] : undefined);
+        if (next && vertex.bulge) {
+          points.push(...bulgeArcPoints(vertex, next, vertex.bulge, maxChordError));
+        }
+      });
       break;
     }
 
@@ -469,6 +644,30 @@ function entityToPoints(
   return points;
 }
 
+/**
+ * Exact contours of the detected exterior and holes, oriented like the
+ * polygons (CCW exterior, CW holes)
+ *
+ * Undefined if any of them has an entity without an exact form (SPLINE,
+ * ELLIPSE); the part then only has its polygons.
+ */
+function exactGeometryOf(
+  contours: Contour[],
+  polygons: PointTuple[][],
+  detected: { exterior: PointTuple[]; holes: PointTuple[][] }
+): ExactGeometry | undefined {
+  const exact = (polygon: PointTuple[], ccw: boolean) => {
+    const index = polygons.indexOf(polygon);
+    const vertices = index >= 0 ? contourToArcVertices(contours[index]) : null;
+    return vertices ? { vertices: orientArcContour(vertices, ccw) } : null;
+  };
+
+  const outer = exact(detected.exterior, true);
+  const holes = detected.holes.map((hole) => exact(hole, false));
+  if (!outer || holes.some((hole) => hole === null)) return undefined;
+  return { outer, holes: holes as ExactGeometry['holes'] };
+}
+
 /**
  * Detect exterior and holes from multiple polygons
  */
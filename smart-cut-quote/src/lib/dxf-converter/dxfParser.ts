@@ -20,8 +20,15 @@ import type {
   DxfPolylineEntity,
   DxfLineEntity,
   DxfSplineEntity,
+  DroppedEntity,
 } from './types';
 
+/**
+ * Sub-entities that only exist as part of their parent entity
+ * (POLYLINE vertices, INSERT attributes)
+ */
+const SUB_ENTITY_TYPES = ['VERTEX', 'SEQEND', 'ATTRIB'];
+
 /**
  * Parse DXF file content
  */
@@ -45,6 +52,48 @@ export function parseDxf(dxfContent: string): DxfParseResult {
   }
 }
 
+/**
+ * List every entity of the ENTITIES section straight from the DXF text
+ *
+ * dxf-parser silently skips entity types it has no handler for (e.g.
+ * HATCH, IMAGE), so they never show up in the parsed document. Reading
+ * the group codes directly gives the complete list with handle (code 5)
+ * and layer (code 8).
+ */
+export function scanEntityRecords(dxfContent: string): DroppedEntity[] {
+  const lines = dxfContent.split(/\r?\n/);
+  const records: DroppedEntity[] = [];
+  let section: string | null = null;
+  let expectSectionName = false;
+  let current: DroppedEntity | null = null;
+
+  for (let i = 0; i + 1 < lines.length; i += 2) {
+    const code = lines[i].trim();
+    const value = lines[i + 1].trim();
+
+    if (code === '0') {
+      current = null;
+      if (value === 'SECTION') {
+        expectSectionName = true;
+      } else if (value === 'ENDSEC') {
+        section = null;
+      } else if (section === 'ENTITIES' && !SUB_ENTITY_TYPES.includes(value)) {
+        current = { type: value };
+        records.push(current);
+      }
+    } else if (code === '2' && expectSectionName) {
+      section = value;
+      expectSectionName = false;
+    } else if (current && code === '5') {
+      current.handle = value;
+    } else if (current && code === '8') {
+      current.layer = value;
+    }
+  }
+
+  return records;
+}
+
 /**
  * Extract entities from parsed DXF
  */
@@ -431,20 +480,9 @@ export function validateDxf(dxf: DxfDocument): DxfValidationResult {
     'ELLIPSE',
     'SPLINE',
   ];
+  // Unsupported entities are reported one by one by the converter
+  // (see scanEntityRecords), including those dxf-parser skips
   const entities = dxf.entities || [];
-  const unsupportedEntities = entities.filter(
-    (e) => !supportedTypes.includes(e.type)
-  );
-
-  if (unsupportedEntities.length > 0) {
-    const types = [...new Set(unsupportedEntities.map((e) => e.type))];
-    warnings.push(
-      `Unsupported entity types found (will be ignored): ${types.join(', ')}`
-    );
-    warnings.push(
-      `${unsupportedEntities.length} unsupported entities will be skipped`
-    );
-  }
 
   // Check if there are ANY supported entities
   const supportedEntities = entities.filter((e) =>
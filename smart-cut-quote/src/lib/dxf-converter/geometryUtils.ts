@@ -95,7 +95,12 @@ export function ensureCounterClockwise(points: PointTuple[]): PointTuple[] {
 }
 
 /**
- * Detect shape structure: exterior vs holes based on signed area
+ * Detect shape structure: exterior vs holes
+ *
+ * The contour with the largest absolute area is the exterior, contours
+ * inside its bounding box are holes. Direction is not used (DXF files
+ * keep whatever direction the contours were drawn in) and is left
+ * unchanged, see orientContours.
  */
 export function detectShape(
   polygons: PointTuple[][]
@@ -104,30 +109,74 @@ export function detectShape(
     return { exterior: [], holes: [] };
   }
 
-  const exteriors: PointTuple[][] = [];
-  const holes: PointTuple[][] = [];
+  const sorted = polygons
+    .filter((polygon) => calculateSignedArea(polygon) !== 0)
+    .sort(
+      (a, b) =>
+        Math.abs(calculateSignedArea(b)) - Math.abs(calculateSignedArea(a))
+    );
 
-  polygons.forEach((polygon) => {
-    const area = calculateSignedArea(polygon);
+  const exterior = sorted[0] || [];
+  if (exterior.length === 0) {
+    return { exterior, holes: [] };
+  }
 
-    if (area > 0) {
-      // CCW = Exterior (outer boundary)
-      exteriors.push(polygon);
-    } else if (area < 0) {
-      // CW = Hole (inner boundary), reverse to CCW for sparroWASM
-      holes.push([...polygon].reverse());
-    }
+  const outerBox = calculateBoundingBox(exterior);
+  const holes = sorted.slice(1).filter((polygon) => {
+    const box = calculateBoundingBox(polygon);
+    return (
+      box.minX >= outerBox.minX &&
+      box.maxX <= outerBox.maxX &&
+      box.minY >= outerBox.minY &&
+      box.maxY <= outerBox.maxY
+    );
   });
 
-  // Use largest exterior if multiple
-  const sortedExteriors = exteriors.sort(
-    (a, b) =>
-      Math.abs(calculateSignedArea(b)) - Math.abs(calculateSignedArea(a))
-  );
+  return { exterior, holes };
+}
+
+/**
+ * Result of orientContours
+ */
+export interface ContourOrientation {
+  exterior: PointTuple[];
+  holes: PointTuple[][];
+  /** The exterior had to be reversed */
+  outerReversed: boolean;
+  /** Number of reversed holes */
+  holesReversed: number;
+}
+
+/**
+ * Enforce the CAM winding convention: exterior counter-clockwise and
+ * holes clockwise (or the opposite when outerCcw is false)
+ */
+export function orientContours(
+  exterior: PointTuple[],
+  holes: PointTuple[][],
+  outerCcw: boolean = true
+): ContourOrientation {
+  const orient = (points: PointTuple[], ccw: boolean): [PointTuple[], boolean] => {
+    const area = calculateSignedArea(points);
+    if (area === 0 || area > 0 === ccw) {
+      return [points, false];
+    }
+    return [[...points].reverse(), true];
+  };
+
+  const [orientedExterior, outerReversed] = orient(exterior, outerCcw);
+  let holesReversed = 0;
+  const orientedHoles = holes.map((hole) => {
+    const [oriented, reversed] = orient(hole, !outerCcw);
+    if (reversed) holesReversed++;
+    return oriented;
+  });
 
   return {
-    exterior: sortedExteriors[0] || [],
-    holes: holes,
+    exterior: orientedExterior,
+    holes: orientedHoles,
+    outerReversed,
+    holesReversed,
   };
 }
 
@@ -408,6 +457,7 @@ export default {
   calculateSignedArea,
   ensureCounterClockwise,
   detectShape,
+  orientContours,
   pointDistance,
   isClosedContour,
   closeContour,
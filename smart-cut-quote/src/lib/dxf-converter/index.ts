@@ -50,6 +50,8 @@ export type {
   DxfEllipseEntity,
   DxfSplineEntity,
   DxfDocument,
+  ArcVertex,
+  ExactGeometry,
 
   // Options & configuration
   DxfConverterOptions,
@@ -67,6 +69,8 @@ export type {
   ConversionError,
   ConversionWarning,
   ConversionStats,
+  PartConversionMetrics,
+  DroppedEntity,
 
   // Input types
   DxfFileInput,
@@ -224,6 +228,7 @@ export async function convertMultipleDxf(
     name: string;
     content: string;
     quantity?: number;
+    partMetadata?: Record<string, unknown>;
   }>,
   options: DxfConverterOptions = {}
 ): Promise<ConvertDxfContentResult> {
@@ -232,6 +237,7 @@ export async function convertMultipleDxf(
     name: f.name,
     content: f.content,
     quantity: f.quantity || 1,
+    partMetadata: f.partMetadata,
   }));
 
   // Run conversion
@@ -30,6 +30,7 @@ import type {
   JsonSizeInfo,
   JsonStats,
   ConversionSettings,
+  ExactGeometry,
 } from './types';
 
 /**
@@ -116,6 +117,20 @@ function cleanPolygonData(polygonData: PointTuple[]): PointTuple[] {
   return ensureCounterClockwise(cleaned);
 }
 
+/**
+ * Clean the coordinates of exact contours, bulges are kept as they are
+ */
+function cleanExactGeometry(geometry: ExactGeometry): ExactGeometry {
+  const clean = (contour: ExactGeometry['outer']) => ({
+    vertices: contour.vertices.map((v) => ({
+      x: cleanCoordinate(v.x),
+      y: cleanCoordinate(v.y),
+      bulge: v.bulge,
+    })),
+  });
+  return { outer: clean(geometry.outer), holes: geometry.holes.map(clean) };
+}
+
 /**
  * Format items into CORRECT sparroWASM JSON structure
  */
@@ -162,6 +177,23 @@ export function formatSparrowJson(
         type: 'simple_polygon' as const,
         data: cleanedPolygonData,
       },
+      ...(item.partMetadata ? { metadata: item.partMetadata } : {}),
+      ...(item.bendDirectionDeg !== undefined
+        ? {
+            bend_direction_deg: item.bendDirectionDeg,
+            bend_lines: (item.bendLines ?? []).map(
+              (line) =>
+                line.map(([x, y]) => [cleanCoordinate(x), cleanCoordinate(y)]) as [
+                  PointTuple,
+                  PointTuple,
+                ]
+            ),
+          }
+        : {}),
+      ...(item.extraSpacing ? { extra_spacing: item.extraSpacing } : {}),
+      ...(item.exactGeometry
+        ? { exact_geometry: cleanExactGeometry(item.exactGeometry) }
+        : {}),
     };
   });
 
@@ -156,6 +156,19 @@ export interface Contour {
   warning?: string | null;
 }
 
+/** Contour vertex, bulge = tan(sweep / 4) of the segment to the next vertex */
+export interface ArcVertex {
+  x: number;
+  y: number;
+  bulge: number;
+}
+
+/** Part contours with true arcs, the PartGeometry format of the Rust engine */
+export interface ExactGeometry {
+  outer: { vertices: ArcVertex[] };
+  holes: { vertices: ArcVertex[] }[];
+}
+
 export interface ContourValidationResult {
   valid: boolean;
   errors: string[];
@@ -180,6 +193,8 @@ export interface DxfConverterOptions {
   arcSegments?: number;
   /** Number of segments for spline discretization (default: 100) */
   splineSegments?: number;
+  /** Largest distance between a bulge arc and its polygon in mm (default: 0.05) */
+  maxChordError?: number;
   /** Point matching tolerance in mm (default: 0.1) */
   tolerance?: number;
   /** Automatically close open contours (default: true) */
@@ -188,6 +203,8 @@ export interface DxfConverterOptions {
   allowRotations?: boolean;
   /** Problem name for output (default: 'dxf_conversion') */
   problemName?: string;
+  /** Fail a file's conversion if any entity would be dropped (default: false) */
+  strictEntities?: boolean;
 }
 
 export interface ConversionSettings {
@@ -221,6 +238,24 @@ export interface SparrowItem {
   dxf: string;
   allowed_orientations: number[];
   shape: SparrowShape;
+  /** Opaque frontend data, returned on every placement of this item */
+  metadata?: Record<string, unknown>;
+  /** Angle of the part's bend lines (degrees), see respect_grain */
+  bend_direction_deg?: number;
+  bend_lines?: [PointTuple, PointTuple][];
+  /** Clearance kept around this part on top of the separation (mm) */
+  extra_spacing?: number;
+  /** Contours with true arcs, for exact cut length and DXF export */
+  exact_geometry?: ExactGeometry;
+  /** One copy already on the sheet here, the rest of the demand is nested around it */
+  fixed_position?: FixedPosition;
+}
+
+/** Strip position (mm) and rotation (degrees) of a pre-placed part */
+export interface FixedPosition {
+  x: number;
+  y: number;
+  rotation?: number;
 }
 
 export interface SparrowJson {
@@ -244,22 +279,45 @@ export interface NestingInputItem {
     originalEntityCount: number;
     contourCount: number;
     boundingBox: BoundingBox;
+    /** Exterior was drawn clockwise and had to be reversed */
+    outerReversed?: boolean;
+    /** Number of holes drawn counter-clockwise that had to be reversed */
+    holesReversed?: number;
   };
+  /** Caller data passed through to the nesting result (max 4KB as JSON) */
+  partMetadata?: Record<string, unknown>;
+  /** Angle of the BEND layer lines in degrees [0, 180) */
+  bendDirectionDeg?: number;
+  /** BEND layer segments, same coordinates as the shape */
+  bendLines?: [PointTuple, PointTuple][];
+  /** Extra clearance around this part in mm, e.g. for thin mesh parts */
+  extraSpacing?: number;
+  /** Contours with true arcs; the shape is their tessellation */
+  exactGeometry?: ExactGeometry;
 }
 
 // ============================================================================
 // Conversion Result Types
 // ============================================================================
 
+/** Entity found in the raw DXF that the converter does not turn into geometry */
+export interface DroppedEntity {
+  type: string;
+  handle?: string;
+  layer?: string;
+}
+
 export interface ConversionError {
   file: string;
   stage: string;
   message: string;
+  droppedEntities?: DroppedEntity[];
 }
 
 export interface ConversionWarning {
   file: string;
   message: string;
+  droppedEntities?: DroppedEntity[];
 }
 
 export interface ConversionStats {
@@ -267,6 +325,17 @@ export interface ConversionStats {
   successfulFiles: number;
   failedFiles: number;
   totalItems: number;
+  /** Parts with at least one contour reversed to CCW exterior / CW holes */
+  reversedParts?: number;
+}
+
+/** Per-part metrics of a converted file */
+export interface PartConversionMetrics {
+  file: string;
+  itemId: number;
+  contourCount: number;
+  outerReversed: boolean;
+  holesReversed: number;
 }
 
 export interface ConversionResult {
@@ -275,6 +344,7 @@ export interface ConversionResult {
   errors: ConversionError[];
   warnings: ConversionWarning[];
   stats?: ConversionStats;
+  parts?: PartConversionMetrics[];
   message?: string;
 }
 
@@ -292,6 +362,7 @@ export interface DxfFileInput {
   name: string;
   content: string;
   quantity?: number;
+  partMetadata?: Record<string, unknown>;
 }
 
 export interface DxfFileWithQuantity {
@@ -4,7 +4,7 @@
  * Based on IMPLEMENTATION_PLAN.md section 8.5
  */
 
-import React, { useState, useEffect } from 'react';
+import React, { useState, useEffect, useRef } from 'react';
 import {
   Box,
   Typography,
@@ -20,10 +20,11 @@ import {
 } from '@mui/material';
 import { useNavigate } from 'react-router-dom';
 import PlayArrowIcon from '@mui/icons-material/PlayArrow';
+import StopIcon from '@mui/icons-material/Stop';
 import ArrowForwardIcon from '@mui/icons-material/ArrowForward';
 import ArrowBackIcon from '@mui/icons-material/ArrowBack';
 import { useQuoteStore } from '../stores/quoteStore';
-import { runNestingWorkflow } from '../services/nestingService';
+import { applyPreviewUpdate, cancelNesting, runNestingWorkflow } from '../services/nestingService';
 import { getNestingSettings, saveNestingSettings } from '../services/database';
 import SvgViewer from '../components/Viewer/SvgViewer';
 
@@ -33,8 +34,14 @@ export default function Nesting() {
   const nestingSvgUrl = useQuoteStore((state) => state.nestingSvgUrl);
   const nestingResult = useQuoteStore((state) => state.nestingResult);
   const setNestingResult = useQuoteStore((state) => state.setNestingResult);
+  const currentQuoteId = useQuoteStore((state) => state.currentQuoteId);
 
   const [loading, setLoading] = useState(false);
+  const [progressLabel, setProgressLabel] = useState<string | null>(null);
+  const previewRef = useRef<HTMLDivElement>(null);
+  const jobIdRef = useRef<string | null>(null);
+  const [cancelling, setCancelling] = useState(false);
+  const cancellingRef = useRef(false);
   const [error, setError] = useState<string | null>(null);
   const [stripHeight, setStripHeight] = useState<number>(6000);
   const [partSpacing, setPartSpacing] = useState<number>(5);
@@ -88,7 +95,24 @@ export default function Nesting() {
     setNestingResult(null, null);
 
     try {
-      const result = await runNestingWorkflow(files, stripHeight, partSpacing, timeLimit);
+      const result = await runNestingWorkflow(
+        files,
+        stripHeight,
+        partSpacing,
+        timeLimit,
+        () => setProgressLabel('Rendering layout...'),
+        (update) => previewRef.current && applyPreviewUpdate(previewRef.current, update),
+        currentQuoteId,
+        (jobId) => (jobIdRef.current = jobId),
+        (progress) => {
+          // The cancel message stays until the run returns
+          if (cancellingRef.current) return;
+          setProgressLabel(
+            `Optimizing: ${progress.placed_items} parts, ` +
+              `${progress.strip_width.toFixed(0)} mm (${progress.elapsed_secs.toFixed(0)}s)`
+          );
+        }
+      );
 
       if (result.success && result.data && result.svgUrl) {
         // Save result to store (both result and svgUrl)
@@ -100,7 +124,25 @@ export default function Nesting() {
     } catch (err: any) {
       setError(err.message || 'An unexpected error occurred');
     } finally {
+      jobIdRef.current = null;
+      cancellingRef.current = false;
       setLoading(false);
+      setCancelling(false);
+      setProgressLabel(null);
+    }
+  };
+
+  // The run finishes with its best layout so far
+  const handleCancelNesting = async () => {
+    if (!jobIdRef.current) return;
+    setCancelling(true);
+    cancellingRef.current = true;
+    try {
+      await cancelNesting(jobIdRef.current);
+      setProgressLabel('Stopping, keeping the best layout so far...');
+    } catch (err) {
+      // Already finished
+      console.warn('Could not cancel nesting:', err);
     }
   };
 
@@ -184,9 +226,22 @@ export default function Nesting() {
             disabled={loading}
             startIcon={loading ? <CircularProgress size={20} /> : <PlayArrowIcon />}
           >
-            {loading ? 'Processing...' : (nestingResult ? 'Recalculate with Current Parameters' : 'Start Nesting')}
+            {loading ? (progressLabel ?? 'Processing...') : (nestingResult ? 'Recalculate with Current Parameters' : 'Start Nesting')}
           </Button>
 
+          {loading && (
+            <Button
+              variant="outlined"
+              color="warning"
+              fullWidth
+              onClick={handleCancelNesting}
+              disabled={cancelling}
+              startIcon={<StopIcon />}
+            >
+              Cancel Nesting
+            </Button>
+          )}
+
           {error && (
             <Alert severity="error" onClose={() => setError(null)}>
               {error}
@@ -215,7 +270,12 @@ export default function Nesting() {
           <Typography variant="h6" gutterBottom>
             Nesting Result
           </Typography>
-          <SvgViewer svgPath={nestingSvgUrl || ''} />
+          {loading ? (
+            // Live preview, patched in place while the optimizer improves the layout
+            <Box ref={previewRef} sx={{ width: '100%', '& svg': { width: '100%', height: 'auto' } }} />
+          ) : (
+            <SvgViewer svgPath={nestingSvgUrl || ''} />
+          )}
         </Paper>
       </Box>
 
@@ -1,6 +1,7 @@
 /**
  * Settings Page
- * Configuration for materials, machines, operations, pricing, and company info
+ * Configuration for materials, machines, operations, pricing, company info and
+ * integrations, plus installation diagnostics
  */
 
 import { useState, useEffect } from 'react';
@@ -19,12 +20,16 @@ import PrecisionManufacturingIcon from '@mui/icons-material/PrecisionManufacturi
 import BuildIcon from '@mui/icons-material/Build';
 import AttachMoneyIcon from '@mui/icons-material/AttachMoney';
 import BusinessIcon from '@mui/icons-material/Business';
+import HealthAndSafetyIcon from '@mui/icons-material/HealthAndSafety';
+import HubIcon from '@mui/icons-material/Hub';
 
 import MaterialStockTab from '../components/Settings/MaterialStockTab';
 import MachineTab from '../components/Settings/MachineTab';
 import OperationsTab from '../components/Settings/OperationsTab';
 import PricingTab from '../components/Settings/PricingTab';
 import CompanyTab from '../components/Settings/CompanyTab';
+import IntegrationsTab from '../components/Settings/IntegrationsTab';
+import DiagnosticsTab from '../components/Settings/DiagnosticsTab';
 
 import { getDatabase } from '../services/database';
 
@@ -88,6 +93,9 @@ export default function Settings() {
           <Typography variant="h6">Database Error</Typography>
           <Typography>{error}</Typography>
         </Alert>
+        <Box sx={{ mt: 3 }}>
+          <DiagnosticsTab />
+        </Box>
       </Box>
     );
   }
@@ -158,6 +166,18 @@ export default function Settings() {
             {...a11yProps(4)}
             iconPosition="start"
           />
+          <Tab
+            icon={<HubIcon />}
+            label="Integrations"
+            {...a11yProps(5)}
+            iconPosition="start"
+          />
+          <Tab
+            icon={<HealthAndSafetyIcon />}
+            label="Diagnostics"
+            {...a11yProps(6)}
+            iconPosition="start"
+          />
         </Tabs>
       </Paper>
 
@@ -178,6 +198,12 @@ export default function Settings() {
         <TabPanel value={tabValue} index={4}>
           <CompanyTab />
         </TabPanel>
+        <TabPanel value={tabValue} index={5}>
+          <IntegrationsTab />
+        </TabPanel>
+        <TabPanel value={tabValue} index={6}>
+          <DiagnosticsTab />
+        </TabPanel>
       </Box>
     </Box>
   );
@@ -0,0 +1,91 @@
+/**
+ * Artifact Service
+ * Reads the files stored with nesting results (SVG, instance JSON),
+ * checked against the checksum recorded when they were written. A
+ * missing or changed file fails with 'not_found' or 'corrupt' and says
+ * which quote renest_exact can regenerate it from, instead of showing a
+ * blank preview.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { CommandError, type CommandResponse } from './commandResponse';
+
+// Backend types (must match Rust structs)
+export type ArtifactKind = 'svg' | 'instance' | 'converted_part'; // converted_part: audit only
+
+export type ArtifactState = 'missing' | 'corrupt';
+
+export interface ArtifactProblem {
+  kind: ArtifactKind;
+  state: ArtifactState;
+  result_id: number | null;
+  reference: string; // Stored path (SVGs relative to the app data dir) or instance hash
+  expected_sha256: string | null;
+  actual_sha256: string | null;
+  replay_quote_id: string | null; // Quote whose snapshot renest_exact replays
+  source_path: string | null; // DXF a converted part is converted from again
+}
+
+export type ArtifactFetch =
+  | { delivery: 'inline'; content: string; size_bytes: number; sha256: string }
+  | { delivery: 'chunked'; token: string; size_bytes: number; chunk_count: number; sha256: string }
+  | ({ delivery: 'unavailable' } & ArtifactProblem);
+
+export interface ArtifactChunk {
+  index: number;
+  content: string;
+  last: boolean;
+}
+
+export interface ArtifactAudit {
+  checked: number;
+  unverified: number; // Stored before checksums were recorded
+  problems: ArtifactProblem[];
+}
+
+/**
+ * Error of an artifact that is missing or no longer matches its checksum
+ */
+export class ArtifactUnavailableError extends CommandError {
+  constructor(public readonly problem: ArtifactProblem, message: string) {
+    super(problem.state === 'missing' ? 'not_found' : 'corrupt', message);
+    this.name = 'ArtifactUnavailableError';
+  }
+}
+
+/**
+ * Content of a stored artifact of a nesting result, read in chunks when large
+ *
+ * Throws ArtifactUnavailableError when it is missing or corrupt.
+ */
+export async function getArtifact(resultId: number, kind: ArtifactKind): Promise<string> {
+  const response = await invoke<CommandResponse<ArtifactFetch>>('get_artifact', { resultId, kind });
+  if (!response.ok) {
+    if (response.delivery === 'unavailable') {
+      const problem = response as unknown as ArtifactProblem;
+      throw new ArtifactUnavailableError(problem, response.message ?? 'Artifact unavailable');
+    }
+    throw new CommandError(response.code ?? 'internal', response.message ?? 'Unknown error');
+  }
+
+  const fetched = response as unknown as ArtifactFetch;
+  if (fetched.delivery === 'inline') {
+    return fetched.content;
+  }
+  if (fetched.delivery === 'chunked') {
+    const chunks: string[] = [];
+    for (let index = 0; index < fetched.chunk_count; index++) {
+      const chunk = await invoke<ArtifactChunk>('read_artifact_chunk', { token: fetched.token, index });
+      chunks.push(chunk.content);
+    }
+    return chunks.join('');
+  }
+  throw new ArtifactUnavailableError(fetched, 'Artifact unavailable');
+}
+
+/**
+ * Check every stored SVG, instance and converted part
+ */
+export async function auditArtifacts(): Promise<ArtifactAudit> {
+  return invoke<ArtifactAudit>('audit_artifacts');
+}
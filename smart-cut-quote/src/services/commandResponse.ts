@@ -0,0 +1,58 @@
+/**
+ * Command Response
+ * Envelope returned by the Tauri commands that report typed errors
+ * (convert_dxf_to_json, run_nesting, run_nesting_integrated and the DXF
+ * file commands). Payload fields are flattened into the envelope.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export type CommandErrorCode =
+  | 'invalid_input'
+  | 'not_found'
+  | 'permission_denied'
+  | 'tool_failed'
+  | 'cancelled'
+  | 'cancelled_before_solution'
+  | 'conflict'
+  | 'corrupt'
+  | 'internal';
+
+export type CommandResponse<T> = {
+  ok: boolean;
+  code?: CommandErrorCode;
+  message?: string;
+  warnings: string[];
+  /** @deprecated Same as ok, removed in the next release */
+  success: boolean;
+  /** @deprecated Same as message, removed in the next release */
+  error?: string;
+} & Partial<T>;
+
+/**
+ * Error thrown for a failed command response
+ */
+export class CommandError extends Error {
+  constructor(
+    public readonly code: CommandErrorCode,
+    message: string,
+    public readonly warnings: string[] = []
+  ) {
+    super(message);
+    this.name = 'CommandError';
+  }
+}
+
+/**
+ * Invoke a command and return its payload, throwing CommandError on failure
+ */
+export async function invokeCommand<T>(command: string, args?: Record<string, unknown>): Promise<T> {
+  const response = await invoke<CommandResponse<T>>(command, args);
+  if (!response.ok) {
+    throw new CommandError(response.code ?? 'internal', response.message ?? 'Unknown error', response.warnings);
+  }
+  for (const warning of response.warnings) {
+    console.warn(`⚠️ ${command}: ${warning}`);
+  }
+  return response as unknown as T;
+}
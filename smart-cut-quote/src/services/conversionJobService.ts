@@ -0,0 +1,136 @@
+/**
+ * Conversion Job Service
+ * Converts a whole directory of DXF files (e.g. a production release
+ * package) as a background job in the backend. Progress is stored per
+ * file, so a job survives closing the screen or the app and can be
+ * resumed; converted files are usable before the job finishes.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { CommandResponse } from './commandResponse';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+
+// Backend types (must match Rust structs)
+export interface ConversionOptions {
+  stripHeight: number;
+  partSpacing: number;
+  arcSegments: number;
+}
+
+export interface ConvertedFile {
+  path: string;
+  output_path: string;
+}
+
+export type ConversionJobState =
+  | 'running'
+  | 'interrupted'
+  | 'cancelled'
+  | 'completed'
+  | 'cancelling'; // convertDxfFiles batch, see its result
+
+export interface ConversionJobStatus {
+  job_id: string;
+  directory: string;
+  status: ConversionJobState;
+  files_done: number;
+  files_failed: number;
+  files_remaining: number;
+  converted: ConvertedFile[];
+}
+
+export interface DxfFileInput {
+  path: string;
+  quantity: number;
+}
+
+export type ConversionResult = CommandResponse<{
+  output_path: string | null;
+  cancelled?: boolean;
+  completed_files?: string[]; // Inputs contained in output_path
+}>;
+
+export interface ConversionProgressEvent {
+  job_id: string;
+  status: ConversionJobState;
+  files_done: number;
+  files_failed: number;
+  files_remaining: number;
+  files_per_minute: number;
+  eta_secs: number | null;
+  file: string | null;
+  output_path: string | null;
+  error: string | null;
+}
+
+/**
+ * Start converting all DXF files of a directory
+ *
+ * @param maxFilesPerMinute - Optional rate limit
+ */
+export async function startConversionJob(
+  directory: string,
+  outputDir: string,
+  options: ConversionOptions,
+  maxFilesPerMinute?: number
+): Promise<ConversionJobStatus> {
+  return invoke<ConversionJobStatus>('start_conversion_job', {
+    directory,
+    outputDir,
+    options,
+    maxFilesPerMinute: maxFilesPerMinute ?? null,
+  });
+}
+
+/**
+ * Continue an interrupted or cancelled job (converted files are skipped)
+ */
+export async function resumeConversionJob(jobId: string): Promise<ConversionJobStatus> {
+  return invoke<ConversionJobStatus>('resume_conversion_job', { jobId });
+}
+
+/**
+ * Convert DXF files into one nesting JSON with dxf-converter.exe
+ *
+ * Pass a jobId to make the batch cancellable with cancelConversionJob;
+ * a cancelled batch resolves with `code: 'cancelled'` and the files
+ * converted so far.
+ */
+export async function convertDxfFiles(
+  inputFiles: DxfFileInput[],
+  outputPath: string,
+  options: ConversionOptions,
+  jobId?: string
+): Promise<ConversionResult> {
+  return invoke<ConversionResult>('convert_dxf_to_json', {
+    inputFiles,
+    outputPath,
+    options,
+    jobId: jobId ?? null,
+  });
+}
+
+/**
+ * Stop a job after the file currently being converted
+ */
+export async function cancelConversionJob(jobId: string): Promise<ConversionJobStatus> {
+  return invoke<ConversionJobStatus>('cancel_conversion_job', { jobId });
+}
+
+/**
+ * Progress and converted files of a job
+ */
+export async function getConversionJob(jobId: string): Promise<ConversionJobStatus> {
+  return invoke<ConversionJobStatus>('get_conversion_job', { jobId });
+}
+
+/**
+ * Listen to progress of all conversion jobs
+ */
+export async function onConversionProgress(
+  handler: (event: ConversionProgressEvent) => void
+): Promise<UnlistenFn> {
+  return listen<ConversionProgressEvent>('conversion://progress', (event) =>
+    handler(event.payload)
+  );
+}
@@ -0,0 +1,41 @@
+/**
+ * Dashboard Server Service
+ * Settings of the read-only HTTP endpoint for shop dashboards
+ * (GET /jobs, /metrics, /quote/{id}/summary with a bearer token).
+ * The backend reads them at startup, changes apply after a restart.
+ */
+
+import { getSetting, setSetting } from './database';
+
+export type DashboardBind = 'localhost' | 'lan';
+
+export interface DashboardServerSettings {
+  enabled: boolean;
+  port: number;
+  bind: DashboardBind; // 'lan' listens on all interfaces
+  token: string; // The server does not start while empty
+}
+
+export const DEFAULT_DASHBOARD_PORT = 8787;
+
+export async function getDashboardServerSettings(): Promise<DashboardServerSettings> {
+  const [enabled, port, bind, token] = await Promise.all([
+    getSetting('dashboard_server_enabled'),
+    getSetting('dashboard_server_port'),
+    getSetting('dashboard_server_bind'),
+    getSetting('dashboard_server_token'),
+  ]);
+  return {
+    enabled: enabled === 'true',
+    port: Number(port) || DEFAULT_DASHBOARD_PORT,
+    bind: bind === 'lan' ? 'lan' : 'localhost',
+    token: token ?? '',
+  };
+}
+
+export async function saveDashboardServerSettings(settings: DashboardServerSettings): Promise<void> {
+  await setSetting('dashboard_server_enabled', settings.enabled ? 'true' : 'false');
+  await setSetting('dashboard_server_port', String(settings.port));
+  await setSetting('dashboard_server_bind', settings.bind);
+  await setSetting('dashboard_server_token', settings.token.trim());
+}
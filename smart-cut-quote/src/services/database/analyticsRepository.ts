@@ -3,6 +3,7 @@
  * Customer analytics and business intelligence queries
  */
 
+import { invoke } from '@tauri-apps/api/core';
 import { query } from './connection';
 import type {
   CustomerAnalytics,
@@ -10,6 +11,9 @@ import type {
   TimelineDataPoint,
   AnalyticsPeriod,
   AnalyticsRow,
+  NestingMetricsBucket,
+  NestingMetricsGroupBy,
+  NestingMetricsRange,
 } from '../../types/analytics';
 
 /**
@@ -197,9 +201,21 @@ export async function getTopClientsByRevenue(limit: number = 10): Promise<any[]>
   return query<any>(sql, [limit]);
 }
 
+/**
+ * Get aggregated nesting run statistics
+ * Buckets are sorted by key, so time groupings come out as a series
+ */
+export async function getNestingMetrics(
+  groupBy: NestingMetricsGroupBy,
+  range?: NestingMetricsRange
+): Promise<NestingMetricsBucket[]> {
+  return invoke<NestingMetricsBucket[]>('get_nesting_metrics', { groupBy, range });
+}
+
 export default {
   searchClientsByPhone,
   getCustomerAnalytics,
   getCustomerTimeline,
   getTopClientsByRevenue,
+  getNestingMetrics,
 };
@@ -4,6 +4,7 @@
  */
 
 import Database from '@tauri-apps/plugin-sql';
+import { getMigrationStatus } from '../migrationService';
 
 const DB_NAME = 'sqlite:smart_cut_quote.db';
 
@@ -14,6 +15,11 @@ let dbInstance: Database | null = null;
  */
 export async function getDatabase(): Promise<Database> {
   if (!dbInstance) {
+    // Do not let the plugin retry migrations that failed at startup
+    const { startup_error } = await getMigrationStatus();
+    if (startup_error) {
+      throw new Error(`Database update failed: ${startup_error.message}`);
+    }
     dbInstance = await Database.load(DB_NAME);
     console.log('Database connection established');
   }
@@ -48,8 +48,8 @@ export async function createMaterial(input: MaterialStockInput): Promise<string>
     `INSERT INTO material_stock (
       id, name, grade, thickness, sheet_width, sheet_max_length,
       price_per_kg, density, quantity_in_stock, min_quantity,
-      cutting_speed, pierce_time, pierce_cost, cut_price_per_meter, is_active
-    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1)`,
+      cutting_speed, pierce_time, pierce_cost, cut_price_per_meter, min_web, is_active
+    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 1)`,
     [
       id,
       input.name,
@@ -65,6 +65,7 @@ export async function createMaterial(input: MaterialStockInput): Promise<string>
       input.pierce_time || 0.5,
       input.pierce_cost || 0.15,
       input.cut_price_per_meter || 0,
+      input.min_web ?? null,
     ]
   );
 
@@ -101,6 +102,8 @@ export async function updateMaterial(id: string, input: Partial<MaterialStockInp
   if (input.price_per_kg !== undefined) {
     updates.push('price_per_kg = ?');
     values.push(input.price_per_kg);
+    // Setting a price is the review of a seeded placeholder
+    updates.push('needs_price_review = 0');
   }
   if (input.density !== undefined) {
     updates.push('density = ?');
@@ -130,6 +133,10 @@ export async function updateMaterial(id: string, input: Partial<MaterialStockInp
     updates.push('cut_price_per_meter = ?');
     values.push(input.cut_price_per_meter);
   }
+  if (input.min_web !== undefined) {
+    updates.push('min_web = ?');
+    values.push(input.min_web);
+  }
 
   if (updates.length === 0) return;
 
@@ -4,7 +4,7 @@
  */
 
 import { query, execute } from './connection';
-import type { Setting, AppSettings, CompanyInfo } from './types';
+import type { Setting, AppSettings, CompanyInfo, DisplayUnits } from './types';
 
 /**
  * Get a single setting value
@@ -191,6 +191,18 @@ export async function saveNestingSettings(settings: NestingSettings): Promise<vo
   ]);
 }
 
+/**
+ * Units the UI shows lengths and areas in (pass as `units` to backend commands)
+ */
+export async function getDisplayUnits(): Promise<DisplayUnits> {
+  const units = await getSetting('display_units');
+  return units === 'in' ? 'in' : 'mm';
+}
+
+export async function saveDisplayUnits(units: DisplayUnits): Promise<void> {
+  await setSetting('display_units', units);
+}
+
 export default {
   getSetting,
   setSetting,
@@ -200,4 +212,6 @@ export default {
   updateCompanyInfo,
   getNestingSettings,
   saveNestingSettings,
+  getDisplayUnits,
+  saveDisplayUnits,
 };
@@ -13,6 +13,9 @@ export interface Setting {
   updated_at?: string;
 }
 
+/** Units lengths are shown in; everything is stored and nested in mm */
+export type DisplayUnits = 'mm' | 'in';
+
 export interface AppSettings {
   default_price_markup: number;
   default_material_markup: number;
@@ -65,6 +68,8 @@ export interface MaterialStock {
   pierce_time: number;
   pierce_cost: number;
   cut_price_per_meter: number; // Price per meter for length-based cutting cost
+  min_web: number | null; // Minimum web width in mm (null = no check)
+  needs_price_review: number; // SQLite boolean, placeholder prices of seeded stock
   is_active: number; // SQLite boolean
   created_at?: string;
   updated_at?: string;
@@ -85,6 +90,7 @@ export interface MaterialStockInput {
   pierce_time?: number;
   pierce_cost?: number;
   cut_price_per_meter?: number;
+  min_web?: number | null;
 }
 
 // =====================================================
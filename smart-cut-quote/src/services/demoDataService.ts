@@ -0,0 +1,31 @@
+/**
+ * Demo Data Service
+ * Fills an empty database with a sample client, stock and a nested quote
+ * so new users can see a finished quote. Only the rows inserted by demo
+ * mode are removed again.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+// Backend types (must match Rust structs)
+export interface DemoDataReport {
+  client_id: string;
+  quote_id: string;
+  material_ids: string[];
+  rows_inserted: number; // 0 when the demo data was already loaded
+  utilization: number | null; // null when the demo nesting did not run again
+}
+
+/**
+ * Load the demo data (runs a 5 s nesting the first time, safe to repeat)
+ */
+export async function loadDemoData(): Promise<DemoDataReport> {
+  return invoke<DemoDataReport>('load_demo_data');
+}
+
+/**
+ * Remove the demo data, returns the number of rows removed
+ */
+export async function clearDemoData(): Promise<number> {
+  return invoke<number>('clear_demo_data');
+}
@@ -0,0 +1,151 @@
+/**
+ * Draft Service
+ * The quote being edited is autosaved (debounced) as a draft of the app
+ * session. Drafts written by a session that crashed are announced with a
+ * 'drafts://recoverable' event at the next startup and can be restored
+ * into the quote store or discarded.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import { useQuoteStore } from '../stores/quoteStore';
+import { Client, DxfFile, QuoteSummary } from '../types/quote';
+
+// Backend types (must match Rust structs)
+export interface DraftSummary {
+  id: string; // Session that wrote the draft
+  size_bytes: number;
+  created_at: string; // UTC 'YYYY-MM-DD HH:MM:SS'
+  updated_at: string;
+}
+
+export interface RecoverableDraft extends DraftSummary {
+  draft_json: string;
+}
+
+export const RECOVERABLE_DRAFTS_EVENT = 'drafts://recoverable';
+
+/** Autosave delay after the last change */
+export const DRAFT_AUTOSAVE_DELAY_MS = 2000;
+
+/**
+ * Part of the quote store kept in a draft
+ * Nesting results and previews are left out, they are recomputed.
+ */
+export interface QuoteDraft {
+  currentStage: number;
+  client: Client | null;
+  files: Omit<DxfFile, 'preview'>[];
+  summary: QuoteSummary | null;
+  currentQuoteId: string | null;
+  currentQuoteNumber: string | null;
+}
+
+/**
+ * Save the draft of this session, replacing the previous one
+ */
+export async function saveDraft(draft: QuoteDraft): Promise<DraftSummary> {
+  return invoke<DraftSummary>('save_draft', { quoteDraftJson: JSON.stringify(draft) });
+}
+
+/**
+ * Drafts left behind by sessions that did not exit cleanly, newest first
+ */
+export async function listRecoverableDrafts(): Promise<RecoverableDraft[]> {
+  return invoke<RecoverableDraft[]>('list_recoverable_drafts');
+}
+
+/**
+ * Delete a draft; false if it did not exist
+ */
+export async function discardDraft(id: string): Promise<boolean> {
+  return invoke<boolean>('discard_draft', { id });
+}
+
+/**
+ * Listen for the recoverable drafts found at startup
+ */
+export async function onRecoverableDrafts(
+  handler: (drafts: DraftSummary[]) => void
+): Promise<UnlistenFn> {
+  return listen<DraftSummary[]>(RECOVERABLE_DRAFTS_EVENT, (event) => handler(event.payload));
+}
+
+/**
+ * Load a recovered draft into the quote store
+ */
+export function restoreDraft(draft: RecoverableDraft): void {
+  const parsed = JSON.parse(draft.draft_json) as QuoteDraft;
+  useQuoteStore.setState({
+    currentStage: parsed.currentStage,
+    client: parsed.client,
+    files: parsed.files,
+    summary: parsed.summary,
+    currentQuoteId: parsed.currentQuoteId,
+    currentQuoteNumber: parsed.currentQuoteNumber,
+    nestingResult: null,
+    nestingSvgUrl: null,
+    batchedNestingResults: null,
+  });
+}
+
+function currentDraft(): QuoteDraft | null {
+  const state = useQuoteStore.getState();
+  if (!state.client && state.files.length === 0) {
+    return null;
+  }
+  return {
+    currentStage: state.currentStage,
+    client: state.client,
+    files: state.files.map(({ preview: _preview, ...file }) => file),
+    summary: state.summary,
+    currentQuoteId: state.currentQuoteId,
+    currentQuoteNumber: state.currentQuoteNumber,
+  };
+}
+
+/**
+ * Autosave the quote store while it changes
+ * An empty quote (closed or reset) discards the session's draft.
+ * Returns a function that stops autosaving.
+ */
+export function startDraftAutosave(delayMs = DRAFT_AUTOSAVE_DELAY_MS): () => void {
+  let timer: ReturnType<typeof setTimeout> | null = null;
+  let savedId: string | null = null;
+
+  const flush = () => {
+    timer = null;
+    const draft = currentDraft();
+    if (draft) {
+      saveDraft(draft)
+        .then((summary) => {
+          savedId = summary.id;
+        })
+        .catch((error) => console.error('Failed to save draft:', error));
+    } else if (savedId) {
+      const id = savedId;
+      savedId = null;
+      discardDraft(id).catch((error) => console.error('Failed to discard draft:', error));
+    }
+  };
+
+  const unsubscribe = useQuoteStore.subscribe((state, previous) => {
+    // Saved quote list refreshes are not edits
+    if (
+      state.currentStage === previous.currentStage &&
+      state.client === previous.client &&
+      state.files === previous.files &&
+      state.summary === previous.summary &&
+      state.currentQuoteId === previous.currentQuoteId
+    ) {
+      return;
+    }
+    if (timer) clearTimeout(timer);
+    timer = setTimeout(flush, delayMs);
+  });
+
+  return () => {
+    if (timer) clearTimeout(timer);
+    unsubscribe();
+  };
+}
@@ -4,7 +4,7 @@
  */
 
 import DxfParser from 'dxf-parser';
-import { invoke } from '@tauri-apps/api/core';
+import { invokeCommand } from './commandResponse';
 import type { DxfEntity, DxfVertex, ParsedDxf } from '../types/dxfHealing';
 
 /**
@@ -14,7 +14,7 @@ import type { DxfEntity, DxfVertex, ParsedDxf } from '../types/dxfHealing';
  */
 export async function parseDxfFile(filePath: string): Promise<ParsedDxf> {
   // 1. Read file via Tauri
-  const content = await invoke<string>('read_dxf_file', { path: filePath });
+  const { content } = await invokeCommand<{ content: string }>('read_dxf_file', { path: filePath });
 
   // 2. Parse with dxf-parser
   const parser = new DxfParser();
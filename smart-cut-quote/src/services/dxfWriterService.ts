@@ -4,7 +4,7 @@
  */
 
 import DxfWriter from 'dxf-writer';
-import { invoke } from '@tauri-apps/api/core';
+import { invokeCommand } from './commandResponse';
 import type { DxfEntity } from '../types/dxfHealing';
 
 /**
@@ -46,7 +46,7 @@ export async function writeDxfFile(
   const dxfString = dxf.toDxfString();
 
   // Write to file via Tauri
-  await invoke('write_dxf_file', { path: filePath, content: dxfString });
+  await invokeCommand('write_dxf_file', { path: filePath, content: dxfString });
 }
 
 /**
@@ -0,0 +1,28 @@
+/**
+ * Event Bus Service
+ * Backend events are rate limited per topic: progress events are
+ * coalesced (latest wins), others queued and dropped when the queue is
+ * full. The counters help diagnosing a lagging UI.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+// Backend types (must match Rust structs)
+export interface TopicStats {
+  emitted: number;
+  coalesced: number; // Replaced by a later event before being sent
+  dropped: number;
+  failed: number;
+}
+
+export interface EventBusStats {
+  topics: Record<string, TopicStats>;
+  held: number; // Events waiting for their rate limit
+}
+
+/**
+ * Emission counters per event topic since app start
+ */
+export async function getEventBusStats(): Promise<EventBusStats> {
+  return invoke<EventBusStats>('get_event_bus_stats');
+}
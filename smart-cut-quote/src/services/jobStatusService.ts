@@ -0,0 +1,76 @@
+/**
+ * Job Status Service
+ * One status API for every long-running backend job: nesting runs and
+ * batches started with a job ID, DXF conversion batches and directory
+ * conversion jobs. Poll with getJobStatus instead of the per-feature
+ * commands.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+// Backend types (must match Rust structs)
+export type JobKind = 'nesting' | 'nesting_batch' | 'conversion';
+
+export type JobStatus =
+  | { state: 'queued' }
+  | {
+      state: 'running';
+      stage: string; // e.g. "optimizing" or a batch group key
+      progress_pct: number | null; // null when the job cannot tell
+      detail: string | null;
+    }
+  | { state: 'completed'; summary_ref: string | null } // e.g. job ID for getDerivedOutput
+  | { state: 'failed'; error: string }
+  | { state: 'cancelled' };
+
+export interface JobInfo<P = unknown> {
+  job_id: string;
+  kind: JobKind;
+  status: JobStatus;
+  payload: P; // { done, total } for batches, ConversionJobStatus for directory jobs
+}
+
+/**
+ * Current status of any job by ID
+ */
+export async function getJobStatus(jobId: string): Promise<JobInfo> {
+  return invoke<JobInfo>('get_job_status', { jobId });
+}
+
+/**
+ * Running and recently finished jobs, newest first
+ */
+export async function listJobs(kindFilter?: JobKind): Promise<JobInfo[]> {
+  return invoke<JobInfo[]>('list_jobs', { kindFilter: kindFilter ?? null });
+}
+
+/**
+ * Poll a job until it finished, calling onStatus with every status
+ *
+ * Resolves with the final status; call the returned stop function to
+ * stop polling early.
+ */
+export function pollJobStatus(
+  jobId: string,
+  onStatus: (job: JobInfo) => void,
+  intervalMs = 1000
+): { done: Promise<JobInfo>; stop: () => void } {
+  let stopped = false;
+  const done = (async () => {
+    for (;;) {
+      const job = await getJobStatus(jobId);
+      onStatus(job);
+      const { state } = job.status;
+      if (stopped || (state !== 'queued' && state !== 'running')) {
+        return job;
+      }
+      await new Promise((resolve) => setTimeout(resolve, intervalMs));
+    }
+  })();
+  return {
+    done,
+    stop: () => {
+      stopped = true;
+    },
+  };
+}
@@ -0,0 +1,65 @@
+/**
+ * Legacy Import Service
+ * Imports the result JSON/SVG files written by the old sparrow-cli
+ * integration into nesting_results. Results are linked to a quote when
+ * the instance name matches its reference or number; the others are kept
+ * unlinked (quoteId null). Folders can be imported again, files already
+ * imported are skipped.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+// Backend types (must match Rust structs)
+export type ImportOutcome =
+  | 'imported'
+  | 'would_import' // Dry run
+  | 'already_imported'
+  | 'not_legacy' // JSON in another schema
+  | 'failed';
+
+export interface ImportedFile {
+  file: string; // Relative to the scanned directory
+  outcome: ImportOutcome;
+  instance_name: string | null;
+  quote_id: string | null; // null: unmatched
+  svg_path: string | null; // Copied SVG (app data relative), source SVG in a dry run
+  error: string | null;
+}
+
+export interface LegacyImportReport {
+  dry_run: boolean;
+  scanned: number;
+  imported: number;
+  unmatched: number;
+  already_imported: number;
+  failed: number;
+  files: ImportedFile[];
+}
+
+export interface LegacyImportProgress {
+  current: number;
+  total: number;
+  file: string;
+}
+
+/**
+ * Import the legacy results below a directory, dryRun only lists them
+ */
+export async function importLegacyResults(
+  dir: string,
+  dryRun = false
+): Promise<LegacyImportReport> {
+  return invoke<LegacyImportReport>('import_legacy_results', { dir, dryRun });
+}
+
+/**
+ * Listen to import progress (coalesced, about ten events per second)
+ */
+export async function onLegacyImportProgress(
+  handler: (event: LegacyImportProgress) => void
+): Promise<UnlistenFn> {
+  return listen<LegacyImportProgress>('legacy-import://progress', (event) =>
+    handler(event.payload)
+  );
+}
@@ -0,0 +1,46 @@
+/**
+ * Migration Service
+ * Database migrations run at startup before the database is loaded.
+ * The backend backs up the database first and restores it when a
+ * migration fails; the failure is reported here and as a
+ * 'startup-error' event.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+// Backend types (must match Rust structs)
+export interface MigrationFailure {
+  version: number | null; // null when the runner failed before migrating
+  message: string;
+  backup_path: string | null;
+  restored: boolean; // database is back in its pre-migration state
+}
+
+export interface AppliedMigration {
+  version: number;
+  description: string;
+  installed_on: string;
+  success: boolean;
+}
+
+export interface PendingMigration {
+  version: number;
+  description: string;
+}
+
+export interface MigrationStatus {
+  latest_version: number;
+  applied: AppliedMigration[];
+  pending: PendingMigration[];
+  backup_path: string | null;
+  startup_error: MigrationFailure | null;
+}
+
+export const STARTUP_ERROR_EVENT = 'startup-error';
+
+/**
+ * Applied and pending migrations, plus the startup failure if any
+ */
+export async function getMigrationStatus(): Promise<MigrationStatus> {
+  return invoke<MigrationStatus>('get_migration_status');
+}
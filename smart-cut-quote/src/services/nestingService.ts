@@ -7,9 +7,13 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { readTextFile, writeTextFile, BaseDirectory } from '@tauri-apps/plugin-fs';
 import { convertMultipleDxf } from '../lib/dxf-converter';
+import { invokeCommand } from './commandResponse';
+import { rasterizeSvgToPng } from './thumbnailService';
 import { DxfFile, NestingResult as NestingResultType } from '../types/quote';
+import type { DisplayUnits } from './database/types';
 
 // ============================================================================
 // Types
@@ -47,33 +51,429 @@ interface BatchedNestingWorkflowResult {
 }
 
 // Backend types (must match Rust structs)
+// Strip grows to fit every part; fixed_sheet nests into fixed_width mm (along X) × strip height
+type SheetMode = { type: 'strip' } | { type: 'fixed_sheet'; fixed_width: number };
+
 interface NestingInput {
-  json_input: string;
+  json_input: string; // Empty when json_path is set
+  json_path?: string; // Instance JSON file (.json below the app data or home folder)
   time_limit?: number;
   seed?: number;
   use_early_termination?: boolean;
   n_workers?: number;
+  utilization_grid?: UtilizationGridOptions;
+  heatmap_overlay?: boolean;
+  clearance_overlay?: boolean; // Draw the zone of parts with extra_spacing (mm)
+  kerf_overlay?: boolean; // Draw the nominal and kerf-compensated outline of every part
+  fit_svg_viewbox?: boolean; // Compute the SVG viewBox from the strip and placements instead of widening the rendered one
+  keep_out_zones?: KeepOutZone[];
+  holes?: [number, number][][]; // Defect zones (scratches, pre-drilled) in sheet coordinates, mm
+  machine_profile_id?: string;
+  hole_nesting?: boolean;
+  include_outlines?: boolean;
+  display_tolerance?: number; // mm, roughly one screen pixel at preview zoom
+  preset?: string; // Settings preset name, recorded in nesting metrics
+  n_restarts?: number; // Optimizer runs with different seeds sharing time_limit, the best is kept (default: 1)
+  target_utilization?: number; // (0, 1], stop at the first layout reaching it
+  quality?: Quality; // Separator preset, slower presets give shorter strips (default: 'fast')
+  min_web?: number; // mm, parts with narrower webs are flagged
+  material_id?: string; // Supplies min_web from the material when not set
+  strict_min_web?: boolean; // Fail instead of warning on narrow webs
+  skip_bad_items?: boolean; // Nest the rest when items cannot be imported, listed in skipped_items
+  max_strip_height?: number; // mm, higher strips are flagged without stock data (default: max_strip_height setting)
+  strict_strip_height?: boolean; // Fail instead of warning on a strip higher than the stock
+  sanity_factor?: number; // Warn when the strip is this many times the estimate (default: 1.5)
+  separation?: number; // mm between parts and to the strip edge (default: 1.0)
+  compress_only?: boolean; // Only compress the warm_start layout, failing when it cannot be continued
+  kerf_width?: number; // mm, outlines are grown by half of it so the separation holds between cut paths (default: 0)
+  rotations?: number[]; // Orientations for every part, replacing the instance's ([0] = never rotate, [] = instance's)
+  respect_grain?: boolean; // Keep bend lines of bent parts along the rolling direction (X)
+  bend_angle_tolerance?: number; // Max bend line angle to X in degrees (default: 1)
+  pre_orient_items?: boolean; // Turn parts drawn at an angle to their smallest bounding box before nesting
+  small_item_area?: number; // mm²; smaller parts are placed after packing, 0 = never (default: 25)
+  filler_items?: FillerItem[]; // Stock parts dropped into the gaps after nesting, outside the order
+  max_filler_count?: number; // Most fillers placed (default: 100)
+  max_filler_area?: number; // mm², most area of all fillers (default: 10% of the sheet)
+  sheet_length?: number; // mm of one stock sheet, enables demand chunking (default: machine profile)
+  chunk_demand_threshold?: number; // Demand above which an item is cut on repeated sheets (default: 1000)
+  allow_mirror?: boolean; // The machine may cut parts mirrored, placements flagged `mirrored` (default: false)
+  item_allow_mirror?: Record<number, boolean>; // Per item ID, overrides allow_mirror (false for finished faces)
+  consolidate_mirror_pairs?: boolean; // Nest mirror pairs as one item (needs allow_mirror, user confirmed)
+  sheet_mode?: SheetMode; // Growing strip (default) or one fixed sheet, overflow left unplaced
+  max_sheets?: number; // Fixed sheets to fill, overflow goes onto the next sheet (default: 10)
+  skip_compression?: boolean; // Whole time limit to exploration, no compression phase
+  kerf?: number; // mm, the separation is compared with it for suggestions (default: material's kerf)
+  container_polygon?: ContainerPolygon; // Remnant to nest into instead of the strip, replaces sheet_mode
+  live_preview?: boolean; // Emit nesting://preview updates while optimizing
+  live_layout_svg?: boolean; // Emit nesting://layout-svg at most once per second while optimizing
+  warm_start?: WarmStart; // Continue from a stored layout of the same instance (compression only)
+  debug_trace?: boolean; // Return config_trace: where every setting came from
+}
+
+type Quality = 'fast' | 'balanced' | 'best';
+
+interface QualitySettings {
+  quality: Quality;
+  explore_strike_limit: number;
+  explore_iter_no_imprv_limit: number;
+  compress_strike_limit: number;
+  compress_iter_no_imprv_limit: number;
+}
+
+interface RestartSummary {
+  seed: number;
+  strip_width: number;
+  utilization: number;
+  total_items_placed: number;
+  winner?: boolean;
+}
+
+// Settings of one layer of a batch run; unset fields inherit
+// (group > batch default > app settings)
+type ConfigOverrides = Pick<
+  NestingInput,
+  | 'time_limit'
+  | 'seed'
+  | 'use_early_termination'
+  | 'n_workers'
+  | 'separation'
+  | 'rotations'
+  | 'keep_out_zones'
+  | 'hole_nesting'
+  | 'respect_grain'
+  | 'bend_angle_tolerance'
+  | 'small_item_area'
+  | 'preset'
+>;
+
+interface NestingGroup {
+  key: string; // e.g. "aluminum-3mm"
+  json_input: string;
+  config?: ConfigOverrides;
+}
+
+interface NestingGroupOutput {
+  key: string;
+  units: DisplayUnits; // Of resolved_config and output
+  resolved_config: Record<string, unknown>; // Settings the group ran with
+  preset?: string;
+  output?: NestingOutput;
+  error?: string;
+  time_budget?: GroupTimeBudget; // Set when the batch shares a total time budget
+}
+
+interface GroupTimeBudget {
+  key: string;
+  difficulty: number; // Copies x distinct shapes
+  planned_secs: number; // Share before any group ran
+  allocated_secs?: number; // Time limit the group ran with
+  used_secs?: number; // Wall time it took
+}
+
+// Time shared by all groups of a batch, replacing their own time limits
+interface BatchTimeBudgetOptions {
+  total_time_budget_secs: number;
+  min_group_time_secs?: number; // Default: 5
+  max_group_time_secs?: number; // Default: the total
+}
+
+// Live preview: a full SVG first, then only the placements that moved
+type PreviewUpdate =
+  | { kind: 'full'; svg: string }
+  | {
+      kind: 'patch';
+      view_box: string;
+      strip_width: number;
+      moves: { placement_id: number; transform: string }[];
+    };
+
+// Full SVG of an improved layout, must match Rust LayoutSvg
+interface LayoutSvg {
+  svg: string;
+  strip_width: number;
+  elapsed_secs: number; // Since the optimizer started
+}
+
+// Solution reported while optimizing, must match Rust ProgressReport
+interface NestingProgress {
+  stage: 'exploration' | 'compression' | 'final' | 'searching';
+  strip_width: number; // mm
+  placed_items: number;
+  elapsed_secs: number;
+}
+
+// Job from startNesting, must match Rust NestingJobStatus
+type ManagedJobState = 'queued' | 'running' | 'completed' | 'failed' | 'cancelled';
+
+interface NestingJobStatus {
+  job_id: string;
+  state: ManagedJobState;
+  progress: NestingProgress | null; // Last solution reported while optimizing
+  elapsed_secs: number; // 0 while queued
+  queue_position?: number; // Jobs ahead of this one
+  error?: string;
+}
+
+type SanityCulprit = 'separation' | 'rotation_restrictions' | 'margin';
+
+interface SheetLayout {
+  sheet_index: number;
+  total_items_placed: number;
+  layouts: PlacedItem[];
+  utilization: number; // 0.0 - 1.0 of the sheet area
+  unplaced_item_ids?: number[]; // Left off this and every earlier sheet
+  svg_string?: string;
+}
+
+// Input change applying a suggestion, lengths in mm
+type SuggestedAction =
+  | { type: 'set_time_limit'; time_limit: number }
+  | { type: 'set_rotations'; rotations: number[] }
+  | { type: 'set_separation'; separation: number }
+  | { type: 'set_chunk_demand_threshold'; chunk_demand_threshold: number }
+  | { type: 'skip_compression' };
+
+interface Suggestion {
+  rule: 'more_time' | 'thin_parts' | 'large_separation' | 'dominant_item' | 'idle_compression';
+  message: string;
+  action: SuggestedAction;
+}
+
+interface SanityWarning {
+  message: string;
+  estimated_strip_width: number;
+  actual_strip_width: number;
+  ratio: number;
+  factor: number;
+  likely_causes: { culprit: SanityCulprit; detail: string }[];
+}
+
+interface PhaseStats {
+  final_solution_phase?: 'exploration' | 'compression';
+  final_solution_secs?: number;
+  handoff_strip_width?: number; // Best explored width (or the stored one), where compression started
+  compression_gain_pct?: number;
+  width_delta?: number; // mm taken off the handoff width by compression
+  phases_run?: ('explore' | 'compress')[]; // ['compress'] when a stored layout was continued
+}
+
+interface MinWebViolation {
+  item_id: number;
+  label: string;
+  contour_a: number; // 0 = outer contour, i + 1 = hole i
+  contour_b: number;
+  distance: number;
+  location: [number, number];
+}
+
+type ViolationKind = 'overlap' | 'separation' | 'outside_sheet';
+
+interface OverlapReport {
+  kind: ViolationKind;
+  placement: number; // Index into layouts
+  item_id: number;
+  other_placement?: number; // The other part of an overlap or separation
+  other_item_id?: number;
+  distance: number; // Clearance found, to the sheet edge negative past it
+  required: number;
+}
+
+interface KeepOutZone {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  label?: string;
+}
+
+// Standard part (washer, test coupon) with unlimited demand, points as [x, y] in mm
+interface FillerItem {
+  id: number; // item_id of its placements, must not be an instance item ID
+  label?: string;
+  outline: [number, number][];
+  rotation_degrees?: number; // Default: 0
+}
+
+interface FillerSummary {
+  count: number;
+  area: number; // Net area of all fillers placed
+  items: { item_id: number; label?: string; count: number }[];
+  capped: boolean; // max_filler_count or max_filler_area stopped the pass
+}
+
+// Outline of a remnant (offcut), points as [x, y] in mm
+interface ContainerPolygon {
+  outer: [number, number][];
+  holes?: [number, number][][];
+}
+
+interface UtilizationGridOptions {
+  cols: number;
+  rows: number;
+}
+
+interface UtilizationGrid {
+  cols: number;
+  rows: number;
+  cell_width: number;
+  cell_height: number;
+  cells: number[];
 }
 
 interface PlacedItem {
   item_id: number;
-  rotation_degrees: number;
+  rotation_degrees: number; // In [0, 360), exact for allowed orientations
+  rotation_radians?: number; // As the optimizer returned it, before snapping (optimizer placements only)
   position_x: number;
   position_y: number;
+  host_item_id?: number;
+  outline?: [number, number][]; // Exact, only with include_outlines
+  display_outline?: [number, number][]; // Simplified for hover rendering
+  metadata?: Record<string, unknown>; // Passed through from the input item
+  bend_line_angle_deg?: number; // Bend line angle to X in (-90, 90], bent parts only
+  post_placed?: boolean; // Small part placed after packing
+  mirrored?: boolean; // Contour mirrored across its Y axis (x -> -x) before the rotation
+  locked?: boolean; // Already on the sheet at its fixed_position, not moved by the optimizer
+  filler?: boolean; // Stock filler dropped into a gap, not part of the order
+}
+
+type ConfigSource =
+  | 'group'
+  | 'batch_default'
+  | 'app_settings'
+  | 'material'
+  | 'machine_profile'
+  | 'cutting_profile'
+  | 'input'
+  | 'item'
+  | 'default'
+  | 'runtime';
+
+// Resolution of every setting (only with debug_trace), must match Rust ConfigTrace
+interface ConfigTrace {
+  parameters: {
+    parameter: string; // e.g. "separation", "separation[item 3]"
+    value: unknown; // Value the run used
+    candidates: { source: ConfigSource; value?: unknown; used: boolean }[];
+  }[];
+}
+
+// viewBox rebuilt from the placements, must match Rust SvgWarning
+interface SvgWarning {
+  message: string;
+  raw_viewbox: string | null; // null when the SVG had no viewBox
+  fallback_viewbox: string | null;
+}
+
+// Cutting of one item, must match Rust ItemCut
+interface ItemCut {
+  item_id: number;
+  cut_length: number; // mm of one copy
+  pierces: number; // Of one copy
+  count: number; // Copies placed in the job
+}
+
+// Identical full sheets of an item ordered in bulk, must match Rust RepeatedSheet
+interface RepeatedSheet {
+  item_id: number;
+  multiplier: number; // Number of identical sheets
+  copies_per_sheet: number;
+  sheet_length: number;
+  sheet_height: number;
+  layouts: PlacedItem[]; // One sheet, in sheet coordinates
+  utilization: number;
+}
+
+// Item that is the mirror image of another, must match Rust MirrorPair
+interface MirrorPair {
+  item_id: number; // Nested for both when consolidated
+  label: string;
+  demand: number;
+  mirror_item_id: number;
+  mirror_label: string;
+  mirror_demand: number;
+  rotation_degrees: number; // Mirror = item mirrored across Y, turned by this, moved by offset
+  offset: [number, number];
+}
+
+interface UnplacedItem {
+  item_id: number;
+  reason: 'TooSmall' | 'SheetFull'; // SheetFull: did not fit the fixed sheet
+}
+
+// Item left out because its geometry cannot be imported (skip_bad_items)
+interface SkippedItem {
+  item_id: number;
+  label?: string; // Label or source DXF file
+  demand: number;
+  error: string; // e.g. "outer contour intersects itself (edges 0 and 2)"
+}
+
+interface WarmStart {
+  strip_width: number; // mm
+  placements: { item_id: number; rotation_degrees: number; x: number; y: number }[];
 }
 
 interface NestingOutput {
   instance_name: string;
+  units?: DisplayUnits; // Of every length (areas in its square), mm when absent
   strip_width: number;
   strip_height: number;
+  sheet_width?: number; // Fixed sheet mode only, equals strip_width
+  sheet_height?: number; // Fixed sheet mode only, equals strip_height
   total_items_placed: number;
   layouts: PlacedItem[];
   utilization: number;
+  total_cut_length_mm: number; // Outer contours and holes of all placed copies, every sheet; mm whatever units
+  total_pierces: number; // One per closed contour
+  item_cuts?: ItemCut[]; // Per item, for quote lines
   computation_time_secs: number;
-  status?: string;
+  status?: string; // 'complete', 'partial', 'cancelled' with the best layout so far, or 'target_reached'
+  target_reached_secs?: number; // Seconds into the optimization target_utilization was reached
   items_requested?: number;
   unplaced_item_ids: number[];
+  unplaced_reasons?: UnplacedItem[];
   svg_string?: string;
+  svg_warnings?: SvgWarning[];
+  utilization_grid?: UtilizationGrid;
+  seed?: number;
+  resolved_config?: Record<string, unknown>;
+  config_trace?: ConfigTrace;
+  instance_hash?: string;
+  engine_version?: string;
+  app_version?: string;
+  algorithm_fingerprint?: string;
+  quality_settings?: QualitySettings; // Quality preset and the separator limits it used
+  restarts?: RestartSummary[]; // Every run with n_restarts, the winner's seed is `seed`
+  keep_out_zones?: KeepOutZone[];
+  keep_out_conflicts?: number[];
+  holes?: [number, number][][]; // Defect zones the layout was checked against
+  defect_conflicts?: number[]; // Placements removed for overlapping a defect zone
+  usable_area?: number; // Strip or remnant area less keep-out and defect zones
+  container_polygon?: ContainerPolygon; // Remnant nested into, moved to the origin
+  stats?: ResourceUsage;
+  min_web_violations?: MinWebViolation[];
+  violations?: OverlapReport[]; // Parts of the finished layout overlapping, too close or off the sheet
+  sanity_warning?: SanityWarning; // Strip far longer than the area estimate
+  phase_stats?: PhaseStats;
+  repeated_sheet?: RepeatedSheet; // The other fields describe the nested remainder only
+  mirror_pairs?: MirrorPair[]; // Offer consolidation when allow_mirror is on
+  origin_offset?: [number, number]; // Instance moved by minus this to nest near the origin; placements are in the original coordinates
+  base_rotations?: { item_id: number; degrees: number }[]; // Items turned before nesting; rotations are relative to the drawing
+  filler_summary?: FillerSummary; // Fillers placed; counts and utilization leave them out
+  kerf_offset?: number; // Half the kerf width every outline was grown by; placements refer to the nominal outline
+  input_warnings?: string[]; // Accepted input values that look wrong, e.g. a strip height typo
+  skipped_items?: SkippedItem[];
+  warm_started?: boolean; // Continued from the input's warm_start
+  sheets?: SheetLayout[]; // Fixed-sheet runs with max_sheets > 1; the flat fields describe sheet 0
+  suggestions?: Suggestion[]; // Settings worth changing for the next run
+}
+
+interface ResourceUsage {
+  rss_before_import_bytes?: number;
+  rss_after_import_bytes?: number;
+  peak_rss_bytes?: number;
+  optimize_cpu_secs?: number;
 }
 
 // ============================================================================
@@ -93,7 +493,13 @@ export async function runNestingWorkflow(
   files: DxfFile[],
   stripHeight: number = 6000,
   partSpacing: number = 5,
-  timeLimit: number = 60
+  timeLimit: number = 60,
+  onPlacementsReady?: (summary: NestingOutput) => void,
+  onPreview?: (update: PreviewUpdate) => void,
+  quoteId?: string | null,
+  onJobStarted?: (jobId: string) => void, // The ID to pass to cancelNesting
+  onProgress?: (progress: NestingProgress) => void, // A few times per second while optimizing
+  onLayoutSvg?: (layout: LayoutSvg) => void // Narrower layouts, at most once per second
 ): Promise<NestingWorkflowResult> {
   try {
     console.log('Starting nesting workflow for ' + files.length + ' files...');
@@ -104,6 +510,7 @@ export async function runNestingWorkflow(
       name: string;
       content: string;
       quantity: number;
+      partMetadata: Record<string, unknown>;
     }> = [];
 
     for (const file of files) {
@@ -115,6 +522,8 @@ export async function runNestingWorkflow(
           name: filename,
           content: content,
           quantity: file.quantity || 1,
+          // Comes back on every placement, so results map to files by ID
+          partMetadata: { fileId: file.id },
         });
 
         console.log('  Read: ' + filename + ' (' + content.length + ' bytes, qty: ' + file.quantity + ')');
@@ -170,6 +579,10 @@ export async function runNestingWorkflow(
       // This reduces iterations when no improvement is found
       use_early_termination: true,
       n_workers: 1,
+      // Flag parts whose holes sit too close to an edge for this material
+      material_id: files[0]?.material?.id,
+      live_preview: onPreview !== undefined,
+      live_layout_svg: onLayoutSvg !== undefined,
     };
 
     // Debug: Log the exact payload being sent to backend
@@ -182,9 +595,61 @@ export async function runNestingWorkflow(
       json_input_length: nestingInput.json_input.length,
     });
 
-    const nestingOutput = await invoke<NestingOutput>('run_nesting_integrated', {
-      input: nestingInput,
-    });
+    // Placements arrive before the SVG is rendered, so the UI can move on
+    const jobId = `nesting_${Date.now()}_${Math.random().toString(36).substring(7)}`;
+    const unlisten = await listen<{ job_id: string; output: NestingOutput }>(
+      'nesting://result-ready',
+      (event) => {
+        if (event.payload.job_id === jobId) {
+          console.log('  Placements ready, rendering layout...');
+          onPlacementsReady?.(event.payload.output);
+        }
+      }
+    );
+
+    const unlistenPreview = await listen<{ job_id: string; update: PreviewUpdate }>(
+      'nesting://preview',
+      (event) => {
+        if (event.payload.job_id === jobId) {
+          onPreview?.(event.payload.update);
+        }
+      }
+    );
+
+    const unlistenProgress = await listen<{ job_id: string } & NestingProgress>(
+      'nesting://progress',
+      (event) => {
+        if (event.payload.job_id === jobId) {
+          onProgress?.(event.payload);
+        }
+      }
+    );
+
+    const unlistenLayoutSvg = await listen<{ job_id: string } & LayoutSvg>(
+      'nesting://layout-svg',
+      (event) => {
+        if (event.payload.job_id === jobId) {
+          onLayoutSvg?.(event.payload);
+        }
+      }
+    );
+
+    let nestingOutput: NestingOutput;
+    try {
+      onJobStarted?.(jobId);
+      nestingOutput = await invokeCommand<NestingOutput>('run_nesting_integrated', {
+        input: nestingInput,
+        jobId,
+        quoteId: quoteId ?? null, // Reported to the webhook, if one is configured
+      });
+    } finally {
+      unlisten();
+      unlistenPreview();
+      unlistenProgress();
+      unlistenLayoutSvg();
+      // The full output is returned, the cached run is no longer needed
+      invoke('release_nesting_job', { jobId }).catch(() => undefined);
+    }
 
     const timeStr = nestingOutput.computation_time_secs.toFixed(2);
     const utilStr = (nestingOutput.utilization * 100).toFixed(1);
@@ -194,6 +659,13 @@ export async function runNestingWorkflow(
     console.log('  Nesting completed: ' + nestingOutput.total_items_placed + ' items placed in ' + timeStr + 's');
     console.log('  Utilization: ' + utilStr + '%');
     console.log('  Strip dimensions: ' + widthStr + ' x ' + heightStr + 'mm');
+    if (nestingOutput.sanity_warning) {
+      console.warn('  ' + nestingOutput.sanity_warning.message);
+    }
+    const outputWarnings = [
+      ...(nestingOutput.input_warnings ?? []),
+      ...(nestingOutput.sanity_warning ? [nestingOutput.sanity_warning.message] : []),
+    ];
 
     // Step 4: Transform result to UI format
     const resultData: NestingResultType = {
@@ -209,6 +681,7 @@ export async function runNestingWorkflow(
       })),
       svgPath: '', // No file path, using blob URL instead
       svgString: nestingOutput.svg_string, // Save SVG string for database persistence
+      warnings: outputWarnings.length > 0 ? outputWarnings : undefined,
     };
 
     // Create blob URL from SVG string if available
@@ -328,10 +801,606 @@ export async function runNestingWorkflowWithBatching(
   }
 }
 
+// ============================================================================
+// Layout Export
+// ============================================================================
+
+/** Contour direction convention of exported DXF files */
+type ExportWinding = 'ccw_outer' | 'cw_outer';
+
+interface LayoutDxfReport {
+  winding: ExportWinding;
+  contour_count: number;
+  parts: {
+    placement_index: number;
+    item_id: number;
+    outer_reversed: boolean;
+    holes_reversed: number;
+  }[];
+  parts_reversed: number;
+  path: string; // Written file, named by the export name template
+}
+
+type ExportKind = 'dxf' | 'html' | 'cut_program' | 'image' | 'csv' | 'pdf' | 'archive';
+
+/**
+ * Reserve the next free file name of an export in `dir`
+ *
+ * Named by the export_name_template setting; the file is created empty so
+ * concurrent exports never get the same name. For files the frontend
+ * writes itself (CSV, PDF, cut program JSON).
+ */
+export async function reserveExportPath(
+  kind: ExportKind,
+  dir: string,
+  quoteId?: string
+): Promise<string> {
+  return invoke<string>('reserve_export_path', { kind, quoteId: quoteId ?? null, dir });
+}
+
+/**
+ * Check an export name template before saving the setting
+ *
+ * Rejects unknown placeholders and templates without {n}.
+ */
+export async function validateExportNameTemplate(template: string): Promise<void> {
+  return invoke<void>('validate_export_name_template', { template });
+}
+
+/**
+ * Export a nested layout as DXF into `dir`
+ *
+ * The output needs part outlines (include_outlines or the derived
+ * outlines). Contours are written CCW outer / CW holes unless
+ * exportWinding is 'cw_outer'. The file name comes from the export name
+ * template, the report carries the written path.
+ */
+export async function exportLayoutDxf(
+  output: NestingOutput,
+  instanceJson: string,
+  dir: string,
+  quoteId?: string,
+  exportWinding: ExportWinding = 'ccw_outer'
+): Promise<LayoutDxfReport> {
+  return invoke<LayoutDxfReport>('export_layout_dxf', {
+    output,
+    instanceJson,
+    dir,
+    quoteId: quoteId ?? null,
+    options: { export_winding: exportWinding },
+  });
+}
+
+interface InteractiveHtmlOptions {
+  display_tolerance?: number; // mm, default: 0.5
+  title?: string; // default: the instance name
+}
+
+interface InteractiveHtmlExport {
+  path: string;
+  bytes: number;
+  placements: number;
+}
+
+/**
+ * Export a layout as one self-contained HTML file customers can open offline
+ *
+ * Shows the layout with hover highlighting and zoom/pan, the parts table
+ * and the utilization. Needs part outlines like the DXF export.
+ */
+export async function exportInteractiveHtml(
+  output: NestingOutput,
+  instanceJson: string,
+  dir: string,
+  quoteId?: string,
+  options?: InteractiveHtmlOptions
+): Promise<InteractiveHtmlExport> {
+  return invoke<InteractiveHtmlExport>('export_interactive_html', {
+    output,
+    instanceJson,
+    dir,
+    quoteId: quoteId ?? null,
+    options,
+  });
+}
+
+type CoordinateFrame = 'bottom_left' | 'top_left';
+
+interface CutProgramOptions {
+  frame?: CoordinateFrame; // default: bottom_left (nesting coordinates)
+  export_winding?: ExportWinding; // In the chosen frame, default: ccw_outer
+  lead_in_length?: number; // mm, default: 2
+  separation?: number; // mm, default: separation of the nesting run
+}
+
+interface CutEntry {
+  sequence: number;
+  placement_index: number; // Index into NestingOutput.layouts
+  item_id: number;
+  technology: 'outer' | 'hole' | 'engrave';
+  contour: [number, number][]; // Closed, starts at the end of the lead-in
+  pierce: [number, number];
+  lead_in: [number, number]; // Vector from pierce point to contour start
+  lead_in_clear: boolean; // False when too close to another part
+}
+
+// Format described by src-tauri/schemas/cut-program-v1.schema.json
+interface CutProgram {
+  format: 'smart-cut-quote/cut-program';
+  version: number;
+  units: 'mm';
+  frame: CoordinateFrame;
+  winding: ExportWinding;
+  sheet_width: number;
+  sheet_height: number;
+  separation: number;
+  lead_in_length: number;
+  entries: CutEntry[];
+  warnings: string[];
+}
+
+/**
+ * Build the neutral JSON cut program of a layout for the post-processor
+ *
+ * Contours in cutting order (holes before their part's outer contour)
+ * with pierce points and lead-ins. Needs part outlines like the DXF export.
+ */
+export async function exportCutProgram(
+  output: NestingOutput,
+  instanceJson: string,
+  options: CutProgramOptions = {}
+): Promise<CutProgram> {
+  return invoke<CutProgram>('export_cut_program', { output, instanceJson, options });
+}
+
+/**
+ * Nest several groups with their own settings
+ *
+ * Settings a group leaves unset come from `defaults`, then from the app
+ * settings. A failing group is reported in its output, the others still run.
+ * With `timeBudget`, time a group does not use goes to the groups after it.
+ */
+export async function runNestingBatch(
+  groups: NestingGroup[],
+  defaults: ConfigOverrides = {},
+  debugTrace = false,
+  units: DisplayUnits = 'mm',
+  jobId?: string, // Progress per group via getJobStatus
+  timeBudget?: BatchTimeBudgetOptions
+): Promise<NestingGroupOutput[]> {
+  return invoke<NestingGroupOutput[]>('run_nesting_batch', {
+    batch: { groups, defaults, debug_trace: debugTrace, units, job_id: jobId ?? null, ...timeBudget },
+  });
+}
+
+/**
+ * Stop a running nesting job
+ *
+ * The job still resolves, with its best layout so far and a warning. A
+ * job from startNesting still waiting in the queue never runs.
+ * Rejects if the job is unknown or already finished.
+ */
+export async function cancelNesting(jobId: string): Promise<void> {
+  return invoke<void>('cancel_nesting', { jobId });
+}
+
+/**
+ * Start a nesting run in the background and return its job ID
+ *
+ * Unlike runNestingWorkflow the result survives a window reload: poll
+ * getNestingStatus and fetch it with getNestingResult. Jobs beyond the
+ * concurrency limit wait in a queue; cancelNesting works for both.
+ */
+export async function startNesting(input: NestingInput, quoteId?: string | null): Promise<string> {
+  return invoke<string>('start_nesting', { input, quoteId: quoteId ?? null });
+}
+
+export async function getNestingStatus(jobId: string): Promise<NestingJobStatus> {
+  return invoke<NestingJobStatus>('get_nesting_status', { jobId });
+}
+
+/**
+ * Result of a finished job from startNesting
+ *
+ * Rejects with code 'conflict' while the job is queued or running. The
+ * result is kept until dropNestingResult.
+ */
+export async function getNestingResult(jobId: string, units: DisplayUnits = 'mm'): Promise<NestingOutput> {
+  return invokeCommand<NestingOutput>('get_nesting_result', { jobId, units });
+}
+
+export async function dropNestingResult(jobId: string): Promise<boolean> {
+  return invoke<boolean>('drop_nesting_result', { jobId });
+}
+
+/** How many jobs from startNesting run at the same time (default: 1) */
+export async function setNestingConcurrency(limit: number): Promise<void> {
+  return invoke<void>('set_nesting_concurrency', { limit });
+}
+
+/**
+ * Find items that are the mirror image of another item (LH / RH parts)
+ *
+ * Ask the user before nesting with allow_mirror and
+ * consolidate_mirror_pairs; the mirrored copies keep their own labels.
+ */
+export async function detectMirrorPairs(
+  instanceJson: string,
+  tolerance?: number // mm (default: 0.05)
+): Promise<MirrorPair[]> {
+  return invoke<MirrorPair[]>('detect_mirror_pairs', {
+    instanceJson,
+    tolerance: tolerance ?? null,
+  });
+}
+
+interface AdjustmentStarted {
+  placements_token: string;
+  instance_hash?: string;
+  placements: number;
+}
+
+interface ProbeResult {
+  colliding: number[]; // Indices into layouts closer than the separation
+  min_clearance?: number; // To the nearest placement within 20 mm, in output units
+  outside_strip: boolean;
+}
+
+/**
+ * Cache a layout for live collision feedback while dragging its parts
+ *
+ * The output must have been nested with include_outlines. Call
+ * endAdjustment when the preview closes.
+ */
+export async function beginAdjustment(output: NestingOutput): Promise<AdjustmentStarted> {
+  return invoke<AdjustmentStarted>('begin_adjustment', { output });
+}
+
+/**
+ * Check a dragged placement at a new position, fast enough for every pointer move
+ */
+export async function probePlacement(
+  adjustment: AdjustmentStarted,
+  placementIndex: number,
+  x: number,
+  y: number,
+  rotation: number
+): Promise<ProbeResult> {
+  return invoke<ProbeResult>('probe_placement', {
+    instanceHash: adjustment.instance_hash ?? null,
+    placementsToken: adjustment.placements_token,
+    placementIndex,
+    x,
+    y,
+    rotation,
+  });
+}
+
+/**
+ * Drop the cached layout of an adjustment
+ */
+export async function endAdjustment(adjustment: AdjustmentStarted): Promise<boolean> {
+  return invoke<boolean>('end_adjustment', { placementsToken: adjustment.placements_token });
+}
+
+interface LayoutVerification {
+  placements_checked: number;
+  instance_changed: boolean; // Not the instance the layout was nested from
+  violations: OverlapReport[];
+}
+
+/**
+ * Check a saved layout for overlapping parts without nesting again
+ *
+ * The output must have been nested with include_outlines.
+ */
+export async function verifyNesting(
+  instanceJson: string,
+  output: NestingOutput
+): Promise<LayoutVerification> {
+  return invoke<LayoutVerification>('verify_nesting', {
+    jsonInput: instanceJson,
+    outputJson: JSON.stringify(output),
+  });
+}
+
+interface PrintPaper {
+  size: 'a4' | 'a3';
+  orientation?: 'landscape' | 'portrait'; // default: landscape
+}
+
+interface PrintPage {
+  svg: string;
+  width_px: number;
+  height_px: number;
+  scale: number; // Denominator, 10 for 1:10
+}
+
+/**
+ * Export a nested layout as PNG at a physical print scale
+ *
+ * One page per sheet, with the title block, overall dimensions and scale
+ * ratio. Without `scale` the finest standard scale that fits is used.
+ * Pages go to `dir`, named by the export name template; several sheets
+ * are written as name-1.png, name-2.png, ...
+ *
+ * @returns Paths of the written pages
+ */
+export async function exportLayoutImage(
+  output: NestingOutput | NestingOutput[],
+  instanceJson: string,
+  dir: string,
+  quoteId?: string,
+  dpi: number = 300,
+  paper: PrintPaper = { size: 'a4', orientation: 'landscape' },
+  scale?: number
+): Promise<string[]> {
+  const sheets = Array.isArray(output) ? output : [output];
+  const pages = await invoke<PrintPage[]>('render_layout_print', {
+    sheets,
+    instanceJson,
+    options: { dpi, paper, scale },
+  });
+
+  const pngs: number[][] = [];
+  for (const page of pages) {
+    const png = await rasterizeSvgToPng(page.svg, page.width_px, page.height_px);
+    pngs.push(Array.from(png));
+  }
+
+  return invoke<string[]>('export_layout_image', {
+    pages: pngs,
+    dir,
+    quoteId: quoteId ?? null,
+    dpi,
+  });
+}
+
+interface ItemSheets {
+  instance_name: string;
+  item_id: number;
+  due_rank: number | null;
+  sheets: number[]; // Indices in the new order
+  completed_on: number;
+}
+
+interface SheetOrder {
+  order: number[]; // Original sheet index at each new position
+  items: ItemSheets[];
+  weighted_completion: number;
+  original_weighted_completion: number;
+  sheet_order_rationale: string;
+}
+
+/**
+ * Order the sheets of a job so parts needed first in assembly are cut first
+ *
+ * Uses the `due_rank` item metadata (1 = needed first). Layouts are not
+ * changed; the returned sheets are renumbered by their new position.
+ */
+export async function orderSheetsForAssembly(
+  sheets: NestingOutput[]
+): Promise<{ sheets: NestingOutput[]; sheet_order: SheetOrder }> {
+  return invoke<{ sheets: NestingOutput[]; sheet_order: SheetOrder }>('order_job_sheets', {
+    sheets,
+  });
+}
+
+interface ItemSource {
+  item_id: number; // Item ID in the consolidated output
+  quote_id: string;
+  quote_number: string;
+  source_item_id: number; // Item ID in the quote's own instance
+  label: string; // Prefixed with the quote number
+  demand: number;
+  net_area: number; // mm² per copy
+}
+
+interface QuoteAllocation {
+  quote_id: string;
+  quote_number: string;
+  items_placed: number;
+  placed_area: number; // mm²
+  share: number; // Fraction of the run's placed area
+  material_cost: number;
+}
+
+// Where the waste of a sheet is charged
+type SheetCostPolicy =
+  | { waste: 'proportional' } // Quotes pay the whole sheet by part area
+  | { waste: 'overhead'; bucket: string }; // Quotes pay their part area, the rest goes to the bucket
+
+interface CostLine {
+  quote_id: string | null; // Null for the overhead bucket
+  label: string; // Quote number or bucket name
+  placed_area: number; // mm², 0 for the bucket
+  cost: number;
+}
+
+interface SheetCostSplit {
+  sheet_index: number;
+  cost: number;
+  lines: CostLine[];
+}
+
+interface SheetCostAllocation {
+  policy: SheetCostPolicy;
+  total_cost: number;
+  sheets: SheetCostSplit[];
+  quotes: CostLine[]; // Totals per quote, adds up to total_cost
+}
+
+interface ConsolidatedGroup {
+  key: string; // Material-Thicknessmm
+  material: string;
+  thickness: number;
+  quote_ids: string[];
+  sources: ItemSource[];
+  output: NestingOutput | null;
+  requirement: {
+    stock_id: string | null;
+    sheets: number;
+    weight_kg: number;
+    cost: number;
+  } | null;
+  allocations: QuoteAllocation[];
+  sheet_costs: SheetCostAllocation | null; // Material cost split sheet by sheet
+  result_id: number | null; // Stored nesting result of the run
+  error: string | null;
+}
+
+interface SkippedQuote {
+  quote_id: string;
+  quote_number: string | null;
+  reason: string;
+}
+
+/**
+ * Nest the parts of several quotes together, one run per material
+ *
+ * Parts come from each quote's saved nesting snapshot; quotes without
+ * one are returned in `skipped`. Material cost of every run is split
+ * between the quotes by placed part area, and sheet by sheet following
+ * `costPolicy`.
+ */
+export async function consolidateQuotes(
+  quoteIds: string[],
+  materialFilter?: string[],
+  units: DisplayUnits = 'mm',
+  costPolicy?: SheetCostPolicy
+): Promise<{ units: DisplayUnits; groups: ConsolidatedGroup[]; skipped: SkippedQuote[] }> {
+  return invoke<{ units: DisplayUnits; groups: ConsolidatedGroup[]; skipped: SkippedQuote[] }>(
+    'consolidate_quotes',
+    {
+      quoteIds,
+      materialFilter: materialFilter ?? null,
+      units,
+      costPolicy: costPolicy ?? null,
+    }
+  );
+}
+
+/**
+ * Split the sheet costs of a stored consolidated run again (areas in mm²)
+ */
+export async function allocateSheetCosts(
+  resultId: number,
+  policy?: SheetCostPolicy
+): Promise<SheetCostAllocation> {
+  return invoke<SheetCostAllocation>('allocate_sheet_costs', {
+    resultId,
+    policy: policy ?? null,
+  });
+}
+
+interface OutputComparison {
+  utilization_a: number;
+  utilization_b: number;
+  strip_width_a: number;
+  strip_width_b: number;
+  items_placed_a: number;
+  items_placed_b: number;
+  provenance_a: Provenance;
+  provenance_b: Provenance;
+  warnings: string[]; // Engine or settings differences
+}
+
+interface Provenance {
+  engine_version: string | null;
+  app_version: string | null;
+  algorithm_fingerprint: string | null;
+}
+
+interface ImprovementReport {
+  result_id: number;
+  new_result_id: number | null; // Stored revision, null without improvement
+  improved: boolean;
+  message: string;
+  warm_started: boolean; // False when the stored layout could not be continued
+  sheets_before: number;
+  sheets_after: number;
+  comparison: OutputComparison; // Stored result as a, the new run as b
+}
+
+/**
+ * Spend a few more seconds improving a stored nesting result
+ *
+ * Continues from the stored placements with the stored settings and a
+ * fresh seed. A new revision linked to the result is saved only when it
+ * uses fewer sheets or reaches a higher utilization.
+ */
+export async function improveNestingResult(
+  resultId: number,
+  extraSecs: number
+): Promise<ImprovementReport> {
+  return invoke<ImprovementReport>('improve_nesting_result', { resultId, extraSecs });
+}
+
+interface PlacementOrigins {
+  preserved: number[]; // Indices into layouts kept from the stored layout
+  renested: number[]; // Indices into layouts placed anew
+}
+
+interface SubsetRenestReport {
+  result_id: number;
+  new_result_id: number; // Stored revision holding the new layout
+  full_renest: boolean; // The whole job was nested again
+  fallback_reason: string | null; // Why the changed items were not placed around the kept ones
+  renested_item_ids: number[]; // Every item after a full re-nest
+  placements: PlacementOrigins;
+  output: NestingOutput; // In mm
+}
+
+/**
+ * Re-nest only the changed items of a stored nesting result
+ *
+ * Placements of the other items stay where they are. When the changed
+ * items do not fit around them, the whole job is nested again for
+ * `extraTimeSecs` and the report is flagged `full_renest`.
+ *
+ * @param instanceJson - Updated parts, default: the quote's saved snapshot
+ */
+export async function renestSubset(
+  resultId: number,
+  changedItemIds: number[],
+  extraTimeSecs: number,
+  instanceJson?: string
+): Promise<SubsetRenestReport> {
+  return invoke<SubsetRenestReport>('renest_subset', {
+    resultId,
+    changedItemIds,
+    extraTimeSecs,
+    instanceJson: instanceJson ?? null,
+  });
+}
+
 // ============================================================================
 // Utility Functions
 // ============================================================================
 
+/**
+ * Apply a live preview update to the element showing the preview
+ *
+ * A full update replaces the content; a patch moves the placements of the
+ * SVG shown, addressed by their data-placement-id attributes.
+ */
+export function applyPreviewUpdate(container: HTMLElement, update: PreviewUpdate): void {
+  if (update.kind === 'full') {
+    container.innerHTML = update.svg;
+    return;
+  }
+
+  const svg = container.querySelector('svg');
+  if (!svg) return;
+  svg.setAttribute('viewBox', update.view_box);
+  svg.querySelector('[data-strip]')?.setAttribute('width', String(update.strip_width));
+  for (const move of update.moves) {
+    svg
+      .querySelector(`[data-placement-id="${move.placement_id}"]`)
+      ?.setAttribute('transform', move.transform);
+  }
+}
+
 /**
  * Create a blob URL from SVG string for display in UI
  */
@@ -359,5 +1428,60 @@ export type {
   BatchedNestingWorkflowResult,
   NestingInput,
   NestingOutput,
+  SkippedItem,
+  WarmStart,
+  OutputComparison,
+  Provenance,
+  ImprovementReport,
+  PlacementOrigins,
+  SubsetRenestReport,
   PlacedItem,
+  ContainerPolygon,
+  FillerItem,
+  FillerSummary,
+  Quality,
+  QualitySettings,
+  RestartSummary,
+  AdjustmentStarted,
+  ProbeResult,
+  MirrorPair,
+  MinWebViolation,
+  ViolationKind,
+  OverlapReport,
+  LayoutVerification,
+  SanityWarning,
+  SanityCulprit,
+  PhaseStats,
+  PreviewUpdate,
+  LayoutSvg,
+  NestingProgress,
+  ManagedJobState,
+  NestingJobStatus,
+  ConfigOverrides,
+  ConfigTrace,
+  NestingGroup,
+  NestingGroupOutput,
+  GroupTimeBudget,
+  BatchTimeBudgetOptions,
+  ExportWinding,
+  ExportKind,
+  LayoutDxfReport,
+  InteractiveHtmlOptions,
+  InteractiveHtmlExport,
+  CoordinateFrame,
+  CutProgramOptions,
+  CutEntry,
+  CutProgram,
+  PrintPaper,
+  PrintPage,
+  ItemSheets,
+  SheetOrder,
+  ItemSource,
+  QuoteAllocation,
+  SheetCostPolicy,
+  CostLine,
+  SheetCostSplit,
+  SheetCostAllocation,
+  ConsolidatedGroup,
+  SkippedQuote,
 };
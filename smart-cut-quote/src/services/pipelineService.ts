@@ -0,0 +1,77 @@
+/**
+ * Pipeline Service
+ * Runs conversion, validation, nesting and persistence of a quote as one
+ * pipeline. A cancelled or failed run is rolled back: its stored result
+ * and SVG are removed, converted geometry stays cached.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import type { ConversionOptions, DxfFileInput } from './conversionJobService';
+import type { NestingInput, NestingOutput } from './nestingService';
+
+// Backend types (must match Rust structs)
+export type PipelineStage = 'conversion' | 'validation' | 'nesting' | 'persistence';
+
+export type PipelineStatus = 'completed' | 'cancelled' | 'failed';
+
+export interface PipelineRequest {
+  pipeline_id: string;
+  quote_id?: string;
+  files: DxfFileInput[];
+  options: ConversionOptions;
+  /** json_input is filled from the converted files */
+  nesting?: Partial<Omit<NestingInput, 'json_input'>>;
+}
+
+export interface PipelineReport {
+  pipeline_id: string;
+  status: PipelineStatus;
+  completed_stages: PipelineStage[];
+  rolled_back_stages: PipelineStage[];
+  /** Conversion cache entries kept on rollback */
+  retained_artifacts: string[];
+  result_id: number | null;
+  output: NestingOutput | null;
+  error: string | null;
+}
+
+/**
+ * Run the quote pipeline, resolves once it completed or was rolled back
+ */
+export async function runQuotePipeline(request: PipelineRequest): Promise<PipelineReport> {
+  return invoke<PipelineReport>('run_quote_pipeline', { request });
+}
+
+/**
+ * Stop a running pipeline at its next stage boundary
+ */
+export async function cancelPipeline(pipelineId: string): Promise<void> {
+  await invoke('cancel_pipeline', { pipelineId });
+}
+
+export interface PurgeReport {
+  deleted_results: number;
+  /** Stored instances no result referenced any more */
+  collected_blobs: number;
+}
+
+/**
+ * Instance JSON of a stored nesting result by its instance_hash, for replay or renest
+ */
+export async function getInstanceBlob(hash: string): Promise<string> {
+  return invoke<string>('get_instance_blob', { hash });
+}
+
+/**
+ * Delete stored nesting results of a quote and/or created before a date
+ * ('YYYY-MM-DD HH:MM:SS'); at least one filter is required
+ */
+export async function purgeNestingResults(filter: {
+  quoteId?: string;
+  before?: string;
+}): Promise<PurgeReport> {
+  return invoke<PurgeReport>('purge_nesting_results', {
+    quoteId: filter.quoteId ?? null,
+    before: filter.before ?? null,
+  });
+}
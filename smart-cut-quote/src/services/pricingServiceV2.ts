@@ -4,6 +4,8 @@
  * Supports operations with different cost types
  */
 
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import {
   getAllMaterials,
   getAllMachines,
@@ -13,6 +15,7 @@ import {
   Machine as DbMachine,
   Operation,
   AppSettings,
+  DisplayUnits,
 } from './database';
 
 export interface CostBreakdown {
@@ -310,16 +313,20 @@ export async function calculatePartCost(
     throw new Error(`Material or machine not found for part ${partData.id}`);
   }
 
+  // Prices of the cutting profile (same row nesting uses), else the material's
+  const resolved = await resolveCuttingProfile(machine.id, material.id);
+  const profile = resolved?.profile;
+
   // Calculate individual costs
   const cuttingCost = calculateCuttingCost(
     partData.cutLength,
-    material.cut_price_per_meter,
+    profile?.price_per_meter ?? material.cut_price_per_meter,
     partData.quantity
   );
 
   const piercingCost = calculatePiercingCost(
     partData.pierceCount,
-    material.pierce_cost,
+    profile?.price_per_pierce ?? material.pierce_cost,
     partData.quantity
   );
 
@@ -511,6 +518,140 @@ export function calculateQuoteTotal(
   };
 }
 
+// Backend types (must match Rust structs in commands/price_matrix.rs)
+export interface PriceMatrixRow {
+  quantity: number;
+  sheets: number; // Stock sheets to order, including scrap allowance
+  strip_width: number; // In PriceMatrix.units
+  utilization: number;
+  material_cost: number;
+  cutting_cost: number;
+  piercing_cost: number;
+  cutting_time_secs?: number; // All pieces, from the feed rate and pierce time
+  unit_cost: number; // Including the default price markup, operations excluded
+  total: number;
+  estimated: boolean; // Extrapolated instead of nested, disclose on the quote
+}
+
+export interface PriceMatrix {
+  part_id: string;
+  quote_id: string;
+  material_id: string;
+  cutting_profile_id?: number; // Profile the prices and separation came from
+  units: DisplayUnits;
+  rows: PriceMatrixRow[]; // By ascending quantity
+  has_estimates: boolean;
+}
+
+export interface PriceMatrixProgress {
+  part_id: string;
+  quantity: number;
+  stage: 'nesting' | 'estimate';
+  current: number;
+  total: number;
+}
+
+/**
+ * Price-break table of one quote part (file ID) at several quantities
+ *
+ * Quantities are nested while the time budget allows (default 60 s),
+ * the rest are extrapolated and flagged as estimated. Without a material
+ * the part's own material is used; the machine selects the cutting profile.
+ */
+export async function getQuantityPriceMatrix(
+  partId: string,
+  quantities: number[],
+  materialId?: string,
+  timeBudgetSecs?: number,
+  onProgress?: (progress: PriceMatrixProgress) => void,
+  units: DisplayUnits = 'mm',
+  machineId?: string
+): Promise<PriceMatrix> {
+  const unlisten = onProgress
+    ? await listen<PriceMatrixProgress>('quote://price-matrix-progress', (event) => {
+        if (event.payload.part_id === partId) onProgress(event.payload);
+      })
+    : undefined;
+  try {
+    return await invoke<PriceMatrix>('quantity_price_matrix', {
+      partId,
+      quantities,
+      materialId,
+      machineId,
+      timeBudgetSecs,
+      units,
+    });
+  } finally {
+    unlisten?.();
+  }
+}
+
+// Backend types (must match Rust structs in commands/cutting_profiles.rs)
+export interface CuttingProfileFields {
+  machine_id?: string | null; // null = any machine
+  material_id: string;
+  thickness_min: number; // mm, inclusive
+  thickness_max: number; // mm, exclusive; equal to thickness_min for an exact thickness
+  kerf?: number | null; // mm
+  min_separation?: number | null; // mm, nesting separation when the input sets none
+  feed_rate?: number | null; // mm/min
+  pierce_time?: number | null; // s
+  price_per_meter?: number | null;
+  price_per_pierce?: number | null;
+}
+
+export interface CuttingProfile extends CuttingProfileFields {
+  id: number;
+}
+
+export interface ResolvedCuttingProfile {
+  profile: CuttingProfile;
+  matched_by: 'exact' | 'range';
+  thickness: number; // mm looked up
+  machine_specific: boolean; // false = profile for any machine
+}
+
+export async function listCuttingProfiles(materialId?: string): Promise<CuttingProfile[]> {
+  return invoke<CuttingProfile[]>('list_cutting_profiles', { materialId });
+}
+
+/**
+ * Add a cutting profile; rejected if its thickness range overlaps another
+ * profile of the same machine and material
+ */
+export async function createCuttingProfile(profile: CuttingProfileFields): Promise<CuttingProfile> {
+  return invoke<CuttingProfile>('create_cutting_profile', { profile });
+}
+
+export async function updateCuttingProfile(
+  id: number,
+  profile: CuttingProfileFields
+): Promise<CuttingProfile> {
+  return invoke<CuttingProfile>('update_cutting_profile', { id, profile });
+}
+
+export async function deleteCuttingProfile(id: number): Promise<boolean> {
+  return invoke<boolean>('delete_cutting_profile', { id });
+}
+
+/**
+ * Cutting profile nesting and pricing use for a machine and material
+ *
+ * Exact thickness beats a range, the machine's profiles beat those for any
+ * machine. Without a thickness the material's is used; null when none matches.
+ */
+export async function resolveCuttingProfile(
+  machineId: string | undefined,
+  materialId: string,
+  thickness?: number
+): Promise<ResolvedCuttingProfile | null> {
+  return invoke<ResolvedCuttingProfile | null>('resolve_cutting_profile', {
+    machineId,
+    materialId,
+    thickness,
+  });
+}
+
 export default {
   loadPricingData,
   getMaterials,
@@ -527,4 +668,10 @@ export default {
   calculatePartCost,
   calculateAllPartsCosts,
   calculateQuoteTotal,
+  getQuantityPriceMatrix,
+  listCuttingProfiles,
+  createCuttingProfile,
+  updateCuttingProfile,
+  deleteCuttingProfile,
+  resolveCuttingProfile,
 };
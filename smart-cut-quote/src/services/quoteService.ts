@@ -15,6 +15,8 @@ import {
   getAppSettings,
 } from './database';
 import { DxfFile, Client, NestingResult, QuoteSummary } from '../types/quote';
+import { invokeCommand } from './commandResponse';
+import type { NestingOutput } from './nestingService';
 
 export interface SavedQuote {
   id: string;
@@ -41,9 +43,60 @@ interface QuoteDataBlob {
   client: Client;
   files: DxfFile[];
   nestingResult?: NestingResult;
+  // Per material + thickness results of a batched run (read by compute_material_requirements)
+  nestingResultsByMaterial?: (NestingResult & { materialGroup: string; materialThickness: number })[];
   summary?: QuoteSummary;
 }
 
+/** Priced line of a quote, part_id is the id of one of its files */
+export interface QuoteLine {
+  part_id: string;
+  quantity: number;
+  unit_price: number;
+  description?: string;
+}
+
+/** Payload of the save_quote command, unset header fields keep their stored value */
+export interface QuotePayload {
+  id?: string; // Omit for a new quote
+  quote_number?: string; // Generated for a new quote when omitted
+  client_id: string;
+  status?: 'draft' | 'sent' | 'accepted' | 'rejected' | 'expired';
+  validity_days?: number;
+  price_markup?: number;
+  material_markup?: number;
+  tax_rate?: number;
+  discount?: number;
+  hidden_discount?: number;
+  subtotal: number;
+  total: number;
+  notes?: string;
+  reference?: string;
+  created_by?: string;
+  files: DxfFile[];
+  lines: QuoteLine[];
+  data?: Partial<Omit<QuoteDataBlob, 'files'>>;
+  nesting?: { instance_json: string; output: NestingOutput }; // Active result, stored as the snapshot
+  task?: { title: string; description?: string; category?: string; priority?: string; due_date?: string };
+}
+
+export interface SavedQuoteIds {
+  quote_id: string;
+  quote_number: string;
+  created: boolean;
+  task_id?: string;
+  audit_id: number;
+}
+
+/**
+ * Save a quote with its lines, nesting snapshot, task and audit entry in
+ * one transaction; throws CommandError 'conflict' while another save of
+ * the same quote is running
+ */
+export async function saveQuoteAtomic(payload: QuotePayload): Promise<SavedQuoteIds> {
+  return invokeCommand<SavedQuoteIds>('save_quote', { quotePayload: payload });
+}
+
 /**
  * Save a new quote to database
  */
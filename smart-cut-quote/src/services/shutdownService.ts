@@ -0,0 +1,38 @@
+/**
+ * Shutdown Service
+ * Closing the app while nesting runs stops the runs and waits a grace
+ * period for their best-so-far results to be checkpointed. The backend
+ * announces the wait with an event; force quit skips it.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+
+// Backend types (must match Rust structs)
+export interface JobsFinishingEvent {
+  running: number;
+  grace_period_secs: number;
+}
+
+/**
+ * Listen for exit waiting on running jobs
+ */
+export async function onJobsFinishing(
+  handler: (event: JobsFinishingEvent) => void
+): Promise<UnlistenFn> {
+  return listen<JobsFinishingEvent>('app://jobs-finishing', (event) => handler(event.payload));
+}
+
+/**
+ * Exit immediately without waiting for running jobs
+ */
+export async function forceQuit(): Promise<void> {
+  await invoke('force_quit');
+}
+
+/**
+ * Change how long exit waits for running jobs (default 5 s)
+ */
+export async function setShutdownGracePeriod(secs: number): Promise<void> {
+  await invoke('set_shutdown_grace_period', { secs });
+}
@@ -0,0 +1,213 @@
+/**
+ * Source Watch Service
+ * Keeps a quote in sync with its source DXF files while they are edited
+ * in a CAD tool. The backend watches the files (watch_quote_sources) and
+ * emits events; this service refreshes metadata, diffs the geometry and
+ * queues re-nests of the affected material groups.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import DxfParser from 'dxf-parser';
+import { convertDxfContent } from '../lib/dxf-converter';
+import { useQuoteStore } from '../stores/quoteStore';
+import { getNestingSettings } from './database';
+import { invokeCommand } from './commandResponse';
+import { calculateDxfMetadata, validateClosedContours } from './dxfMetadataService';
+import { BatchedNestingResult, runNestingWorkflowWithBatching } from './nestingService';
+import type { DxfFile } from '../types/quote';
+
+// Backend types (must match Rust structs)
+export interface SourcePart {
+  file_id: string;
+  name: string;
+  path: string;
+  material_group: string;
+  material_thickness: number;
+  batch_key: string;
+}
+
+export interface SourceChangedEvent {
+  quote_id: string;
+  parts: SourcePart[];
+  auto_renest: boolean;
+}
+
+export interface SourceMissingEvent {
+  quote_id: string;
+  parts: SourcePart[];
+}
+
+interface InstanceDiff {
+  added: unknown[];
+  removed: unknown[];
+  quantity_changed: unknown[];
+  geometry_changed: unknown[];
+}
+
+// Converted instance per source path, baseline for the geometry diff
+const baselines = new Map<string, string>();
+
+// Re-nests run one after another, a batch is queued at most once
+let renestQueue: Promise<void> = Promise.resolve();
+const queuedBatches = new Set<string>();
+
+/**
+ * Start or stop the backend watcher of a quote's source files
+ */
+export async function watchQuoteSources(quoteId: string, enabled: boolean): Promise<number> {
+  return invoke<number>('watch_quote_sources', { quoteId, enabled });
+}
+
+/**
+ * Watch the sources of the current quote until the returned function is called
+ *
+ * Call the returned function when the quote is closed; the backend also
+ * stops all watchers when the app exits.
+ */
+export async function startSourceWatch(
+  quoteId: string,
+  onMissing?: (parts: SourcePart[]) => void
+): Promise<() => Promise<void>> {
+  baselines.clear();
+  for (const file of useQuoteStore.getState().files) {
+    const instance = await convertSource(file.path, file.name).catch(() => null);
+    if (instance) {
+      baselines.set(file.path, instance);
+    }
+  }
+
+  const unlistenChanged: UnlistenFn = await listen<SourceChangedEvent>(
+    'quote://source-changed',
+    (event) => {
+      if (event.payload.quote_id === quoteId) {
+        handleSourceChanged(event.payload).catch((error) =>
+          console.error('Failed to refresh changed sources:', error)
+        );
+      }
+    }
+  );
+
+  const unlistenMissing: UnlistenFn = await listen<SourceMissingEvent>(
+    'quote://source-missing',
+    (event) => {
+      if (event.payload.quote_id !== quoteId) return;
+
+      console.warn('Source files missing:', event.payload.parts.map((p) => p.path));
+      const { updateFile } = useQuoteStore.getState();
+      event.payload.parts.forEach((part) => updateFile(part.file_id, { status: 'error' }));
+      onMissing?.(event.payload.parts);
+    }
+  );
+
+  const watched = await watchQuoteSources(quoteId, true);
+  console.log(`👁️ Watching ${watched} source files of quote ${quoteId}`);
+
+  return async () => {
+    unlistenChanged();
+    unlistenMissing();
+    baselines.clear();
+    await watchQuoteSources(quoteId, false);
+  };
+}
+
+async function convertSource(path: string, name: string): Promise<string | null> {
+  const { content } = await invokeCommand<{ content: string }>('read_dxf_file', { path });
+  const result = await convertDxfContent(content, { filename: name });
+  return result.jsonString;
+}
+
+/**
+ * Refresh metadata of changed parts and re-nest their batches if needed
+ */
+async function handleSourceChanged(event: SourceChangedEvent): Promise<void> {
+  const { updateFile } = useQuoteStore.getState();
+  const geometryChanged: SourcePart[] = [];
+
+  for (const part of event.parts) {
+    const { content } = await invokeCommand<{ content: string }>('read_dxf_file', { path: part.path });
+
+    // Step 1: Re-run metadata extraction
+    const dxf = new DxfParser().parseSync(content);
+    if (dxf?.entities) {
+      updateFile(part.file_id, {
+        metadata: calculateDxfMetadata(dxf.entities),
+        status: validateClosedContours(dxf.entities) ? 'ok' : 'error',
+      });
+    }
+
+    // Step 2: Diff the converted geometry against the last known version
+    const current = (await convertDxfContent(content, { filename: part.name })).jsonString;
+    const baseline = baselines.get(part.path);
+
+    if (current && baseline) {
+      const diff = await invoke<InstanceDiff>('diff_instances', {
+        instanceAJson: baseline,
+        instanceBJson: current,
+      });
+      if (
+        diff.geometry_changed.length > 0 ||
+        diff.added.length > 0 ||
+        diff.removed.length > 0
+      ) {
+        geometryChanged.push(part);
+      }
+    } else {
+      // No comparable baseline, assume the geometry changed
+      geometryChanged.push(part);
+    }
+
+    if (current) {
+      baselines.set(part.path, current);
+    }
+  }
+
+  console.log(
+    `📝 ${event.parts.length} sources changed, ${geometryChanged.length} with geometry changes`
+  );
+
+  // Step 3: Re-nest affected material groups
+  if (event.auto_renest) {
+    new Set(geometryChanged.map((p) => p.batch_key)).forEach(enqueueRenest);
+  }
+}
+
+function batchKeyOf(file: DxfFile): string {
+  const materialGroup = file.materialGroup || file.material?.name || 'Unknown';
+  const materialThickness = file.materialThickness || file.material?.thickness || 0;
+  return materialGroup + '-' + materialThickness + 'mm';
+}
+
+/**
+ * Queue a background re-nest of one material group
+ */
+function enqueueRenest(batchKey: string): void {
+  if (queuedBatches.has(batchKey)) return;
+  queuedBatches.add(batchKey);
+
+  renestQueue = renestQueue
+    .then(async () => {
+      queuedBatches.delete(batchKey);
+
+      const { files } = useQuoteStore.getState();
+      const batchFiles = files.filter((f) => f.selected !== false && batchKeyOf(f) === batchKey);
+      if (batchFiles.length === 0) return;
+
+      console.log('🔁 Re-nesting batch ' + batchKey + ' after source change');
+      const nestingSettings = await getNestingSettings();
+      const result = await runNestingWorkflowWithBatching(
+        batchFiles,
+        nestingSettings.stripHeight,
+        nestingSettings.partSpacing
+      );
+
+      // Replace this batch's result, keep the other batches
+      const { batchedNestingResults, setBatchedNestingResults } = useQuoteStore.getState();
+      const merged: BatchedNestingResult[] = [
+        ...(batchedNestingResults || []).filter((b) => b.batchKey !== batchKey),
+        ...result.batches,
+      ];
+      setBatchedNestingResults(merged);
+    })
+    .catch((error) => console.error('Re-nest of batch ' + batchKey + ' failed:', error));
+}
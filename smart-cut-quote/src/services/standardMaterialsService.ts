@@ -0,0 +1,25 @@
+/**
+ * Standard Materials Service
+ * Seeds common stock (mild steel, stainless, aluminum in standard
+ * thicknesses and sheet sizes) so the first quote does not start with
+ * manual data entry. Prices are placeholders flagged needs_price_review;
+ * stock the user already has is never touched.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+// Backend types (must match Rust structs)
+export type StockRegion = 'metric' | 'imperial'; // imperial: inch thicknesses, 4×8/5×10/5×12 ft sheets
+
+export interface SeedReport {
+  region: StockRegion;
+  inserted: string[]; // IDs of the rows added
+  skipped: number; // Same material, thickness and sheet size already present
+}
+
+/**
+ * Insert the standard stock of a region (safe to repeat)
+ */
+export async function seedStandardMaterials(region: StockRegion = 'metric'): Promise<SeedReport> {
+  return invoke<SeedReport>('seed_standard_materials', { region });
+}
@@ -0,0 +1,57 @@
+/**
+ * Startup Check Service
+ * The backend checks the installation once at startup (bundled binaries,
+ * writable app folders, database) and emits the findings as a
+ * 'startup://issues' event. Findings never block the app; the
+ * diagnostics tab lists them and can run a repair.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+
+// Backend types (must match Rust structs)
+export type FindingSeverity = 'warning' | 'error';
+
+export type FindingKind =
+  | 'missing_resource'
+  | 'directory_not_writable'
+  | 'database_unavailable';
+
+export interface StartupFinding {
+  kind: FindingKind;
+  severity: FindingSeverity;
+  path: string; // File or directory the finding is about
+  message: string;
+  repairable: boolean; // repairInstallation() can fix it
+}
+
+export interface StartupReport {
+  checked_at: string;
+  findings: StartupFinding[];
+  repaired: string[]; // Repairs done by the last repairInstallation() call
+}
+
+export const STARTUP_ISSUES_EVENT = 'startup://issues';
+
+/**
+ * Report of the startup check or the last repair (null before the check ran)
+ */
+export async function getStartupReport(): Promise<StartupReport | null> {
+  return invoke<StartupReport | null>('get_startup_report');
+}
+
+/**
+ * Recreate missing app folders and resources, then check again
+ */
+export async function repairInstallation(): Promise<StartupReport> {
+  return invoke<StartupReport>('repair_installation');
+}
+
+/**
+ * Listen for startup check reports (startup and after each repair)
+ */
+export async function onStartupIssues(
+  handler: (report: StartupReport) => void
+): Promise<UnlistenFn> {
+  return listen<StartupReport>(STARTUP_ISSUES_EVENT, (event) => handler(event.payload));
+}
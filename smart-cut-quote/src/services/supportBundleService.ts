@@ -0,0 +1,55 @@
+/**
+ * Support Bundle Service
+ * Collects logs, the startup check report, recent nesting metrics,
+ * settings, the last failed nesting run and environment info into one
+ * zip to send to support. Secrets are always removed; customer names
+ * and prices only stay when explicitly included.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+// Backend types (must match Rust structs)
+export interface BundlePrivacy {
+  include_customer_names?: boolean; // Keep client and contact names, emails and addresses
+  include_prices?: boolean; // Keep prices, costs and markups
+  exclude_geometry?: boolean; // Failed instance parts become bounding rectangles
+}
+
+export interface BundleEntry {
+  name: string;
+  size_bytes: number;
+}
+
+export interface ExcludedEntry {
+  name: string;
+  reason: string; // E.g. over the size cap or not available
+}
+
+export interface SupportBundle {
+  path: string;
+  format_version: number;
+  app_version: string;
+  engine_version: string;
+  created_at: string;
+  privacy: Required<BundlePrivacy>;
+  size_cap_bytes: number;
+  entries: BundleEntry[];
+  excluded: ExcludedEntry[];
+}
+
+/**
+ * Write a support bundle to `destZip`
+ *
+ * @param includeDb - Add a database snapshot, scrubbed like the rest
+ */
+export async function createSupportBundle(
+  destZip: string,
+  includeDb: boolean,
+  privacy?: BundlePrivacy
+): Promise<SupportBundle> {
+  return invoke<SupportBundle>('create_support_bundle', {
+    destZip,
+    includeDb,
+    privacy: privacy ?? null,
+  });
+}
@@ -92,6 +92,42 @@ export async function generateDxfThumbnail(filePath: string, size: number = 80):
   }
 }
 
+/**
+ * Rasterize an SVG document to PNG bytes at its pixel size
+ * @param svg - SVG markup with width/height in pixels
+ * @param width - Canvas width in pixels
+ * @param height - Canvas height in pixels
+ * @returns PNG file contents
+ */
+export async function rasterizeSvgToPng(svg: string, width: number, height: number): Promise<Uint8Array> {
+  const url = URL.createObjectURL(new Blob([svg], { type: 'image/svg+xml' }));
+  try {
+    const image = new Image();
+    image.src = url;
+    await image.decode();
+
+    // Create offscreen canvas
+    const canvas = document.createElement('canvas');
+    canvas.width = width;
+    canvas.height = height;
+    const ctx = canvas.getContext('2d');
+    if (!ctx) {
+      throw new Error('Canvas 2D context is not available');
+    }
+
+    // Drawn 1:1, the SVG is already sized in pixels
+    ctx.drawImage(image, 0, 0, width, height);
+
+    const blob = await new Promise<Blob | null>((resolve) => canvas.toBlob(resolve, 'image/png'));
+    if (!blob) {
+      throw new Error('Failed to encode page as PNG');
+    }
+    return new Uint8Array(await blob.arrayBuffer());
+  } finally {
+    URL.revokeObjectURL(url);
+  }
+}
+
 function calculateBounds(entities: any[]): Bounds {
   let minX = Infinity;
   let maxX = -Infinity;
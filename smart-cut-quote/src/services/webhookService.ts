@@ -0,0 +1,42 @@
+/**
+ * Webhook Service
+ * Optional notification of an external system (MES) when a nesting run
+ * finishes. The backend POSTs to the configured URL, retries on network
+ * errors and logs every delivery; an empty URL turns it off.
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+import { getSetting, setSetting } from './database';
+
+// Backend types (must match Rust structs)
+export interface WebhookTestResult {
+  ok: boolean;
+  http_status: number | null; // null when no response arrived
+  error: string | null;
+  elapsed_ms: number;
+}
+
+/** Header the secret is sent in */
+export const WEBHOOK_SECRET_HEADER = 'X-Webhook-Secret';
+
+export interface WebhookSettings {
+  url: string; // Empty = notifications off
+  secret: string;
+}
+
+export async function getWebhookSettings(): Promise<WebhookSettings> {
+  const [url, secret] = await Promise.all([getSetting('webhook_url'), getSetting('webhook_secret')]);
+  return { url: url ?? '', secret: secret ?? '' };
+}
+
+export async function saveWebhookSettings(settings: WebhookSettings): Promise<void> {
+  await setSetting('webhook_url', settings.url.trim());
+  await setSetting('webhook_secret', settings.secret);
+}
+
+/**
+ * Send a test notification; uses the saved settings for values left out
+ */
+export async function testWebhook(url?: string, secret?: string): Promise<WebhookTestResult> {
+  return invoke<WebhookTestResult>('test_webhook', { url, secret });
+}
@@ -75,3 +75,21 @@ export interface AnalyticsRow {
   in_production_value: number;
   avg_order_value: number;
 }
+
+// Nesting run statistics (get_nesting_metrics command)
+export type NestingMetricsGroupBy = 'day' | 'week' | 'month' | 'preset' | 'status' | 'engine_version';
+
+export interface NestingMetricsRange {
+  from?: string; // Inclusive, UTC 'YYYY-MM-DD[ HH:MM:SS]'
+  to?: string;   // Exclusive
+}
+
+export interface NestingMetricsBucket {
+  key: string;
+  runs: number;
+  avg_utilization: number | null; // Runs that produced a layout only
+  avg_duration_secs: number | null;
+  partial_runs: number;
+  failed_runs: number;
+  total_sheets: number;
+}
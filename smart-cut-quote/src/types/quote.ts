@@ -39,6 +39,7 @@ export interface NestingResult {
   placements: Placement[];
   svgPath: string;
   svgString?: string; // SVG content for recreating blob URL after database load
+  warnings?: string[]; // Shown in the warnings panel (e.g. result far worse than estimated)
 }
 
 export interface Placement {